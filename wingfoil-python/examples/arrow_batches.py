@@ -0,0 +1,57 @@
+"""Times a per-tick `map` against `to_arrow_batches` on the same stream.
+
+Both variants compute the mean price of every 1,000 ticks; the first calls
+Python once per tick, the second once per Arrow batch. Needs pyarrow.
+
+    python examples/arrow_batches.py
+"""
+
+import time
+
+from wingfoil import ticker
+
+TICKS = 200_000
+BATCH = 1_000
+
+
+def source():
+    return ticker(0.001).count().map(lambda i: {"price": 100.0 + i % 50, "qty": i % 7})
+
+
+def per_tick():
+    state = {"sum": 0.0, "n": 0}
+
+    def on_tick(row):
+        state["sum"] += row["price"]
+        state["n"] += 1
+        if state["n"] == BATCH:
+            mean = state["sum"] / BATCH
+            state["sum"], state["n"] = 0.0, 0
+            return mean
+        return None
+
+    return source().map(on_tick).filter(lambda mean: mean is not None)
+
+
+def batched():
+    import pyarrow.compute as pc
+
+    return source().to_arrow_batches(BATCH).map(
+        lambda batch: pc.mean(batch.column("price")).as_py()
+    )
+
+
+def timed(label, build):
+    means = build().collect()
+    start = time.perf_counter()
+    means.run(realtime=False, cycles=TICKS)
+    elapsed = time.perf_counter() - start
+    print(f"{label:>18}: {elapsed:.3f}s, {elapsed / TICKS * 1e9:,.0f} ns/tick")
+    return elapsed, means.peek_value()
+
+
+if __name__ == "__main__":
+    base, expected = timed("per-tick map", per_tick)
+    fast, actual = timed("to_arrow_batches", batched)
+    assert expected == actual
+    print(f"{'speedup':>18}: {base / fast:.1f}x")
@@ -1,6 +1,7 @@
 mod proxy_stream;
 #[cfg(feature = "aeron")]
 mod py_aeron;
+mod py_arrow;
 mod py_augurs;
 mod py_csv;
 mod py_element;
@@ -22,7 +23,7 @@ mod py_web;
 mod py_zmq;
 mod types;
 
-use ::wingfoil::{Dep, Node, NodeOperators};
+use ::wingfoil::{Dep, Node, NodeOperators, Stream};
 use py_element::*;
 use py_stream::*;
 use types::ToPyResult;
@@ -95,8 +96,19 @@ fn constant(val: Py<PyAny>) -> PyStream {
 }
 
 /// maps steams a amd b into a new stream using func (e.g lambda a, b: a + b)
+///
+/// An active stream triggers the bimap to cycle when it ticks.  A passive
+/// stream is only read, so `bimap(fast, slow, f, b_active=False)` ticks with
+/// each tick of `fast` and the latest value of `slow`.
 #[pyfunction]
-fn bimap(a: Py<PyAny>, b: Py<PyAny>, func: Py<PyAny>) -> PyResult<PyStream> {
+#[pyo3(signature = (a, b, func, a_active=true, b_active=true))]
+fn bimap(
+    a: Py<PyAny>,
+    b: Py<PyAny>,
+    func: Py<PyAny>,
+    a_active: bool,
+    b_active: bool,
+) -> PyResult<PyStream> {
     Python::attach(|py| {
         let a = a
             .as_ref()
@@ -111,8 +123,8 @@ fn bimap(a: Py<PyAny>, b: Py<PyAny>, func: Py<PyAny>) -> PyResult<PyStream> {
             .to_pyresult()?
             .inner_stream();
         let stream = ::wingfoil::try_bimap(
-            Dep::Active(a),
-            Dep::Active(b),
+            dep(a, a_active),
+            dep(b, b_active),
             move |a: PyElement, b: PyElement| {
                 Python::attach(|py: Python<'_>| {
                     let res = func
@@ -126,6 +138,14 @@ fn bimap(a: Py<PyAny>, b: Py<PyAny>, func: Py<PyAny>) -> PyResult<PyStream> {
     })
 }
 
+fn dep(stream: Rc<dyn Stream<PyElement>>, active: bool) -> Dep<PyElement> {
+    if active {
+        Dep::Active(stream)
+    } else {
+        Dep::Passive(stream)
+    }
+}
+
 #[pyclass(unsendable, name = "Graph", from_py_object)]
 #[derive(Clone)]
 pub(crate) struct PyGraph(Vec<Rc<dyn Node>>);
@@ -0,0 +1,168 @@
+//! Batched hand-off of stream values to Python as Arrow record batches.
+//!
+//! `.to_arrow_batches(n)` buffers `n` ticks in Rust and converts the whole
+//! window to one `pyarrow.RecordBatch` under a single GIL acquisition, so
+//! Python code runs once per batch instead of once per tick. The batch is
+//! exported through the Arrow C data interface, so pyarrow adopts the Rust
+//! buffers without copying them.
+
+use std::rc::Rc;
+use std::sync::Arc;
+
+use arrow_array::{
+    ArrayRef, BooleanArray, Float64Array, Int64Array, NullArray, RecordBatch, StringArray,
+    TimestampNanosecondArray,
+};
+use arrow_pyarrow::ToPyArrow;
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyString};
+use wingfoil::{NanoTime, Stream, StreamOperators};
+
+use crate::py_element::PyElement;
+use crate::py_stream::{PyStream, py_callback_error};
+
+/// Inner implementation for the `.to_arrow_batches()` stream method.
+pub fn py_to_arrow_batches_inner(
+    stream: &Rc<dyn Stream<PyElement>>,
+    batch_size: usize,
+) -> PyResult<PyStream> {
+    if batch_size == 0 {
+        return Err(PyValueError::new_err(
+            "to_arrow_batches: batch_size must be at least 1",
+        ));
+    }
+    let batches =
+        stream
+            .with_time()
+            .buffer(batch_size)
+            .try_map(|rows: Vec<(NanoTime, PyElement)>| {
+                Python::attach(|py| {
+                    let batch = record_batch(py, &rows)?;
+                    Ok(PyElement::new(batch.to_pyarrow(py)?.unbind()))
+                })
+                .map_err(py_callback_error)
+            });
+    Ok(PyStream(batches))
+}
+
+/// Builds one record batch from a window of ticks.
+///
+/// The first column is `time`, the graph time as a nanosecond timestamp.
+/// Dict values add one column per key of the first dict in the window, with
+/// keys missing from later dicts read as nulls; any other value gives a
+/// single `value` column.
+fn record_batch(py: Python<'_>, rows: &[(NanoTime, PyElement)]) -> PyResult<RecordBatch> {
+    let times = rows.iter().map(|(time, _)| u64::from(*time) as i64);
+    let mut fields = vec![Field::new(
+        "time",
+        DataType::Timestamp(TimeUnit::Nanosecond, None),
+        false,
+    )];
+    let mut columns: Vec<ArrayRef> =
+        vec![Arc::new(TimestampNanosecondArray::from_iter_values(times))];
+
+    let values: Vec<&Bound<'_, PyAny>> = rows
+        .iter()
+        .map(|(_, value)| value.as_ref().bind(py))
+        .collect();
+    match values.first().map(|first| first.cast::<PyDict>()) {
+        Some(Ok(first)) => {
+            let dicts = values
+                .iter()
+                .map(|value| {
+                    value.cast::<PyDict>().map_err(|_| {
+                        PyTypeError::new_err(format!(
+                            "to_arrow_batches: expected every value to be a dict, got {value}"
+                        ))
+                    })
+                })
+                .collect::<PyResult<Vec<_>>>()?;
+            for key in first.keys() {
+                let name = key.extract::<String>().map_err(|_| {
+                    PyTypeError::new_err(format!(
+                        "to_arrow_batches: dict keys must be str, got {key}"
+                    ))
+                })?;
+                let cells = dicts
+                    .iter()
+                    .map(|dict| dict.get_item(&key))
+                    .collect::<PyResult<Vec<_>>>()?;
+                let (field, column) = column(&name, &cells)?;
+                fields.push(field);
+                columns.push(column);
+            }
+        }
+        _ => {
+            let cells: Vec<_> = values
+                .into_iter()
+                .map(|value| Some(value.clone()))
+                .collect();
+            let (field, column) = column("value", &cells)?;
+            fields.push(field);
+            columns.push(column);
+        }
+    }
+
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+        .map_err(|err| PyValueError::new_err(format!("to_arrow_batches: {err}")))
+}
+
+/// Converts one column of cells, typed by its first non-None cell: bool,
+/// int, float or str.  A column of only Nones has the null type.
+fn column(name: &str, cells: &[Option<Bound<'_, PyAny>>]) -> PyResult<(Field, ArrayRef)> {
+    let cells: Vec<Option<&Bound<'_, PyAny>>> = cells
+        .iter()
+        .map(|cell| cell.as_ref().filter(|cell| !cell.is_none()))
+        .collect();
+    let Some(first) = cells.iter().flatten().next() else {
+        let field = Field::new(name, DataType::Null, true);
+        return Ok((field, Arc::new(NullArray::new(cells.len()))));
+    };
+    // bool is checked before int, as Python's bool is a subclass of int.
+    let array: ArrayRef = if first.is_instance_of::<PyBool>() {
+        Arc::new(convert::<bool, BooleanArray>(name, first, &cells)?)
+    } else if first.is_instance_of::<PyInt>() {
+        Arc::new(convert::<i64, Int64Array>(name, first, &cells)?)
+    } else if first.is_instance_of::<PyFloat>() {
+        Arc::new(convert::<f64, Float64Array>(name, first, &cells)?)
+    } else if first.is_instance_of::<PyString>() {
+        Arc::new(convert::<String, StringArray>(name, first, &cells)?)
+    } else {
+        return Err(PyTypeError::new_err(format!(
+            "to_arrow_batches: column {name:?} has unsupported value {first}; \
+             expected bool, int, float or str"
+        )));
+    };
+    // Always nullable, so that batches of one stream share a schema.
+    let field = Field::new(name, array.data_type().clone(), true);
+    Ok((field, array))
+}
+
+/// Extracts every non-None cell as a `T`, failing on the first that is not.
+fn convert<T, A>(
+    name: &str,
+    first: &Bound<'_, PyAny>,
+    cells: &[Option<&Bound<'_, PyAny>>],
+) -> PyResult<A>
+where
+    T: for<'a, 'py> FromPyObject<'a, 'py>,
+    A: FromIterator<Option<T>>,
+{
+    cells
+        .iter()
+        .map(|cell| {
+            cell.map(|cell| {
+                cell.extract::<T>().map_err(|err| {
+                    PyTypeError::new_err(format!(
+                        "to_arrow_batches: column {name:?} is typed by its first value {first}, \
+                         but got {cell}: {}",
+                        Into::<PyErr>::into(err)
+                    ))
+                })
+            })
+            .transpose()
+        })
+        .collect()
+}
@@ -3,7 +3,7 @@ use pyo3::BoundObject;
 use std::any::type_name;
 
 use ::wingfoil::adapters::statistics::{StatisticsOperators, Weighting, Window};
-use ::wingfoil::{Element, IntoStream, NodeOperators, Stream, StreamOperators};
+use ::wingfoil::{Element, IntoStream, NanoTime, NodeOperators, Stream, StreamOperators};
 
 use pyo3::conversion::IntoPyObject;
 use pyo3::prelude::*;
@@ -44,6 +44,27 @@ impl PyStream {
         })
     }
 
+    /// Pairs each value with the graph time, converted to a float by `to_float`.
+    fn with_time_as(&self, to_float: fn(NanoTime) -> f64) -> PyStream {
+        let strm = self.0.with_time().map(move |(t, v)| {
+            Python::attach(|py| {
+                let py_tuple = pyo3::types::PyTuple::new(
+                    py,
+                    &[
+                        to_float(t)
+                            .into_pyobject(py)
+                            .expect("invariant: IntoPyObject for f64 is infallible")
+                            .into_any(),
+                        v.value().into_bound(py),
+                    ],
+                )
+                .expect("invariant: fixed-size tuple construction cannot fail");
+                PyElement::new(py_tuple.into_any().unbind())
+            })
+        });
+        PyStream(strm)
+    }
+
     pub fn inner_stream(&self) -> Rc<dyn Stream<PyElement>> {
         self.0.clone()
     }
@@ -221,6 +242,21 @@ impl PyStream {
         PyStream(strm)
     }
 
+    /// Emit each tumbling window of `batch_size` ticks as one
+    /// `pyarrow.RecordBatch`, converted in Rust under a single GIL hold.
+    ///
+    /// The batch has a `time` column (graph time as a nanosecond timestamp)
+    /// followed by one column per key when values are dicts, or a single
+    /// `value` column otherwise. Columns are typed from their first non-None
+    /// value (bool, int, float or str). The last batch of a run may be short.
+    /// Requires `pyarrow` to be installed.
+    ///
+    /// Raises:
+    ///     ValueError: if `batch_size` is 0.
+    fn to_arrow_batches(&self, batch_size: usize) -> PyResult<PyStream> {
+        crate::py_arrow::py_to_arrow_batches_inner(&self.0, batch_size)
+    }
+
     fn finally(&self, func: Py<PyAny>) -> PyNode {
         let node = self.0.finally(move |py_elmnt, _| {
             Python::attach(move |py| {
@@ -342,24 +378,13 @@ impl PyStream {
     /// Pairs each value with the graph time as a `(float, value)` tuple,
     /// where the float is seconds since Unix epoch.
     fn with_time(&self) -> PyStream {
-        let strm = self.0.with_time().map(|(t, v)| {
-            Python::attach(|py| {
-                let time_secs: f64 = t.into();
-                let py_tuple = pyo3::types::PyTuple::new(
-                    py,
-                    &[
-                        time_secs
-                            .into_pyobject(py)
-                            .expect("invariant: IntoPyObject for f64 is infallible")
-                            .into_any(),
-                        v.value().into_bound(py),
-                    ],
-                )
-                .expect("invariant: fixed-size tuple construction cannot fail");
-                PyElement::new(py_tuple.into_any().unbind())
-            })
-        });
-        PyStream(strm)
+        self.with_time_as(|t| u64::from(t) as f64 * NanoTime::SECONDS_PER_NANO)
+    }
+
+    /// Pairs each value with the graph time as a `(float, value)` tuple,
+    /// where the float is nanoseconds since Unix epoch.
+    fn with_time_nanos(&self) -> PyStream {
+        self.with_time_as(|t| u64::from(t) as f64)
     }
 
     /// Write this stream of dicts to a CSV file.
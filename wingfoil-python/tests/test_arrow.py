@@ -0,0 +1,93 @@
+"""Tests for Stream.to_arrow_batches. Skipped when pyarrow is not installed."""
+
+import unittest
+
+from wingfoil import ticker
+
+try:
+    import pyarrow as pa
+
+    PYARROW_AVAILABLE = True
+except ImportError:
+    PYARROW_AVAILABLE = False
+
+
+@unittest.skipUnless(PYARROW_AVAILABLE, "pyarrow not installed")
+class TestToArrowBatches(unittest.TestCase):
+    def test_scalars_batch_into_value_column(self):
+        stream = ticker(0.1).count().to_arrow_batches(3).collect()
+        stream.run(realtime=False, cycles=7)
+        batches = stream.peek_value()
+        self.assertEqual([b.num_rows for b in batches], [3, 3, 1])
+        self.assertIsInstance(batches[0], pa.RecordBatch)
+        self.assertEqual(batches[0].schema.names, ["time", "value"])
+        self.assertEqual(batches[0].schema.field("time").type, pa.timestamp("ns"))
+        self.assertEqual(batches[0].schema.field("value").type, pa.int64())
+        values = [v for b in batches for v in b.column("value").to_pylist()]
+        self.assertEqual(values, [1, 2, 3, 4, 5, 6, 7])
+
+    def test_time_column_is_graph_time(self):
+        stream = ticker(0.1).count().to_arrow_batches(2).collect()
+        stream.run(realtime=False, start=0.0, cycles=2)
+        times = stream.peek_value()[0].column("time").cast(pa.int64()).to_pylist()
+        self.assertEqual(times, [0, 100_000_000])
+
+    def test_dicts_batch_into_columns(self):
+        stream = (
+            ticker(0.1)
+            .count()
+            .map(lambda i: {"px": i * 0.5, "qty": i, "side": "buy", "live": i % 2 == 0})
+            .to_arrow_batches(4)
+            .collect()
+        )
+        stream.run(realtime=False, cycles=4)
+        batch = stream.peek_value()[0]
+        self.assertEqual(batch.schema.names, ["time", "px", "qty", "side", "live"])
+        self.assertEqual(
+            [f.type for f in batch.schema][1:],
+            [pa.float64(), pa.int64(), pa.string(), pa.bool_()],
+        )
+        self.assertEqual(batch.column("qty").to_pylist(), [1, 2, 3, 4])
+        self.assertEqual(batch.column("live").to_pylist(), [False, True, False, True])
+
+    def test_missing_keys_and_none_are_null(self):
+        stream = (
+            ticker(0.1)
+            .count()
+            .map(lambda i: {"a": i, "b": None} if i % 2 else {"a": None})
+            .to_arrow_batches(3)
+            .collect()
+        )
+        stream.run(realtime=False, cycles=3)
+        batch = stream.peek_value()[0]
+        self.assertEqual(batch.column("a").to_pylist(), [1, None, 3])
+        self.assertEqual(batch.schema.field("b").type, pa.null())
+
+    def test_batches_share_a_schema(self):
+        stream = ticker(0.1).count().map(float).to_arrow_batches(2).collect()
+        stream.run(realtime=False, cycles=5)
+        table = pa.Table.from_batches(stream.peek_value())
+        self.assertEqual(table.column("value").to_pylist(), [1.0, 2.0, 3.0, 4.0, 5.0])
+
+    def test_mixed_column_types_raise(self):
+        stream = (
+            ticker(0.1)
+            .count()
+            .map(lambda i: i if i == 1 else "two")
+            .to_arrow_batches(2)
+        )
+        with self.assertRaises(TypeError):
+            stream.run(realtime=False, cycles=2)
+
+    def test_unsupported_values_raise(self):
+        stream = ticker(0.1).count().map(lambda i: [i]).to_arrow_batches(1)
+        with self.assertRaises(TypeError):
+            stream.run(realtime=False, cycles=1)
+
+    def test_zero_batch_size_raises(self):
+        with self.assertRaises(ValueError):
+            ticker(0.1).count().to_arrow_batches(0)
+
+
+if __name__ == "__main__":
+    unittest.main()
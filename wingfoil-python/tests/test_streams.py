@@ -62,6 +62,22 @@ class TestBasicOperators(unittest.TestCase):
         values = [v for _, v in result]
         self.assertEqual(values, [1, 2, 3])
         self.assertTrue(all(times[i] < times[i + 1] for i in range(len(times) - 1)))
+        self.assertAlmostEqual(times[1] - times[0], 0.1, places=3)
+
+    def test_with_time_nanos(self):
+        stream = (
+            ticker(0.1)
+                .count()
+                .with_time_nanos()
+                .collect()
+        )
+        stream.run(realtime=False, cycles=3)
+        result = stream.peek_value()
+        times = [t for t, _ in result]
+        values = [v for _, v in result]
+        self.assertEqual(values, [1, 2, 3])
+        for t, expected in zip(times, [0.0, 1e8, 2e8]):
+            self.assertAlmostEqual(t - times[0], expected, delta=1e3)
 
     def test_limit(self):
         stream = ticker(0.1).count().limit(3).collect()
@@ -186,6 +202,21 @@ class TestBimap(unittest.TestCase):
         self.assertEqual(result.peek_value(), ["hello world"])
 
 
+    def test_bimap_passive_stream_does_not_trigger(self):
+        # with_latest_from: only fast ticks drive the output
+        fast = ticker(0.1).count()
+        slow = ticker(0.3).count().map(lambda x: x * 100)
+        result = bimap(fast, slow, lambda x, y: x + y, b_active=False).collect()
+        result.run(realtime=False, cycles=7)
+        self.assertEqual(result.peek_value(), [101, 102, 103, 204, 205, 206, 307])
+
+    def test_bimap_passive_first_argument(self):
+        fast = ticker(0.1).count()
+        slow = ticker(0.3).count().map(lambda x: x * 100)
+        result = bimap(slow, fast, lambda x, y: x + y, a_active=False).collect()
+        result.run(realtime=False, cycles=4)
+        self.assertEqual(result.peek_value(), [101, 102, 103, 204])
+
 class TestNodeAndGraph(unittest.TestCase):
     def test_node_run(self):
         # PyNode.run() directly (not stream.run())
@@ -0,0 +1,92 @@
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use serde::{Deserialize, Serialize};
+use wingfoil::adapters::codec::{Bincode, Codec, Json};
+
+/// A 64-byte market data record.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+struct Quote {
+    time: u64,
+    instrument: u64,
+    bid: f64,
+    ask: f64,
+    bid_size: f64,
+    ask_size: f64,
+    sequence: u64,
+    flags: u64,
+}
+
+/// A hand-rolled codec: the fields as little-endian words.
+struct RawQuote;
+
+impl Codec<Quote> for RawQuote {
+    fn version(&self) -> u8 {
+        0x80
+    }
+
+    fn encode(&self, q: &Quote, buf: &mut Vec<u8>) -> anyhow::Result<()> {
+        for word in [q.time, q.instrument, q.bid.to_bits(), q.ask.to_bits()] {
+            buf.extend_from_slice(&word.to_le_bytes());
+        }
+        for word in [
+            q.bid_size.to_bits(),
+            q.ask_size.to_bits(),
+            q.sequence,
+            q.flags,
+        ] {
+            buf.extend_from_slice(&word.to_le_bytes());
+        }
+        Ok(())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> anyhow::Result<Quote> {
+        let bytes: &[u8; 64] = bytes.try_into()?;
+        let word = |i: usize| u64::from_le_bytes(bytes[i * 8..i * 8 + 8].try_into().unwrap());
+        Ok(Quote {
+            time: word(0),
+            instrument: word(1),
+            bid: f64::from_bits(word(2)),
+            ask: f64::from_bits(word(3)),
+            bid_size: f64::from_bits(word(4)),
+            ask_size: f64::from_bits(word(5)),
+            sequence: word(6),
+            flags: word(7),
+        })
+    }
+}
+
+fn bench_codec(crit: &mut Criterion, name: &str, codec: impl Codec<Quote>) {
+    let quote = Quote {
+        time: 1_700_000_000_000_000_000,
+        instrument: 42,
+        bid: 101.25,
+        ask: 101.5,
+        bid_size: 300.0,
+        ask_size: 200.0,
+        sequence: 123_456,
+        flags: 3,
+    };
+    let mut buf = Vec::with_capacity(256);
+    crit.bench_function(&format!("codec_encode_{name}"), |bencher| {
+        bencher.iter(|| {
+            buf.clear();
+            codec.encode(black_box(&quote), &mut buf).unwrap();
+        })
+    });
+    buf.clear();
+    codec.encode(&quote, &mut buf).unwrap();
+    assert_eq!(codec.decode(&buf).unwrap(), quote);
+    crit.bench_function(&format!("codec_decode_{name}"), |bencher| {
+        bencher.iter(|| codec.decode(black_box(&buf)).unwrap())
+    });
+}
+
+fn bench(crit: &mut Criterion) {
+    bench_codec(crit, "bincode", Bincode);
+    bench_codec(crit, "json", Json);
+    bench_codec(crit, "raw", RawQuote);
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -1,6 +1,6 @@
 use criterion::{Criterion, criterion_group, criterion_main};
 use std::rc::Rc;
-use wingfoil::{Node, NodeOperators, StreamOperators, add_bench, merge};
+use wingfoil::{Node, NodeOperators, Stream, StreamOperators, add_bench, fuse_maps, merge, never};
 
 fn node(trig: Rc<dyn Node>) -> Rc<dyn Node> {
     trig
@@ -20,10 +20,76 @@ fn nodes(trig: Rc<dyn Node>, width: usize, depth: usize) -> Rc<dyn Node> {
     merge(streams)
 }
 
+/// A payload that is expensive to clone.
+fn large_payload(trig: Rc<dyn Node>) -> Rc<dyn Stream<Vec<u64>>> {
+    trig.count().map(|n| vec![n; 1 << 16])
+}
+
+/// `depth` readers of a large payload, each cloning it via `map`.
+fn map_large(trig: Rc<dyn Node>, depth: usize) -> Rc<dyn Node> {
+    let src = large_payload(trig);
+    let readers = (0..depth)
+        .map(|_| src.map(|v| std::hint::black_box(v[0])))
+        .collect::<Vec<_>>();
+    merge(readers).as_node()
+}
+
+/// As [map_large] but borrowing the payload via `map_ref`.
+fn map_ref_large(trig: Rc<dyn Node>, depth: usize) -> Rc<dyn Node> {
+    let src = large_payload(trig);
+    let readers = (0..depth)
+        .map(|_| src.map_ref(|v| std::hint::black_box(v[0])))
+        .collect::<Vec<_>>();
+    merge(readers).as_node()
+}
+
+/// One source with `active` downstreams that tick with it and `passive`
+/// downstreams that only sample it.  Passive edges should cost nothing per
+/// cycle, so both widths of `passive` should run alike.
+fn fan_out(trig: Rc<dyn Node>, active: usize, passive: usize) -> Rc<dyn Node> {
+    let src = trig.count();
+    let actives = (0..active)
+        .map(|_| src.map(std::hint::black_box))
+        .collect::<Vec<_>>();
+    let quiet = never();
+    let passives = (0..passive)
+        .map(|_| src.sample(quiet.clone()))
+        .collect::<Vec<_>>();
+    merge(vec![merge(actives), merge(passives)]).as_node()
+}
+
+/// A chain of `depth` maps, optionally fused into one node by [fuse_maps].
+fn map_chain(trig: Rc<dyn Node>, depth: usize, fuse: bool) -> Rc<dyn Node> {
+    let mut stream = trig.count();
+    for _ in 0..depth {
+        stream = stream.map(std::hint::black_box);
+    }
+    let node = stream.as_node();
+    if fuse {
+        fuse_maps(std::slice::from_ref(&node));
+    }
+    node
+}
+
+/// A value logged at a level the logger has disabled, which should cost
+/// little more than [node].
+fn logged_disabled(trig: Rc<dyn Node>) -> Rc<dyn Node> {
+    trig.count().logged("count", log::Level::Trace).as_node()
+}
+
 fn bench(crit: &mut Criterion) {
     add_bench(crit, "node", node);
     add_bench(crit, "10x10", |trig| nodes(trig, 10, 10));
     add_bench(crit, "100x100", |trig| nodes(trig, 100, 100));
+    add_bench(crit, "map_large", |trig| map_large(trig, 10));
+    add_bench(crit, "map_ref_large", |trig| map_ref_large(trig, 10));
+    add_bench(crit, "fan_out_10k", |trig| fan_out(trig, 10_000, 0));
+    add_bench(crit, "fan_out_10k_10k_passive", |trig| {
+        fan_out(trig, 10_000, 10_000)
+    });
+    add_bench(crit, "map_chain_10", |trig| map_chain(trig, 10, false));
+    add_bench(crit, "map_chain_10_fused", |trig| map_chain(trig, 10, true));
+    add_bench(crit, "logged_disabled", logged_disabled);
 }
 
 criterion_group!(benches, bench);
@@ -0,0 +1,55 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::rc::Rc;
+use wingfoil::{
+    Burst, Graph, IntoStream, IteratorStream, NanoTime, Node, RunFor, RunMode, Stream, ValueAt,
+    merge, merge_ordered,
+};
+
+const SOURCES: u64 = 10;
+const ROWS: u64 = 1_000;
+
+/// Rows of source `i`, interleaved with the other sources.
+fn rows(i: u64) -> impl Iterator<Item = ValueAt<u64>> {
+    (0..ROWS).map(move |k| {
+        let time = k * SOURCES + i;
+        ValueAt::new(time, NanoTime::new(time))
+    })
+}
+
+fn run(node: Rc<dyn Node>) {
+    Graph::new(
+        vec![node],
+        RunMode::HistoricalFrom(NanoTime::ZERO),
+        RunFor::Forever,
+    )
+    .run()
+    .unwrap();
+}
+
+/// One node reading all sources via a heap.
+fn ordered() -> Rc<dyn Node> {
+    let sources = (0..SOURCES)
+        .map(|i| {
+            Box::new(rows(i).map(Ok)) as Box<dyn Iterator<Item = anyhow::Result<ValueAt<u64>>>>
+        })
+        .collect();
+    merge_ordered(sources).as_node()
+}
+
+/// One node per source, each scheduling its own callbacks, into `merge`.
+fn plain() -> Rc<dyn Node> {
+    let sources: Vec<Rc<dyn Stream<Burst<u64>>>> = (0..SOURCES)
+        .map(|i| IteratorStream::new(Box::new(rows(i))).into_stream())
+        .collect();
+    merge(sources).as_node()
+}
+
+fn bench(crit: &mut Criterion) {
+    crit.bench_function("merge_ordered_10", |bencher| {
+        bencher.iter(|| run(ordered()))
+    });
+    crit.bench_function("merge_10", |bencher| bencher.iter(|| run(plain())));
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
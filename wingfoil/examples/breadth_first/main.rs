@@ -1,16 +1,28 @@
 #![doc = include_str!("./README.md")]
 
+use std::rc::Rc;
+use std::time::Duration;
 use wingfoil::*;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    env_logger::init();
-    let mut source = constant(1_u128);
-    for _ in 1..128 {
+const DEPTH: usize = 128;
+
+fn branch_and_recombine(mut source: Rc<dyn Stream<u128>>) -> Rc<dyn Stream<u128>> {
+    for _ in 1..DEPTH {
         source = add(&source, &source);
     }
+    source
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::init();
+    let source = branch_and_recombine(constant(1_u128));
     source
         .timed()
         .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)?;
     println!("value {:?}", source.peek_value());
+
+    // Tick the same graph on every cycle to measure its throughput.
+    let ticking = branch_and_recombine(ticker(Duration::from_nanos(1)).produce(|| 1_u128));
+    println!("{}", ticking.bench(100_000)?);
     Ok(())
 }
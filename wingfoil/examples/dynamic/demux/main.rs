@@ -1,16 +1,18 @@
-//! Alternative price aggregator using `demux_it` instead of the dynamic-graph
+//! Alternative price aggregator using `demux_it_map` instead of the dynamic-graph
 //! `state.add_upstream()` / `state.remove_node()` API.
 //!
 //! The approach:
 //! 1. Map both `inst_price` and `del_instrument` to a common [`InstEvent`] enum.
 //! 2. [`combine`] the two event streams so simultaneous ticks are both captured.
-//! 3. [`demux_it`] routes each event to a fixed-capacity pool of per-instrument
-//!    slots; [`DemuxEvent::Close`] recycles a slot when its instrument is deleted,
-//!    keeping resource use proportional to the number of *concurrent* instruments.
-//! 4. Apply price rounding per slot, passing [`Delete`] events through unchanged.
-//! 5. [`combine`] all slots; flatten and [`fold`] into a [`BTreeMap`] price book.
+//! 3. [`demux_it_map`] routes each event to a fixed-capacity pool of
+//!    per-instrument slots; [`DemuxEvent::Close`] recycles a slot when its
+//!    instrument is deleted, keeping resource use proportional to the number of
+//!    *concurrent* instruments.
+//! 4. Each slot applies price rounding, passing [`Delete`] events through
+//!    unchanged, and `demux_it_map` combines the slots.
+//! 5. Flatten and [`fold`] into a [`BTreeMap`] price book.
 //!
-//! [`demux_it`]: wingfoil::StreamOperators::demux_it
+//! [`demux_it_map`]: wingfoil::StreamOperators::demux_it_map
 //! [`Delete`]: InstEvent::Delete
 
 #[path = "../market_data.rs"]
@@ -35,8 +37,8 @@ enum InstEvent {
 
 fn inst_key(event: &InstEvent) -> Instrument {
     match event {
-        InstEvent::Price(i, _) | InstEvent::Delete(i) => i.clone(),
-        InstEvent::None => String::new(),
+        InstEvent::Price(i, _) | InstEvent::Delete(i) => *i,
+        InstEvent::None => Instrument::default(),
     }
 }
 
@@ -47,18 +49,17 @@ pub fn build(period: Duration) -> (Rc<dyn Stream<PriceBook>>, Rc<dyn Node>) {
     let price_events = src.inst_price.map(|(i, p)| InstEvent::Price(i, p));
     let del_events = src.del_instrument.map(InstEvent::Delete);
     let all_events = combine(vec![price_events, del_events]);
-    let (slots, overflow) = all_events.demux_it(CAPACITY, |event| {
-        let key = inst_key(event);
-        let de = match event {
-            InstEvent::Delete(_) => DemuxEvent::Close,
-            _ => DemuxEvent::None,
-        };
-        (key, de)
-    });
-    let overflow_node = overflow.panic();
-    let processed: Vec<Rc<dyn Stream<Burst<InstEvent>>>> = slots
-        .into_iter()
-        .map(|slot| {
+    let (processed, overflow) = all_events.demux_it_map(
+        CAPACITY,
+        |event| {
+            let key = inst_key(event);
+            let de = match event {
+                InstEvent::Delete(_) => DemuxEvent::Close,
+                _ => DemuxEvent::None,
+            };
+            (key, de)
+        },
+        |slot| {
             slot.map(|burst| {
                 burst
                     .into_iter()
@@ -66,11 +67,12 @@ pub fn build(period: Duration) -> (Rc<dyn Stream<PriceBook>>, Rc<dyn Node>) {
                         InstEvent::Price(i, p) => InstEvent::Price(i, (p * 100.0).round()),
                         other => other,
                     })
-                    .collect()
+                    .collect::<Burst<InstEvent>>()
             })
-        })
-        .collect();
-    let price_book = combine(processed)
+        },
+    );
+    let overflow_node = overflow.panic();
+    let price_book = processed
         .map(|bursts: Burst<Burst<InstEvent>>| -> Burst<InstEvent> {
             bursts.into_iter().flatten().collect()
         })
@@ -117,7 +119,10 @@ mod tests {
     use wingfoil::{Graph, NanoTime, RunFor, RunMode};
 
     fn b(pairs: &[(&str, f64)]) -> BTreeMap<Instrument, Price> {
-        pairs.iter().map(|&(k, v)| (k.into(), v)).collect()
+        pairs
+            .iter()
+            .map(|&(k, v)| (k.parse().unwrap(), v))
+            .collect()
     }
 
     /// Expected price-book state after each emission over 20 cycles.
@@ -154,6 +159,70 @@ mod tests {
         ]
     }
 
+    /// The slot processing wired by hand onto [demux_it] and [combine]d,
+    /// as [build] did before [demux_it_map](StreamOperators::demux_it_map).
+    fn build_by_hand(period: Duration) -> (Rc<dyn Stream<PriceBook>>, Rc<dyn Node>) {
+        let src = self::source::market_data(period);
+        let price_events = src.inst_price.map(|(i, p)| InstEvent::Price(i, p));
+        let del_events = src.del_instrument.map(InstEvent::Delete);
+        let (slots, overflow) =
+            combine(vec![price_events, del_events]).demux_it(CAPACITY, |event| match event {
+                InstEvent::Delete(_) => (inst_key(event), DemuxEvent::Close),
+                _ => (inst_key(event), DemuxEvent::None),
+            });
+        let processed = slots
+            .into_iter()
+            .map(|slot| {
+                slot.map(|burst| {
+                    burst
+                        .into_iter()
+                        .map(|event| match event {
+                            InstEvent::Price(i, p) => InstEvent::Price(i, (p * 100.0).round()),
+                            other => other,
+                        })
+                        .collect::<Burst<InstEvent>>()
+                })
+            })
+            .collect();
+        let price_book = combine(processed).fold(|book: &mut PriceBook, bursts| {
+            for event in bursts.into_iter().flatten() {
+                match event {
+                    InstEvent::Price(inst, price) => {
+                        book.insert(inst, price);
+                    }
+                    InstEvent::Delete(inst) => {
+                        book.remove(&inst);
+                    }
+                    InstEvent::None => {}
+                }
+            }
+        });
+        (price_book, overflow.panic())
+    }
+
+    fn book_states(
+        (price_book, overflow_node): (Rc<dyn Stream<PriceBook>>, Rc<dyn Node>),
+    ) -> Vec<PriceBook> {
+        let states = price_book.accumulate();
+        Graph::new(
+            vec![states.clone().as_node(), overflow_node],
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(20),
+        )
+        .run()
+        .unwrap();
+        states.peek_value()
+    }
+
+    #[test]
+    fn demux_it_map_matches_hand_wired_demux_it() {
+        let period = Duration::from_secs(1);
+        assert_eq!(
+            book_states(build(period)),
+            book_states(build_by_hand(period))
+        );
+    }
+
     /// Demux has no recycle cycles, so RunFor::Cycles(20) maps cleanly to n=1..20.
     #[test]
     fn price_book_accumulation() {
@@ -34,14 +34,11 @@ pub fn build(period: Duration) -> (Rc<dyn Stream<PriceBook>>, Rc<dyn Node>) {
         src.del_instrument,
         {
             let inst_price = inst_price.clone();
-            move |inst: Instrument| {
-                let inst_key = inst.clone();
-                process(inst_price.filter_value(move |(i, _)| i == &inst_key))
-            }
+            move |inst: Instrument| process(inst_price.filter_value(move |(i, _)| *i == inst))
         },
         BTreeMap::new(),
         |book: &mut PriceBook, key, (_, price)| {
-            book.insert(key.clone(), price);
+            book.insert(*key, price);
         },
         |book: &mut PriceBook, key| {
             book.remove(key);
@@ -64,10 +64,9 @@ impl MutableNode for PriceAggregator {
     fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
         if state.ticked(self.new_instrument.clone().as_node()) {
             let instrument = self.new_instrument.peek_value();
-            let inst_key = instrument.clone();
             let processed = process(
                 self.inst_price
-                    .filter_value(move |(inst, _)| inst == &inst_key),
+                    .filter_value(move |(inst, _)| *inst == instrument),
             );
             state.add_upstream(processed.clone().as_node(), true, true);
             self.per_instrument.insert(instrument, processed);
@@ -83,7 +82,7 @@ impl MutableNode for PriceAggregator {
         for (inst, processed) in &self.per_instrument {
             if state.ticked(processed.clone().as_node()) {
                 let (_inst, price) = processed.peek_value();
-                self.value.insert(inst.clone(), price);
+                self.value.insert(*inst, price);
                 ticked = true;
             }
         }
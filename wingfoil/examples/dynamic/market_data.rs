@@ -1,11 +1,13 @@
 //! Source module: emits instrument lifecycle events and price data.
 
+use std::fmt::Write;
 use std::rc::Rc;
 use std::time::Duration;
 
 use wingfoil::*;
 
-pub type Instrument = String;
+/// Stored inline, so keying and cloning instruments never allocates.
+pub type Instrument = SmallStr;
 pub type Price = f64;
 
 /// Bundles all three streams produced by [`market_data()`].
@@ -39,7 +41,7 @@ impl Default for LifecycleState {
         Self {
             next_id: 1,
             live: Vec::new(),
-            event: (true, String::new()),
+            event: (true, Instrument::default()),
         }
     }
 }
@@ -66,9 +68,9 @@ pub fn market_data(period: Duration) -> MarketData {
                 state.event = (false, inst);
             } else {
                 // Add a fresh instrument.
-                let inst = format!("inst{}", state.next_id);
+                let inst = instrument(state.next_id);
                 state.next_id += 1;
-                state.live.push(inst.clone());
+                state.live.push(inst);
                 state.event = (true, inst);
             }
         })
@@ -85,7 +87,7 @@ pub fn market_data(period: Duration) -> MarketData {
 
     let inst_price = price_ticker.count().map(|n: u64| {
         let id = (n - 1) / 3 + (n - 1) % 3 + 1;
-        (format!("inst{id}"), id as f64 + n as f64 / 100.0)
+        (instrument(id), id as f64 + n as f64 / 100.0)
     });
 
     MarketData {
@@ -95,6 +97,13 @@ pub fn market_data(period: Duration) -> MarketData {
     }
 }
 
+/// `inst<id>`, written in place.
+fn instrument(id: u64) -> Instrument {
+    let mut inst = Instrument::default();
+    write!(inst, "inst{id}").expect("instrument id fits inline");
+    inst
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -116,14 +125,19 @@ mod tests {
             assert_eq!(
                 v,
                 ["inst1", "inst2", "inst3", "inst4", "inst5", "inst6"]
-                    .map(String::from)
+                    .map(|inst| Instrument::new(inst).unwrap())
                     .to_vec()
             );
             Ok(())
         });
 
         let del_insts = src.del_instrument.accumulate().finally(|v, _| {
-            assert_eq!(v, ["inst1", "inst2", "inst3"].map(String::from).to_vec());
+            assert_eq!(
+                v,
+                ["inst1", "inst2", "inst3"]
+                    .map(|inst| Instrument::new(inst).unwrap())
+                    .to_vec()
+            );
             Ok(())
         });
 
@@ -131,15 +145,15 @@ mod tests {
             assert_eq!(
                 v,
                 vec![
-                    ("inst1".into(), 1.01),
-                    ("inst2".into(), 2.02),
-                    ("inst3".into(), 3.03),
-                    ("inst2".into(), 2.04),
-                    ("inst3".into(), 3.05),
-                    ("inst4".into(), 4.06),
-                    ("inst3".into(), 3.07),
-                    ("inst4".into(), 4.08),
-                    ("inst5".into(), 5.09),
+                    (Instrument::new("inst1").unwrap(), 1.01),
+                    (Instrument::new("inst2").unwrap(), 2.02),
+                    (Instrument::new("inst3").unwrap(), 3.03),
+                    (Instrument::new("inst2").unwrap(), 2.04),
+                    (Instrument::new("inst3").unwrap(), 3.05),
+                    (Instrument::new("inst4").unwrap(), 4.06),
+                    (Instrument::new("inst3").unwrap(), 3.07),
+                    (Instrument::new("inst4").unwrap(), 4.08),
+                    (Instrument::new("inst5").unwrap(), 5.09),
                 ]
             );
             Ok(())
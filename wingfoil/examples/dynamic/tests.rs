@@ -1,7 +1,10 @@
 use super::*;
 
 fn b(pairs: &[(&str, f64)]) -> BTreeMap<Instrument, Price> {
-    pairs.iter().map(|&(k, v)| (k.into(), v)).collect()
+    pairs
+        .iter()
+        .map(|&(k, v)| (k.parse().unwrap(), v))
+        .collect()
 }
 
 /// Expected price-book state after each emission over 20 cycles.
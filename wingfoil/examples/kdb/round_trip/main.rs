@@ -86,7 +86,7 @@ fn main() -> Result<()> {
     // write
     generate(num_rows)
         .kdb_write(conn.clone(), table)
-        .run(run_mode, run_for)?;
+        .run(run_mode, run_for.clone())?;
     let baseline = generate(num_rows);
     // read — use kdb_read with time-slice filtering
     let read = kdb_read::<Trade>(
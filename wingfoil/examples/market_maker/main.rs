@@ -0,0 +1,456 @@
+#![doc = include_str!("./README.md")]
+
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Duration;
+
+use log::Level::Info;
+use serde::{Deserialize, Serialize};
+use wingfoil::adapters::csv::*;
+use wingfoil::adapters::statistics::*;
+use wingfoil::*;
+
+/// One hour of NASDAQ AAPL limit orders, shared with the order book example.
+const INPUT: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/examples/order_book/data/aapl.csv"
+);
+/// The fixture starts at the 09:30 open, in seconds from midnight.
+const MARKET_OPEN: Duration = Duration::from_secs(34_200);
+const SESSION: Duration = Duration::from_secs(3_600);
+/// LOBSTER prices are in ten-thousandths of a dollar.
+const DOLLARS_PER_PRICE: f64 = 1e-4;
+/// One cent.
+const PRICE_TICK: u64 = 100;
+
+/// A LOBSTER message row.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Message {
+    seconds: f64,
+    message_type: u8,
+    order_id: u128,
+    quantity: u64,
+    price: u64,
+    direction: i8,
+}
+
+/// The best bid and ask, with the quantity resting at each.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Top {
+    bid: u64,
+    bid_qty: u64,
+    ask: u64,
+    ask_qty: u64,
+}
+
+impl Top {
+    fn mid(&self) -> f64 {
+        (self.bid + self.ask) as f64 / 2.0
+    }
+
+    /// From -1, all the resting quantity on the ask, to 1, all on the bid.
+    fn imbalance(&self) -> f64 {
+        let (bid, ask) = (self.bid_qty as f64, self.ask_qty as f64);
+        (bid - ask) / (bid + ask)
+    }
+}
+
+/// Our two-sided quote.  A side is `None` when we are not quoting it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Quote {
+    bid: Option<u64>,
+    ask: Option<u64>,
+    size: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Side {
+    #[default]
+    Buy,
+    Sell,
+}
+
+/// One of our quotes trading.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Fill {
+    pub side: Side,
+    pub price: u64,
+    pub qty: u64,
+}
+
+/// Shares held and the cash, in dollars, paid for them.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Position {
+    pub qty: i64,
+    pub cash: f64,
+}
+
+impl Position {
+    fn apply(&mut self, fill: &Fill) {
+        let (qty, cash) = (
+            fill.qty as i64,
+            (fill.price * fill.qty) as f64 * DOLLARS_PER_PRICE,
+        );
+        match fill.side {
+            Side::Buy => {
+                self.qty += qty;
+                self.cash -= cash;
+            }
+            Side::Sell => {
+                self.qty -= qty;
+                self.cash += cash;
+            }
+        }
+    }
+
+    fn pnl(&self, mid: f64) -> f64 {
+        self.cash + self.qty as f64 * mid * DOLLARS_PER_PRICE
+    }
+}
+
+/// What the quoter needs to know from the tracker, fed back a cycle later.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Risk {
+    position: i64,
+    killed: bool,
+}
+
+/// Strategy and simulation settings.
+#[derive(Debug, Clone, Copy)]
+pub struct Params {
+    /// From sending a quote to it resting at the exchange.
+    pub latency: Duration,
+    pub quote_size: u64,
+    /// Half-life of the mid-price EWMA that anchors fair value.
+    pub fair_half_life: Duration,
+    /// How far fair value leans towards the heavier side of the book at
+    /// full imbalance, in price units.
+    pub imbalance_skew: f64,
+    /// How far fair value leans away from the position, per share held.
+    pub inventory_skew: f64,
+    /// Quoted either side of fair value, in price units.
+    pub half_spread: f64,
+    /// No more buying (selling) once long (short) this many shares.
+    pub max_position: i64,
+    /// Fall from the PnL high-water mark, in dollars, that stops quoting
+    /// for the rest of the session.
+    pub max_drawdown: f64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            latency: Duration::from_micros(500),
+            quote_size: 100,
+            fair_half_life: Duration::from_secs(1),
+            imbalance_skew: 200.0,
+            inventory_skew: 2.0,
+            half_spread: 300.0,
+            max_position: 500,
+            max_drawdown: 500.0,
+        }
+    }
+}
+
+impl Params {
+    fn quote(&self, top: Top, fair: f64, risk: Risk) -> Quote {
+        if risk.killed {
+            return Quote::default();
+        }
+        let fair = fair + self.imbalance_skew * top.imbalance()
+            - self.inventory_skew * risk.position as f64;
+        let bid = (fair - self.half_spread) as u64 / PRICE_TICK * PRICE_TICK;
+        let ask = (fair + self.half_spread) as u64 / PRICE_TICK * PRICE_TICK + PRICE_TICK;
+        Quote {
+            // never cross the market: rest behind the best price at worst
+            bid: (risk.position < self.max_position).then_some(bid.min(top.bid)),
+            ask: (risk.position > -self.max_position).then_some(ask.max(top.ask)),
+            size: self.quote_size,
+        }
+    }
+}
+
+/// Stands in for an exchange connection.  The latest quote to arrive rests
+/// until the market trades through one of its sides, which then fills in
+/// full and is not requoted until the next quote arrives.
+struct SimulatedGateway {
+    quotes: Rc<dyn Stream<Quote>>,
+    top: Rc<dyn Stream<Top>>,
+    resting: Quote,
+    fills: Burst<Fill>,
+}
+
+impl SimulatedGateway {
+    fn new(quotes: Rc<dyn Stream<Quote>>, top: Rc<dyn Stream<Top>>) -> Self {
+        Self {
+            quotes,
+            top,
+            resting: Quote::default(),
+            fills: Burst::new(),
+        }
+    }
+}
+
+impl MutableNode for SimulatedGateway {
+    fn upstreams(&self) -> UpStreams {
+        UpStreams::new(
+            vec![self.quotes.clone().as_node(), self.top.clone().as_node()],
+            vec![],
+        )
+    }
+
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        self.fills.clear();
+        if state.ticked(self.quotes.clone().as_node()) {
+            self.resting = self.quotes.peek_value();
+        }
+        let top = self.top.peek_value();
+        let qty = self.resting.size;
+        if let Some(price) = self.resting.bid
+            && top.ask <= price
+        {
+            self.fills.push(Fill {
+                side: Side::Buy,
+                price,
+                qty,
+            });
+            self.resting.bid = None;
+        }
+        if let Some(price) = self.resting.ask
+            && top.bid >= price
+        {
+            self.fills.push(Fill {
+                side: Side::Sell,
+                price,
+                qty,
+            });
+            self.resting.ask = None;
+        }
+        Ok(!self.fills.is_empty())
+    }
+}
+
+impl StreamPeekRef<Burst<Fill>> for SimulatedGateway {
+    fn peek_ref(&self) -> &Burst<Fill> {
+        &self.fills
+    }
+}
+
+/// The streams of a wired market maker, and the nodes to run it.
+pub struct MarketMaker {
+    pub fills: Rc<dyn Stream<Burst<Fill>>>,
+    pub pnl: Rc<dyn Stream<f64>>,
+    pub killed: Rc<dyn Stream<bool>>,
+    pub nodes: Vec<Rc<dyn Node>>,
+}
+
+/// Wires the market maker over `input`, writing `fills.csv` and `pnl.csv`
+/// to `out_dir`.  Message times are shifted by `time_offset`.
+pub fn build(
+    input: &str,
+    out_dir: &Path,
+    params: Params,
+    time_offset: NanoTime,
+) -> anyhow::Result<MarketMaker> {
+    // order book
+    let book = RefCell::new(lobster::OrderBook::default());
+    let get_time = move |msg: &Message| NanoTime::new((msg.seconds * 1e9) as u64) + time_offset;
+    let top = csv_read(input, get_time, true)?
+        .map(move |messages| update_book(messages, &mut book.borrow_mut()))
+        .filter_none();
+
+    // signals
+    let mid = top.map(|top| top.mid());
+    let fair = mid.ewma(EwmaSpan::HalfLife(params.fair_half_life));
+
+    // quoting, reading risk fed back from the tracker below
+    let (risk_tx, risk_rx) = feedback::<Risk>();
+    let quotes = trimap(
+        Dep::Active(top.clone()),
+        Dep::Passive(fair),
+        Dep::Passive(risk_rx),
+        move |top, fair, risk| params.quote(top, fair, risk),
+    )
+    .distinct();
+
+    // gateway
+    let fills = SimulatedGateway::new(quotes.delay(params.latency), top).into_stream();
+
+    // position, PnL and the kill switch
+    let position = fills.fold(|position: &mut Position, fills| {
+        fills.iter().for_each(|fill| position.apply(fill));
+    });
+    let pnl = bimap(
+        Dep::Active(position.clone()),
+        Dep::Active(mid),
+        |position, mid| position.pnl(mid),
+    );
+    let max_drawdown = params.max_drawdown;
+    let killed = pnl
+        .drawdown()
+        .fold(move |killed: &mut bool, drawdown| *killed |= drawdown < -max_drawdown)
+        .distinct();
+    let risk = bimap(
+        Dep::Active(position),
+        Dep::Active(killed.clone()),
+        |position, killed| Risk {
+            position: position.qty,
+            killed,
+        },
+    )
+    .feedback(risk_tx);
+
+    // exports
+    let fills_export = fills.csv_write(out_dir.join("fills.csv").to_str().unwrap());
+    let pnl_curve = pnl.throttle(Duration::from_secs(60));
+    let pnl_export = pnl_curve.csv_write(out_dir.join("pnl.csv").to_str().unwrap());
+    let kill_log = killed.logged("killed", Info).as_node();
+    let pnl_log = pnl_curve.logged("pnl", Info).as_node();
+
+    Ok(MarketMaker {
+        fills,
+        pnl,
+        killed,
+        nodes: vec![risk.as_node(), fills_export, pnl_export, kill_log, pnl_log],
+    })
+}
+
+/// Applies a burst of same-time messages to the book and returns its top,
+/// or `None` while either side is empty.
+fn update_book(messages: Burst<Message>, book: &mut lobster::OrderBook) -> Option<Top> {
+    for msg in messages {
+        // hidden executions (5) carry no book information
+        if msg.message_type != 5 {
+            book.execute(parse_order(msg));
+        }
+    }
+    let depth = book.depth(1);
+    let (bid, ask) = (depth.bids.last()?, depth.asks.first()?);
+    Some(Top {
+        bid: bid.price,
+        bid_qty: bid.qty,
+        ask: ask.price,
+        ask_qty: ask.qty,
+    })
+}
+
+fn parse_order(msg: Message) -> lobster::OrderType {
+    let side = match msg.direction {
+        1 => lobster::Side::Bid,
+        -1 => lobster::Side::Ask,
+        direction => panic!("unrecognised direction: {direction}"),
+    };
+    let (id, qty, price) = (msg.order_id, msg.quantity, msg.price);
+    match msg.message_type {
+        1 => lobster::OrderType::Limit {
+            id,
+            side,
+            qty,
+            price,
+        },
+        // partial (2) and full (3) cancels, both treated as full
+        2 | 3 => lobster::OrderType::Cancel { id },
+        // an execution against a resting order: replay it as the aggressor
+        4 => lobster::OrderType::Limit {
+            id,
+            side: !side,
+            qty,
+            price,
+        },
+        other => panic!("unrecognised message type: {other}"),
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+    let realtime = std::env::args().any(|arg| arg == "--realtime");
+    let (run_mode, run_for, time_offset) = if realtime {
+        // replay the session paced in real time, starting now
+        let offset = NanoTime::now() - NanoTime::new(MARKET_OPEN.as_nanos() as u64);
+        (RunMode::RealTime, RunFor::Duration(SESSION), offset)
+    } else {
+        (
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Forever,
+            NanoTime::ZERO,
+        )
+    };
+    let market_maker = build(INPUT, Path::new("."), Params::default(), time_offset)?;
+    Graph::new(market_maker.nodes, run_mode, run_for)
+        .print()
+        .run()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What a fixture session leaves behind.
+    struct Session {
+        fills: usize,
+        /// Data rows written to fills.csv.
+        exported_fills: usize,
+        final_pnl: f64,
+        killed: Vec<bool>,
+    }
+
+    fn run_session(params: Params) -> Session {
+        let out_dir = std::env::temp_dir().join(format!(
+            "wingfoil_mm_{}_{}",
+            std::process::id(),
+            params.max_drawdown
+        ));
+        std::fs::create_dir_all(&out_dir).unwrap();
+        let market_maker = build(INPUT, &out_dir, params, NanoTime::ZERO).unwrap();
+        let fills = market_maker
+            .fills
+            .fold(|count: &mut usize, fills| *count += fills.len());
+        let pnl = market_maker.pnl.collect();
+        let killed = market_maker.killed.collect();
+        let mut nodes = market_maker.nodes;
+        nodes.extend([
+            fills.clone().as_node(),
+            pnl.clone().as_node(),
+            killed.clone().as_node(),
+        ]);
+        Graph::new(
+            nodes,
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Forever,
+        )
+        .run()
+        .unwrap();
+        let exported = std::fs::read_to_string(out_dir.join("fills.csv")).unwrap();
+        std::fs::remove_dir_all(&out_dir).unwrap();
+        Session {
+            fills: fills.peek_value(),
+            exported_fills: exported.lines().count() - 1,
+            final_pnl: pnl.peek_value().last().unwrap().value,
+            killed: killed.peek_value().into_iter().map(|v| v.value).collect(),
+        }
+    }
+
+    #[test]
+    fn fixture_session_regression() {
+        let session = run_session(Params::default());
+        assert_eq!(session.fills, 58);
+        assert_eq!(session.exported_fills, session.fills);
+        assert_eq!(format!("{:.2}", session.final_pnl), "992.00");
+        assert_eq!(session.killed, vec![false]);
+    }
+
+    #[test]
+    fn kill_switch_stops_quoting() {
+        let session = run_session(Params {
+            max_drawdown: 100.0,
+            ..Params::default()
+        });
+        assert_eq!(session.killed, vec![false, true]);
+        assert_eq!(session.fills, 7);
+        assert_eq!(session.exported_fills, session.fills);
+        // the position held when quoting stopped still marks to market
+        assert_eq!(format!("{:.2}", session.final_pnl), "362.00");
+    }
+}
@@ -99,7 +99,7 @@ fn main() -> Result<()> {
     // write
     generate(num_rows)
         .postgres_write(conn.clone(), table)
-        .run(run_mode, run_for)?;
+        .run(run_mode, run_for.clone())?;
 
     let baseline = generate(num_rows);
     // read — time-sliced, one query per day here (single 24h slice covers the run)
@@ -1,5 +1,6 @@
 use log::Level::Info;
 use std::rc::Rc;
+use std::sync::mpsc;
 use std::thread;
 use std::time::Duration;
 use wingfoil::*;
@@ -30,6 +31,25 @@ fn main() {
         .mapper(map_graph)
         .collapse()
         .logged(&label("main-post"), Info)
+        .run(run_mode, run_for.clone())
+        .unwrap();
+
+    // The same producer hop, wired by hand with pipe_local: the sending
+    // graph runs on its own thread and hands back a receiver factory.
+    let (tx, rx) = mpsc::channel();
+    let worker_run_for = run_for.clone();
+    let worker = thread::spawn(move || {
+        let (send, recv) = pipe_local(ticker(period).count().logged(&label("piped"), Info));
+        tx.send(recv)
+            .expect("main thread is waiting for the receiver");
+        send.run(run_mode, worker_run_for)
+    });
+    let recv = rx.recv().expect("worker sends the receiver before running");
+    recv()
+        .collapse()
+        .map(|x| x * 10)
+        .logged(&label("main-pipe"), Info)
         .run(run_mode, run_for)
         .unwrap();
+    worker.join().expect("pipe worker panicked").unwrap();
 }
@@ -110,14 +110,15 @@ impl WindowFilter {
 ///
 /// * `period` must be non-zero (a zero period cannot advance through the window).
 /// * `start_time` must be non-zero — i.e. `RunMode::HistoricalFrom` with an explicit start.
-/// * `end_time` must be a bounded time from `RunFor::Duration`; `Forever` arrives as
-///   `Ok(NanoTime::MAX)` and `Cycles` as `Err`, both rejected.
+/// * `end_time` is [`RunParams::end_time`](crate::nodes::RunParams::end_time):
+///   `None` (`RunFor::Forever`, `Cycles` or `Condition`) means unbounded, so
+///   the slices run up to the present, past which a table holds no rows.
 pub(crate) fn compute_validated_time_slices(
     adapter: &str,
     start_time: NanoTime,
-    end_time: anyhow::Result<NanoTime>,
+    end_time: Option<NanoTime>,
     period: std::time::Duration,
-) -> anyhow::Result<Vec<((NanoTime, NanoTime), i32, usize)>> {
+) -> anyhow::Result<TimeSlices> {
     if period.is_zero() {
         anyhow::bail!("{adapter}: period must be greater than zero");
     }
@@ -127,20 +128,13 @@ pub(crate) fn compute_validated_time_slices(
             use RunMode::HistoricalFrom with an explicit start time"
         );
     }
-    let end_time = match end_time {
-        Ok(t) if t == NanoTime::MAX => anyhow::bail!(
-            "{adapter} requires RunFor::Duration; \
-            RunFor::Forever would generate an unbounded number of slices"
-        ),
-        Ok(t) => t,
-        Err(_) => anyhow::bail!(
-            "{adapter} requires RunFor::Duration; \
-            RunFor::Cycles does not provide an end time"
-        ),
-    };
+    let end_time = end_time.unwrap_or_else(NanoTime::now);
     Ok(compute_time_slices(start_time, end_time, period))
 }
 
+#[cfg(any(feature = "kdb", feature = "postgres"))]
+const DAY_NANOS: i64 = 86_400_000_000_000;
+
 #[cfg(any(feature = "kdb", feature = "postgres"))]
 /// Split `[start_time, end_time)` into contiguous half-open slices of length `period`.
 ///
@@ -150,76 +144,87 @@ pub(crate) fn compute_validated_time_slices(
 /// slicing begins at the period boundary that contains `start_time` rather than
 /// at midnight, so a mid-day start does not generate empty leading slices.
 ///
-/// `start_time` must be non-zero (callers validate this) and `end_time` must be a
-/// bounded time (i.e. `RunFor::Duration`, not `Forever`).
+/// The slices are generated lazily, so a long window with a short period costs
+/// no memory up front.  `start_time` must be non-zero (callers validate this).
 pub(crate) fn compute_time_slices(
     start_time: NanoTime,
     end_time: NanoTime,
     period: std::time::Duration,
-) -> Vec<((NanoTime, NanoTime), i32, usize)> {
-    const DAY_NANOS: i64 = 86_400_000_000_000;
+) -> TimeSlices {
     let period_nanos = period.as_nanos() as i64;
-
     let start_kdb = start_time.to_kdb_timestamp();
     let end_kdb = end_time.to_kdb_timestamp();
-
     let start_day = start_kdb.div_euclid(DAY_NANOS);
-    // Subtract 1 before dividing so that an end_time that falls exactly on midnight
-    // does not pull in an extra (empty) day. Note end_kdb can be negative for
-    // pre-2000 windows (KDB epoch is 2000-01-01) — div_euclid keeps day
-    // arithmetic correct there, so don't replace it with plain `/` division.
-    let end_day = (end_kdb - 1).div_euclid(DAY_NANOS);
+    TimeSlices {
+        period_nanos,
+        end_kdb,
+        // Subtract 1 before dividing so that an end_time that falls exactly on midnight
+        // does not pull in an extra (empty) day. Note end_kdb can be negative for
+        // pre-2000 windows (KDB epoch is 2000-01-01) — div_euclid keeps day
+        // arithmetic correct there, so don't replace it with plain `/` division.
+        end_day: (end_kdb - 1).div_euclid(DAY_NANOS),
+        day: start_day,
+        // For the first day, begin at the period boundary that contains start_time
+        // rather than always starting at midnight.
+        iteration: ((start_kdb - start_day * DAY_NANOS) / period_nanos) as usize,
+    }
+}
 
-    let mut result = Vec::new();
+#[cfg(any(feature = "kdb", feature = "postgres"))]
+/// The slices of [`compute_time_slices`]: `((t0, t1), kdb_date, iteration)`.
+#[derive(Debug)]
+pub(crate) struct TimeSlices {
+    period_nanos: i64,
+    end_kdb: i64,
+    end_day: i64,
+    day: i64,
+    iteration: usize,
+}
+
+#[cfg(any(feature = "kdb", feature = "postgres"))]
+impl Iterator for TimeSlices {
+    type Item = ((NanoTime, NanoTime), i32, usize);
 
-    for day in start_day..=end_day {
-        let kdb_date = day as i32;
-        let midnight_kdb = day * DAY_NANOS;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.day > self.end_day {
+            return None;
+        }
+        let midnight_kdb = self.day * DAY_NANOS;
         let next_midnight_kdb = midnight_kdb + DAY_NANOS;
 
-        // For the first day, begin at the period boundary that contains start_time
-        // rather than always starting at midnight.
-        let mut iteration = if day == start_day {
-            ((start_kdb - midnight_kdb) / period_nanos) as usize
+        // Half-open intervals [t0, t1): caller uses `time >= t0, time < t1`.
+        // t0 and t1 are always round multiples of period (or midnight),
+        // so queries contain only clean numbers with no ±1 adjustments.
+        let t0 = midnight_kdb + self.iteration as i64 * self.period_nanos;
+
+        // On the last day, stop once the slice start has reached or passed
+        // end_time — any further slice would be entirely outside the range.
+        if self.day == self.end_day && t0 >= self.end_kdb {
+            self.day += 1;
+            return None;
+        }
+
+        let natural_t1 = t0 + self.period_nanos;
+        // For the final slice of the day, t1 clamps to next midnight (also a round
+        // number: 86400000000000j per day). For non-final slices t1 = t0 + period.
+        let t1 = natural_t1.min(next_midnight_kdb);
+        let slice = (
+            (
+                NanoTime::from_kdb_timestamp(t0),
+                NanoTime::from_kdb_timestamp(t1),
+            ),
+            self.day as i32,
+            self.iteration,
+        );
+
+        if natural_t1 >= next_midnight_kdb {
+            self.day += 1;
+            self.iteration = 0;
         } else {
-            0
-        };
-
-        loop {
-            // Half-open intervals [t0, t1): caller uses `time >= t0, time < t1`.
-            // t0 and t1 are always round multiples of period (or midnight),
-            // so queries contain only clean numbers with no ±1 adjustments.
-            let t0 = midnight_kdb + iteration as i64 * period_nanos;
-
-            // On the last day, stop once the slice start has reached or passed
-            // end_time — any further slice would be entirely outside the range.
-            if day == end_day && t0 >= end_kdb {
-                break;
-            }
-
-            let natural_t1 = t0 + period_nanos;
-            // For the final slice of the day, t1 clamps to next midnight (also a round
-            // number: 86400000000000j per day). For non-final slices t1 = t0 + period.
-            let t1 = natural_t1.min(next_midnight_kdb);
-
-            result.push((
-                (
-                    NanoTime::from_kdb_timestamp(t0),
-                    NanoTime::from_kdb_timestamp(t1),
-                ),
-                kdb_date,
-                iteration,
-            ));
-
-            if natural_t1 >= next_midnight_kdb {
-                break;
-            }
-
-            iteration += 1;
+            self.iteration += 1;
         }
+        Some(slice)
     }
-
-    result
 }
 
 #[cfg(all(test, any(feature = "kdb", feature = "postgres")))]
@@ -241,7 +246,7 @@ mod tests {
         let start = epoch;
         let end = NanoTime::new(u64::from(epoch) + DAY_NANOS - 1);
 
-        let slices = compute_time_slices(start, end, period);
+        let slices = compute_time_slices(start, end, period).collect::<Vec<_>>();
         assert_eq!(slices.len(), 3, "expected 3 slices for 8h period");
 
         for &(_, date, _) in &slices {
@@ -278,7 +283,7 @@ mod tests {
         let start = epoch;
         let end = NanoTime::new(u64::from(epoch) + DAY_NANOS - 1);
 
-        let slices = compute_time_slices(start, end, period);
+        let slices = compute_time_slices(start, end, period).collect::<Vec<_>>();
         assert_eq!(slices.len(), 5, "expected 4 full + 1 stub = 5 slices");
 
         let period_nanos = period.as_nanos() as u64;
@@ -304,7 +309,7 @@ mod tests {
         let start = epoch;
         let end = NanoTime::new(u64::from(epoch) + 2 * DAY_NANOS - 1);
 
-        let slices = compute_time_slices(start, end, period);
+        let slices = compute_time_slices(start, end, period).collect::<Vec<_>>();
         assert_eq!(slices.len(), 4); // 2 slices × 2 days
 
         assert_eq!(slices[0].1, 0);
@@ -333,7 +338,8 @@ mod tests {
         let next_midnight = NanoTime::from_kdb_timestamp(DAY_NANOS);
 
         // --- 60s period: one slice [23:59:00, 00:00:00), iteration 1439 ---
-        let slices = compute_time_slices(start, end, std::time::Duration::from_secs(60));
+        let slices =
+            compute_time_slices(start, end, std::time::Duration::from_secs(60)).collect::<Vec<_>>();
         assert_eq!(slices.len(), 1, "60s: expected 1 slice");
         let (t0, t1) = slices[0].0;
         assert_eq!(
@@ -345,7 +351,8 @@ mod tests {
         assert_eq!(slices[0].2, 1439, "60s: iteration should be 1439");
 
         // --- 30s period: one slice [23:59:30, 00:00:00), iteration 2879 ---
-        let slices = compute_time_slices(start, end, std::time::Duration::from_secs(30));
+        let slices =
+            compute_time_slices(start, end, std::time::Duration::from_secs(30)).collect::<Vec<_>>();
         assert_eq!(slices.len(), 1, "30s: expected 1 slice");
         let (t0, t1) = slices[0].0;
         assert_eq!(t0, start, "30s: t0 should be 23:59:30");
@@ -353,7 +360,8 @@ mod tests {
         assert_eq!(slices[0].2, 2879, "30s: iteration should be 2879");
 
         // --- 10s period: three slices starting at 23:59:30 ---
-        let slices = compute_time_slices(start, end, std::time::Duration::from_secs(10));
+        let slices =
+            compute_time_slices(start, end, std::time::Duration::from_secs(10)).collect::<Vec<_>>();
         assert_eq!(slices.len(), 3, "10s: expected 3 slices");
         assert_eq!(slices[0].0.0, start, "10s: first t0 should be 23:59:30");
         assert_eq!(
@@ -381,7 +389,7 @@ mod tests {
         let period = std::time::Duration::from_secs(8 * 3600); // 3 slices per day
 
         let end = NanoTime::new(u64::from(epoch) + DAY_NANOS);
-        let slices = compute_time_slices(epoch, end, period);
+        let slices = compute_time_slices(epoch, end, period).collect::<Vec<_>>();
         assert_eq!(
             slices.len(),
             3,
@@ -402,7 +410,7 @@ mod tests {
         // 30 minutes into day 1
         let end = NanoTime::new(u64::from(epoch) + DAY_NANOS + 30 * 60 * 1_000_000_000);
 
-        let slices = compute_time_slices(start, end, period);
+        let slices = compute_time_slices(start, end, period).collect::<Vec<_>>();
 
         // 24 full-hour slices on day 0 + 1 slice [00:00,01:00) on day 1
         assert_eq!(slices.len(), 25, "expected 25 slices");
@@ -431,7 +439,7 @@ mod tests {
         let start = NanoTime::new(u64::from(epoch) + 23 * HOUR_NANOS);
         let end = NanoTime::new(u64::from(epoch) + DAY_NANOS + 30 * 60 * 1_000_000_000);
 
-        let slices = compute_time_slices(start, end, period);
+        let slices = compute_time_slices(start, end, period).collect::<Vec<_>>();
 
         assert_eq!(slices.len(), 2, "expected 2 slices");
 
@@ -456,7 +464,7 @@ mod tests {
         let err = compute_validated_time_slices(
             "test_adapter",
             kdb_epoch(),
-            Ok(NanoTime::new(u64::from(kdb_epoch()) + DAY_NANOS)),
+            Some(NanoTime::new(u64::from(kdb_epoch()) + DAY_NANOS)),
             std::time::Duration::ZERO,
         )
         .unwrap_err();
@@ -469,7 +477,7 @@ mod tests {
         let err = compute_validated_time_slices(
             "test_adapter",
             NanoTime::ZERO,
-            Ok(NanoTime::new(DAY_NANOS)),
+            Some(NanoTime::new(DAY_NANOS)),
             std::time::Duration::from_secs(3600),
         )
         .unwrap_err();
@@ -477,26 +485,21 @@ mod tests {
     }
 
     #[test]
-    fn test_validated_rejects_forever_and_cycles() {
-        // RunFor::Forever arrives as Ok(NanoTime::MAX).
-        let err = compute_validated_time_slices(
-            "test_adapter",
-            kdb_epoch(),
-            Ok(NanoTime::MAX),
-            std::time::Duration::from_secs(3600),
-        )
-        .unwrap_err();
-        assert!(err.to_string().contains("RunFor::Forever"));
-
-        // RunFor::Cycles arrives as Err.
-        let err = compute_validated_time_slices(
+    fn test_validated_unbounded_end_runs_to_now() {
+        // RunFor::Forever, Cycles and Condition arrive as None.
+        let day_ago = NanoTime::now() - NanoTime::new(DAY_NANOS);
+        let slices = compute_validated_time_slices(
             "test_adapter",
-            kdb_epoch(),
-            Err(anyhow::anyhow!("end_time not available for RunFor::Cycles")),
+            day_ago,
+            None,
             std::time::Duration::from_secs(3600),
         )
-        .unwrap_err();
-        assert!(err.to_string().contains("RunFor::Cycles"));
+        .unwrap()
+        .collect::<Vec<_>>();
+        // 24 hours from an unaligned start, with a midnight in between.
+        assert!((25..=26).contains(&slices.len()), "{}", slices.len());
+        let last_end = slices.last().unwrap().0.1;
+        assert!(last_end > NanoTime::now() - NanoTime::new(3_600_000_000_000));
     }
 
     #[test]
@@ -504,11 +507,11 @@ mod tests {
         let slices = compute_validated_time_slices(
             "test_adapter",
             kdb_epoch(),
-            Ok(NanoTime::new(u64::from(kdb_epoch()) + DAY_NANOS)),
+            Some(NanoTime::new(u64::from(kdb_epoch()) + DAY_NANOS)),
             std::time::Duration::from_secs(8 * 3600),
         )
         .unwrap();
-        assert_eq!(slices.len(), 3);
+        assert_eq!(slices.count(), 3);
     }
 
     /// end_time exactly on a period boundary, one period into day 1.
@@ -521,7 +524,7 @@ mod tests {
         // end exactly at 02:00 on day 1 (on a period boundary)
         let end = NanoTime::new(u64::from(epoch) + DAY_NANOS + 2 * HOUR_NANOS);
 
-        let slices = compute_time_slices(start, end, period);
+        let slices = compute_time_slices(start, end, period).collect::<Vec<_>>();
 
         // 12 slices on day 0 + 1 slice [00:00, 02:00) on day 1
         assert_eq!(slices.len(), 13, "expected 13 slices");
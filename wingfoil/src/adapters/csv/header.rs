@@ -0,0 +1,801 @@
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::ser::{self, Impossible, SerializeTuple};
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+/// The csv columns of a record type, traced from its
+/// [`Deserialize`](serde::Deserialize) impl rather than from any one record,
+/// so an `Option` of a nested struct spans all of the struct's columns even
+/// when it is `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Shape {
+    /// A single column.
+    Column,
+    /// An `Option`.  `None` is written as an empty field in every column of
+    /// the inner shape.
+    Option(Box<Shape>),
+    /// Struct fields, or unnamed tuple elements, in column order.
+    Fields(Vec<(Option<&'static str>, Shape)>),
+}
+
+impl Shape {
+    /// Traces the columns of `T`.  `None` for types whose columns depend on
+    /// the value: sequences, maps, enums with data and self-describing types
+    /// such as `serde_json::Value`.  Enums are one column, on the assumption
+    /// that all their variants are unit variants.
+    pub(super) fn of<T: DeserializeOwned>() -> Option<Shape> {
+        let mut shape = Shape::Column;
+        T::deserialize(Tracer { shape: &mut shape }).ok()?;
+        Some(shape)
+    }
+
+    fn width(&self) -> usize {
+        match self {
+            Shape::Column => 1,
+            Shape::Option(inner) => inner.width(),
+            Shape::Fields(fields) => fields.iter().map(|(_, shape)| shape.width()).sum(),
+        }
+    }
+
+    fn collect_header(&self, collector: &mut HeaderCollector) {
+        match self {
+            Shape::Column => collector.names.push(collector.label.to_string()),
+            Shape::Option(inner) => inner.collect_header(collector),
+            Shape::Fields(fields) => {
+                for (name, shape) in fields {
+                    let outer = collector.label;
+                    if let Some(name) = name {
+                        collector.label = name;
+                        collector.named = true;
+                    }
+                    shape.collect_header(collector);
+                    collector.label = outer;
+                }
+            }
+        }
+    }
+}
+
+/// Derives the csv header row for `record`.
+///
+/// Each leaf column is named after the innermost struct field that contains
+/// it, so nested structs flatten to their field names in the same order the
+/// csv serializer writes them.  Columns outside any named struct field are
+/// labelled `value`.  Returns `None` when the record has no named fields
+/// (scalars, tuples) so such streams keep writing headerless files.
+///
+/// The columns come from `shape` when the record type could be traced, and
+/// otherwise from walking `record` itself, where a `None` of a nested struct
+/// names a single column.
+pub(super) fn header_for<T: Serialize + ?Sized>(
+    shape: Option<&Shape>,
+    record: &T,
+) -> anyhow::Result<Option<Vec<String>>> {
+    let collector = collect_header(shape, record)?;
+    Ok(collector.named.then_some(collector.names))
+}
+
+/// As [header_for], but also names the columns of scalars and tuples, each
+/// `value`.
+pub(super) fn column_names_for<T: Serialize + ?Sized>(
+    shape: Option<&Shape>,
+    record: &T,
+) -> anyhow::Result<Vec<String>> {
+    Ok(collect_header(shape, record)?.names)
+}
+
+fn collect_header<T: Serialize + ?Sized>(
+    shape: Option<&Shape>,
+    record: &T,
+) -> anyhow::Result<HeaderCollector> {
+    let mut collector = HeaderCollector {
+        names: Vec::new(),
+        label: "value",
+        named: false,
+    };
+    match shape {
+        Some(shape) => shape.collect_header(&mut collector),
+        None => record
+            .serialize(&mut collector)
+            .map_err(|e| anyhow::anyhow!("Failed to derive CSV header: {e}"))?,
+    }
+    Ok(collector)
+}
+
+struct HeaderCollector {
+    names: Vec<String>,
+    label: &'static str,
+    named: bool,
+}
+
+impl HeaderCollector {
+    fn leaf(&mut self) -> Result<(), HeaderError> {
+        self.names.push(self.label.to_string());
+        Ok(())
+    }
+
+    fn field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), HeaderError> {
+        let outer = std::mem::replace(&mut self.label, key);
+        self.named = true;
+        value.serialize(&mut *self)?;
+        self.label = outer;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct HeaderError(String);
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for HeaderError {}
+
+impl ser::Error for HeaderError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+impl de::Error for HeaderError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+macro_rules! leaf {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(fn $method(self, _: $ty) -> Result<(), HeaderError> { self.leaf() })*
+    };
+}
+
+impl ser::Serializer for &mut HeaderCollector {
+    type Ok = ();
+    type Error = HeaderError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Impossible<(), HeaderError>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    leaf!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_unit_struct(&'static str),
+    );
+
+    fn serialize_none(self) -> Result<(), HeaderError> {
+        self.leaf()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), HeaderError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), HeaderError> {
+        self.leaf()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+    ) -> Result<(), HeaderError> {
+        self.leaf()
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), HeaderError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), HeaderError> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self, HeaderError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self, HeaderError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self, HeaderError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, HeaderError> {
+        Ok(self)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, HeaderError> {
+        Err(ser::Error::custom("maps cannot be written as CSV columns"))
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, HeaderError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, HeaderError> {
+        Ok(self)
+    }
+}
+
+macro_rules! positional {
+    ($($trait:ident::$method:ident),* $(,)?) => {
+        $(impl ser::$trait for &mut HeaderCollector {
+            type Ok = ();
+            type Error = HeaderError;
+
+            fn $method<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), HeaderError> {
+                value.serialize(&mut **self)
+            }
+
+            fn end(self) -> Result<(), HeaderError> {
+                Ok(())
+            }
+        })*
+    };
+}
+
+positional!(
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field,
+    SerializeTupleVariant::serialize_field,
+);
+
+macro_rules! named {
+    ($($trait:ident),* $(,)?) => {
+        $(impl ser::$trait for &mut HeaderCollector {
+            type Ok = ();
+            type Error = HeaderError;
+
+            fn serialize_field<T: Serialize + ?Sized>(
+                &mut self,
+                key: &'static str,
+                value: &T,
+            ) -> Result<(), HeaderError> {
+                self.field(key, value)
+            }
+
+            fn end(self) -> Result<(), HeaderError> {
+                Ok(())
+            }
+        })*
+    };
+}
+
+named!(SerializeStruct, SerializeStructVariant);
+
+/// Deserializes a placeholder value of the traced type, recording its
+/// [Shape] as it goes.
+struct Tracer<'a> {
+    shape: &'a mut Shape,
+}
+
+impl Tracer<'_> {
+    fn fields<'de, V: Visitor<'de>>(
+        self,
+        names: Vec<Option<&'static str>>,
+        visitor: V,
+    ) -> Result<V::Value, HeaderError> {
+        let mut fields = Vec::with_capacity(names.len());
+        let value = visitor.visit_seq(FieldTracer {
+            names: names.into_iter(),
+            fields: &mut fields,
+        })?;
+        *self.shape = Shape::Fields(fields);
+        Ok(value)
+    }
+}
+
+fn untraceable(what: &str) -> HeaderError {
+    de::Error::custom(format!("{what} have no fixed CSV columns"))
+}
+
+macro_rules! column {
+    ($($method:ident => $visit:ident($default:expr)),* $(,)?) => {
+        $(fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, HeaderError> {
+            visitor.$visit($default)
+        })*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Tracer<'_> {
+    type Error = HeaderError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, HeaderError> {
+        Err(untraceable("self-describing types"))
+    }
+
+    column!(
+        deserialize_bool => visit_bool(false),
+        deserialize_i8 => visit_i8(0),
+        deserialize_i16 => visit_i16(0),
+        deserialize_i32 => visit_i32(0),
+        deserialize_i64 => visit_i64(0),
+        deserialize_i128 => visit_i128(0),
+        deserialize_u8 => visit_u8(0),
+        deserialize_u16 => visit_u16(0),
+        deserialize_u32 => visit_u32(0),
+        deserialize_u64 => visit_u64(0),
+        deserialize_u128 => visit_u128(0),
+        deserialize_f32 => visit_f32(0.0),
+        deserialize_f64 => visit_f64(0.0),
+        deserialize_char => visit_char('\0'),
+        deserialize_str => visit_str(""),
+        deserialize_string => visit_str(""),
+        deserialize_bytes => visit_bytes(&[]),
+        deserialize_byte_buf => visit_bytes(&[]),
+        deserialize_identifier => visit_str(""),
+    );
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, HeaderError> {
+        let mut inner = Shape::Column;
+        let value = visitor.visit_some(Tracer { shape: &mut inner })?;
+        *self.shape = Shape::Option(Box::new(inner));
+        Ok(value)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, HeaderError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, HeaderError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, HeaderError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, _: V) -> Result<V::Value, HeaderError> {
+        Err(untraceable("sequences"))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, HeaderError> {
+        self.fields(vec![None; len], visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, HeaderError> {
+        self.fields(vec![None; len], visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, _: V) -> Result<V::Value, HeaderError> {
+        Err(untraceable("maps"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, HeaderError> {
+        self.fields(fields.iter().copied().map(Some).collect(), visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, HeaderError> {
+        let variant = variants.first().ok_or_else(|| untraceable("empty enums"))?;
+        visitor.visit_enum(UnitVariant(variant))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, HeaderError> {
+        visitor.visit_unit()
+    }
+}
+
+struct FieldTracer<'a> {
+    names: std::vec::IntoIter<Option<&'static str>>,
+    fields: &'a mut Vec<(Option<&'static str>, Shape)>,
+}
+
+impl<'de> de::SeqAccess<'de> for FieldTracer<'_> {
+    type Error = HeaderError;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, HeaderError> {
+        let Some(name) = self.names.next() else {
+            return Ok(None);
+        };
+        let mut shape = Shape::Column;
+        let value = seed.deserialize(Tracer { shape: &mut shape })?;
+        self.fields.push((name, shape));
+        Ok(Some(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.names.len())
+    }
+}
+
+/// Picks an enum's first variant, which must be a unit variant.
+struct UnitVariant(&'static str);
+
+impl<'de> de::EnumAccess<'de> for UnitVariant {
+    type Error = HeaderError;
+    type Variant = Self;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<(S::Value, Self), HeaderError> {
+        let name: de::value::StrDeserializer<HeaderError> = self.0.into_deserializer();
+        let variant = seed.deserialize(name)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for UnitVariant {
+    type Error = HeaderError;
+
+    fn unit_variant(self) -> Result<(), HeaderError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(self, _: S) -> Result<S::Value, HeaderError> {
+        Err(untraceable("enums with data"))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _: usize, _: V) -> Result<V::Value, HeaderError> {
+        Err(untraceable("enums with data"))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _: &'static [&'static str],
+        _: V,
+    ) -> Result<V::Value, HeaderError> {
+        Err(untraceable("enums with data"))
+    }
+}
+
+/// Serializes `value` as it would be on its own, except that a `None` whose
+/// [Shape] spans several columns is written as one empty field per column,
+/// keeping the row aligned with the header.  With no shape it serializes
+/// `value` unchanged.
+pub(super) struct Padded<'a, T: ?Sized> {
+    pub value: &'a T,
+    pub shape: Option<&'a Shape>,
+}
+
+impl<T: Serialize + ?Sized> Serialize for Padded<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(Padder {
+            inner: serializer,
+            shape: self.shape,
+        })
+    }
+}
+
+struct Padder<'a, S> {
+    inner: S,
+    shape: Option<&'a Shape>,
+}
+
+macro_rules! forward {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(fn $method(self, v: $ty) -> Result<S::Ok, S::Error> {
+            self.inner.$method(v)
+        })*
+    };
+}
+
+impl<'a, S: Serializer> Serializer for Padder<'a, S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = Compound<'a, S::SerializeSeq>;
+    type SerializeTuple = Compound<'a, S::SerializeTuple>;
+    type SerializeTupleStruct = Compound<'a, S::SerializeTupleStruct>;
+    type SerializeTupleVariant = Compound<'a, S::SerializeTupleVariant>;
+    type SerializeMap = Compound<'a, S::SerializeMap>;
+    type SerializeStruct = Compound<'a, S::SerializeStruct>;
+    type SerializeStructVariant = Compound<'a, S::SerializeStructVariant>;
+
+    forward!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_unit_struct(&'static str),
+    );
+
+    fn serialize_none(self) -> Result<S::Ok, S::Error> {
+        match self.shape {
+            Some(Shape::Option(inner)) if inner.width() != 1 => {
+                let width = inner.width();
+                let mut fields = self.inner.serialize_tuple(width)?;
+                for _ in 0..width {
+                    fields.serialize_element(&None::<()>)?;
+                }
+                fields.end()
+            }
+            _ => self.inner.serialize_none(),
+        }
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<S::Ok, S::Error> {
+        let shape = match self.shape {
+            Some(Shape::Option(inner)) => Some(&**inner),
+            _ => None,
+        };
+        self.inner.serialize_some(&Padded { value, shape })
+    }
+
+    fn serialize_unit(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit_variant(name, index, variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        let shape = self.shape;
+        self.inner
+            .serialize_newtype_struct(name, &Padded { value, shape })
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner
+            .serialize_newtype_variant(name, index, variant, value)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, S::Error> {
+        Ok(Compound::new(self.inner.serialize_seq(len)?, None))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, S::Error> {
+        Ok(Compound::new(self.inner.serialize_tuple(len)?, self.shape))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, S::Error> {
+        let inner = self.inner.serialize_tuple_struct(name, len)?;
+        Ok(Compound::new(inner, self.shape))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, S::Error> {
+        let inner = self
+            .inner
+            .serialize_tuple_variant(name, index, variant, len)?;
+        Ok(Compound::new(inner, None))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, S::Error> {
+        Ok(Compound::new(self.inner.serialize_map(len)?, None))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, S::Error> {
+        let inner = self.inner.serialize_struct(name, len)?;
+        Ok(Compound::new(inner, self.shape))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, S::Error> {
+        let inner = self
+            .inner
+            .serialize_struct_variant(name, index, variant, len)?;
+        Ok(Compound::new(inner, None))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// A struct, tuple or sequence being serialized through a [Padder], handing
+/// each element the shape of its field.
+struct Compound<'a, C> {
+    inner: C,
+    fields: &'a [(Option<&'static str>, Shape)],
+    index: usize,
+}
+
+impl<'a, C> Compound<'a, C> {
+    fn new(inner: C, shape: Option<&'a Shape>) -> Self {
+        let fields = match shape {
+            Some(Shape::Fields(fields)) => fields.as_slice(),
+            _ => &[],
+        };
+        Self {
+            inner,
+            fields,
+            index: 0,
+        }
+    }
+
+    fn next_shape(&mut self) -> Option<&'a Shape> {
+        let shape = self.fields.get(self.index).map(|(_, shape)| shape);
+        self.index += 1;
+        shape
+    }
+
+    fn field_shape(&self, key: &'static str) -> Option<&'a Shape> {
+        self.fields
+            .iter()
+            .find(|(name, _)| *name == Some(key))
+            .map(|(_, shape)| shape)
+    }
+}
+
+macro_rules! padded_positional {
+    ($($trait:ident::$method:ident),* $(,)?) => {
+        $(impl<C: ser::$trait> ser::$trait for Compound<'_, C> {
+            type Ok = C::Ok;
+            type Error = C::Error;
+
+            fn $method<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+                let shape = self.next_shape();
+                self.inner.$method(&Padded { value, shape })
+            }
+
+            fn end(self) -> Result<C::Ok, C::Error> {
+                self.inner.end()
+            }
+        })*
+    };
+}
+
+padded_positional!(
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field,
+    SerializeTupleVariant::serialize_field,
+);
+
+macro_rules! padded_named {
+    ($($trait:ident),* $(,)?) => {
+        $(impl<C: ser::$trait> ser::$trait for Compound<'_, C> {
+            type Ok = C::Ok;
+            type Error = C::Error;
+
+            fn serialize_field<T: Serialize + ?Sized>(
+                &mut self,
+                key: &'static str,
+                value: &T,
+            ) -> Result<(), C::Error> {
+                let shape = self.field_shape(key);
+                self.inner.serialize_field(key, &Padded { value, shape })
+            }
+
+            fn end(self) -> Result<C::Ok, C::Error> {
+                self.inner.end()
+            }
+        })*
+    };
+}
+
+padded_named!(SerializeStruct, SerializeStructVariant);
+
+impl<C: ser::SerializeMap> ser::SerializeMap for Compound<'_, C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), C::Error> {
+        self.inner.serialize_key(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.inner.serialize_value(value)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
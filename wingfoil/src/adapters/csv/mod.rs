@@ -1,9 +1,13 @@
 //! CSV adapter — read and write comma-separated values files.
 //!
-//! Provides one read function and a fluent write operator:
+//! Provides read functions and a fluent write operator:
 //!
 //! - [`csv_read`] — producer that emits each tick's records as a [`Burst<T>`]
+//! - [`csv_read_with_time`] — as `csv_read`, with times parsed from columns per a [`TimeSpec`]
+//! - [`csv_read_files`] — as `csv_read`, over several files played back in sequence
 //! - [`CsvOperators::csv_write`] — consumer that writes a `Burst<T>` stream to a CSV file
+//! - [`CsvOperators::csv_write_partitioned`] / [`CsvOperators::ndjson_write_partitioned`] — one file per key, e.g. per symbol
+//! - [`CsvResultsOperators::write_results_csv`] — writes [`collect`](crate::nodes::StreamOperators::collect)ed results as the graph stops
 //!
 //! Record types must implement [`serde::Serialize`] and [`serde::de::DeserializeOwned`].
 //!
@@ -41,8 +45,13 @@
 //!     .unwrap();
 //! ```
 
+mod header;
+mod partitioned;
 mod read;
+mod time_spec;
 mod write;
 
+pub use partitioned::{DEFAULT_MAX_OPEN_FILES, PartitionFormat, PartitionedWriterNode};
 pub use read::*;
+pub use time_spec::*;
 pub use write::*;
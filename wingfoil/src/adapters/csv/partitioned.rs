@@ -0,0 +1,309 @@
+use super::header::Shape;
+use super::write::{write_header, write_row};
+use serde::{Serialize, de::DeserializeOwned};
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use crate::queue::ValueAt;
+use crate::types::*;
+
+/// Files a partitioned writer keeps open at once unless told otherwise.
+pub const DEFAULT_MAX_OPEN_FILES: usize = 256;
+
+/// File format of a partitioned writer.  Both lead each record with its tick
+/// time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionFormat {
+    /// `<key>.csv` with a `time` column, as written by
+    /// [csv_write](super::CsvOperators::csv_write).
+    Csv,
+    /// `<key>.ndjson`, one [ValueAt](crate::queue::ValueAt) object,
+    /// `{"value":..,"time":..}`, per line.
+    Ndjson,
+}
+
+impl PartitionFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            PartitionFormat::Csv => "csv",
+            PartitionFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+enum PartitionFile {
+    Csv(Box<csv::Writer<File>>),
+    Ndjson(BufWriter<File>),
+}
+
+impl PartitionFile {
+    fn write<T: Serialize>(
+        &mut self,
+        shape: Option<&Shape>,
+        time: NanoTime,
+        value: &T,
+    ) -> anyhow::Result<()> {
+        match self {
+            PartitionFile::Csv(writer) => write_row(writer, shape, time, value),
+            PartitionFile::Ndjson(writer) => {
+                serde_json::to_writer(&mut *writer, &ValueAt::new(value, time))?;
+                writer.write_all(b"\n")?;
+                Ok(())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> anyhow::Result<()> {
+        match self {
+            PartitionFile::Csv(writer) => writer.flush()?,
+            PartitionFile::Ndjson(writer) => writer.flush()?,
+        }
+        Ok(())
+    }
+}
+
+/// Maps a key to a file stem that is safe on any platform: anything other
+/// than ascii alphanumerics, `-`, `_` and `.` becomes `_`.  Keys that
+/// sanitize alike share a file.
+fn sanitize(key: &str) -> String {
+    let stem: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if stem.is_empty() || stem.chars().all(|c| c == '.') {
+        "_".repeat(stem.len().max(1))
+    } else {
+        stem
+    }
+}
+
+struct OpenFile {
+    file: PartitionFile,
+    last_used: u64,
+}
+
+/// Writes each element of a [`Burst<T>`] stream to the file for its key
+/// under `dir`.  Files are opened on first use and, beyond `max_open_files`,
+/// the least recently written is closed and reopened in append mode when its
+/// key next appears.  Used by
+/// [`CsvOperators::csv_write_partitioned`](super::CsvOperators::csv_write_partitioned).
+pub struct PartitionedWriterNode<T: Element> {
+    upstream: Rc<dyn Stream<Burst<T>>>,
+    dir: PathBuf,
+    format: PartitionFormat,
+    max_open_files: usize,
+    key_fn: Box<dyn Fn(&T) -> String>,
+    open: HashMap<String, OpenFile>,
+    /// Stems whose file has been created by this run, and so is appended to.
+    created: HashSet<String>,
+    uses: u64,
+    shape: Option<Shape>,
+}
+
+impl<T: Element> PartitionedWriterNode<T> {
+    pub fn new(
+        upstream: Rc<dyn Stream<Burst<T>>>,
+        dir: &str,
+        format: PartitionFormat,
+        max_open_files: usize,
+        key_fn: impl Fn(&T) -> String + 'static,
+    ) -> Self {
+        assert!(max_open_files > 0, "max_open_files must be non-zero");
+        Self {
+            upstream,
+            dir: PathBuf::from(dir),
+            format,
+            max_open_files,
+            key_fn: Box::new(key_fn),
+            open: HashMap::new(),
+            created: HashSet::new(),
+            uses: 0,
+            shape: None,
+        }
+    }
+}
+
+impl<T: Element + Serialize + DeserializeOwned> PartitionedWriterNode<T> {
+    fn write(&mut self, time: NanoTime, rec: &T) -> anyhow::Result<()> {
+        let stem = sanitize(&(self.key_fn)(rec));
+        self.uses += 1;
+        if !self.open.contains_key(&stem) {
+            if self.open.len() >= self.max_open_files {
+                self.close_least_recent()?;
+            }
+            let file = self.open_file(&stem, rec)?;
+            self.open
+                .insert(stem.clone(), OpenFile { file, last_used: 0 });
+        }
+        let open = self.open.get_mut(&stem).expect("opened above");
+        open.last_used = self.uses;
+        open.file.write(self.shape.as_ref(), time, rec)
+    }
+
+    fn open_file(&mut self, stem: &str, rec: &T) -> anyhow::Result<PartitionFile> {
+        let path = self.dir.join(format!("{stem}.{}", self.format.extension()));
+        let append = !self.created.insert(stem.to_string());
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .append(append)
+            .truncate(!append)
+            .open(&path)
+            .map_err(|e| anyhow::anyhow!("failed to open {} for writing: {e}", path.display()))?;
+        Ok(match self.format {
+            PartitionFormat::Csv => {
+                let mut writer = csv::WriterBuilder::new()
+                    .has_headers(false)
+                    .from_writer(file);
+                if !append {
+                    write_header(&mut writer, self.shape.as_ref(), rec)?;
+                }
+                PartitionFile::Csv(Box::new(writer))
+            }
+            PartitionFormat::Ndjson => PartitionFile::Ndjson(BufWriter::new(file)),
+        })
+    }
+
+    fn close_least_recent(&mut self) -> anyhow::Result<()> {
+        let stem = self
+            .open
+            .iter()
+            .min_by_key(|(_, open)| open.last_used)
+            .map(|(stem, _)| stem.clone());
+        if let Some(mut open) = stem.and_then(|stem| self.open.remove(&stem)) {
+            open.file.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[node(active = [upstream])]
+impl<T: Element + Serialize + DeserializeOwned> MutableNode for PartitionedWriterNode<T> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        for rec in self.upstream.peek_value().iter() {
+            self.write(state.time(), rec)?;
+        }
+        Ok(false)
+    }
+
+    fn start(&mut self, _state: &mut GraphState) -> anyhow::Result<()> {
+        self.shape = Shape::of::<T>();
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| anyhow::anyhow!("failed to create {}: {e}", self.dir.display()))
+    }
+
+    fn stop(&mut self, _state: &mut GraphState) -> anyhow::Result<()> {
+        for (_, mut open) in self.open.drain() {
+            open.file.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::csv::*;
+    use crate::graph::*;
+    use crate::nodes::*;
+    use crate::queue::ValueAt;
+    use serde::Deserialize;
+    use std::cell::RefCell;
+
+    #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+    struct Trade {
+        symbol: String,
+        price: u32,
+    }
+
+    fn trades() -> Rc<dyn Stream<Trade>> {
+        let src = Rc::new(RefCell::new(CallBackStream::new()));
+        let symbols = ["AAPL", "MSFT", "BRK/B"];
+        for i in 0..9u32 {
+            let trade = Trade {
+                symbol: symbols[i as usize % 3].to_string(),
+                price: 100 + i,
+            };
+            src.borrow_mut()
+                .push(ValueAt::new(trade, NanoTime::new(10 * (i as u64 + 1))));
+        }
+        src.as_stream()
+    }
+
+    fn write_and_read(name: &str, format: PartitionFormat, max_open_files: usize) -> Vec<String> {
+        let dir = std::env::temp_dir().join(format!(
+            "wingfoil_partitioned_{name}_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        trades()
+            .write_partitioned(
+                dir.to_str().unwrap(),
+                format,
+                max_open_files,
+                |t: &Trade| t.symbol.clone(),
+            )
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        let mut files: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        files.sort();
+        let contents = files
+            .iter()
+            .map(|path| {
+                let name = path.file_name().unwrap().to_str().unwrap();
+                format!("{name}\n{}", std::fs::read_to_string(path).unwrap())
+            })
+            .collect();
+        std::fs::remove_dir_all(&dir).unwrap();
+        contents
+    }
+
+    #[test]
+    fn csv_write_partitioned_writes_a_file_per_key() {
+        let expected = vec![
+            "AAPL.csv\ntime,symbol,price\n10,AAPL,100\n40,AAPL,103\n70,AAPL,106\n",
+            "BRK_B.csv\ntime,symbol,price\n30,BRK/B,102\n60,BRK/B,105\n90,BRK/B,108\n",
+            "MSFT.csv\ntime,symbol,price\n20,MSFT,101\n50,MSFT,104\n80,MSFT,107\n",
+        ];
+        assert_eq!(
+            write_and_read("csv", PartitionFormat::Csv, DEFAULT_MAX_OPEN_FILES),
+            expected
+        );
+        // every write evicts a file, which is then reopened for append
+        assert_eq!(write_and_read("csv_lru", PartitionFormat::Csv, 1), expected);
+    }
+
+    #[test]
+    fn ndjson_write_partitioned_with_one_open_file() {
+        let written = write_and_read("ndjson", PartitionFormat::Ndjson, 1);
+        assert_eq!(written.len(), 3);
+        assert_eq!(
+            written[0],
+            "AAPL.ndjson\n\
+             {\"value\":{\"symbol\":\"AAPL\",\"price\":100},\"time\":10}\n\
+             {\"value\":{\"symbol\":\"AAPL\",\"price\":103},\"time\":40}\n\
+             {\"value\":{\"symbol\":\"AAPL\",\"price\":106},\"time\":70}\n"
+        );
+    }
+
+    #[test]
+    fn sanitize_keeps_keys_inside_the_directory() {
+        assert_eq!(sanitize("BRK/B"), "BRK_B");
+        assert_eq!(sanitize("../etc"), ".._etc");
+        assert_eq!(sanitize(".."), "__");
+        assert_eq!(sanitize(""), "_");
+        assert_eq!(sanitize("ES-Z5.CME"), "ES-Z5.CME");
+    }
+}
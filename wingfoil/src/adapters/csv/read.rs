@@ -3,7 +3,8 @@ use serde::de::DeserializeOwned;
 use std::fs::File;
 use std::rc::Rc;
 
-use crate::nodes::TryIteratorStream;
+use super::TimeSpec;
+use crate::nodes::{TryIteratorStream, chain_streams, merge_ordered};
 use crate::queue::ValueAt;
 use crate::types::*;
 
@@ -29,6 +30,41 @@ where
     })))
 }
 
+fn csv_iterator_with_time<T>(
+    path: &str,
+    time_spec: &TimeSpec,
+    has_headers: bool,
+) -> anyhow::Result<Box<dyn Iterator<Item = anyhow::Result<ValueAt<T>>>>>
+where
+    T: Element + DeserializeOwned + 'static,
+{
+    let file = File::open(path).with_context(|| format!("csv_read: failed to open {path}"))?;
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(has_headers)
+        .from_reader(file);
+    let headers = if has_headers {
+        let headers = reader
+            .headers()
+            .with_context(|| format!("csv_read: failed to read headers from {path}"))?;
+        Some(headers.clone())
+    } else {
+        None
+    };
+    let parser = time_spec.parser(headers.as_ref())?;
+    let path_owned = path.to_owned();
+    Ok(Box::new(reader.into_records().map(move |record| {
+        let record =
+            record.with_context(|| format!("csv_read: failed to read row from {path_owned}"))?;
+        let time = parser
+            .parse(&record)
+            .with_context(|| format!("csv_read: bad time in row from {path_owned}"))?;
+        let value = record
+            .deserialize(headers.as_ref())
+            .with_context(|| format!("csv_read: failed to deserialize row from {path_owned}"))?;
+        Ok(ValueAt { value, time })
+    })))
+}
+
 /// Returns a stream that emits records from a CSV file as a [`Burst<T>`] per tick.
 /// Multiple rows sharing the same timestamp are grouped into a single burst.
 /// Use [`.collapse()`](crate::StreamOperators::collapse) when the source is
@@ -50,6 +86,79 @@ where
     Ok(TryIteratorStream::new(csv_iterator(path, get_time_func, has_headers)?).into_stream())
 }
 
+/// Like [`csv_read`], but takes each row's time from its columns as
+/// described by a [`TimeSpec`], rather than from a closure over the record.
+/// ```ignore
+/// use wingfoil::adapters::csv::*;
+///
+/// let spec = TimeSpec::column("timestamp", TimeFormat::Rfc3339);
+/// let trades = csv_read_with_time::<Trade>("trades.csv", spec, true)?;
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened or a named column is
+/// missing.  A row whose time fails to parse, like one that fails to
+/// deserialize, is surfaced to graph execution when the stream reaches it.
+pub fn csv_read_with_time<T>(
+    path: &str,
+    time_spec: TimeSpec,
+    has_headers: bool,
+) -> anyhow::Result<Rc<dyn Stream<Burst<T>>>>
+where
+    T: Element + DeserializeOwned + 'static,
+{
+    let rows = csv_iterator_with_time(path, &time_spec, has_headers)?;
+    Ok(TryIteratorStream::new(rows).into_stream())
+}
+
+/// Reads several CSV files in sequence, e.g. one per day, as a single
+/// [`Burst<T>`] stream.  Each file is read as by [`csv_read`] and the files
+/// are played back with [`chain_streams`](crate::nodes::chain_streams), so
+/// each must start after the previous one ends.
+///
+/// # Errors
+///
+/// Returns an error if any file cannot be opened.
+pub fn csv_read_files<T>(
+    paths: &[&str],
+    get_time_func: impl Fn(&T) -> NanoTime + Clone + 'static,
+    has_headers: bool,
+) -> anyhow::Result<Rc<dyn Stream<Burst<T>>>>
+where
+    T: Element + DeserializeOwned + 'static,
+{
+    let streams = paths
+        .iter()
+        .map(|path| csv_read(path, get_time_func.clone(), has_headers))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(chain_streams(streams))
+}
+
+/// Reads several CSV files covering the same period, e.g. one per
+/// instrument, as a single time-ordered [`Burst<T>`] stream.  Each file must
+/// be ascending in time; rows from different files sharing a timestamp are
+/// emitted in one burst, in `paths` order.  See
+/// [`merge_ordered`](crate::nodes::merge_ordered).
+///
+/// # Errors
+///
+/// Returns an error if any file cannot be opened.
+pub fn csv_read_merged<T>(
+    paths: &[&str],
+    get_time_func: impl Fn(&T) -> NanoTime + Clone + 'static,
+    has_headers: bool,
+) -> anyhow::Result<Rc<dyn Stream<Burst<T>>>>
+where
+    T: Element + DeserializeOwned + 'static,
+{
+    let sources = paths
+        .iter()
+        .map(|path| csv_iterator(path, get_time_func.clone(), has_headers))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(merge_ordered(sources))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -148,4 +257,59 @@ mod tests {
         assert!(burst_1003.is_some());
         assert_eq!(burst_1003.unwrap().value.len(), 2);
     }
+
+    #[test]
+    fn csv_read_files_reads_files_in_sequence() {
+        // read_test.csv covers 1001..=1006, read_test_next.csv 2001..=2003
+        let stream = csv_read_files(
+            &[
+                "src/adapters/csv/test_data/read_test.csv",
+                "src/adapters/csv/test_data/read_test_next.csv",
+            ],
+            get_time,
+            false,
+        )
+        .unwrap();
+        let collected = stream.collect();
+        collected
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        let ticks = collected.peek_value();
+        assert_eq!(ticks.len(), 9);
+        assert!(ticks.windows(2).all(|w| w[0].time < w[1].time));
+        let all: Vec<u32> = ticks
+            .iter()
+            .flat_map(|b| b.value.iter().map(|r| r.1))
+            .collect();
+        assert_eq!(all, vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn csv_read_merged_interleaves_files_in_time_order() {
+        // part_i.csv holds 3 + i rows at 1000 + i + 10k, so the files
+        // interleave row by row and run out at different times.
+        let paths: Vec<String> = (0..10)
+            .map(|i| format!("src/adapters/csv/test_data/merge/part_{i}.csv"))
+            .collect();
+        let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+        let collected = csv_read_merged(&paths, get_time, false).unwrap().collect();
+        collected
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        let rows: Vec<Record> = collected
+            .peek_value()
+            .into_iter()
+            .flat_map(|burst| {
+                assert!(burst.value.iter().all(|r| r.0 == burst.time));
+                burst.value
+            })
+            .collect();
+        assert!(rows.windows(2).all(|w| w[0].0 < w[1].0));
+        let mut expected: Vec<u32> = (0..10u32)
+            .flat_map(|i| (0..3 + i).map(move |k| 1000 + i + 10 * k))
+            .collect();
+        expected.sort_unstable();
+        let values: Vec<u32> = rows.iter().map(|r| r.1).collect();
+        assert_eq!(values, expected);
+    }
 }
@@ -0,0 +1,362 @@
+use anyhow::{Context, bail};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, Utc};
+use csv::StringRecord;
+
+use crate::types::NanoTime;
+
+/// A CSV column, by header name or zero-based position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+    Name(String),
+    Index(usize),
+}
+
+impl From<&str> for Column {
+    fn from(name: &str) -> Self {
+        Column::Name(name.to_owned())
+    }
+}
+
+impl From<usize> for Column {
+    fn from(index: usize) -> Self {
+        Column::Index(index)
+    }
+}
+
+impl Column {
+    fn index(&self, headers: Option<&StringRecord>) -> anyhow::Result<usize> {
+        match (self, headers) {
+            (Column::Index(index), _) => Ok(*index),
+            (Column::Name(name), Some(headers)) => headers
+                .iter()
+                .position(|header| header == name)
+                .with_context(|| format!("csv_read: no column named {name:?}")),
+            (Column::Name(name), None) => {
+                bail!("csv_read: column {name:?} is named but the file has no headers")
+            }
+        }
+    }
+}
+
+/// How a time column is written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// Integer nanoseconds since the unix epoch.
+    EpochNanos,
+    /// Integer milliseconds since the unix epoch.
+    EpochMillis,
+    /// Decimal seconds since the unix epoch, e.g. `1709285400.123456789`.
+    /// Parsed as a decimal, not a float, so up to 9 places are exact.
+    EpochSecondsFloat,
+    /// e.g. `2024-03-01T09:30:00.123Z` or `2024-03-01T10:30:00+01:00`.
+    Rfc3339,
+    /// A [chrono strftime](chrono::format::strftime) format.  Without an
+    /// offset (`%z`) the time is naive and read in the [TimeSpec]'s timezone.
+    Strftime(String),
+}
+
+/// Where a row's time comes from, for
+/// [csv_read_with_time](super::csv_read_with_time).
+/// ```
+/// # use wingfoil::adapters::csv::*;
+/// # use chrono::FixedOffset;
+/// // An ISO timestamp column.
+/// TimeSpec::column("timestamp", TimeFormat::Rfc3339);
+/// // Separate date and time columns, in New York winter time.
+/// TimeSpec::columns("date", "time", "%Y%m%d %H:%M:%S%.f")
+///     .with_timezone(FixedOffset::west_opt(5 * 3600).unwrap());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeSpec {
+    /// One column holding the whole time.
+    Column {
+        column: Column,
+        format: TimeFormat,
+        /// Offset of naive times, UTC if `None`.
+        timezone: Option<FixedOffset>,
+    },
+    /// A date column and a time column, joined with a space and parsed with
+    /// the strftime `format`, e.g. `"%Y-%m-%d %H:%M:%S"`.
+    Columns {
+        date: Column,
+        time: Column,
+        format: String,
+        /// Offset of naive times, UTC if `None`.
+        timezone: Option<FixedOffset>,
+    },
+}
+
+impl TimeSpec {
+    pub fn column(column: impl Into<Column>, format: TimeFormat) -> Self {
+        TimeSpec::Column {
+            column: column.into(),
+            format,
+            timezone: None,
+        }
+    }
+
+    pub fn columns(date: impl Into<Column>, time: impl Into<Column>, format: &str) -> Self {
+        TimeSpec::Columns {
+            date: date.into(),
+            time: time.into(),
+            format: format.to_owned(),
+            timezone: None,
+        }
+    }
+
+    /// Reads naive times at `timezone` rather than UTC.  Fixed offsets only:
+    /// a file spanning a daylight saving change needs its own offset column.
+    #[must_use]
+    pub fn with_timezone(mut self, timezone: FixedOffset) -> Self {
+        match &mut self {
+            TimeSpec::Column { timezone: tz, .. } | TimeSpec::Columns { timezone: tz, .. } => {
+                *tz = Some(timezone);
+            }
+        }
+        self
+    }
+
+    /// Resolves column names against the file's `headers`.
+    pub(super) fn parser(&self, headers: Option<&StringRecord>) -> anyhow::Result<TimeParser> {
+        let utc = FixedOffset::east_opt(0).expect("invariant: zero offset is valid");
+        Ok(match self {
+            TimeSpec::Column {
+                column,
+                format,
+                timezone,
+            } => TimeParser {
+                date: None,
+                time: column.index(headers)?,
+                format: format.clone(),
+                timezone: timezone.unwrap_or(utc),
+            },
+            TimeSpec::Columns {
+                date,
+                time,
+                format,
+                timezone,
+            } => TimeParser {
+                date: Some(date.index(headers)?),
+                time: time.index(headers)?,
+                format: TimeFormat::Strftime(format.clone()),
+                timezone: timezone.unwrap_or(utc),
+            },
+        })
+    }
+}
+
+/// A [TimeSpec] with its columns resolved to positions.
+pub(super) struct TimeParser {
+    date: Option<usize>,
+    time: usize,
+    format: TimeFormat,
+    timezone: FixedOffset,
+}
+
+impl TimeParser {
+    pub fn parse(&self, record: &StringRecord) -> anyhow::Result<NanoTime> {
+        let field = |index: usize| {
+            record
+                .get(index)
+                .with_context(|| format!("csv_read: row has no column {index}"))
+        };
+        let time = field(self.time)?;
+        let text = match self.date {
+            Some(date) => format!("{} {time}", field(date)?),
+            None => time.to_owned(),
+        };
+        self.parse_str(&text)
+            .with_context(|| format!("csv_read: failed to parse time {text:?}"))
+    }
+
+    fn parse_str(&self, text: &str) -> anyhow::Result<NanoTime> {
+        let text = text.trim();
+        match &self.format {
+            TimeFormat::EpochNanos => Ok(NanoTime::new(text.parse()?)),
+            TimeFormat::EpochMillis => text
+                .parse::<u64>()?
+                .checked_mul(1_000_000)
+                .map(NanoTime::new)
+                .context("outside NanoTime's range"),
+            TimeFormat::EpochSecondsFloat => parse_epoch_seconds(text),
+            TimeFormat::Rfc3339 => DateTime::parse_from_rfc3339(text)?.to_utc().try_into(),
+            TimeFormat::Strftime(format) => self.parse_strftime(text, format),
+        }
+    }
+
+    fn parse_strftime(&self, text: &str, format: &str) -> anyhow::Result<NanoTime> {
+        // Try with an offset first: a naive parse would silently drop one.
+        if let Ok(date_time) = DateTime::parse_from_str(text, format) {
+            return date_time.to_utc().try_into();
+        }
+        let naive = match NaiveDateTime::parse_from_str(text, format) {
+            Ok(naive) => naive,
+            Err(err) => match NaiveDate::parse_from_str(text, format) {
+                Ok(date) => date.and_time(Default::default()),
+                Err(_) => return Err(err.into()),
+            },
+        };
+        let local = naive
+            .and_local_timezone(self.timezone)
+            .single()
+            .context("ambiguous local time")?;
+        DateTime::<Utc>::from(local).try_into()
+    }
+}
+
+/// Decimal seconds, exact to the nanosecond; further places are truncated.
+fn parse_epoch_seconds(text: &str) -> anyhow::Result<NanoTime> {
+    let (secs, fraction) = text.split_once('.').unwrap_or((text, ""));
+    if !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        bail!("invalid fractional seconds");
+    }
+    let nanos = match fraction.get(..9).unwrap_or(fraction) {
+        "" => 0,
+        digits => format!("{digits:0<9}").parse::<u64>()?,
+    };
+    secs.parse::<u64>()?
+        .checked_mul(NanoTime::NANOS_PER_SECOND)
+        .and_then(|secs| secs.checked_add(nanos))
+        .map(NanoTime::new)
+        .context("outside NanoTime's range")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::csv::csv_read_with_time;
+    use crate::graph::*;
+    use crate::nodes::{NodeOperators, StreamOperators};
+    use serde::Deserialize;
+
+    /// 2024-03-01T09:30:00Z
+    const OPEN: u64 = 1_709_285_400 * NanoTime::NANOS_PER_SECOND;
+
+    #[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+    struct Row {
+        price: f64,
+    }
+
+    fn times(file: &str, spec: TimeSpec) -> Vec<(u64, f64)> {
+        let path = format!("src/adapters/csv/test_data/time/{file}");
+        let collected = csv_read_with_time::<Row>(&path, spec, true)
+            .unwrap()
+            .collect();
+        collected
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        collected
+            .peek_value()
+            .into_iter()
+            .flat_map(|burst| {
+                let time = u64::from(burst.time);
+                burst.value.into_iter().map(move |row| (time, row.price))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn epoch_nanos_by_name_and_index() {
+        let expected = vec![(OPEN + 123_456_789, 1.5), (OPEN + 1_000_000_000, 2.5)];
+        let spec = TimeSpec::column("time", TimeFormat::EpochNanos);
+        assert_eq!(times("epoch_nanos.csv", spec), expected);
+
+        type Headerless = (u64, f64);
+        let spec = TimeSpec::column(0, TimeFormat::EpochNanos);
+        let collected = csv_read_with_time::<Headerless>(
+            "src/adapters/csv/test_data/time/epoch_nanos_headerless.csv",
+            spec,
+            false,
+        )
+        .unwrap()
+        .collapse()
+        .collect();
+        collected
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        let rows: Vec<_> = collected
+            .peek_value()
+            .into_iter()
+            .map(|row| (u64::from(row.time), row.value.1))
+            .collect();
+        assert_eq!(rows, expected);
+    }
+
+    #[test]
+    fn epoch_millis() {
+        let spec = TimeSpec::column("time", TimeFormat::EpochMillis);
+        assert_eq!(
+            times("epoch_millis.csv", spec),
+            vec![(OPEN + 123_000_000, 1.5), (OPEN + 1_000_000_000, 2.5)]
+        );
+    }
+
+    #[test]
+    fn epoch_seconds_are_exact() {
+        let spec = TimeSpec::column("time", TimeFormat::EpochSecondsFloat);
+        assert_eq!(
+            times("epoch_seconds.csv", spec),
+            vec![
+                (OPEN + 123_456_789, 1.5),
+                (OPEN + 1_000_000_000, 2.5),
+                (OPEN + 1_500_000_000, 3.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn rfc3339_honours_offsets() {
+        let spec = TimeSpec::column("time", TimeFormat::Rfc3339);
+        assert_eq!(
+            times("rfc3339.csv", spec),
+            vec![(OPEN + 123_456_789, 1.5), (OPEN + 1_000_000_000, 2.5)]
+        );
+    }
+
+    #[test]
+    fn naive_strftime_read_in_supplied_timezone() {
+        let new_york = FixedOffset::west_opt(5 * 3600).unwrap();
+        let format = TimeFormat::Strftime("%Y-%m-%d %H:%M:%S%.f".into());
+        let spec = TimeSpec::column("time", format).with_timezone(new_york);
+        assert_eq!(
+            times("naive_new_york.csv", spec),
+            vec![(OPEN + 123_456_789, 1.5), (OPEN + 1_000_000_000, 2.5)]
+        );
+    }
+
+    #[test]
+    fn date_and_time_columns() {
+        let spec = TimeSpec::columns("date", "time", "%Y%m%d %H:%M:%S%.f");
+        assert_eq!(
+            times("date_time_columns.csv", spec),
+            vec![(OPEN + 123_456_789, 1.5), (OPEN + 1_000_000_000, 2.5)]
+        );
+    }
+
+    #[test]
+    fn unparseable_time_fails_the_run() {
+        let spec = TimeSpec::column("time", TimeFormat::Rfc3339);
+        let err =
+            csv_read_with_time::<Row>("src/adapters/csv/test_data/time/malformed.csv", spec, true)
+                .unwrap()
+                .collect()
+                .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+                .expect_err("expected a time parse error");
+        assert!(
+            format!("{err:#}").contains("failed to parse time \"not a time\""),
+            "unexpected error message: {err:#}"
+        );
+    }
+
+    #[test]
+    fn unknown_column_is_an_error_on_open() {
+        let spec = TimeSpec::column("timestamp", TimeFormat::EpochNanos);
+        let err = csv_read_with_time::<Row>(
+            "src/adapters/csv/test_data/time/epoch_nanos.csv",
+            spec,
+            true,
+        )
+        .expect_err("expected a missing column error");
+        assert!(format!("{err:#}").contains("no column named \"timestamp\""));
+    }
+}
@@ -1,11 +1,14 @@
+use super::header::{Padded, Shape, column_names_for, header_for};
+use super::partitioned::*;
 use crate::burst;
 use derive_new::new;
 use serde::{Serialize, de::DeserializeOwned};
-use serde_aux::serde_introspection::serde_introspect;
 use std::fs::File;
+use std::io::Write;
 use std::rc::Rc;
 
 use crate::nodes::StreamOperators;
+use crate::queue::ValueAt;
 use crate::types::*;
 
 /// Writes a [`Burst<T>`] stream to a CSV file, one row per element per tick.
@@ -16,29 +19,90 @@ pub struct CsvWriterNode<T: Element> {
     writer: csv::Writer<File>,
     #[new(default)]
     headers_written: bool,
+    #[new(default)]
+    shape: Option<Shape>,
+    #[new(default)]
+    flush_every: Option<usize>,
+    #[new(default)]
+    unflushed_rows: usize,
+}
+
+impl<T: Element> CsvWriterNode<T> {
+    /// Flushes the file every `rows` rows, and on the last cycle, rather than
+    /// only when the csv crate's buffer fills and on stop.
+    pub fn flush_every(mut self, rows: usize) -> Self {
+        assert!(rows > 0, "flush_every must be positive");
+        self.flush_every = Some(rows);
+        self
+    }
+
+    fn flush(&mut self) -> anyhow::Result<()> {
+        self.unflushed_rows = 0;
+        self.writer
+            .flush()
+            .map_err(|e| anyhow::anyhow!("Failed to flush CSV writer: {e}"))
+    }
+}
+
+/// One csv row: the tick time followed by the record's columns.  The csv
+/// serializer lays nested structs out positionally, which is also how
+/// `(NanoTime, T)` reads them back, so rows round-trip through [`csv_read`](super::csv_read).
+#[derive(Serialize)]
+struct CsvRow<'a, T: ?Sized> {
+    time: NanoTime,
+    value: Padded<'a, T>,
+}
+
+/// Writes `value` as one row at `time`, with any `None` padded out to the
+/// columns `shape` gives it.
+pub(super) fn write_row<W: Write, T: Serialize>(
+    writer: &mut csv::Writer<W>,
+    shape: Option<&Shape>,
+    time: NanoTime,
+    value: &T,
+) -> anyhow::Result<()> {
+    writer
+        .serialize(CsvRow {
+            time,
+            value: Padded { value, shape },
+        })
+        .map_err(|e| anyhow::anyhow!("Failed to serialize CSV record: {e}"))
 }
 
 #[node(active = [upstream])]
 impl<T: Element + Serialize + DeserializeOwned + 'static> MutableNode for CsvWriterNode<T> {
     fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
-        if !self.headers_written {
-            write_header::<T>(&mut self.writer)?;
-            self.headers_written = true;
+        for rec in self.upstream.peek_value().iter() {
+            if !self.headers_written {
+                self.shape = Shape::of::<T>();
+                write_header(&mut self.writer, self.shape.as_ref(), rec)?;
+                self.headers_written = true;
+            }
+            write_row(&mut self.writer, self.shape.as_ref(), state.time(), rec)?;
+            self.unflushed_rows += 1;
+            if self.flush_every == Some(self.unflushed_rows) {
+                self.flush()?;
+            }
         }
-        for rec in self.upstream.peek_value() {
-            self.writer
-                .serialize((state.time(), rec))
-                .map_err(|e| anyhow::anyhow!("Failed to serialize CSV record: {e}"))?;
+        if self.flush_every.is_some() && self.unflushed_rows > 0 && state.is_last_cycle() {
+            self.flush()?;
         }
         Ok(false)
     }
+
+    fn stop(&mut self, _state: &mut GraphState) -> anyhow::Result<()> {
+        self.flush()
+    }
 }
 
-fn write_header<T: Serialize + DeserializeOwned + 'static>(
+/// Writes the header for records of `shape`, falling back to the columns of
+/// `rec` when the record type could not be traced.
+pub(super) fn write_header<T: Serialize>(
     writer: &mut csv::Writer<File>,
+    shape: Option<&Shape>,
+    rec: &T,
 ) -> anyhow::Result<()> {
-    let fields = serde_introspect::<T>();
-    if !fields.is_empty() {
+    if let Some(fields) = header_for(shape, rec)? {
         writer
             .write_field("time")
             .map_err(|e| anyhow::anyhow!("Failed to write CSV time header: {e}"))?;
@@ -49,20 +113,80 @@ fn write_header<T: Serialize + DeserializeOwned + 'static>(
     Ok(())
 }
 
+fn open_writer(path: &str) -> csv::Writer<File> {
+    csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_path(path)
+        .unwrap_or_else(|e| panic!("csv_write: failed to open {path} for writing: {e}"))
+}
+
 /// Trait to add csv write operators to streams.
 pub trait CsvOperators<T: Element> {
     /// Writes each element of the burst to a CSV file, one row per element per tick.
     #[must_use]
     fn csv_write(self: &Rc<Self>, path: &str) -> Rc<dyn Node>;
+
+    /// Like [csv_write](Self::csv_write), but flushes the file every
+    /// `flush_every` rows and on the last cycle, so that a reader tailing it
+    /// sees rows promptly in a long realtime run.
+    #[must_use]
+    fn csv_write_flushed(self: &Rc<Self>, path: &str, flush_every: usize) -> Rc<dyn Node>;
+
+    /// Writes each element to `<dir>/<key>.csv`, one file per distinct key,
+    /// keeping at most [DEFAULT_MAX_OPEN_FILES] open at once.
+    #[must_use]
+    fn csv_write_partitioned(
+        self: &Rc<Self>,
+        dir: &str,
+        key_fn: impl Fn(&T) -> String + 'static,
+    ) -> Rc<dyn Node> {
+        self.write_partitioned(dir, PartitionFormat::Csv, DEFAULT_MAX_OPEN_FILES, key_fn)
+    }
+
+    /// Writes each element to `<dir>/<key>.ndjson`, one file per distinct
+    /// key, keeping at most [DEFAULT_MAX_OPEN_FILES] open at once.
+    #[must_use]
+    fn ndjson_write_partitioned(
+        self: &Rc<Self>,
+        dir: &str,
+        key_fn: impl Fn(&T) -> String + 'static,
+    ) -> Rc<dyn Node> {
+        self.write_partitioned(dir, PartitionFormat::Ndjson, DEFAULT_MAX_OPEN_FILES, key_fn)
+    }
+
+    /// Writes each element to the `format` file for its key under `dir`.
+    /// Keys are sanitized into file names, and files are flushed and closed
+    /// on stop.  Beyond `max_open_files` the least recently written file is
+    /// closed, and reopened in append mode when its key next appears.
+    #[must_use]
+    fn write_partitioned(
+        self: &Rc<Self>,
+        dir: &str,
+        format: PartitionFormat,
+        max_open_files: usize,
+        key_fn: impl Fn(&T) -> String + 'static,
+    ) -> Rc<dyn Node>;
 }
 
 impl<T: Element + Serialize + DeserializeOwned + 'static> CsvOperators<T> for dyn Stream<Burst<T>> {
     fn csv_write(self: &Rc<Self>, path: &str) -> Rc<dyn Node> {
-        let writer = csv::WriterBuilder::new()
-            .has_headers(false)
-            .from_path(path)
-            .unwrap_or_else(|e| panic!("csv_write: failed to open {path} for writing: {e}"));
-        CsvWriterNode::new(self.clone(), writer).into_node()
+        CsvWriterNode::new(self.clone(), open_writer(path)).into_node()
+    }
+
+    fn csv_write_flushed(self: &Rc<Self>, path: &str, flush_every: usize) -> Rc<dyn Node> {
+        CsvWriterNode::new(self.clone(), open_writer(path))
+            .flush_every(flush_every)
+            .into_node()
+    }
+
+    fn write_partitioned(
+        self: &Rc<Self>,
+        dir: &str,
+        format: PartitionFormat,
+        max_open_files: usize,
+        key_fn: impl Fn(&T) -> String + 'static,
+    ) -> Rc<dyn Node> {
+        PartitionedWriterNode::new(self.clone(), dir, format, max_open_files, key_fn).into_node()
     }
 }
 
@@ -70,6 +194,61 @@ impl<T: Element + Serialize + DeserializeOwned + 'static> CsvOperators<T> for dy
     fn csv_write(self: &Rc<Self>, path: &str) -> Rc<dyn Node> {
         self.map(|v| burst![v]).csv_write(path)
     }
+
+    fn csv_write_flushed(self: &Rc<Self>, path: &str, flush_every: usize) -> Rc<dyn Node> {
+        self.map(|v| burst![v]).csv_write_flushed(path, flush_every)
+    }
+
+    fn write_partitioned(
+        self: &Rc<Self>,
+        dir: &str,
+        format: PartitionFormat,
+        max_open_files: usize,
+        key_fn: impl Fn(&T) -> String + 'static,
+    ) -> Rc<dyn Node> {
+        self.map(|v| burst![v])
+            .write_partitioned(dir, format, max_open_files, key_fn)
+    }
+}
+
+/// Writes the output of [collect](crate::nodes::StreamOperators::collect)
+/// to a CSV file as the graph stops.
+pub trait CsvResultsOperators<T: Element> {
+    /// Writes one `time,value` row per collected value when the graph stops.
+    /// Struct values get one column per field, named as for
+    /// [csv_write](CsvOperators::csv_write).  Unlike `csv_write` the header
+    /// is always written, including for scalars and when nothing was
+    /// collected.
+    #[must_use]
+    fn write_results_csv(self: &Rc<Self>, path: &str) -> Rc<dyn Node>;
+}
+
+impl<T: Element + Serialize + DeserializeOwned> CsvResultsOperators<T>
+    for dyn Stream<Vec<ValueAt<T>>>
+{
+    fn write_results_csv(self: &Rc<Self>, path: &str) -> Rc<dyn Node> {
+        let path = path.to_string();
+        self.finally(move |results, _| {
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_path(&path)
+                .map_err(|e| anyhow::anyhow!("write_results_csv: failed to open {path}: {e}"))?;
+            let shape = Shape::of::<T>();
+            let columns = match results.first() {
+                Some(first) => column_names_for(shape.as_ref(), &first.value)?,
+                None => column_names_for(shape.as_ref(), &T::default())?,
+            };
+            writer
+                .write_record(std::iter::once("time".to_string()).chain(columns))
+                .map_err(|e| anyhow::anyhow!("Failed to write CSV header record: {e}"))?;
+            for result in &results {
+                write_row(&mut writer, shape.as_ref(), result.time, &result.value)?;
+            }
+            writer
+                .flush()
+                .map_err(|e| anyhow::anyhow!("Failed to flush CSV writer: {e}"))
+        })
+    }
 }
 
 #[cfg(test)]
@@ -77,11 +256,35 @@ mod tests {
     use crate::adapters::csv::*;
     use crate::graph::*;
     use crate::nodes::*;
-    use crate::types::NanoTime;
+    use crate::queue::ValueAt;
+    use crate::types::{AsStream, NanoTime, Node, Stream};
+    use serde::{Deserialize, Serialize};
+    use std::cell::RefCell;
+    use std::rc::Rc;
     use std::time::Duration;
 
     type Record = (NanoTime, u32);
 
+    #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+    struct TwoWayPrice {
+        bid_price: Option<u64>,
+        ask_price: Option<u64>,
+    }
+
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    struct Quote {
+        symbol: String,
+        price: TwoWayPrice,
+    }
+
+    /// Flat view of a written `TwoWayPrice` row, read back by header name.
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    struct PriceRow {
+        time: NanoTime,
+        bid_price: Option<u64>,
+        ask_price: Option<u64>,
+    }
+
     #[test]
     pub fn csv_simple_works() {
         let run_to = RunFor::Duration(Duration::from_nanos(1));
@@ -105,4 +308,319 @@ mod tests {
             .run(run_mode, run_to)
             .unwrap();
     }
+
+    #[test]
+    fn csv_round_trips_nested_option_fields() {
+        let path =
+            std::env::temp_dir().join(format!("wingfoil_csv_prices_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        let prices = vec![
+            ValueAt::new(
+                TwoWayPrice {
+                    bid_price: Some(100),
+                    ask_price: None,
+                },
+                NanoTime::new(10),
+            ),
+            ValueAt::new(
+                TwoWayPrice {
+                    bid_price: None,
+                    ask_price: Some(105),
+                },
+                NanoTime::new(20),
+            ),
+            ValueAt::new(
+                TwoWayPrice {
+                    bid_price: Some(101),
+                    ask_price: Some(104),
+                },
+                NanoTime::new(30),
+            ),
+        ];
+        let src = Rc::new(RefCell::new(CallBackStream::new()));
+        for price in &prices {
+            src.borrow_mut().push(price.clone());
+        }
+        src.clone()
+            .as_stream()
+            .csv_write(path)
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        let written = std::fs::read_to_string(path).unwrap();
+        assert_eq!(
+            written,
+            "time,bid_price,ask_price\n10,100,\n20,,105\n30,101,104\n"
+        );
+
+        let read_back = csv_read(path, |r: &PriceRow| r.time, true)
+            .unwrap()
+            .collapse()
+            .collect();
+        read_back
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        let round_tripped: Vec<ValueAt<TwoWayPrice>> = read_back
+            .peek_value()
+            .into_iter()
+            .map(|row| {
+                let price = TwoWayPrice {
+                    bid_price: row.value.bid_price,
+                    ask_price: row.value.ask_price,
+                };
+                ValueAt::new(price, row.time)
+            })
+            .collect();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(round_tripped, prices);
+    }
+
+    #[test]
+    fn csv_header_flattens_nested_structs() {
+        let path =
+            std::env::temp_dir().join(format!("wingfoil_csv_quotes_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        let quote = Quote {
+            symbol: "AAPL".to_string(),
+            price: TwoWayPrice {
+                bid_price: Some(100),
+                ask_price: None,
+            },
+        };
+        constant(quote)
+            .csv_write(path)
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(1))
+            .unwrap();
+        let written = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(written, "time,symbol,bid_price,ask_price\n0,AAPL,100,\n");
+    }
+
+    #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+    struct Book {
+        symbol: String,
+        top: Option<TwoWayPrice>,
+    }
+
+    /// Flat view of a written `Book` row, read back by header name.
+    #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+    struct BookRow {
+        time: NanoTime,
+        symbol: String,
+        bid_price: Option<u64>,
+        ask_price: Option<u64>,
+    }
+
+    #[test]
+    fn csv_header_spans_nested_option_that_starts_none() {
+        let path =
+            std::env::temp_dir().join(format!("wingfoil_csv_books_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        let tops = [
+            None,
+            Some(TwoWayPrice {
+                bid_price: Some(100),
+                ask_price: None,
+            }),
+            Some(TwoWayPrice {
+                bid_price: Some(101),
+                ask_price: Some(104),
+            }),
+        ];
+        let src = Rc::new(RefCell::new(CallBackStream::new()));
+        for (i, top) in tops.into_iter().enumerate() {
+            let book = Book {
+                symbol: "AAPL".to_string(),
+                top,
+            };
+            src.borrow_mut()
+                .push(ValueAt::new(book, NanoTime::new(10 * (i as u64 + 1))));
+        }
+        src.as_stream()
+            .csv_write(path)
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        let written = std::fs::read_to_string(path).unwrap();
+        assert_eq!(
+            written,
+            "time,symbol,bid_price,ask_price\n10,AAPL,,\n20,AAPL,100,\n30,AAPL,101,104\n"
+        );
+        let read_back = csv_read(path, |r: &BookRow| r.time, true)
+            .unwrap()
+            .collapse()
+            .collect();
+        read_back
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        std::fs::remove_file(path).unwrap();
+        let prices: Vec<(Option<u64>, Option<u64>)> = read_back
+            .peek_value()
+            .iter()
+            .map(|row| (row.value.bid_price, row.value.ask_price))
+            .collect();
+        assert_eq!(
+            prices,
+            [(None, None), (Some(100), None), (Some(101), Some(104))]
+        );
+    }
+
+    /// A [SmallStr](crate::SmallStr) keyed row, read back by header name.
+    #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+    struct TickerRow {
+        time: NanoTime,
+        ticker: crate::SmallStr,
+        price: u64,
+    }
+
+    #[test]
+    fn csv_round_trips_small_str_fields() {
+        #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+        struct Tick {
+            ticker: crate::SmallStr,
+            price: u64,
+        }
+        let path =
+            std::env::temp_dir().join(format!("wingfoil_csv_small_str_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        let tickers = ["AAPL", "VOD.L"].map(|t| t.parse().unwrap());
+        ticker(Duration::from_nanos(10))
+            .count()
+            .map(move |n| Tick {
+                ticker: tickers[n as usize % 2],
+                price: n,
+            })
+            .csv_write(path)
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(2))
+            .unwrap();
+        let written = std::fs::read_to_string(path).unwrap();
+        assert_eq!(written, "time,ticker,price\n0,VOD.L,1\n10,AAPL,2\n");
+        let read_back = csv_read(path, |r: &TickerRow| r.time, true)
+            .unwrap()
+            .collapse()
+            .collect();
+        read_back
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        std::fs::remove_file(path).unwrap();
+        let tickers: Vec<String> = read_back
+            .peek_value()
+            .iter()
+            .map(|row| row.value.ticker.to_string())
+            .collect();
+        assert_eq!(tickers, ["VOD.L", "AAPL"]);
+    }
+
+    #[test]
+    fn csv_write_as_side_branch_of_a_chain() {
+        let path =
+            std::env::temp_dir().join(format!("wingfoil_csv_also_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        let totals = ticker(Duration::from_nanos(10))
+            .count()
+            .also(|count| count.csv_write(path))
+            .fold(|total: &mut u64, n| *total += n)
+            .collect();
+        totals
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+            .unwrap();
+        let written = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(written, "0,1\n10,2\n20,3\n");
+        let totals: Vec<u64> = totals.peek_value().iter().map(|v| v.value).collect();
+        assert_eq!(totals, vec![1, 3, 6]);
+    }
+
+    /// Rows on disk as seen by each tick of a `count` written with `write`,
+    /// read after that tick's row is written.
+    fn rows_on_disk(
+        name: &str,
+        write: fn(&Rc<dyn Stream<u64>>, &str) -> Rc<dyn Node>,
+    ) -> Vec<usize> {
+        let path =
+            std::env::temp_dir().join(format!("wingfoil_csv_{name}_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap().to_string();
+        let reader_path = path.clone();
+        let seen = ticker(Duration::from_nanos(10))
+            .count()
+            .also(|count| write(count, &path))
+            .map(move |_| {
+                std::fs::read_to_string(&reader_path)
+                    .map(|text| text.lines().count())
+                    .unwrap_or(0)
+            })
+            .collect();
+        seen.run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(5))
+            .unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(written.lines().count(), 5);
+        seen.peek_value().iter().map(|v| v.value).collect()
+    }
+
+    #[test]
+    fn csv_write_flushed_rows_are_on_disk_before_the_run_ends() {
+        let seen = rows_on_disk("flushed", |count, path| count.csv_write_flushed(path, 2));
+        // flushed every second row, then on the last cycle
+        assert_eq!(seen, vec![0, 2, 2, 4, 5]);
+    }
+
+    #[test]
+    fn csv_write_buffers_until_stop() {
+        let seen = rows_on_disk("buffered", |count, path| count.csv_write(path));
+        assert_eq!(seen, vec![0, 0, 0, 0, 0]);
+    }
+
+    fn results_csv(name: &str, results: &Rc<dyn Stream<Vec<ValueAt<TwoWayPrice>>>>) -> String {
+        let path = std::env::temp_dir().join(format!(
+            "wingfoil_csv_results_{name}_{}.csv",
+            std::process::id()
+        ));
+        let path = path.to_str().unwrap();
+        results
+            .write_results_csv(path)
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+            .unwrap();
+        let written = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        written
+    }
+
+    #[test]
+    fn write_results_csv_writes_collected_rows() {
+        let prices = ticker(Duration::from_nanos(10))
+            .count()
+            .map(|n| TwoWayPrice {
+                bid_price: Some(n),
+                ask_price: (n != 2).then_some(n + 1),
+            });
+        assert_eq!(
+            results_csv("prices", &prices.collect()),
+            "time,bid_price,ask_price\n0,1,2\n10,2,\n20,3,4\n"
+        );
+        let path = std::env::temp_dir().join(format!(
+            "wingfoil_csv_results_scalar_{}.csv",
+            std::process::id()
+        ));
+        let path = path.to_str().unwrap();
+        ticker(Duration::from_nanos(10))
+            .count()
+            .collect()
+            .write_results_csv(path)
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(2))
+            .unwrap();
+        let written = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(written, "time,value\n0,1\n10,2\n");
+    }
+
+    #[test]
+    fn write_results_csv_empty_is_header_only() {
+        let none = ticker(Duration::from_nanos(10))
+            .count()
+            .filter_value(|_| false)
+            .map(|_| TwoWayPrice::default());
+        assert_eq!(
+            results_csv("empty", &none.collect()),
+            "time,bid_price,ask_price\n"
+        );
+    }
 }
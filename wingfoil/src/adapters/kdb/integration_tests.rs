@@ -378,6 +378,99 @@ fn test_kdb_deserialization_error() -> Result<()> {
     Ok(())
 }
 
+/// TestTrade's value columns, read by [kdb_read_serde] with no
+/// [KdbDeserialize] impl.
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize)]
+struct SerdeTrade {
+    sym: Sym,
+    price: f64,
+    qty: i64,
+}
+
+#[test]
+fn test_kdb_read_serde_matches_trait_read() -> Result<()> {
+    let _ = env_logger::try_init();
+    with_test_data(3, 2, true, |_n, conn| {
+        let period = std::time::Duration::from_secs(24 * 3600);
+        let by_trait = kdb_read::<TestTrade>(
+            conn.clone(),
+            period,
+            |within, date, _| slice_query(date, within.0, within.1),
+            None,
+        )
+        .collect();
+        let by_serde = kdb_read_serde::<SerdeTrade>(
+            conn,
+            period,
+            |within, date, _| slice_query(date, within.0, within.1),
+            "time",
+            None,
+        )
+        .collect();
+        crate::Graph::new(
+            vec![by_trait.clone().as_node(), by_serde.clone().as_node()],
+            RunMode::HistoricalFrom(NanoTime::from_kdb_timestamp(0)),
+            RunFor::Duration(std::time::Duration::from_secs(2 * 86400)),
+        )
+        .run()?;
+        let by_trait: Vec<(NanoTime, SerdeTrade)> = by_trait
+            .peek_value()
+            .into_iter()
+            .flat_map(|burst| {
+                burst.value.into_iter().map(move |trade| {
+                    let TestTrade { sym, price, qty } = trade;
+                    (burst.time, SerdeTrade { sym, price, qty })
+                })
+            })
+            .collect();
+        let by_serde: Vec<(NanoTime, SerdeTrade)> = by_serde
+            .peek_value()
+            .into_iter()
+            .flat_map(|burst| {
+                burst
+                    .value
+                    .into_iter()
+                    .map(move |trade| (burst.time, trade))
+            })
+            .collect();
+        assert_eq!(by_trait.len(), 6);
+        assert_eq!(by_serde, by_trait);
+        Ok(())
+    })
+}
+
+#[test]
+fn test_kdb_read_serde_missing_column() -> Result<()> {
+    let _ = env_logger::try_init();
+    #[derive(Debug, Clone, Default, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct Quote {
+        sym: Sym,
+        bid: f64,
+    }
+    let result = with_test_data(3, 1, true, |_n, conn| {
+        kdb_read_serde::<Quote>(
+            conn,
+            std::time::Duration::from_secs(24 * 3600),
+            |within, date, _| slice_query(date, within.0, within.1),
+            "time",
+            None,
+        )
+        .collect()
+        .run(
+            RunMode::HistoricalFrom(NanoTime::from_kdb_timestamp(0)),
+            RunFor::Duration(std::time::Duration::from_secs(86400)),
+        )?;
+        Ok(())
+    });
+    let err = format!("{:#}", result.unwrap_err());
+    assert!(
+        err.contains("no column `bid`, available columns: date, time, sym, price, qty"),
+        "{err}"
+    );
+    Ok(())
+}
+
 #[test]
 fn test_read_read_perf() -> Result<()> {
     /*
@@ -505,6 +598,35 @@ fn test_kdb_read_works() -> Result<()> {
     })
 }
 
+/// `kdb_read` with `RunFor::Forever` has no end time, so it reads on to the
+/// present and returns the whole table.  The rows are dated yesterday, so a
+/// run from three days ago needs only a handful of daily slices.
+#[test]
+fn test_kdb_read_forever_reads_full_table() -> Result<()> {
+    let _ = env_logger::try_init();
+    with_empty_table(|conn| {
+        let builder = TestDataBuilder::new(conn.clone(), tokio::runtime::Runtime::new()?);
+        builder.tokio.block_on(builder.execute(&format!(
+            "insert[`{TABLE_NAME};(3#.z.d-1;(`timestamp$.z.d-1)+28800000000000j*til 3;\
+             `AAPL`GOOG`MSFT;1.0 2.0 3.0;10 20 30j)]"
+        )))?;
+
+        let stream = kdb_read::<TestTrade>(
+            conn,
+            std::time::Duration::from_secs(24 * 3600),
+            |within, date, _| slice_query(date, within.0, within.1),
+            None,
+        );
+        let collected = stream.collapse().collect();
+        let start = NanoTime::now() - NanoTime::from(std::time::Duration::from_secs(3 * 86400));
+        collected
+            .clone()
+            .run(RunMode::HistoricalFrom(start), RunFor::Forever)?;
+        assert_eq!(collected.peek_value().len(), 3);
+        Ok(())
+    })
+}
+
 // --- Write integration tests ---
 
 /// Helper: creates an empty WRITE_TABLE_NAME, writes trades via the graph, queries KDB to verify.
@@ -744,6 +866,49 @@ pub(super) async fn connect(conn: &KdbConnection) -> Result<QStream> {
         .context("Failed to connect to KDB+")
 }
 
+/// Runs `query` on the test instance and returns the result.
+fn q_query(query: &str) -> Result<K> {
+    let conn = TestDataBuilder::connection();
+    tokio::runtime::Runtime::new()?.block_on(async {
+        let mut socket = connect(&conn).await?;
+        q_exec(&mut socket, query).await
+    })
+}
+
+/// A keyed reference table reads without `0!` on the q side: key columns
+/// first, then value columns.
+#[test]
+fn test_kdb_keyed_table_rows() -> Result<()> {
+    let table = q_query("([sym:`AAPL`MSFT; venue:`X`Y] lot:100 10; px:1.5 2.5)")?;
+    assert_eq!(table.column_names()?, ["sym", "venue", "lot", "px"]);
+    let rows = table.rows()?;
+    assert_eq!(rows.len(), 2);
+    let mut interner = SymbolInterner::default();
+    let row = rows.get(1).expect("second row");
+    assert_eq!(row.get_sym(0, &mut interner)?.to_string(), "MSFT");
+    assert_eq!(row.get_sym(1, &mut interner)?.to_string(), "Y");
+    assert_eq!(row.get(2)?.get_long()?, 10);
+    assert_eq!(row.get(3)?.get_float()?, 2.5);
+    Ok(())
+}
+
+/// Nested list columns, including empty typed and untyped inner lists.
+#[test]
+fn test_kdb_nested_list_columns() -> Result<()> {
+    let table = q_query(
+        "([] id:1 2; legs:(1 2 3; enlist 4); wts:(0.5 0.5; `float$()); venues:(`x`y; ()))",
+    )?;
+    let rows = table.rows()?;
+    let (first, second) = (rows.get(0).expect("row 0"), rows.get(1).expect("row 1"));
+    assert_eq!(first.get_list::<i64>(1)?, [1, 2, 3]);
+    assert_eq!(second.get_list::<i64>(1)?, [4]);
+    assert_eq!(first.get_list::<f64>(2)?, [0.5, 0.5]);
+    assert!(second.get_list::<f64>(2)?.is_empty());
+    assert_eq!(first.get_list::<String>(3)?, ["x", "y"]);
+    assert!(second.get_list::<String>(3)?.is_empty());
+    Ok(())
+}
+
 /// End-to-end test of the `kdb_sub` subscribe → receive → decode path against a
 /// live q instance acting as a tickerplant (see `TICKERPLANT_INIT`).
 ///
@@ -4,6 +4,8 @@
 //! allowing query results to be streamed into wingfoil graphs. Historical reads
 //! (`kdb_read`), real-time tickerplant subscriptions (`kdb_sub`), and writes
 //! (`kdb_write`) are all supported.
+//! Records implement [`KdbDeserialize`] for reads, or derive serde's
+//! `Deserialize` for [`kdb_read_serde`], which trades speed for no boilerplate.
 //!
 //! # Example
 //!
@@ -61,6 +63,7 @@
 
 mod read;
 mod read_cached;
+mod read_serde;
 mod sub;
 mod write;
 
@@ -72,6 +75,7 @@ mod integration_tests;
 pub use crate::adapters::cache::CacheConfig;
 pub use read::*;
 pub use read_cached::*;
+pub use read_serde::*;
 pub use sub::*;
 pub use write::*;
 
@@ -122,6 +126,11 @@ impl<'de> serde::Deserialize<'de> for Sym {
     }
 }
 
+/// A symbol stored inline rather than interned, for symbols such as tickers
+/// that fit in a [`SmallStr`](crate::SmallStr): no interner, no allocation
+/// and no reference count.  Read with [`Row::get_sym_small`].
+pub type SymSmall = crate::SmallStr;
+
 /// Deduplicates symbol strings so repeated values share a single `Arc<str>` allocation.
 ///
 /// Created once per read call and passed to `from_kdb_row` / `Row::get_sym`.
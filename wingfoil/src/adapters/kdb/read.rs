@@ -1,6 +1,6 @@
 //! KDB+ read functionality for streaming data from q/kdb+ instances.
 
-use super::{KdbConnection, Sym, SymbolInterner};
+use super::{KdbConnection, Sym, SymSmall, SymbolInterner};
 use crate::adapters::common::{TimeWindow, WindowFilter, compute_validated_time_slices};
 use crate::nodes::produce_async;
 use crate::types::*;
@@ -16,18 +16,22 @@ pub trait KdbExt {
     /// Extract column names from a KDB table.
     ///
     /// For tables (qtype 98), the result is a flipped dictionary where the keys are column names.
+    /// Keyed tables (qtype 99, a dictionary of table to table) are unkeyed: the
+    /// key columns come first, then the value columns, as with `0!` in q.
     ///
     /// # Errors
-    /// Returns an error if the K object is not a table.
+    /// Returns an error if the K object is not a table or keyed table.
     fn column_names(&self) -> Result<Vec<String>>;
 
     /// Get a row accessor for iterating over table rows.
     ///
     /// Tables are stored column-wise in KDB. This returns a `Rows` struct
     /// that provides zero-allocation row iteration via indexed access.
+    /// Keyed tables are unkeyed, in the same column order as
+    /// [`column_names`](Self::column_names).
     ///
     /// # Errors
-    /// Returns an error if the K object is not a table.
+    /// Returns an error if the K object is not a table or keyed table.
     fn rows(&self) -> Result<Rows>;
 
     /// Get element at index from a K list/vector.
@@ -137,7 +141,17 @@ pub struct Row<'a> {
     index: usize,
 }
 
-impl Row<'_> {
+impl<'a> Row<'a> {
+    /// The whole column vector at `col`.
+    pub(super) fn column(&self, col: usize) -> Option<&'a K> {
+        self.columns.get(col)
+    }
+
+    /// The index of this row within its columns.
+    pub(super) fn index(&self) -> usize {
+        self.index
+    }
+
     /// Get value at column index.
     pub fn get(&self, col: usize) -> Result<K, KdbError> {
         self.columns
@@ -162,6 +176,21 @@ impl Row<'_> {
     /// Accesses the underlying `Vec<String>` directly and interns the `&str`,
     /// bypassing `element_at` (which clones the String into a new K object).
     pub fn get_sym(&self, col: usize, interner: &mut SymbolInterner) -> Result<Sym, KdbError> {
+        Ok(interner.intern(self.sym_str(col, "get_sym")?))
+    }
+
+    /// Get a symbol stored inline as a [`SymSmall`], without an interner or
+    /// any allocation.  Fails for symbols longer than
+    /// [`SymSmall::CAPACITY`] bytes.
+    pub fn get_sym_small(&self, col: usize) -> Result<SymSmall, KdbError> {
+        SymSmall::new(self.sym_str(col, "get_sym_small")?).map_err(|_| KdbError::InvalidCast {
+            from: "symbol",
+            to: "SymSmall",
+        })
+    }
+
+    /// The symbol at `col`, borrowed from the column's `Vec<String>`.
+    fn sym_str(&self, col: usize, operator: &'static str) -> Result<&'a str, KdbError> {
         let column = self.columns.get(col).ok_or(KdbError::IndexOutOfBounds {
             index: col,
             length: self.columns.len(),
@@ -169,7 +198,7 @@ impl Row<'_> {
         let strings = column
             .as_vec::<String>()
             .map_err(|_| KdbError::InvalidOperation {
-                operator: "get_sym",
+                operator,
                 operand_type: "K",
                 expected: Some("symbol list"),
             })?;
@@ -177,7 +206,60 @@ impl Row<'_> {
             index: self.index,
             length: strings.len(),
         })?;
-        Ok(interner.intern(s))
+        Ok(s)
+    }
+
+    /// Get a nested list column's value for this row, e.g. a `legs` column
+    /// holding a variable-length list per row.
+    ///
+    /// `T` is the element type of the inner lists: `i64` for long (and
+    /// timestamp) lists, `f64` for float lists and `String` for symbol lists.
+    /// An untyped empty list `()` reads as an empty `Vec`.
+    pub fn get_list<T: Clone + 'static>(&self, col: usize) -> Result<Vec<T>, KdbError> {
+        let column = self.columns.get(col).ok_or(KdbError::IndexOutOfBounds {
+            index: col,
+            length: self.columns.len(),
+        })?;
+        let lists = column
+            .as_vec::<K>()
+            .map_err(|_| KdbError::InvalidOperation {
+                operator: "get_list",
+                operand_type: "K",
+                expected: Some("nested list column"),
+            })?;
+        let list = lists.get(self.index).ok_or(KdbError::IndexOutOfBounds {
+            index: self.index,
+            length: lists.len(),
+        })?;
+        if list.get_type() == qtype::COMPOUND_LIST && list.is_empty() {
+            return Ok(Vec::new());
+        }
+        list.as_vec::<T>()
+            .cloned()
+            .map_err(|_| KdbError::InvalidOperation {
+                operator: "get_list",
+                operand_type: "K",
+                expected: Some("list of the requested element type"),
+            })
+    }
+
+    /// Splits into the first `mid` columns and the rest, or `None` if the
+    /// row has fewer than `mid` columns.
+    fn split_at(&self, mid: usize) -> Option<(Self, Self)> {
+        (mid <= self.columns.len()).then(|| {
+            let (left, right) = self.columns.split_at(mid);
+            let index = self.index;
+            (
+                Row {
+                    columns: left,
+                    index,
+                },
+                Row {
+                    columns: right,
+                    index,
+                },
+            )
+        })
     }
 
     /// Number of columns.
@@ -191,37 +273,52 @@ impl Row<'_> {
     }
 }
 
-impl KdbExt for K {
-    fn column_names(&self) -> Result<Vec<String>> {
-        if self.get_type() != qtype::TABLE {
-            bail!("expected table (qtype 98), got qtype {}", self.get_type());
+/// The column names and column vectors of a table, or of a keyed table
+/// unkeyed, key columns first.
+fn table_parts(table: &K) -> Result<(Vec<String>, Vec<K>)> {
+    match table.get_type() {
+        qtype::TABLE => {
+            let dict = table.get_dictionary()?;
+            let dict_parts = dict.as_vec::<K>()?;
+            let keys = dict_parts
+                .first()
+                .ok_or_else(|| anyhow::anyhow!("table dictionary has no keys"))?;
+            if dict_parts.len() < 2 {
+                bail!("table dictionary missing values");
+            }
+            let names = keys.as_vec::<String>()?.clone();
+            let columns = dict_parts[1].as_vec::<K>()?.clone();
+            Ok((names, columns))
         }
+        qtype::DICTIONARY => {
+            let parts = table.as_vec::<K>()?;
+            match parts.as_slice() {
+                [key, value]
+                    if key.get_type() == qtype::TABLE && value.get_type() == qtype::TABLE =>
+                {
+                    let (mut names, mut columns) = table_parts(key)?;
+                    let (value_names, value_columns) = table_parts(value)?;
+                    names.extend(value_names);
+                    columns.extend(value_columns);
+                    Ok((names, columns))
+                }
+                _ => {
+                    bail!("expected keyed table (qtype 99 of table!table), got a plain dictionary")
+                }
+            }
+        }
+        other => bail!("expected table (qtype 98) or keyed table (qtype 99), got qtype {other}"),
+    }
+}
 
-        let dict = self.get_dictionary()?;
-        let dict_parts = dict.as_vec::<K>()?;
-        let keys = dict_parts
-            .first()
-            .ok_or_else(|| anyhow::anyhow!("table dictionary has no keys"))?;
-        let symbols = keys.as_vec::<String>()?;
-        Ok(symbols.clone())
+impl KdbExt for K {
+    fn column_names(&self) -> Result<Vec<String>> {
+        Ok(table_parts(self)?.0)
     }
 
     fn rows(&self) -> Result<Rows> {
-        if self.get_type() != qtype::TABLE {
-            bail!("expected table (qtype 98), got qtype {}", self.get_type());
-        }
-
-        let dict = self.get_dictionary()?;
-        let dict_parts = dict.as_vec::<K>()?;
-
-        if dict_parts.len() < 2 {
-            bail!("table dictionary missing values");
-        }
-
-        let values = &dict_parts[1];
-        let columns = values.as_vec::<K>()?.clone();
+        let (_, columns) = table_parts(self)?;
         let n_rows = columns.first().map(|c| c.len()).unwrap_or(0);
-
         Ok(Rows { columns, n_rows })
     }
 
@@ -336,12 +433,19 @@ pub trait KdbDeserialize: Sized {
 ///
 /// `prev_time` is reset each chunk so time-of-day columns work correctly when
 /// advancing across date partitions (timestamps restart at midnight on each new date).
-fn chunk_stream<T>(
+///
+/// `decode` turns each row into `(time, record)`; `name` labels warnings.
+fn chunk_stream<T, E>(
+    name: &'static str,
     mut socket: QStream,
     mut next_slice: impl FnMut() -> Option<(String, TimeWindow)> + Send + 'static,
+    mut decode: impl FnMut(Row<'_>, &[String], &mut SymbolInterner) -> Result<(NanoTime, T), E>
+    + Send
+    + 'static,
 ) -> impl futures::Stream<Item = anyhow::Result<(NanoTime, T)>> + Send + 'static
 where
-    T: KdbDeserialize + Send + 'static,
+    T: Send + 'static,
+    E: std::error::Error + Send + Sync + 'static,
 {
     async_stream::stream! {
         let mut interner = SymbolInterner::default();
@@ -363,9 +467,9 @@ where
             info!("KDB query: {} rows in {:?}", row_count, fetch_start.elapsed());
 
             let mut prev_time: Option<NanoTime> = None;
-            let mut filter = WindowFilter::new("kdb_read", window);
+            let mut filter = WindowFilter::new(name, window);
             for row in &rows {
-                let (time, record) = match T::from_kdb_row(row, &columns, &mut interner) {
+                let (time, record) = match decode(row, &columns, &mut interner) {
                     Ok(r) => r,
                     Err(e) => { yield Err(e.into()); break 'outer; }
                 };
@@ -399,6 +503,10 @@ where
 /// (a fast KDB replay never blocks but can build an arbitrarily large backlog
 /// if the graph is the bottleneck), while `Some(n)` bounds it to `n` items so
 /// the replay applies back-pressure, capping memory use.
+///
+/// The read ends at `start + duration` for `RunFor::Duration`.  Other
+/// `RunFor`s fix no end time, so the read is unbounded: slices run up to the
+/// present and the whole table from `start` is read.
 #[must_use]
 pub fn kdb_read<T>(
     connection: KdbConnection,
@@ -408,25 +516,41 @@ pub fn kdb_read<T>(
 ) -> Rc<dyn Stream<Burst<T>>>
 where
     T: Element + Send + KdbDeserialize + 'static,
+{
+    read_sliced(
+        "kdb_read",
+        connection,
+        period,
+        query_fn,
+        buffer_size,
+        T::from_kdb_row,
+    )
+}
+
+/// The time-sliced read behind [`kdb_read`], decoding rows with `decode`.
+pub(super) fn read_sliced<T, E>(
+    name: &'static str,
+    connection: KdbConnection,
+    period: std::time::Duration,
+    query_fn: impl FnMut((NanoTime, NanoTime), i32, usize) -> String + Send + 'static,
+    buffer_size: Option<usize>,
+    decode: impl FnMut(Row<'_>, &[String], &mut SymbolInterner) -> Result<(NanoTime, T), E>
+    + Send
+    + 'static,
+) -> Rc<dyn Stream<Burst<T>>>
+where
+    T: Element + Send + 'static,
+    E: std::error::Error + Send + Sync + 'static,
 {
     produce_async(
         move |ctx| {
             let start_time = ctx.start_time;
-            let end_time_result = ctx.end_time();
+            let end_time = ctx.end_time();
 
             async move {
-                // `compute_validated_time_slices` consumes `end_time_result`; capture the
-                // concrete bound first (NanoTime is Copy) so the per-slice clamp below can
-                // reference `end_time`. Validation guarantees it is present (bounded, non-MAX).
-                let end_time_bound = end_time_result.as_ref().ok().copied();
-                let slices = compute_validated_time_slices(
-                    "kdb_read_time_sliced",
-                    start_time,
-                    end_time_result,
-                    period,
-                )?;
-                let end_time = end_time_bound
-                    .expect("compute_validated_time_slices accepted a bounded end_time");
+                let slices = compute_validated_time_slices(name, start_time, end_time, period)?;
+                // An unbounded run keeps every row up to the end of the table.
+                let end_time = end_time.unwrap_or(NanoTime::MAX);
 
                 let creds = connection.credentials_string();
                 let socket = QStream::connect(
@@ -451,7 +575,150 @@ where
                     Some((query, window))
                 };
 
-                Ok(chunk_stream::<T>(socket, slice_fn))
+                Ok(chunk_stream(name, socket, slice_fn, decode))
+            }
+        },
+        buffer_size,
+    )
+}
+
+/// Trait for deserializing the rows of a KDB+ asof join, as streamed by
+/// [`kdb_aj`].
+///
+/// An `aj` result holds every column of the left table, in the left table's
+/// order, followed by the columns the right table adds.  `left` and `right`
+/// give indexed access to those two parts, so column indices can be read off
+/// each table's own schema.  As with [`KdbDeserialize`], the implementation
+/// owns time extraction — usually the left table's time column.
+pub trait KdbAsofJoinDeserialize: Sized {
+    fn from_aj_row(
+        left: Row<'_>,
+        right: Row<'_>,
+        interner: &mut SymbolInterner,
+    ) -> Result<(NanoTime, Self), KdbError>;
+}
+
+/// Describes a KDB+ asof join for [`kdb_aj`]: for each row of `left_table`,
+/// the prevailing row of `right_table` matching on `join_cols` at or before
+/// its `time_col`.
+#[derive(Debug, Clone)]
+pub struct KdbAsofJoinConfig {
+    pub left_table: String,
+    pub right_table: String,
+    /// Exact-match columns, e.g. `sym`.  `time_col` is appended as the
+    /// final, asof-matched column.
+    pub join_cols: Vec<String>,
+    pub time_col: String,
+    /// Extra q constraints applied to both tables, e.g. `` sym in `AAPL`MSFT ``,
+    /// so may only reference columns they share.
+    pub where_clause: Option<String>,
+}
+
+impl KdbAsofJoinConfig {
+    pub fn new(
+        left_table: impl Into<String>,
+        right_table: impl Into<String>,
+        join_cols: &[&str],
+        time_col: impl Into<String>,
+    ) -> Self {
+        Self {
+            left_table: left_table.into(),
+            right_table: right_table.into(),
+            join_cols: join_cols.iter().map(|c| c.to_string()).collect(),
+            time_col: time_col.into(),
+            where_clause: None,
+        }
+    }
+
+    /// Filters both tables with `clause`.
+    #[must_use]
+    pub fn with_where(mut self, clause: impl Into<String>) -> Self {
+        self.where_clause = Some(clause.into());
+        self
+    }
+
+    /// The `aj` query for left rows in `[t0, t1)`.  The right table is only
+    /// bounded above, so rows before `t0` still supply the prevailing values
+    /// at the start of the slice.
+    fn query(&self, t0: NanoTime, t1: NanoTime) -> String {
+        let time = &self.time_col;
+        let (t0, t1) = (t0.to_kdb_timestamp(), t1.to_kdb_timestamp());
+        let extra = self
+            .where_clause
+            .as_ref()
+            .map(|clause| format!(", {clause}"))
+            .unwrap_or_default();
+        let cols: String = self
+            .join_cols
+            .iter()
+            .chain(std::iter::once(time))
+            .map(|col| format!("`{col}"))
+            .collect();
+        format!(
+            "aj[{cols}; select from {left} where {time} >= {t0}j, {time} < {t1}j{extra}; \
+             select from {right} where {time} < {t1}j{extra}]",
+            left = self.left_table,
+            right = self.right_table,
+        )
+    }
+}
+
+/// Streams a KDB+ asof join (`aj`), for aligning sparse reference data such
+/// as quotes with dense tick data such as trades.
+///
+/// Reads in time slices of `period` exactly like [`kdb_read`], with the same
+/// run-mode requirements, out-of-window row handling and `buffer_size`
+/// semantics; the query for each slice is generated from `config`.  The left
+/// table must return rows sorted by time.
+#[must_use]
+pub fn kdb_aj<T>(
+    connection: KdbConnection,
+    config: KdbAsofJoinConfig,
+    period: std::time::Duration,
+    buffer_size: Option<usize>,
+) -> Rc<dyn Stream<Burst<T>>>
+where
+    T: Element + Send + KdbAsofJoinDeserialize + 'static,
+{
+    produce_async(
+        move |ctx| {
+            let start_time = ctx.start_time;
+            let end_time = ctx.end_time();
+
+            async move {
+                let slices = compute_validated_time_slices("kdb_aj", start_time, end_time, period)?;
+                let end_time = end_time.unwrap_or(NanoTime::MAX);
+
+                let creds = connection.credentials_string();
+                let mut socket = QStream::connect(
+                    ConnectionMethod::TCP,
+                    &connection.host,
+                    connection.port,
+                    &creds,
+                )
+                .await?;
+                // Where the left table's columns end in each joined row.
+                let left_width = socket
+                    .send_sync_message(&format!("count cols {}", config.left_table).as_str())
+                    .await?
+                    .get_long()? as usize;
+
+                let mut slices_iter = slices.into_iter();
+                let slice_fn = move || -> Option<(String, TimeWindow)> {
+                    let ((t0, t1), _date, _iteration) = slices_iter.next()?;
+                    let window = TimeWindow::clamp(t0, t1, start_time, end_time);
+                    Some((config.query(t0, t1), window))
+                };
+                let decode = move |row: Row<'_>, _: &[String], interner: &mut SymbolInterner| {
+                    let (left, right) =
+                        row.split_at(left_width).ok_or(KdbError::IndexOutOfBounds {
+                            index: left_width,
+                            length: row.len(),
+                        })?;
+                    T::from_aj_row(left, right, interner)
+                };
+
+                Ok(chunk_stream("kdb_aj", socket, slice_fn, decode))
             }
         },
         buffer_size,
@@ -462,6 +729,131 @@ where
 mod tests {
     use super::*;
 
+    fn sym_list(syms: &[&str]) -> K {
+        let syms = syms.iter().map(|s| s.to_string()).collect();
+        K::new_symbol_list(syms, kdb_plus_fixed::qattribute::NONE)
+    }
+
+    /// `([] sym:`AAPL`MSFT; lot:100 10; px:1.5 2.5)`, keyed on the first
+    /// `keys` columns.
+    fn ref_table(keys: usize) -> K {
+        use kdb_plus_fixed::qattribute::NONE;
+        let table = K::new_dictionary(
+            sym_list(&["sym", "lot", "px"]),
+            K::new_compound_list(vec![
+                sym_list(&["AAPL", "MSFT"]),
+                K::new_long_list(vec![100, 10], NONE),
+                K::new_float_list(vec![1.5, 2.5], NONE),
+            ]),
+        )
+        .unwrap()
+        .flip()
+        .unwrap();
+        if keys == 0 {
+            table
+        } else {
+            table.enkey(keys).unwrap()
+        }
+    }
+
+    #[test]
+    fn keyed_table_is_unkeyed_key_columns_first() {
+        for keys in [0, 1, 2] {
+            let table = ref_table(keys);
+            assert_eq!(table.column_names().unwrap(), ["sym", "lot", "px"]);
+            let rows = table.rows().unwrap();
+            assert_eq!(rows.len(), 2, "{keys} keys");
+            let mut interner = SymbolInterner::default();
+            let row = rows.get(1).unwrap();
+            assert_eq!(row.get_sym(0, &mut interner).unwrap().to_string(), "MSFT");
+            assert_eq!(row.get(1).unwrap().get_long().unwrap(), 10);
+            assert_eq!(row.get(2).unwrap().get_float().unwrap(), 2.5);
+        }
+    }
+
+    #[test]
+    fn get_sym_small_reads_inline() {
+        use kdb_plus_fixed::qattribute::NONE;
+        let long = "X".repeat(SymSmall::CAPACITY + 1);
+        let table = K::new_dictionary(
+            sym_list(&["sym", "lot"]),
+            K::new_compound_list(vec![
+                sym_list(&["AAPL", &long]),
+                K::new_long_list(vec![100, 10], NONE),
+            ]),
+        )
+        .unwrap()
+        .flip()
+        .unwrap();
+        let rows = table.rows().unwrap();
+        assert_eq!(rows.get(0).unwrap().get_sym_small(0).unwrap(), "AAPL");
+        assert!(matches!(
+            rows.get(1).unwrap().get_sym_small(0),
+            Err(KdbError::InvalidCast { to: "SymSmall", .. })
+        ));
+        assert!(matches!(
+            rows.get(0).unwrap().get_sym_small(1),
+            Err(KdbError::InvalidOperation {
+                operator: "get_sym_small",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn plain_dictionary_is_not_a_table() {
+        let dict = K::new_dictionary(
+            sym_list(&["a"]),
+            K::new_long_list(vec![1], kdb_plus_fixed::qattribute::NONE),
+        )
+        .unwrap();
+        let err = dict.column_names().unwrap_err().to_string();
+        assert!(err.contains("plain dictionary"), "{err}");
+        assert!(K::new_long(1).rows().is_err());
+    }
+
+    #[test]
+    fn get_list_reads_nested_list_columns() {
+        use kdb_plus_fixed::qattribute::NONE;
+        // ([] legs:(1 2 3; enlist 4); wts:(0.5 0.5; `float$()); venues:(`x`y; ()))
+        let table = K::new_dictionary(
+            sym_list(&["legs", "wts", "venues"]),
+            K::new_compound_list(vec![
+                K::new_compound_list(vec![
+                    K::new_long_list(vec![1, 2, 3], NONE),
+                    K::new_long_list(vec![4], NONE),
+                ]),
+                K::new_compound_list(vec![
+                    K::new_float_list(vec![0.5, 0.5], NONE),
+                    K::new_float_list(vec![], NONE),
+                ]),
+                K::new_compound_list(vec![sym_list(&["x", "y"]), K::new_compound_list(vec![])]),
+            ]),
+        )
+        .unwrap()
+        .flip()
+        .unwrap();
+        let rows = table.rows().unwrap();
+        let (first, second) = (rows.get(0).unwrap(), rows.get(1).unwrap());
+        assert_eq!(first.get_list::<i64>(0).unwrap(), [1, 2, 3]);
+        assert_eq!(second.get_list::<i64>(0).unwrap(), [4]);
+        assert_eq!(first.get_list::<f64>(1).unwrap(), [0.5, 0.5]);
+        assert!(second.get_list::<f64>(1).unwrap().is_empty());
+        assert_eq!(first.get_list::<String>(2).unwrap(), ["x", "y"]);
+        assert!(second.get_list::<String>(2).unwrap().is_empty());
+        // wrong element type, and a flat column
+        assert!(first.get_list::<f64>(0).is_err());
+        assert!(
+            ref_table(0)
+                .rows()
+                .unwrap()
+                .get(0)
+                .unwrap()
+                .get_list::<i64>(1)
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_nanotime_from_kdb_timestamp() {
         // KDB timestamp 0 = 2000-01-01 00:00:00
@@ -758,4 +1150,165 @@ mod tests {
             assert!((a - b).abs() < 1e-10, "serialized vec_float mismatch");
         }
     }
+
+    #[test]
+    fn aj_query_layout() {
+        let config = KdbAsofJoinConfig::new("trades", "quotes", &["sym"], "time")
+            .with_where("sym in `AAPL`MSFT");
+        let t0 = NanoTime::from_kdb_timestamp(100);
+        let t1 = NanoTime::from_kdb_timestamp(200);
+        assert_eq!(
+            config.query(t0, t1),
+            "aj[`sym`time; select from trades where time >= 100j, time < 200j, sym in `AAPL`MSFT; \
+             select from quotes where time < 200j, sym in `AAPL`MSFT]"
+        );
+    }
+
+    /// Serves `kdb_aj`'s queries over one connection, standing in for a q
+    /// process holding a `trades` table (time, sym, price, size) and a
+    /// `quotes` table (time, sym, bid, ask).  Returns the queries received.
+    fn serve_mock_aj(port: u16, trade_times: Vec<i64>) -> std::thread::JoinHandle<Vec<String>> {
+        use kdb_plus_fixed::ipc::qmsg_type;
+        use kdb_plus_fixed::qattribute;
+
+        let symbols = |syms: &[&str]| {
+            let syms = syms.iter().map(|s| s.to_string()).collect();
+            K::new_symbol_list(syms, qattribute::NONE)
+        };
+        let n = trade_times.len();
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(async move {
+                let mut socket = QStream::accept(ConnectionMethod::TCP, "127.0.0.1", port)
+                    .await
+                    .unwrap();
+                let mut queries = Vec::new();
+                while let Ok((_, message)) = socket.receive_message().await {
+                    let query = message.as_string().unwrap().to_string();
+                    let response = if query == "count cols trades" {
+                        K::new_long(4)
+                    } else {
+                        // aj keeps the trades columns and appends bid and ask.
+                        let header = symbols(&["time", "sym", "price", "size", "bid", "ask"]);
+                        let columns = K::new_compound_list(vec![
+                            K::new_long_list(trade_times.clone(), qattribute::NONE),
+                            symbols(&vec!["AAPL"; n]),
+                            K::new_float_list(vec![101.5; n], qattribute::NONE),
+                            K::new_long_list(vec![100; n], qattribute::NONE),
+                            K::new_float_list(vec![101.0; n], qattribute::NONE),
+                            K::new_float_list(vec![102.0; n], qattribute::NONE),
+                        ]);
+                        K::new_dictionary(header, columns).unwrap().flip().unwrap()
+                    };
+                    queries.push(query);
+                    socket
+                        .send_message(&response, qmsg_type::response)
+                        .await
+                        .unwrap();
+                }
+                queries
+            })
+        })
+    }
+
+    #[test]
+    fn kdb_aj_streams_joined_rows() {
+        use crate::nodes::{NodeOperators, StreamOperators};
+        use crate::{RunFor, RunMode};
+        use std::time::Duration;
+
+        #[derive(Debug, Clone, Default, PartialEq)]
+        struct TradeWithQuote {
+            sym: String,
+            price: f64,
+            bid: f64,
+            ask: f64,
+        }
+
+        impl KdbAsofJoinDeserialize for TradeWithQuote {
+            fn from_aj_row(
+                left: Row<'_>,
+                right: Row<'_>,
+                interner: &mut SymbolInterner,
+            ) -> Result<(NanoTime, Self), KdbError> {
+                let time = left.get_timestamp(0)?;
+                Ok((
+                    time,
+                    TradeWithQuote {
+                        sym: left.get_sym(1, interner)?.to_string(),
+                        price: left.get(2)?.get_float()?,
+                        bid: right.get(0)?.get_float()?,
+                        ask: right.get(1)?.get_float()?,
+                    },
+                ))
+            }
+        }
+
+        // The mock server authenticates against an account file holding the
+        // SHA1 of the password.
+        let accounts =
+            std::env::temp_dir().join(format!("wingfoil_kdb_aj_accounts_{}", std::process::id()));
+        std::fs::write(
+            &accounts,
+            "wingfoil:e5e9fa1ba31ecd1ae84f75caaa474f3a663f05f4\n",
+        )
+        .unwrap();
+        // SAFETY: only kdb-plus-fixed's acceptor reads this variable.
+        unsafe { std::env::set_var("KDBPLUS_ACCOUNT_FILE", &accounts) };
+
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let start = NanoTime::from_kdb_timestamp(1_000_000_000_000);
+        let second = 1_000_000_000;
+        let trade_times = vec![1_001_000_000_000, 1_002_000_000_000];
+        let server = serve_mock_aj(port, trade_times.clone());
+        std::thread::sleep(Duration::from_millis(200));
+
+        let connection =
+            KdbConnection::new("127.0.0.1", port).with_credentials("wingfoil", "secret");
+        let config = KdbAsofJoinConfig::new("trades", "quotes", &["sym"], "time");
+        let joined =
+            kdb_aj::<TradeWithQuote>(connection, config, Duration::from_secs(10), None).collect();
+        joined
+            .run(
+                RunMode::HistoricalFrom(start),
+                RunFor::Duration(Duration::from_secs(10)),
+            )
+            .unwrap();
+
+        let rows: Vec<(NanoTime, TradeWithQuote)> = joined
+            .peek_value()
+            .into_iter()
+            .flat_map(|burst| {
+                let time = burst.time;
+                burst.value.into_iter().map(move |row| (time, row))
+            })
+            .collect();
+        let expected_row = TradeWithQuote {
+            sym: "AAPL".to_string(),
+            price: 101.5,
+            bid: 101.0,
+            ask: 102.0,
+        };
+        assert_eq!(
+            rows,
+            vec![
+                (start + NanoTime::new(second), expected_row.clone()),
+                (start + NanoTime::new(2 * second), expected_row),
+            ]
+        );
+
+        let queries = server.join().unwrap();
+        assert_eq!(queries.len(), 2);
+        assert_eq!(queries[0], "count cols trades");
+        assert!(
+            queries[1].starts_with("aj[`sym`time; select from trades where time >= "),
+            "unexpected query: {}",
+            queries[1]
+        );
+        std::fs::remove_file(accounts).unwrap();
+    }
 }
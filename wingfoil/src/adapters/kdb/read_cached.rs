@@ -77,21 +77,13 @@ where
     produce_async(
         move |ctx| {
             let start_time = ctx.start_time;
-            let end_time_result = ctx.end_time();
+            let end_time = ctx.end_time();
 
             async move {
-                // `compute_validated_time_slices` consumes `end_time_result`; capture the
-                // concrete bound first (NanoTime is Copy) so the per-slice clamp below can
-                // reference `end_time`. Validation guarantees it is present (bounded, non-MAX).
-                let end_time_bound = end_time_result.as_ref().ok().copied();
-                let slices = compute_validated_time_slices(
-                    "kdb_read_cached",
-                    start_time,
-                    end_time_result,
-                    period,
-                )?;
-                let end_time = end_time_bound
-                    .expect("compute_validated_time_slices accepted a bounded end_time");
+                let slices =
+                    compute_validated_time_slices("kdb_read_cached", start_time, end_time, period)?;
+                // An unbounded run keeps every row up to the end of the table.
+                let end_time = end_time.unwrap_or(NanoTime::MAX);
 
                 tokio::fs::create_dir_all(&cache_config.folder).await?;
                 let cache = FileCache::<T>::new(cache_config);
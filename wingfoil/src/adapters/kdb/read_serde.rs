@@ -0,0 +1,509 @@
+//! Serde-based KDB+ reads, for record types without a hand-written
+//! [`KdbDeserialize`](super::KdbDeserialize) impl.
+
+use super::KdbConnection;
+use super::read::{Row, read_sliced};
+use crate::types::*;
+use kdb_plus_fixed::ipc::K;
+use kdb_plus_fixed::qtype;
+use serde::de::value::StrDeserializer;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+use serde::forward_to_deserialize_any;
+use std::fmt;
+use std::rc::Rc;
+
+/// Like [`kdb_read`](super::kdb_read) but deserializes each row with serde,
+/// so a record only needs `#[derive(Deserialize)]` rather than a
+/// [`KdbDeserialize`](super::KdbDeserialize) impl.
+///
+/// Struct fields are matched to columns by name, ignoring ASCII case; a
+/// field with no column fails the read with the list of available columns.
+/// The time of each row is read from the timestamp column `time_col`.  Values
+/// map to serde as follows:
+///
+/// * longs, ints, shorts, floats, reals, booleans and bytes to the
+///   matching primitive, and dates, times and timespans to their raw `i32` or
+///   `i64` value;
+/// * timestamps to their raw KDB `i64`, or, read as a `u64` such as a
+///   [`NanoTime`] field, to nanoseconds since the Unix epoch;
+/// * symbols to strings, [`Sym`](super::Sym) or unit enum variants;
+/// * strings (char lists) to strings, and other nested lists to sequences;
+/// * nulls to `None` for `Option` fields.
+///
+/// The [`KdbDeserialize`](super::KdbDeserialize) path remains the fast one:
+/// this resolves columns by name for every row and allocates each symbol
+/// rather than interning it.
+/// ```ignore
+/// #[derive(Debug, Clone, Default, serde::Deserialize)]
+/// struct Trade {
+///     time: NanoTime,
+///     sym: Sym,
+///     price: f64,
+///     qty: i64,
+/// }
+///
+/// kdb_read_serde::<Trade>(
+///     conn,
+///     std::time::Duration::from_secs(3600),
+///     |(t0, t1), date, _| query(t0, t1, date),
+///     "time",
+///     None,
+/// );
+/// ```
+#[must_use]
+pub fn kdb_read_serde<T>(
+    connection: KdbConnection,
+    period: std::time::Duration,
+    query_fn: impl FnMut((NanoTime, NanoTime), i32, usize) -> String + Send + 'static,
+    time_col: impl Into<String>,
+    buffer_size: Option<usize>,
+) -> Rc<dyn Stream<Burst<T>>>
+where
+    T: Element + Send + DeserializeOwned + 'static,
+{
+    let time_col = time_col.into();
+    read_sliced(
+        "kdb_read_serde",
+        connection,
+        period,
+        query_fn,
+        buffer_size,
+        move |row, columns, _| from_row(row, columns, &time_col),
+    )
+}
+
+/// Why a row could not be deserialized.
+#[derive(Debug)]
+struct DeError(String);
+
+impl fmt::Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "kdb_read_serde: {}", self.0)
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<M: fmt::Display>(msg: M) -> Self {
+        DeError(msg.to_string())
+    }
+}
+
+/// The index of the column called `name`, ignoring ASCII case.
+fn column_index(columns: &[String], name: &str) -> Result<usize, DeError> {
+    columns
+        .iter()
+        .position(|column| column.eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            DeError(format!(
+                "no column `{name}`, available columns: {}",
+                columns.join(", ")
+            ))
+        })
+}
+
+/// Deserializes `row` with its time read from `time_col`.
+fn from_row<T: DeserializeOwned>(
+    row: Row<'_>,
+    columns: &[String],
+    time_col: &str,
+) -> Result<(NanoTime, T), DeError> {
+    let time = row
+        .get_timestamp(column_index(columns, time_col)?)
+        .map_err(|e| DeError(format!("time column `{time_col}`: {e}")))?;
+    let value = T::deserialize(RowDeserializer { row, columns })?;
+    Ok((time, value))
+}
+
+/// Deserializes a row as a struct or map of its columns.
+struct RowDeserializer<'a> {
+    row: Row<'a>,
+    columns: &'a [String],
+}
+
+impl<'de> de::Deserializer<'de> for RowDeserializer<'_> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        let entries = self.columns.iter().map(String::as_str).zip(0..).collect();
+        visitor.visit_map(ColumnsAccess::new(self.row, entries))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        let entries = fields
+            .iter()
+            .map(|field| Ok((*field, column_index(self.columns, field)?)))
+            .collect::<Result<_, DeError>>()?;
+        visitor.visit_map(ColumnsAccess::new(self.row, entries))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct enum identifier ignored_any
+    }
+}
+
+/// The named columns of a row, as map entries.
+struct ColumnsAccess<'a> {
+    row: Row<'a>,
+    entries: std::vec::IntoIter<(&'a str, usize)>,
+    /// The entry whose key was just read.
+    current: Option<(&'a str, usize)>,
+}
+
+impl<'a> ColumnsAccess<'a> {
+    fn new(row: Row<'a>, entries: Vec<(&'a str, usize)>) -> Self {
+        Self {
+            row,
+            entries: entries.into_iter(),
+            current: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for ColumnsAccess<'_> {
+    type Error = DeError;
+
+    fn next_key_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, DeError> {
+        self.current = self.entries.next();
+        match self.current {
+            Some((name, _)) => seed.deserialize(StrDeserializer::new(name)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<S::Value, DeError> {
+        let (name, col) = self
+            .current
+            .take()
+            .ok_or_else(|| DeError("value read before its key".to_string()))?;
+        let list = self
+            .row
+            .column(col)
+            .ok_or_else(|| DeError(format!("column `{name}` is missing from the row")))?;
+        seed.deserialize(ValueDeserializer {
+            list,
+            index: self.row.index(),
+        })
+        .map_err(|e| DeError(format!("column `{name}`: {}", e.0)))
+    }
+}
+
+/// Deserializes the element at `index` of a KDB list.
+struct ValueDeserializer<'a> {
+    list: &'a K,
+    index: usize,
+}
+
+impl<'a> ValueDeserializer<'a> {
+    fn element<T: 'static>(&self) -> Result<&'a T, DeError> {
+        let values = self
+            .list
+            .as_vec::<T>()
+            .map_err(|e| DeError(e.to_string()))?;
+        values.get(self.index).ok_or_else(|| {
+            DeError(format!(
+                "index {} out of bounds for length {}",
+                self.index,
+                values.len()
+            ))
+        })
+    }
+
+    fn is_null(&self) -> bool {
+        match self.list.get_type() {
+            qtype::LONG_LIST | qtype::TIMESTAMP_LIST | qtype::TIMESPAN_LIST => {
+                self.element::<i64>().is_ok_and(|v| *v == i64::MIN)
+            }
+            qtype::INT_LIST | qtype::DATE_LIST | qtype::TIME_LIST => {
+                self.element::<i32>().is_ok_and(|v| *v == i32::MIN)
+            }
+            qtype::SHORT_LIST => self.element::<i16>().is_ok_and(|v| *v == i16::MIN),
+            qtype::FLOAT_LIST => self.element::<f64>().is_ok_and(|v| v.is_nan()),
+            qtype::REAL_LIST => self.element::<f32>().is_ok_and(|v| v.is_nan()),
+            qtype::SYMBOL_LIST => self.element::<String>().is_ok_and(String::is_empty),
+            _ => false,
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'_> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self.list.get_type() {
+            qtype::BOOL_LIST => visitor.visit_bool(*self.element::<bool>()?),
+            qtype::BYTE_LIST => visitor.visit_u8(*self.element::<u8>()?),
+            qtype::SHORT_LIST => visitor.visit_i16(*self.element::<i16>()?),
+            qtype::INT_LIST | qtype::DATE_LIST | qtype::TIME_LIST => {
+                visitor.visit_i32(*self.element::<i32>()?)
+            }
+            qtype::LONG_LIST | qtype::TIMESTAMP_LIST | qtype::TIMESPAN_LIST => {
+                visitor.visit_i64(*self.element::<i64>()?)
+            }
+            qtype::REAL_LIST => visitor.visit_f32(*self.element::<f32>()?),
+            qtype::FLOAT_LIST => visitor.visit_f64(*self.element::<f64>()?),
+            qtype::SYMBOL_LIST => visitor.visit_str(self.element::<String>()?),
+            // a char column: one char per row
+            qtype::STRING => {
+                let chars = self.list.as_string().map_err(|e| DeError(e.to_string()))?;
+                let char = chars.chars().nth(self.index).ok_or_else(|| {
+                    DeError(format!(
+                        "index {} out of bounds for length {}",
+                        self.index,
+                        chars.len()
+                    ))
+                })?;
+                visitor.visit_char(char)
+            }
+            qtype::COMPOUND_LIST => {
+                let item = self.element::<K>()?;
+                match item.get_type() {
+                    qtype::STRING => {
+                        visitor.visit_str(item.as_string().map_err(|e| DeError(e.to_string()))?)
+                    }
+                    qtype::COMPOUND_LIST..=qtype::TIME_LIST => visitor.visit_seq(ListAccess {
+                        list: item,
+                        index: 0,
+                    }),
+                    other => Err(DeError(format!(
+                        "unsupported list element of qtype {other}"
+                    ))),
+                }
+            }
+            other => Err(DeError(format!("unsupported column of qtype {other}"))),
+        }
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        if self.list.get_type() == qtype::TIMESTAMP_LIST {
+            let time = NanoTime::from_kdb_timestamp(*self.element::<i64>()?);
+            visitor.visit_u64(time.into())
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        if self.is_null() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        if self.list.get_type() == qtype::SYMBOL_LIST {
+            let variant: StrDeserializer<DeError> =
+                self.element::<String>()?.as_str().into_deserializer();
+            visitor.visit_enum(variant)
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+/// The elements of a nested list.
+struct ListAccess<'a> {
+    list: &'a K,
+    index: usize,
+}
+
+impl<'de> SeqAccess<'de> for ListAccess<'_> {
+    type Error = DeError;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, DeError> {
+        if self.index >= self.list.len() {
+            return Ok(None);
+        }
+        let value = seed.deserialize(ValueDeserializer {
+            list: self.list,
+            index: self.index,
+        })?;
+        self.index += 1;
+        Ok(Some(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.list.len() - self.index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::kdb::{KdbExt, Sym, SymbolInterner};
+    use kdb_plus_fixed::qattribute::NONE;
+    use serde::Deserialize;
+
+    /// `([] Time; sym; price; legs; venue; note)` with two rows, the second
+    /// with a null price.
+    fn trades() -> K {
+        let names = ["Time", "sym", "price", "legs", "venue", "note"];
+        K::new_dictionary(
+            K::new_symbol_list(names.iter().map(|s| s.to_string()).collect(), NONE),
+            K::new_compound_list(vec![
+                K::new_timestamp_list(
+                    [1_000, 2_000]
+                        .map(|t| NanoTime::from_kdb_timestamp(t).into())
+                        .to_vec(),
+                    NONE,
+                ),
+                K::new_symbol_list(vec!["AAPL".to_string(), "MSFT".to_string()], NONE),
+                K::new_float_list(vec![1.5, f64::NAN], NONE),
+                K::new_compound_list(vec![
+                    K::new_long_list(vec![1, 2], NONE),
+                    K::new_compound_list(vec![]),
+                ]),
+                K::new_symbol_list(vec!["Lit".to_string(), "Dark".to_string()], NONE),
+                K::new_compound_list(vec![
+                    K::new_string("first".to_string(), NONE),
+                    K::new_string("".to_string(), NONE),
+                ]),
+            ]),
+        )
+        .unwrap()
+        .flip()
+        .unwrap()
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Venue {
+        Lit,
+        Dark,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Trade {
+        time: NanoTime,
+        #[serde(rename = "SYM")]
+        sym: Sym,
+        price: Option<f64>,
+        legs: Vec<i64>,
+        venue: Venue,
+        note: String,
+    }
+
+    fn read<T: DeserializeOwned>(table: &K, time_col: &str) -> Result<Vec<(NanoTime, T)>, DeError> {
+        let columns = table.column_names().unwrap();
+        let rows = table.rows().unwrap();
+        rows.iter()
+            .map(|row| from_row(row, &columns, time_col))
+            .collect()
+    }
+
+    #[test]
+    fn fields_match_columns_ignoring_case() {
+        let trades: Vec<(NanoTime, Trade)> = read(&trades(), "time").unwrap();
+        let mut syms = SymbolInterner::default();
+        let first = NanoTime::from_kdb_timestamp(1_000);
+        let second = NanoTime::from_kdb_timestamp(2_000);
+        assert_eq!(
+            trades,
+            vec![
+                (
+                    first,
+                    Trade {
+                        time: first,
+                        sym: syms.intern("AAPL"),
+                        price: Some(1.5),
+                        legs: vec![1, 2],
+                        venue: Venue::Lit,
+                        note: "first".to_string(),
+                    }
+                ),
+                (
+                    second,
+                    Trade {
+                        time: second,
+                        sym: syms.intern("MSFT"),
+                        price: None,
+                        legs: vec![],
+                        venue: Venue::Dark,
+                        note: String::new(),
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn timestamps_read_as_raw_kdb_longs() {
+        #[derive(Deserialize)]
+        struct Raw {
+            time: i64,
+        }
+        let raw: Vec<(NanoTime, Raw)> = read(&trades(), "TIME").unwrap();
+        let times: Vec<i64> = raw.iter().map(|(_, raw)| raw.time).collect();
+        assert_eq!(times, vec![1_000, 2_000]);
+    }
+
+    #[test]
+    fn missing_column_lists_available_columns() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Quote {
+            sym: String,
+            bid: f64,
+        }
+        let err = read::<Quote>(&trades(), "time").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "kdb_read_serde: no column `bid`, available columns: Time, sym, price, legs, venue, note"
+        );
+        let err = read::<Quote>(&trades(), "when").unwrap_err();
+        assert!(err.to_string().contains("no column `when`"), "{err}");
+    }
+
+    #[test]
+    fn type_mismatch_names_the_column() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Bad {
+            sym: f64,
+        }
+        let err = read::<Bad>(&trades(), "time").unwrap_err().to_string();
+        assert!(
+            err.starts_with("kdb_read_serde: column `sym`: invalid type: string"),
+            "{err}"
+        );
+    }
+}
@@ -6,6 +6,8 @@ pub mod aeron;
 pub mod augurs;
 #[cfg(feature = "kdb")]
 pub mod cache;
+#[cfg(feature = "codec")]
+pub mod codec;
 /// Shared helpers reusable across I/O adapters (e.g. the out-of-window row
 /// filter for historical reads). Always compiled so any adapter can use it
 /// without touching feature gates.
@@ -34,6 +36,10 @@ pub mod postgres;
 pub mod prometheus;
 #[cfg(feature = "redis")]
 pub mod redis;
+#[cfg(feature = "shmem")]
+pub mod shmem;
+#[cfg(feature = "socket")]
+pub mod socket;
 /// Streaming statistics operators (EWMA, weighted moments, rolling windows).
 /// Pure-Rust with no external service, so it is always compiled; bring
 /// [`statistics::StatisticsOperators`] into scope with
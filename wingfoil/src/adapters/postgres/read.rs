@@ -68,8 +68,8 @@ pub fn postgres_timestamp(time: NanoTime) -> String {
 
 /// Read a time-partitioned PostgreSQL table, one query per time slice.
 ///
-/// The run's `[start, end)` window (from `RunMode::HistoricalFrom` + `RunFor::Duration`)
-/// is split into contiguous, half-open slices of length `period`. `query_fn` is called
+/// The run's `[start, end)` window (from `RunMode::HistoricalFrom` + `RunFor::Duration`,
+/// or up to the present for an unbounded `RunFor`) is split into contiguous, half-open slices of length `period`. `query_fn` is called
 /// once per slice with `((t0, t1), date, iteration)` and must return a SQL query filtering
 /// on `time >= t0 AND time < t1`, ordered by time. Rows are streamed on-graph as
 /// `Burst<T>` in time order; a non-monotonic timestamp aborts the run.
@@ -88,7 +88,8 @@ pub fn postgres_timestamp(time: NanoTime) -> String {
 ///
 /// # Requirements
 /// - `RunMode::HistoricalFrom` with a non-zero start time.
-/// - `RunFor::Duration` (not `Forever`/`Cycles`) so the slice set is bounded.
+/// - `RunFor::Duration` to stop at `start + duration`; any other `RunFor` reads the
+///   whole table from `start`, one query per slice up to the present.
 #[must_use]
 pub fn postgres_read<T>(
     connection: impl Into<PostgresConnection>,
@@ -102,24 +103,15 @@ where
     produce_async(
         move |ctx| {
             let start_time = ctx.start_time;
-            let end_time_result = ctx.end_time();
+            let end_time = ctx.end_time();
             let connection = connection;
             let mut query_fn = query_fn;
 
             async move {
-                // `compute_validated_time_slices` consumes `end_time_result`; capture the
-                // concrete bound first (NanoTime is Copy) so emitted rows can be clamped to
-                // the run's `[start_time, end_time)` window below. Validation guarantees the
-                // value is present (bounded, non-MAX) by the time we unwrap it.
-                let end_time_bound = end_time_result.as_ref().ok().copied();
-                let slices = compute_validated_time_slices(
-                    "postgres_read",
-                    start_time,
-                    end_time_result,
-                    period,
-                )?;
-                let end_time = end_time_bound
-                    .expect("compute_validated_time_slices accepted a bounded end_time");
+                let slices =
+                    compute_validated_time_slices("postgres_read", start_time, end_time, period)?;
+                // An unbounded run keeps every row up to the end of the table.
+                let end_time = end_time.unwrap_or(NanoTime::MAX);
 
                 let (client, conn) = tokio_postgres::connect(&connection.conn_str, NoTls)
                     .await
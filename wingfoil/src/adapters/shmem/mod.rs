@@ -0,0 +1,84 @@
+//! Same-host, single-producer / single-consumer IPC over a memory-mapped
+//! file.
+//!
+//! [`shmem_pub`](ShmemPub::shmem_pub) creates a ring buffer in a file
+//! (ideally on a tmpfs such as `/dev/shm`) and writes each tick to it as a
+//! bincode-encoded frame; [`shmem_sub`] maps the same file from another
+//! process or thread and streams the values back out.  Delivery is ordered
+//! and lossless: a publisher that gets more than a ring's worth ahead of its
+//! subscriber waits for space, and fails the graph if none frees up.
+//!
+//! Real-time only.  Exactly one publisher and one subscriber per ring.
+//!
+//! ```ignore
+//! // process A
+//! ticker(Duration::from_millis(1)).count().shmem_pub("/dev/shm/counts");
+//! // process B
+//! let counts = shmem_sub::<u64>("/dev/shm/counts");
+//! ```
+
+mod read;
+mod ring;
+mod write;
+
+pub use read::*;
+pub use write::*;
+
+/// Default ring size in bytes, used by [`shmem_pub`](ShmemPub::shmem_pub).
+pub const DEFAULT_CAPACITY: usize = 1 << 20;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::{NodeOperators, StreamOperators};
+    use crate::{RunFor, RunMode, ticker};
+    use std::time::Duration;
+
+    fn ring_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("wingfoil_shmem_{name}_{}", std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn round_trip_between_threads_is_ordered() {
+        let path = ring_path("round_trip");
+        let publisher = {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                // Give the subscriber a head start so it has to wait for the ring.
+                std::thread::sleep(Duration::from_millis(50));
+                ticker(Duration::from_millis(1))
+                    .count()
+                    .shmem_pub_with_capacity(&path, 256)
+                    .run(RunMode::RealTime, RunFor::Cycles(200))
+            })
+        };
+        let received = shmem_sub::<u64>(&path).collect();
+        received
+            .run(RunMode::RealTime, RunFor::Duration(Duration::from_secs(2)))
+            .unwrap();
+        publisher.join().unwrap().unwrap();
+        let values: Vec<u64> = received
+            .peek_value()
+            .into_iter()
+            .flat_map(|burst| burst.value)
+            .collect();
+        assert_eq!(values, (1..=200).collect::<Vec<u64>>());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn publisher_rejects_historical_mode() {
+        let path = ring_path("historical");
+        let result = ticker(Duration::from_millis(1))
+            .count()
+            .shmem_pub(&path)
+            .run(
+                RunMode::HistoricalFrom(crate::NanoTime::ZERO),
+                RunFor::Cycles(1),
+            );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use super::ring::ShmRing;
+use crate::channel::{ChannelSender, Message};
+use crate::nodes::receiver::ReceiverStream;
+use crate::{Burst, Element, IntoStream, Stream};
+use derive_new::new;
+use serde::de::DeserializeOwned;
+
+/// Empty polls to busy-spin through before yielding the thread.
+const SPIN_LIMIT: u32 = 10_000;
+/// How often to look for the ring while no publisher has created it.
+const OPEN_RETRY: Duration = Duration::from_millis(1);
+
+#[derive(new)]
+struct ShmemSubscriber<T: Element + Send> {
+    path: PathBuf,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Element + Send + DeserializeOwned> ShmemSubscriber<T> {
+    fn run(&self, channel_sender: ChannelSender<T>, stop: Arc<AtomicBool>) -> anyhow::Result<()> {
+        let mut ring = loop {
+            if stop.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+            match ShmRing::open(&self.path)? {
+                Some(ring) => break ring,
+                None => std::thread::sleep(OPEN_RETRY),
+            }
+        };
+        let mut buf = Vec::new();
+        let mut idle = 0u32;
+        loop {
+            if !ring.try_pop(&mut buf) {
+                if stop.load(Ordering::Relaxed) {
+                    return Ok(());
+                }
+                idle = idle.saturating_add(1);
+                if idle < SPIN_LIMIT {
+                    std::hint::spin_loop();
+                } else {
+                    std::thread::yield_now();
+                }
+                continue;
+            }
+            idle = 0;
+            let msg: Message<T> = bincode::deserialize(&buf)
+                .unwrap_or_else(|err| Message::Error(Arc::new(err.into())));
+            match msg {
+                Message::RealtimeValue(v) => {
+                    channel_sender.send_message(Message::RealtimeValue(v))?;
+                }
+                Message::EndOfStream => {
+                    channel_sender.send_message(Message::EndOfStream)?;
+                    return Ok(());
+                }
+                Message::Error(err) => {
+                    channel_sender.send_message(Message::Error(err))?;
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Stream values written by [`shmem_pub`](super::ShmemPub::shmem_pub) to the
+/// ring at `path`.  The subscriber polls on a dedicated thread, waiting for
+/// the publisher to create the ring if it does not exist yet.
+///
+/// Ticks with each burst of received values.
+pub fn shmem_sub<T: Element + Send + DeserializeOwned>(path: &str) -> Rc<dyn Stream<Burst<T>>> {
+    let subscriber = ShmemSubscriber::new(PathBuf::from(path));
+    ReceiverStream::new(move |s, stop| subscriber.run(s, stop), true).into_stream()
+}
@@ -0,0 +1,160 @@
+//! Single-producer / single-consumer byte ring laid out in a memory-mapped
+//! file.
+//!
+//! ```text
+//! [0..8)      magic        written last by the creator
+//! [8..16)     capacity     size of the data region, a power of two
+//! [64..72)    head         bytes ever written, producer-owned
+//! [128..136)  tail         bytes ever read, consumer-owned
+//! [192..)     data         `capacity` bytes of u32-length-prefixed frames
+//! ```
+//!
+//! `head` and `tail` sit on separate cache lines so the two sides don't
+//! false-share.  Both only ever grow; positions are taken modulo `capacity`
+//! and a frame may wrap around the end of the data region.
+
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::Context;
+use memmap2::MmapMut;
+
+const MAGIC: u64 = u64::from_le_bytes(*b"WFSHMEM1");
+const MAGIC_OFFSET: usize = 0;
+const CAPACITY_OFFSET: usize = 8;
+const HEAD_OFFSET: usize = 64;
+const TAIL_OFFSET: usize = 128;
+const HEADER_LEN: usize = 192;
+const LEN_PREFIX: usize = size_of::<u32>();
+
+pub(super) struct ShmRing {
+    mmap: MmapMut,
+    capacity: usize,
+}
+
+impl ShmRing {
+    /// Creates a fresh, empty ring at `path`, replacing any existing file.
+    /// The ring is initialised under a temporary name and renamed into place,
+    /// so a subscriber never maps a half-initialised file.
+    pub fn create(path: &Path, capacity: usize) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            capacity.is_power_of_two() && capacity >= 64,
+            "shmem capacity must be a power of two of at least 64 bytes, got {capacity}"
+        );
+        let tmp = path.with_extension("tmp");
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&tmp)
+            .with_context(|| format!("failed to create {}", tmp.display()))?;
+        file.set_len((HEADER_LEN + capacity) as u64)?;
+        // SAFETY: the file was just created by us and is only ever mapped by
+        // the single publisher and single subscriber of this ring.
+        let mut mmap = unsafe { MmapMut::map_mut(&file)? };
+        mmap[CAPACITY_OFFSET..CAPACITY_OFFSET + 8]
+            .copy_from_slice(&(capacity as u64).to_le_bytes());
+        let ring = Self { mmap, capacity };
+        ring.atomic(HEAD_OFFSET).store(0, Ordering::Relaxed);
+        ring.atomic(TAIL_OFFSET).store(0, Ordering::Relaxed);
+        ring.atomic(MAGIC_OFFSET).store(MAGIC, Ordering::Release);
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("failed to move ring into place at {}", path.display()))?;
+        Ok(ring)
+    }
+
+    /// Maps the ring at `path`, or returns `None` if no publisher has
+    /// created it yet.
+    pub fn open(path: &Path) -> anyhow::Result<Option<Self>> {
+        let file = match OpenOptions::new().read(true).write(true).open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("failed to open {}", path.display())),
+        };
+        let len = file.metadata()?.len() as usize;
+        if len < HEADER_LEN {
+            return Ok(None);
+        }
+        // SAFETY: see `create`.
+        let mmap = unsafe { MmapMut::map_mut(&file)? };
+        let mut ring = Self { mmap, capacity: 0 };
+        if ring.atomic(MAGIC_OFFSET).load(Ordering::Acquire) != MAGIC {
+            return Ok(None);
+        }
+        let mut capacity = [0u8; 8];
+        capacity.copy_from_slice(&ring.mmap[CAPACITY_OFFSET..CAPACITY_OFFSET + 8]);
+        ring.capacity = u64::from_le_bytes(capacity) as usize;
+        anyhow::ensure!(
+            ring.capacity.is_power_of_two() && len == HEADER_LEN + ring.capacity,
+            "{} is not a valid shmem ring",
+            path.display()
+        );
+        Ok(Some(ring))
+    }
+
+    fn atomic(&self, offset: usize) -> &AtomicU64 {
+        // SAFETY: every offset passed here is 8-byte aligned within the
+        // page-aligned mapping, and the slot is only accessed atomically.
+        unsafe { &*(self.mmap.as_ptr().add(offset) as *const AtomicU64) }
+    }
+
+    /// Appends `frame`, returning `false` if there is not room for it yet.
+    pub fn try_push(&mut self, frame: &[u8]) -> anyhow::Result<bool> {
+        let needed = LEN_PREFIX + frame.len();
+        anyhow::ensure!(
+            needed <= self.capacity,
+            "frame of {} bytes does not fit in a {} byte shmem ring",
+            frame.len(),
+            self.capacity
+        );
+        let head = self.atomic(HEAD_OFFSET).load(Ordering::Relaxed);
+        let tail = self.atomic(TAIL_OFFSET).load(Ordering::Acquire);
+        if self.capacity - (head - tail) as usize >= needed {
+            let len = u32::try_from(frame.len())?.to_le_bytes();
+            self.copy_in(head, &len);
+            self.copy_in(head + LEN_PREFIX as u64, frame);
+            self.atomic(HEAD_OFFSET)
+                .store(head + needed as u64, Ordering::Release);
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Moves the oldest frame into `buf`, returning `false` if the ring is
+    /// empty.
+    pub fn try_pop(&mut self, buf: &mut Vec<u8>) -> bool {
+        let tail = self.atomic(TAIL_OFFSET).load(Ordering::Relaxed);
+        let head = self.atomic(HEAD_OFFSET).load(Ordering::Acquire);
+        if tail == head {
+            return false;
+        }
+        let mut len = [0u8; LEN_PREFIX];
+        self.copy_out(tail, &mut len);
+        let len = u32::from_le_bytes(len) as usize;
+        buf.resize(len, 0);
+        self.copy_out(tail + LEN_PREFIX as u64, buf);
+        self.atomic(TAIL_OFFSET)
+            .store(tail + (LEN_PREFIX + len) as u64, Ordering::Release);
+        true
+    }
+
+    fn copy_in(&mut self, pos: u64, bytes: &[u8]) {
+        let start = pos as usize & (self.capacity - 1);
+        let first = bytes.len().min(self.capacity - start);
+        let data = &mut self.mmap[HEADER_LEN..];
+        data[start..start + first].copy_from_slice(&bytes[..first]);
+        data[..bytes.len() - first].copy_from_slice(&bytes[first..]);
+    }
+
+    fn copy_out(&self, pos: u64, bytes: &mut [u8]) {
+        let start = pos as usize & (self.capacity - 1);
+        let first = bytes.len().min(self.capacity - start);
+        let data = &self.mmap[HEADER_LEN..];
+        bytes[..first].copy_from_slice(&data[start..start + first]);
+        let rest = bytes.len() - first;
+        bytes[first..].copy_from_slice(&data[..rest]);
+    }
+}
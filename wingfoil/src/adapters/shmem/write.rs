@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use super::DEFAULT_CAPACITY;
+use super::ring::ShmRing;
+use crate::channel::Message;
+use crate::{Element, GraphState, IntoNode, MutableNode, Node, RunMode, Stream, UpStreams};
+use serde::Serialize;
+
+/// How long the publisher waits for the subscriber to free up space before
+/// failing the graph.
+const FULL_TIMEOUT: Duration = Duration::from_secs(1);
+
+struct ShmemSenderNode<T: Element + Send + Serialize> {
+    src: Rc<dyn Stream<T>>,
+    path: PathBuf,
+    capacity: usize,
+    ring: Option<ShmRing>,
+    buf: Vec<u8>,
+}
+
+impl<T: Element + Send + Serialize> ShmemSenderNode<T> {
+    fn send(&mut self, msg: &Message<T>) -> anyhow::Result<()> {
+        let ring = self
+            .ring
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("missing shmem ring"))?;
+        self.buf.clear();
+        bincode::serialize_into(&mut self.buf, msg)?;
+        let deadline = Instant::now() + FULL_TIMEOUT;
+        while !ring.try_push(&self.buf)? {
+            if Instant::now() > deadline {
+                anyhow::bail!(
+                    "shmem ring {} full for {FULL_TIMEOUT:?}, subscriber is not keeping up",
+                    self.path.display()
+                );
+            }
+            std::thread::yield_now();
+        }
+        Ok(())
+    }
+}
+
+impl<T: Element + Send + Serialize> MutableNode for ShmemSenderNode<T> {
+    fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+        self.send(&Message::RealtimeValue(self.src.peek_value()))?;
+        Ok(true)
+    }
+
+    fn upstreams(&self) -> UpStreams {
+        UpStreams::new(vec![self.src.clone().as_node()], vec![])
+    }
+
+    fn start(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
+        if state.run_mode() != RunMode::RealTime {
+            anyhow::bail!("shmem nodes only support real-time mode");
+        }
+        self.ring = Some(ShmRing::create(&self.path, self.capacity)?);
+        Ok(())
+    }
+
+    fn stop(&mut self, _: &mut GraphState) -> anyhow::Result<()> {
+        if self.ring.is_none() {
+            return Ok(());
+        }
+        self.send(&Message::EndOfStream)
+    }
+}
+
+/// Fluent API for publishing any stream over a shared-memory ring.
+pub trait ShmemPub<T: Element + Send> {
+    /// Create a ring of [DEFAULT_CAPACITY] bytes at `path` and write every
+    /// tick to it.
+    fn shmem_pub(&self, path: &str) -> Rc<dyn Node>;
+    /// As [`shmem_pub`](Self::shmem_pub), with a ring of `capacity` bytes
+    /// (a power of two).
+    fn shmem_pub_with_capacity(&self, path: &str, capacity: usize) -> Rc<dyn Node>;
+}
+
+impl<T: Element + Send + Serialize> ShmemPub<T> for Rc<dyn Stream<T>> {
+    fn shmem_pub(&self, path: &str) -> Rc<dyn Node> {
+        self.shmem_pub_with_capacity(path, DEFAULT_CAPACITY)
+    }
+
+    fn shmem_pub_with_capacity(&self, path: &str, capacity: usize) -> Rc<dyn Node> {
+        ShmemSenderNode {
+            src: self.clone(),
+            path: PathBuf::from(path),
+            capacity,
+            ring: None,
+            buf: Vec::new(),
+        }
+        .into_node()
+    }
+}
@@ -0,0 +1,182 @@
+//! Wire framing for the socket adapter.
+//!
+//! A [`Framer`] turns the raw byte stream read off a socket into discrete
+//! frames and back again. Three framers are provided:
+//!
+//! - [`LengthPrefixed`] — each frame is preceded by a 4-byte big-endian length
+//! - [`NewlineDelimited`] — each frame is terminated by `\n` (a trailing `\r` is stripped)
+//! - [`Raw`] — no framing; every read is emitted as-is and writes are passed through
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+/// Splits an inbound byte stream into frames and encodes outbound frames.
+///
+/// Implementations are cloned so the inbound (decode) and outbound (encode)
+/// sides of a connection each own a copy; a framer should therefore carry
+/// configuration only, with any partial-frame state living in the buffer.
+pub trait Framer: Clone + Send + 'static {
+    /// Remove one complete frame from the front of `buf`, or return `None` if
+    /// `buf` does not yet hold a complete frame. Called repeatedly after every
+    /// read until it returns `None`.
+    fn decode(&mut self, buf: &mut BytesMut) -> anyhow::Result<Option<Bytes>>;
+
+    /// Append `frame` to `dst` in wire format.
+    fn encode(&mut self, frame: &[u8], dst: &mut BytesMut) -> anyhow::Result<()>;
+}
+
+/// Frames preceded by a 4-byte big-endian length header.
+///
+/// Inbound frames longer than `max_frame_len` are rejected so a corrupt or
+/// hostile header can't make the reader buffer unbounded data.
+#[derive(Debug, Clone, Copy)]
+pub struct LengthPrefixed {
+    pub max_frame_len: usize,
+}
+
+impl LengthPrefixed {
+    /// Default cap on inbound frame length: 16 MiB.
+    pub const DEFAULT_MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+    const HEADER_LEN: usize = 4;
+}
+
+impl Default for LengthPrefixed {
+    fn default() -> Self {
+        Self {
+            max_frame_len: Self::DEFAULT_MAX_FRAME_LEN,
+        }
+    }
+}
+
+impl Framer for LengthPrefixed {
+    fn decode(&mut self, buf: &mut BytesMut) -> anyhow::Result<Option<Bytes>> {
+        if buf.len() < Self::HEADER_LEN {
+            return Ok(None);
+        }
+        let len = u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]) as usize;
+        if len > self.max_frame_len {
+            anyhow::bail!(
+                "inbound frame of {len} bytes exceeds max_frame_len of {}",
+                self.max_frame_len
+            );
+        }
+        if buf.len() < Self::HEADER_LEN + len {
+            buf.reserve(Self::HEADER_LEN + len - buf.len());
+            return Ok(None);
+        }
+        buf.advance(Self::HEADER_LEN);
+        Ok(Some(buf.split_to(len).freeze()))
+    }
+
+    fn encode(&mut self, frame: &[u8], dst: &mut BytesMut) -> anyhow::Result<()> {
+        let len = u32::try_from(frame.len())
+            .map_err(|_| anyhow::anyhow!("outbound frame of {} bytes is too long", frame.len()))?;
+        dst.reserve(Self::HEADER_LEN + frame.len());
+        dst.put_u32(len);
+        dst.put_slice(frame);
+        Ok(())
+    }
+}
+
+/// Frames terminated by `\n`. The terminator (and a preceding `\r`, if any)
+/// is stripped from inbound frames and `\n` is appended to outbound ones.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NewlineDelimited;
+
+impl Framer for NewlineDelimited {
+    fn decode(&mut self, buf: &mut BytesMut) -> anyhow::Result<Option<Bytes>> {
+        let Some(pos) = buf.iter().position(|&b| b == b'\n') else {
+            return Ok(None);
+        };
+        let mut line = buf.split_to(pos + 1);
+        line.truncate(pos);
+        if line.last() == Some(&b'\r') {
+            line.truncate(pos - 1);
+        }
+        Ok(Some(line.freeze()))
+    }
+
+    fn encode(&mut self, frame: &[u8], dst: &mut BytesMut) -> anyhow::Result<()> {
+        dst.reserve(frame.len() + 1);
+        dst.put_slice(frame);
+        dst.put_u8(b'\n');
+        Ok(())
+    }
+}
+
+/// No framing: each chunk read off the socket is emitted as one frame, and
+/// outbound frames are written verbatim. Chunk boundaries depend on the
+/// network, so this suits protocols that do their own framing downstream.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Raw;
+
+impl Framer for Raw {
+    fn decode(&mut self, buf: &mut BytesMut) -> anyhow::Result<Option<Bytes>> {
+        if buf.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(buf.split().freeze()))
+    }
+
+    fn encode(&mut self, frame: &[u8], dst: &mut BytesMut) -> anyhow::Result<()> {
+        dst.put_slice(frame);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_all<F: Framer>(framer: &mut F, buf: &mut BytesMut) -> Vec<Bytes> {
+        let mut frames = Vec::new();
+        while let Some(frame) = framer.decode(buf).unwrap() {
+            frames.push(frame);
+        }
+        frames
+    }
+
+    #[test]
+    fn length_prefixed_round_trips_and_waits_for_partial_frames() {
+        let mut framer = LengthPrefixed::default();
+        let mut wire = BytesMut::new();
+        framer.encode(b"hello", &mut wire).unwrap();
+        framer.encode(b"", &mut wire).unwrap();
+        framer.encode(b"world", &mut wire).unwrap();
+
+        // Feed all but the last byte: the third frame is incomplete.
+        let mut buf = BytesMut::from(&wire[..wire.len() - 1]);
+        assert_eq!(decode_all(&mut framer, &mut buf), vec!["hello", ""]);
+        buf.put_u8(wire[wire.len() - 1]);
+        assert_eq!(decode_all(&mut framer, &mut buf), vec!["world"]);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn length_prefixed_rejects_oversized_frames() {
+        let mut framer = LengthPrefixed { max_frame_len: 4 };
+        let mut buf = BytesMut::new();
+        framer.encode(b"too long", &mut buf).unwrap();
+        assert!(framer.decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn newline_delimited_strips_terminators() {
+        let mut framer = NewlineDelimited;
+        let mut buf = BytesMut::from(&b"one\ntwo\r\nthr"[..]);
+        assert_eq!(decode_all(&mut framer, &mut buf), vec!["one", "two"]);
+        assert_eq!(&buf[..], b"thr");
+
+        let mut wire = BytesMut::new();
+        framer.encode(b"four", &mut wire).unwrap();
+        assert_eq!(&wire[..], b"four\n");
+    }
+
+    #[test]
+    fn raw_emits_whatever_was_read() {
+        let mut framer = Raw;
+        let mut buf = BytesMut::from(&b"abc"[..]);
+        assert_eq!(decode_all(&mut framer, &mut buf), vec!["abc"]);
+        assert!(buf.is_empty());
+    }
+}
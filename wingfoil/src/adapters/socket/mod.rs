@@ -0,0 +1,427 @@
+//! Socket adapter — a low-level TCP / TLS framing layer.
+//!
+//! [`tcp_connect`] opens a single client connection and exposes it to the
+//! graph as a pair of halves:
+//!
+//! - an inbound `Burst<Bytes>` stream, one [`Bytes`] per frame decoded by a
+//!   pluggable [`Framer`]
+//! - a [`SocketWriter`] whose [`send`](SocketWriter::send) attaches an
+//!   outbound `Burst<Bytes>` stream, encoding each frame with the same framer
+//!
+//! The two halves share one connection, driven by a single task on the
+//! `produce_async` runtime; the outbound node built on `consume_async` only
+//! forwards frames to that task. TLS is provided by rustls with the `ring`
+//! crypto provider, matching the FIX and web adapters.
+//!
+//! [`ws_server`] is the server-side counterpart for WebSocket clients: it
+//! broadcasts a stream as JSON text frames, pings every client on a
+//! heartbeat and drops those that stop answering, reporting joins and
+//! departures as a [`WsConnectionEvent`] stream.
+//!
+//! There is no reconnect: connect failures, read timeouts, I/O errors and a
+//! peer closing mid-frame terminate the producer and propagate to the graph.
+//! A clean close by the peer simply ends the inbound stream.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use wingfoil::adapters::socket::*;
+//! use wingfoil::*;
+//!
+//! let config = SocketConfig::new("example.com", 443)
+//!     .tls(TlsConfig::default())
+//!     .read_timeout(std::time::Duration::from_secs(30));
+//! let (inbound, writer) = tcp_connect(config, NewlineDelimited);
+//!
+//! let requests = constant(burst![bytes::Bytes::from_static(b"PING")]);
+//! let sink = writer.send(&requests);
+//! let printer = inbound.collapse().for_each(|frame, _| println!("{frame:?}"));
+//!
+//! Graph::new(vec![sink, printer], RunMode::RealTime, RunFor::Forever)
+//!     .run()
+//!     .unwrap();
+//! ```
+
+mod framer;
+mod ws;
+
+pub use framer::*;
+pub use ws::{WsConnectionEvent, WsServerConfig, ws_server};
+
+use crate::nodes::{FutStream, RunParams, StreamOperators, produce_async};
+use crate::types::*;
+use bytes::{Bytes, BytesMut};
+use futures::StreamExt;
+use rustls::pki_types::{CertificateDer, ServerName};
+use rustls::{ClientConfig, RootCertStore};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_rustls::TlsConnector;
+
+/// Initial capacity of the inbound read buffer.
+const READ_BUFFER_SIZE: usize = 8 * 1024;
+
+/// TLS settings for a [`SocketConfig`].
+#[derive(Debug, Clone, Default)]
+pub struct TlsConfig {
+    /// Name sent for SNI and verified against the server certificate.
+    /// Defaults to [`SocketConfig::host`] when `None`.
+    pub server_name: Option<String>,
+    /// Trust anchors for the server certificate. When empty, the Mozilla
+    /// root bundle from `webpki-roots` is used.
+    pub root_certs: Vec<CertificateDer<'static>>,
+}
+
+impl TlsConfig {
+    /// Verify the server against `server_name` instead of the connect host.
+    pub fn server_name(mut self, server_name: impl Into<String>) -> Self {
+        self.server_name = Some(server_name.into());
+        self
+    }
+
+    /// Trust `cert` (e.g. a self-signed or private CA certificate). Once any
+    /// root is added the `webpki-roots` bundle is no longer used.
+    pub fn root_cert(mut self, cert: CertificateDer<'static>) -> Self {
+        self.root_certs.push(cert);
+        self
+    }
+
+    fn client_config(&self) -> anyhow::Result<ClientConfig> {
+        let mut roots = RootCertStore::empty();
+        if self.root_certs.is_empty() {
+            roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        } else {
+            for cert in &self.root_certs {
+                roots.add(cert.clone())?;
+            }
+        }
+        Ok(
+            ClientConfig::builder_with_provider(rustls::crypto::ring::default_provider().into())
+                .with_safe_default_protocol_versions()?
+                .with_root_certificates(roots)
+                .with_no_client_auth(),
+        )
+    }
+}
+
+/// Where and how [`tcp_connect`] connects.
+#[derive(Debug, Clone)]
+pub struct SocketConfig {
+    pub host: String,
+    pub port: u16,
+    /// Wrap the connection in TLS when set.
+    pub tls: Option<TlsConfig>,
+    /// Fail the connection if no bytes arrive for this long.
+    pub read_timeout: Option<Duration>,
+    /// Enable TCP keepalive, probing after this much idle time.
+    pub keepalive: Option<Duration>,
+}
+
+impl SocketConfig {
+    /// A plain TCP connection to `host:port` with no timeout or keepalive.
+    pub fn new(host: impl Into<String>, port: u16) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            tls: None,
+            read_timeout: None,
+            keepalive: None,
+        }
+    }
+
+    pub fn tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    pub fn read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = Some(read_timeout);
+        self
+    }
+
+    pub fn keepalive(mut self, keepalive: Duration) -> Self {
+        self.keepalive = Some(keepalive);
+        self
+    }
+}
+
+/// Outbound half of a [`tcp_connect`] connection.
+///
+/// Dropping it without calling [`send`](SocketWriter::send) leaves the
+/// connection read-only.
+pub struct SocketWriter {
+    tx: mpsc::UnboundedSender<Bytes>,
+}
+
+impl SocketWriter {
+    /// Write every frame of `upstream` to the connection.
+    #[must_use]
+    pub fn send(self, upstream: &Rc<dyn Stream<Burst<Bytes>>>) -> Rc<dyn Node> {
+        let tx = self.tx;
+        upstream.consume_async(Box::new(
+            move |_ctx: RunParams, mut source: Pin<Box<dyn FutStream<Burst<Bytes>>>>| async move {
+                while let Some((_time, burst)) = source.next().await {
+                    for frame in burst {
+                        tx.send(frame)
+                            .map_err(|_| anyhow::anyhow!("socket connection closed"))?;
+                    }
+                }
+                Ok(())
+            },
+        ))
+    }
+}
+
+trait AsyncIo: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> AsyncIo for T {}
+
+async fn connect(config: &SocketConfig) -> anyhow::Result<Box<dyn AsyncIo>> {
+    let (host, port) = (config.host.as_str(), config.port);
+    let stream = TcpStream::connect((host, port))
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to connect to {host}:{port}: {e}"))?;
+    stream.set_nodelay(true)?;
+    if let Some(idle) = config.keepalive {
+        let keepalive = socket2::TcpKeepalive::new().with_time(idle);
+        socket2::SockRef::from(&stream).set_tcp_keepalive(&keepalive)?;
+    }
+    let Some(tls) = &config.tls else {
+        return Ok(Box::new(stream));
+    };
+    let name = tls.server_name.as_deref().unwrap_or(host);
+    let server_name: ServerName<'static> = name
+        .to_string()
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("invalid TLS server name: {name}"))?;
+    let connector = TlsConnector::from(Arc::new(tls.client_config()?));
+    let stream = connector
+        .connect(server_name, stream)
+        .await
+        .map_err(|e| anyhow::anyhow!("TLS handshake with {host}:{port} failed: {e}"))?;
+    Ok(Box::new(stream))
+}
+
+/// What woke the connection task.
+enum Event {
+    Read(std::io::Result<usize>),
+    Write(Option<Bytes>),
+    Timeout,
+}
+
+/// Connect to `config.host:config.port`, framing traffic with `framer`.
+///
+/// Returns the inbound frame stream and the [`SocketWriter`] for outbound
+/// frames. The connection is opened when the graph starts. Inbound frames
+/// are stamped with wall-clock `NanoTime::now()`, so this is designed for
+/// `RunMode::RealTime`.
+///
+/// Emits `Burst<Bytes>`. Use `.collapse()` for single-frame processing.
+#[must_use]
+pub fn tcp_connect<F: Framer>(
+    config: SocketConfig,
+    framer: F,
+) -> (Rc<dyn Stream<Burst<Bytes>>>, SocketWriter) {
+    let (tx, mut rx) = mpsc::unbounded_channel::<Bytes>();
+    let inbound = produce_async(
+        move |_ctx: RunParams| async move {
+            let mut io = connect(&config).await?;
+            let mut decoder = framer.clone();
+            let mut encoder = framer;
+            Ok(async_stream::try_stream! {
+                let mut inbound = BytesMut::with_capacity(READ_BUFFER_SIZE);
+                let mut outbound = BytesMut::new();
+                let mut outbound_open = true;
+                let mut deadline = config
+                    .read_timeout
+                    .map(|timeout| tokio::time::Instant::now() + timeout);
+                loop {
+                    let event = tokio::select! {
+                        read = io.read_buf(&mut inbound) => Event::Read(read),
+                        frame = rx.recv(), if outbound_open => Event::Write(frame),
+                        _ = tokio::time::sleep_until(deadline.unwrap_or_else(tokio::time::Instant::now)),
+                            if deadline.is_some() => Event::Timeout,
+                    };
+                    match event {
+                        Event::Read(read) => {
+                            if read? == 0 {
+                                if !inbound.is_empty() {
+                                    Err(anyhow::anyhow!(
+                                        "connection closed with {} bytes of a partial frame",
+                                        inbound.len()
+                                    ))?;
+                                }
+                                break;
+                            }
+                            if let Some(timeout) = config.read_timeout {
+                                deadline = Some(tokio::time::Instant::now() + timeout);
+                            }
+                            let now = NanoTime::now();
+                            while let Some(frame) = decoder.decode(&mut inbound)? {
+                                yield (now, frame);
+                            }
+                        }
+                        Event::Write(Some(frame)) => {
+                            encoder.encode(&frame, &mut outbound)?;
+                            io.write_all_buf(&mut outbound).await?;
+                            io.flush().await?;
+                        }
+                        Event::Write(None) => outbound_open = false,
+                        Event::Timeout => Err(anyhow::anyhow!(
+                            "no data received for {:?}",
+                            config.read_timeout.unwrap_or_default()
+                        ))?,
+                    }
+                }
+            })
+        },
+        None,
+    );
+    (inbound, SocketWriter { tx })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::*;
+    use crate::nodes::*;
+    use tokio::net::TcpListener;
+    use tokio_rustls::TlsAcceptor;
+
+    /// Accept one connection on an ephemeral port and echo everything back,
+    /// optionally behind TLS. Returns the bound port.
+    fn echo_server(tls: Option<TlsAcceptor>) -> u16 {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            rt.block_on(async move {
+                let listener = TcpListener::from_std(listener).unwrap();
+                let (stream, _) = listener.accept().await.unwrap();
+                match tls {
+                    Some(acceptor) => echo(acceptor.accept(stream).await.unwrap()).await,
+                    None => echo(stream).await,
+                }
+            });
+        });
+        port
+    }
+
+    async fn echo(stream: impl AsyncRead + AsyncWrite + Unpin) {
+        let (mut reader, mut writer) = tokio::io::split(stream);
+        let _ = tokio::io::copy(&mut reader, &mut writer).await;
+    }
+
+    /// Send `frames` through `framer` to an echo server and return every
+    /// inbound frame, concatenated across bursts.
+    fn round_trip<F: Framer>(
+        config: SocketConfig,
+        framer: F,
+        frames: &[&'static [u8]],
+    ) -> Vec<Bytes> {
+        let (inbound, writer) = tcp_connect(config, framer);
+        let outbound = constant(frames.iter().map(|f| Bytes::from_static(f)).collect());
+        let sink = writer.send(&outbound);
+        let received = inbound.collect();
+        Graph::new(
+            vec![sink, received.clone().as_node()],
+            RunMode::RealTime,
+            RunFor::Duration(Duration::from_millis(500)),
+        )
+        .run()
+        .unwrap();
+        received
+            .peek_value()
+            .into_iter()
+            .flat_map(|burst| burst.value)
+            .collect()
+    }
+
+    const FRAMES: [&[u8]; 3] = [b"alpha", b"beta", b"gamma"];
+
+    #[test]
+    fn length_prefixed_echo() {
+        let port = echo_server(None);
+        let frames = round_trip(
+            SocketConfig::new("127.0.0.1", port),
+            LengthPrefixed::default(),
+            &FRAMES,
+        );
+        assert_eq!(frames, FRAMES);
+    }
+
+    #[test]
+    fn newline_delimited_echo() {
+        let port = echo_server(None);
+        let config = SocketConfig::new("127.0.0.1", port).keepalive(Duration::from_secs(10));
+        let frames = round_trip(config, NewlineDelimited, &FRAMES);
+        assert_eq!(frames, FRAMES);
+    }
+
+    #[test]
+    fn raw_echo() {
+        let port = echo_server(None);
+        let frames = round_trip(SocketConfig::new("127.0.0.1", port), Raw, &FRAMES);
+        // Chunk boundaries are up to the network; only the bytes are preserved.
+        assert_eq!(frames.concat(), FRAMES.concat());
+    }
+
+    #[test]
+    fn tls_echo_with_self_signed_cert() {
+        let issued = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert: CertificateDer<'static> = issued.cert.der().clone();
+        let key =
+            rustls::pki_types::PrivateKeyDer::try_from(issued.key_pair.serialize_der()).unwrap();
+        let server_config = rustls::ServerConfig::builder_with_provider(
+            rustls::crypto::ring::default_provider().into(),
+        )
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert.clone()], key)
+        .unwrap();
+        let port = echo_server(Some(TlsAcceptor::from(Arc::new(server_config))));
+
+        let config = SocketConfig::new("localhost", port).tls(TlsConfig::default().root_cert(cert));
+        let frames = round_trip(config, LengthPrefixed::default(), &FRAMES);
+        assert_eq!(frames, FRAMES);
+    }
+
+    #[test]
+    fn read_timeout_fails_the_graph() {
+        // The echo server never sends unprompted, so with nothing written the
+        // read deadline must expire.
+        let port = echo_server(None);
+        let config = SocketConfig::new("127.0.0.1", port).read_timeout(Duration::from_millis(50));
+        let (inbound, _writer) = tcp_connect(config, Raw);
+        let result = inbound.run(
+            RunMode::RealTime,
+            RunFor::Duration(Duration::from_millis(500)),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn connect_failure_fails_the_graph() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let (inbound, _writer) = tcp_connect(SocketConfig::new("127.0.0.1", port), Raw);
+        let result = inbound.run(
+            RunMode::RealTime,
+            RunFor::Duration(Duration::from_millis(500)),
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,353 @@
+//! `ws_server` — broadcast a stream to WebSocket clients with heartbeat
+//! based stale-connection detection.
+
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use serde::Serialize;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::nodes::{FutStream, RunParams, StreamOperators, produce_async};
+use crate::queue::ValueAt;
+use crate::types::*;
+
+/// Per-client outbound queue depth. Frames for a client whose queue is
+/// full are dropped so a slow client never back-pressures the graph.
+const CLIENT_OUTBOUND_CAPACITY: usize = 1024;
+
+/// Heartbeat and admission settings for [`ws_server`].
+#[derive(Debug, Clone)]
+pub struct WsServerConfig {
+    /// How often each client is sent a ping.
+    pub heartbeat_interval: Duration,
+    /// A client that sends nothing (pongs included) for this long is
+    /// disconnected. Also bounds how long a single send may block.
+    pub client_timeout: Duration,
+    /// Connections beyond this many are closed straight after the handshake.
+    pub max_clients: usize,
+}
+
+impl Default for WsServerConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval: Duration::from_secs(10),
+            client_timeout: Duration::from_secs(30),
+            max_clients: 1024,
+        }
+    }
+}
+
+/// A client joining or leaving the [`ws_server`] broadcast list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WsConnectionEvent {
+    Connected(SocketAddr),
+    Disconnected(SocketAddr),
+}
+
+impl Default for WsConnectionEvent {
+    fn default() -> Self {
+        WsConnectionEvent::Disconnected(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)))
+    }
+}
+
+type Clients = Arc<Mutex<HashMap<SocketAddr, mpsc::Sender<Message>>>>;
+
+/// Serve `upstream` to WebSocket clients connecting to `bind_addr`.
+///
+/// Every upstream value is sent to all connected clients as a JSON text
+/// frame, `{"value":..,"time":..}` as for [ValueAt], the same record the
+/// csv adapter's `ndjson_write_partitioned` writes per line. Each client is pinged every
+/// [`heartbeat_interval`](WsServerConfig::heartbeat_interval); one that goes
+/// quiet for [`client_timeout`](WsServerConfig::client_timeout) is dropped
+/// from the broadcast list, so a peer that vanished without a close frame
+/// doesn't linger.
+///
+/// Returns the broadcast node and a stream of [`WsConnectionEvent`]s. The
+/// listener is bound when the graph starts and closed when it stops; a bind
+/// failure fails the graph. Designed for `RunMode::RealTime`.
+#[must_use]
+pub fn ws_server<T: Element + Send + Serialize>(
+    bind_addr: impl Into<String>,
+    config: WsServerConfig,
+    upstream: &Rc<dyn Stream<T>>,
+) -> (Rc<dyn Node>, Rc<dyn Stream<Burst<WsConnectionEvent>>>) {
+    let bind_addr = bind_addr.into();
+    let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+    let node = upstream.consume_async(Box::new(
+        move |_ctx: RunParams, mut source: Pin<Box<dyn FutStream<T>>>| async move {
+            let listener = TcpListener::bind(&bind_addr)
+                .await
+                .map_err(|e| anyhow::anyhow!("ws_server: bind to {bind_addr} failed: {e}"))?;
+            let clients = Clients::default();
+            let acceptor = tokio::spawn(accept_loop(listener, config, clients.clone(), events_tx));
+            let result = async {
+                while let Some((time, value)) = source.next().await {
+                    let text = serde_json::to_string(&ValueAt::new(value, time))?;
+                    broadcast(&clients, Message::Text(text));
+                }
+                Ok(())
+            }
+            .await;
+            // Dropping the accept loop's JoinSet aborts every client task.
+            acceptor.abort();
+            result
+        },
+    ));
+    let events = produce_async(
+        move |_ctx: RunParams| async move {
+            Ok(async_stream::stream! {
+                while let Some(event) = events_rx.recv().await {
+                    yield Ok((NanoTime::now(), event));
+                }
+            })
+        },
+        None,
+    );
+    (node, events)
+}
+
+fn broadcast(clients: &Clients, message: Message) {
+    let guard = clients.lock().expect("ws_server clients lock poisoned");
+    for (addr, tx) in guard.iter() {
+        if let Err(mpsc::error::TrySendError::Full(_)) = tx.try_send(message.clone()) {
+            log::warn!("ws_server: client {addr} outbound full, dropping frame");
+        }
+    }
+}
+
+async fn accept_loop(
+    listener: TcpListener,
+    config: WsServerConfig,
+    clients: Clients,
+    events: mpsc::UnboundedSender<WsConnectionEvent>,
+) {
+    let mut tasks = JoinSet::new();
+    loop {
+        let (stream, addr) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                log::warn!("ws_server: accept failed: {e}");
+                continue;
+            }
+        };
+        while tasks.try_join_next().is_some() {}
+        tasks.spawn(serve_client(
+            stream,
+            addr,
+            config.clone(),
+            clients.clone(),
+            events.clone(),
+        ));
+    }
+}
+
+/// Per-connection task: forwards broadcast frames, pings on every heartbeat
+/// and drops the client once it has been silent for `client_timeout`.
+async fn serve_client(
+    stream: TcpStream,
+    addr: SocketAddr,
+    config: WsServerConfig,
+    clients: Clients,
+    events: mpsc::UnboundedSender<WsConnectionEvent>,
+) {
+    let ws = match tokio_tungstenite::accept_async(stream).await {
+        Ok(ws) => ws,
+        Err(e) => {
+            log::debug!("ws_server: handshake with {addr} failed: {e}");
+            return;
+        }
+    };
+    let (tx, mut rx) = mpsc::channel(CLIENT_OUTBOUND_CAPACITY);
+    {
+        let mut guard = clients.lock().expect("ws_server clients lock poisoned");
+        if guard.len() >= config.max_clients {
+            log::warn!("ws_server: rejecting {addr}, max_clients reached");
+            return;
+        }
+        guard.insert(addr, tx);
+    }
+    let _ = events.send(WsConnectionEvent::Connected(addr));
+
+    let (mut sink, mut inbound) = ws.split();
+    let mut heartbeat = tokio::time::interval(config.heartbeat_interval);
+    let mut last_seen = Instant::now();
+    loop {
+        let outbound = tokio::select! {
+            message = rx.recv() => match message {
+                Some(message) => message,
+                None => break,
+            },
+            message = inbound.next() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {
+                    last_seen = Instant::now();
+                    continue;
+                }
+            },
+            _ = heartbeat.tick() => {
+                if last_seen.elapsed() > config.client_timeout {
+                    log::info!("ws_server: client {addr} timed out");
+                    break;
+                }
+                Message::Ping(Vec::new())
+            }
+        };
+        match tokio::time::timeout(config.client_timeout, sink.send(outbound)).await {
+            Ok(Ok(())) => {}
+            _ => break,
+        }
+    }
+
+    clients
+        .lock()
+        .expect("ws_server clients lock poisoned")
+        .remove(&addr);
+    let _ = events.send(WsConnectionEvent::Disconnected(addr));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::*;
+    use crate::nodes::*;
+
+    fn free_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    /// Run `ws_server` over a counting ticker for `run_for` while `client`
+    /// runs on its own thread, returning the connection events and the
+    /// client's result.
+    fn run_with_client<R: Send + 'static>(
+        config: WsServerConfig,
+        run_for: Duration,
+        client: impl FnOnce(u16) -> R + Send + 'static,
+    ) -> (Vec<WsConnectionEvent>, R) {
+        let port = free_port();
+        let client = std::thread::spawn(move || client(port));
+        let source = ticker(Duration::from_millis(10)).count();
+        let (node, events) = ws_server(format!("127.0.0.1:{port}"), config, &source);
+        let events = events.collect();
+        Graph::new(
+            vec![node, events.clone().as_node()],
+            RunMode::RealTime,
+            RunFor::Duration(run_for),
+        )
+        .run()
+        .unwrap();
+        let events = events
+            .peek_value()
+            .into_iter()
+            .flat_map(|burst| burst.value)
+            .collect();
+        (events, client.join().unwrap())
+    }
+
+    /// Connect, retrying until the graph has bound the listener.
+    async fn connect(
+        port: u16,
+    ) -> tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<TcpStream>> {
+        let url = format!("ws://127.0.0.1:{port}");
+        for _ in 0..100 {
+            if let Ok((ws, _)) = tokio_tungstenite::connect_async(url.as_str()).await {
+                return ws;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("could not connect to {url}");
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn clients_receive_broadcast_values() {
+        let (events, received) = run_with_client(
+            WsServerConfig::default(),
+            Duration::from_millis(500),
+            |port| {
+                block_on(async move {
+                    let mut ws = connect(port).await;
+                    let mut received = Vec::new();
+                    while received.len() < 3 {
+                        if let Some(Ok(Message::Text(text))) = ws.next().await {
+                            let at: ValueAt<u64> = serde_json::from_str(&text).unwrap();
+                            received.push(at.value);
+                        }
+                    }
+                    ws.close(None).await.unwrap();
+                    received
+                })
+            },
+        );
+        assert!(received.windows(2).all(|w| w[0] < w[1]), "{received:?}");
+        assert!(matches!(events[..], [WsConnectionEvent::Connected(_), ..]));
+    }
+
+    #[test]
+    fn silent_client_is_removed_after_timeout() {
+        // The client completes the handshake and then never reads, so it
+        // never answers a ping and never sends a close frame. It outlives the
+        // graph, so only the heartbeat can have removed it.
+        let config = WsServerConfig {
+            heartbeat_interval: Duration::from_millis(20),
+            client_timeout: Duration::from_millis(100),
+            ..Default::default()
+        };
+        let (events, ()) = run_with_client(config, Duration::from_millis(600), |port| {
+            block_on(async move {
+                let ws = connect(port).await;
+                tokio::time::sleep(Duration::from_millis(800)).await;
+                drop(ws);
+            })
+        });
+        let [
+            WsConnectionEvent::Connected(a),
+            WsConnectionEvent::Disconnected(b),
+        ] = events[..]
+        else {
+            panic!("unexpected events {events:?}");
+        };
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn max_clients_rejects_extra_connections() {
+        let config = WsServerConfig {
+            max_clients: 1,
+            ..Default::default()
+        };
+        let (events, ()) = run_with_client(config, Duration::from_millis(500), |port| {
+            block_on(async move {
+                let _first = connect(port).await;
+                let mut second = connect(port).await;
+                // The server drops the second connection right after the handshake.
+                assert!(!matches!(second.next().await, Some(Ok(Message::Text(_)))));
+                tokio::time::sleep(Duration::from_millis(300)).await;
+            })
+        });
+        let connected = events
+            .iter()
+            .filter(|e| matches!(e, WsConnectionEvent::Connected(_)))
+            .count();
+        assert_eq!(connected, 1);
+    }
+}
@@ -14,11 +14,14 @@
 //!   [RollingExtremeStream]) — over a count window: `mean`/`var`/`std` (either
 //!   weighting), `sum`, and `min`/`max` (monotonic deque), each maintained in
 //!   O(1) per tick by updating as samples enter and leave the window.
+//! * **Drawdown** ([DrawdownStream]) — running high-water mark, drawdown from
+//!   it and the worst drawdown so far, maintained together by one O(1) node.
 //! * **Recompute-per-tick** ([WindowStream]) — `median` (any window) and the
 //!   time-windowed `sum`/`min`/`max`, which have no cheap incremental form here.
 //!
 //! All operators consume `T: Element + ToPrimitive` and emit `f64`.
 
+use crate::nodes::StreamOperators;
 use crate::types::*;
 
 use num_traits::ToPrimitive;
@@ -132,6 +135,20 @@ pub trait StatisticsOperators<T: Element + ToPrimitive> {
     /// elapsed time.  The first sample seeds the average.
     #[must_use]
     fn ewma(self: &Rc<Self>, span: EwmaSpan) -> Rc<dyn Stream<f64>>;
+    /// Running high-water mark, drawdown and maximum drawdown, emitted together
+    /// as a [Drawdown] on every tick.  The accessors below map out one field.
+    #[must_use]
+    fn drawdown_stats(self: &Rc<Self>) -> Rc<dyn Stream<Drawdown>>;
+    /// Running maximum of the stream (the high-water mark).
+    #[must_use]
+    fn high_water_mark(self: &Rc<Self>) -> Rc<dyn Stream<f64>>;
+    /// Current value minus the high-water mark; always `<= 0`.
+    #[must_use]
+    fn drawdown(self: &Rc<Self>) -> Rc<dyn Stream<f64>>;
+    /// Most negative [`drawdown`](StatisticsOperators::drawdown) seen so far;
+    /// always `<= 0`.
+    #[must_use]
+    fn max_drawdown(self: &Rc<Self>) -> Rc<dyn Stream<f64>>;
 }
 
 impl<T: Element + ToPrimitive + 'static> StatisticsOperators<T> for dyn Stream<T> {
@@ -213,6 +230,22 @@ impl<T: Element + ToPrimitive + 'static> StatisticsOperators<T> for dyn Stream<T
         };
         EwmaStream::new(self.clone(), decay).into_stream()
     }
+
+    fn drawdown_stats(self: &Rc<Self>) -> Rc<dyn Stream<Drawdown>> {
+        DrawdownStream::new(self.clone()).into_stream()
+    }
+
+    fn high_water_mark(self: &Rc<Self>) -> Rc<dyn Stream<f64>> {
+        self.drawdown_stats().map(|d| d.high_water_mark)
+    }
+
+    fn drawdown(self: &Rc<Self>) -> Rc<dyn Stream<f64>> {
+        self.drawdown_stats().map(|d| d.drawdown)
+    }
+
+    fn max_drawdown(self: &Rc<Self>) -> Rc<dyn Stream<f64>> {
+        self.drawdown_stats().map(|d| d.max_drawdown)
+    }
 }
 
 impl<T: Element + ToPrimitive + 'static> dyn Stream<T> {
@@ -628,6 +661,59 @@ impl<T: Element> CumulativeStream<T> {
     }
 }
 
+/// Drawdown state emitted by [`drawdown_stats`](StatisticsOperators::drawdown_stats).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Drawdown {
+    /// The latest sample.
+    pub value: f64,
+    /// Running maximum of the samples.
+    pub high_water_mark: f64,
+    /// `value - high_water_mark`; zero at a new high, negative below it.
+    pub drawdown: f64,
+    /// Most negative `drawdown` seen so far.
+    pub max_drawdown: f64,
+}
+
+/// Tracks the high-water mark, drawdown and maximum drawdown of a stream in a
+/// single node, in O(1) time and memory.  The first sample seeds the high-water
+/// mark, so the drawdown starts at zero.
+pub(crate) struct DrawdownStream<T: Element> {
+    upstream: Rc<dyn Stream<T>>,
+    value: Drawdown,
+    seeded: bool,
+}
+
+#[node(active = [upstream], output = value: Drawdown)]
+impl<T: Element + ToPrimitive> MutableNode for DrawdownStream<T> {
+    fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+        let sample = self.upstream.peek_value().to_f64().unwrap_or(f64::NAN);
+        let high_water_mark = if self.seeded {
+            self.value.high_water_mark.max(sample)
+        } else {
+            sample
+        };
+        let drawdown = sample - high_water_mark;
+        self.value = Drawdown {
+            value: sample,
+            high_water_mark,
+            drawdown,
+            max_drawdown: self.value.max_drawdown.min(drawdown),
+        };
+        self.seeded = true;
+        Ok(true)
+    }
+}
+
+impl<T: Element> DrawdownStream<T> {
+    pub fn new(upstream: Rc<dyn Stream<T>>) -> Self {
+        Self {
+            upstream,
+            value: Drawdown::default(),
+            seeded: false,
+        }
+    }
+}
+
 /// Incremental rolling sum over the most recent `window` samples.
 ///
 /// Maintains a running total: each sample is added on arrival and the evicted
@@ -1330,4 +1416,63 @@ mod tests {
             .unwrap();
         assert!((med.peek_value() - 3.0).abs() < 1e-10);
     }
+
+    // ── drawdown ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn drawdown_tracks_equity_curve() {
+        // Equity curve with a drawdown, a recovery to a new high, a shallower
+        // drawdown and flat segments at and below the high.
+        let curve = [
+            100.0, 110.0, 110.0, 99.0, 88.0, 88.0, 105.0, 120.0, 114.0, 114.0,
+        ];
+        let stats = counter()
+            .map(move |n: u64| curve[n as usize - 1])
+            .drawdown_stats()
+            .collect();
+        stats
+            .run(
+                RunMode::HistoricalFrom(NanoTime::ZERO),
+                RunFor::Cycles(curve.len() as u32),
+            )
+            .unwrap();
+        let expected = [
+            // (high_water_mark, drawdown, max_drawdown)
+            (100.0, 0.0, 0.0),
+            (110.0, 0.0, 0.0),
+            (110.0, 0.0, 0.0),
+            (110.0, -11.0, -11.0),
+            (110.0, -22.0, -22.0),
+            (110.0, -22.0, -22.0),
+            (110.0, -5.0, -22.0),
+            (120.0, 0.0, -22.0),
+            (120.0, -6.0, -22.0),
+            (120.0, -6.0, -22.0),
+        ];
+        let actual: Vec<_> = stats
+            .peek_value()
+            .iter()
+            .map(|d| {
+                let d = d.value;
+                (d.high_water_mark, d.drawdown, d.max_drawdown)
+            })
+            .collect();
+        assert_eq!(actual, expected);
+        let values: Vec<f64> = stats.peek_value().iter().map(|d| d.value.value).collect();
+        assert_eq!(values, curve);
+    }
+
+    #[test]
+    fn drawdown_accessors_map_fields() {
+        // 2, 4, 1, 8
+        let curve = || counter().map(|n: u64| if n == 3 { 1 } else { n * 2 });
+        let run = |s: Rc<dyn Stream<f64>>| {
+            s.run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(4))
+                .unwrap();
+            s.peek_value()
+        };
+        assert_eq!(run(curve().high_water_mark()), 8.0);
+        assert_eq!(run(curve().drawdown()), 0.0);
+        assert_eq!(run(curve().max_drawdown()), -3.0);
+    }
 }
@@ -1,10 +1,17 @@
-use super::{ZeroMqPub, ZmqStatus, zmq_sub};
-use crate::{Graph, Node, NodeOperators, RunFor, RunMode, StreamOperators, ticker};
+use super::{
+    ZeroMqPub, ZmqStatus, pipe_ipc, pipe_zmq, pipe_zmq_with_codec, zmq_sub, zmq_sub_with_codec,
+    zmq_sub_with_snapshot,
+};
+use crate::adapters::codec::{Bincode, Codec, Json};
+use crate::{
+    Burst, Graph, NanoTime, Node, NodeOperators, RunFor, RunMode, Stream, StreamOperators, ValueAt,
+    ticker,
+};
 use log::Level::Info;
 use std::rc::Rc;
 use std::time::Duration;
 
-// --- ZMQ integration tests (ports 5556–5563) ---
+// --- ZMQ integration tests (ports 5556–5568) ---
 
 #[test]
 fn zmq_deserialization_error_propagates() {
@@ -80,6 +87,167 @@ fn zmq_same_thread() {
     .unwrap();
 }
 
+fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+/// A hand-rolled codec: the value's eight little-endian bytes.
+#[derive(Clone)]
+struct RawU64;
+
+impl Codec<u64> for RawU64 {
+    fn version(&self) -> u8 {
+        0x80
+    }
+
+    fn encode(&self, value: &u64, buf: &mut Vec<u8>) -> anyhow::Result<()> {
+        buf.extend_from_slice(&value.to_le_bytes());
+        Ok(())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> anyhow::Result<u64> {
+        Ok(u64::from_le_bytes(bytes.try_into()?))
+    }
+}
+
+/// [zmq_same_thread] with `codec` at both ends.
+fn same_thread_with_codec(codec: impl Codec<u64> + Clone) {
+    _ = env_logger::try_init();
+    let period = Duration::from_millis(50);
+    let port = free_port();
+    let (data, _status) =
+        zmq_sub_with_codec(format!("tcp://127.0.0.1:{port}"), codec.clone()).unwrap();
+    let recv_node = data.collect().finally(|res, _| {
+        let values: Vec<u64> = res.into_iter().flat_map(|item| item.value).collect();
+        assert!(
+            values.len() >= 5,
+            "expected at least 5 items, got {values:?}"
+        );
+        for window in values.windows(2) {
+            assert_eq!(window[1], window[0] + 1, "expected consecutive integers");
+        }
+        Ok(())
+    });
+    let send_node = ticker(period).count().zmq_pub_with_codec(port, (), codec);
+    Graph::new(
+        vec![send_node, recv_node],
+        RunMode::RealTime,
+        RunFor::Duration(period * 10),
+    )
+    .run()
+    .unwrap();
+}
+
+#[test]
+fn zmq_same_thread_bincode_codec() {
+    same_thread_with_codec(Bincode);
+}
+
+#[test]
+fn zmq_same_thread_json_codec() {
+    same_thread_with_codec(Json);
+}
+
+#[test]
+fn zmq_same_thread_custom_codec() {
+    same_thread_with_codec(RawU64);
+}
+
+#[test]
+fn zmq_codec_mismatch_fails_naming_version_byte() {
+    _ = env_logger::try_init();
+    let period = Duration::from_millis(50);
+    let port = free_port();
+    let (data, _status) = zmq_sub_with_codec::<u64>(format!("tcp://127.0.0.1:{port}"), Bincode)
+        .expect("zmq_sub failed");
+    let send_node = ticker(period).count().zmq_pub_with_codec(port, (), Json);
+    let err = Graph::new(
+        vec![send_node, data.as_node()],
+        RunMode::RealTime,
+        RunFor::Duration(Duration::from_secs(2)),
+    )
+    .run()
+    .expect_err("expected a codec mismatch error");
+    let err = format!("{err:?}");
+    assert!(
+        err.contains("version byte 0x02 (json)") && err.contains("expects 0x01 (bincode)"),
+        "expected the error to name both version bytes, got: {err}"
+    );
+}
+
+/// Publishes 1, 2, 3, ... with a snapshot reduced by `reduce`, pausing after
+/// 5 until a subscriber started after the pause has received its first
+/// value.  Returns the subscriber's values.
+fn late_joiner(port: u16, reduce: fn(u64, u64) -> u64) -> Vec<u64> {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+    _ = env_logger::try_init();
+    let published = Arc::new(AtomicU64::new(0));
+    let resume = Arc::new(AtomicBool::new(false));
+    let publisher = {
+        let (published, resume) = (published.clone(), resume.clone());
+        std::thread::spawn(move || {
+            ticker(Duration::from_millis(20))
+                .count()
+                .filter_value(move |_| {
+                    let pass =
+                        published.load(Ordering::SeqCst) < 5 || resume.load(Ordering::SeqCst);
+                    if pass {
+                        published.fetch_add(1, Ordering::SeqCst);
+                    }
+                    pass
+                })
+                .count()
+                .zmq_pub_with_snapshot(port, (), reduce)
+                .run(RunMode::RealTime, RunFor::Duration(Duration::from_secs(3)))
+        })
+    };
+    while published.load(Ordering::SeqCst) < 5 {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let (data, _status) = zmq_sub_with_snapshot::<u64>(format!("tcp://127.0.0.1:{port}")).unwrap();
+    let values = data
+        .map(move |burst| {
+            resume.store(true, Ordering::SeqCst);
+            burst
+        })
+        .collect();
+    values
+        .clone()
+        .run(
+            RunMode::RealTime,
+            RunFor::Duration(Duration::from_millis(1500)),
+        )
+        .unwrap();
+    publisher.join().unwrap().unwrap();
+    values
+        .peek_value()
+        .into_iter()
+        .flat_map(|item| item.value)
+        .collect()
+}
+
+#[test]
+fn zmq_late_joiner_starts_from_latest_value() {
+    let values = late_joiner(5565, |_, latest| latest);
+    assert_eq!(values[..4], [5, 6, 7, 8], "{values:?}");
+    for window in values.windows(2) {
+        assert_eq!(window[1], window[0] + 1, "{values:?}");
+    }
+}
+
+#[test]
+fn zmq_late_joiner_starts_from_reduced_snapshot() {
+    let values = late_joiner(5567, |sum, value| sum + value);
+    assert_eq!(values[..4], [15, 6, 7, 8], "{values:?}");
+}
+
 #[test]
 fn zmq_separate_threads() {
     _ = env_logger::try_init();
@@ -91,7 +259,7 @@ fn zmq_separate_threads() {
         .port();
     let address = format!("tcp://127.0.0.1:{port}");
     let run_for = RunFor::Duration(Duration::from_secs(2));
-    let rf_send = run_for;
+    let rf_send = run_for.clone();
     let rf_rec = run_for;
     let rec = std::thread::spawn(move || receiver(&address).run(RunMode::RealTime, rf_rec));
     let send =
@@ -689,3 +857,66 @@ mod etcd_tests {
         );
     }
 }
+
+// --- pipe_zmq / pipe_ipc ---
+
+type PipeFactory = Box<dyn FnOnce() -> Rc<dyn Stream<Burst<u64>>> + Send>;
+
+/// Runs `send` on this thread and the receiving graph built from `recv` on a
+/// worker thread, both in historical mode, returning what the receiver saw.
+fn run_historical_pipe(send: Rc<dyn Node>, recv: PipeFactory) -> Vec<ValueAt<u64>> {
+    let run_mode = RunMode::HistoricalFrom(NanoTime::ZERO);
+    let worker = std::thread::spawn(move || {
+        let received = recv().collapse().map(|x: u64| x * 10).collect();
+        received.run(run_mode, RunFor::Forever)?;
+        anyhow::Ok(received.peek_value())
+    });
+    send.run(run_mode, RunFor::Duration(Duration::from_secs(1)))
+        .unwrap();
+    worker.join().unwrap().unwrap()
+}
+
+fn piped_source() -> Rc<dyn Stream<u64>> {
+    ticker(Duration::from_millis(100)).count().limit(6)
+}
+
+#[test]
+fn pipe_zmq_historical_is_deterministic() {
+    _ = env_logger::try_init();
+    let expected = piped_source().map(|x| x * 10).collect();
+    expected
+        .run(
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Duration(Duration::from_secs(1)),
+        )
+        .unwrap();
+    for _ in 0..3 {
+        let (send, recv) = pipe_zmq(piped_source(), "tcp://127.0.0.1:5564");
+        let actual = run_historical_pipe(send, Box::new(recv));
+        assert_eq!(actual, expected.peek_value());
+    }
+}
+
+#[test]
+fn pipe_ipc_historical_delivers_all_values() {
+    _ = env_logger::try_init();
+    let path = std::env::temp_dir().join(format!("wingfoil_pipe_{}.ipc", std::process::id()));
+    let (send, recv) = pipe_ipc(piped_source(), path.to_str().unwrap());
+    let values: Vec<u64> = run_historical_pipe(send, Box::new(recv))
+        .into_iter()
+        .map(|v| v.value)
+        .collect();
+    assert_eq!(values, vec![10, 20, 30, 40, 50, 60]);
+}
+
+#[test]
+fn pipe_zmq_with_json_codec_delivers_all_values() {
+    _ = env_logger::try_init();
+    let endpoint = format!("tcp://127.0.0.1:{}", free_port());
+    let (send, recv) = pipe_zmq_with_codec(piped_source(), &endpoint, Json);
+    let values: Vec<u64> = run_historical_pipe(send, Box::new(recv))
+        .into_iter()
+        .map(|v| v.value)
+        .collect();
+    assert_eq!(values, vec![10, 20, 30, 40, 50, 60]);
+}
@@ -6,6 +6,21 @@
 //! - [`zmq_sub`] — subscriber that connects to a ZMQ PUB socket
 //! - [`ZeroMqPub::zmq_pub`] — publisher that binds a ZMQ PUB socket
 //!
+//! and a point-to-point pipe between two graphs, [`pipe_zmq`] (or [`pipe_ipc`]
+//! over a unix domain socket), with the same API as [`pipe_local`](crate::pipe_local).
+//! Unlike pub/sub it works in both run modes and loses no messages.
+//!
+//! Messages are encoded with [`Bincode`](crate::adapters::codec::Bincode)
+//! unless another [`Codec`](crate::adapters::codec::Codec) is passed to
+//! [`ZeroMqPub::zmq_pub_with_codec`], [`zmq_sub_with_codec`] or
+//! [`pipe_zmq_with_codec`]; both ends must use the same codec.
+//!
+//! A subscriber that joins late misses everything published before it
+//! connected.  [`ZeroMqPub::zmq_pub_with_snapshot`] and
+//! [`zmq_sub_with_snapshot`] fix that: the publisher also serves its latest
+//! value, or a reduction over all of them, on the next port up, and the
+//! subscriber starts from it before switching to live values.
+//!
 //! # Setup
 //!
 //! ZMQ is peer-to-peer — no broker process is required. The `zmq` feature
@@ -41,6 +56,18 @@
 //! # Ok::<(), anyhow::Error>(())
 //! ```
 //!
+//! # Piping between graphs
+//!
+//! ```ignore
+//! use wingfoil::adapters::zmq::pipe_zmq;
+//! use wingfoil::*;
+//!
+//! let (send, recv) = pipe_zmq(ticker(Duration::from_millis(100)).count(), "tcp://127.0.0.1:5570");
+//! // Build the receiving graph elsewhere with `recv()`, then run both with the same RunMode.
+//! send.run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(10))?;
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
 //! # etcd-based discovery
 //!
 //! ```ignore
@@ -65,6 +92,7 @@
 //! # Ok::<(), anyhow::Error>(())
 //! ```
 
+mod pipe;
 mod read;
 pub mod registry;
 mod write;
@@ -72,6 +100,7 @@ mod write;
 #[cfg(all(test, feature = "zmq-integration-test"))]
 mod integration_tests;
 
+pub use pipe::*;
 pub use read::*;
 pub use registry::{ZmqHandle, ZmqPubRegistration, ZmqRegistry, ZmqSubConfig};
 pub use write::*;
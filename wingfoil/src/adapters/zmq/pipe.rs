@@ -0,0 +1,156 @@
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::adapters::codec::{Bincode, Codec, decode_message, encode_message};
+use crate::channel::{ChannelSender, Message};
+use crate::{
+    Burst, Element, GraphState, IntoNode, IntoStream, MutableNode, Node, ReceiverStream, Stream,
+    UpStreams,
+};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// How long a closing pipe socket keeps trying to deliver queued messages.
+const LINGER_MS: i32 = 1000;
+
+/// Sending half of a zmq pipe.  Unlike [`zmq_pub`](super::ZeroMqPub::zmq_pub)
+/// it uses a PUSH socket, which queues messages until the receiver connects
+/// rather than dropping them, so every value (and the final
+/// [`Message::EndOfStream`]) is delivered in both run modes.
+struct ZmqPipeSenderNode<T: Element + Send, C: Codec<T>> {
+    src: Rc<dyn Stream<T>>,
+    endpoint: String,
+    codec: C,
+    /// Reused for every message so sending does not allocate.
+    scratch: Vec<u8>,
+    /// The number of values sent so far, stamped on every message.
+    sequence: u64,
+    socket: Option<zmq::Socket>,
+}
+
+impl<T: Element + Send, C: Codec<T>> ZmqPipeSenderNode<T, C> {
+    fn send(&mut self, msg: &Message<T>) -> anyhow::Result<()> {
+        encode_message(&self.codec, self.sequence, msg, &mut self.scratch)?;
+        self.socket
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("missing socket"))?
+            .send(&self.scratch[..], 0)?;
+        Ok(())
+    }
+}
+
+impl<T: Element + Send, C: Codec<T>> MutableNode for ZmqPipeSenderNode<T, C> {
+    fn upstreams(&self) -> UpStreams {
+        UpStreams::new(vec![self.src.clone().as_node()], vec![])
+    }
+
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        self.sequence += 1;
+        self.send(&Message::build(self.src.peek_value(), state))?;
+        Ok(true)
+    }
+
+    fn start(&mut self, _state: &mut GraphState) -> anyhow::Result<()> {
+        let context = zmq::Context::new();
+        let socket = context.socket(zmq::PUSH)?;
+        socket.set_linger(LINGER_MS)?;
+        socket.bind(&self.endpoint)?;
+        self.socket = Some(socket);
+        Ok(())
+    }
+
+    fn stop(&mut self, _state: &mut GraphState) -> anyhow::Result<()> {
+        self.send(&Message::EndOfStream)
+    }
+}
+
+/// Forwards messages from the PULL socket onto the receiver's channel until
+/// the sender signals end-of-stream or the receiving graph stops.
+fn pull<T: Element + Send>(
+    endpoint: &str,
+    codec: &impl Codec<T>,
+    sender: ChannelSender<T>,
+    stop: Arc<AtomicBool>,
+) -> anyhow::Result<()> {
+    let context = zmq::Context::new();
+    let socket = context.socket(zmq::PULL)?;
+    socket.connect(endpoint)?;
+    loop {
+        if stop.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let mut items = [socket.as_poll_item(zmq::POLLIN)];
+        zmq::poll(&mut items, 200)?;
+        if items[0].is_readable() {
+            let res = socket.recv_bytes(0)?;
+            let msg: Message<T> = decode_message(codec, &res)
+                .map_or_else(|err| Message::Error(Arc::new(err)), |(_, msg)| msg);
+            let last = matches!(msg, Message::EndOfStream | Message::Error(_));
+            sender.send_message(msg)?;
+            if last {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Pipes a [Stream] from this graph into another graph, typically in another
+/// process, over a zmq socket at `endpoint` (e.g. `"tcp://127.0.0.1:5570"`).
+///
+/// Same API and semantics as [`pipe_local`](crate::pipe_local): returns the
+/// sending [Node] for this graph, which binds `endpoint`, and a factory that
+/// builds the receiving `Stream<Burst<T>>`, which connects to it.  Values
+/// carry the sender's engine time, so historical runs stay deterministic
+/// across the pipe.  Both graphs must use the same [RunMode](crate::RunMode).
+pub fn pipe_zmq<T: Element + Send + Serialize + DeserializeOwned>(
+    stream: Rc<dyn Stream<T>>,
+    endpoint: &str,
+) -> (
+    Rc<dyn Node>,
+    impl FnOnce() -> Rc<dyn Stream<Burst<T>>> + Send + 'static + use<T>,
+) {
+    pipe_zmq_with_codec(stream, endpoint, Bincode)
+}
+
+/// [`pipe_zmq`], encoding and decoding with `codec` rather than [`Bincode`].
+/// Use an `ipc://` endpoint for a unix domain socket, as [`pipe_ipc`] does.
+pub fn pipe_zmq_with_codec<T: Element + Send, C: Codec<T> + Clone>(
+    stream: Rc<dyn Stream<T>>,
+    endpoint: &str,
+    codec: C,
+) -> (
+    Rc<dyn Node>,
+    impl FnOnce() -> Rc<dyn Stream<Burst<T>>> + Send + 'static + use<T, C>,
+) {
+    let send = ZmqPipeSenderNode {
+        src: stream,
+        endpoint: endpoint.to_string(),
+        codec: codec.clone(),
+        scratch: Vec::new(),
+        sequence: 0,
+        socket: None,
+    }
+    .into_node();
+    let endpoint = endpoint.to_string();
+    let recv = move || {
+        ReceiverStream::new(
+            move |sender, stop| pull(&endpoint, &codec, sender, stop),
+            false,
+        )
+        .into_stream()
+    };
+    (send, recv)
+}
+
+/// [`pipe_zmq`] over a unix domain socket at `path`, for graphs in separate
+/// processes on the same host.
+pub fn pipe_ipc<T: Element + Send + Serialize + DeserializeOwned>(
+    stream: Rc<dyn Stream<T>>,
+    path: &str,
+) -> (
+    Rc<dyn Node>,
+    impl FnOnce() -> Rc<dyn Stream<Burst<T>>> + Send + 'static + use<T>,
+) {
+    pipe_zmq(stream, &format!("ipc://{path}"))
+}
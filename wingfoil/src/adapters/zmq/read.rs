@@ -2,25 +2,66 @@ use std::marker::PhantomData;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
 use super::registry::{ZmqSubConfig, ZmqSubResolution};
 use super::{ZmqEvent, ZmqStatus};
+use crate::adapters::codec::{Bincode, Codec, decode_message};
 use crate::channel::{ChannelSender, Message};
 use crate::{Burst, Element, IntoStream, MapFilterStream, ReceiverStream, Stream};
 use derive_new::new;
+use serde::Serialize;
 use serde::de::DeserializeOwned;
 
 static MONITOR_ID: AtomicUsize = AtomicUsize::new(0);
 const ZMQ_EVENT_CONNECTED: u16 = 0x0001;
 const ZMQ_EVENT_DISCONNECTED: u16 = 0x0200;
 
+/// How long to wait after connecting before asking for a snapshot, so that
+/// our subscription has reached the publisher and no live message published
+/// after the snapshot is missed.  Matches the publisher's own allowance.
+const SUBSCRIPTION_DELAY: Duration = Duration::from_millis(50);
+const SNAPSHOT_TIMEOUT_MS: i32 = 5000;
+
 #[derive(new)]
-struct ZeroMqSubscriber<T: Element + Send> {
+struct ZeroMqSubscriber<T: Element + Send, C: Codec<T>> {
     address: String,
+    /// The publisher's snapshot endpoint, for [zmq_sub_with_snapshot].
+    snapshot: Option<String>,
+    codec: C,
     _phantom: PhantomData<T>,
 }
 
-impl<T: Element + Send + DeserializeOwned> ZeroMqSubscriber<T> {
+impl<T: Element + Send, C: Codec<T>> ZeroMqSubscriber<T, C> {
+    /// Asks the publisher for its snapshot, passes it on, and returns the
+    /// sequence number it covers.  A publisher that has not ticked yet has
+    /// none, so any value it sends is new to us.
+    fn request_snapshot(
+        &self,
+        context: &zmq::Context,
+        endpoint: &str,
+        channel_sender: &ChannelSender<ZmqEvent<T>>,
+    ) -> anyhow::Result<u64> {
+        let socket = context.socket(zmq::REQ)?;
+        socket.set_linger(0)?;
+        socket.set_rcvtimeo(SNAPSHOT_TIMEOUT_MS)?;
+        socket.connect(endpoint)?;
+        socket.send("", 0)?;
+        let reply = socket
+            .recv_bytes(0)
+            .map_err(|err| anyhow::anyhow!("zmq snapshot request to {endpoint} failed: {err}"))?;
+        if reply.is_empty() {
+            return Ok(0);
+        }
+        match decode_message(&self.codec, &reply)? {
+            (sequence, Message::RealtimeValue(value)) => {
+                channel_sender.send_message(Message::RealtimeValue(ZmqEvent::Data(value)))?;
+                Ok(sequence)
+            }
+            (_, msg) => anyhow::bail!("zmq snapshot reply was not a value: {msg:?}"),
+        }
+    }
+
     fn run(
         &self,
         channel_sender: ChannelSender<ZmqEvent<T>>,
@@ -40,15 +81,31 @@ impl<T: Element + Send + DeserializeOwned> ZeroMqSubscriber<T> {
         let monitor = context.socket(zmq::PAIR)?;
         monitor.connect(&monitor_addr)?;
 
+        // With a snapshot endpoint: the sequence number of the latest value
+        // passed on, snapshot included, or None until the snapshot arrives.
+        let mut seen: Option<u64> = None;
+        let mut connected_at: Option<Instant> = None;
+
         loop {
             if stop.load(Ordering::Relaxed) {
                 return Ok(());
             }
+            if let Some(endpoint) = &self.snapshot
+                && seen.is_none()
+                && connected_at.is_some_and(|at| at.elapsed() >= SUBSCRIPTION_DELAY)
+            {
+                seen = Some(self.request_snapshot(&context, endpoint, &channel_sender)?);
+            }
             let mut items = [
                 socket.as_poll_item(zmq::POLLIN),
                 monitor.as_poll_item(zmq::POLLIN),
             ];
-            zmq::poll(&mut items, 200)?;
+            let timeout = if self.snapshot.is_some() && connected_at.is_some() && seen.is_none() {
+                10
+            } else {
+                200
+            };
+            zmq::poll(&mut items, timeout)?;
 
             if items[1].is_readable() {
                 let event_frame = monitor.recv_msg(0)?;
@@ -59,6 +116,7 @@ impl<T: Element + Send + DeserializeOwned> ZeroMqSubscriber<T> {
                     let event_id = u16::from_le_bytes([event_frame[0], event_frame[1]]);
                     match event_id {
                         ZMQ_EVENT_CONNECTED => {
+                            connected_at.get_or_insert_with(Instant::now);
                             channel_sender.send_message(Message::RealtimeValue(
                                 ZmqEvent::Status(ZmqStatus::Connected),
                             ))?;
@@ -75,12 +133,34 @@ impl<T: Element + Send + DeserializeOwned> ZeroMqSubscriber<T> {
 
             if items[0].is_readable() {
                 let res = socket.recv_bytes(0)?;
-                let msg: Message<T> = bincode::deserialize(&res)
-                    .unwrap_or_else(|err| Message::Error(std::sync::Arc::new(err.into())));
+                let (sequence, msg) = decode_message(&self.codec, &res)
+                    .unwrap_or_else(|err| (0, Message::Error(std::sync::Arc::new(err))));
                 match msg {
-                    Message::RealtimeValue(v) => {
-                        channel_sender.send_message(Message::RealtimeValue(ZmqEvent::Data(v)))?;
-                    }
+                    Message::RealtimeValue(v) => match (&self.snapshot, seen) {
+                        // The snapshot still to be requested will cover it.
+                        (Some(_), None) => {}
+                        (Some(_), Some(seen)) if sequence <= seen => {}
+                        // Values went missing since the snapshot, so ask for
+                        // a fresh one, which covers this value too.
+                        (Some(endpoint), Some(last)) if sequence > last + 1 => {
+                            log::warn!(
+                                "zmq sub {}: missed values {}..{sequence}, requesting a new snapshot",
+                                self.address,
+                                last + 1,
+                            );
+                            seen =
+                                Some(self.request_snapshot(&context, endpoint, &channel_sender)?);
+                        }
+                        (Some(_), Some(_)) => {
+                            seen = Some(sequence);
+                            channel_sender
+                                .send_message(Message::RealtimeValue(ZmqEvent::Data(v)))?;
+                        }
+                        (None, _) => {
+                            channel_sender
+                                .send_message(Message::RealtimeValue(ZmqEvent::Data(v)))?;
+                        }
+                    },
                     Message::EndOfStream => {
                         channel_sender.send_message(Message::RealtimeValue(ZmqEvent::Status(
                             ZmqStatus::Disconnected,
@@ -120,21 +200,69 @@ impl<T: Element + Send + DeserializeOwned> ZeroMqSubscriber<T> {
 /// Returns a `(data, status)` pair:
 /// - `data` ticks with each burst of received messages
 /// - `status` ticks when the connection status changes (`Connected`/`Disconnected`)
-pub fn zmq_sub<T: Element + Send + DeserializeOwned>(
+pub fn zmq_sub<T: Element + Send + Serialize + DeserializeOwned>(
+    config: impl Into<ZmqSubConfig>,
+) -> anyhow::Result<(Rc<dyn Stream<Burst<T>>>, Rc<dyn Stream<ZmqStatus>>)> {
+    zmq_sub_with_codec(config, Bincode)
+}
+
+/// [`zmq_sub`], decoding with `codec` rather than [`Bincode`]. It must match
+/// the publisher's codec; messages from a publisher using another codec fail
+/// the stream with an error naming both version bytes.
+pub fn zmq_sub_with_codec<T: Element + Send>(
+    config: impl Into<ZmqSubConfig>,
+    codec: impl Codec<T>,
+) -> anyhow::Result<(Rc<dyn Stream<Burst<T>>>, Rc<dyn Stream<ZmqStatus>>)> {
+    let address = match config.into().0 {
+        ZmqSubResolution::Direct(addr) => addr,
+        ZmqSubResolution::Discover(name, reg) => reg.lookup(&name)?,
+    };
+    Ok(zmq_sub_direct(&address, None, codec))
+}
+
+/// [`zmq_sub`] for a publisher made with
+/// [`zmq_pub_with_snapshot`](super::ZeroMqPub::zmq_pub_with_snapshot), so a
+/// subscriber joining late starts from the publisher's current state rather
+/// than from whatever ticks next.
+///
+/// Once connected, it requests the snapshot from the port after the
+/// publisher's and emits it as its first value, then emits the live values
+/// published after it; each message carries a sequence number, so live
+/// values the snapshot already covers are dropped.  If values go missing it
+/// requests a fresh snapshot.
+pub fn zmq_sub_with_snapshot<T: Element + Send + Serialize + DeserializeOwned>(
     config: impl Into<ZmqSubConfig>,
 ) -> anyhow::Result<(Rc<dyn Stream<Burst<T>>>, Rc<dyn Stream<ZmqStatus>>)> {
     let address = match config.into().0 {
         ZmqSubResolution::Direct(addr) => addr,
         ZmqSubResolution::Discover(name, reg) => reg.lookup(&name)?,
     };
-    Ok(zmq_sub_direct::<T>(&address))
+    let snapshot = snapshot_address(&address)?;
+    Ok(zmq_sub_direct(&address, Some(snapshot), Bincode))
+}
+
+/// The snapshot endpoint of a publisher at `address`: the same host, one
+/// port up.
+fn snapshot_address(address: &str) -> anyhow::Result<String> {
+    let (host, port) = address
+        .rsplit_once(':')
+        .ok_or_else(|| anyhow::anyhow!("zmq address {address:?} has no port"))?;
+    let port: u16 = port
+        .parse()
+        .map_err(|_| anyhow::anyhow!("zmq address {address:?} has no numeric port"))?;
+    let port = port
+        .checked_add(1)
+        .ok_or_else(|| anyhow::anyhow!("no port above {port} for snapshots"))?;
+    Ok(format!("{host}:{port}"))
 }
 
-fn zmq_sub_direct<T: Element + Send + DeserializeOwned>(
+fn zmq_sub_direct<T: Element + Send>(
     address: &str,
+    snapshot: Option<String>,
+    codec: impl Codec<T>,
 ) -> (Rc<dyn Stream<Burst<T>>>, Rc<dyn Stream<ZmqStatus>>) {
     let events: Rc<dyn Stream<Burst<ZmqEvent<T>>>> = {
-        let subscriber = ZeroMqSubscriber::new(address.to_string());
+        let subscriber = ZeroMqSubscriber::new(address.to_string(), snapshot, codec);
         ReceiverStream::new(move |s, stop| subscriber.run(s, stop), true).into_stream()
     };
     let data = MapFilterStream::new(
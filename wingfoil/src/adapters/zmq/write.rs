@@ -1,11 +1,15 @@
 use std::rc::Rc;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 
 use super::registry::{ZmqHandle, ZmqPubRegistration};
+use crate::adapters::codec::{Bincode, Codec, encode_message};
 use crate::channel::Message;
 use crate::{Element, GraphState, IntoNode, MutableNode, Node, RunMode, Stream, UpStreams};
 use serde::Serialize;
+use serde::de::DeserializeOwned;
 
 static MONITOR_ID: AtomicUsize = AtomicUsize::new(0);
 const ZMQ_EVENT_ACCEPTED: u16 = 0x0008;
@@ -15,8 +19,85 @@ const ZMQ_EVENT_ACCEPTED: u16 = 0x0008;
 /// after this window should not receive stale data.
 const BUFFER_TIMEOUT: Duration = Duration::from_millis(500);
 
-struct ZeroMqSenderNode<T: Element + Send + Serialize> {
+/// The state a publisher serves to late joiners, see
+/// [`ZeroMqPub::zmq_pub_with_snapshot`].
+struct Snapshot<T> {
+    reduce: Box<dyn Fn(T, T) -> T>,
+    state: Option<T>,
+    /// The encoded snapshot, empty until the first tick, shared with the
+    /// thread answering snapshot requests.
+    encoded: Arc<Mutex<Vec<u8>>>,
+    server: Option<(JoinHandle<()>, Arc<AtomicBool>)>,
+}
+
+impl<T: Element + Send> Snapshot<T> {
+    /// Folds `value` into the snapshot.  Called before the value is sent, so
+    /// a subscriber that has seen sequence `n` always gets a snapshot at `n`
+    /// or later.
+    fn update(&mut self, codec: &impl Codec<T>, sequence: u64, value: T) -> anyhow::Result<()> {
+        let state = match self.state.take() {
+            Some(state) => (self.reduce)(state, value),
+            None => value,
+        };
+        let mut encoded = self.encoded.lock().expect("snapshot lock poisoned");
+        encode_message(
+            codec,
+            sequence,
+            &Message::RealtimeValue(state.clone()),
+            &mut encoded,
+        )?;
+        self.state = Some(state);
+        Ok(())
+    }
+
+    fn serve(&mut self, context: &zmq::Context, address: &str) -> anyhow::Result<()> {
+        let socket = context.socket(zmq::REP)?;
+        socket.bind(address)?;
+        let encoded = self.encoded.clone();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let handle = std::thread::spawn(move || {
+            if let Err(err) = answer_snapshot_requests(&socket, &encoded, &stopped) {
+                log::warn!("zmq snapshot server stopped: {err}");
+            }
+        });
+        self.server = Some((handle, stop));
+        Ok(())
+    }
+
+    fn shutdown(&mut self) {
+        if let Some((handle, stop)) = self.server.take() {
+            stop.store(true, Ordering::Relaxed);
+            let _ = handle.join();
+        }
+    }
+}
+
+fn answer_snapshot_requests(
+    socket: &zmq::Socket,
+    encoded: &Mutex<Vec<u8>>,
+    stop: &AtomicBool,
+) -> anyhow::Result<()> {
+    while !stop.load(Ordering::Relaxed) {
+        let mut items = [socket.as_poll_item(zmq::POLLIN)];
+        zmq::poll(&mut items, 100)?;
+        if items[0].is_readable() {
+            socket.recv_bytes(0)?;
+            let reply = encoded.lock().expect("snapshot lock poisoned").clone();
+            socket.send(reply, 0)?;
+        }
+    }
+    Ok(())
+}
+
+struct ZeroMqSenderNode<T: Element + Send, C: Codec<T>> {
     src: Rc<dyn Stream<T>>,
+    codec: C,
+    /// Reused for every message so sending does not allocate.
+    scratch: Vec<u8>,
+    /// The number of values sent so far, stamped on every message.
+    sequence: u64,
+    snapshot: Option<Snapshot<T>>,
     port: u16,
     bind_address: String,
     registration: ZmqPubRegistration,
@@ -31,7 +112,33 @@ struct ZeroMqSenderNode<T: Element + Send + Serialize> {
 
 const FLAGS: i32 = 0;
 
-impl<T: Element + Send + Serialize> ZeroMqSenderNode<T> {
+impl<T: Element + Send, C: Codec<T>> ZeroMqSenderNode<T, C> {
+    fn new(
+        src: Rc<dyn Stream<T>>,
+        bind_address: &str,
+        port: u16,
+        registration: ZmqPubRegistration,
+        codec: C,
+    ) -> Self {
+        Self {
+            src,
+            codec,
+            scratch: Vec::new(),
+            sequence: 0,
+            snapshot: None,
+            port,
+            bind_address: bind_address.to_string(),
+            registration,
+            socket: None,
+            monitor: None,
+            registry_handle: None,
+            subscriber_connected: false,
+            accepted_at: None,
+            buffer: Vec::new(),
+            buffer_start: None,
+        }
+    }
+
     fn check_monitor(&mut self) {
         let Some(monitor) = self.monitor.as_ref() else {
             return;
@@ -59,7 +166,7 @@ impl<T: Element + Send + Serialize> ZeroMqSenderNode<T> {
     }
 }
 
-impl<T: Element + Send + Serialize> MutableNode for ZeroMqSenderNode<T> {
+impl<T: Element + Send, C: Codec<T>> MutableNode for ZeroMqSenderNode<T, C> {
     fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
         if !self.subscriber_connected {
             self.check_monitor();
@@ -74,9 +181,13 @@ impl<T: Element + Send + Serialize> MutableNode for ZeroMqSenderNode<T> {
             }
         }
 
+        self.sequence += 1;
         let value = self.src.peek_value();
+        if let Some(snapshot) = &mut self.snapshot {
+            snapshot.update(&self.codec, self.sequence, value.clone())?;
+        }
         let msg = Message::build(value, state);
-        let data = bincode::serialize(&msg)?;
+        encode_message(&self.codec, self.sequence, &msg, &mut self.scratch)?;
         let sock = self
             .socket
             .as_ref()
@@ -88,7 +199,7 @@ impl<T: Element + Send + Serialize> MutableNode for ZeroMqSenderNode<T> {
                 sock.send(buffered, FLAGS)?;
             }
             self.buffer_start = None;
-            sock.send(data, FLAGS)?;
+            sock.send(&self.scratch[..], FLAGS)?;
         } else {
             // No subscriber yet — buffer the message.
             let now = Instant::now();
@@ -105,7 +216,7 @@ impl<T: Element + Send + Serialize> MutableNode for ZeroMqSenderNode<T> {
                 self.buffer.clear();
                 self.buffer_start = Some(now);
             }
-            self.buffer.push(data);
+            self.buffer.push(self.scratch.clone());
         }
 
         Ok(true)
@@ -132,6 +243,13 @@ impl<T: Element + Send + Serialize> MutableNode for ZeroMqSenderNode<T> {
         let address = format!("tcp://{}:{}", self.bind_address, self.port);
         socket.bind(&address)?;
         self.socket = Some(socket);
+        if let Some(snapshot) = &mut self.snapshot {
+            let port = self
+                .port
+                .checked_add(1)
+                .ok_or_else(|| anyhow::anyhow!("no port above {} for snapshots", self.port))?;
+            snapshot.serve(&context, &format!("tcp://{}:{port}", self.bind_address))?;
+        }
         if let Some((name, registry)) = &self.registration.0 {
             self.registry_handle = Some(registry.register(name, &address)?);
         }
@@ -142,12 +260,19 @@ impl<T: Element + Send + Serialize> MutableNode for ZeroMqSenderNode<T> {
         if let Some(mut h) = self.registry_handle.take() {
             h.revoke();
         }
+        if let Some(snapshot) = &mut self.snapshot {
+            snapshot.shutdown();
+        }
         let Some(sock) = self.socket.as_ref() else {
             return Ok(());
         };
-        let msg: Message<T> = Message::EndOfStream;
-        let data = bincode::serialize(&msg)?;
-        sock.send(data, FLAGS)?;
+        encode_message(
+            &self.codec,
+            self.sequence,
+            &Message::EndOfStream,
+            &mut self.scratch,
+        )?;
+        sock.send(&self.scratch[..], FLAGS)?;
         Ok(())
     }
 }
@@ -181,24 +306,41 @@ pub trait ZeroMqPub<T: Element + Send> {
         port: u16,
         registration: impl Into<ZmqPubRegistration>,
     ) -> Rc<dyn Node>;
+    /// [`zmq_pub`](Self::zmq_pub), encoding with `codec` rather than
+    /// [`Bincode`]. Subscribers must use the same codec.
+    fn zmq_pub_with_codec(
+        &self,
+        port: u16,
+        registration: impl Into<ZmqPubRegistration>,
+        codec: impl Codec<T>,
+    ) -> Rc<dyn Node>;
+    /// [`zmq_pub_on`](Self::zmq_pub_on), encoding with `codec` rather than
+    /// [`Bincode`]. Subscribers must use the same codec.
+    fn zmq_pub_on_with_codec(
+        &self,
+        address: &str,
+        port: u16,
+        registration: impl Into<ZmqPubRegistration>,
+        codec: impl Codec<T>,
+    ) -> Rc<dyn Node>;
+    /// [`zmq_pub`](Self::zmq_pub), also serving a snapshot to late joiners
+    /// on a REP socket at `port + 1`, for
+    /// [`zmq_sub_with_snapshot`](super::zmq_sub_with_snapshot).
+    ///
+    /// The snapshot starts as the first value and `reduce(snapshot, value)`
+    /// folds in each later one, so `|_, latest| latest` serves the last
+    /// value and other reducers can serve, say, a book built from deltas.
+    fn zmq_pub_with_snapshot(
+        &self,
+        port: u16,
+        registration: impl Into<ZmqPubRegistration>,
+        reduce: impl Fn(T, T) -> T + 'static,
+    ) -> Rc<dyn Node>;
 }
 
-impl<T: Element + Send + Serialize> ZeroMqPub<T> for Rc<dyn Stream<T>> {
+impl<T: Element + Send + Serialize + DeserializeOwned> ZeroMqPub<T> for Rc<dyn Stream<T>> {
     fn zmq_pub(&self, port: u16, registration: impl Into<ZmqPubRegistration>) -> Rc<dyn Node> {
-        ZeroMqSenderNode {
-            src: self.clone(),
-            port,
-            bind_address: "127.0.0.1".to_string(),
-            registration: registration.into(),
-            socket: None,
-            monitor: None,
-            registry_handle: None,
-            subscriber_connected: false,
-            accepted_at: None,
-            buffer: Vec::new(),
-            buffer_start: None,
-        }
-        .into_node()
+        self.zmq_pub_with_codec(port, registration, Bincode)
     }
 
     fn zmq_pub_on(
@@ -207,19 +349,47 @@ impl<T: Element + Send + Serialize> ZeroMqPub<T> for Rc<dyn Stream<T>> {
         port: u16,
         registration: impl Into<ZmqPubRegistration>,
     ) -> Rc<dyn Node> {
-        ZeroMqSenderNode {
-            src: self.clone(),
+        self.zmq_pub_on_with_codec(address, port, registration, Bincode)
+    }
+
+    fn zmq_pub_with_codec(
+        &self,
+        port: u16,
+        registration: impl Into<ZmqPubRegistration>,
+        codec: impl Codec<T>,
+    ) -> Rc<dyn Node> {
+        self.zmq_pub_on_with_codec("127.0.0.1", port, registration, codec)
+    }
+
+    fn zmq_pub_on_with_codec(
+        &self,
+        address: &str,
+        port: u16,
+        registration: impl Into<ZmqPubRegistration>,
+        codec: impl Codec<T>,
+    ) -> Rc<dyn Node> {
+        ZeroMqSenderNode::new(self.clone(), address, port, registration.into(), codec).into_node()
+    }
+
+    fn zmq_pub_with_snapshot(
+        &self,
+        port: u16,
+        registration: impl Into<ZmqPubRegistration>,
+        reduce: impl Fn(T, T) -> T + 'static,
+    ) -> Rc<dyn Node> {
+        let mut node = ZeroMqSenderNode::new(
+            self.clone(),
+            "127.0.0.1",
             port,
-            bind_address: address.to_string(),
-            registration: registration.into(),
-            socket: None,
-            monitor: None,
-            registry_handle: None,
-            subscriber_connected: false,
-            accepted_at: None,
-            buffer: Vec::new(),
-            buffer_start: None,
-        }
-        .into_node()
+            registration.into(),
+            Bincode,
+        );
+        node.snapshot = Some(Snapshot {
+            reduce: Box::new(reduce),
+            state: None,
+            encoded: Arc::default(),
+            server: None,
+        });
+        node.into_node()
     }
 }
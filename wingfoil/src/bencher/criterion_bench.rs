@@ -14,7 +14,7 @@ pub fn add_bench<F>(crit: &mut Criterion, name: &str, f: F)
 where
     F: BenchBuilder + 'static,
 {
-    let custom_bencher = RefCell::new(Bencher::new(f, RunMode::RealTime));
+    let custom_bencher = RefCell::new(CriterionBencher::new(f, RunMode::RealTime));
     custom_bencher.borrow_mut().start();
 
     crit.bench_function(name, {
@@ -34,13 +34,13 @@ where
 pub trait BenchBuilder: FnOnce(Rc<dyn Node>) -> Rc<dyn Node> + Send {}
 impl<F> BenchBuilder for F where F: FnOnce(Rc<dyn Node>) -> Rc<dyn Node> + Send {}
 
-struct Bencher {
+struct CriterionBencher {
     run_mode: RunMode,
     signal: Arc<AtomicU8>,
     builder: Option<Box<dyn BenchBuilder>>,
 }
 
-impl Bencher {
+impl CriterionBencher {
     pub fn new<B: BenchBuilder + 'static>(builder: B, run_mode: RunMode) -> Self {
         let signal = Arc::new(AtomicU8::new(Signal::Ready.into()));
         let builder: Option<Box<dyn BenchBuilder>> = Some(Box::new(builder));
@@ -69,7 +69,7 @@ impl Bencher {
         let builder = self
             .builder
             .take()
-            .expect("Bencher::start called more than once");
+            .expect("CriterionBencher::start called more than once");
         let signal = self.signal.clone();
         let run_mode = self.run_mode;
         std::thread::spawn(move || {
@@ -0,0 +1,232 @@
+//! Timing harness for measuring graph performance.
+//!
+//! [Bencher] steps a historical graph one engine cycle at a time and times
+//! each cycle, so results reflect the cost of the nodes rather than of graph
+//! setup.  The criterion integration (`add_bench`) needs the `bench` feature.
+
+#[cfg(feature = "bench")]
+mod criterion_bench;
+
+#[cfg(feature = "bench")]
+pub use criterion_bench::*;
+
+use std::fmt;
+use std::rc::Rc;
+use std::time::Instant;
+
+use crate::{Graph, NanoTime, Node, RunFor, RunMode};
+
+/// How much slower `current` may be than `baseline` before
+/// [Bencher::compare] reports a regression.
+pub const REGRESSION_TOLERANCE: f64 = 0.05;
+
+/// Per-cycle timings from [Bencher::run_with_warmup].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchResult {
+    /// Number of timed cycles.  Fewer than requested if the graph ran out
+    /// of work.
+    pub cycles: u32,
+    pub mean_ns_per_cycle: f64,
+    pub std_dev_ns: f64,
+    pub min_ns: u64,
+    pub max_ns: u64,
+    pub throughput_cycles_per_sec: f64,
+}
+
+impl fmt::Display for BenchResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} cycles, {:.1}ns/cycle (± {:.1}ns, min {}ns, max {}ns), {:.0} cycles/sec",
+            self.cycles,
+            self.mean_ns_per_cycle,
+            self.std_dev_ns,
+            self.min_ns,
+            self.max_ns,
+            self.throughput_cycles_per_sec
+        )
+    }
+}
+
+/// The outcome of [Bencher::compare].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchComparison {
+    /// Baseline mean over current mean: above 1.0 means `current` is faster.
+    pub speedup: f64,
+    /// Whether `current` is more than [REGRESSION_TOLERANCE] slower.
+    pub regression: bool,
+}
+
+/// Times the engine cycles of a graph.
+/// ```
+/// # use wingfoil::*;
+/// # use std::time::Duration;
+/// let sum = ticker(Duration::from_nanos(1))
+///     .count()
+///     .fold(|acc: &mut u64, x| *acc += x);
+/// let result = Bencher::new(vec![sum.as_node()])
+///     .run_with_warmup(100, 1_000)
+///     .unwrap();
+/// assert_eq!(result.cycles, 1_000);
+/// ```
+pub struct Bencher {
+    root_nodes: Vec<Rc<dyn Node>>,
+}
+
+impl Bencher {
+    pub fn new(root_nodes: Vec<Rc<dyn Node>>) -> Self {
+        Self { root_nodes }
+    }
+
+    /// Runs `warmup_iters` untimed cycles then times the next `bench_iters`,
+    /// in [RunMode::HistoricalFrom], so the graph runs as fast as it can.
+    /// Each call runs a fresh graph over the same nodes.  Fails if the graph
+    /// fails or completes no timed cycles.
+    pub fn run_with_warmup(
+        &self,
+        warmup_iters: u32,
+        bench_iters: u32,
+    ) -> anyhow::Result<BenchResult> {
+        let mut graph = Graph::new(
+            self.root_nodes.clone(),
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(warmup_iters.saturating_add(bench_iters)),
+        );
+        let mut stepper = graph.stepper()?;
+        for _ in 0..warmup_iters {
+            if stepper.step()?.is_none() {
+                break;
+            }
+        }
+        let mut stats = CycleStats::default();
+        for _ in 0..bench_iters {
+            let start = Instant::now();
+            let stepped = stepper.step()?;
+            let elapsed = start.elapsed().as_nanos() as u64;
+            if stepped.is_none() {
+                break;
+            }
+            stats.push(elapsed);
+        }
+        stepper.finish()?;
+        stats
+            .result()
+            .ok_or_else(|| anyhow::anyhow!("graph completed no cycles after warmup"))
+    }
+
+    /// Compares two results of the same benchmark.
+    pub fn compare(baseline: BenchResult, current: BenchResult) -> BenchComparison {
+        BenchComparison {
+            speedup: baseline.mean_ns_per_cycle / current.mean_ns_per_cycle,
+            regression: current.mean_ns_per_cycle
+                > baseline.mean_ns_per_cycle * (1.0 + REGRESSION_TOLERANCE),
+        }
+    }
+}
+
+/// Times `n_iters` cycles of the graph rooted at `root_nodes`, after warming
+/// up for a tenth as many.
+pub fn bench(root_nodes: Vec<Rc<dyn Node>>, n_iters: u32) -> anyhow::Result<BenchResult> {
+    Bencher::new(root_nodes).run_with_warmup(n_iters / 10, n_iters)
+}
+
+/// Running mean and variance (Welford), so timing needs no allocation.
+#[derive(Default)]
+struct CycleStats {
+    count: u32,
+    mean: f64,
+    m2: f64,
+    min: u64,
+    max: u64,
+}
+
+impl CycleStats {
+    fn push(&mut self, nanos: u64) {
+        self.min = if self.count == 0 {
+            nanos
+        } else {
+            self.min.min(nanos)
+        };
+        self.max = self.max.max(nanos);
+        self.count += 1;
+        let delta = nanos as f64 - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (nanos as f64 - self.mean);
+    }
+
+    fn result(&self) -> Option<BenchResult> {
+        (self.count > 0).then(|| BenchResult {
+            cycles: self.count,
+            mean_ns_per_cycle: self.mean,
+            std_dev_ns: (self.m2 / self.count as f64).sqrt(),
+            min_ns: self.min,
+            max_ns: self.max,
+            // Guard against a zero mean from a coarse clock.
+            throughput_cycles_per_sec: 1e9 / self.mean.max(1.0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::*;
+    use std::time::Duration;
+
+    fn result(mean: f64) -> BenchResult {
+        BenchResult {
+            cycles: 1,
+            mean_ns_per_cycle: mean,
+            std_dev_ns: 0.0,
+            min_ns: mean as u64,
+            max_ns: mean as u64,
+            throughput_cycles_per_sec: 1e9 / mean,
+        }
+    }
+
+    #[test]
+    fn cycle_stats() {
+        let mut stats = CycleStats::default();
+        for nanos in [2, 4, 4, 4, 5, 5, 7, 9] {
+            stats.push(nanos);
+        }
+        let result = stats.result().unwrap();
+        assert_eq!(result.cycles, 8);
+        assert_eq!(result.mean_ns_per_cycle, 5.0);
+        assert_eq!(result.std_dev_ns, 2.0);
+        assert_eq!((result.min_ns, result.max_ns), (2, 9));
+        assert_eq!(result.throughput_cycles_per_sec, 2e8);
+        assert!(CycleStats::default().result().is_none());
+    }
+
+    #[test]
+    fn times_requested_cycles_after_warmup() {
+        let count = ticker(Duration::from_nanos(1)).count();
+        let result = Bencher::new(vec![count.clone().as_node()])
+            .run_with_warmup(10, 100)
+            .unwrap();
+        assert_eq!(result.cycles, 100);
+        assert_eq!(count.peek_value(), 110);
+        assert!(result.min_ns as f64 <= result.mean_ns_per_cycle);
+        assert!(result.mean_ns_per_cycle <= result.max_ns as f64);
+    }
+
+    #[test]
+    fn stops_when_graph_runs_out_of_work() {
+        let result = Bencher::new(vec![constant(1).as_node()])
+            .run_with_warmup(0, 100)
+            .unwrap();
+        assert_eq!(result.cycles, 1);
+        // The only cycle is spent warming up.
+        assert!(constant(1).bench(100).is_err());
+    }
+
+    #[test]
+    fn compare_flags_regressions_beyond_tolerance() {
+        let comparison = Bencher::compare(result(100.0), result(50.0));
+        assert_eq!(comparison.speedup, 2.0);
+        assert!(!comparison.regression);
+        assert!(!Bencher::compare(result(100.0), result(104.0)).regression);
+        assert!(Bencher::compare(result(100.0), result(110.0)).regression);
+    }
+}
@@ -51,6 +51,15 @@ impl<T: Element + Send> ChannelReceiver<T> {
             Err(_) => Some(Message::EndOfStream), // channel closed by sender
         }
     }
+    #[cfg(any(
+        feature = "zmq",
+        feature = "aeron",
+        feature = "aeron-rs",
+        feature = "shmem"
+    ))]
+    pub fn is_empty(&self) -> bool {
+        self.kanal_receiver.is_empty()
+    }
     pub fn recv(&self) -> Message<T> {
         self.kanal_receiver.recv().unwrap_or(Message::EndOfStream)
     }
@@ -202,7 +211,7 @@ impl<T: Element + Send> ReceiverMessageSource<T> for ChannelReceiver<T> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct ChannelSender<T: Element + Send> {
     kanal_sender: Option<Sender<Message<T>>>,
     ready_notifier: Option<ReadyNotifier>,
@@ -248,6 +257,14 @@ impl<T: Element + Send> ChannelSender<T> {
         self.send_message(message)
     }
 
+    /// Like [send](Self::send) but stamps the value with engine time in
+    /// every run mode, so a wingfoil receiver on another graph can record
+    /// its value time.
+    pub fn send_timed(&self, state: &GraphState, value: T) -> SendResult {
+        let value_at = ValueAt::new(crate::burst![value], state.time());
+        self.send_message(Message::HistoricalValue(value_at))
+    }
+
     pub fn send_checkpoint(&self, state: &GraphState) -> SendResult {
         let message = Message::CheckPoint(state.time());
         self.send_message(message)
@@ -20,6 +20,11 @@ pub(crate) enum Message<T: Element + Send> {
     /// the channel is ticking less frequently than
     /// other inputs.
     CheckPoint(NanoTime),
+    /// Sent back by a lockstep mapper's worker graph in
+    /// [RunMode::HistoricalFrom] once it has processed the first time.  The
+    /// second is the worker's next scheduled callback, if any, so the parent
+    /// can wake it then.
+    Ack(NanoTime, Option<NanoTime>),
     /// Tells the receiving node that there are no messages
     /// so it can shutdown cleanly.
     EndOfStream,
@@ -75,6 +80,7 @@ impl<T: Element + Send + PartialEq> PartialEq for Message<T> {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Message::CheckPoint(t1), Message::CheckPoint(t2)) => t1 == t2,
+            (Message::Ack(t1, n1), Message::Ack(t2, n2)) => t1 == t2 && n1 == n2,
             (Message::EndOfStream, Message::EndOfStream) => true,
             (Message::HistoricalValue(v1), Message::HistoricalValue(v2)) => v1 == v2,
             (Message::RealtimeValue(v1), Message::RealtimeValue(v2)) => v1 == v2,
@@ -122,6 +128,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ack_eq() {
+        let t = NanoTime::new(42);
+        let next = Some(NanoTime::new(50));
+        assert_eq!(Message::<u64>::Ack(t, next), Message::Ack(t, next));
+        assert_ne!(Message::<u64>::Ack(t, next), Message::Ack(t, None));
+        assert_ne!(Message::<u64>::Ack(t, None), Message::CheckPoint(t));
+    }
+
     #[test]
     fn end_of_stream_eq() {
         assert_eq!(Message::<u64>::EndOfStream, Message::EndOfStream);
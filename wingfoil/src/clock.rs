@@ -0,0 +1,95 @@
+//! The source of "now" for realtime graphs.  A graph reads the time from its
+//! [Clock], [WallClock] unless one is set with
+//! [GraphBuilder::with_clock](crate::GraphBuilder::with_clock).  Tests can
+//! pass a [MockClock] to run realtime graphs deterministically.  The clock
+//! drives engine time only: [GraphState::wall_time](crate::GraphState::wall_time)
+//! and latency stamps always read the real wall clock.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::time::NanoTime;
+
+/// Tells a realtime graph what time it is and how long to wait when idle.
+pub trait Clock: Send + Sync {
+    /// The current time.
+    fn now(&self) -> NanoTime;
+
+    /// How long an idle graph should block waiting for a ready callback,
+    /// given that nothing is scheduled before `until`.
+    fn idle_timeout(&self, until: NanoTime) -> Duration {
+        Duration::from_nanos(u64::from(until).saturating_sub(u64::from(self.now())))
+    }
+}
+
+/// The real wall clock, read with [NanoTime::now].  The default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WallClock;
+
+impl Clock for WallClock {
+    fn now(&self) -> NanoTime {
+        NanoTime::now()
+    }
+}
+
+/// A clock that only moves when told to.  Clones share the same time.
+///
+/// When the graph is idle it jumps the clock straight to its next scheduled
+/// callback instead of sleeping, so timer driven realtime graphs run
+/// instantly and tick at exact times.  Time never moves backwards.
+/// ```
+/// # use wingfoil::*;
+/// # use std::rc::Rc;
+/// # use std::time::Duration;
+/// let clock = MockClock::new(NanoTime::new(1_000));
+/// let times = ticker(Duration::from_nanos(100)).ticked_at().collect();
+/// let mut graph = Graph::builder().with_clock(clock.clone()).build(
+///     vec![times.clone().as_node()],
+///     RunMode::RealTime,
+///     RunFor::Cycles(3),
+/// );
+/// graph.run().unwrap();
+/// let times: Vec<u64> = times.peek_value().iter().map(|v| v.value.into()).collect();
+/// assert_eq!(times, vec![1_000, 1_100, 1_200]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    time: Arc<AtomicU64>,
+}
+
+impl MockClock {
+    pub fn new(time: NanoTime) -> Self {
+        Self {
+            time: Arc::new(AtomicU64::new(time.into())),
+        }
+    }
+
+    /// Moves the clock to `time`, if that is later than now.
+    pub fn set(&self, time: NanoTime) {
+        self.time.fetch_max(time.into(), Ordering::AcqRel);
+    }
+
+    /// Moves the clock on by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        self.time
+            .fetch_add(duration.as_nanos() as u64, Ordering::AcqRel);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> NanoTime {
+        NanoTime::new(self.time.load(Ordering::Acquire))
+    }
+
+    fn idle_timeout(&self, until: NanoTime) -> Duration {
+        if until == NanoTime::MAX {
+            // Nothing is scheduled, so only another thread can make
+            // progress: poll briefly rather than jump to the end of time.
+            Duration::from_millis(1)
+        } else {
+            self.set(until);
+            Duration::ZERO
+        }
+    }
+}
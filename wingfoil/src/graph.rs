@@ -1,17 +1,26 @@
-use crate::queue::TimeQueue;
-use crate::types::{NanoTime, Node};
+use crate::clock::{Clock, WallClock};
+use crate::nodes::CallBackStream;
+use crate::progress::{Progress, ProgressReporter};
+use crate::queue::{TimeQueue, ValueAt};
+use crate::types::{AsNode, Element, NanoTime, Node, Stream};
+use anyhow::Context;
 use by_address::ByThinAddress;
+use itertools::Itertools;
 
 use crossbeam::channel::{Receiver, SendError, Sender, select};
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
 use std::cmp::{max, min};
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
 use std::convert::TryInto;
+use std::fmt;
 use std::fs::File;
+use std::hash::{Hash, Hasher};
 use std::io::{Error, Write};
 use std::path::Path;
 use std::rc::Rc;
-#[cfg(feature = "async")]
 use std::sync::Arc;
 #[cfg(feature = "async")]
 use std::sync::OnceLock;
@@ -41,14 +50,72 @@ struct PendingAddition {
     recycle: bool,
 }
 
+/// An entry in a node's active downstream list.  The downstream's layer is
+/// cached alongside its index so dirty propagation, the hot path, never
+/// touches the downstream's [NodeData].  `fix_layers` keeps it up to date.
+#[derive(Clone, Copy, Debug)]
+struct ActiveDownstream {
+    node_index: u32,
+    layer: u32,
+}
+
 struct NodeData {
     node: Rc<dyn Node>,
     upstreams: Vec<Edge>,
-    downstreams: Vec<Edge>,
+    /// Downstreams this node's ticks propagate to.
+    active_downstreams: Vec<ActiveDownstream>,
+    /// Downstreams that only read this node's value.  Kept for wiring,
+    /// layering and inspection; never walked when the node ticks.
+    passive_downstreams: Vec<u32>,
     layer: usize,
     active: bool,
 }
 
+impl NodeData {
+    fn new(node: Rc<dyn Node>, upstreams: Vec<Edge>, layer: usize) -> Self {
+        Self {
+            node,
+            upstreams,
+            active_downstreams: vec![],
+            passive_downstreams: vec![],
+            layer,
+            active: true,
+        }
+    }
+
+    /// Indices of active and passive downstreams, in index order.
+    fn downstream_indices(&self) -> Vec<usize> {
+        let mut indices: Vec<usize> = self
+            .active_downstreams
+            .iter()
+            .map(|dn| dn.node_index as usize)
+            .chain(self.passive_downstreams.iter().map(|&ix| ix as usize))
+            .collect();
+        indices.sort_unstable();
+        indices
+    }
+}
+
+/// A snapshot of one wired node, as returned by [Graph::nodes_info].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeInfo {
+    pub index: usize,
+    pub type_name: String,
+    /// Source nodes are at layer 0.
+    pub layer: usize,
+    /// Indices of active and passive upstreams, in wiring order.
+    pub upstream_indices: Vec<usize>,
+    pub downstream_indices: Vec<usize>,
+}
+
+/// One node's entry in [Graph::memory_report].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeMemory {
+    pub info: NodeInfo,
+    /// From [MutableNode::approx_memory](crate::MutableNode::approx_memory), `None` if the node doesn't report.
+    pub approx_bytes: Option<usize>,
+}
+
 /// A frame on the explicit work stack used by [`Graph::initialise_node`] to wire
 /// the graph iteratively (in place of recursion). Holds a node whose upstreams
 /// are being processed one at a time.
@@ -97,20 +164,69 @@ impl RunMode {
 }
 
 /// Defines how long the graph should run for.  Can be a
-/// Duration, number of cycles or forever.
-#[derive(Clone, Copy, Debug)]
+/// Duration, number of cycles, forever or until a condition holds.
+#[derive(Clone)]
 pub enum RunFor {
     Duration(Duration),
     Cycles(u32),
     Forever,
+    /// Runs until the condition returns true.  It is checked between engine
+    /// cycles, with the number of cycles completed and the engine time
+    /// elapsed since the start, so the graph stops before the next cycle.
+    /// Unlike the other bounds, no cycle is flagged in advance as
+    /// [the last](GraphState::is_last_cycle).
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::sync::Arc;
+    /// # use std::time::Duration;
+    /// let events = ticker(Duration::from_millis(1)).count();
+    /// let run_for = RunFor::Condition(Arc::new(|cycles, _elapsed| cycles >= 1_000));
+    /// events
+    ///     .run(RunMode::HistoricalFrom(NanoTime::ZERO), run_for)
+    ///     .unwrap();
+    /// assert_eq!(events.peek_value(), 1_000);
+    /// ```
+    Condition(Arc<dyn Fn(u32, NanoTime) -> bool + Send + Sync>),
+}
+
+impl fmt::Debug for RunFor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunFor::Duration(duration) => f.debug_tuple("Duration").field(duration).finish(),
+            RunFor::Cycles(cycles) => f.debug_tuple("Cycles").field(cycles).finish(),
+            RunFor::Forever => f.write_str("Forever"),
+            RunFor::Condition(_) => f.write_str("Condition(..)"),
+        }
+    }
 }
 
 impl RunFor {
+    /// Runs until `max_cycles` cycles have completed or a cycle at or after
+    /// `max_duration` has run, whichever comes first.
+    pub fn until_cycles_or_time(max_cycles: u32, max_duration: Duration) -> RunFor {
+        let max_elapsed = NanoTime::from(max_duration);
+        RunFor::Condition(Arc::new(move |cycles, elapsed| {
+            cycles >= max_cycles || elapsed >= max_elapsed
+        }))
+    }
+
+    /// When a run starting at `start_time` ends: `start_time` plus the
+    /// duration for [RunFor::Duration].  None for the other bounds, which
+    /// fix no end time in advance, so the run is unbounded in time and
+    /// time-bounded work should run on to [NanoTime::MAX].
+    pub fn end_time(&self, start_time: NanoTime) -> Option<NanoTime> {
+        match self {
+            RunFor::Duration(duration) => Some(start_time + *duration),
+            RunFor::Cycles(_) | RunFor::Forever | RunFor::Condition(_) => None,
+        }
+    }
+
     pub fn done(&self, cycle: u32, elapsed: NanoTime) -> bool {
         match self {
             RunFor::Cycles(cycles) => cycle > *cycles,
             RunFor::Duration(duration) => elapsed > NanoTime::from(*duration),
             RunFor::Forever => false,
+            RunFor::Condition(condition) => condition(cycle, elapsed),
         }
     }
 }
@@ -125,6 +241,16 @@ struct RunBounds {
     end_cycle: u32,
 }
 
+/// Outcome of [`Graph::prepare_cycle`].
+enum CyclePrep {
+    /// Dirty nodes are queued; the caller should run the cycle.
+    Ready,
+    /// Realtime only: nothing was ready before the wait timed out.
+    Idle,
+    /// The run bound was reached, or historical mode ran out of callbacks.
+    Finished,
+}
+
 /// Return the first `Err` in `results`, attaching any subsequent errors as
 /// context so that a failure in a later lifecycle phase (e.g. `teardown`) is
 /// preserved rather than dropped when an earlier phase (e.g. the run loop) has
@@ -155,6 +281,21 @@ fn average_duration(duration: Duration, n: u32) -> Duration {
     Duration::from_nanos(avg_nanos as u64)
 }
 
+/// Adds `index` to a layer's dirty nodes, keeping them in node-index
+/// (wiring) order so that a cycle runs same-layer nodes deterministically,
+/// whatever order their callbacks arrived in.  Nodes are mostly marked in
+/// index order already, so this is usually a push.
+#[inline]
+fn insert_dirty(layer: &mut Vec<usize>, index: usize) {
+    match layer.last() {
+        Some(&last) if last > index => {
+            let pos = layer.partition_point(|&ix| ix < index);
+            layer.insert(pos, index);
+        }
+        _ => layer.push(index),
+    }
+}
+
 /// A struct produced by [Graph] that can be used by a [Node]
 /// to notify the [Graph] that it is required to be cycled
 /// on the next engine cycle.   It is bound to the [Node]
@@ -171,15 +312,33 @@ impl ReadyNotifier {
     }
 }
 
+/// Where a [Graph] is in its lifecycle, as reported by its [Debug] output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Lifecycle {
+    /// Wired but not yet started.
+    Ready,
+    Running,
+    Stopped,
+}
+
 /// Maintains the parts of the graph state that is accessible to Nodes.
 pub struct GraphState {
     time: NanoTime,
     /// Wall-clock timestamp of the start of the current engine cycle.
-    /// Unlike [`time`], this is always a real wall-clock snap in both realtime
-    /// and historical mode — used for latency measurement and perf telemetry.
+    /// Unlike [`time`], this is always a snap of the graph's [Clock] in both
+    /// realtime and historical mode — used for latency measurement and perf telemetry.
     /// It is populated once per cycle (before nodes are dispatched) and
     /// explicitly does not feed business-logic decisions.
     wall_time: NanoTime,
+    /// In realtime mode, when the current cycle's work was due: the time of
+    /// the earliest scheduled callback it fires, else its [`time`].  See
+    /// [lag](Self::lag).
+    due_time: NanoTime,
+    /// Set by [GraphBuilder::lag_budget].
+    lag_budget: Option<Duration>,
+    /// Set by [Graph::run_with_deadline]: when the run must be over by, in
+    /// wall-clock terms, and the budget that gave it.
+    wall_deadline: Option<(Instant, Duration)>,
     /// True until the first engine cycle completes; suppresses the
     /// strict-advance check so the very first cycle can fire at NanoTime::ZERO.
     first_cycle: bool,
@@ -189,8 +348,16 @@ pub struct GraphState {
     always_callbacks: Vec<usize>,
     node_to_index: HashMap<ByThinAddress<Rc<dyn Node>>, usize>,
     node_ticked: Vec<bool>,
+    /// Whether each node has had [on_first_tick](MutableNode::on_first_tick)
+    /// called.  Unlike `node_ticked` this is never reset.
+    first_tick_done: Vec<bool>,
+    /// Per node, the value time recorded by [set_value_time](Self::set_value_time)
+    /// and the cycle it was recorded on.
+    value_times: Vec<Option<(u64, NanoTime)>>,
     #[cfg(feature = "async")]
     run_time: OnceLock<Arc<tokio::runtime::Runtime>>,
+    #[cfg(feature = "async")]
+    runtime_config: RuntimeConfig,
     run_mode: RunMode,
     run_for: RunFor,
     ready_notifier: Sender<usize>,
@@ -203,10 +370,23 @@ pub struct GraphState {
     /// A wiring error (e.g. a cycle) detected during `initialise`. `Graph::new`
     /// is infallible, so the error is stashed here and surfaced from `run()`.
     wiring_error: Option<anyhow::Error>,
+    /// Indices of the nodes the graph was built from.
+    root_indices: Vec<usize>,
     #[cfg(feature = "dynamic-graph")]
     pending_additions: Vec<PendingAddition>,
     #[cfg(feature = "dynamic-graph")]
     pending_removals: Vec<Rc<dyn Node>>,
+    /// Roots of subgraphs queued by [attach](Self::attach).
+    #[cfg(feature = "dynamic-graph")]
+    pending_attachments: Vec<Rc<dyn Node>>,
+    lifecycle: Lifecycle,
+    /// Engine cycles completed so far.
+    cycle_count: u64,
+    context: GraphContext,
+    progress: Option<ProgressReporter>,
+    /// Set by [GraphBuilder::strict_uninitialized].
+    strict_uninitialized: bool,
+    clock: Arc<dyn Clock>,
 }
 
 impl GraphState {
@@ -216,6 +396,9 @@ impl GraphState {
         Self {
             time: NanoTime::ZERO,
             wall_time: NanoTime::ZERO,
+            due_time: NanoTime::ZERO,
+            lag_budget: None,
+            wall_deadline: None,
             first_cycle: true,
             is_last_cycle: false,
             current_node_index: None,
@@ -223,8 +406,12 @@ impl GraphState {
             always_callbacks: Vec::new(),
             node_to_index: HashMap::new(),
             node_ticked: Vec::new(),
+            first_tick_done: Vec::new(),
+            value_times: Vec::new(),
             #[cfg(feature = "async")]
             run_time: OnceLock::new(),
+            #[cfg(feature = "async")]
+            runtime_config: RuntimeConfig::default(),
             ready_notifier,
             run_mode,
             run_for,
@@ -235,13 +422,34 @@ impl GraphState {
             dirty_nodes_by_layer: Vec::new(),
             node_dirty: Vec::new(),
             wiring_error: None,
+            root_indices: Vec::new(),
             #[cfg(feature = "dynamic-graph")]
             pending_additions: Vec::new(),
             #[cfg(feature = "dynamic-graph")]
             pending_removals: Vec::new(),
+            #[cfg(feature = "dynamic-graph")]
+            pending_attachments: Vec::new(),
+            lifecycle: Lifecycle::Ready,
+            cycle_count: 0,
+            context: GraphContext::default(),
+            progress: None,
+            strict_uninitialized: false,
+            clock: Arc::new(WallClock),
         }
     }
 
+    /// The value of type `T` registered with [GraphBuilder::with_context],
+    /// if any.  Sub-graphs run by [producer](crate::nodes::producer) and
+    /// [mapper](crate::nodes::StreamOperators::mapper) see the same values.
+    pub fn context<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.context.get()
+    }
+
+    /// The registered context values, for handing on to a sub-graph.
+    pub(crate) fn shared_context(&self) -> GraphContext {
+        self.context.clone()
+    }
+
     /// The current engine time
     pub fn time(&self) -> NanoTime {
         self.time
@@ -266,6 +474,32 @@ impl GraphState {
         NanoTime::now()
     }
 
+    /// How far a realtime graph has fallen behind: the time on the graph's
+    /// [Clock] now, less when the current cycle's work was due (the time of
+    /// the earliest timer it fires).  Includes time spent by nodes earlier
+    /// in this cycle.  Always zero in historical mode.
+    pub fn lag(&self) -> Duration {
+        match self.run_mode {
+            RunMode::RealTime => Duration::from_nanos(
+                u64::from(self.clock.now()).saturating_sub(u64::from(self.due_time)),
+            ),
+            RunMode::HistoricalFrom(_) => Duration::ZERO,
+        }
+    }
+
+    /// Whether [lag](Self::lag) exceeds the budget set with
+    /// [GraphBuilder::lag_budget].  Always false without a budget.
+    pub fn is_behind(&self) -> bool {
+        self.lag_budget.is_some_and(|budget| self.lag() > budget)
+    }
+
+    /// The graph's [Clock], set with [GraphBuilder::with_clock].  Realtime
+    /// sources should stamp values with `state.clock().now()` rather than
+    /// [NanoTime::now], so they follow a mock clock in tests.
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
+
     /// Engine time elapsed since the run started.
     ///
     /// Saturates at zero: `state.time` is initialised to `NanoTime::ZERO` and
@@ -280,6 +514,13 @@ impl GraphState {
         self.start_time
     }
 
+    /// When the run ends, for nodes that bound their work by it, such as a
+    /// session or warm-up window.  See [RunFor::end_time]: None means
+    /// unbounded.
+    pub fn end_time(&self) -> Option<NanoTime> {
+        self.run_for.end_time(self.start_time)
+    }
+
     pub(crate) fn ready_notifier(&self) -> ReadyNotifier {
         ReadyNotifier {
             node_index: self
@@ -301,8 +542,7 @@ impl GraphState {
                     );
                 }
                 Arc::new(
-                    tokio::runtime::Builder::new_multi_thread()
-                        .enable_all()
+                    self.runtime_config
                         .build()
                         .expect("failed to build tokio runtime"),
                 )
@@ -310,11 +550,35 @@ impl GraphState {
             .clone()
     }
 
-    pub fn add_callback(&mut self, time: NanoTime) {
+    /// Schedules the current node to cycle at `time`.
+    pub fn schedule_at_self(&mut self, time: NanoTime) {
         let ix = self
             .current_node_index
-            .expect("add_callback called outside of a node cycle");
+            .expect("schedule_at_self called outside of a node cycle");
+        self.add_callback_for_node(ix, time);
+    }
+
+    #[deprecated(note = "renamed to schedule_at_self")]
+    pub fn add_callback(&mut self, time: NanoTime) {
+        self.schedule_at_self(time);
+    }
+
+    /// Schedules `node` to cycle at `time`, e.g. an order timeout node
+    /// waking the node that cancels the order.  Fails if `node` is not
+    /// wired into the graph.
+    pub fn schedule_at(&mut self, node: Rc<dyn Node>, time: NanoTime) -> anyhow::Result<()> {
+        let ix = self
+            .node_index(node.clone())
+            .ok_or_else(|| anyhow::anyhow!("schedule_at: {node} is not wired into the graph"))?;
         self.add_callback_for_node(ix, time);
+        Ok(())
+    }
+
+    /// Schedules `node` to cycle `delay` after the current time.  See
+    /// [schedule_at](Self::schedule_at).
+    pub fn schedule_after(&mut self, node: Rc<dyn Node>, delay: Duration) -> anyhow::Result<()> {
+        let time = self.time + delay;
+        self.schedule_at(node, time)
     }
 
     pub(crate) fn current_node_id(&self) -> usize {
@@ -329,8 +593,9 @@ impl GraphState {
         // Registering a node to tick every cycle is idempotent: deduplicate so a
         // node that calls this from `cycle()` (rather than once from `start()`)
         // doesn't grow `always_callbacks` without bound and slow every cycle.
-        if !self.always_callbacks.contains(&ix) {
-            self.always_callbacks.push(ix);
+        // Kept sorted so always-callbacks cycle in wiring order.
+        if let Err(pos) = self.always_callbacks.binary_search(&ix) {
+            self.always_callbacks.insert(pos, ix);
         }
     }
 
@@ -357,8 +622,8 @@ impl GraphState {
     /// triggers the calling node on each tick (true) or is read-only (false).
     /// Processed at the end of the current cycle.
     ///
-    /// If `recycle` is true, `add_callback(state.time() + 1ns)` is called on
-    /// the new upstream after it is wired, scheduling it to fire at `t+1ns`.
+    /// If `recycle` is true, the new upstream is scheduled, after it is
+    /// wired, to fire at `t+1ns`.
     /// This lets the calling node catch the value that triggered the
     /// `add_upstream` call without waiting for the next source tick.
     #[cfg(feature = "dynamic-graph")]
@@ -376,17 +641,93 @@ impl GraphState {
 
     /// Deregister `node` at the end of the current cycle:
     /// unlinks it from all upstream downstream-lists and all downstream upstream-lists,
-    /// then calls stop() + teardown().
+    /// then calls stop() + teardown().  The node is dropped from `node_to_index`,
+    /// so it can be wired in again later, as a new node.
     ///
-    /// **Note on memory**: removed nodes are marked inactive but their entries in the
-    /// internal index (`node_to_index`, `node_ticked`, `node_dirty`, `nodes`) are never
-    /// freed. In long-running graphs that add and remove many nodes over time, these
-    /// dead entries accumulate. This is a known limitation of the current implementation.
+    /// **Note on memory**: removed nodes are marked inactive but their slots in the
+    /// per-index tables (`node_ticked`, `node_dirty`, `nodes`) are never freed. In
+    /// long-running graphs that add and remove many nodes over time, these dead slots
+    /// accumulate. This is a known limitation of the current implementation.
     #[cfg(feature = "dynamic-graph")]
     pub fn remove_node(&mut self, node: Rc<dyn Node>) {
         self.pending_removals.push(node);
     }
 
+    /// Builds a consumer subgraph with `build` and wires it into the running
+    /// graph at the end of the current cycle, so it sees every tick from the
+    /// next cycle on.  Call it from a node's `cycle`, e.g. to give a client
+    /// that just connected its own view of existing streams while a realtime
+    /// graph runs.  The new nodes are set up and started as they are wired.
+    /// They may only consume existing streams: a subgraph that introduces a
+    /// new source is rejected here, before anything is queued.
+    #[cfg(feature = "dynamic-graph")]
+    pub fn attach(
+        &mut self,
+        build: impl FnOnce(&AttachCtx) -> Rc<dyn Node>,
+    ) -> anyhow::Result<AttachHandle> {
+        let (root, handle) = self.prepare_attach(build)?;
+        self.pending_attachments.push(root);
+        Ok(handle)
+    }
+
+    /// Unwires, stops and tears down the nodes added by an earlier
+    /// [attach](Self::attach) at the end of the current cycle, as for
+    /// [remove_node](Self::remove_node).
+    #[cfg(feature = "dynamic-graph")]
+    pub fn detach(&mut self, handle: AttachHandle) {
+        self.pending_removals.extend(handle.nodes);
+    }
+
+    /// Runs `build` and checks that every node it adds (transitively) hangs
+    /// off nodes that are already wired: a new source would have nothing
+    /// driving it in step with the rest of the graph.  Returns the root to
+    /// wire and a handle to the nodes it adds.
+    #[cfg(feature = "dynamic-graph")]
+    fn prepare_attach(
+        &self,
+        build: impl FnOnce(&AttachCtx) -> Rc<dyn Node>,
+    ) -> anyhow::Result<(Rc<dyn Node>, AttachHandle)> {
+        let root = build(&AttachCtx { state: self });
+        let mut nodes = Vec::new();
+        let mut stack = vec![root.clone()];
+        let mut visited: HashSet<ByThinAddress<Rc<dyn Node>>> = HashSet::new();
+        while let Some(node) = stack.pop() {
+            if self.seen(node.clone()) || !visited.insert(ByThinAddress(node.clone())) {
+                continue;
+            }
+            let ups = node.upstreams();
+            anyhow::ensure!(
+                !(ups.active.is_empty() && ups.passive.is_empty()),
+                "cannot attach `{}`: attached nodes must be downstream of the running graph",
+                node.type_name()
+            );
+            stack.extend(ups.active);
+            stack.extend(ups.passive);
+            nodes.push(node);
+        }
+        Ok((root, AttachHandle { nodes }))
+    }
+
+    /// Records, for the node being cycled, the time its value was originally
+    /// produced when that differs from engine time, e.g. the sender's time
+    /// of a value received from another graph.
+    pub fn set_value_time(&mut self, time: NanoTime) {
+        let ix = self
+            .current_node_index
+            .expect("set_value_time called outside of a node cycle");
+        self.value_times[ix] = Some((self.cycle_count, time));
+    }
+
+    /// The value time of `node` on the current engine cycle: the time it
+    /// recorded with [set_value_time](Self::set_value_time), or engine time
+    /// if it recorded none.
+    pub fn value_time(&self, node: Rc<dyn Node>) -> NanoTime {
+        self.node_index(node)
+            .and_then(|i| self.value_times[i])
+            .filter(|(cycle, _)| *cycle == self.cycle_count)
+            .map_or(self.time, |(_, time)| time)
+    }
+
     #[allow(dead_code)]
     /// Returns true if node has ticked on the current engine cycle
     pub(crate) fn node_index_ticked(&self, node_index: usize) -> bool {
@@ -403,18 +744,33 @@ impl GraphState {
             .unwrap_or(NanoTime::MAX)
     }
 
+    /// Time of the earliest pending scheduled callback, if any.
+    pub(crate) fn next_callback_time(&self) -> Option<NanoTime> {
+        self.scheduled_callbacks.next_time()
+    }
+
     pub(crate) fn add_callback_for_node(&mut self, node_index: usize, time: NanoTime) {
-        self.scheduled_callbacks.push(node_index, time);
+        // In historical mode time only moves forward, so a callback before
+        // the current time can never fire when it asked to.  Realtime
+        // callbacks in the past are fine: they fire as soon as possible.
+        debug_assert!(
+            !matches!(self.run_mode, RunMode::HistoricalFrom(_)) || time >= self.time,
+            "node [{node_index}] scheduled a historical callback at {time}, before the current graph time {}",
+            self.time,
+        );
+        // Ranked by node index, so callbacks due at the same time cycle in
+        // wiring order whichever was scheduled first.
+        self.scheduled_callbacks
+            .push_ranked(node_index, time, node_index as u64);
     }
 
     fn wait_ready_callback(&mut self, end_time: NanoTime) -> Option<usize> {
-        let now = NanoTime::now();
-        if now > end_time {
+        if self.clock.now() > end_time {
             // might step in here if timeout on recv isnt 100%
             // accurate
             None
         } else {
-            let timeout = u64::from(end_time - now);
+            let timeout = self.clock.idle_timeout(end_time);
             select! {
                 recv(self.ready_callbacks) -> msg => {
                     // Only `Err` if all senders are dropped. Senders live on
@@ -422,13 +778,48 @@ impl GraphState {
                     // means a worker has gone away mid-run; treat as no event.
                     msg.ok()
                 },
-                default(Duration::from_nanos(timeout)) => {
+                default(timeout) => {
                     None
                 }
             }
         }
     }
 
+    /// With [strict_uninitialized](GraphBuilder::strict_uninitialized) on,
+    /// fails the cycle if `upstream` has never ticked, so its value is still
+    /// the default.  Only a flag check when off.  Callers then read
+    /// `upstream` as usual, e.g. by `peek_ref_cell`, so nothing is cloned
+    /// for the check.  Used by the built-in combining operators, e.g.
+    /// [bimap](crate::nodes::bimap).
+    #[inline]
+    pub fn ensure_ticked_ever<T: 'static>(
+        &self,
+        upstream: &Rc<dyn Stream<T>>,
+    ) -> anyhow::Result<()> {
+        if !self.strict_uninitialized {
+            return Ok(());
+        }
+        self.ensure_node_ticked_ever(upstream.clone().as_node())
+    }
+
+    fn ensure_node_ticked_ever(&self, upstream: Rc<dyn Node>) -> anyhow::Result<()> {
+        let Some(index) = self.node_index(upstream.clone()) else {
+            return Ok(());
+        };
+        if self.first_tick_done[index] {
+            return Ok(());
+        }
+        // The reader is mid-cycle, so can't be borrowed for its name here;
+        // the engine adds it as context to the error.
+        let reader = self
+            .current_node_index
+            .map_or("a node".to_string(), |ix| format!("node [{ix}]"));
+        anyhow::bail!(
+            "{reader} read [{index}] {} before it ever ticked, so saw its default value",
+            upstream.type_name()
+        )
+    }
+
     pub fn node_index(&self, node: Rc<dyn Node>) -> Option<usize> {
         let key = ByThinAddress(node.clone());
         self.node_to_index.get(&key).copied()
@@ -443,6 +834,8 @@ impl GraphState {
     fn push_node(&mut self, node: Rc<dyn Node>) {
         let index = self.node_ticked.len();
         self.node_ticked.push(false);
+        self.first_tick_done.push(false);
+        self.value_times.push(None);
         //self.nodes.push(node.clone());
         self.node_to_index
             .insert(ByThinAddress(node.clone()), index);
@@ -461,7 +854,7 @@ impl GraphState {
     }
 
     pub fn run_for(&self) -> RunFor {
-        self.run_for
+        self.run_for.clone()
     }
 
     pub fn log(&self, level: log::Level, msg: &str) {
@@ -484,12 +877,306 @@ impl GraphState {
     pub(crate) fn mark_dirty(&mut self, index: usize) {
         if !self.node_dirty[index] {
             let layer = self.nodes[index].layer;
-            self.dirty_nodes_by_layer[layer].push(index);
+            insert_dirty(&mut self.dirty_nodes_by_layer[layer], index);
             self.node_dirty[index] = true;
         }
     }
 }
 
+/// Shared values registered with [GraphBuilder::with_context], one per type.
+/// Values are `Send + Sync` so sub-graphs on worker threads can share them.
+#[derive(Clone, Default)]
+pub(crate) struct GraphContext {
+    values: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl GraphContext {
+    fn insert<T: Any + Send + Sync>(&mut self, value: T) {
+        self.values.insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    fn get<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        let value = self.values.get(&TypeId::of::<T>())?.clone();
+        value.downcast::<T>().ok()
+    }
+}
+
+/// Builds a [Graph] with shared context values that any node can read from
+/// [GraphState::context], saving threading the same configuration or
+/// services through every closure.  Created by [Graph::builder].
+/// ```
+/// # use wingfoil::*;
+/// # use std::time::Duration;
+/// struct Config {
+///     scale: u64,
+/// }
+/// let node = ticker(Duration::from_nanos(1)).produce(|| ());
+/// let mut graph = Graph::builder()
+///     .with_context(Config { scale: 10 })
+///     .build(vec![node], RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(1));
+/// graph.run().unwrap();
+/// ```
+#[derive(Default)]
+pub struct GraphBuilder {
+    context: GraphContext,
+    progress: Option<ProgressReporter>,
+    strict_uninitialized: bool,
+    clock: Option<Arc<dyn Clock>>,
+    fuse_maps: bool,
+    lag_budget: Option<Duration>,
+}
+
+impl GraphBuilder {
+    /// Registers `value` as the context of type `T`, replacing any earlier
+    /// value of that type.
+    #[must_use]
+    pub fn with_context<T: Any + Send + Sync>(mut self, value: T) -> Self {
+        self.context.insert(value);
+        self
+    }
+
+    /// Calls `func` with the run's [Progress] at most once per `every` of
+    /// wall clock time, checked after each cycle, and once more when the run
+    /// finishes.  The `progress` feature adds `ProgressBar`, a ready-made
+    /// stderr display.
+    #[must_use]
+    pub fn on_progress(mut self, every: Duration, func: impl Fn(Progress) + 'static) -> Self {
+        self.progress = Some(ProgressReporter::new(every, Box::new(func)));
+        self
+    }
+
+    /// Fails the run when a built-in combining operator, such as
+    /// [bimap](crate::nodes::bimap) or [sample](crate::nodes::StreamOperators::sample),
+    /// reads an upstream that has never ticked, rather than letting its
+    /// default value (e.g. a price of `0.0`) flow on.  Off by default; a
+    /// debugging aid for tests and validation runs.  Reads are checked by
+    /// [GraphState::ensure_ticked_ever], which costs only a flag check when off.
+    #[must_use]
+    pub fn strict_uninitialized(mut self, strict: bool) -> Self {
+        self.strict_uninitialized = strict;
+        self
+    }
+
+    /// Reads the time from `clock` rather than the wall clock, e.g. a
+    /// [MockClock](crate::MockClock) to make a realtime test deterministic.
+    /// Sub-graphs run by [producer](crate::nodes::producer) and
+    /// [mapper](crate::nodes::StreamOperators::mapper) share it.
+    #[must_use]
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// How far a realtime graph may fall behind before
+    /// [GraphState::is_behind] reports it, and so before
+    /// [skip_if_behind](crate::nodes::StreamOperators::skip_if_behind)
+    /// starts shedding ticks.  Unset by default, so never behind.
+    #[must_use]
+    pub fn lag_budget(mut self, budget: Duration) -> Self {
+        self.lag_budget = Some(budget);
+        self
+    }
+
+    /// Runs [fuse_maps] over the graph before wiring it, so chains of maps
+    /// cost one node each.  Off by default.
+    #[must_use]
+    pub fn fuse_maps(mut self, fuse: bool) -> Self {
+        self.fuse_maps = fuse;
+        self
+    }
+
+    pub fn build(self, root_nodes: Vec<Rc<dyn Node>>, run_mode: RunMode, run_for: RunFor) -> Graph {
+        if self.fuse_maps {
+            fuse_maps(&root_nodes);
+        }
+        let mut graph = Graph::new(root_nodes, run_mode, run_for);
+        graph.state.context = self.context;
+        graph.state.progress = self.progress;
+        graph.state.strict_uninitialized = self.strict_uninitialized;
+        graph.state.lag_budget = self.lag_budget;
+        if let Some(clock) = self.clock {
+            graph.set_clock(clock);
+        }
+        graph
+    }
+}
+
+/// Configures the tokio runtime that a graph lazily creates for its async
+/// nodes.  The default matches tokio's: one worker per core, unpinned.
+/// Used by [Graph::new_with_runtime_config].
+/// ```
+/// # use wingfoil::*;
+/// # use std::time::Duration;
+/// let config = RuntimeConfig::default()
+///     .with_worker_threads(1)
+///     .with_thread_name("md-io")
+///     .with_core_ids(vec![0]);
+/// let node = ticker(Duration::from_nanos(1)).produce(|| ());
+/// let mut graph = Graph::new_with_runtime_config(
+///     vec![node],
+///     RunMode::HistoricalFrom(NanoTime::ZERO),
+///     RunFor::Cycles(1),
+///     config,
+/// );
+/// graph.run().unwrap();
+/// ```
+#[cfg(feature = "async")]
+#[derive(Debug, Clone, Default)]
+pub struct RuntimeConfig {
+    worker_threads: Option<usize>,
+    thread_name: Option<String>,
+    core_ids: Vec<usize>,
+}
+
+#[cfg(feature = "async")]
+impl RuntimeConfig {
+    /// Number of worker threads, instead of one per core.
+    #[must_use]
+    pub fn with_worker_threads(mut self, worker_threads: usize) -> Self {
+        assert!(worker_threads > 0, "worker_threads must be positive");
+        self.worker_threads = Some(worker_threads);
+        self
+    }
+
+    /// Name given to every worker thread, as seen in `top -H` or a debugger.
+    #[must_use]
+    pub fn with_thread_name(mut self, thread_name: impl Into<String>) -> Self {
+        self.thread_name = Some(thread_name.into());
+        self
+    }
+
+    /// Pins every worker thread to this set of cores, keeping async IO off
+    /// the graph's own core.  Linux only: elsewhere this is ignored.
+    #[must_use]
+    pub fn with_core_ids(mut self, core_ids: Vec<usize>) -> Self {
+        self.core_ids = core_ids;
+        self
+    }
+
+    fn build(&self) -> std::io::Result<tokio::runtime::Runtime> {
+        let mut builder = tokio::runtime::Builder::new_multi_thread();
+        builder.enable_all();
+        if let Some(worker_threads) = self.worker_threads {
+            builder.worker_threads(worker_threads);
+        }
+        if let Some(thread_name) = &self.thread_name {
+            builder.thread_name(thread_name);
+        }
+        if !self.core_ids.is_empty() {
+            let core_ids = self.core_ids.clone();
+            builder.on_thread_start(move || {
+                if let Err(err) = pin_current_thread(&core_ids) {
+                    log::warn!("failed to pin tokio worker to cores {core_ids:?}: {err}");
+                }
+            });
+        }
+        builder.build()
+    }
+}
+
+#[cfg(all(feature = "async", target_os = "linux"))]
+fn pin_current_thread(core_ids: &[usize]) -> std::io::Result<()> {
+    let max = libc::CPU_SETSIZE as usize;
+    if let Some(&bad) = core_ids.iter().find(|&&core| core >= max) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("core {bad} is out of range (CPU_SETSIZE = {max})"),
+        ));
+    }
+    // SAFETY: `set` is a plain bitmask, zeroed before use, and every index
+    // passed to `CPU_SET` was checked against `CPU_SETSIZE` above.
+    let rc = unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_ZERO(&mut set);
+        for &core in core_ids {
+            libc::CPU_SET(core, &mut set);
+        }
+        libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set)
+    };
+    if rc == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+#[cfg(all(feature = "async", not(target_os = "linux")))]
+fn pin_current_thread(_core_ids: &[usize]) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Fuses chains of [map](crate::nodes::StreamOperators::map)s reachable
+/// from `roots`, so that `.map(f).map(g).map(h)` cycles as one node applying
+/// the three closures in turn.  Returns how many map nodes were bypassed.
+///
+/// A map is only folded into the map that reads it when nothing else in the
+/// graph reads it and it is not a root.  A map folded away no longer ticks,
+/// so pass any map you want to keep peeking as a root.
+/// Call it before building the graph;
+/// [GraphBuilder::fuse_maps] does this for you.
+/// ```
+/// # use wingfoil::*;
+/// # use std::time::Duration;
+/// let total = ticker(Duration::from_nanos(100))
+///     .count()
+///     .map(|n| n + 1)
+///     .map(|n| n * 2)
+///     .map(|n| n - 1)
+///     .fold(|total: &mut u64, n| *total += n);
+/// assert_eq!(fuse_maps(&[total.clone().as_node()]), 2);
+/// ```
+pub fn fuse_maps(roots: &[Rc<dyn Node>]) -> usize {
+    fn key(node: &Rc<dyn Node>) -> *const () {
+        Rc::as_ptr(node) as *const ()
+    }
+    // Post-order, so every upstream chain is fused before its reader is
+    // visited.
+    let mut seen: HashSet<*const ()> = HashSet::new();
+    let mut order: Vec<Rc<dyn Node>> = Vec::new();
+    let mut stack: Vec<(Rc<dyn Node>, bool)> = roots
+        .iter()
+        .rev()
+        .map(|root| (root.clone(), false))
+        .collect();
+    while let Some((node, expanded)) = stack.pop() {
+        if expanded {
+            order.push(node);
+            continue;
+        }
+        if !seen.insert(key(&node)) {
+            continue;
+        }
+        let upstreams = node.upstreams();
+        stack.push((node, true));
+        for upstream in upstreams.active.into_iter().chain(upstreams.passive) {
+            if !seen.contains(&key(&upstream)) {
+                stack.push((upstream, false));
+            }
+        }
+    }
+    // Fan-out within the graph, counting a root as read.
+    let mut readers: HashMap<*const (), usize> = HashMap::new();
+    for node in roots {
+        *readers.entry(key(node)).or_default() += 1;
+    }
+    for node in &order {
+        let upstreams = node.upstreams();
+        for upstream in upstreams.active.iter().chain(&upstreams.passive) {
+            *readers.entry(key(upstream)).or_default() += 1;
+        }
+    }
+    order
+        .iter()
+        .filter(|node| {
+            let upstreams = node.upstreams();
+            match (upstreams.active.as_slice(), upstreams.passive.is_empty()) {
+                ([upstream], true) => readers[&key(upstream)] == 1 && node.fuse_upstream(),
+                _ => false,
+            }
+        })
+        .count()
+}
+
 /// Engine for co-ordinating execution of [Node]s
 pub struct Graph {
     pub(crate) state: GraphState,
@@ -504,6 +1191,42 @@ impl Graph {
         graph
     }
 
+    /// Like [Graph::new] but with control over the tokio runtime that async
+    /// nodes run on.
+    #[cfg(feature = "async")]
+    pub fn new_with_runtime_config(
+        root_nodes: Vec<Rc<dyn Node>>,
+        run_mode: RunMode,
+        run_for: RunFor,
+        runtime_config: RuntimeConfig,
+    ) -> Graph {
+        let start_time = run_mode.start_time();
+        let mut state = GraphState::new(run_mode, run_for, start_time);
+        state.runtime_config = runtime_config;
+        let mut graph = Graph { state };
+        graph.initialise(root_nodes);
+        graph
+    }
+
+    /// Starts a [GraphBuilder], for a graph with shared context values.
+    pub fn builder() -> GraphBuilder {
+        GraphBuilder::default()
+    }
+
+    /// Gives a sub-graph the context of the graph that spawned it.
+    #[cfg(feature = "async")]
+    pub(crate) fn inherit_context(&mut self, context: GraphContext) {
+        self.state.context = context;
+    }
+
+    /// Reads the time from `clock`, restarting a realtime graph at its time.
+    pub(crate) fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        if self.state.run_mode == RunMode::RealTime {
+            self.state.start_time = clock.now();
+        }
+        self.state.clock = clock;
+    }
+
     #[cfg(feature = "async")]
     pub fn new_with(
         root_nodes: Vec<Rc<dyn Node>>,
@@ -569,23 +1292,20 @@ impl Graph {
 
     fn resolve_start_end(&self) -> RunBounds {
         let start_time = match self.state.run_mode() {
-            RunMode::RealTime => NanoTime::now(),
+            RunMode::RealTime => self.state.clock.now(),
             RunMode::HistoricalFrom(t) => t,
         };
         // Defaults leave the loop unbounded until refined by `run_for`.
-        let mut end_time = NanoTime::MAX;
+        let end_time = self
+            .state
+            .run_for
+            .end_time(start_time)
+            .unwrap_or(NanoTime::MAX);
         let mut end_cycle = u32::MAX;
-        match self.state.run_for {
-            RunFor::Duration(duration) => {
-                end_time = start_time + duration;
-                debug!("end_time = {end_time}",);
-            }
-            RunFor::Cycles(cycle) => {
-                end_cycle = cycle;
-                debug!("end_cycle = {end_cycle}",);
-            }
-            RunFor::Forever => {}
+        if let RunFor::Cycles(cycle) = self.state.run_for {
+            end_cycle = cycle;
         }
+        debug!("end_time = {end_time}, end_cycle = {end_cycle}");
         RunBounds {
             start_time,
             end_time,
@@ -593,57 +1313,88 @@ impl Graph {
         }
     }
 
+    /// Everything that happens between two engine cycles: checks the run
+    /// bounds, flags the last cycle and advances time by marking the nodes
+    /// whose callbacks are due. Shared by [`run_nodes`](Self::run_nodes) and
+    /// [`Stepper::step`] so both drive the graph identically.
+    fn prepare_cycle(&mut self, cycles: u32, bounds: &RunBounds) -> anyhow::Result<CyclePrep> {
+        let RunBounds {
+            end_time,
+            end_cycle,
+            ..
+        } = *bounds;
+        // Single source of truth for whether we have reached the configured
+        // bound: the duration elapsed or the cycle count was hit.
+        // Comparisons stay `>=` to preserve historical behavior (see #374).
+        let cycles_done = cycles >= end_cycle;
+        let time_done = self.state.time >= end_time;
+        let condition_done = match &self.state.run_for {
+            RunFor::Condition(condition) => condition(cycles, self.state.elapsed()),
+            _ => false,
+        };
+        // Break once the bound has been reached. The cycle-count bound can
+        // terminate immediately (it requires no final cycle to run), which
+        // gives `Cycles(0)` a clean zero-cycle exit; the time bound is gated
+        // on `is_last_cycle` so the final scheduled cycle still executes.
+        if cycles_done || condition_done || (self.state.is_last_cycle && time_done) {
+            debug!(
+                "Finished. {:}, {:}, {:}, {:}, {:}",
+                time_done, cycles_done, condition_done, self.state.time, end_time
+            );
+            return Ok(CyclePrep::Finished);
+        }
+        // One-cycle lookahead: flag the upcoming cycle as the last. The
+        // `cycles + 1 >= end_cycle` form avoids the `end_cycle - 1`
+        // underflow that previously wrapped to `u32::MAX` for `Cycles(0)`.
+        if !self.state.is_last_cycle && (cycles + 1 >= end_cycle || time_done) {
+            debug!("last cycle");
+            self.state.is_last_cycle = true;
+        }
+        if matches!(self.state.run_mode(), RunMode::RealTime) {
+            if !self.process_callbacks_realtime(end_time) {
+                return Ok(CyclePrep::Idle);
+            }
+        } else if !self.process_callbacks_historical()? {
+            debug!("Terminating early.");
+            return Ok(CyclePrep::Finished);
+        }
+        Ok(CyclePrep::Ready)
+    }
+
     pub(crate) fn run_nodes(&mut self) -> anyhow::Result<()> {
         let run_timer = Instant::now();
         let mut cycles: u32 = 0;
         let mut empty_cycles: u32 = 0;
-        let RunBounds {
-            start_time,
-            end_time,
-            end_cycle,
-        } = self.resolve_start_end();
-        let is_realtime = matches!(self.state.run_mode(), RunMode::RealTime);
-        self.state.start_time = start_time;
+        let bounds = self.resolve_start_end();
+        self.state.start_time = bounds.start_time;
+        if let Some(reporter) = &mut self.state.progress {
+            reporter.start();
+        }
         loop {
-            // Single source of truth for whether we have reached the configured
-            // bound: the duration elapsed or the cycle count was hit.
-            // Comparisons stay `>=` to preserve historical behavior (see #374).
-            let cycles_done = cycles >= end_cycle;
-            let time_done = self.state.time >= end_time;
-            // Break once the bound has been reached. The cycle-count bound can
-            // terminate immediately (it requires no final cycle to run), which
-            // gives `Cycles(0)` a clean zero-cycle exit; the time bound is gated
-            // on `is_last_cycle` so the final scheduled cycle still executes.
-            if cycles_done || (self.state.is_last_cycle && time_done) {
-                debug!(
-                    "Finished. {:}, {:}, {:}, {:}",
-                    time_done, cycles_done, self.state.time, end_time
+            if let Some((deadline, budget)) = self.state.wall_deadline
+                && Instant::now() >= deadline
+            {
+                anyhow::bail!(
+                    "Run exceeded its wall-clock deadline of {budget:?} after {cycles} cycles"
                 );
-                break;
-            }
-            // One-cycle lookahead: flag the upcoming cycle as the last. The
-            // `cycles + 1 >= end_cycle` form avoids the `end_cycle - 1`
-            // underflow that previously wrapped to `u32::MAX` for `Cycles(0)`.
-            if !self.state.is_last_cycle && (cycles + 1 >= end_cycle || time_done) {
-                debug!("last cycle");
-                self.state.is_last_cycle = true;
-            }
-            if is_realtime {
-                let progressed = self.process_callbacks_realtime(end_time);
-                if !progressed {
+            }
+            match self.prepare_cycle(cycles, &bounds)? {
+                CyclePrep::Finished => break,
+                CyclePrep::Idle => {
                     empty_cycles += 1;
                     continue;
                 }
-            } else {
-                let progressed = self.process_callbacks_historical()?;
-                if !progressed {
-                    debug!("Terminating early.");
-                    break;
-                }
+                CyclePrep::Ready => {}
             }
             self.cycle()?;
             cycles += 1;
             debug!("cycles={cycles}");
+            if self.state.progress.as_ref().is_some_and(|r| r.due()) {
+                self.report_progress(cycles, &bounds, false);
+            }
+        }
+        if self.state.progress.is_some() {
+            self.report_progress(cycles, &bounds, true);
         }
         let elapsed = run_timer.elapsed();
         debug!("{empty_cycles} empty cycles");
@@ -656,6 +1407,22 @@ impl Graph {
         Ok(())
     }
 
+    fn report_progress(&mut self, cycles: u32, bounds: &RunBounds, finished: bool) {
+        let time = self.state.time;
+        if let Some(reporter) = &mut self.state.progress {
+            let progress = Progress {
+                time,
+                start_time: bounds.start_time,
+                end_time: (bounds.end_time != NanoTime::MAX).then_some(bounds.end_time),
+                cycles,
+                end_cycle: (bounds.end_cycle != u32::MAX).then_some(bounds.end_cycle),
+                elapsed: reporter.elapsed(),
+                finished,
+            };
+            reporter.report(progress);
+        }
+    }
+
     #[cfg_attr(feature = "instrument-run", tracing::instrument(skip_all))]
     pub fn run(&mut self) -> anyhow::Result<()> {
         // Surface any wiring error (e.g. a cycle) detected during construction
@@ -670,6 +1437,7 @@ impl Graph {
         // leaking them until the process exits.
         self.setup_nodes()?;
 
+        self.state.lifecycle = Lifecycle::Running;
         let start_result = self.start_nodes();
         // Skip the run loop if any node failed to start, but still stop and
         // tear down so partially-started nodes get cleaned up.
@@ -679,6 +1447,7 @@ impl Graph {
             Ok(())
         };
         let stop_result = self.stop_nodes();
+        self.state.lifecycle = Lifecycle::Stopped;
         let teardown_result = self.teardown_nodes();
 
         // Surface the first failure in lifecycle order; attach any later ones so
@@ -686,18 +1455,85 @@ impl Graph {
         first_error([start_result, run_result, stop_result, teardown_result])
     }
 
+    /// Like [run](Self::run) but fails if the run takes longer than
+    /// `wall_timeout` of real time, whatever the [RunMode] and [RunFor].
+    /// Nodes are still stopped and torn down.  Guards tests against a
+    /// realtime [RunFor::Forever] graph hanging the suite.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let err = ticker(Duration::from_millis(1))
+    ///     .count()
+    ///     .into_graph(RunMode::RealTime, RunFor::Forever)
+    ///     .run_with_deadline(Duration::from_millis(20))
+    ///     .unwrap_err();
+    /// assert!(err.to_string().contains("deadline"));
+    /// ```
+    pub fn run_with_deadline(&mut self, wall_timeout: Duration) -> anyhow::Result<()> {
+        self.state.wall_deadline = Some((Instant::now() + wall_timeout, wall_timeout));
+        let result = self.run();
+        self.state.wall_deadline = None;
+        result
+    }
+
+    /// Sets up and starts the graph, returning a [Stepper] that drives it one
+    /// engine cycle at a time.  Streams can be peeked between steps, which is
+    /// handy for debugging a single node from a unit test.
+    /// Only [RunMode::HistoricalFrom] is supported.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let count = ticker(Duration::from_nanos(100)).count();
+    /// let mut graph = count.into_graph(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever);
+    /// let mut stepper = graph.stepper().unwrap();
+    /// stepper.step().unwrap();
+    /// assert_eq!(count.peek_value(), 1);
+    /// stepper.advance_to(NanoTime::new(300)).unwrap();
+    /// assert_eq!(count.peek_value(), 4);
+    /// stepper.finish().unwrap();
+    /// ```
+    pub fn stepper(&mut self) -> anyhow::Result<Stepper<'_>> {
+        if let Some(e) = self.state.wiring_error.take() {
+            return Err(e);
+        }
+        anyhow::ensure!(
+            matches!(self.state.run_mode(), RunMode::HistoricalFrom(_)),
+            "Graph::stepper requires RunMode::HistoricalFrom"
+        );
+        let bounds = self.resolve_start_end();
+        self.state.start_time = bounds.start_time;
+        self.setup_nodes()?;
+        self.state.lifecycle = Lifecycle::Running;
+        let start_result = self.start_nodes();
+        if start_result.is_err() {
+            let stop_result = self.stop_nodes();
+            self.state.lifecycle = Lifecycle::Stopped;
+            let teardown_result = self.teardown_nodes();
+            first_error([start_result, stop_result, teardown_result])?;
+        }
+        Ok(Stepper {
+            graph: self,
+            bounds,
+            cycles: 0,
+            finished: false,
+        })
+    }
+
     #[cfg_attr(feature = "instrument-initialise", tracing::instrument(skip_all))]
     fn initialise(&mut self, root_nodes: Vec<Rc<dyn Node>>) -> &mut Graph {
         let timer = Instant::now();
         for node in root_nodes {
             // `initialise_node` is a no-op for an already-seen node, so it can be
             // called unconditionally.
-            if let Err(e) = self.initialise_node(&node) {
-                // Wiring failed (e.g. a cycle). `Graph::new` is infallible, so
-                // stash the error and skip the rest of wiring; `run()` surfaces
-                // it before touching the partially-built graph.
-                self.state.wiring_error.get_or_insert(e);
-                return self;
+            match self.initialise_node(&node) {
+                Ok(index) => self.state.root_indices.push(index),
+                Err(e) => {
+                    // Wiring failed (e.g. a cycle). `Graph::new` is infallible, so
+                    // stash the error and skip the rest of wiring; `run()` surfaces
+                    // it before touching the partially-built graph.
+                    self.state.wiring_error.get_or_insert(e);
+                    return self;
+                }
             }
         }
         let mut max_layer: i32 = -1;
@@ -712,10 +1548,7 @@ impl Graph {
             self.state.node_dirty.push(false);
             for j in 0..self.state.nodes[i].upstreams.len() {
                 let edge = self.state.nodes[i].upstreams[j];
-                self.state.nodes[edge.node_index].downstreams.push(Edge {
-                    node_index: i,
-                    active: edge.active,
-                });
+                self.link_downstream(edge.node_index, i, edge.active);
             }
         }
         for _ in 0..max_layer + 1 {
@@ -792,13 +1625,7 @@ impl Graph {
                     // All upstreams processed — finalise this node.
                     let frame = stack.pop().expect("stack non-empty");
                     in_progress.remove(&ByThinAddress(frame.node.clone()));
-                    let node_data = NodeData {
-                        node: frame.node.clone(),
-                        upstreams: frame.edges,
-                        downstreams: vec![],
-                        layer: frame.layer,
-                        active: true,
-                    };
+                    let node_data = NodeData::new(frame.node.clone(), frame.edges, frame.layer);
                     self.state.push_node(frame.node);
                     self.state.nodes.push(node_data);
                 }
@@ -811,14 +1638,27 @@ impl Graph {
             .expect("root registered after wiring"))
     }
 
-    fn mark_dirty(&mut self, index: usize) {
-        if !self.state.node_dirty[index] {
-            let layer = self.state.nodes[index].layer;
-            self.state.dirty_nodes_by_layer[layer].push(index);
-            self.state.node_dirty[index] = true;
+    /// Records `downstream` in `upstream`'s active or passive downstream
+    /// list.
+    fn link_downstream(&mut self, upstream: usize, downstream: usize, active: bool) {
+        let node_index = u32::try_from(downstream).expect("graph node count fits in u32");
+        if active {
+            let layer = self.state.nodes[downstream].layer;
+            let layer = u32::try_from(layer).expect("graph layer count fits in u32");
+            self.state.nodes[upstream]
+                .active_downstreams
+                .push(ActiveDownstream { node_index, layer });
+        } else {
+            self.state.nodes[upstream]
+                .passive_downstreams
+                .push(node_index);
         }
     }
 
+    fn mark_dirty(&mut self, index: usize) {
+        self.state.mark_dirty(index);
+    }
+
     fn process_scheduled_callbacks(&mut self) -> bool {
         let mut progressed = false;
         for i in 0..self.state.always_callbacks.len() {
@@ -883,25 +1723,45 @@ impl Graph {
 
     fn process_callbacks_realtime(&mut self, end_time: NanoTime) -> bool {
         let mut progressed = self.process_ready_callbacks();
-        if self.process_scheduled_callbacks() {
-            progressed = true;
-        }
-        if !progressed {
-            let wait_until = min(end_time, self.state.next_scheduled_time());
+        let next_scheduled = self.state.next_scheduled_time();
+        let idle =
+            self.state.always_callbacks.is_empty() && next_scheduled > self.state.clock.now();
+        if !progressed && idle {
+            let mut wait_until = min(end_time, next_scheduled);
+            if let Some((deadline, _)) = self.state.wall_deadline {
+                // wake in time for run_nodes to notice the deadline
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                wait_until = min(wait_until, self.state.clock.now() + remaining);
+            }
             if let Some(ix) = self.state.wait_ready_callback(wait_until) {
                 self.mark_dirty(ix);
                 progressed = true;
             }
         }
-        self.state.time = NanoTime::now().max(self.state.time + 1);
+        // Snap the time after any wait, so callbacks due by now fire this
+        // cycle at the clock's time rather than on the next pass.
+        self.state.time = self.state.clock.now().max(self.state.time + 1);
+        self.state.due_time = min(next_scheduled, self.state.time);
+        if self.state.time >= end_time {
+            self.state.is_last_cycle = true;
+        }
+        if self.process_scheduled_callbacks() {
+            progressed = true;
+        }
         progressed
     }
 
     #[cfg_attr(feature = "instrument-cycle", tracing::instrument(skip_all))]
     fn cycle(&mut self) -> anyhow::Result<()> {
+        self.cycle_dirty_nodes()?;
+        self.finish_cycle()
+    }
+
+    fn cycle_dirty_nodes(&mut self) -> anyhow::Result<()> {
         // Snap wall-clock time once per cycle for latency / perf telemetry.
         // Separate from `state.time` so historical mode still has deterministic
-        // logical time for business logic.
+        // logical time for business logic.  Read the real clock, not the
+        // injected one: a mock clock drives engine time, not latency stamps.
         self.state.wall_time = NanoTime::now();
         for lyr in 0..self.state.dirty_nodes_by_layer.len() {
             for i in 0..self.state.dirty_nodes_by_layer[lyr].len() {
@@ -909,11 +1769,20 @@ impl Graph {
                 self.cycle_node(ix)?;
             }
         }
+        Ok(())
+    }
+
+    /// Clears per-cycle tick/dirty state and applies any graph mutations
+    /// requested during the cycle.
+    fn finish_cycle(&mut self) -> anyhow::Result<()> {
         self.reset();
+        self.state.cycle_count += 1;
         #[cfg(feature = "dynamic-graph")]
         self.process_pending_removals()?;
         #[cfg(feature = "dynamic-graph")]
         self.process_pending_additions()?;
+        #[cfg(feature = "dynamic-graph")]
+        self.process_pending_attachments()?;
         Ok(())
     }
 
@@ -939,10 +1808,19 @@ impl Graph {
 
         if ticked {
             self.state.set_ticked(index);
-            for i in 0..self.state.nodes[index].downstreams.len() {
-                let edge = self.state.nodes[index].downstreams[i];
-                if edge.active {
-                    self.mark_dirty(edge.node_index)
+            if !self.state.first_tick_done[index] {
+                self.state.first_tick_done[index] = true;
+                self.state.current_node_index = Some(index);
+                let node = self.state.nodes[index].node.clone();
+                node.on_first_tick(&mut self.state);
+                self.state.current_node_index = None;
+            }
+            let state = &mut self.state;
+            for dn in &state.nodes[index].active_downstreams {
+                let dn_index = dn.node_index as usize;
+                if !state.node_dirty[dn_index] {
+                    insert_dirty(&mut state.dirty_nodes_by_layer[dn.layer as usize], dn_index);
+                    state.node_dirty[dn_index] = true;
                 }
             }
         }
@@ -974,14 +1852,17 @@ impl Graph {
             // Unlink from upstreams' downstreams
             let upstreams: Vec<Edge> = self.state.nodes[index].upstreams.clone();
             for edge in &upstreams {
-                self.state.nodes[edge.node_index]
-                    .downstreams
-                    .retain(|e| e.node_index != index);
+                let upstream = &mut self.state.nodes[edge.node_index];
+                upstream
+                    .active_downstreams
+                    .retain(|dn| dn.node_index as usize != index);
+                upstream
+                    .passive_downstreams
+                    .retain(|&ix| ix as usize != index);
             }
             // Unlink from downstreams' upstreams
-            let downstreams: Vec<Edge> = self.state.nodes[index].downstreams.clone();
-            for edge in &downstreams {
-                self.state.nodes[edge.node_index]
+            for dn_index in self.state.nodes[index].downstream_indices() {
+                self.state.nodes[dn_index]
                     .upstreams
                     .retain(|e| e.node_index != index);
             }
@@ -999,6 +1880,7 @@ impl Graph {
             })?;
             self.state.current_node_index = None;
             self.state.nodes[index].active = false;
+            self.state.node_to_index.remove(&ByThinAddress(node));
         }
         Ok(())
     }
@@ -1017,24 +1899,7 @@ impl Graph {
             self.initialise_node(&addition.node)?;
         }
 
-        // Indices of truly new nodes
-        let new_indices: Vec<usize> = (start_index..self.state.nodes.len()).collect();
-
-        // Push node_dirty entries for new nodes (node_ticked already pushed by push_node)
-        for _ in &new_indices {
-            self.state.node_dirty.push(false);
-        }
-
-        // Wire declared upstreams' downstreams for new nodes
-        for &ix in &new_indices {
-            let upstreams = self.state.nodes[ix].upstreams.clone();
-            for edge in upstreams {
-                self.state.nodes[edge.node_index].downstreams.push(Edge {
-                    node_index: ix,
-                    active: edge.active,
-                });
-            }
-        }
+        let new_indices = self.link_new_nodes(start_index);
 
         // Wire the dynamic caller→node edges and fix layers.
         // Track wired (caller, node) pairs to skip duplicates if add_upstream
@@ -1052,10 +1917,7 @@ impl Graph {
                         node_index,
                         active: addition.is_active,
                     });
-                self.state.nodes[node_index].downstreams.push(Edge {
-                    node_index: addition.caller_index,
-                    active: addition.is_active,
-                });
+                self.link_downstream(node_index, addition.caller_index, addition.is_active);
             }
             self.fix_layers(addition.caller_index);
             if addition.recycle {
@@ -1089,8 +1951,36 @@ impl Graph {
             }
         }
 
-        // Batch setup then batch start for new nodes only
+        self.setup_and_start_new_nodes(&new_indices)
+    }
+
+    /// Finishes wiring the nodes registered by `initialise_node` from
+    /// `start_index` onwards: pushes their dirty flags and links them into
+    /// their upstreams' downstream lists.  Returns their indices.
+    #[cfg(feature = "dynamic-graph")]
+    fn link_new_nodes(&mut self, start_index: usize) -> Vec<usize> {
+        // Indices of truly new nodes
+        let new_indices: Vec<usize> = (start_index..self.state.nodes.len()).collect();
+
+        // Push node_dirty entries for new nodes (node_ticked already pushed by push_node)
+        for _ in &new_indices {
+            self.state.node_dirty.push(false);
+        }
+
+        // Wire declared upstreams' downstreams for new nodes
         for &ix in &new_indices {
+            let upstreams = self.state.nodes[ix].upstreams.clone();
+            for edge in upstreams {
+                self.link_downstream(edge.node_index, ix, edge.active);
+            }
+        }
+        new_indices
+    }
+
+    /// Batch setup then batch start for new nodes only.
+    #[cfg(feature = "dynamic-graph")]
+    fn setup_and_start_new_nodes(&mut self, new_indices: &[usize]) -> anyhow::Result<()> {
+        for &ix in new_indices {
             let node = self.state.nodes[ix].node.clone();
             self.state.current_node_index = Some(ix);
             node.setup(&mut self.state).map_err(|e| {
@@ -1100,7 +1990,7 @@ impl Graph {
             })?;
             self.state.current_node_index = None;
         }
-        for &ix in &new_indices {
+        for &ix in new_indices {
             let node = self.state.nodes[ix].node.clone();
             self.state.current_node_index = Some(ix);
             node.start(&mut self.state).map_err(|e| {
@@ -1113,6 +2003,33 @@ impl Graph {
         Ok(())
     }
 
+    /// Wires the subgraphs queued by [GraphState::attach].
+    #[cfg(feature = "dynamic-graph")]
+    fn process_pending_attachments(&mut self) -> anyhow::Result<()> {
+        for root in std::mem::take(&mut self.state.pending_attachments) {
+            self.wire_attached(&root)?;
+        }
+        Ok(())
+    }
+
+    /// Wires `root`, checked by [GraphState::prepare_attach], and its new
+    /// upstreams into the graph, then sets up and starts them.
+    #[cfg(feature = "dynamic-graph")]
+    fn wire_attached(&mut self, root: &Rc<dyn Node>) -> anyhow::Result<()> {
+        let start_index = self.state.nodes.len();
+        self.initialise_node(root)?;
+        let new_indices = self.link_new_nodes(start_index);
+        let max_layer = new_indices
+            .iter()
+            .map(|&ix| self.state.nodes[ix].layer)
+            .max()
+            .unwrap_or(0);
+        while self.state.dirty_nodes_by_layer.len() <= max_layer {
+            self.state.dirty_nodes_by_layer.push(vec![]);
+        }
+        self.setup_and_start_new_nodes(&new_indices)
+    }
+
     /// Recalculate `node_index`'s layer based on its current upstreams,
     /// propagate any increase to its downstreams, and extend
     /// `dirty_nodes_by_layer` to accommodate the new layer.
@@ -1131,12 +2048,18 @@ impl Graph {
                 .map_or(0, |m| m + 1);
             if required > self.state.nodes[node_index].layer {
                 self.state.nodes[node_index].layer = required;
-                let downstreams: Vec<usize> = self.state.nodes[node_index]
-                    .downstreams
-                    .iter()
-                    .map(|e| e.node_index)
-                    .collect();
-                for dn_idx in downstreams {
+                let cached = u32::try_from(required).expect("graph layer count fits in u32");
+                let upstreams = self.state.nodes[node_index].upstreams.clone();
+                for edge in upstreams.iter().filter(|e| e.active) {
+                    for dn in self.state.nodes[edge.node_index]
+                        .active_downstreams
+                        .iter_mut()
+                        .filter(|dn| dn.node_index as usize == node_index)
+                    {
+                        dn.layer = cached;
+                    }
+                }
+                for dn_idx in self.state.nodes[node_index].downstream_indices() {
                     queue.push_back(dn_idx);
                 }
             }
@@ -1166,15 +2089,206 @@ impl Graph {
         output
     }
 
+    /// Prints the node table, i.e. the graph's [Display] output.  Use
+    /// `to_string()` to get it without printing.
     pub fn print(&mut self) -> &mut Graph {
-        for (i, node_data) in self.state.nodes.iter().enumerate() {
-            print!("[{i:02}] ");
-            for _ in 0..node_data.layer {
-                print!("   ");
+        self.print_layers()
+    }
+
+    /// Prints one line per layer, listing the `[index] type_name` of each
+    /// node in that layer.
+    pub fn print_layers(&mut self) -> &mut Graph {
+        print!("{self}");
+        self
+    }
+
+    /// The deepest layer in the graph.  Source nodes are at layer 0.
+    pub fn max_layer(&self) -> usize {
+        self.state
+            .nodes
+            .iter()
+            .filter(|node_data| node_data.active)
+            .map(|node_data| node_data.layer)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Number of nodes wired into the graph.
+    pub fn node_count(&self) -> usize {
+        self.state
+            .nodes
+            .iter()
+            .filter(|node_data| node_data.active)
+            .count()
+    }
+
+    /// Number of layers, i.e. `max_layer() + 1` for a non-empty graph.
+    pub fn layers(&self) -> usize {
+        if self.node_count() == 0 {
+            0
+        } else {
+            self.max_layer() + 1
+        }
+    }
+
+    /// A [NodeInfo] for every node wired into the graph, in index order.
+    pub fn nodes_info(&self) -> Vec<NodeInfo> {
+        let indices = |edges: &[Edge]| edges.iter().map(|edge| edge.node_index).collect();
+        self.state
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node_data)| node_data.active)
+            .map(|(index, node_data)| NodeInfo {
+                index,
+                type_name: node_data.node.type_name(),
+                layer: node_data.layer,
+                upstream_indices: indices(&node_data.upstreams),
+                downstream_indices: node_data.downstream_indices(),
+            })
+            .collect()
+    }
+
+    /// Checks the wired graph for structural problems before it is run:
+    ///
+    /// - root nodes that can never tick, because no source reaches them
+    ///   through active edges
+    /// - nodes wired only passively, which nothing can fire
+    /// - nodes that fire others not wired into the graph, such as demux
+    ///   children or an overflow that were left out
+    ///
+    /// Every problem found is listed in one error rather than surfacing one
+    /// at a time from `setup`.  Sources are assumed to tick, and nodes fired
+    /// directly are recognised through [MutableNode::triggers](crate::MutableNode::triggers).  Call it
+    /// before [run](Graph::run), which consumes demux children in `setup`.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(e) = &self.state.wiring_error {
+            anyhow::bail!("graph validation failed:\n  - {e:#}");
+        }
+        let nodes = &self.state.nodes;
+        let describe = |ix: usize| format!("[{ix}] {}", nodes[ix].node.type_name());
+        let mut issues = Vec::new();
+        let mut triggered: Vec<Vec<usize>> = vec![vec![]; nodes.len()];
+        let mut is_triggered = vec![false; nodes.len()];
+        for (ix, node_data) in nodes.iter().enumerate() {
+            for target in node_data.node.triggers() {
+                match self.state.node_index(target.clone()) {
+                    Some(target_ix) => {
+                        triggered[ix].push(target_ix);
+                        is_triggered[target_ix] = true;
+                    }
+                    None => issues.push(format!(
+                        "{} fires `{}`, which is not wired into the graph",
+                        describe(ix),
+                        target.type_name()
+                    )),
+                }
             }
-            println!("{:}", node_data.node);
         }
-        self
+        // Walk forward from the sources along active edges and triggers.
+        let mut can_fire = vec![false; nodes.len()];
+        let mut stack: Vec<usize> = (0..nodes.len())
+            .filter(|&ix| nodes[ix].active && nodes[ix].upstreams.is_empty())
+            .collect();
+        while let Some(ix) = stack.pop() {
+            if std::mem::replace(&mut can_fire[ix], true) {
+                continue;
+            }
+            let active = nodes[ix]
+                .active_downstreams
+                .iter()
+                .map(|dn| dn.node_index as usize);
+            stack.extend(active.chain(triggered[ix].iter().copied()));
+        }
+        for (ix, node_data) in nodes.iter().enumerate() {
+            if !node_data.active || can_fire[ix] {
+                continue;
+            }
+            let passive_only = !node_data.upstreams.is_empty()
+                && node_data.upstreams.iter().all(|edge| !edge.active)
+                && !is_triggered[ix];
+            if passive_only {
+                issues.push(format!(
+                    "{} is wired passively only and can never fire",
+                    describe(ix)
+                ));
+            } else if self.state.root_indices.contains(&ix) {
+                issues.push(format!(
+                    "root {} never ticks: no source reaches it through active edges",
+                    describe(ix)
+                ));
+            }
+        }
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            anyhow::bail!("graph validation failed:\n  - {}", issues.join("\n  - "))
+        }
+    }
+
+    /// Every node wired into the graph with its
+    /// [approx_memory](crate::MutableNode::approx_memory), largest first, then the
+    /// nodes that don't report.  Only an estimate, but enough to find the
+    /// node holding on to a growing buffer, such as an `accumulate` left in
+    /// production.
+    pub fn memory_report(&self) -> Vec<NodeMemory> {
+        let mut report: Vec<NodeMemory> = self
+            .nodes_info()
+            .into_iter()
+            .map(|info| {
+                let approx_bytes = self.state.nodes[info.index].node.approx_memory();
+                NodeMemory { info, approx_bytes }
+            })
+            .collect();
+        report.sort_by_key(|node| std::cmp::Reverse(node.approx_bytes));
+        report
+    }
+
+    /// A hash of the graph's shape: the type name, upstream indices and layer
+    /// of every node, in index order.  Graphs wired the same way hash the
+    /// same, across runs of the same build, so it can key caches of graph
+    /// output or detect unintended changes to the graph.  It is not stable
+    /// across Rust versions, and closure types appear in type names, so
+    /// editing a closure's source location can change it.
+    pub fn topology_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for info in self.nodes_info() {
+            (info.type_name, info.upstream_indices, info.layer).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// [topology_hash](Graph::topology_hash) as 16 hex digits.
+    pub fn topology_fingerprint(&self) -> String {
+        format!("{:016x}", self.topology_hash())
+    }
+
+    /// `(node_index, type_name)` of every node at `layer`.
+    pub fn nodes_at_layer(&self, layer: usize) -> Vec<(usize, String)> {
+        self.state
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node_data)| node_data.active && node_data.layer == layer)
+            .map(|(ix, node_data)| (ix, node_data.node.type_name()))
+            .collect()
+    }
+
+    /// Indices of all nodes of the given type.  `type_name` may either be the
+    /// full name, e.g. `"MapStream<u64, bool>"`, or omit the generic
+    /// parameters, e.g. `"MapStream"`.
+    pub fn find_nodes_by_type(&self, type_name: &str) -> Vec<usize> {
+        self.state
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node_data)| {
+                let name = node_data.node.type_name();
+                let base = name.split('<').next().unwrap_or_default();
+                node_data.active && (name == type_name || base == type_name)
+            })
+            .map(|(ix, _)| ix)
+            .collect()
     }
 
     pub fn export(&self, path: &str) -> Result<(), Error> {
@@ -1197,10 +2311,10 @@ impl Graph {
             writeln!(output, "    ]")?;
         }
         for (i, node) in self.state.nodes.iter().enumerate() {
-            for edge in node.downstreams.iter() {
+            for dn_index in node.downstream_indices() {
                 writeln!(output, "    edge [")?;
                 writeln!(output, "        source {i}")?;
-                writeln!(output, "        target {}", edge.node_index)?;
+                writeln!(output, "        target {dn_index}")?;
                 writeln!(output, "    ]")?;
             }
         }
@@ -1208,6 +2322,221 @@ impl Graph {
     }
 }
 
+/// Read-only view of a running graph, passed to the builder given to
+/// [GraphState::attach] or [Stepper::attach].
+#[cfg(feature = "dynamic-graph")]
+pub struct AttachCtx<'a> {
+    state: &'a GraphState,
+}
+
+#[cfg(feature = "dynamic-graph")]
+impl AttachCtx<'_> {
+    /// The current engine time.
+    pub fn time(&self) -> NanoTime {
+        self.state.time
+    }
+
+    /// Whether `node` is already wired into the graph.
+    pub fn is_wired(&self, node: Rc<dyn Node>) -> bool {
+        self.state.seen(node)
+    }
+}
+
+/// The nodes added by one [GraphState::attach] or [Stepper::attach], to be
+/// removed again with [GraphState::detach] or [Stepper::detach].
+#[cfg(feature = "dynamic-graph")]
+#[derive(Clone)]
+pub struct AttachHandle {
+    nodes: Vec<Rc<dyn Node>>,
+}
+
+#[cfg(feature = "dynamic-graph")]
+impl fmt::Debug for AttachHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AttachHandle")
+            .field("nodes", &self.nodes.len())
+            .finish()
+    }
+}
+
+/// The outcome of a single [Stepper::step].
+#[derive(Clone, Debug, PartialEq)]
+pub struct StepResult {
+    /// Engine time of the cycle.
+    pub time: NanoTime,
+    /// Indices of the nodes that ticked during the cycle, in ascending order.
+    /// See [Stepper::node_index].
+    pub ticked_nodes: Vec<usize>,
+}
+
+/// One line per layer, listing the `[index] type_name` of each node in that
+/// layer, as printed by [Graph::print].
+impl fmt::Display for Graph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for layer in 0..=self.max_layer() {
+            let nodes = self
+                .nodes_at_layer(layer)
+                .into_iter()
+                .map(|(ix, name)| format!("[{ix:02}] {name}"))
+                .join("  ");
+            writeln!(f, "[{layer:02}] {nodes}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Graph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Graph")
+            .field("id", &self.state.id)
+            .field("nodes", &self.node_count())
+            .field("layers", &self.layers())
+            .field("state", &self.state.lifecycle)
+            .field("run_mode", &self.state.run_mode)
+            .field("run_for", &self.state.run_for)
+            .field("cycles", &self.state.cycle_count)
+            .finish()
+    }
+}
+
+impl fmt::Debug for GraphState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GraphState")
+            .field("id", &self.id)
+            .field("time", &self.time)
+            .field("nodes", &self.nodes.iter().filter(|n| n.active).count())
+            .field("scheduled_callbacks", &self.scheduled_callbacks.len())
+            .finish()
+    }
+}
+
+/// Drives a historical [Graph] one engine cycle at a time.
+/// Created by [Graph::stepper].  Nodes are stopped and torn down by
+/// [finish](Stepper::finish), or on drop if `finish` was not called.
+pub struct Stepper<'a> {
+    graph: &'a mut Graph,
+    bounds: RunBounds,
+    cycles: u32,
+    finished: bool,
+}
+
+impl Stepper<'_> {
+    /// Runs the next engine cycle.  Returns `None`, without cycling, once the
+    /// [RunFor] bound is reached or there are no pending callbacks.
+    pub fn step(&mut self) -> anyhow::Result<Option<StepResult>> {
+        if self.finished {
+            return Ok(None);
+        }
+        match self.graph.prepare_cycle(self.cycles, &self.bounds)? {
+            CyclePrep::Ready => {}
+            CyclePrep::Idle | CyclePrep::Finished => return Ok(None),
+        }
+        self.graph.cycle_dirty_nodes()?;
+        let time = self.graph.state.time;
+        let ticked_nodes = self
+            .graph
+            .state
+            .node_ticked
+            .iter()
+            .enumerate()
+            .filter_map(|(ix, ticked)| ticked.then_some(ix))
+            .collect();
+        self.graph.finish_cycle()?;
+        self.cycles += 1;
+        Ok(Some(StepResult { time, ticked_nodes }))
+    }
+
+    /// Steps through every cycle scheduled at or before `time`.
+    pub fn advance_to(&mut self, time: NanoTime) -> anyhow::Result<Vec<StepResult>> {
+        let mut results = Vec::new();
+        while self.graph.state.next_scheduled_time() <= time {
+            match self.step()? {
+                Some(result) => results.push(result),
+                None => break,
+            }
+        }
+        Ok(results)
+    }
+
+    /// Queues `value` on a [CallBackStream] wired into this graph and
+    /// schedules it to tick at `time`, which must not be in the past.
+    pub fn inject<T: Element + PartialEq>(
+        &mut self,
+        stream: &Rc<RefCell<CallBackStream<T>>>,
+        value: T,
+        time: NanoTime,
+    ) -> anyhow::Result<()> {
+        let index = self
+            .node_index(stream.clone().as_node())
+            .context("inject target is not wired into this graph")?;
+        anyhow::ensure!(
+            time >= self.graph.state.time,
+            "cannot inject at {time}, engine time is already {}",
+            self.graph.state.time
+        );
+        stream.borrow_mut().push(ValueAt::new(value, time));
+        self.graph.state.add_callback_for_node(index, time);
+        Ok(())
+    }
+
+    /// The current engine time.
+    pub fn time(&self) -> NanoTime {
+        self.graph.state.time
+    }
+
+    /// Wires the node returned by `build`, and any of its upstreams not yet in
+    /// the graph, into the running graph.  As [GraphState::attach], but
+    /// applied at once rather than at the end of a cycle, so the new nodes
+    /// see every tick from the next step on.
+    #[cfg(feature = "dynamic-graph")]
+    pub fn attach(
+        &mut self,
+        build: impl FnOnce(&AttachCtx) -> Rc<dyn Node>,
+    ) -> anyhow::Result<AttachHandle> {
+        anyhow::ensure!(!self.finished, "cannot attach to a finished graph");
+        let (root, handle) = self.graph.state.prepare_attach(build)?;
+        self.graph.wire_attached(&root)?;
+        Ok(handle)
+    }
+
+    /// Unwires, stops and tears down the nodes added by an earlier
+    /// [attach](Stepper::attach), at once.  They can be attached again.
+    #[cfg(feature = "dynamic-graph")]
+    pub fn detach(&mut self, handle: AttachHandle) -> anyhow::Result<()> {
+        self.graph.state.detach(handle);
+        self.graph.process_pending_removals()
+    }
+
+    /// Index of `node` in the graph, as reported in [StepResult::ticked_nodes].
+    pub fn node_index(&self, node: Rc<dyn Node>) -> Option<usize> {
+        self.graph.state.node_index(node)
+    }
+
+    /// Stops and tears down the graph's nodes.
+    pub fn finish(mut self) -> anyhow::Result<()> {
+        self.shutdown()
+    }
+
+    fn shutdown(&mut self) -> anyhow::Result<()> {
+        if self.finished {
+            return Ok(());
+        }
+        self.finished = true;
+        let stop_result = self.graph.stop_nodes();
+        self.graph.state.lifecycle = Lifecycle::Stopped;
+        let teardown_result = self.graph.teardown_nodes();
+        first_error([stop_result, teardown_result])
+    }
+}
+
+impl Drop for Stepper<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = self.shutdown() {
+            error!("failed to shut down stepped graph: {e:?}");
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -1258,6 +2587,63 @@ mod tests {
         );
     }
 
+    // ── Context ──────────────────────────────────────────────────────────────
+
+    struct Scale(u64);
+
+    /// Reads [Scale] from the context at setup and applies it every cycle.
+    struct ScalingNode {
+        upstream: Rc<dyn Stream<u64>>,
+        scale: Option<Arc<Scale>>,
+        value: u64,
+    }
+
+    #[node(active = [upstream], output = value: u64)]
+    impl MutableNode for ScalingNode {
+        fn setup(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
+            self.scale = Some(state.context::<Scale>().context("no Scale registered")?);
+            Ok(())
+        }
+        fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+            let scale = self.scale.as_ref().map_or(0, |s| s.0);
+            self.value = self.upstream.peek_value() * scale;
+            Ok(true)
+        }
+    }
+
+    fn scaled_count() -> Rc<dyn Stream<u64>> {
+        let upstream = ticker(Duration::from_nanos(100)).count();
+        ScalingNode {
+            upstream,
+            scale: None,
+            value: 0,
+        }
+        .into_stream()
+    }
+
+    #[test]
+    fn nodes_read_context_from_setup() {
+        let scaled = scaled_count().collect();
+        Graph::builder()
+            .with_context("unrelated")
+            .with_context(Scale(10))
+            .build(
+                vec![scaled.clone().as_node()],
+                RunMode::HistoricalFrom(NanoTime::ZERO),
+                RunFor::Cycles(3),
+            )
+            .run()
+            .unwrap();
+        let values: Vec<u64> = scaled.peek_value().iter().map(|v| v.value).collect();
+        assert_eq!(values, vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn missing_context_is_none() {
+        let result = scaled_count().run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(1));
+        assert!(format!("{:#}", result.unwrap_err()).contains("no Scale registered"));
+    }
+
     // ── Graph wiring (iterative) ─────────────────────────────────────────────
 
     #[test]
@@ -1481,6 +2867,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn run_with_deadline_kills_forever_realtime_run() {
+        // The ticker fires once a second, so the deadline must also cut the
+        // idle wait between ticks short.
+        let stopped = Rc::new(std::cell::Cell::new(false));
+        let stopped_flag = stopped.clone();
+        let timer = Instant::now();
+        let err = ticker(Duration::from_secs(1))
+            .count()
+            .finally(move |_, _| {
+                stopped_flag.set(true);
+                Ok(())
+            })
+            .into_graph(RunMode::RealTime, RunFor::Forever)
+            .run_with_deadline(Duration::from_millis(50))
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("wall-clock deadline of 50ms"),
+            "{err:#}"
+        );
+        assert!(timer.elapsed() < Duration::from_millis(500));
+        assert!(stopped.get(), "stop() must run after a deadline");
+    }
+
+    #[test]
+    fn run_with_deadline_kills_forever_historical_run() {
+        let err = ticker(Duration::from_nanos(1))
+            .count()
+            .into_graph(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .run_with_deadline(Duration::from_millis(20))
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("deadline"), "{err:#}");
+    }
+
+    #[test]
+    fn run_with_deadline_passes_runs_that_finish_in_time() {
+        let count = ticker(Duration::from_millis(1)).count();
+        count
+            .clone()
+            .as_node()
+            .into_graph(RunMode::RealTime, RunFor::Cycles(3))
+            .run_with_deadline(Duration::from_secs(10))
+            .unwrap();
+        assert_eq!(count.peek_value(), 3);
+    }
+
     #[test]
     fn run_for_cycles_done_when_exceeded() {
         let rf = RunFor::Cycles(3);
@@ -1502,6 +2934,63 @@ mod tests {
         assert!(!rf.done(u32::MAX, NanoTime::MAX));
     }
 
+    #[test]
+    fn run_for_end_time_is_bounded_only_by_duration() {
+        use std::time::Duration;
+        let start = NanoTime::new(1_000);
+        let rf = RunFor::Duration(Duration::from_nanos(100));
+        assert_eq!(rf.end_time(start), Some(NanoTime::new(1_100)));
+        assert_eq!(RunFor::Cycles(3).end_time(start), None);
+        assert_eq!(RunFor::Forever.end_time(start), None);
+
+        ticker(Duration::from_nanos(10))
+            .count()
+            .finally(|_, state| {
+                assert_eq!(state.end_time(), Some(NanoTime::new(1_100)));
+                Ok(())
+            })
+            .run(RunMode::HistoricalFrom(start), rf)
+            .unwrap();
+    }
+
+    #[test]
+    fn run_for_until_cycles_or_time_done_on_either_bound() {
+        use std::time::Duration;
+        let rf = RunFor::until_cycles_or_time(3, Duration::from_nanos(100));
+        assert!(!rf.done(2, NanoTime::new(99)));
+        assert!(rf.done(3, NanoTime::new(99)));
+        assert!(rf.done(2, NanoTime::new(100)));
+        assert_eq!(format!("{rf:?}"), "Condition(..)");
+    }
+
+    #[test]
+    fn run_for_condition_stops_graph_in_both_modes() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::time::Duration;
+        for run_mode in [RunMode::HistoricalFrom(NanoTime::ZERO), RunMode::RealTime] {
+            // Stop once the graph itself has seen 3 events.
+            let seen = Arc::new(AtomicU64::new(0));
+            let seen_by_graph = seen.clone();
+            let count = ticker(Duration::from_millis(1))
+                .count()
+                .inspect(move |n| seen_by_graph.store(*n, Ordering::Relaxed));
+            let run_for =
+                RunFor::Condition(Arc::new(move |_, _| seen.load(Ordering::Relaxed) >= 3));
+            count.run(run_mode, run_for).unwrap();
+            assert_eq!(count.peek_value(), 3, "{run_mode:?}");
+
+            let count = ticker(Duration::from_millis(1)).count();
+            let run_for = RunFor::until_cycles_or_time(1_000, Duration::from_millis(5));
+            count.run(run_mode, run_for).unwrap();
+            assert!((5..=7).contains(&count.peek_value()), "{run_mode:?}");
+
+            let count = ticker(Duration::from_millis(1)).count();
+            let run_for = RunFor::until_cycles_or_time(4, Duration::from_secs(60));
+            count.run(run_mode, run_for).unwrap();
+            assert_eq!(count.peek_value(), 4, "{run_mode:?}");
+        }
+    }
+
     // ── average_duration ─────────────────────────────────────────────────────
 
     #[test]
@@ -1609,6 +3098,224 @@ mod tests {
         fs::remove_file(path).unwrap();
     }
 
+    #[test]
+    fn layers_of_odds_evens_example() {
+        use std::time::Duration;
+        let source = ticker(Duration::from_millis(10)).count();
+        let is_even = source.map(|i| i % 2 == 0);
+        let odds = source.filter(is_even.not()).map(|i| format!("{i} is odd"));
+        let evens = source.filter(is_even).map(|i| format!("{i} is even"));
+        let mut graph = merge(vec![odds, evens])
+            .print()
+            .into_graph(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(6));
+        graph.print_layers();
+
+        let layer_of = |ix: usize| {
+            (0..=graph.max_layer())
+                .find(|layer| graph.nodes_at_layer(*layer).iter().any(|(i, _)| *i == ix))
+                .unwrap()
+        };
+        let layers_of = |type_name: &str| {
+            graph
+                .find_nodes_by_type(type_name)
+                .into_iter()
+                .map(layer_of)
+                .sorted()
+                .collect_vec()
+        };
+        // count() is constant(1).sample(ticker).reduce(..)
+        assert_eq!(layers_of("TickNode"), vec![0]);
+        assert_eq!(layers_of("ConstantStream"), vec![0]);
+        assert_eq!(layers_of("SampleStream"), vec![1]);
+        assert_eq!(layers_of("FoldStream"), vec![2]);
+        // is_even, is_even.not(), then the odd/even formatters
+        assert_eq!(layers_of("MapStream<u64, bool>"), vec![3]);
+        assert_eq!(layers_of("MapStream<bool, bool>"), vec![4]);
+        assert_eq!(layers_of("FilterStream"), vec![4, 5]);
+        assert_eq!(layers_of("MapStream<u64, String>"), vec![5, 6]);
+        assert_eq!(layers_of("MergeStream"), vec![7]);
+        assert_eq!(layers_of("PrintStream"), vec![8]);
+        assert_eq!(graph.max_layer(), 8);
+        assert_eq!(graph.nodes_at_layer(0).len(), 2);
+        assert!(graph.nodes_at_layer(9).is_empty());
+        assert!(graph.find_nodes_by_type("NoSuchNode").is_empty());
+    }
+
+    #[test]
+    fn debug_and_display_summarise_graph() {
+        use std::time::Duration;
+        let run_mode = RunMode::HistoricalFrom(NanoTime::ZERO);
+        let mut graph = ticker(Duration::from_nanos(100))
+            .count()
+            .into_graph(run_mode, RunFor::Cycles(3));
+        let debug = format!("{graph:?}");
+        assert!(debug.contains("nodes: 4"), "{debug}");
+        assert!(debug.contains("layers: 3"), "{debug}");
+        assert!(debug.contains("state: Ready"), "{debug}");
+        assert!(debug.contains("HistoricalFrom"), "{debug}");
+        assert!(debug.contains("cycles: 0"), "{debug}");
+        let state = format!("{:?}", graph.state);
+        assert!(state.contains("nodes: 4"), "{state}");
+        assert!(state.contains("scheduled_callbacks"), "{state}");
+
+        graph.run().unwrap();
+        let debug = format!("{graph:?}");
+        assert!(debug.contains("state: Stopped"), "{debug}");
+        assert!(debug.contains("cycles: 3"), "{debug}");
+
+        let table = graph.to_string();
+        assert_eq!(table.lines().count(), graph.layers());
+        assert!(table.starts_with("[00] "));
+        assert!(table.contains("TickNode"));
+    }
+
+    #[test]
+    fn nodes_info_of_odds_evens_example() {
+        use std::time::Duration;
+        let source = ticker(Duration::from_millis(10)).count();
+        let is_even = source.map(|i| i % 2 == 0);
+        let odds = source.filter(is_even.not()).map(|i| format!("{i} is odd"));
+        let evens = source.filter(is_even).map(|i| format!("{i} is even"));
+        let graph = merge(vec![odds, evens])
+            .print()
+            .into_graph(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(6));
+
+        assert_eq!(graph.node_count(), 12);
+        assert_eq!(graph.layers(), 9);
+        let info = graph.nodes_info();
+        assert_eq!(info.len(), graph.node_count());
+        for node in &info {
+            assert_eq!(node.type_name, info[node.index].type_name);
+            for up in &node.upstream_indices {
+                assert!(info[*up].layer < node.layer);
+                assert!(info[*up].downstream_indices.contains(&node.index));
+            }
+        }
+        let sources = info.iter().filter(|n| n.upstream_indices.is_empty());
+        assert!(sources.map(|n| n.layer).all(|layer| layer == 0));
+        let merge_ix = graph.find_nodes_by_type("MergeStream")[0];
+        assert_eq!(info[merge_ix].upstream_indices.len(), 2);
+        assert_eq!(info[merge_ix].downstream_indices.len(), 1);
+    }
+
+    #[test]
+    fn memory_report_puts_large_accumulate_first() {
+        use std::time::Duration;
+        let source = ticker(Duration::from_millis(10)).count();
+        let nodes = vec![
+            source.accumulate().as_node(),
+            source.buffer(10).as_node(),
+            source.window(Duration::from_millis(100)).as_node(),
+        ];
+        let mut graph = Graph::new(
+            nodes,
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(10_000),
+        );
+        graph.run().unwrap();
+        let report = graph.memory_report();
+        assert_eq!(report.len(), graph.node_count());
+        let top = &report[0];
+        assert!(top.info.type_name.starts_with("FoldStream"));
+        assert!(top.approx_bytes.unwrap() >= 10_000 * std::mem::size_of::<u64>());
+        let reporting = report.iter().filter(|n| n.approx_bytes.is_some()).count();
+        assert_eq!(reporting, 3);
+        // Unreported nodes, such as the ticker, sort last.
+        assert!(report[reporting..].iter().all(|n| n.approx_bytes.is_none()));
+    }
+
+    fn validation_error(nodes: Vec<Rc<dyn Node>>) -> String {
+        let graph = Graph::new(
+            nodes,
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(1),
+        );
+        format!("{:#}", graph.validate().unwrap_err())
+    }
+
+    #[test]
+    fn validate_accepts_a_fully_wired_demux() {
+        use std::time::Duration;
+        let source = ticker(Duration::from_nanos(10)).count();
+        let (children, overflow) = source.demux(2, |n: &u64| (*n % 2, DemuxEvent::None));
+        let mut nodes: Vec<Rc<dyn Node>> = children.iter().map(|c| c.clone().as_node()).collect();
+        nodes.push(overflow.stream().as_node());
+        let graph = Graph::new(
+            nodes,
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(1),
+        );
+        graph.validate().unwrap();
+    }
+
+    #[test]
+    fn validate_reports_passive_only_nodes_and_the_roots_they_starve() {
+        use std::time::Duration;
+        let a = ticker(Duration::from_nanos(10)).count();
+        let b = ticker(Duration::from_nanos(20)).count();
+        let passive = bimap(Dep::Passive(a), Dep::Passive(b), |a, b| a + b);
+        let root = passive.map(|n| n * 2);
+        let err = validation_error(vec![root.as_node()]);
+        assert!(err.contains("BiMapStream"), "{err}");
+        assert!(err.contains("wired passively only"), "{err}");
+        assert!(err.contains("MapStream<u64, u64> never ticks"), "{err}");
+    }
+
+    #[test]
+    fn validate_reports_unwired_demux_children() {
+        use std::time::Duration;
+        let source = ticker(Duration::from_nanos(10)).count();
+        let (children, _overflow) = source.demux(3, |n: &u64| (*n % 3, DemuxEvent::None));
+        // only the first child is wired: the other two and the overflow are not
+        let err = validation_error(vec![children[0].clone().as_node()]);
+        assert_eq!(err.matches("not wired into the graph").count(), 3, "{err}");
+    }
+
+    #[test]
+    fn topology_hash_identifies_graph_shape() {
+        use std::time::Duration;
+        let build = |extra_map: bool| {
+            let count = ticker(Duration::from_millis(10)).count();
+            let doubled = count.map(|x| x * 2);
+            let out = if extra_map {
+                doubled.map(|x| x + 1)
+            } else {
+                doubled
+            };
+            out.into_graph(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(1))
+        };
+        let graph = build(false);
+        assert_eq!(graph.topology_hash(), build(false).topology_hash());
+        assert_ne!(graph.topology_hash(), build(true).topology_hash());
+        let fingerprint = graph.topology_fingerprint();
+        assert_eq!(fingerprint.len(), 16);
+        assert_eq!(
+            u64::from_str_radix(&fingerprint, 16).unwrap(),
+            graph.topology_hash()
+        );
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn runtime_config_sizes_and_names_workers() {
+        use std::time::Duration;
+        let node = ticker(Duration::from_nanos(1)).produce(|| ());
+        let graph = Graph::new_with_runtime_config(
+            vec![node],
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(1),
+            RuntimeConfig::default()
+                .with_worker_threads(1)
+                .with_thread_name("wingfoil-io"),
+        );
+        let runtime = graph.state.tokio_runtime();
+        assert_eq!(runtime.metrics().num_workers(), 1);
+        let name = runtime
+            .block_on(runtime.spawn(async { std::thread::current().name().map(str::to_string) }))
+            .unwrap();
+        assert_eq!(name.as_deref(), Some("wingfoil-io"));
+    }
+
     #[test]
     fn historical_mode_works() {
         // wire up graph..
@@ -1693,6 +3400,128 @@ mod tests {
         assert_eq!(captured_data, expected);
     }
 
+    #[test]
+    fn stepper_replicates_historical_mode_works() {
+        let inputs: Vec<Rc<RefCell<CallBackStream<i32>>>> = (0..7)
+            .map(|_| Rc::new(RefCell::new(CallBackStream::new())))
+            .collect();
+        let distincts: Vec<Rc<dyn Stream<i32>>> = inputs
+            .iter()
+            .map(|stream| stream.clone().as_stream().distinct())
+            .collect();
+        // Same tree as the tree_reduce in historical_mode_works, spelled out
+        // so the intermediate adds can be identified.
+        let a = add(&distincts[0], &distincts[1]);
+        let b = add(&distincts[2], &distincts[3]);
+        let c = add(&distincts[4], &distincts[5]);
+        let ab = add(&a, &b);
+        let cd = add(&c, &distincts[6]);
+        let sum = add(&ab, &cd);
+        let captured = sum.collect();
+
+        push_all(&inputs, ValueAt::new(1, NanoTime::new(100)));
+        push_all(&inputs, ValueAt::new(1, NanoTime::new(200)));
+        push_first(&inputs, ValueAt::new(2, NanoTime::new(300)));
+        push_first(&inputs, ValueAt::new(2, NanoTime::new(400)));
+
+        let mut graph = Graph::new(
+            vec![captured.clone().as_node()],
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Forever,
+        );
+        let node_count = graph.state.nodes.len();
+        let mut stepper = graph.stepper().unwrap();
+        let index = |node: Rc<dyn Node>| stepper.node_index(node).unwrap();
+        let input_ix: Vec<usize> = inputs.iter().map(|i| index(i.clone().as_node())).collect();
+        let distinct_ix: Vec<usize> = distincts
+            .iter()
+            .map(|d| index(d.clone().as_node()))
+            .collect();
+        let add_ix: Vec<usize> = [&a, &b, &c, &ab, &cd, &sum]
+            .iter()
+            .map(|s| index((*s).clone().as_node()))
+            .collect();
+        // collect() and everything it wires in downstream of `sum`
+        let tail_ix: Vec<usize> = (0..node_count)
+            .filter(|ix| {
+                !input_ix.contains(ix) && !distinct_ix.contains(ix) && !add_ix.contains(ix)
+            })
+            .collect();
+
+        // t=100: every node ticks
+        let step = stepper.step().unwrap().unwrap();
+        assert_eq!(step.time, NanoTime::new(100));
+        assert_eq!(step.ticked_nodes, (0..node_count).collect::<Vec<_>>());
+        assert_eq!(sum.peek_value(), 7);
+
+        // t=200: inputs repeat their value, so distinct stops propagation
+        let step = stepper.step().unwrap().unwrap();
+        assert_eq!(step.time, NanoTime::new(200));
+        assert_eq!(
+            step.ticked_nodes,
+            input_ix.iter().copied().sorted().collect_vec()
+        );
+
+        // t=300: only the path from the first input to the output ticks
+        let step = stepper.step().unwrap().unwrap();
+        assert_eq!(step.time, NanoTime::new(300));
+        let expected = [input_ix[0], distinct_ix[0], add_ix[0], add_ix[3], add_ix[5]]
+            .into_iter()
+            .chain(tail_ix.iter().copied())
+            .sorted()
+            .collect_vec();
+        assert_eq!(step.ticked_nodes, expected);
+        assert_eq!(sum.peek_value(), 8);
+
+        // t=400: first input repeats, nothing downstream ticks
+        let step = stepper.step().unwrap().unwrap();
+        assert_eq!(step.time, NanoTime::new(400));
+        assert_eq!(step.ticked_nodes, vec![input_ix[0]]);
+
+        assert_eq!(stepper.step().unwrap(), None);
+        stepper.finish().unwrap();
+        assert_eq!(
+            captured.peek_value(),
+            vec![
+                ValueAt::new(7, NanoTime::new(100)),
+                ValueAt::new(8, NanoTime::new(300))
+            ]
+        );
+    }
+
+    #[test]
+    fn stepper_inject_and_advance_to() {
+        let src = Rc::new(RefCell::new(CallBackStream::<u64>::new()));
+        let total = src.clone().as_stream().reduce(|a, b| a + b);
+        let mut graph = total.into_graph(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever);
+        let mut stepper = graph.stepper().unwrap();
+        assert_eq!(stepper.step().unwrap(), None);
+
+        stepper.inject(&src, 1, NanoTime::new(10)).unwrap();
+        stepper.inject(&src, 2, NanoTime::new(20)).unwrap();
+        stepper.inject(&src, 4, NanoTime::new(30)).unwrap();
+        let steps = stepper.advance_to(NanoTime::new(20)).unwrap();
+        let times: Vec<NanoTime> = steps.iter().map(|s| s.time).collect();
+        assert_eq!(times, vec![NanoTime::new(10), NanoTime::new(20)]);
+        assert_eq!(total.peek_value(), 3);
+        assert_eq!(stepper.time(), NanoTime::new(20));
+
+        assert!(stepper.inject(&src, 8, NanoTime::new(5)).is_err());
+        let other = Rc::new(RefCell::new(CallBackStream::<u64>::new()));
+        assert!(stepper.inject(&other, 8, NanoTime::new(50)).is_err());
+
+        stepper.advance_to(NanoTime::MAX).unwrap();
+        assert_eq!(total.peek_value(), 7);
+    }
+
+    #[test]
+    fn stepper_requires_historical_mode() {
+        let mut graph = ticker(std::time::Duration::from_millis(1))
+            .count()
+            .into_graph(RunMode::RealTime, RunFor::Cycles(1));
+        assert!(graph.stepper().is_err());
+    }
+
     #[test]
     fn error_context_shows_graph_structure() {
         use std::time::Duration;
@@ -1772,13 +3601,13 @@ Caused by:
             let t = state.time();
             self.times.push(t);
             if self.times.len() == 1 {
-                state.add_callback(self.resched_time);
+                state.schedule_at_self(self.resched_time);
             }
             Ok(true)
         }
 
         fn start(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
-            state.add_callback(NanoTime::new(100));
+            state.schedule_at_self(NanoTime::new(100));
             Ok(())
         }
     }
@@ -1809,7 +3638,14 @@ Caused by:
     }
 
     /// Time must not go backwards when a node re-schedules itself in the past.
+    /// Debug builds flag the back-dated callback instead.
     #[test]
+    #[cfg_attr(
+        debug_assertions,
+        should_panic(
+            expected = "node [0] scheduled a historical callback at 50, before the current graph time 100"
+        )
+    )]
     fn time_advances_with_past_scheduled_time() {
         let node = Rc::new(RefCell::new(TimeCapturingNode {
             times: vec![],
@@ -1833,6 +3669,309 @@ Caused by:
         );
     }
 
+    /// Records the times it cycles, only ever woken by another node.
+    #[derive(Default)]
+    struct CancelNode {
+        times: Vec<NanoTime>,
+    }
+
+    impl MutableNode for CancelNode {
+        fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+            self.times.push(state.time());
+            Ok(true)
+        }
+    }
+
+    /// On each order, schedules `cancel` to fire `timeout` later.
+    struct OrderTimeoutNode {
+        orders: Rc<dyn Node>,
+        cancel: Rc<dyn Node>,
+        timeout: Duration,
+    }
+
+    impl MutableNode for OrderTimeoutNode {
+        fn upstreams(&self) -> UpStreams {
+            UpStreams::new(vec![self.orders.clone()], vec![])
+        }
+
+        fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+            state.schedule_after(self.cancel.clone(), self.timeout)?;
+            Ok(true)
+        }
+    }
+
+    #[test]
+    fn node_schedules_another_node() {
+        let orders = Rc::new(RefCell::new(CallBackStream::<i32>::new()));
+        orders
+            .borrow_mut()
+            .push(ValueAt::new(1, NanoTime::new(100)));
+        orders
+            .borrow_mut()
+            .push(ValueAt::new(2, NanoTime::new(250)));
+        let cancel = Rc::new(RefCell::new(CancelNode::default()));
+        let timeout = OrderTimeoutNode {
+            orders: orders.clone().as_node(),
+            cancel: cancel.clone().as_node(),
+            timeout: Duration::from_nanos(30),
+        }
+        .into_node();
+        Graph::new(
+            vec![timeout, cancel.clone().as_node()],
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Forever,
+        )
+        .run()
+        .unwrap();
+        assert_eq!(
+            cancel.borrow().times,
+            vec![NanoTime::new(130), NanoTime::new(280)]
+        );
+    }
+
+    #[test]
+    fn scheduling_an_unwired_node_fails_the_cycle() {
+        let orders = Rc::new(RefCell::new(CallBackStream::<i32>::new()));
+        orders
+            .borrow_mut()
+            .push(ValueAt::new(1, NanoTime::new(100)));
+        let timeout = OrderTimeoutNode {
+            orders: orders.clone().as_node(),
+            cancel: CancelNode::default().into_node(),
+            timeout: Duration::from_nanos(30),
+        }
+        .into_node();
+        let err = timeout
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .expect_err("expected the unwired cancel node to be rejected");
+        assert!(format!("{err:#}").contains("is not wired into the graph"));
+    }
+
+    /// Ticks on every other cycle of its source and records each
+    /// `on_first_tick` call.
+    struct FirstTickNode {
+        source: Rc<dyn Node>,
+        cycles: u32,
+        first_ticks: Vec<NanoTime>,
+    }
+
+    impl MutableNode for FirstTickNode {
+        fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+            self.cycles += 1;
+            Ok(self.cycles.is_multiple_of(2))
+        }
+
+        fn upstreams(&self) -> UpStreams {
+            UpStreams::new(vec![self.source.clone()], vec![])
+        }
+
+        fn on_first_tick(&mut self, state: &mut GraphState) {
+            self.first_ticks.push(state.time());
+        }
+    }
+
+    #[test]
+    fn on_first_tick_is_called_once_on_the_first_tick() {
+        let node = Rc::new(RefCell::new(FirstTickNode {
+            source: ticker(Duration::from_nanos(100)),
+            cycles: 0,
+            first_ticks: vec![],
+        }));
+        Graph::new(
+            vec![node.clone().as_node()],
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(6),
+        )
+        .run()
+        .unwrap();
+        let node = node.borrow();
+        // Ticked on cycles 2, 4 and 6; the hook fired only for the first.
+        assert_eq!(node.cycles, 6);
+        assert_eq!(node.first_ticks, vec![NanoTime::new(100)]);
+    }
+
+    /// Realtime callbacks in the past are legitimate: they fire as soon as
+    /// possible and are not flagged.
+    #[test]
+    fn past_scheduled_time_is_allowed_in_realtime() {
+        let node = Rc::new(RefCell::new(TimeCapturingNode {
+            times: vec![],
+            resched_time: NanoTime::new(50),
+        }));
+        Graph::new(
+            vec![node.clone().as_node()],
+            RunMode::RealTime,
+            RunFor::Cycles(2),
+        )
+        .run()
+        .unwrap();
+        assert_eq!(node.borrow().times.len(), 2);
+    }
+
+    /// Records the time and last-cycle flag of each cycle, rescheduling
+    /// itself every `period`.
+    struct LastCycleNode {
+        period: Duration,
+        cycles: Vec<(NanoTime, bool)>,
+    }
+
+    impl MutableNode for LastCycleNode {
+        fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+            self.cycles.push((state.time(), state.is_last_cycle()));
+            state.schedule_at_self(state.time() + self.period);
+            Ok(true)
+        }
+
+        fn start(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
+            state.schedule_at_self(state.start_time());
+            Ok(())
+        }
+    }
+
+    /// A realtime callback due at the end time fires on that cycle, at the
+    /// exact time, and that cycle is flagged as the last.
+    #[test]
+    fn mock_clock_realtime_callback_at_end_time_is_last_cycle() {
+        use crate::clock::MockClock;
+        let start = NanoTime::new(1_000_000);
+        let period = Duration::from_secs(60);
+        let node = Rc::new(RefCell::new(LastCycleNode {
+            period,
+            cycles: vec![],
+        }));
+        Graph::builder()
+            .with_clock(MockClock::new(start))
+            .build(
+                vec![node.clone().as_node()],
+                RunMode::RealTime,
+                RunFor::Duration(period * 3),
+            )
+            .run()
+            .unwrap();
+        let expected: Vec<(NanoTime, bool)> =
+            (0..=3).map(|i| (start + period * i, i == 3)).collect();
+        assert_eq!(node.borrow().cycles, expected);
+    }
+
+    #[test]
+    fn mock_clock_drives_realtime_ticker() {
+        use crate::clock::{Clock, MockClock};
+        use std::time::Duration;
+        let start = NanoTime::new(1_000_000);
+        let clock = MockClock::new(start);
+        let times = ticker(Duration::from_secs(60)).ticked_at().collect();
+        let timer = Instant::now();
+        // an hour of realtime ticks, jumped through without sleeping
+        Graph::builder()
+            .with_clock(clock.clone())
+            .build(
+                vec![times.clone().as_node()],
+                RunMode::RealTime,
+                RunFor::Duration(Duration::from_secs(3600)),
+            )
+            .run()
+            .unwrap();
+        assert!(timer.elapsed() < Duration::from_secs(10));
+        let times: Vec<NanoTime> = times.peek_value().iter().map(|v| v.value).collect();
+        let expected: Vec<NanoTime> = (0..=60)
+            .map(|i| start + Duration::from_secs(60 * i))
+            .collect();
+        assert_eq!(times, expected);
+        assert_eq!(clock.now(), start + Duration::from_secs(3600));
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(clock.now(), start + Duration::from_secs(3601));
+    }
+
+    #[test]
+    fn mock_clock_leaves_wall_time_on_the_real_clock() {
+        struct WallTimes(Vec<(NanoTime, NanoTime, NanoTime)>);
+        impl MutableNode for WallTimes {
+            fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+                self.0
+                    .push((state.time(), state.wall_time(), state.wall_time_precise()));
+                Ok(true)
+            }
+            fn start(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
+                state.schedule_at_self(state.start_time());
+                Ok(())
+            }
+        }
+        let start = NanoTime::new(1_000);
+        let node = Rc::new(RefCell::new(WallTimes(Vec::new())));
+        let before = NanoTime::now();
+        Graph::builder()
+            .with_clock(crate::clock::MockClock::new(start))
+            .build(
+                vec![node.clone().as_node()],
+                RunMode::RealTime,
+                RunFor::Cycles(1),
+            )
+            .run()
+            .unwrap();
+        let (time, wall_time, wall_time_precise) = node.borrow().0[0];
+        assert_eq!(time, start);
+        assert!(wall_time >= before, "{wall_time}");
+        assert!(wall_time_precise >= wall_time, "{wall_time_precise}");
+    }
+
+    fn map_chain(depth: usize) -> Rc<dyn Stream<Vec<ValueAt<u64>>>> {
+        let mut stream = ticker(std::time::Duration::from_nanos(100)).count();
+        for i in 0..depth as u64 {
+            stream = stream.map(move |n| n * 2 + i);
+        }
+        stream.collect()
+    }
+
+    fn run_chain(depth: usize, fuse: bool) -> (usize, Vec<ValueAt<u64>>) {
+        let collected = map_chain(depth);
+        let mut graph = Graph::builder().fuse_maps(fuse).build(
+            vec![collected.clone().as_node()],
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(4),
+        );
+        graph.run().unwrap();
+        (graph.state.nodes.len(), collected.peek_value())
+    }
+
+    #[test]
+    fn fuse_maps_collapses_a_chain_to_one_node() {
+        let (unfused_nodes, unfused) = run_chain(20, false);
+        let (fused_nodes, fused) = run_chain(20, true);
+        // the 20 maps cycle as one
+        assert_eq!(fused_nodes, unfused_nodes - 19);
+        assert_eq!(fused, unfused);
+    }
+
+    #[test]
+    fn fuse_maps_keeps_maps_that_are_read_elsewhere() {
+        let count = ticker(std::time::Duration::from_nanos(100)).count();
+        let held = count.map(|n| n + 1);
+        let shared = held.map(|n| n * 10);
+        let inner = shared.map(|n| n + 1);
+        let a = inner.map(|n| n + 2);
+        let b = shared.map(|n| n + 3);
+        let roots = vec![
+            a.clone().as_node(),
+            b.clone().as_node(),
+            held.clone().as_node(),
+        ];
+        // only `a`'s inner map is read once in the graph; handles held
+        // outside it, like `inner`, make no difference
+        let _view = inner.clone();
+        assert_eq!(fuse_maps(&roots), 1);
+        Graph::new(
+            roots,
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(2),
+        )
+        .run()
+        .unwrap();
+        assert_eq!(held.peek_value(), 3);
+        assert_eq!(shared.peek_value(), 30);
+        assert_eq!(a.peek_value(), 33);
+        assert_eq!(b.peek_value(), 33);
+    }
+
     /// `RunFor::Cycles(0)` must exit cleanly without running any cycle and
     /// without panicking. This guards the run-loop termination against the
     /// `end_cycle - 1` underflow (which wrapped to `u32::MAX` for `Cycles(0)`,
@@ -1859,6 +3998,195 @@ Caused by:
         );
     }
 
+    // ── Same-cycle ordering ──────────────────────────────────────────────────
+
+    type CycleLog = Rc<RefCell<Vec<(NanoTime, &'static str)>>>;
+
+    /// Logs each cycle, waking at `times` (popped from the back) and, if
+    /// `always`, every cycle from its first.  If `notifiers` is set it hands
+    /// over a ready notifier at start.
+    struct OrderRecorder {
+        name: &'static str,
+        log: CycleLog,
+        times: Vec<NanoTime>,
+        always: bool,
+        notifiers: Option<Rc<RefCell<Vec<ReadyNotifier>>>>,
+    }
+
+    impl OrderRecorder {
+        fn new(name: &'static str, log: &CycleLog, mut times: Vec<u64>) -> Self {
+            times.reverse();
+            Self {
+                name,
+                log: log.clone(),
+                times: times.into_iter().map(NanoTime::new).collect(),
+                always: false,
+                notifiers: None,
+            }
+        }
+    }
+
+    impl MutableNode for OrderRecorder {
+        fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+            self.log.borrow_mut().push((state.time(), self.name));
+            if self.always {
+                state.always_callback();
+            }
+            if let Some(time) = self.times.pop() {
+                state.schedule_at_self(time);
+            }
+            Ok(true)
+        }
+        fn start(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
+            if let Some(time) = self.times.pop() {
+                state.schedule_at_self(time);
+            }
+            if let Some(notifiers) = &self.notifiers {
+                notifiers.borrow_mut().push(state.ready_notifier());
+            }
+            Ok(())
+        }
+    }
+
+    fn names_at(log: &CycleLog, time: NanoTime) -> Vec<&'static str> {
+        log.borrow()
+            .iter()
+            .filter(|(t, _)| *t == time)
+            .map(|(_, name)| *name)
+            .collect()
+    }
+
+    #[test]
+    fn equal_time_scheduled_callbacks_cycle_in_node_index_order() {
+        // b asks for t=10 at t=0, before a asks at t=5, but a was wired
+        // first so cycles first.
+        let log = CycleLog::default();
+        let a = Rc::new(RefCell::new(OrderRecorder::new("a", &log, vec![5, 10])));
+        let b = Rc::new(RefCell::new(OrderRecorder::new("b", &log, vec![0, 10])));
+        Graph::new(
+            vec![a.as_node(), b.as_node()],
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Forever,
+        )
+        .run()
+        .unwrap();
+        assert_eq!(names_at(&log, NanoTime::new(10)), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn always_callbacks_cycle_in_node_index_order() {
+        // b registers its always-callback before a does.
+        let log = CycleLog::default();
+        let mut a = OrderRecorder::new("a", &log, vec![5]);
+        a.always = true;
+        let mut b = OrderRecorder::new("b", &log, vec![0]);
+        b.always = true;
+        let (a, b) = (Rc::new(RefCell::new(a)), Rc::new(RefCell::new(b)));
+        Graph::new(
+            vec![a.as_node(), b.as_node()],
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(4),
+        )
+        .run()
+        .unwrap();
+        let last = log.borrow().last().unwrap().0;
+        assert_eq!(names_at(&log, last), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn ready_callbacks_cycle_in_node_index_order() {
+        /// Fires the ready notifiers it is given, last-wired first.
+        struct Notify {
+            notifiers: Rc<RefCell<Vec<ReadyNotifier>>>,
+        }
+        impl MutableNode for Notify {
+            fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+                let mut notifiers = self.notifiers.borrow_mut();
+                notifiers.sort_by_key(|notifier| std::cmp::Reverse(notifier.node_index));
+                for notifier in notifiers.iter() {
+                    notifier.notify()?;
+                }
+                Ok(true)
+            }
+            fn start(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
+                state.schedule_at_self(state.start_time());
+                Ok(())
+            }
+        }
+
+        let log = CycleLog::default();
+        let notifiers = Rc::new(RefCell::new(Vec::new()));
+        let recorder = |name| {
+            let mut recorder = OrderRecorder::new(name, &log, vec![]);
+            recorder.notifiers = Some(notifiers.clone());
+            Rc::new(RefCell::new(recorder))
+        };
+        let (a, b) = (recorder("a"), recorder("b"));
+        let notify = Rc::new(RefCell::new(Notify {
+            notifiers: notifiers.clone(),
+        }));
+        Graph::builder()
+            .with_clock(crate::clock::MockClock::new(NanoTime::new(1_000)))
+            .build(
+                vec![a.as_node(), b.as_node(), notify.as_node()],
+                RunMode::RealTime,
+                RunFor::Cycles(2),
+            )
+            .run()
+            .unwrap();
+        let names: Vec<_> = log.borrow().iter().map(|(_, name)| *name).collect();
+        assert_eq!(names, vec!["a", "b"]);
+    }
+
+    /// Fifty sources ticking at random, overlapping times, combined into
+    /// one burst per cycle.  Values are source ids.
+    fn random_sources_combined(seed: u64) -> Rc<dyn Stream<Vec<ValueAt<Burst<u64>>>>> {
+        let mut state = seed;
+        let mut next = move |bound: u64| {
+            // Knuth's MMIX linear congruential generator.
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) % bound
+        };
+        let sources = (0..50)
+            .map(|id| {
+                let source: Rc<RefCell<CallBackStream<u64>>> =
+                    Rc::new(RefCell::new(CallBackStream::new()));
+                for _ in 0..next(10) {
+                    let time = NanoTime::new(next(20));
+                    source.borrow_mut().push(ValueAt::new(id, time));
+                }
+                source.as_stream()
+            })
+            .collect();
+        combine(sources).collect()
+    }
+
+    #[test]
+    fn same_cycle_ticks_from_many_sources_are_deterministic() {
+        let run = |seed| {
+            let combined = random_sources_combined(seed);
+            combined
+                .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+                .unwrap();
+            combined.peek_value()
+        };
+        for seed in [1, 7, 42] {
+            let first = run(seed);
+            assert_eq!(format!("{first:?}"), format!("{:?}", run(seed)));
+            // Within a cycle, sources tick in wiring order.
+            for burst in &first {
+                assert!(
+                    burst.value.windows(2).all(|pair| pair[0] < pair[1]),
+                    "burst at {} out of wiring order: {:?}",
+                    burst.time,
+                    burst.value
+                );
+            }
+        }
+    }
+
     // ── Dynamism tests ────────────────────────────────────────────────────────
 
     #[test]
@@ -2524,5 +4852,135 @@ Caused by:
                 "teardown called once on removal"
             );
         }
+
+        #[test]
+        fn attached_collector_sees_only_later_ticks_until_detached() {
+            use std::time::Duration;
+            let count = ticker(Duration::from_nanos(100)).count();
+            let mut graph = count
+                .clone()
+                .into_graph(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever);
+            let mut stepper = graph.stepper().unwrap();
+            for _ in 0..5 {
+                stepper.step().unwrap();
+            }
+            let collected: Rc<RefCell<Vec<u64>>> = Rc::new(RefCell::new(Vec::new()));
+            let sink = collected.clone();
+            let handle = stepper
+                .attach(|ctx| {
+                    assert!(ctx.is_wired(count.clone().as_node()));
+                    count.for_each(move |n, _| sink.borrow_mut().push(n))
+                })
+                .unwrap();
+            for _ in 0..3 {
+                stepper.step().unwrap();
+            }
+            assert_eq!(*collected.borrow(), vec![6, 7, 8]);
+            stepper.detach(handle).unwrap();
+            for _ in 0..3 {
+                stepper.step().unwrap();
+            }
+            assert_eq!(*collected.borrow(), vec![6, 7, 8]);
+            assert_eq!(count.peek_value(), 11);
+            stepper.finish().unwrap();
+        }
+
+        #[test]
+        fn attaching_a_new_source_is_rejected() {
+            use std::time::Duration;
+            let count = ticker(Duration::from_nanos(100)).count();
+            let mut graph =
+                count.into_graph(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever);
+            let mut stepper = graph.stepper().unwrap();
+            stepper.step().unwrap();
+            let err = stepper
+                .attach(|_| ticker(Duration::from_nanos(10)).count().as_node())
+                .unwrap_err();
+            assert!(err.to_string().contains("downstream of the running graph"));
+            stepper.finish().unwrap();
+        }
+
+        /// Attaches `count.for_each(..)` from inside the running graph once
+        /// `count` reaches `attach_at`, and detaches it at `detach_at`.
+        struct AttachingNode {
+            count: Rc<dyn Stream<u64>>,
+            attach_at: u64,
+            detach_at: u64,
+            seen: Rc<RefCell<Vec<u64>>>,
+            handle: Option<AttachHandle>,
+        }
+
+        impl MutableNode for AttachingNode {
+            fn upstreams(&self) -> UpStreams {
+                UpStreams::new(vec![self.count.clone().as_node()], vec![])
+            }
+
+            fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+                let n = self.count.peek_value();
+                if n == self.attach_at {
+                    let count = self.count.clone();
+                    let seen = self.seen.clone();
+                    self.handle =
+                        Some(state.attach(move |_| {
+                            count.for_each(move |n, _| seen.borrow_mut().push(n))
+                        })?);
+                }
+                if n == self.detach_at {
+                    state.detach(self.handle.take().expect("attached before detaching"));
+                }
+                Ok(false)
+            }
+        }
+
+        #[test]
+        fn realtime_graph_attaches_and_detaches_between_cycles() {
+            use crate::clock::MockClock;
+            use std::time::Duration;
+            let count = ticker(Duration::from_secs(1)).count();
+            let seen = Rc::new(RefCell::new(Vec::new()));
+            let attacher = Rc::new(RefCell::new(AttachingNode {
+                count: count.clone(),
+                attach_at: 5,
+                detach_at: 8,
+                seen: seen.clone(),
+                handle: None,
+            }));
+            Graph::builder()
+                .with_clock(MockClock::new(NanoTime::new(1_000)))
+                .build(
+                    vec![attacher.clone().as_node()],
+                    RunMode::RealTime,
+                    RunFor::Cycles(11),
+                )
+                .run()
+                .unwrap();
+            // wired at the end of tick 5, unwired at the end of tick 8
+            assert_eq!(*seen.borrow(), vec![6, 7, 8]);
+            assert_eq!(count.peek_value(), 11);
+            assert!(attacher.borrow().handle.is_none());
+        }
+
+        #[test]
+        fn detached_nodes_can_be_attached_again() {
+            use std::time::Duration;
+            let count = ticker(Duration::from_nanos(100)).count();
+            let mut graph = count
+                .clone()
+                .into_graph(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever);
+            let mut stepper = graph.stepper().unwrap();
+            let collected: Rc<RefCell<Vec<u64>>> = Rc::new(RefCell::new(Vec::new()));
+            let sink = collected.clone();
+            let consumer = count.for_each(move |n, _| sink.borrow_mut().push(n));
+            let handle = stepper.attach(|_| consumer.clone()).unwrap();
+            stepper.step().unwrap();
+            stepper.detach(handle).unwrap();
+            assert_eq!(stepper.node_index(consumer.clone()), None);
+            stepper.step().unwrap();
+            let handle = stepper.attach(|_| consumer.clone()).unwrap();
+            stepper.step().unwrap();
+            assert_eq!(*collected.borrow(), vec![1, 3]);
+            stepper.detach(handle).unwrap();
+            stepper.finish().unwrap();
+        }
     } // mod dynamism
 }
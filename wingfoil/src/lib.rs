@@ -46,6 +46,15 @@
 //! </div>
 //! The input and output nodes tick 6 times each and the evens and odds nodes tick 3 times.
 //!
+//! ### Ordering within a cycle
+//!
+//! Each node runs at most once per cycle, after all of its upstreams.  When several
+//! sources tick in the same cycle - scheduled for the same time, registered with
+//! [GraphState::always_callback], or woken by a real-time source - their nodes run
+//! layer by layer and, within a layer, in the order they were wired, whatever order
+//! their callbacks arrived in.  A historical run therefore always produces the same
+//! output, including the order of values in a [combine]d [Burst].
+//!
 //! ## Historical vs RealTime
 //! Time is a first-class citizen in wingfoil.  Engine time is measured in nanoseconds from the
 //! [UNIX epoch](https://en.wikipedia.org/wiki/Unix_time) and represented by a [NanoTime].
@@ -122,7 +131,7 @@
 //!  
 //! For best performance we recommend using **cheaply cloneable** types:
 //!
-//! - For small strings: [`arraystring`](https://crates.io/crates/arraystring)
+//! - For small strings: [`SmallStr`], stored inline and `Copy`, or [`arraystring`](https://crates.io/crates/arraystring)
 //! - For small vectors: [`tinyvec`](https://crates.io/crates/tinyvec)
 //! - For larger or heap-allocated types:
 //!   - Use [`Rc<T>`](https://doc.rust-lang.org/std/rc/struct.Rc.html) for single threaded contexts.
@@ -225,20 +234,24 @@ macro_rules! tracing_log_enabled {
 
 pub mod adapters;
 
-#[cfg(feature = "bench")]
 mod bencher;
 mod channel;
+mod clock;
 mod graph;
 mod latency;
 mod nodes;
+mod progress;
 mod queue;
+mod small_str;
 mod time;
 mod types;
 
-#[cfg(feature = "bench")]
 pub use bencher::*;
+pub use clock::*;
 pub use graph::*;
 pub use latency::*;
 pub use nodes::*;
+pub use progress::*;
 pub use queue::*;
+pub use small_str::*;
 pub use types::*;
@@ -0,0 +1,290 @@
+use crate::nodes::{StreamOperators, combine};
+use crate::types::*;
+
+use std::any::Any;
+use std::fmt;
+use std::rc::Rc;
+
+/// How serious an [Alert] is.  Ordered from least to most severe, so
+/// thresholds can be compared with `>=`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl Severity {
+    fn log_level(self) -> log::Level {
+        match self {
+            Severity::Info => log::Level::Info,
+            Severity::Warning => log::Level::Warn,
+            Severity::Error | Severity::Critical => log::Level::Error,
+        }
+    }
+}
+
+/// A structured alert raised by a stream, e.g. by
+/// [alert_when](crate::nodes::StreamOperators::alert_when).  Strings are shared
+/// and the payload is reference counted, so cloning is cheap.
+#[derive(Clone, Default)]
+pub struct Alert {
+    pub severity: Severity,
+    /// Label of the component that raised the alert.
+    pub source: Rc<str>,
+    pub time: NanoTime,
+    pub message: Rc<str>,
+    /// The value that triggered the alert, if any.  Recover it with
+    /// `payload.downcast_ref::<T>()`.
+    pub payload: Option<Rc<dyn Any>>,
+}
+
+impl fmt::Debug for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Alert")
+            .field("severity", &self.severity)
+            .field("source", &self.source)
+            .field("time", &self.time)
+            .field("message", &self.message)
+            .field("payload", &self.payload.is_some())
+            .finish()
+    }
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:?} [{}] {}",
+            self.time.pretty(),
+            self.severity,
+            self.source,
+            self.message
+        )
+    }
+}
+
+/// Merges several alert streams into one, emitting every alert raised on a
+/// cycle as a [Burst], most severe first (the earliest-supplied first among
+/// equals).
+#[must_use]
+pub fn alerts_merge(alerts: Vec<Rc<dyn Stream<Alert>>>) -> Rc<dyn Stream<Burst<Alert>>> {
+    combine(alerts).map(|mut burst| {
+        // stable, so ties keep their supplied order
+        burst.sort_by_key(|alert| std::cmp::Reverse(alert.severity));
+        burst
+    })
+}
+
+/// Operators for streams of [Alert]s.
+pub trait AlertStreamOperators {
+    /// Logs alerts at or above `min_log`, and fails the graph with an error
+    /// describing the alert if it is at or above `min_terminate`.
+    #[must_use]
+    fn route_alerts(self: &Rc<Self>, min_log: Severity, min_terminate: Severity) -> Rc<dyn Node>;
+}
+
+fn route_alert(alert: &Alert, min_log: Severity, min_terminate: Severity) -> anyhow::Result<()> {
+    anyhow::ensure!(alert.severity < min_terminate, "alert: {alert}");
+    if alert.severity >= min_log {
+        log::log!(target: "wingfoil", alert.severity.log_level(), "{alert}");
+    }
+    Ok(())
+}
+
+impl AlertStreamOperators for dyn Stream<Alert> {
+    fn route_alerts(self: &Rc<Self>, min_log: Severity, min_terminate: Severity) -> Rc<dyn Node> {
+        self.try_for_each(move |alert, _| route_alert(&alert, min_log, min_terminate))
+    }
+}
+
+/// Routes each alert of a burst, e.g. from [alerts_merge].
+impl AlertStreamOperators for dyn Stream<Burst<Alert>> {
+    fn route_alerts(self: &Rc<Self>, min_log: Severity, min_terminate: Severity) -> Rc<dyn Node> {
+        self.try_for_each(move |alerts, _| {
+            alerts
+                .iter()
+                .try_for_each(|alert| route_alert(alert, min_log, min_terminate))
+        })
+    }
+}
+
+/// Emits an [Alert] for each source value matching `predicate`.
+/// Used by [alert_when](crate::nodes::StreamOperators::alert_when).
+pub(crate) struct AlertWhenStream<T: Element> {
+    upstream: Rc<dyn Stream<T>>,
+    label: Rc<str>,
+    predicate: Box<dyn Fn(&T) -> bool>,
+    severity: Severity,
+    message: Box<dyn Fn(&T) -> String>,
+    value: Alert,
+}
+
+impl<T: Element> AlertWhenStream<T> {
+    pub fn new(
+        upstream: Rc<dyn Stream<T>>,
+        label: &str,
+        predicate: Box<dyn Fn(&T) -> bool>,
+        severity: Severity,
+        message: Box<dyn Fn(&T) -> String>,
+    ) -> Self {
+        Self {
+            upstream,
+            label: label.into(),
+            predicate,
+            severity,
+            message,
+            value: Alert::default(),
+        }
+    }
+}
+
+#[node(active = [upstream], output = value: Alert)]
+impl<T: Element> MutableNode for AlertWhenStream<T> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        let value = self.upstream.peek_value();
+        if !(self.predicate)(&value) {
+            return Ok(false);
+        }
+        self.value = Alert {
+            severity: self.severity,
+            source: self.label.clone(),
+            time: state.time(),
+            message: (self.message)(&value).into(),
+            payload: Some(Rc::new(value)),
+        };
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::*;
+    use crate::nodes::*;
+    use std::time::Duration;
+
+    fn counter() -> Rc<dyn Stream<u64>> {
+        ticker(Duration::from_nanos(100)).count()
+    }
+
+    #[test]
+    fn severity_is_ordered() {
+        use Severity::*;
+        let mut severities = vec![Critical, Info, Error, Warning];
+        severities.sort();
+        assert_eq!(severities, vec![Info, Warning, Error, Critical]);
+        assert!(Warning >= Warning);
+        assert!(Error < Critical);
+    }
+
+    #[test]
+    fn alert_when_emits_matching_values() {
+        let alerts = counter()
+            .alert_when(
+                "evens",
+                |n| n % 2 == 0,
+                Severity::Warning,
+                |n| format!("{n} is even"),
+            )
+            .collect();
+        alerts
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(5))
+            .unwrap();
+        let alerts: Vec<Alert> = alerts.peek_value().into_iter().map(|a| a.value).collect();
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(&*alerts[0].message, "2 is even");
+        assert_eq!(&*alerts[1].source, "evens");
+        assert_eq!(alerts[1].time, NanoTime::new(300));
+        assert_eq!(alerts[1].severity, Severity::Warning);
+        let payload = alerts[1].payload.as_ref().unwrap();
+        assert_eq!(payload.downcast_ref::<u64>(), Some(&4));
+    }
+
+    #[test]
+    fn alerts_merge_keeps_every_alert_most_severe_first() {
+        let src = counter();
+        let warn = src.alert_when("warn", |_| true, Severity::Warning, |_| "w".into());
+        let crit = src.alert_when("crit", |n| *n == 2, Severity::Critical, |_| "c".into());
+        let info = src.alert_when("info", |n| *n == 2, Severity::Info, |_| "i".into());
+        let warn2 = src.alert_when("warn2", |n| *n == 2, Severity::Warning, |_| "w".into());
+        let merged = alerts_merge(vec![info, warn, crit, warn2]).collect();
+        merged
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+            .unwrap();
+        let sources: Vec<Vec<String>> = merged
+            .peek_value()
+            .iter()
+            .map(|burst| burst.value.iter().map(|a| a.source.to_string()).collect())
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                vec!["warn"],
+                vec!["crit", "warn", "warn2", "info"],
+                vec!["warn"]
+            ]
+        );
+    }
+
+    #[test]
+    fn merged_alerts_are_all_routed() {
+        let src = counter();
+        let info = src.alert_when("info", |_| true, Severity::Info, |_| "i".into());
+        let crit = src.alert_when("crit", |n| *n == 2, Severity::Critical, |_| "c".into());
+        // the critical alert fails the graph though it shares a cycle
+        let err = alerts_merge(vec![info, crit])
+            .route_alerts(Severity::Info, Severity::Critical)
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+            .unwrap_err();
+        assert!(format!("{err:?}").contains("[crit]"), "{err:?}");
+    }
+
+    #[test]
+    fn route_alerts_logs_below_terminate_threshold() {
+        counter()
+            .alert_when("big", |n| *n > 2, Severity::Error, |n| format!("{n}"))
+            .route_alerts(Severity::Warning, Severity::Critical)
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(5))
+            .unwrap();
+    }
+
+    #[test]
+    fn critical_alert_fails_graph() {
+        let err = counter()
+            .alert_when(
+                "risk-check",
+                |n| *n == 3,
+                Severity::Critical,
+                |n| format!("position limit breached at {n}"),
+            )
+            .route_alerts(Severity::Info, Severity::Critical)
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(5))
+            .unwrap_err();
+        let err = format!("{err:?}");
+        assert!(err.contains("position limit breached at 3"), "{err}");
+        assert!(err.contains("[risk-check]"), "{err}");
+    }
+
+    #[test]
+    fn overflow_alert_does_not_panic() {
+        // capacity for one key, so every later key overflows
+        let (children, overflow) = counter().demux(1, |n| (*n, DemuxEvent::None));
+        let alerts = overflow.alert(Severity::Error).collect();
+        let mut roots: Vec<Rc<dyn Node>> = children.into_iter().map(|c| c.as_node()).collect();
+        roots.push(alerts.clone().as_node());
+        Graph::new(
+            roots,
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(3),
+        )
+        .run()
+        .unwrap();
+        let alerts = alerts.peek_value();
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(&*alerts[0].value.source, "demux overflow");
+        assert_eq!(alerts[0].value.severity, Severity::Error);
+    }
+}
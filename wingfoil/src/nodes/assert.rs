@@ -0,0 +1,103 @@
+//! Terminal operators for tests: collect a stream and check it against
+//! expected values when the graph stops.  Available in the crate's own
+//! tests and, for downstream crates, behind the `test-utils` feature.
+
+use std::fmt::{Debug, Write};
+use std::rc::Rc;
+
+use crate::nodes::StreamOperators;
+use crate::queue::ValueAt;
+use crate::types::*;
+
+/// Test assertions on a stream.  A mismatch fails the graph run with an
+/// error listing expected and actual values side by side, e.g.
+/// ```text
+///     0: ValueAt { value: 1, time: NanoTime(0) }   | ValueAt { value: 1, time: NanoTime(0) }
+/// *   1: ValueAt { value: 5, time: NanoTime(100) } | ValueAt { value: 2, time: NanoTime(100) }
+/// *   2: -                                         | ValueAt { value: 3, time: NanoTime(200) }
+/// ```
+pub trait AssertStreamOperators<T: Element + PartialEq> {
+    /// Checks every tick, value and time, against `expected`.
+    #[must_use]
+    fn assert_eq(self: &Rc<Self>, expected: Vec<ValueAt<T>>) -> Rc<dyn Node>;
+    /// Checks the values ticked against `expected`, ignoring times.
+    #[must_use]
+    fn assert_values(self: &Rc<Self>, expected: Vec<T>) -> Rc<dyn Node>;
+}
+
+impl<T: Element + PartialEq> AssertStreamOperators<T> for dyn Stream<T> {
+    fn assert_eq(self: &Rc<Self>, expected: Vec<ValueAt<T>>) -> Rc<dyn Node> {
+        self.collect()
+            .finally(move |actual, _| check(&expected, &actual))
+    }
+
+    fn assert_values(self: &Rc<Self>, expected: Vec<T>) -> Rc<dyn Node> {
+        self.collect().finally(move |actual, _| {
+            let actual: Vec<T> = actual.into_iter().map(|v| v.value).collect();
+            check(&expected, &actual)
+        })
+    }
+}
+
+fn check<T: PartialEq + Debug>(expected: &[T], actual: &[T]) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        expected == actual,
+        "stream did not match, {} expected vs {} actual ticks:\n{}",
+        expected.len(),
+        actual.len(),
+        side_by_side(expected, actual)
+    );
+    Ok(())
+}
+
+/// One row per tick, `expected | actual`, with mismatched rows marked `*`
+/// and a missing tick shown as `-`.
+fn side_by_side<T: PartialEq + Debug>(expected: &[T], actual: &[T]) -> String {
+    let cell = |row: Option<&T>| row.map_or("-".to_string(), |v| format!("{v:?}"));
+    let rows: Vec<(String, String, bool)> = (0..expected.len().max(actual.len()))
+        .map(|i| {
+            let (e, a) = (expected.get(i), actual.get(i));
+            (cell(e), cell(a), e != a)
+        })
+        .collect();
+    let width = rows.iter().map(|(e, _, _)| e.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    for (i, (e, a, differs)) in rows.iter().enumerate() {
+        let mark = if *differs { '*' } else { ' ' };
+        let _ = writeln!(out, "{mark} {i:>3}: {e:<width$} | {a}");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::*;
+    use crate::nodes::*;
+
+    #[test]
+    fn mismatch_reports_side_by_side() {
+        let err = ticker(Duration::from_nanos(100))
+            .count()
+            .assert_eq(vec![
+                ValueAt::new(1, NanoTime::new(0)),
+                ValueAt::new(5, NanoTime::new(100)),
+            ])
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+            .unwrap_err();
+        let err = format!("{err:#}");
+        assert!(err.contains("2 expected vs 3 actual"), "{err}");
+        assert!(err.contains("    0: ValueAt { value: 1"), "{err}");
+        assert!(err.contains("*   1: ValueAt { value: 5"), "{err}");
+        assert!(err.contains("*   2: -"), "{err}");
+    }
+
+    #[test]
+    fn assert_values_ignores_times() {
+        ticker(Duration::from_nanos(100))
+            .count()
+            .assert_values(vec![1, 2, 3])
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+            .unwrap();
+    }
+}
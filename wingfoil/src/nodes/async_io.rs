@@ -9,12 +9,13 @@ use futures::stream::StreamExt;
 use std::future::Future;
 use std::pin::Pin;
 use std::rc::Rc;
+use std::sync::Arc;
 
 /// Context passed to async producer closures during graph setup.
 ///
 /// This provides the run configuration so producers can adapt their behavior
 /// (e.g., derive time ranges for database queries).
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct RunParams {
     pub run_mode: RunMode,
     pub run_for: RunFor,
@@ -22,16 +23,13 @@ pub struct RunParams {
 }
 
 impl RunParams {
-    /// Compute end time based on run_for.
+    /// When the run ends: `start_time + duration` for `RunFor::Duration`.
     ///
-    /// Returns `start_time + duration` for `RunFor::Duration`,
-    /// `NanoTime::MAX` for `Forever`, or an error for `Cycles`.
-    pub fn end_time(&self) -> anyhow::Result<NanoTime> {
-        match self.run_for {
-            RunFor::Duration(d) => Ok(self.start_time + d),
-            RunFor::Forever => Ok(NanoTime::MAX),
-            RunFor::Cycles(_) => anyhow::bail!("end_time not available for RunFor::Cycles"),
-        }
+    /// None for `Forever`, `Cycles` and `Condition`, which fix no end time in
+    /// advance.  That means unbounded: a time-bounded producer should read on
+    /// to `NanoTime::MAX` (or the end of its data) rather than fail.
+    pub fn end_time(&self) -> Option<NanoTime> {
+        self.run_for.end_time(self.start_time)
     }
 }
 
@@ -122,6 +120,7 @@ where
     fn setup(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
         let run_mode = state.run_mode();
         let run_for = state.run_for();
+        let clock = state.clock();
         let rx = self
             .rx
             .take()
@@ -133,15 +132,15 @@ where
             .ok_or_else(|| anyhow::anyhow!("func is already taken"))?;
         let ctx = RunParams {
             run_mode,
-            run_for,
+            run_for: run_for.clone(),
             start_time: state.start_time(),
         };
 
         let f = async move {
             let src = rx
                 .to_boxed_message_stream()
-                .limit(run_mode, run_for)
-                .to_stream();
+                .limit(run_mode, run_for, clock.clone())
+                .to_stream(clock);
             let fut = func(ctx, Box::pin(src));
             fut.await
         };
@@ -214,6 +213,7 @@ where
     fn setup(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
         let run_mode = state.run_mode();
         let run_for = state.run_for();
+        let clock = state.clock();
         let mut sender = self
             .sender
             .take()
@@ -230,7 +230,7 @@ where
             .ok_or_else(|| anyhow::anyhow!("func is already taken"))?;
         let ctx = RunParams {
             run_mode,
-            run_for,
+            run_for: run_for.clone(),
             start_time: state.start_time(),
         };
         let fut = async move {
@@ -243,7 +243,9 @@ where
                         .await;
                 }
                 Ok(stream) => {
-                    let source = stream.to_message_stream(run_mode).limit(run_mode, run_for);
+                    let source = stream
+                        .to_message_stream(run_mode)
+                        .limit(run_mode, run_for, clock);
                     let mut source = Box::pin(source);
                     while let Some(message) = source.next().await {
                         // A send error means the receiver was dropped (a normal
@@ -308,7 +310,7 @@ where
 /// ```ignore
 /// produce_async(|ctx| async move {
 ///     let start = ctx.start_time;
-///     let end = ctx.end_time();
+///     let end = ctx.end_time().unwrap_or(NanoTime::MAX);
 ///     Ok(async_stream::stream! {
 ///         // Use start, end in async code...
 ///     })
@@ -376,9 +378,14 @@ where
 }
 
 trait MessageStream<T: Element + Send> {
-    fn limit(self, run_mode: RunMode, run_for: RunFor) -> impl futures::Stream<Item = Message<T>>;
-
-    fn to_stream(self) -> impl futures::Stream<Item = (NanoTime, T)>;
+    fn limit(
+        self,
+        run_mode: RunMode,
+        run_for: RunFor,
+        clock: Arc<dyn Clock>,
+    ) -> impl futures::Stream<Item = Message<T>>;
+
+    fn to_stream(self, clock: Arc<dyn Clock>) -> impl futures::Stream<Item = (NanoTime, T)>;
 }
 
 impl<T, STRM> MessageStream<T> for STRM
@@ -386,9 +393,17 @@ where
     STRM: futures::Stream<Item = Message<T>>,
     T: Element + Send,
 {
-    fn limit(self, run_mode: RunMode, run_for: RunFor) -> impl futures::Stream<Item = Message<T>> {
+    fn limit(
+        self,
+        run_mode: RunMode,
+        run_for: RunFor,
+        clock: Arc<dyn Clock>,
+    ) -> impl futures::Stream<Item = Message<T>> {
         async_stream::stream! {
-            let time0 = run_mode.start_time();
+            let time0 = match run_mode {
+                RunMode::RealTime => clock.now(),
+                RunMode::HistoricalFrom(start_time) => start_time,
+            };
             let mut time = time0;
             let mut elapsed: NanoTime;
             let mut cycle = 0;
@@ -398,12 +413,12 @@ where
             {
                 match &message {
                     Message::RealtimeValue(_) => {
-                        time = NanoTime::now();
+                        time = clock.now();
                     }
                     Message::HistoricalValue(value_at) => {
                         time = value_at.time;
                     },
-                    Message::CheckPoint(t) => {
+                    Message::CheckPoint(t) | Message::Ack(t, _) => {
                         time = *t;
                     },
                     Message::EndOfStream => {
@@ -423,13 +438,13 @@ where
         }
     }
 
-    fn to_stream(self) -> impl futures::Stream<Item = (NanoTime, T)> {
+    fn to_stream(self, clock: Arc<dyn Clock>) -> impl futures::Stream<Item = (NanoTime, T)> {
         async_stream::stream! {
             let mut source = Box::pin(self);
             while let Some(message) = source.next().await {
                 match message {
                     Message::RealtimeValue(value) => {
-                        yield (NanoTime::now(), value)
+                        yield (clock.now(), value)
                     }
                     Message::HistoricalValue(value_at) => {
                         let time = value_at.time;
@@ -437,7 +452,7 @@ where
                             yield (time, value)
                         }
                     },
-                    Message::CheckPoint(_) => {},
+                    Message::CheckPoint(_) | Message::Ack(..) => {},
                     Message::EndOfStream => {},
                     Message::Error(err) => {
                         // Log the error and stop the stream.
@@ -496,6 +511,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn produce_async_receives_graph_run_params() {
+        let start = NanoTime::new(1_000);
+        let run_for = RunFor::Duration(Duration::from_millis(5));
+        let (tx, rx) = std::sync::mpsc::channel();
+        let producer = move |ctx: RunParams| async move {
+            tx.send(ctx).unwrap();
+            Ok(futures::stream::empty())
+        };
+        let stream: std::rc::Rc<dyn Stream<Burst<u32>>> = produce_async(producer, None);
+        Graph::new(
+            vec![stream.as_node()],
+            RunMode::HistoricalFrom(start),
+            run_for,
+        )
+        .run()
+        .unwrap();
+        let ctx = rx.recv().unwrap();
+        assert_eq!(ctx.run_mode, RunMode::HistoricalFrom(start));
+        assert!(matches!(ctx.run_for, RunFor::Duration(d) if d == Duration::from_millis(5)));
+        assert_eq!(ctx.start_time, start);
+        assert_eq!(ctx.end_time(), Some(start + Duration::from_millis(5)));
+        for run_for in [RunFor::Cycles(3), RunFor::Forever] {
+            let unbounded = RunParams {
+                run_for,
+                ..ctx.clone()
+            };
+            assert_eq!(unbounded.end_time(), None);
+        }
+    }
+
     #[test]
     fn produce_async_mid_stream_error() {
         let _ = env_logger::try_init();
@@ -564,7 +610,7 @@ mod tests {
                     .collapse()
                     .logged("on-graph", log::Level::Info)
                     .consume_async(Box::new(example_consumer))
-                    .run(run_mode, run_for)
+                    .run(run_mode, run_for.clone())
                     .unwrap();
             }
         }
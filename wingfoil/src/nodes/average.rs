@@ -0,0 +1,182 @@
+use std::collections::VecDeque;
+use std::ops::{Add, Sub};
+use std::rc::Rc;
+
+use crate::types::*;
+
+/// What [moving_average](crate::nodes::FloatStreamOperators::moving_average)
+/// emits when its window holds no values.  NaN inputs are never added to the
+/// window, so it is empty whenever every recent input was NaN.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EmptyWindowPolicy {
+    /// Emit `f64::NAN`.
+    #[default]
+    Nan,
+    /// Emit the last average computed over a non-empty window (`NAN` if
+    /// there has not been one yet).
+    HoldPrevious,
+}
+
+/// Values a [MovingAverageStream] can average.
+pub(crate) trait WindowMean:
+    Element + Copy + Add<Output = Self> + Sub<Output = Self>
+{
+    fn zero() -> Self;
+    /// Whether `self` should be left out of the window (NaN for floats).
+    fn is_missing(&self) -> bool;
+    /// Emitted for an empty window under [EmptyWindowPolicy::Nan].
+    fn missing() -> Self;
+    /// `sum` divided by `count`, which is never zero.
+    fn mean(sum: Self, count: usize) -> Self;
+}
+
+impl WindowMean for f64 {
+    fn zero() -> Self {
+        0.0
+    }
+    fn is_missing(&self) -> bool {
+        self.is_nan()
+    }
+    fn missing() -> Self {
+        f64::NAN
+    }
+    fn mean(sum: Self, count: usize) -> Self {
+        sum / count as f64
+    }
+}
+
+/// The extent of a moving average window.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum AverageWindow {
+    /// Values ticked less than this long ago.
+    Time(NanoTime),
+    /// The last `n` values.
+    Count(usize),
+}
+
+/// Emits the mean of the values currently inside a sliding window on every
+/// upstream tick.  Keeps a running sum so each tick is amortised O(1).
+pub(crate) struct MovingAverageStream<T: WindowMean> {
+    upstream: Rc<dyn Stream<T>>,
+    window: AverageWindow,
+    policy: EmptyWindowPolicy,
+    entries: VecDeque<(NanoTime, T)>,
+    sum: T,
+    average: T,
+}
+
+impl<T: WindowMean> MovingAverageStream<T> {
+    pub fn new(
+        upstream: Rc<dyn Stream<T>>,
+        window: AverageWindow,
+        policy: EmptyWindowPolicy,
+    ) -> Self {
+        Self {
+            upstream,
+            window,
+            policy,
+            entries: VecDeque::new(),
+            sum: T::zero(),
+            average: T::missing(),
+        }
+    }
+
+    fn evict_front(&mut self) {
+        if let Some((_, value)) = self.entries.pop_front() {
+            self.sum = self.sum - value;
+        }
+    }
+}
+
+#[node(active = [upstream], output = average: T)]
+impl<T: WindowMean> MutableNode for MovingAverageStream<T> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        let time = state.time();
+        let value = self.upstream.peek_value();
+        if !value.is_missing() {
+            self.entries.push_back((time, value));
+            self.sum = self.sum + value;
+        }
+        match self.window {
+            AverageWindow::Time(window) => {
+                while let Some(&(oldest, _)) = self.entries.front() {
+                    if time - oldest < window {
+                        break;
+                    }
+                    self.evict_front();
+                }
+            }
+            AverageWindow::Count(size) => {
+                while self.entries.len() > size {
+                    self.evict_front();
+                }
+            }
+        }
+        if self.entries.is_empty() {
+            // Start afresh so rounding error cannot accumulate across
+            // empty periods.
+            self.sum = T::zero();
+            if self.policy == EmptyWindowPolicy::Nan {
+                self.average = T::missing();
+            }
+        } else {
+            self.average = T::mean(self.sum, self.entries.len());
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::*;
+    use crate::nodes::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn source(values: &[(u64, f64)]) -> Rc<dyn Stream<f64>> {
+        let src = Rc::new(RefCell::new(CallBackStream::<f64>::new()));
+        for &(time, value) in values {
+            src.borrow_mut()
+                .push(ValueAt::new(value, NanoTime::new(time)));
+        }
+        src.as_stream()
+    }
+
+    fn averages(stream: Rc<dyn Stream<f64>>) -> Vec<f64> {
+        let collected = stream.collect();
+        collected
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        collected.peek_value().iter().map(|v| v.value).collect()
+    }
+
+    #[test]
+    fn time_window_evicts_old_values() {
+        let src = source(&[(0, 1.0), (100, 2.0), (200, 3.0), (300, 4.0), (400, 5.0)]);
+        let values =
+            averages(src.moving_average(Duration::from_nanos(250), EmptyWindowPolicy::Nan));
+        // At t=400 the values from t=0 and t=100 have left the window.
+        assert_eq!(values, vec![1.0, 1.5, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn count_window_keeps_last_n() {
+        let src = source(&[(0, 1.0), (100, 2.0), (200, 3.0), (300, 4.0), (400, 5.0)]);
+        let values = averages(src.moving_average_count(3, EmptyWindowPolicy::Nan));
+        assert_eq!(values, vec![1.0, 1.5, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn empty_window_policy() {
+        let ticks = [(0, 2.0), (100, f64::NAN), (300, f64::NAN), (400, 6.0)];
+        let window = Duration::from_nanos(150);
+
+        let nan = averages(source(&ticks).moving_average(window, EmptyWindowPolicy::Nan));
+        assert_eq!(nan[..2], [2.0, 2.0]);
+        assert!(nan[2].is_nan());
+        assert_eq!(nan[3], 6.0);
+
+        let held = averages(source(&ticks).moving_average(window, EmptyWindowPolicy::HoldPrevious));
+        assert_eq!(held, vec![2.0, 2.0, 2.0, 6.0]);
+    }
+}
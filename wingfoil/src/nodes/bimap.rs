@@ -27,7 +27,9 @@ impl<IN1: 'static, IN2: 'static, OUT: Element> MutableNode for BiMapStream<IN1,
             passive.into_iter().map(|(n, _)| n).collect(),
         )
     }
-    fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        state.ensure_ticked_ever(self.upstream1.stream())?;
+        state.ensure_ticked_ever(self.upstream2.stream())?;
         self.value = (self.func)(
             self.upstream1.stream().peek_value(),
             self.upstream2.stream().peek_value(),
@@ -40,6 +42,8 @@ impl<IN1: 'static, IN2: 'static, OUT: Element> MutableNode for BiMapStream<IN1,
 mod tests {
     use crate::graph::*;
     use crate::nodes::*;
+    use crate::queue::ValueAt;
+    use std::cell::RefCell;
     use std::time::Duration;
 
     #[test]
@@ -75,4 +79,45 @@ mod tests {
             vec![NanoTime::new(0), NanoTime::new(100), NanoTime::new(200)]
         );
     }
+
+    /// A price that first ticks at 150, read passively by a bimap driven by a
+    /// ticker from 0.
+    fn quote(strict: bool) -> (Graph, Rc<dyn Stream<Vec<ValueAt<u64>>>>) {
+        let price = Rc::new(RefCell::new(CallBackStream::new()));
+        price
+            .borrow_mut()
+            .push(ValueAt::new(100, NanoTime::new(150)));
+        let clock = ticker(Duration::from_nanos(100)).count();
+        let quote = bimap(
+            Dep::Active(clock),
+            Dep::Passive(price.as_stream()),
+            |_, price: u64| price + 1,
+        )
+        .collect();
+        let graph = Graph::builder().strict_uninitialized(strict).build(
+            vec![quote.clone().as_node()],
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(4),
+        );
+        (graph, quote)
+    }
+
+    #[test]
+    fn bimap_reads_default_of_unticked_passive_input() {
+        let (mut graph, quote) = quote(false);
+        graph.run().unwrap();
+        let values: Vec<u64> = quote.peek_value().iter().map(|v| v.value).collect();
+        assert_eq!(values, vec![1, 1, 101]);
+    }
+
+    #[test]
+    fn strict_uninitialized_names_reader_and_unticked_input() {
+        let (mut graph, _) = quote(true);
+        let err = format!("{:#}", graph.run().unwrap_err());
+        assert!(err.contains(">>> [05]          BiMapStream"), "{err}");
+        assert!(
+            err.contains("node [5] read [4] CallBackStream<u64> before it ever ticked"),
+            "{err}"
+        );
+    }
 }
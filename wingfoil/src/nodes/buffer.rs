@@ -23,6 +23,10 @@ impl<T: Element> MutableNode for BufferStream<T> {
             Ok(false)
         }
     }
+
+    fn approx_memory(&self) -> Option<usize> {
+        Some(vec_memory(&self.buffer) + vec_memory(&self.value))
+    }
 }
 
 impl<T: Element> BufferStream<T> {
@@ -51,7 +55,7 @@ mod tests {
             for run_for in [RunFor::Cycles(n), RunFor::Duration(period * n)] {
                 let count = ticker(Duration::from_millis(500)).count();
                 let buffer = count.buffer(2);
-                buffer.run(mode, run_for).unwrap();
+                buffer.run(mode, run_for.clone()).unwrap();
                 let buffer = buffer.peek_value();
                 let src = count.peek_value();
                 let buffered = buffer[buffer.len() - 1];
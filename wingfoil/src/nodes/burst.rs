@@ -0,0 +1,256 @@
+use std::rc::Rc;
+
+use crate::types::*;
+
+/// Applies a closure to each non-empty burst of its source, borrowing the
+/// burst rather than cloning it.  Ticks when the closure returns `Some`.
+/// Backs the [BurstStreamOperators].
+pub(crate) struct BurstMapStream<IN: Element, OUT: Element> {
+    upstream: Rc<dyn Stream<Burst<IN>>>,
+    value: OUT,
+    func: Box<dyn Fn(&Burst<IN>) -> Option<OUT>>,
+}
+
+impl<IN: Element, OUT: Element> BurstMapStream<IN, OUT> {
+    fn new(
+        upstream: Rc<dyn Stream<Burst<IN>>>,
+        func: impl Fn(&Burst<IN>) -> Option<OUT> + 'static,
+    ) -> Self {
+        Self {
+            upstream,
+            value: OUT::default(),
+            func: Box::new(func),
+        }
+    }
+}
+
+#[node(active = [upstream], output = value: OUT)]
+impl<IN: Element, OUT: Element> MutableNode for BurstMapStream<IN, OUT> {
+    fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+        let burst = self.upstream.peek_ref_cell();
+        if burst.is_empty() {
+            return Ok(false);
+        }
+        match (self.func)(&burst) {
+            Some(value) => {
+                self.value = value;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+/// Operators on a `Stream<Burst<T>>`, as ticked by async and threaded
+/// sources such as [produce_async](crate::nodes::produce_async), that work
+/// on every element of a burst instead of
+/// [collapsing](crate::nodes::StreamOperators::collapse) it to the last.
+/// None of them tick on an empty burst.
+pub trait BurstStreamOperators<T: Element> {
+    /// Maps each whole burst to one value.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let rows = ticker(Duration::from_millis(10))
+    ///     .count()
+    ///     .map(|n| (0..n).collect::<Burst<u64>>());
+    /// let sizes = rows.map_burst(|rows| rows.len());
+    /// ```
+    #[must_use]
+    fn map_burst<OUT: Element>(
+        self: &Rc<Self>,
+        func: impl Fn(&Burst<T>) -> OUT + 'static,
+    ) -> Rc<dyn Stream<OUT>>;
+
+    /// Maps each element, keeping the burst's size and order.
+    #[must_use]
+    fn map_each<OUT: Element>(
+        self: &Rc<Self>,
+        func: impl Fn(T) -> OUT + 'static,
+    ) -> Rc<dyn Stream<Burst<OUT>>>;
+
+    /// Keeps the elements that satisfy `predicate`, in order.  Does not tick
+    /// when none do.
+    #[must_use]
+    fn filter_each(
+        self: &Rc<Self>,
+        predicate: impl Fn(&T) -> bool + 'static,
+    ) -> Rc<dyn Stream<Burst<T>>>;
+
+    /// Folds the elements of each burst, starting afresh from `init`, and
+    /// ticks the result.
+    #[must_use]
+    fn fold_burst<OUT: Element>(
+        self: &Rc<Self>,
+        init: OUT,
+        func: impl Fn(OUT, T) -> OUT + 'static,
+    ) -> Rc<dyn Stream<OUT>>;
+
+    /// Maps each burst to a new burst with one call to `func`, which sees
+    /// the elements as a slice so it can process them together, e.g. with
+    /// SIMD or BLAS.  The output need not match the input's size; a call
+    /// returning no elements does not tick.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let prices = ticker(Duration::from_millis(10))
+    ///     .count()
+    ///     .map(|n| (0..n).map(|i| i as f64).collect::<Burst<f64>>());
+    /// let scaled = prices.batch_map(|prices| prices.iter().map(|p| p * 0.5).collect());
+    /// ```
+    #[must_use]
+    fn batch_map<OUT: Element>(
+        self: &Rc<Self>,
+        func: impl Fn(&[T]) -> Vec<OUT> + 'static,
+    ) -> Rc<dyn Stream<Burst<OUT>>>;
+}
+
+impl<T: Element> BurstStreamOperators<T> for dyn Stream<Burst<T>> {
+    fn map_burst<OUT: Element>(
+        self: &Rc<Self>,
+        func: impl Fn(&Burst<T>) -> OUT + 'static,
+    ) -> Rc<dyn Stream<OUT>> {
+        BurstMapStream::new(self.clone(), move |burst| Some(func(burst))).into_stream()
+    }
+
+    fn map_each<OUT: Element>(
+        self: &Rc<Self>,
+        func: impl Fn(T) -> OUT + 'static,
+    ) -> Rc<dyn Stream<Burst<OUT>>> {
+        BurstMapStream::new(self.clone(), move |burst: &Burst<T>| {
+            Some(burst.iter().cloned().map(&func).collect())
+        })
+        .into_stream()
+    }
+
+    fn filter_each(
+        self: &Rc<Self>,
+        predicate: impl Fn(&T) -> bool + 'static,
+    ) -> Rc<dyn Stream<Burst<T>>> {
+        BurstMapStream::new(self.clone(), move |burst: &Burst<T>| {
+            let kept: Burst<T> = burst.iter().filter(|x| predicate(x)).cloned().collect();
+            (!kept.is_empty()).then_some(kept)
+        })
+        .into_stream()
+    }
+
+    fn fold_burst<OUT: Element>(
+        self: &Rc<Self>,
+        init: OUT,
+        func: impl Fn(OUT, T) -> OUT + 'static,
+    ) -> Rc<dyn Stream<OUT>> {
+        BurstMapStream::new(self.clone(), move |burst: &Burst<T>| {
+            Some(burst.iter().cloned().fold(init.clone(), &func))
+        })
+        .into_stream()
+    }
+
+    fn batch_map<OUT: Element>(
+        self: &Rc<Self>,
+        func: impl Fn(&[T]) -> Vec<OUT> + 'static,
+    ) -> Rc<dyn Stream<Burst<OUT>>> {
+        BurstMapStream::new(self.clone(), move |burst: &Burst<T>| {
+            let mapped = func(burst);
+            (!mapped.is_empty()).then(|| mapped.into_iter().collect())
+        })
+        .into_stream()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::burst;
+    use crate::graph::*;
+    use crate::nodes::*;
+    use crate::queue::ValueAt;
+    use std::cell::Cell;
+
+    /// Bursts of sizes 3, 0, 1 and 5 ticking at times 0 to 3.
+    fn bursts() -> Rc<dyn Stream<Burst<u32>>> {
+        let mut stream = CallBackStream::new();
+        let sizes = [3, 0, 1, 5];
+        let mut next = 1;
+        for (i, size) in sizes.into_iter().enumerate() {
+            let burst: Burst<u32> = (next..next + size).collect();
+            next += size;
+            stream.push(ValueAt::new(burst, NanoTime::new(i as u64)));
+        }
+        stream.into_stream()
+    }
+
+    fn run<T: Element>(stream: &Rc<dyn Stream<T>>) -> Vec<(u64, T)> {
+        let collected = stream.collect();
+        collected
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        collected
+            .peek_value()
+            .into_iter()
+            .map(|v| (v.time.into(), v.value))
+            .collect()
+    }
+
+    #[test]
+    fn map_burst_sees_whole_burst() {
+        let sums = bursts().map_burst(|burst| burst.iter().sum::<u32>());
+        assert_eq!(run(&sums), vec![(0, 6), (2, 4), (3, 5 + 6 + 7 + 8 + 9)]);
+    }
+
+    #[test]
+    fn map_each_keeps_burst_structure() {
+        let tens = bursts().map_each(|x| x * 10);
+        assert_eq!(
+            run(&tens),
+            vec![
+                (0, burst![10, 20, 30]),
+                (2, burst![40]),
+                (3, burst![50, 60, 70, 80, 90]),
+            ]
+        );
+    }
+
+    #[test]
+    fn filter_each_skips_bursts_left_empty() {
+        let even = bursts().filter_each(|x| x % 2 == 0);
+        assert_eq!(
+            run(&even),
+            vec![(0, burst![2]), (2, burst![4]), (3, burst![6, 8])]
+        );
+        let big = bursts().filter_each(|x| *x > 4);
+        assert_eq!(run(&big), vec![(3, burst![5, 6, 7, 8, 9])]);
+    }
+
+    #[test]
+    fn fold_burst_restarts_each_burst() {
+        let digits = bursts().fold_burst(String::new(), |acc, x| format!("{acc}{x}"));
+        assert_eq!(
+            run(&digits),
+            vec![
+                (0, "123".to_string()),
+                (2, "4".to_string()),
+                (3, "56789".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn batch_map_calls_once_per_burst() {
+        let calls = Rc::new(Cell::new(0));
+        let pairs = {
+            let calls = calls.clone();
+            bursts().batch_map(move |xs| {
+                calls.set(calls.get() + 1);
+                xs.chunks(2).map(|pair| pair.iter().sum::<u32>()).collect()
+            })
+        };
+        assert_eq!(
+            run(&pairs),
+            vec![(0, burst![3, 3]), (2, burst![4]), (3, burst![11, 15, 9]),]
+        );
+        // the empty burst at time 1 is not passed on
+        assert_eq!(calls.get(), 3);
+        let none = bursts().batch_map(|xs| xs.iter().filter(|x| **x > 9).copied().collect());
+        assert_eq!(run(&none), vec![]);
+    }
+}
@@ -18,19 +18,20 @@ pub struct CallBackStream<T: Element + PartialEq> {
 impl<T: Element + PartialEq> MutableNode for CallBackStream<T> {
     fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
         let mut ticked = false;
-        while let Some(value) = self.queue.pop_if_pending(state.time()) {
+        while let Some((value, time)) = self.queue.pop_timed_if_pending(state.time()) {
             self.value = value;
+            state.set_value_time(time);
             ticked = true;
         }
         if let Some(callback_time) = self.queue.next_time() {
-            state.add_callback(callback_time);
+            state.schedule_at_self(callback_time);
         }
         Ok(ticked)
     }
 
     fn start(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
         if let Some(time) = self.queue.next_time() {
-            state.add_callback(time);
+            state.schedule_at_self(time);
         }
         Ok(())
     }
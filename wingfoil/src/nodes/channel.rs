@@ -1,13 +1,37 @@
-use crate::channel::{ChannelReceiver, ChannelSender, Message, NotifierChannelSender};
+use crate::channel::{ChannelReceiver, ChannelSender, Message, NotifierChannelSender, SendResult};
 use crate::*;
 
 use anyhow::anyhow;
 use derive_more::Debug;
 use derive_new::new;
 use std::collections::VecDeque;
+use std::fmt;
 use std::option::Option;
 use std::rc::Rc;
 
+/// Sends a lockstep [Message::Ack] back to the parent graph.
+pub(crate) type AckFn = Box<dyn Fn(NanoTime, Option<NanoTime>) -> SendResult>;
+
+/// A historical message stamped before the receiving graph's current time,
+/// so it can no longer be delivered when it happened.
+#[derive(Debug)]
+pub(crate) struct LateMessage {
+    time: NanoTime,
+    graph_time: NanoTime,
+}
+
+impl fmt::Display for LateMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "received Historical message but with time less than graph time, {} < {}",
+            self.time, self.graph_time
+        )
+    }
+}
+
+impl std::error::Error for LateMessage {}
+
 pub(crate) trait ChannelOperators<T>
 where
     T: Element + Send,
@@ -61,7 +85,7 @@ impl<T: Element + Send> MutableNode for SenderNode<T> {
                 .expect("invariant: channel sender source wired at graph init")
         });
         if state.node_index_ticked(source_index) {
-            self.sender.send(state, self.source.peek_value())?;
+            self.sender.send_timed(state, self.source.peek_value())?;
             Ok(true)
         } else {
             match &self.trigger {
@@ -97,18 +121,51 @@ pub struct ChannelReceiverStream<T: Element + Send> {
     message_time: Option<NanoTime>,
     #[new(default)]
     queue: VecDeque<ValueAt<Burst<T>>>,
+    #[debug(skip)]
+    #[new(default)]
+    ack: Option<AckFn>,
+    /// Time of the last message received and not yet acknowledged.
+    #[new(default)]
+    pending_ack: Option<NanoTime>,
+}
+
+impl<T: Element + Send> ChannelReceiverStream<T> {
+    /// In [RunMode::HistoricalFrom], acknowledge each message received with
+    /// `ack` once the graph has processed its time, just before blocking for
+    /// the next one.  Used by lockstep mappers.
+    pub(crate) fn with_ack(mut self, ack: AckFn) -> Self {
+        self.ack = Some(ack);
+        self
+    }
+
+    fn send_ack(&mut self, state: &GraphState) -> anyhow::Result<()> {
+        if let (Some(ack), Some(time)) = (&self.ack, self.pending_ack.take()) {
+            ack(time, state.next_callback_time())?;
+        }
+        Ok(())
+    }
 }
 
 // `finished` is only read by `ReceiverStream`, which is itself gated behind the
-// zmq/aeron adapters; gate the accessor the same way to avoid a dead-code warning
-// in the default build.
-#[cfg(any(feature = "zmq", feature = "aeron", feature = "aeron-rs"))]
+// zmq/aeron/shmem adapters; gate the accessor the same way to avoid a dead-code
+// warning in the default build.
+#[cfg(any(
+    feature = "zmq",
+    feature = "aeron",
+    feature = "aeron-rs",
+    feature = "shmem"
+))]
 impl<T: Element + Send> ChannelReceiverStream<T> {
     /// Whether the producer has signalled end-of-stream (i.e. a
     /// [`Message::EndOfStream`] has been received and drained).
     pub(crate) fn finished(&self) -> bool {
         self.finished
     }
+
+    /// Whether the channel holds messages not yet read by [`cycle`](MutableNode::cycle).
+    pub(crate) fn has_buffered(&self) -> bool {
+        !self.receiver.is_empty()
+    }
 }
 
 #[node(output = value: Burst<T>)]
@@ -136,10 +193,11 @@ impl<T: Element + Send> MutableNode for ChannelReceiverStream<T> {
                                     values.push(value);
                                 }
                                 Message::HistoricalValue(value_at) => {
+                                    state.set_value_time(value_at.time);
                                     values.extend(value_at.value);
                                 }
                                 Message::EndOfStream => self.finished = true,
-                                Message::CheckPoint(_) => {}
+                                Message::CheckPoint(_) | Message::Ack(..) => {}
                                 Message::Error(err) => {
                                     return Err(anyhow!(err));
                                 }
@@ -189,7 +247,9 @@ impl<T: Element + Send> MutableNode for ChannelReceiverStream<T> {
                             None => break,
                         }
                     } else {
-                        // block for message
+                        // Everything up to now has been processed, so this is
+                        // the point to acknowledge it before blocking.
+                        self.send_ack(state)?;
                         self.receiver.recv()
                     };
                     match message {
@@ -200,18 +260,26 @@ impl<T: Element + Send> MutableNode for ChannelReceiverStream<T> {
                         }
                         Message::HistoricalValue(value_at) => {
                             if value_at.time < state.time() {
-                                return Err(anyhow!(
-                                    "received Historical message but with time less than graph time, {} < {}",
-                                    value_at.time,
-                                    state.time()
-                                ));
+                                return Err(LateMessage {
+                                    time: value_at.time,
+                                    graph_time: state.time(),
+                                }
+                                .into());
                             }
                             self.message_time = Some(value_at.time);
+                            self.pending_ack = Some(value_at.time);
                             self.queue.push_back(value_at);
                         }
                         Message::EndOfStream => self.finished = true,
                         Message::CheckPoint(check_point) => {
                             self.message_time = Some(check_point);
+                            self.pending_ack = Some(check_point);
+                        }
+                        Message::Ack(time, next) => {
+                            self.message_time = Some(time);
+                            if let Some(next) = next {
+                                state.schedule_at_self(next);
+                            }
                         }
                         Message::Error(err) => {
                             return Err(anyhow!(err));
@@ -222,23 +290,33 @@ impl<T: Element + Send> MutableNode for ChannelReceiverStream<T> {
                     if value_at.time <= state.time() {
                         // front() returned Some, so pop_front is guaranteed.
                         let popped = self.queue.pop_front().expect("front() just returned Some");
+                        state.set_value_time(popped.time);
                         values.extend(popped.value);
                     } else {
                         break;
                     }
                 }
                 match self.queue.front() {
-                    Some(head) => state.add_callback(head.time),
+                    Some(head) => state.schedule_at_self(head.time),
                     None => {
                         // No buffered look-ahead. If the stream is still open and
                         // we are self-driven (no trigger), schedule one more wakeup
                         // so the next cycle blocks for the next message; a triggered
                         // or finished receiver is left to wind down. Clearing
-                        // message_time makes that next cycle block.
+                        // message_time makes that next cycle block.  A checkpoint
+                        // ahead of now means nothing arrives before it, so wake
+                        // then instead, letting the graph run up to it first.
                         if !self.finished && self.trigger.is_none() {
-                            state.add_callback(state.time());
+                            match self.message_time {
+                                Some(t) if t > state.time() => state.schedule_at_self(t),
+                                _ => {
+                                    state.schedule_at_self(state.time());
+                                    self.message_time = None;
+                                }
+                            }
+                        } else {
+                            self.message_time = None;
                         }
-                        self.message_time = None;
                     }
                 }
             }
@@ -261,7 +339,7 @@ impl<T: Element + Send> MutableNode for ChannelReceiverStream<T> {
             }
             RunMode::HistoricalFrom(time) => {
                 if self.trigger.is_none() {
-                    state.add_callback(time);
+                    state.schedule_at_self(time);
                 }
             }
         }
@@ -2,6 +2,8 @@ use crate::types::*;
 use derive_new::new;
 
 use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
 use std::rc::Rc;
 
 #[derive(new)]
@@ -44,11 +46,127 @@ pub fn combine<T: Element>(streams: Vec<Rc<dyn Stream<T>>>) -> Rc<dyn Stream<Bur
     CombineStream2::new(nodes, combined).into_stream()
 }
 
+/// Maps each key to the latest value of its stream, emitting the whole map
+/// whenever any stream ticks.  Used by
+/// [combine_keyed](crate::nodes::combine_keyed).
+struct CombineKeyedStream<K: Element + Hash + Eq, T: Element> {
+    keys: Vec<K>,
+    upstreams: Vec<Rc<dyn Stream<T>>>,
+    /// Graph indices of `upstreams`, resolved once on the first cycle.
+    upstream_indices: Vec<usize>,
+    value: Rc<HashMap<K, T>>,
+}
+
+#[node(active = [upstreams], output = value: Rc<HashMap<K, T>>)]
+impl<K: Element + Hash + Eq, T: Element> MutableNode for CombineKeyedStream<K, T> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        if self.upstream_indices.is_empty() && !self.upstreams.is_empty() {
+            self.upstream_indices = self
+                .upstreams
+                .iter()
+                .map(|stream| {
+                    state
+                        .node_index(stream.clone().as_node())
+                        .expect("invariant: combine_keyed upstream wired at graph init")
+                })
+                .collect();
+        }
+        let map = Rc::make_mut(&mut self.value);
+        for ((key, stream), &index) in self
+            .keys
+            .iter()
+            .zip(&self.upstreams)
+            .zip(&self.upstream_indices)
+        {
+            if state.node_index_ticked(index) {
+                map.insert(key.clone(), stream.peek_value());
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[must_use]
+pub fn combine_keyed<K: Element + Hash + Eq, T: Element>(
+    pairs: Vec<(K, Rc<dyn Stream<T>>)>,
+) -> Rc<dyn Stream<Rc<HashMap<K, T>>>> {
+    let (keys, upstreams) = pairs.into_iter().unzip();
+    CombineKeyedStream {
+        keys,
+        upstreams,
+        upstream_indices: Vec::new(),
+        value: Rc::default(),
+    }
+    .into_stream()
+}
+
+/// Queues each source's values and emits the oldest value of every source
+/// once all have one.  Used by [zip_n](crate::nodes::zip_n).
+struct ZipNStream<T: Element> {
+    upstreams: Vec<Rc<dyn Stream<T>>>,
+    /// Graph indices of `upstreams`, resolved once on the first cycle.
+    upstream_indices: Vec<usize>,
+    queues: Vec<VecDeque<T>>,
+    value: Vec<T>,
+}
+
+#[node(active = [upstreams], output = value: Vec<T>)]
+impl<T: Element> MutableNode for ZipNStream<T> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        if self.upstream_indices.is_empty() && !self.upstreams.is_empty() {
+            self.upstream_indices = self
+                .upstreams
+                .iter()
+                .map(|stream| {
+                    state
+                        .node_index(stream.clone().as_node())
+                        .expect("invariant: zip_n upstream wired at graph init")
+                })
+                .collect();
+        }
+        for ((stream, &index), queue) in self
+            .upstreams
+            .iter()
+            .zip(&self.upstream_indices)
+            .zip(&mut self.queues)
+        {
+            if state.node_index_ticked(index) {
+                queue.push_back(stream.peek_value());
+            }
+        }
+        // Each queue grows by at most one per cycle, so at most one full row
+        // is ever ready.
+        if self.queues.iter().any(VecDeque::is_empty) {
+            return Ok(false);
+        }
+        self.value = self
+            .queues
+            .iter_mut()
+            .map(|queue| queue.pop_front().expect("checked non-empty"))
+            .collect();
+        Ok(true)
+    }
+}
+
+#[must_use]
+pub fn zip_n<T: Element>(streams: Vec<Rc<dyn Stream<T>>>) -> Rc<dyn Stream<Vec<T>>> {
+    ZipNStream {
+        queues: vec![VecDeque::new(); streams.len()],
+        upstreams: streams,
+        upstream_indices: Vec::new(),
+        value: Vec::new(),
+    }
+    .into_stream()
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::queue::ValueAt;
     use crate::{
-        NanoTime, NodeOperators, RunFor, RunMode, StreamOperators, burst, combine, ticker,
+        CallBackStream, IntoStream, NanoTime, NodeOperators, RunFor, RunMode, StreamOperators,
+        burst, combine, combine_keyed, ticker, zip_n,
     };
+    use std::collections::HashMap;
     use std::time::Duration;
     #[test]
     fn combine_works() {
@@ -72,4 +190,77 @@ mod tests {
             .run(run_mode, run_for)
             .unwrap();
     }
+
+    #[test]
+    fn combine_keyed_tracks_latest_value_per_key() {
+        let stream = |ticks: &[(u64, i32)]| {
+            let mut stream = CallBackStream::new();
+            for &(time, value) in ticks {
+                stream.push(ValueAt::new(value, NanoTime::new(time)));
+            }
+            stream.into_stream()
+        };
+        let bid = stream(&[(0, 10), (2, 11)]);
+        let ask = stream(&[(1, 12), (2, 13), (3, 14)]);
+        let combined = combine_keyed(vec![("bid", bid), ("ask", ask)]).collect();
+        combined
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        let expected = vec![
+            ValueAt::new(HashMap::from([("bid", 10)]), NanoTime::new(0)),
+            ValueAt::new(HashMap::from([("bid", 10), ("ask", 12)]), NanoTime::new(1)),
+            ValueAt::new(HashMap::from([("bid", 11), ("ask", 13)]), NanoTime::new(2)),
+            ValueAt::new(HashMap::from([("bid", 11), ("ask", 14)]), NanoTime::new(3)),
+        ];
+        let actual = combined
+            .peek_value()
+            .into_iter()
+            .map(|v| ValueAt::new((*v.value).clone(), v.time))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn zip_n_emits_at_the_slowest_rate_in_order() {
+        // Ticks every 1, 2 and 3ns; the nth output holds each stream's nth
+        // value, tagged by stream so misalignment would show.
+        let streams = [(1, 1), (2, 10), (3, 100)]
+            .into_iter()
+            .map(|(period, scale)| {
+                ticker(Duration::from_nanos(period))
+                    .count()
+                    .map(move |n| n * scale)
+            })
+            .collect();
+        let zipped = zip_n(streams).collect();
+        zipped
+            .run(
+                RunMode::HistoricalFrom(NanoTime::ZERO),
+                RunFor::Duration(Duration::from_nanos(9)),
+            )
+            .unwrap();
+        let expected = (1..=4)
+            .map(|n| ValueAt::new(vec![n, n * 10, n * 100], NanoTime::new((n - 1) * 3)))
+            .collect::<Vec<_>>();
+        assert_eq!(zipped.peek_value(), expected);
+    }
+
+    #[test]
+    fn zip_n_waits_for_every_stream() {
+        let stream = |times: &[u64]| {
+            let mut stream = CallBackStream::new();
+            for (value, &time) in times.iter().enumerate() {
+                stream.push(ValueAt::new(value, NanoTime::new(time)));
+            }
+            stream.into_stream()
+        };
+        let zipped = zip_n(vec![stream(&[0, 1, 2]), stream(&[5])]).collect();
+        zipped
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        assert_eq!(
+            zipped.peek_value(),
+            vec![ValueAt::new(vec![0, 0], NanoTime::new(5))]
+        );
+    }
 }
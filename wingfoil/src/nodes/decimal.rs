@@ -0,0 +1,248 @@
+//! Fixed-point [Decimal] streams for price arithmetic, where `f64`
+//! representation error is unacceptable (`0.1 * 3.0 != 0.3`).
+//!
+//! `Decimal` is an ordinary [Element], so generic operators such as
+//! [fold](StreamOperators::fold), [difference](StreamOperators::difference)
+//! and [add](crate::nodes::add) already work on it exactly.  The operators
+//! here cover what would otherwise force a detour through `f64`.  With the
+//! `serde` support enabled here, decimals serialize as strings, so csv and
+//! json output round-trips without loss; convert with
+//! [to_f64](DecimalStreamOperators::to_f64) for float-only sinks such as kdb
+//! float columns.  The [statistics](crate::adapters::statistics) operators,
+//! drawdown included, take a `Decimal` stream directly and emit `f64`.
+
+use std::rc::Rc;
+use std::time::Duration;
+
+use rust_decimal::prelude::ToPrimitive;
+pub use rust_decimal::{Decimal, RoundingStrategy};
+
+use super::StreamOperators;
+use super::average::{AverageWindow, EmptyWindowPolicy, MovingAverageStream, WindowMean};
+use crate::types::*;
+
+impl WindowMean for Decimal {
+    fn zero() -> Self {
+        Decimal::ZERO
+    }
+    fn is_missing(&self) -> bool {
+        false
+    }
+    fn missing() -> Self {
+        Decimal::ZERO
+    }
+    fn mean(sum: Self, count: usize) -> Self {
+        sum / Decimal::from(count)
+    }
+}
+
+/// Operators available only on a `Stream<Decimal>`.
+pub trait DecimalStreamOperators {
+    /// Mean of the values ticked within the last `window` of engine time,
+    /// emitted on every tick.  Unlike the float version this stays a
+    /// `Decimal`; division rounds at `Decimal`'s 28 digits of precision.
+    #[must_use]
+    fn moving_average(self: &Rc<Self>, window: Duration) -> Rc<dyn Stream<Decimal>>;
+
+    /// Mean of the last `size` values, emitted on every tick.
+    #[must_use]
+    fn moving_average_count(self: &Rc<Self>, size: usize) -> Rc<dyn Stream<Decimal>>;
+
+    /// Rounds each value to `dp` decimal places, e.g. to quantize a
+    /// computed price to the instrument's tick size.
+    #[must_use]
+    fn round_dp(self: &Rc<Self>, dp: u32, strategy: RoundingStrategy) -> Rc<dyn Stream<Decimal>>;
+
+    /// The nearest `f64` to each value.
+    #[must_use]
+    fn to_f64(self: &Rc<Self>) -> Rc<dyn Stream<f64>>;
+}
+
+impl DecimalStreamOperators for dyn Stream<Decimal> {
+    fn moving_average(self: &Rc<Self>, window: Duration) -> Rc<dyn Stream<Decimal>> {
+        let window = AverageWindow::Time(NanoTime::from(window));
+        // The current tick is always in the window, so it is never empty.
+        MovingAverageStream::new(self.clone(), window, EmptyWindowPolicy::HoldPrevious)
+            .into_stream()
+    }
+
+    fn moving_average_count(self: &Rc<Self>, size: usize) -> Rc<dyn Stream<Decimal>> {
+        assert!(
+            size > 0,
+            "moving_average_count requires a size of at least 1"
+        );
+        let window = AverageWindow::Count(size);
+        MovingAverageStream::new(self.clone(), window, EmptyWindowPolicy::HoldPrevious)
+            .into_stream()
+    }
+
+    fn round_dp(self: &Rc<Self>, dp: u32, strategy: RoundingStrategy) -> Rc<dyn Stream<Decimal>> {
+        self.map(move |value| value.round_dp_with_strategy(dp, strategy))
+    }
+
+    fn to_f64(self: &Rc<Self>) -> Rc<dyn Stream<f64>> {
+        self.map(|value| value.to_f64().unwrap_or(f64::NAN))
+    }
+}
+
+/// Converts `value` to a [Decimal] rounded to `dp` places, or `None` if it
+/// is NaN or infinite.  Used by
+/// [to_decimal](crate::nodes::FloatStreamOperators::to_decimal).
+pub(crate) fn f64_to_decimal(value: f64, dp: u32, strategy: RoundingStrategy) -> Option<Decimal> {
+    Decimal::from_f64_retain(value).map(|d| d.round_dp_with_strategy(dp, strategy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::*;
+    use crate::nodes::*;
+
+    fn tenth() -> Decimal {
+        Decimal::new(1, 1)
+    }
+
+    #[test]
+    fn summing_a_tenth_is_exact() {
+        let decimal_sum = ticker(Duration::from_nanos(1))
+            .produce(tenth)
+            .fold(|acc: &mut Decimal, x| *acc += x);
+        decimal_sum
+            .run(
+                RunMode::HistoricalFrom(NanoTime::ZERO),
+                RunFor::Cycles(10_000),
+            )
+            .unwrap();
+        assert_eq!(decimal_sum.peek_value(), Decimal::from(1000));
+
+        let float_sum = ticker(Duration::from_nanos(1))
+            .produce(|| 0.1)
+            .fold(|acc: &mut f64, x| *acc += x);
+        float_sum
+            .run(
+                RunMode::HistoricalFrom(NanoTime::ZERO),
+                RunFor::Cycles(10_000),
+            )
+            .unwrap();
+        assert_ne!(float_sum.peek_value(), 1000.0);
+    }
+
+    #[test]
+    fn moving_average_stays_decimal() {
+        // 0.1, 0.2, 0.3, 0.4, ...
+        let averages = ticker(Duration::from_nanos(100))
+            .count()
+            .map(|n| Decimal::new(n as i64, 1))
+            .moving_average_count(2)
+            .collect();
+        averages
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+            .unwrap();
+        let values: Vec<Decimal> = averages.peek_value().iter().map(|v| v.value).collect();
+        assert_eq!(
+            values,
+            vec![Decimal::new(1, 1), Decimal::new(15, 2), Decimal::new(25, 2)]
+        );
+    }
+
+    #[test]
+    fn conversions_round_explicitly() {
+        let prices = ticker(Duration::from_nanos(100))
+            .produce(|| 100.0 / 3.0)
+            .to_decimal(2, RoundingStrategy::MidpointNearestEven);
+        let quantized = prices
+            .map(|p| p * Decimal::from(3))
+            .round_dp(1, RoundingStrategy::ToZero)
+            .collect();
+        let floats = prices.to_f64().collect();
+        Graph::new(
+            vec![quantized.clone().as_node(), floats.clone().as_node()],
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(1),
+        )
+        .run()
+        .unwrap();
+        // 33.33 * 3 = 99.99, truncated to one place.
+        assert_eq!(quantized.peek_value()[0].value, Decimal::new(999, 1));
+        assert_eq!(floats.peek_value()[0].value, 33.33);
+    }
+
+    #[test]
+    fn drawdown_of_decimal_equity_curve() {
+        use crate::adapters::statistics::StatisticsOperators;
+        let equity = [100, 110, 105, 120, 90, 95];
+        let drawdowns = ticker(Duration::from_nanos(100))
+            .count()
+            .map(move |n| Decimal::new(equity[n as usize - 1], 0))
+            .drawdown_stats()
+            .collect();
+        drawdowns
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(6))
+            .unwrap();
+        let last = drawdowns.peek_value().last().unwrap().value;
+        assert_eq!(last.high_water_mark, 120.0);
+        assert_eq!(last.drawdown, -25.0);
+        assert_eq!(last.max_drawdown, -30.0);
+    }
+
+    #[test]
+    fn ohlc_bars_stay_decimal() {
+        // 0.1, 0.3, 0.2, 0.4 | 0.6, 0.5, 0.7 every 30ns, in 100ns bars
+        let prices = [1, 3, 2, 4, 6, 5, 7];
+        let bars = ticker(Duration::from_nanos(30))
+            .count()
+            .map(move |n| Decimal::new(prices[n as usize - 1], 1))
+            .tumbling_fold(
+                Duration::from_nanos(100),
+                None,
+                |bar: &mut Option<[Decimal; 4]>, price| match bar {
+                    None => *bar = Some([price; 4]),
+                    Some([_, high, low, close]) => {
+                        *high = (*high).max(price);
+                        *low = (*low).min(price);
+                        *close = price;
+                    }
+                },
+            )
+            .collect();
+        bars.run(
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Duration(Duration::from_nanos(180)),
+        )
+        .unwrap();
+        let bars: Vec<[Decimal; 4]> = bars.peek_value().iter().map(|v| v.value.unwrap()).collect();
+        let d = |n| Decimal::new(n, 1);
+        assert_eq!(
+            bars,
+            vec![[d(1), d(4), d(1), d(4)], [d(6), d(7), d(5), d(7)]]
+        );
+    }
+
+    #[test]
+    fn non_finite_floats_are_not_converted() {
+        assert_eq!(f64_to_decimal(f64::NAN, 2, RoundingStrategy::ToZero), None);
+    }
+
+    #[test]
+    fn decimals_round_trip_through_json() {
+        let path =
+            std::env::temp_dir().join(format!("wingfoil_decimal_test_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        ticker(Duration::from_nanos(100))
+            .count()
+            .map(|n| Decimal::new(n as i64, 1))
+            .collect_to_file(path)
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+            .unwrap();
+        let replayed = replay::<Decimal>(path).unwrap().collect();
+        replayed
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        let values: Vec<Decimal> = replayed.peek_value().iter().map(|v| v.value).collect();
+        assert_eq!(
+            values,
+            vec![Decimal::new(1, 1), Decimal::new(2, 1), Decimal::new(3, 1)]
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
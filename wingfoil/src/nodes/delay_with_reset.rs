@@ -60,13 +60,16 @@ impl<T: Element + PartialEq> MutableNode for DelayWithResetStream<T> {
                     self.initialized = true;
                 }
                 let next_time = current_time + self.delay;
-                state.add_callback(next_time);
+                state.schedule_at_self(next_time);
                 self.queue.push(value, next_time)
             }
             while let Some(value) = self.queue.pop_if_pending(current_time) {
                 self.value = value;
                 ticked = true;
             }
+            if ticked {
+                state.set_value_time(current_time - self.delay);
+            }
             Ok(ticked)
         }
     }
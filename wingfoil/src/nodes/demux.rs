@@ -1,6 +1,8 @@
+use crate::nodes::combine;
+use crate::types::vec_memory;
 use crate::{
-    AsNode, Burst, Element, GraphState, IntoStream, MutableNode, Node, Stream, StreamOperators,
-    StreamPeekRef, UpStreams,
+    Alert, AsNode, Burst, Element, GraphState, IntoStream, MutableNode, Node, Severity, Stream,
+    StreamOperators, StreamPeekRef, UpStreams,
 };
 use derive_more::Debug;
 use derive_new::new;
@@ -9,6 +11,7 @@ use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt;
 use std::hash::Hash;
+use std::mem;
 use std::rc::Rc;
 
 /// A message used to signal that a demuxed child stream
@@ -19,6 +22,25 @@ pub enum DemuxEvent {
     Close,
 }
 
+/// Why a value was routed to the [Overflow] stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowReason {
+    /// The key was new and every child was already in use.
+    #[default]
+    NoSlotsAvailable,
+    /// The key overflowed earlier and has not been closed since.
+    KeyAlreadyOverflowed,
+}
+
+/// An overflowed value with the key it was demuxed on and the
+/// [OverflowReason].  Output of [StreamOperators::demux_with_diagnostics].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OverflowEvent<T, K> {
+    pub value: T,
+    pub key: K,
+    pub reason: OverflowReason,
+}
+
 enum DemuxEntry {
     Some(usize),
     Overflow,
@@ -54,6 +76,24 @@ where
     fn size(&self) -> usize {
         self.inner.borrow().size
     }
+
+    /// True if `key` overflowed and has not been closed since.
+    fn is_overflowed(&self, key: &K) -> bool {
+        matches!(self.inner.borrow().in_use.get(key), Some(None))
+    }
+
+    /// Shallow bytes of the key-to-slot tables, from their capacities.
+    fn approx_memory(&self) -> usize {
+        let inner = self.inner.borrow();
+        inner.available.capacity() * mem::size_of::<usize>()
+            + inner.in_use.capacity() * mem::size_of::<(K, Option<usize>)>()
+    }
+
+    fn share(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -142,6 +182,61 @@ impl<T: Element> Overflow<T> {
             panic!("overflow!\n{itm:?}");
         })
     }
+    /// Non-panicking alternative to [panic](Self::panic): logs each
+    /// overflowed value at `level` and otherwise drops it.
+    #[must_use]
+    pub fn log_and_drop(&self, level: log::Level) -> Rc<dyn Node> {
+        self.stream().for_each(move |itm, _| {
+            log::log!(level, "demux overflow, dropping {itm:?}");
+        })
+    }
+    /// Non-panicking alternative to [panic](Self::panic): raises an
+    /// [Alert] with source `"demux overflow"` for each overflowed value.
+    #[must_use]
+    pub fn alert(&self, severity: Severity) -> Rc<dyn Stream<Alert>> {
+        self.stream().alert_when(
+            "demux overflow",
+            |_| true,
+            severity,
+            |itm| format!("overflow!\n{itm:?}"),
+        )
+    }
+    /// Non-panicking alternative to [panic](Self::panic): calls `func` for
+    /// each overflowed value, or burst for [StreamOperators::demux_it], and
+    /// counts them.  The returned stream ticks with the running count,
+    /// which can be read after the run, e.g. to size the demux capacity.
+    #[must_use]
+    pub fn on_overflow(&self, func: impl Fn(&T, &GraphState) + 'static) -> Rc<dyn Stream<u64>> {
+        OverflowCallback::new(self.stream(), Box::new(func)).into_stream()
+    }
+}
+
+/// Calls `func` on and counts each tick of the overflow stream.  Used by
+/// [Overflow::on_overflow].
+#[derive(new)]
+struct OverflowCallback<T: Element> {
+    overflow: Rc<dyn Stream<T>>,
+    func: Box<dyn Fn(&T, &GraphState)>,
+    #[new(default)]
+    count: u64,
+}
+
+impl<T: Element> MutableNode for OverflowCallback<T> {
+    fn upstreams(&self) -> UpStreams {
+        UpStreams::new(vec![self.overflow.clone().as_node()], vec![])
+    }
+
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        (self.func)(&self.overflow.peek_ref_cell(), state);
+        self.count += 1;
+        Ok(true)
+    }
+}
+
+impl<T: Element> StreamPeekRef<u64> for OverflowCallback<T> {
+    fn peek_ref(&self) -> &u64 {
+        &self.count
+    }
 }
 
 pub(crate) fn demux<K, T, F>(
@@ -172,6 +267,59 @@ where
     (demuxed, overflow)
 }
 
+/// [demux] with `build_child` wired onto each slot and the children
+/// [combine](crate::nodes::combine)d.
+pub(crate) fn demux_map<K, T, F, OUT>(
+    source: Rc<dyn Stream<T>>,
+    map: DemuxMap<K>,
+    func: F,
+    build_child: impl Fn(Rc<dyn Stream<T>>) -> Rc<dyn Stream<OUT>>,
+) -> (Rc<dyn Stream<Burst<OUT>>>, Overflow<T>)
+where
+    K: Hash + Eq + PartialEq + fmt::Debug + 'static,
+    T: Element,
+    OUT: Element,
+    F: Fn(&T) -> (K, DemuxEvent) + 'static,
+{
+    let (slots, overflow) = demux(source, map, func);
+    let children = slots.into_iter().map(build_child).collect();
+    (combine(children), overflow)
+}
+
+/// Like [demux] but each overflowed value comes with its key and an
+/// [OverflowReason].  The reason is read from `map` just before the parent
+/// routes the value, and the key is stashed for the overflow child, which
+/// cycles in the same engine cycle.
+pub(crate) fn demux_with_diagnostics<K, T, F>(
+    source: Rc<dyn Stream<T>>,
+    map: DemuxMap<K>,
+    func: F,
+) -> (Vec<Rc<dyn Stream<T>>>, Overflow<OverflowEvent<T, K>>)
+where
+    K: Element + Hash + Eq,
+    T: Element,
+    F: Fn(&T) -> (K, DemuxEvent) + 'static,
+{
+    let last = Rc::new(RefCell::new((K::default(), OverflowReason::default())));
+    let shared_map = map.share();
+    let record = last.clone();
+    let (demuxed, overflow) = demux(source, map, move |value: &T| {
+        let (key, event) = func(value);
+        let reason = if shared_map.is_overflowed(&key) {
+            OverflowReason::KeyAlreadyOverflowed
+        } else {
+            OverflowReason::NoSlotsAvailable
+        };
+        *record.borrow_mut() = (key.clone(), reason);
+        (key, event)
+    });
+    let events = overflow.stream().map(move |value| {
+        let (key, reason) = last.borrow().clone();
+        OverflowEvent { value, key, reason }
+    });
+    (demuxed, Overflow(Rc::new(RefCell::new(Some(events)))))
+}
+
 /// Resolve the graph indices of a demux parent's children and overflow child
 /// during `setup`. Drains `children` into `index_map` (preserving order), takes
 /// the overflow child, and returns its resolved graph index. Shared by
@@ -213,6 +361,20 @@ fn setup_demux_children<S: AsNode + ?Sized>(
     Ok(overflow_graph_index)
 }
 
+/// The children and overflow child a demux parent marks dirty, until `setup`
+/// takes them.
+fn demux_triggers<S: AsNode + ?Sized>(
+    children: &RefCell<Vec<Rc<S>>>,
+    overflow_child: &RefCell<Option<Rc<S>>>,
+) -> Vec<Rc<dyn Node>> {
+    children
+        .borrow()
+        .iter()
+        .chain(overflow_child.borrow().iter())
+        .map(|child| child.clone().as_node())
+        .collect()
+}
+
 #[derive(new, Debug)]
 struct DemuxParent<T, F, K>
 where
@@ -284,6 +446,14 @@ where
         )?;
         Ok(())
     }
+
+    fn approx_memory(&self) -> Option<usize> {
+        Some(self.map.approx_memory())
+    }
+
+    fn triggers(&self) -> Vec<Rc<dyn Node>> {
+        demux_triggers(&self.children, &self.overflow_child)
+    }
 }
 
 #[derive(new)]
@@ -351,6 +521,26 @@ where
     (demuxed, overflow)
 }
 
+/// [demux_it] with `build_child` wired onto each slot and the children
+/// [combine](crate::nodes::combine)d.
+pub(crate) fn demux_it_map<K, T, F, I, OUT>(
+    source: Rc<dyn Stream<I>>,
+    map: DemuxMap<K>,
+    func: F,
+    build_child: impl Fn(Rc<dyn Stream<Burst<T>>>) -> Rc<dyn Stream<OUT>>,
+) -> (Rc<dyn Stream<Burst<OUT>>>, Overflow<Burst<T>>)
+where
+    K: Hash + Eq + PartialEq + fmt::Debug + 'static,
+    T: Element,
+    OUT: Element,
+    F: Fn(&T) -> (K, DemuxEvent) + 'static,
+    I: IntoIterator<Item = T> + Element,
+{
+    let (slots, overflow) = demux_it(source, map, func);
+    let children = slots.into_iter().map(build_child).collect();
+    (combine(children), overflow)
+}
+
 #[derive(new, Debug)]
 struct DemuxVecParent<T, F, K, I>
 where
@@ -436,6 +626,14 @@ where
         )?;
         Ok(())
     }
+
+    fn approx_memory(&self) -> Option<usize> {
+        Some(self.map.approx_memory() + vec_memory(&self.value))
+    }
+
+    fn triggers(&self) -> Vec<Rc<dyn Node>> {
+        demux_triggers(&self.children, &self.overflow_child)
+    }
 }
 
 #[derive(new)]
@@ -485,6 +683,7 @@ mod tests {
 
     // export RUST_LOG=INFO; cargo test --lib demux -- --no-capture 2>&1 | grep source | sort | more
 
+    use std::cell::RefCell;
     use std::collections::HashSet;
     use std::fmt::Debug;
     use std::sync::LazyLock;
@@ -639,7 +838,7 @@ mod tests {
             let muxed = combine(streams);
             let (demuxed, overflow) = muxed.demux_it_with_map(map, parse_message);
             let (results, nodes) = build_results(demuxed, overflow, with_overflow);
-            Graph::new(nodes, *run_mode, *RUN_FOR).run().unwrap();
+            Graph::new(nodes, *run_mode, RUN_FOR.clone()).run().unwrap();
             let parse_topic = |msgs: &Burst<Message>| {
                 assert!(msgs.len() == 1);
                 msgs[0].topic
@@ -658,11 +857,137 @@ mod tests {
             let muxed = merge(streams);
             let (demuxed, overflow) = muxed.demux(capacity, parse_message);
             let (results, nodes) = build_results(demuxed, overflow, with_overflow);
-            Graph::new(nodes, *run_mode, *RUN_FOR).run().unwrap();
+            Graph::new(nodes, *run_mode, RUN_FOR.clone()).run().unwrap();
             validate_results(results, |msg| msg.topic);
         }
     }
 
+    #[test]
+    fn demux_with_diagnostics_reports_overflow_reason() {
+        let mut source = CallBackStream::new();
+        for (time, key) in ["a", "b", "b", "c", "a"].into_iter().enumerate() {
+            source.push(ValueAt::new(key.to_string(), NanoTime::new(time as u64)));
+        }
+        let (demuxed, overflow) = source
+            .into_stream()
+            .demux_with_diagnostics(1, |key: &String| (key.clone(), DemuxEvent::None));
+        let events = overflow.stream().collect();
+        let mut roots = vec![events.clone().as_node()];
+        roots.extend(demuxed.iter().map(|strm| strm.clone().as_node()));
+        Graph::new(
+            roots,
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Forever,
+        )
+        .run()
+        .unwrap();
+        let event = |key: &str, reason| OverflowEvent {
+            value: key.to_string(),
+            key: key.to_string(),
+            reason,
+        };
+        let expected = vec![
+            event("b", OverflowReason::NoSlotsAvailable),
+            event("b", OverflowReason::KeyAlreadyOverflowed),
+            event("c", OverflowReason::NoSlotsAvailable),
+        ];
+        let actual = events
+            .peek_value()
+            .into_iter()
+            .map(|v| v.value)
+            .collect::<Vec<_>>();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn demux_map_builds_a_child_per_slot() {
+        // Symbols cycle sym1, sym2, sym0, ...; each slot counts its own symbol.
+        let (counts, overflow) = ticker(Duration::from_nanos(100))
+            .count()
+            .map(|n| format!("sym{}", n % 3))
+            .demux_map(
+                3,
+                |symbol: &String| (symbol.clone(), DemuxEvent::None),
+                |slot| {
+                    let count = slot.clone().as_node().count();
+                    bimap(Dep::Active(slot), Dep::Passive(count), |symbol, n| {
+                        (symbol, n)
+                    })
+                },
+            );
+        let collected = counts.collapse().collect();
+        Graph::new(
+            vec![collected.clone().as_node(), overflow.panic()],
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(6),
+        )
+        .run()
+        .unwrap();
+        let values: Vec<(String, u64)> = collected
+            .peek_value()
+            .into_iter()
+            .map(|v| v.value)
+            .collect();
+        let expected = [
+            ("sym1", 1),
+            ("sym2", 1),
+            ("sym0", 1),
+            ("sym1", 2),
+            ("sym2", 2),
+            ("sym0", 2),
+        ];
+        let expected: Vec<_> = expected.iter().map(|(s, n)| (s.to_string(), *n)).collect();
+        assert_eq!(values, expected);
+    }
+
+    #[test]
+    fn overflow_log_and_drop_does_not_panic() {
+        let (demuxed, overflow) = ticker(Duration::from_nanos(100))
+            .count()
+            .demux(1, |n: &u64| (*n, DemuxEvent::None));
+        let mut roots = vec![overflow.log_and_drop(log::Level::Debug)];
+        roots.extend(demuxed.iter().map(|strm| strm.clone().as_node()));
+        Graph::new(
+            roots,
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(5),
+        )
+        .run()
+        .unwrap();
+    }
+
+    #[test]
+    fn on_overflow_calls_back_and_counts_each_overflowed_value() {
+        // two slots for keys 0 and 1, so 2, 3 and 4 overflow
+        let seen = Rc::new(RefCell::new(vec![]));
+        let recorder = seen.clone();
+        let (demuxed, overflow) = ticker(Duration::from_nanos(100))
+            .count()
+            .map(|n| n - 1)
+            .demux(2, |n: &u64| (*n, DemuxEvent::None));
+        let count = overflow.on_overflow(move |n, state| {
+            recorder.borrow_mut().push((*n, state.time()));
+        });
+        let mut roots = vec![count.clone().as_node()];
+        roots.extend(demuxed.iter().map(|strm| strm.clone().as_node()));
+        Graph::new(
+            roots,
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(5),
+        )
+        .run()
+        .unwrap();
+        assert_eq!(count.peek_value(), 3);
+        assert_eq!(
+            *seen.borrow(),
+            vec![
+                (2, NanoTime::new(200)),
+                (3, NanoTime::new(300)),
+                (4, NanoTime::new(400)),
+            ]
+        );
+    }
+
     #[test]
     pub fn demux_works() {
         let _ = env_logger::try_init();
@@ -32,10 +32,44 @@ impl<T: Element + Sub<Output = T>> MutableNode for DifferenceStream<T> {
     }
 }
 
+/// Emits the rate of change per second of it's source, i.e. the
+/// difference in value divided by the difference in engine time (in
+/// seconds) since the previous tick.  Does not emit on the first tick or
+/// when the time gap is zero.
+/// Used by [derivative](crate::nodes::FloatStreamOperators::derivative).
+#[derive(new)]
+pub(crate) struct DerivativeStream {
+    upstream: Rc<dyn Stream<f64>>,
+    #[new(default)]
+    rate: f64,
+    #[new(default)]
+    prev: Option<(NanoTime, f64)>,
+}
+
+#[node(active = [upstream], output = rate: f64)]
+impl MutableNode for DerivativeStream {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        let value = self.upstream.peek_value();
+        let time = state.time();
+        let ticked = match self.prev {
+            Some((prev_time, prev_value)) if time > prev_time => {
+                let secs = f64::from(time - prev_time) * NanoTime::SECONDS_PER_NANO;
+                self.rate = (value - prev_value) / secs;
+                true
+            }
+            _ => false,
+        };
+        self.prev = Some((time, value));
+        Ok(ticked)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::graph::*;
     use crate::nodes::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
 
     #[test]
     fn first_tick_does_not_emit() {
@@ -78,4 +112,33 @@ mod tests {
         let values: Vec<u64> = diff.peek_value().iter().map(|v| v.value).collect();
         assert_eq!(values, vec![3, 5, 7]);
     }
+
+    #[test]
+    fn derivative_of_irregular_series() {
+        let src = Rc::new(RefCell::new(CallBackStream::<f64>::new()));
+        // (time in ms, value)
+        for (ms, value) in [(0, 10.0), (500, 12.0), (2_000, 9.0), (2_250, 9.5)] {
+            src.borrow_mut()
+                .push(ValueAt::new(value, NanoTime::new(ms * 1_000_000)));
+        }
+        let rate = src.clone().as_stream().derivative().collect();
+        rate.run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        let values: Vec<f64> = rate.peek_value().iter().map(|v| v.value).collect();
+        // +2 over 0.5s, -3 over 1.5s, +0.5 over 0.25s
+        assert_eq!(values, vec![4.0, -2.0, 2.0]);
+    }
+
+    #[test]
+    fn derivative_skips_first_tick() {
+        let rate = ticker(Duration::from_secs(1))
+            .count()
+            .map(|x: u64| x as f64)
+            .derivative()
+            .collect();
+        rate.run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+            .unwrap();
+        let values: Vec<f64> = rate.peek_value().iter().map(|v| v.value).collect();
+        assert_eq!(values, vec![1.0, 1.0]);
+    }
 }
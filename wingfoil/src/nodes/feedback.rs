@@ -17,8 +17,10 @@ pub(crate) struct FeedbackStream<T: Element + PartialEq> {
 impl<T: Element + PartialEq> MutableNode for FeedbackStream<T> {
     fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
         let mut ticked = false;
-        while let Some(value) = self.queue.borrow_mut().pop_if_pending(state.time()) {
+        while let Some((value, due)) = self.queue.borrow_mut().pop_timed_if_pending(state.time()) {
             self.value = value;
+            // Sent 1ns before it was due.
+            state.set_value_time(due - NanoTime::new(1));
             ticked = true;
         }
         Ok(ticked)
@@ -110,10 +112,18 @@ impl<T: Element + PartialEq> MutableNode for FeedbackSendStream<T> {
 /// ```
 #[must_use]
 pub fn feedback<T: Element + PartialEq>() -> (FeedbackSink<T>, Rc<dyn Stream<T>>) {
+    feedback_init(T::default())
+}
+
+/// Like [feedback] but the source stream holds `init` until the first value
+/// arrives.
+pub(crate) fn feedback_init<T: Element + PartialEq>(
+    init: T,
+) -> (FeedbackSink<T>, Rc<dyn Stream<T>>) {
     let queue = Rc::new(RefCell::new(TimeQueue::new()));
     let node_id = Rc::new(Cell::new(None));
     let stream = FeedbackStream {
-        value: T::default(),
+        value: init,
         queue: queue.clone(),
         node_id: node_id.clone(),
     };
@@ -162,6 +172,29 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn feedback_loop_matches_manual_wiring() {
+        let period = Duration::from_nanos(100);
+        for (init, expected) in [
+            (0, vec![1, 12, 123, 1234, 12345, 123456]),
+            (5, vec![51, 512, 5123, 51234, 512345, 5123456]),
+        ] {
+            let source = ticker(period).count();
+            let (value, writer) = source.feedback_loop(init, |src, fb| src + fb * 10);
+            let res = value.accumulate().finally(move |values, _| {
+                assert_eq!(expected, values);
+                Ok(())
+            });
+            Graph::new(
+                vec![writer, res],
+                RunMode::HistoricalFrom(NanoTime::ZERO),
+                RunFor::Duration(period * 5),
+            )
+            .run()
+            .unwrap();
+        }
+    }
+
     #[test]
     fn feedback_active_works() {
         let (tx, rx) = feedback::<u64>();
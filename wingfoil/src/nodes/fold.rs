@@ -2,6 +2,8 @@ use crate::types::*;
 
 use derive_new::new;
 
+use std::collections::VecDeque;
+use std::ops::Add;
 use std::rc::Rc;
 
 #[derive(new)]
@@ -10,6 +12,23 @@ pub(crate) struct FoldStream<IN: Element, OUT: Element> {
     func: Box<dyn Fn(&mut OUT, IN)>,
     #[new(default)]
     value: OUT,
+    #[new(default)]
+    memory: Option<fn(&OUT) -> usize>,
+}
+
+impl<IN: Element, OUT: Element> FoldStream<IN, OUT> {
+    /// Reports [approx_memory](MutableNode::approx_memory) of the
+    /// accumulator with `memory`, for folds that grow it, e.g. into a `Vec`.
+    pub fn with_memory(mut self, memory: fn(&OUT) -> usize) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
+    /// Starts the accumulator at `init` instead of `OUT::default()`.
+    pub fn with_init(mut self, init: OUT) -> Self {
+        self.value = init;
+        self
+    }
 }
 
 #[node(active = [upstream], output = value: OUT)]
@@ -18,6 +37,82 @@ impl<IN: Element, OUT: Element> MutableNode for FoldStream<IN, OUT> {
         (self.func)(&mut self.value, self.upstream.peek_value());
         Ok(true)
     }
+
+    fn approx_memory(&self) -> Option<usize> {
+        self.memory.map(|memory| memory(&self.value))
+    }
+}
+
+/// Keeps the last `max` values of its source in a ring buffer and emits them,
+/// oldest first, on each tick.  Used by
+/// [accumulate_bounded](crate::nodes::StreamOperators::accumulate_bounded).
+pub(crate) struct BoundedAccumulateStream<T: Element> {
+    upstream: Rc<dyn Stream<T>>,
+    max: usize,
+    ring: VecDeque<T>,
+    value: Vec<T>,
+}
+
+impl<T: Element> BoundedAccumulateStream<T> {
+    pub fn new(upstream: Rc<dyn Stream<T>>, max: usize) -> Self {
+        assert!(max > 0, "accumulate_bounded max must be non-zero");
+        Self {
+            upstream,
+            max,
+            ring: VecDeque::with_capacity(max),
+            value: Vec::with_capacity(max),
+        }
+    }
+}
+
+#[node(active = [upstream], output = value: Vec<T>)]
+impl<T: Element> MutableNode for BoundedAccumulateStream<T> {
+    fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+        if self.ring.len() == self.max {
+            self.ring.pop_front();
+        }
+        self.ring.push_back(self.upstream.peek_value());
+        self.value.clear();
+        self.value.extend(self.ring.iter().cloned());
+        Ok(true)
+    }
+
+    fn approx_memory(&self) -> Option<usize> {
+        Some((self.ring.capacity() + self.value.capacity()) * std::mem::size_of::<T>())
+    }
+}
+
+/// Sums its source but only ticks once, with the grand total, on the last
+/// engine cycle, and takes the total as its value when the graph stops
+/// however the run ended.  Used by
+/// [total](crate::nodes::StreamOperators::total).
+#[derive(new)]
+pub(crate) struct TotalStream<T: Element + Add<Output = T>> {
+    upstream: Rc<dyn Stream<T>>,
+    #[new(default)]
+    sum: T,
+    #[new(default)]
+    value: T,
+}
+
+#[node(active = [upstream], output = value: T)]
+impl<T: Element + Add<Output = T>> MutableNode for TotalStream<T> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        self.sum = std::mem::take(&mut self.sum) + self.upstream.peek_value();
+        if state.is_last_cycle() {
+            self.value = self.sum.clone();
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn stop(&mut self, _state: &mut GraphState) -> anyhow::Result<()> {
+        // no cycle is flagged last when a RunFor::Forever replay drains, and
+        // the source need not tick on the one that is
+        self.value = self.sum.clone();
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -26,6 +121,8 @@ mod tests {
     use crate::graph::*;
     use crate::nodes::*;
     use crate::time::NanoTime;
+    use std::cell::RefCell;
+    use std::rc::Rc;
     use std::time::Duration;
 
     #[test]
@@ -94,6 +191,83 @@ mod tests {
         assert_eq!(vec![1, 2, 3, 4], reduced.peek_value());
     }
 
+    #[test]
+    fn total_ticks_on_last_cycle() {
+        let total = ticker(Duration::from_nanos(100)).count().total();
+        let captured = total.collect();
+        captured
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(4))
+            .unwrap();
+        assert_eq!(
+            captured.peek_value(),
+            vec![ValueAt::new(10, NanoTime::new(300))]
+        );
+    }
+
+    #[test]
+    fn total_of_drained_forever_run() {
+        let src: Rc<RefCell<CallBackStream<u64>>> = Rc::new(RefCell::new(CallBackStream::new()));
+        for (value, time) in [(1, 10), (2, 20), (3, 30)] {
+            src.borrow_mut()
+                .push(ValueAt::new(value, NanoTime::new(time)));
+        }
+        let total = src.as_stream().total();
+        total
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        assert_eq!(total.peek_value(), 6);
+    }
+
+    #[test]
+    fn total_when_source_does_not_tick_on_last_cycle() {
+        // the source ticks at 0 and 100; the run's last cycle is at 150
+        let total = ticker(Duration::from_nanos(100)).count().total();
+        let other = ticker(Duration::from_nanos(50)).count();
+        Graph::new(
+            vec![total.clone().as_node(), other.as_node()],
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(4),
+        )
+        .run()
+        .unwrap();
+        assert_eq!(total.peek_value(), 1 + 2);
+    }
+
+    #[test]
+    fn fold_init_product_starts_at_one() {
+        let product = ticker(Duration::from_nanos(100))
+            .count()
+            .fold_init(1, |acc: &mut u64, n| *acc *= n);
+        let captured = product.clone().collect();
+        assert_eq!(product.peek_value(), 1);
+        captured
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(5))
+            .unwrap();
+        let values: Vec<u64> = captured.peek_value().iter().map(|v| v.value).collect();
+        assert_eq!(values, vec![1, 2, 6, 24, 120]);
+    }
+
+    #[test]
+    fn accumulate_bounded_keeps_most_recent() {
+        let max = 5;
+        let window = ticker(Duration::from_nanos(100))
+            .count()
+            .accumulate_bounded(max);
+        let captured = window.clone().collect();
+        captured
+            .run(
+                RunMode::HistoricalFrom(NanoTime::ZERO),
+                RunFor::Cycles(1_000),
+            )
+            .unwrap();
+        let captured = captured.peek_value();
+        assert_eq!(captured.len(), 1_000);
+        assert!(captured.iter().all(|v| v.value.len() <= max));
+        assert_eq!(captured[2].value, vec![1, 2, 3]);
+        assert_eq!(captured[5].value, vec![2, 3, 4, 5, 6]);
+        assert_eq!(window.peek_value(), vec![996, 997, 998, 999, 1000]);
+    }
+
     #[test]
     fn count() {
         let count = ticker(Duration::from_nanos(100)).count();
@@ -119,4 +293,18 @@ mod tests {
         assert_eq!(expected, captured.peek_value());
         assert_eq!(3, count.peek_value());
     }
+
+    #[test]
+    fn total_emits_only_final_sum() {
+        let total = ticker(Duration::from_nanos(100)).count().total();
+        let captured = total.collect();
+        captured
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(4))
+            .unwrap();
+        let expected = vec![ValueAt {
+            value: 10,
+            time: NanoTime::new(300),
+        }];
+        assert_eq!(expected, captured.peek_value());
+    }
 }
@@ -1,8 +1,10 @@
-use crate::nodes::channel::ChannelReceiverStream;
+use crate::nodes::channel::{ChannelReceiverStream, LateMessage};
+use crate::nodes::map::panic_message;
 use crate::*;
-use channel::{ChannelSender, channel_pair};
+use channel::{ChannelSender, Message, channel_pair};
 use nodes::channel::ChannelOperators;
 
+use anyhow::Context;
 use std::cell::OnceCell;
 use std::cmp::Eq;
 use std::hash::Hash;
@@ -11,6 +13,15 @@ use std::rc::Rc;
 use std::time::Duration;
 use std::{thread, vec};
 
+/// Joins a sub-graph worker thread, surfacing its run error or panic so the
+/// parent graph fails instead of silently dropping it.
+fn join_worker(handle: thread::JoinHandle<anyhow::Result<()>>, name: &str) -> anyhow::Result<()> {
+    match handle.join() {
+        Ok(result) => result.with_context(|| format!("{name} terminated")),
+        Err(panic) => anyhow::bail!("{name} panicked: {}", panic_message(&*panic)),
+    }
+}
+
 #[derive(Debug, Default)]
 enum GraphProducerStreamState<T, FUNC>
 where
@@ -18,7 +29,7 @@ where
     FUNC: FnOnce() -> Rc<dyn Stream<T>> + Send + 'static,
 {
     Func(FUNC),
-    Handle(thread::JoinHandle<()>),
+    Handle(thread::JoinHandle<anyhow::Result<()>>),
     #[default]
     Empty,
 }
@@ -81,13 +92,15 @@ where
                 let tokio_runtime = graph_state.tokio_runtime();
                 let start_time = graph_state.start_time();
                 let run_mode = graph_state.run_mode();
+                let context = graph_state.shared_context();
+                let clock = graph_state.clock();
                 let task = move || {
                     let node = func().send(sender, None);
                     let mut graph =
                         Graph::new_with(vec![node], tokio_runtime, run_mode, run_for, start_time);
-                    if let Err(e) = graph.run() {
-                        log::error!("graph producer worker thread terminated: {e:#}");
-                    }
+                    graph.inherit_context(context);
+                    graph.set_clock(clock);
+                    graph.run()
                 };
 
                 let handle = thread::spawn(task);
@@ -106,14 +119,10 @@ where
         let state = mem::take(&mut self.state);
         match state {
             GraphProducerStreamState::Handle(handle) => {
-                if let Err(panic) = handle.join() {
-                    log::error!("graph producer worker thread panicked: {panic:?}");
-                }
-                self.state = GraphProducerStreamState::Empty;
+                join_worker(handle, "graph producer worker thread")
             }
             _ => anyhow::bail!("unexpected state"),
         }
-        Ok(())
     }
 }
 
@@ -132,6 +141,17 @@ where
 
 /// Creates a [Stream] emitting values on this thread
 /// but produced on a worker thread.
+///
+/// Nodes are `Rc<RefCell<..>>` and must be wired on the thread that runs
+/// them, so `func` builds the worker's graph from scratch on the worker.
+/// Capturing a stream of the calling thread is rejected at compile time;
+/// see `examples/threading` for passing values between graphs instead.
+/// ```compile_fail
+/// # use wingfoil::*;
+/// # use std::time::Duration;
+/// let parent = ticker(Duration::from_millis(10)).count();
+/// producer(move || parent.map(|n| n * 2));
+/// ```
 #[must_use]
 pub fn producer<T: Element + Send + Hash + Eq>(
     func: impl FnOnce() -> Rc<dyn Stream<T>> + Send + 'static,
@@ -147,7 +167,7 @@ where
     FUNC: FnOnce(Rc<dyn Stream<Burst<IN>>>) -> Rc<dyn Stream<OUT>> + Send + 'static,
 {
     Func(FUNC, ChannelSender<OUT>),
-    Handle(thread::JoinHandle<()>),
+    Handle(thread::JoinHandle<anyhow::Result<()>>),
     #[default]
     Empty,
     _Phantom(IN, OUT),
@@ -163,6 +183,7 @@ where
     sender: OnceCell<ChannelSender<IN>>,
     receiver_stream: ChannelReceiverStream<OUT>,
     state: GraphMapStreamState<FUNC, IN, OUT>,
+    lockstep: bool,
 }
 
 impl<IN, OUT, FUNC> GraphMapStream<FUNC, IN, OUT>
@@ -171,7 +192,7 @@ where
     OUT: Element + Send,
     FUNC: FnOnce(Rc<dyn Stream<Burst<IN>>>) -> Rc<dyn Stream<OUT>> + Send + 'static,
 {
-    pub fn new(source: Rc<dyn Stream<IN>>, func: FUNC) -> Self {
+    pub fn new(source: Rc<dyn Stream<IN>>, func: FUNC, lockstep: bool) -> Self {
         let trigger = Some(source.clone().as_node());
         let (sender_out, receiver_out) = channel_pair(None, None);
         //let receiver_out = ChannelReceiver::new(rx_out);
@@ -183,6 +204,7 @@ where
             sender,
             receiver_stream,
             state,
+            lockstep,
         }
     }
 }
@@ -198,13 +220,26 @@ where
     }
 
     fn cycle(&mut self, graph_state: &mut GraphState) -> anyhow::Result<bool> {
+        let sender = self
+            .sender
+            .get_mut()
+            .ok_or_else(|| anyhow::anyhow!("GraphMapStream cycled before setup"))?;
         if graph_state.ticked(self.source.clone()) {
-            self.sender
-                .get_mut()
-                .ok_or_else(|| anyhow::anyhow!("GraphMapStream cycled before setup"))?
-                .send(graph_state, self.source.peek_value())?;
+            sender.send_timed(graph_state, self.source.peek_value())?;
+        } else if self.lockstep && matches!(graph_state.run_mode(), RunMode::HistoricalFrom(_)) {
+            // Woken at the worker's next callback: let it run up to now.
+            sender.send_checkpoint(graph_state)?;
         }
-        self.receiver_stream.cycle(graph_state)
+        self.receiver_stream.cycle(graph_state).map_err(|err| {
+            if err.is::<LateMessage>() {
+                err.context(
+                    "historical mapper with a delay that is not a multiple of the trigger \
+                     period is unsupported, use mapper_lockstep",
+                )
+            } else {
+                err
+            }
+        })
     }
 
     fn setup(&mut self, graph_state: &mut GraphState) -> anyhow::Result<()> {
@@ -224,19 +259,31 @@ where
                     RunMode::RealTime => Some(tx_notif),
                     RunMode::HistoricalFrom(_) => None,
                 };
-                let run_mode = graph_state.run_mode();
                 let run_for = graph_state.run_for();
                 let tokio_runtime = graph_state.tokio_runtime();
                 let start_time = graph_state.start_time();
+                let context = graph_state.shared_context();
+                let clock = graph_state.clock();
                 let (mut sender_in, receiver_in) = channel_pair(None, None);
+                let lockstep = self.lockstep && matches!(run_mode, RunMode::HistoricalFrom(_));
                 let task = move || {
-                    let src = ChannelReceiverStream::new(receiver_in, None, tx_notif).into_stream();
-                    let node = func(src.clone()).send(sender_out, Some(src.as_node()));
+                    let receiver = ChannelReceiverStream::new(receiver_in, None, tx_notif);
+                    let node = if lockstep {
+                        // Acks stand in for the checkpoints a trigger would send.
+                        let acker = sender_out.clone();
+                        let receiver = receiver.with_ack(Box::new(move |time, next| {
+                            acker.send_message(Message::Ack(time, next))
+                        }));
+                        func(receiver.into_stream()).send(sender_out, None)
+                    } else {
+                        let src = receiver.into_stream();
+                        func(src.clone()).send(sender_out, Some(src.as_node()))
+                    };
                     let mut graph =
                         Graph::new_with(vec![node], tokio_runtime, run_mode, run_for, start_time);
-                    if let Err(e) = graph.run() {
-                        log::error!("graph map worker thread terminated: {e:#}");
-                    }
+                    graph.inherit_context(context);
+                    graph.set_clock(clock);
+                    graph.run()
                 };
                 let handle = thread::spawn(task);
                 self.state = GraphMapStreamState::Handle(handle);
@@ -271,15 +318,9 @@ where
         self.receiver_stream.teardown(graph_state)?;
         let state = mem::take(&mut self.state);
         match state {
-            GraphMapStreamState::Handle(handle) => {
-                if let Err(panic) = handle.join() {
-                    log::error!("graph map worker thread panicked: {panic:?}");
-                }
-                self.state = GraphMapStreamState::Empty;
-            }
+            GraphMapStreamState::Handle(handle) => join_worker(handle, "graph map worker thread"),
             _ => anyhow::bail!("Invalid state"),
         }
-        Ok(())
     }
 }
 
@@ -297,8 +338,8 @@ where
 #[cfg(test)]
 mod tests {
 
+    use crate::nodes::graph_state::GraphStateStream;
     use crate::*;
-    use std::panic::catch_unwind;
     use std::rc::Rc;
     use std::{thread, time::Duration};
 
@@ -313,7 +354,7 @@ mod tests {
 
         let delays = [
             Duration::ZERO,
-            //half_period,
+            half_period,
             //period,
         ];
         let run_modes = [RunMode::HistoricalFrom(NanoTime::ZERO), RunMode::RealTime];
@@ -345,9 +386,15 @@ mod tests {
                     .map(move |xs| xs.iter().flatten().map(|x| x * 10).collect::<Vec<u64>>())
                     .logged(label().as_str(), log::Level::Info)
             };
+            let run_for = run_for.clone();
             let f = move || {
-                producer(seq)
-                    .mapper(scale)
+                // Off-period delays need lockstep to replay historically.
+                let mapped = if delay == half_period {
+                    producer(seq).mapper_lockstep(scale)
+                } else {
+                    producer(seq).mapper(scale)
+                };
+                mapped
                     .map(move |xs| {
                         if sleep_map {
                             std::thread::sleep(period * 2);
@@ -375,11 +422,183 @@ mod tests {
             println!(
                 "{run_mode:?}, delay={delay:?}, sleep_produce={sleep_produce:}, sleep_map={sleep_map:}"
             );
-            if delay == half_period && matches!(run_mode, RunMode::HistoricalFrom(_)) {
-                assert!(catch_unwind(f).is_err());
-            } else {
-                f();
+            f();
+        }
+    }
+
+    fn delayed_by_half_period(lockstep: bool) -> anyhow::Result<Vec<ValueAt<Vec<u64>>>> {
+        let period = Duration::from_millis(10);
+        let delay = move |src: Rc<dyn Stream<Burst<u64>>>| src.delay(period / 2);
+        let src = ticker(period).count().limit(4);
+        let mapped = if lockstep {
+            src.mapper_lockstep(delay)
+        } else {
+            src.mapper(delay)
+        };
+        let collected = mapped
+            .map(|xs| xs.into_iter().flatten().collect::<Vec<u64>>())
+            .collect();
+        collected.run(
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Duration(period * 5),
+        )?;
+        Ok(collected.peek_value())
+    }
+
+    #[test]
+    fn historical_mapper_off_period_delay_is_rejected() {
+        let err = delayed_by_half_period(false).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("not a multiple of the trigger period"),
+            "unexpected error: {err:#}"
+        );
+    }
+
+    #[test]
+    fn lockstep_mapper_replays_off_period_delay() {
+        let ms = |n: u64| NanoTime::new(n * 1_000_000);
+        let expected: Vec<ValueAt<Vec<u64>>> = (1..=4)
+            .map(|i| ValueAt::new(vec![i], ms(10 * i - 5)))
+            .collect();
+        for _ in 0..5 {
+            assert_eq!(delayed_by_half_period(true).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn lockstep_mapper_chain_is_deterministic() {
+        let period = Duration::from_millis(10);
+        let sum = |xs: Burst<u64>| xs.into_iter().sum::<u64>();
+        let chained = ticker(period)
+            .count()
+            .limit(3)
+            .mapper_lockstep(move |src| {
+                src.map(sum)
+                    .mapper_lockstep(move |src| src.delay(period / 4).map(sum))
+                    .map(sum)
+                    .delay(period / 2)
+            })
+            .map(sum)
+            .collect();
+        chained
+            .run(
+                RunMode::HistoricalFrom(NanoTime::ZERO),
+                RunFor::Duration(period * 4),
+            )
+            .unwrap();
+        let us = |n: u64| NanoTime::new(n * 1_000);
+        assert_eq!(
+            chained.peek_value(),
+            vec![
+                ValueAt::new(1, us(7_500)),
+                ValueAt::new(2, us(17_500)),
+                ValueAt::new(3, us(27_500)),
+            ]
+        );
+    }
+
+    #[test]
+    fn mapper_worker_error_fails_parent_graph() {
+        for run_mode in [RunMode::HistoricalFrom(NanoTime::ZERO), RunMode::RealTime] {
+            let result = ticker(Duration::from_millis(1))
+                .count()
+                .limit(5)
+                .mapper(|src| {
+                    src.try_map(|xs| {
+                        if xs.contains(&3) {
+                            anyhow::bail!("mapper sub-graph failed on 3");
+                        }
+                        Ok(xs.len())
+                    })
+                })
+                .run(run_mode, RunFor::Duration(Duration::from_millis(50)));
+            let err = result.expect_err("parent graph must fail when the mapper sub-graph fails");
+            assert!(
+                format!("{err:#}").contains("mapper sub-graph failed on 3"),
+                "{run_mode:?}: unexpected error: {err:#}"
+            );
+        }
+    }
+
+    #[test]
+    fn mapper_sub_graph_sees_parent_context() {
+        struct Scale(u64);
+        let scaled = ticker(Duration::from_millis(1))
+            .count()
+            .limit(3)
+            .mapper(|src| {
+                GraphStateStream::new(
+                    src.clone().as_node(),
+                    Box::new(move |state: &mut GraphState| {
+                        let scale = state.context::<Scale>().map_or(0, |s| s.0);
+                        src.peek_value()
+                            .iter()
+                            .map(|x| x * scale)
+                            .collect::<Vec<u64>>()
+                    }),
+                )
+                .into_stream()
+            })
+            .collect();
+        Graph::builder()
+            .with_context(Scale(10))
+            .build(
+                vec![scaled.clone().as_node()],
+                RunMode::HistoricalFrom(NanoTime::ZERO),
+                RunFor::Duration(Duration::from_millis(10)),
+            )
+            .run()
+            .unwrap();
+        let values: Vec<u64> = scaled
+            .peek_value()
+            .into_iter()
+            .flat_map(|burst| burst.value.into_iter().flatten())
+            .collect();
+        assert_eq!(values, vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn mapper_preserves_value_time() {
+        for run_mode in [RunMode::HistoricalFrom(NanoTime::ZERO), RunMode::RealTime] {
+            // Each output is the worker's engine time when it produced it.
+            let produced_at = ticker(Duration::from_millis(1))
+                .count()
+                .limit(5)
+                .mapper(|src| src.ticked_at())
+                .collect_with_value_time();
+            produced_at
+                .run(run_mode, RunFor::Duration(Duration::from_millis(20)))
+                .unwrap();
+            let collected = produced_at.peek_value();
+            assert_eq!(collected.len(), 5, "{run_mode:?}");
+            for v in collected {
+                let produced = *v.value.last().unwrap();
+                assert_eq!(v.value_time, produced, "{run_mode:?}");
+                // In realtime the parent cycles on its own clock, so ticked_at
+                // drifts from value_time by however long delivery took.
+                if run_mode != RunMode::RealTime {
+                    assert_eq!(v.time, v.value_time);
+                }
             }
         }
     }
+
+    #[test]
+    fn producer_worker_panic_fails_parent_graph() {
+        let result = producer(|| {
+            ticker(Duration::from_millis(1)).count().map(|x| {
+                assert!(x < 3, "producer sub-graph panicked");
+                x
+            })
+        })
+        .run(
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Duration(Duration::from_millis(50)),
+        );
+        let err = result.expect_err("parent graph must fail when the producer sub-graph panics");
+        assert!(
+            format!("{err:#}").contains("producer sub-graph panicked"),
+            "unexpected error: {err:#}"
+        );
+    }
 }
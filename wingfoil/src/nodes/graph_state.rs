@@ -56,4 +56,35 @@ mod tests {
         let vals: Vec<NanoTime> = elapsed.peek_value().iter().map(|v| v.value).collect();
         assert_eq!(vals, vec![NanoTime::new(100), NanoTime::new(250)]);
     }
+
+    #[test]
+    fn time_bucket_emits_once_per_bucket() {
+        let src: Rc<RefCell<CallBackStream<u64>>> = Rc::new(RefCell::new(CallBackStream::new()));
+        for time in [1_000, 1_300, 1_999, 2_000, 2_500, 4_100] {
+            src.borrow_mut().push(ValueAt::new(0, NanoTime::new(time)));
+        }
+
+        let buckets = src
+            .clone()
+            .as_node()
+            .time_bucket(std::time::Duration::from_nanos(1_000))
+            .collect();
+        buckets
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        let vals: Vec<(NanoTime, NanoTime)> = buckets
+            .peek_value()
+            .iter()
+            .map(|v| (v.value, v.time))
+            .collect();
+        // a bucket ticks on its first tick and a skipped bucket not at all
+        assert_eq!(
+            vals,
+            vec![
+                (NanoTime::new(1_000), NanoTime::new(1_000)),
+                (NanoTime::new(2_000), NanoTime::new(2_000)),
+                (NanoTime::new(4_000), NanoTime::new(4_100)),
+            ]
+        );
+    }
 }
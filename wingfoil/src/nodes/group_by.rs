@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::types::*;
+
+/// Folds `value` into `key`'s running aggregate, returning the new aggregate.
+/// The first value seen for a key becomes its aggregate as is.
+fn reduce_into<K: Hash + Eq, T: Clone>(
+    map: &mut HashMap<K, T>,
+    key: K,
+    value: T,
+    reduce_fn: &dyn Fn(T, T) -> T,
+) -> T {
+    match map.entry(key) {
+        Entry::Occupied(mut entry) => {
+            let reduced = reduce_fn(entry.get().clone(), value);
+            entry.insert(reduced.clone());
+            reduced
+        }
+        Entry::Vacant(entry) => entry.insert(value).clone(),
+    }
+}
+
+/// Keeps a running per-key aggregate of its source and emits a snapshot of
+/// every key's aggregate on each tick.  Used by
+/// [reduce_by_key](crate::nodes::StreamOperators::reduce_by_key).
+pub(crate) struct ReduceByKeyStream<T: Element, K: Element + Hash + Eq> {
+    upstream: Rc<dyn Stream<T>>,
+    key_fn: Box<dyn Fn(&T) -> K>,
+    reduce_fn: Box<dyn Fn(T, T) -> T>,
+    value: Rc<HashMap<K, T>>,
+}
+
+impl<T: Element, K: Element + Hash + Eq> ReduceByKeyStream<T, K> {
+    pub fn new(
+        upstream: Rc<dyn Stream<T>>,
+        key_fn: Box<dyn Fn(&T) -> K>,
+        reduce_fn: Box<dyn Fn(T, T) -> T>,
+    ) -> Self {
+        Self {
+            upstream,
+            key_fn,
+            reduce_fn,
+            value: Rc::default(),
+        }
+    }
+}
+
+#[node(active = [upstream], output = value: Rc<HashMap<K, T>>)]
+impl<T: Element, K: Element + Hash + Eq> MutableNode for ReduceByKeyStream<T, K> {
+    fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+        let value = self.upstream.peek_value();
+        let key = (self.key_fn)(&value);
+        reduce_into(Rc::make_mut(&mut self.value), key, value, &*self.reduce_fn);
+        Ok(true)
+    }
+}
+
+/// Like [ReduceByKeyStream] but emits only the `(key, aggregate)` that
+/// changed.  Used by
+/// [reduce_by_key_updates](crate::nodes::StreamOperators::reduce_by_key_updates).
+pub(crate) struct ReduceByKeyUpdatesStream<T: Element, K: Element + Hash + Eq> {
+    upstream: Rc<dyn Stream<T>>,
+    key_fn: Box<dyn Fn(&T) -> K>,
+    reduce_fn: Box<dyn Fn(T, T) -> T>,
+    aggregates: HashMap<K, T>,
+    value: (K, T),
+}
+
+impl<T: Element, K: Element + Hash + Eq> ReduceByKeyUpdatesStream<T, K> {
+    pub fn new(
+        upstream: Rc<dyn Stream<T>>,
+        key_fn: Box<dyn Fn(&T) -> K>,
+        reduce_fn: Box<dyn Fn(T, T) -> T>,
+    ) -> Self {
+        Self {
+            upstream,
+            key_fn,
+            reduce_fn,
+            aggregates: HashMap::new(),
+            value: Default::default(),
+        }
+    }
+}
+
+#[node(active = [upstream], output = value: (K, T))]
+impl<T: Element, K: Element + Hash + Eq> MutableNode for ReduceByKeyUpdatesStream<T, K> {
+    fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+        let value = self.upstream.peek_value();
+        let key = (self.key_fn)(&value);
+        let reduced = reduce_into(&mut self.aggregates, key.clone(), value, &*self.reduce_fn);
+        self.value = (key, reduced);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::*;
+    use crate::nodes::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Debug, Clone, Default, PartialEq)]
+    struct Fill {
+        symbol: &'static str,
+        pnl: i64,
+    }
+
+    fn fills() -> Rc<dyn Stream<Fill>> {
+        let src = Rc::new(RefCell::new(CallBackStream::<Fill>::new()));
+        let fills = [
+            ("AAPL", 5),
+            ("MSFT", -2),
+            ("AAPL", 3),
+            ("GOOG", 10),
+            ("MSFT", 4),
+            ("AAPL", -1),
+            ("GOOG", -6),
+            ("MSFT", 1),
+            ("AAPL", 2),
+            ("GOOG", 3),
+        ];
+        for (i, (symbol, pnl)) in fills.into_iter().enumerate() {
+            src.borrow_mut().push(ValueAt::new(
+                Fill { symbol, pnl },
+                NanoTime::new(i as u64 * 100),
+            ));
+        }
+        src.as_stream()
+    }
+
+    fn add_fills(a: Fill, b: Fill) -> Fill {
+        Fill {
+            symbol: a.symbol,
+            pnl: a.pnl + b.pnl,
+        }
+    }
+
+    #[test]
+    fn reduce_by_key_sums_per_symbol() {
+        let pnl = fills().reduce_by_key(|fill| fill.symbol, add_fills);
+        pnl.run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        let snapshot = pnl.peek_value();
+        assert_eq!(snapshot.len(), 3);
+        assert_eq!(snapshot["AAPL"].pnl, 9);
+        assert_eq!(snapshot["MSFT"].pnl, 3);
+        assert_eq!(snapshot["GOOG"].pnl, 7);
+    }
+
+    #[test]
+    fn reduce_by_key_updates_emits_changed_key() {
+        let updates = fills()
+            .reduce_by_key_updates(|fill| fill.symbol, add_fills)
+            .collect();
+        updates
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        let updates: Vec<(&str, i64)> = updates
+            .peek_value()
+            .iter()
+            .map(|v| (v.value.0, v.value.1.pnl))
+            .collect();
+        assert_eq!(
+            updates,
+            vec![
+                ("AAPL", 5),
+                ("MSFT", -2),
+                ("AAPL", 8),
+                ("GOOG", 10),
+                ("MSFT", 2),
+                ("AAPL", 7),
+                ("GOOG", 4),
+                ("MSFT", 3),
+                ("AAPL", 9),
+                ("GOOG", 7),
+            ]
+        );
+    }
+}
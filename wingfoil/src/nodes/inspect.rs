@@ -29,6 +29,34 @@ impl<T: Element> MutableNode for InspectStream<T> {
     }
 }
 
+/// Passes through upstream values unchanged, carrying a side branch built
+/// off the same upstream as a passive upstream so that wiring the stream
+/// also wires the branch.
+/// Used by [also](crate::nodes::StreamOperators::also).
+pub struct AlsoStream<T: Element> {
+    upstream: Rc<dyn Stream<T>>,
+    side: Rc<dyn Node>,
+    value: T,
+}
+
+impl<T: Element> AlsoStream<T> {
+    pub fn new(upstream: Rc<dyn Stream<T>>, side: Rc<dyn Node>) -> AlsoStream<T> {
+        AlsoStream {
+            upstream,
+            side,
+            value: T::default(),
+        }
+    }
+}
+
+#[node(active = [upstream], passive = [side], output = value: T)]
+impl<T: Element> MutableNode for AlsoStream<T> {
+    fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+        self.value = self.upstream.peek_value();
+        Ok(true)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::graph::*;
@@ -50,4 +78,21 @@ mod tests {
         assert_eq!(result.peek_value(), 3);
         assert_eq!(*seen.borrow(), vec![1, 2, 3]);
     }
+
+    #[test]
+    fn also_side_branch_cycles_without_readers() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let seen_clone = seen.clone();
+        let result = ticker(Duration::from_millis(1))
+            .count()
+            .also(|count| count.for_each(move |v, _| seen_clone.borrow_mut().push(v)))
+            .map(|v| v * 10)
+            .collect();
+        result
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+            .unwrap();
+        let values: Vec<u64> = result.peek_value().iter().map(|v| v.value).collect();
+        assert_eq!(values, vec![10, 20, 30]);
+        assert_eq!(*seen.borrow(), vec![1, 2, 3]);
+    }
 }
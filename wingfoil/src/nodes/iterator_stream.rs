@@ -4,7 +4,9 @@ use crate::queue::ValueAt;
 use crate::types::*;
 use anyhow::anyhow;
 
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, VecDeque};
+use std::rc::Rc;
 
 type Peeker<T> = std::iter::Peekable<Box<dyn Iterator<Item = ValueAt<T>>>>;
 
@@ -18,7 +20,7 @@ pub struct IteratorStream<T: Element> {
 fn add_callback<T>(peekable: &mut Peeker<T>, state: &mut GraphState) -> anyhow::Result<bool> {
     match peekable.peek() {
         Some(value_at) => {
-            state.add_callback(value_at.time);
+            state.schedule_at_self(value_at.time);
             Ok(true)
         }
         None => Ok(false),
@@ -94,7 +96,7 @@ fn try_add_callback<T: Element>(
     match peekable.peek() {
         Some(Ok(value_at)) => {
             let time = value_at.time;
-            state.add_callback(time);
+            state.schedule_at_self(time);
             Ok(true)
         }
         Some(Err(_)) => Err(peekable
@@ -169,6 +171,7 @@ impl<T: Element> MutableNode for SimpleIteratorStream<T> {
             .next()
             .expect("SimpleIteratorStream cycled with no upcoming item");
         self.value = val_at1.value;
+        state.set_value_time(val_at1.time);
 
         if let Some(val_at2) = self.peekable.peek() {
             match val_at1.time.cmp(&val_at2.time) {
@@ -208,6 +211,142 @@ where
     }
 }
 
+/// K-way merge of fallible iterators, each ascending in time, exposed as a
+/// single [`Stream`] of [`Burst<T>`].  A binary heap keyed on each source's
+/// next time picks the earliest, so the graph schedules one callback per
+/// timestamp however many sources there are.  Items sharing a time are
+/// emitted in one burst, in source order.  A source that runs out simply
+/// drops out of the heap; the stream ends when they all have.
+/// Used by [merge_ordered](crate::nodes::merge_ordered).
+pub struct MergeOrderedStream<T: Element> {
+    sources: Vec<TryPeeker<T>>,
+    /// `(next time, source index)` of every source not yet exhausted.
+    heap: BinaryHeap<Reverse<(NanoTime, usize)>>,
+    value: Burst<T>,
+}
+
+impl<T: Element> MergeOrderedStream<T> {
+    pub fn new(sources: Vec<Box<dyn Iterator<Item = anyhow::Result<ValueAt<T>>>>>) -> Self {
+        Self {
+            heap: BinaryHeap::with_capacity(sources.len()),
+            sources: sources.into_iter().map(Iterator::peekable).collect(),
+            value: Burst::new(),
+        }
+    }
+
+    /// Queues source `index` at its next time, surfacing its error if it has
+    /// one, or leaves it out of the heap if it is exhausted.
+    fn queue_source(&mut self, index: usize, after: NanoTime) -> anyhow::Result<()> {
+        match self.sources[index].peek() {
+            Some(Ok(value_at)) if value_at.time < after => Err(anyhow!(
+                "merge_ordered: source {index} time was descending, {} < {}",
+                value_at.time,
+                after
+            )),
+            Some(Ok(value_at)) => {
+                self.heap.push(Reverse((value_at.time, index)));
+                Ok(())
+            }
+            Some(Err(_)) => Err(self.sources[index]
+                .next()
+                .expect("peek() just returned Some")
+                .expect_err("peek() just returned Err")),
+            None => Ok(()),
+        }
+    }
+
+    fn schedule_next(&self, state: &mut GraphState) {
+        if let Some(Reverse((time, _))) = self.heap.peek() {
+            state.schedule_at_self(*time);
+        }
+    }
+}
+
+#[node(output = value: Burst<T>)]
+impl<T: Element> MutableNode for MergeOrderedStream<T> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        self.value.clear();
+        while let Some(&Reverse((time, index))) = self.heap.peek() {
+            if time != state.time() {
+                break;
+            }
+            self.heap.pop();
+            // The heap only holds sources whose next item is Ok.
+            let value_at = self.sources[index]
+                .next()
+                .expect("invariant: queued source has a next item")
+                .expect("invariant: queued source's next item is Ok");
+            self.value.push(value_at.value);
+            // A further item at the same time is queued as (time, index) and
+            // popped again before any later source, keeping source order.
+            self.queue_source(index, time)?;
+        }
+        self.schedule_next(state);
+        Ok(!self.value.is_empty())
+    }
+
+    fn start(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
+        for index in 0..self.sources.len() {
+            self.queue_source(index, NanoTime::ZERO)?;
+        }
+        self.schedule_next(state);
+        Ok(())
+    }
+}
+
+/// Plays back a sequence of streams one after another, e.g. one historical
+/// source per day.  Used by [chain_streams](crate::nodes::chain_streams).
+///
+/// The chain moves on to a later stream as soon as it ticks; it is an error
+/// for an earlier stream to tick after that, or for two streams to tick on
+/// the same cycle, since the sources would then overlap in time.
+pub struct ChainIteratorStream<T: Element> {
+    upstreams: Vec<Rc<dyn Stream<T>>>,
+    /// The current stream followed by those not yet reached.
+    pending: VecDeque<Rc<dyn Stream<T>>>,
+    value: T,
+}
+
+impl<T: Element> ChainIteratorStream<T> {
+    pub fn new(streams: Vec<Rc<dyn Stream<T>>>) -> Self {
+        Self {
+            pending: streams.iter().cloned().collect(),
+            upstreams: streams,
+            value: T::default(),
+        }
+    }
+}
+
+#[node(active = [upstreams], output = value: T)]
+impl<T: Element> MutableNode for ChainIteratorStream<T> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        let ticked: Vec<usize> = (0..self.upstreams.len())
+            .filter(|&i| state.ticked(self.upstreams[i].clone().as_node()))
+            .collect();
+        let current = self.upstreams.len() - self.pending.len();
+        match ticked.as_slice() {
+            [] => Ok(false),
+            [i] if *i >= current => {
+                self.pending.drain(..i - current);
+                let stream = self
+                    .pending
+                    .front()
+                    .expect("invariant: ticked stream is pending");
+                self.value = stream.peek_value();
+                Ok(true)
+            }
+            [i] => Err(anyhow!(
+                "chain_streams: stream {i} ticked at {} after the chain moved on to stream {current}",
+                state.time().pretty()
+            )),
+            _ => Err(anyhow!(
+                "chain_streams: streams {ticked:?} overlap at {}",
+                state.time().pretty()
+            )),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -253,6 +392,46 @@ mod tests {
         assert_eq!(ticks[1].value.as_slice(), &[3u64]);
     }
 
+    fn merged(sources: Vec<Vec<(u64, u64)>>) -> anyhow::Result<Vec<ValueAt<Vec<u64>>>> {
+        let sources = sources
+            .iter()
+            .map(|pairs| {
+                let items = value_ats(pairs).into_iter().map(Ok);
+                Box::new(items) as Box<dyn Iterator<Item = anyhow::Result<ValueAt<u64>>>>
+            })
+            .collect();
+        let out = merge_ordered(sources).map(|b| b.to_vec()).collect();
+        out.run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)?;
+        Ok(out.peek_value())
+    }
+
+    #[test]
+    fn merge_ordered_interleaves_sources_by_time() {
+        // The second source runs out first; ties burst together in source order.
+        let ticks = merged(vec![
+            vec![(1, 0), (3, 200), (5, 300), (6, 300)],
+            vec![(2, 100), (4, 200)],
+            vec![],
+        ])
+        .unwrap();
+        let expected = vec![
+            ValueAt::new(vec![1], NanoTime::new(0)),
+            ValueAt::new(vec![2], NanoTime::new(100)),
+            ValueAt::new(vec![3, 4], NanoTime::new(200)),
+            ValueAt::new(vec![5, 6], NanoTime::new(300)),
+        ];
+        assert_eq!(ticks, expected);
+    }
+
+    #[test]
+    fn merge_ordered_rejects_descending_source() {
+        let err = merged(vec![vec![(1, 0), (2, 200), (3, 100)], vec![(4, 150)]]).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("source 0 time was descending"),
+            "{err:#}"
+        );
+    }
+
     #[test]
     fn try_iterator_groups_ok_items_into_burst() {
         // Two Ok items at t=0, one at t=100. The final group (t=100) must emit.
@@ -350,4 +529,49 @@ mod tests {
             .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever);
         assert!(result.is_err());
     }
+
+    fn callback_stream(pairs: &[(u64, u64)]) -> Rc<dyn Stream<u64>> {
+        let stream = Rc::new(std::cell::RefCell::new(CallBackStream::new()));
+        for value_at in value_ats(pairs) {
+            stream.borrow_mut().push(value_at);
+        }
+        stream.as_stream()
+    }
+
+    #[test]
+    fn chain_streams_plays_streams_in_sequence() {
+        let first = callback_stream(&[(1, 10), (2, 20)]);
+        let second = callback_stream(&[(3, 30), (4, 40)]);
+        let chained = chain_streams(vec![first, second]).collect();
+        chained
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        assert_eq!(
+            chained.peek_value(),
+            value_ats(&[(1, 10), (2, 20), (3, 30), (4, 40)])
+        );
+    }
+
+    #[test]
+    fn chain_streams_errors_when_streams_interleave() {
+        let first = callback_stream(&[(1, 10), (2, 30)]);
+        let second = callback_stream(&[(3, 20)]);
+        let err = chain_streams(vec![first, second])
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("after the chain moved on"),
+            "{err:#}"
+        );
+    }
+
+    #[test]
+    fn chain_streams_errors_when_streams_tick_together() {
+        let first = callback_stream(&[(1, 10)]);
+        let second = callback_stream(&[(2, 10)]);
+        let err = chain_streams(vec![first, second])
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("overlap"), "{err:#}");
+    }
 }
@@ -0,0 +1,97 @@
+use derive_new::new;
+
+use crate::types::*;
+use std::rc::Rc;
+
+/// Pairs each tick of `left` with the most recent `right` value at-or-before
+/// it, provided that value is no older than `tolerance`.
+/// Used by [asof_join](crate::nodes::StreamOperators::asof_join).
+///
+/// `right` is wired actively so its tick times can be recorded, but only
+/// ticks of `left` produce output.
+#[derive(new)]
+pub struct AsofJoinStream<A: Element, B: Element> {
+    left: Rc<dyn Stream<A>>,
+    right: Rc<dyn Stream<B>>,
+    tolerance: NanoTime,
+    #[new(default)]
+    latest: Option<(NanoTime, B)>,
+    #[new(default)]
+    value: (A, Option<B>),
+}
+
+#[node(active = [left, right], output = value: (A, Option<B>))]
+impl<A: Element, B: Element> MutableNode for AsofJoinStream<A, B> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        let now = state.time();
+        if state.ticked(self.right.clone().as_node()) {
+            self.latest = Some((now, self.right.peek_value()));
+        }
+        if !state.ticked(self.left.clone().as_node()) {
+            return Ok(false);
+        }
+        let right = self
+            .latest
+            .as_ref()
+            .filter(|(time, _)| now - *time <= self.tolerance)
+            .map(|(_, value)| value.clone());
+        self.value = (self.left.peek_value(), right);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::*;
+    use crate::nodes::*;
+    use crate::queue::ValueAt;
+    use std::cell::RefCell;
+
+    fn stream_of<T: Element + PartialEq>(ticks: &[(u64, T)]) -> Rc<dyn Stream<T>> {
+        let src = Rc::new(RefCell::new(CallBackStream::new()));
+        for (ms, value) in ticks {
+            src.borrow_mut()
+                .push(ValueAt::new(value.clone(), NanoTime::new(ms * 1_000_000)));
+        }
+        src.as_stream()
+    }
+
+    #[test]
+    fn asof_join_pairs_trades_with_prevailing_quote() {
+        // quotes every 10ms, trades at irregular times
+        let quotes = stream_of(&[(0, 100.0), (10, 101.0), (20, 102.0), (30, 103.0)]);
+        let trades = stream_of(&[(5, 1u32), (10, 2), (25, 3), (60, 4)]);
+        let joined = trades
+            .asof_join(quotes, Duration::from_millis(10))
+            .collect();
+        joined
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        let values: Vec<(u32, Option<f64>)> =
+            joined.peek_value().into_iter().map(|v| v.value).collect();
+        assert_eq!(
+            values,
+            vec![
+                (1, Some(100.0)),
+                // a quote at the same time as the trade counts as prevailing
+                (2, Some(101.0)),
+                (3, Some(102.0)),
+                // last quote is 30ms old, beyond the 10ms tolerance
+                (4, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn asof_join_is_none_before_first_right_tick() {
+        let quotes = stream_of(&[(20, 100.0)]);
+        let trades = stream_of(&[(10, 1u32), (20, 2)]);
+        let joined = trades.asof_join(quotes, Duration::from_secs(1)).collect();
+        joined
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        let values: Vec<(u32, Option<f64>)> =
+            joined.peek_value().into_iter().map(|v| v.value).collect();
+        assert_eq!(values, vec![(1, None), (2, Some(100.0))]);
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::graph::RunMode;
+use crate::types::*;
+
+/// Emits its source `delay` later.  A constant delay keeps ticks in arrival
+/// order, so a FIFO suffices: unlike [delay](crate::nodes::StreamOperators::delay)
+/// this needs no `PartialEq` and never merges equal values.  Used by
+/// [lag](crate::nodes::StreamOperators::lag).
+pub(crate) struct LagStream<T: Element> {
+    upstream: Rc<dyn Stream<T>>,
+    delay: NanoTime,
+    pending: VecDeque<(NanoTime, T)>,
+    value: T,
+}
+
+impl<T: Element> LagStream<T> {
+    pub fn new(upstream: Rc<dyn Stream<T>>, delay: NanoTime) -> Self {
+        Self {
+            upstream,
+            delay,
+            pending: VecDeque::new(),
+            value: T::default(),
+        }
+    }
+}
+
+#[node(active = [upstream], output = value: T)]
+impl<T: Element> MutableNode for LagStream<T> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        let now = state.time();
+        if self.delay == NanoTime::ZERO {
+            self.value = self.upstream.peek_value();
+            return Ok(true);
+        }
+        if state.ticked(self.upstream.clone().as_node()) {
+            let due = now + self.delay;
+            state.schedule_at_self(due);
+            self.pending.push_back((due, self.upstream.peek_value()));
+        }
+        let mut ticked = false;
+        while self.pending.front().is_some_and(|(due, _)| *due <= now) {
+            if let Some((due, value)) = self.pending.pop_front() {
+                self.value = value;
+                state.set_value_time(due - self.delay);
+                ticked = true;
+            }
+        }
+        Ok(ticked)
+    }
+}
+
+/// On each upstream tick, emits the value from `ticks` ticks earlier.  Silent
+/// until that many ticks have been seen.  Used by
+/// [lag_ticks](crate::nodes::StreamOperators::lag_ticks).
+pub(crate) struct LagTicksStream<T: Element> {
+    upstream: Rc<dyn Stream<T>>,
+    ticks: usize,
+    window: VecDeque<T>,
+    value: T,
+}
+
+impl<T: Element> LagTicksStream<T> {
+    pub fn new(upstream: Rc<dyn Stream<T>>, ticks: usize) -> Self {
+        Self {
+            upstream,
+            ticks,
+            window: VecDeque::with_capacity(ticks + 1),
+            value: T::default(),
+        }
+    }
+}
+
+#[node(active = [upstream], output = value: T)]
+impl<T: Element> MutableNode for LagTicksStream<T> {
+    fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+        self.window.push_back(self.upstream.peek_value());
+        if self.window.len() > self.ticks {
+            if let Some(value) = self.window.pop_front() {
+                self.value = value;
+            }
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+/// Pairs each value with the one `ticks` ticks after it, emitting
+/// `(value, later)` when the later value arrives.  Historical only.  Used by
+/// [lead_ticks](crate::nodes::StreamOperators::lead_ticks).
+pub(crate) struct LeadTicksStream<T: Element> {
+    lagged: LagTicksStream<T>,
+    value: (T, T),
+}
+
+impl<T: Element> LeadTicksStream<T> {
+    pub fn new(upstream: Rc<dyn Stream<T>>, ticks: usize) -> Self {
+        Self {
+            lagged: LagTicksStream::new(upstream, ticks),
+            value: Default::default(),
+        }
+    }
+}
+
+impl<T: Element> MutableNode for LeadTicksStream<T> {
+    fn upstreams(&self) -> UpStreams {
+        self.lagged.upstreams()
+    }
+
+    fn setup(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            matches!(state.run_mode(), RunMode::HistoricalFrom(_)),
+            "lead_ticks looks ahead, so requires RunMode::HistoricalFrom"
+        );
+        Ok(())
+    }
+
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        if !self.lagged.cycle(state)? {
+            return Ok(false);
+        }
+        self.value = (self.lagged.value.clone(), self.lagged.upstream.peek_value());
+        Ok(true)
+    }
+}
+
+impl<T: Element> StreamPeekRef<(T, T)> for LeadTicksStream<T> {
+    fn peek_ref(&self) -> &(T, T) {
+        &self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::*;
+    use crate::nodes::*;
+
+    /// Deliberately neither `PartialEq` nor `Hash`.
+    #[derive(Debug, Clone, Default)]
+    struct Bar {
+        close: u64,
+    }
+
+    fn values_and_times<T: Element>(stream: Rc<dyn Stream<T>>, cycles: u32) -> Vec<(T, NanoTime)> {
+        let collected = stream.collect();
+        collected
+            .run(
+                RunMode::HistoricalFrom(NanoTime::ZERO),
+                RunFor::Cycles(cycles),
+            )
+            .unwrap();
+        collected
+            .peek_value()
+            .into_iter()
+            .map(|v| (v.value, v.time))
+            .collect()
+    }
+
+    #[test]
+    fn lag_shifts_values_without_partial_eq() {
+        // Upstream ticks at 0, 100, 200, .. interleave with the lagged 250, 350, ..
+        let bars = ticker(Duration::from_nanos(100))
+            .count()
+            .map(|close| Bar { close })
+            .lag(Duration::from_nanos(250))
+            .map(|bar| bar.close);
+        assert_eq!(
+            values_and_times(bars, 8),
+            vec![
+                (1, NanoTime::new(250)),
+                (2, NanoTime::new(350)),
+                (3, NanoTime::new(450)),
+            ]
+        );
+    }
+
+    #[test]
+    fn lag_keeps_value_time_of_source_tick() {
+        let lagged = ticker(Duration::from_nanos(100))
+            .count()
+            .lag(Duration::from_nanos(250))
+            .collect_with_value_time();
+        lagged
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(6))
+            .unwrap();
+        let times: Vec<(u64, NanoTime, NanoTime)> = lagged
+            .peek_value()
+            .into_iter()
+            .map(|v| (v.value, v.time, v.value_time))
+            .collect();
+        assert_eq!(
+            times,
+            vec![
+                (1, NanoTime::new(250), NanoTime::new(0)),
+                (2, NanoTime::new(350), NanoTime::new(100)),
+            ]
+        );
+    }
+
+    #[test]
+    fn lag_ticks_emits_value_from_n_ticks_ago() {
+        let lagged = ticker(Duration::from_nanos(100)).count().lag_ticks(2);
+        assert_eq!(
+            values_and_times(lagged, 5),
+            vec![
+                (1, NanoTime::new(200)),
+                (2, NanoTime::new(300)),
+                (3, NanoTime::new(400)),
+            ]
+        );
+        let unlagged = ticker(Duration::from_nanos(100)).count().lag_ticks(0);
+        assert_eq!(values_and_times(unlagged, 2).len(), 2);
+    }
+
+    #[test]
+    fn lead_ticks_pairs_values_with_later_ones() {
+        let labelled = ticker(Duration::from_nanos(100)).count().lead_ticks(2);
+        assert_eq!(
+            values_and_times(labelled, 4),
+            vec![((1, 3), NanoTime::new(200)), ((2, 4), NanoTime::new(300))]
+        );
+    }
+
+    #[test]
+    fn lead_ticks_is_rejected_in_realtime() {
+        let result = ticker(Duration::from_millis(1))
+            .count()
+            .lead_ticks(1)
+            .run(RunMode::RealTime, RunFor::Cycles(1));
+        let err = format!("{:#}", result.unwrap_err());
+        assert!(err.contains("requires RunMode::HistoricalFrom"), "{err}");
+    }
+}
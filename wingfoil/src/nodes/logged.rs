@@ -0,0 +1,53 @@
+use crate::types::*;
+use derive_new::new;
+use log::Level;
+use std::rc::Rc;
+
+/// Propagates its upstream, logging a line for each value at `level`.
+/// Whether the level is enabled is checked per tick, so a logger
+/// reconfigured mid-run takes effect; `line` is only called when it is.
+#[derive(new)]
+pub(crate) struct LoggedStream<T: Element> {
+    upstream: Rc<dyn Stream<T>>,
+    target: String,
+    level: Level,
+    line: Box<dyn Fn(&T, NanoTime) -> String>,
+    #[new(default)]
+    value: T,
+}
+
+impl<T: Element> LoggedStream<T> {
+    #[cfg(not(feature = "tracing"))]
+    fn emit(&self, time: NanoTime) {
+        // an atomic load that rejects most disabled levels before asking
+        // the logger about the target
+        if self.level > log::max_level() || !log::log_enabled!(target: &self.target, self.level) {
+            return;
+        }
+        let text = (self.line)(&self.value, time);
+        log::log!(target: &self.target, self.level, "{text}");
+    }
+
+    #[cfg(feature = "tracing")]
+    fn emit(&self, time: NanoTime) {
+        // tracing targets are static, so a custom target is a field instead
+        if !tracing_log_enabled!(self.level) {
+            return;
+        }
+        let text = (self.line)(&self.value, time);
+        if self.target == "wingfoil" {
+            tracing_log!(self.level, target: "wingfoil", "{}", text);
+        } else {
+            tracing_log!(self.level, target: "wingfoil", log_target = %self.target, "{}", text);
+        }
+    }
+}
+
+#[node(active = [upstream], output = value: T)]
+impl<T: Element> MutableNode for LoggedStream<T> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        self.value = self.upstream.peek_value();
+        self.emit(state.elapsed());
+        Ok(true)
+    }
+}
@@ -1,14 +1,117 @@
 use derive_new::new;
 
+use std::any::Any;
 use std::boxed::Box;
+use std::panic::{AssertUnwindSafe, catch_unwind};
 use std::rc::Rc;
 
 use crate::types::*;
 
 /// Map's it's source into a new [Stream] using the supplied closure.
 /// Used by [map](crate::nodes::StreamOperators::map).
-#[derive(new)]
 pub struct MapStream<IN, OUT: Element> {
+    upstream: Rc<dyn Stream<IN>>,
+    value: OUT,
+    func: Rc<dyn Fn(IN) -> OUT>,
+    /// Set by [fuse_maps](crate::fuse_maps) when `upstream` is a map folded
+    /// into this one: the source it read, and its closures composed over it.
+    /// `upstream` is then no longer wired or read.
+    fused: Option<Box<(Rc<dyn Node>, Rc<dyn Fn() -> IN>)>>,
+}
+
+impl<IN: 'static, OUT: Element> MapStream<IN, OUT> {
+    pub fn new(upstream: Rc<dyn Stream<IN>>, func: Box<dyn Fn(IN) -> OUT>) -> Self {
+        Self {
+            upstream,
+            value: OUT::default(),
+            func: Rc::from(func),
+            fused: None,
+        }
+    }
+}
+
+impl<IN: 'static, OUT: Element> StreamPeekRef<OUT> for MapStream<IN, OUT> {
+    fn peek_ref(&self) -> &OUT {
+        &self.value
+    }
+}
+
+impl<IN: 'static, OUT: Element> MutableNode for MapStream<IN, OUT> {
+    fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+        self.value = match &self.fused {
+            None => (self.func)(self.upstream.peek_value()),
+            Some(fused) => (self.func)((fused.1)()),
+        };
+        Ok(true)
+    }
+
+    fn upstreams(&self) -> UpStreams {
+        let source = match &self.fused {
+            None => self.upstream.clone().as_node(),
+            Some(fused) => fused.0.clone(),
+        };
+        UpStreams::new(vec![source], vec![])
+    }
+
+    fn composed_map(&self) -> Option<(Rc<dyn Node>, Box<dyn Any>)> {
+        let func = self.func.clone();
+        let (source, read): (Rc<dyn Node>, Rc<dyn Fn() -> OUT>) = match &self.fused {
+            None => {
+                let upstream = self.upstream.clone();
+                (
+                    upstream.clone().as_node(),
+                    Rc::new(move || func(upstream.peek_value())),
+                )
+            }
+            Some(fused) => {
+                let input = fused.1.clone();
+                (fused.0.clone(), Rc::new(move || func(input())))
+            }
+        };
+        Some((source, Box::new(read)))
+    }
+
+    fn fuse_upstream(&mut self) -> bool {
+        if self.fused.is_some() {
+            return false;
+        }
+        let Some((source, read)) = self.upstream.composed_map() else {
+            return false;
+        };
+        match read.downcast::<Rc<dyn Fn() -> IN>>() {
+            Ok(read) => {
+                self.fused = Some(Box::new((source, *read)));
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// Like [MapStream] but passes the closure a reference to the source's value,
+/// so reading an expensive payload does not clone it.
+/// Used by [map_ref](crate::nodes::StreamOperators::map_ref).
+#[derive(new)]
+pub struct MapRefStream<IN, OUT: Element> {
+    upstream: Rc<dyn Stream<IN>>,
+    #[new(default)]
+    value: OUT,
+    func: Box<dyn Fn(&IN) -> OUT>,
+}
+
+#[node(active = [upstream], output = value: OUT)]
+impl<IN, OUT: Element> MutableNode for MapRefStream<IN, OUT> {
+    fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+        self.value = (self.func)(&self.upstream.peek_ref_cell());
+        Ok(true)
+    }
+}
+
+/// Like [MapStream] but a panic in the closure fails the graph with an
+/// error instead of unwinding through it.
+/// Used by [map_catch](crate::nodes::StreamOperators::map_catch).
+#[derive(new)]
+pub struct MapCatchStream<IN, OUT: Element> {
     upstream: Rc<dyn Stream<IN>>,
     #[new(default)]
     value: OUT,
@@ -16,21 +119,35 @@ pub struct MapStream<IN, OUT: Element> {
 }
 
 #[node(active = [upstream], output = value: OUT)]
-impl<IN, OUT: Element> MutableNode for MapStream<IN, OUT> {
+impl<IN, OUT: Element> MutableNode for MapCatchStream<IN, OUT> {
     fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
-        self.value = (self.func)(self.upstream.peek_value());
+        let value = self.upstream.peek_value();
+        // The closure's state is not touched again once it has panicked: the
+        // graph stops on the error.
+        self.value = catch_unwind(AssertUnwindSafe(|| (self.func)(value)))
+            .map_err(|panic| anyhow::anyhow!("map closure panicked: {}", panic_message(&*panic)))?;
         Ok(true)
     }
 }
 
+/// The message a panic was raised with, if it was a string.
+pub(crate) fn panic_message(panic: &(dyn Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".to_string())
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
     use crate::graph::*;
     use crate::nodes::*;
-    use std::cell::RefCell;
+    use std::cell::{Cell, RefCell};
     use std::rc::Rc;
+    use std::time::Duration;
 
     #[test]
     fn map_stream_works() {
@@ -68,4 +185,91 @@ mod tests {
         println!("{:?}", captured.peek_value());
         assert_eq!(expected, captured.peek_value());
     }
+
+    /// Counts how often it is cloned.
+    #[derive(Debug, Default)]
+    struct Payload(Rc<Cell<u32>>);
+
+    impl Clone for Payload {
+        fn clone(&self) -> Self {
+            self.0.set(self.0.get() + 1);
+            Payload(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn map_ref_does_not_clone_source() {
+        let clones = Rc::new(Cell::new(0));
+        let source = {
+            let clones = clones.clone();
+            ticker(Duration::from_nanos(100)).produce(move || Payload(clones.clone()))
+        };
+        let reads = source.map_ref(|payload| payload.0.get()).collect();
+        reads
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+            .unwrap();
+        assert_eq!(reads.peek_value().len(), 3);
+        assert_eq!(clones.get(), 0);
+
+        let cloned = {
+            let clones = clones.clone();
+            ticker(Duration::from_nanos(100))
+                .produce(move || Payload(clones.clone()))
+                .map(|payload| payload.0.get())
+        };
+        cloned
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+            .unwrap();
+        assert_eq!(clones.get(), 3);
+    }
+
+    #[test]
+    fn map_catch_turns_panic_into_error() {
+        let result = ticker(Duration::from_nanos(100))
+            .count()
+            .map_catch(|x| {
+                assert!(x != 3, "cannot map {x}");
+                x * 10
+            })
+            .collect()
+            .finally(|values, _| {
+                let values: Vec<u64> = values.iter().map(|v| v.value).collect();
+                assert_eq!(values, vec![10, 20]);
+                Ok(())
+            })
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(5));
+        let err = format!("{:#}", result.unwrap_err());
+        assert!(err.contains("map closure panicked: cannot map 3"), "{err}");
+    }
+
+    #[test]
+    fn boxed_map_stream_cycles() {
+        let source = ticker(Duration::from_nanos(100)).count();
+        let boxed: Box<dyn StreamPeekRef<u64>> =
+            Box::new(MapStream::new(source, Box::new(|x: u64| x * 10)));
+        let captured = boxed.into_stream().collect();
+        captured
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+            .unwrap();
+        let values: Vec<u64> = captured.peek_value().iter().map(|v| v.value).collect();
+        assert_eq!(values, vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn boxed_mutable_nodes_can_be_collected_and_wired() {
+        let source = ticker(Duration::from_nanos(100)).count();
+        let nodes: Vec<Box<dyn MutableNode>> = vec![
+            Box::new(MapStream::new(source.clone(), Box::new(|x: u64| x + 1))),
+            Box::new(MapStream::new(source, Box::new(|x: u64| x * 2))),
+        ];
+        let nodes: Vec<Rc<dyn Node>> = nodes.into_iter().map(|node| node.into_node()).collect();
+        assert!(nodes[0].type_name().starts_with("MapStream"));
+        Graph::new(
+            nodes,
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(2),
+        )
+        .run()
+        .unwrap();
+    }
 }
@@ -0,0 +1,284 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::types::*;
+
+/// The changes between two consecutive map snapshots, as produced by
+/// [diff_map](MapSnapshotStreamOperators::diff_map) and consumed by
+/// [apply_deltas](MapDeltaStreamOperators::apply_deltas).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapDelta<K: Hash + Eq, V> {
+    /// Keys absent from the previous snapshot.
+    pub inserted: HashMap<K, V>,
+    /// Keys whose value changed.
+    pub updated: HashMap<K, V>,
+    /// Keys absent from the new snapshot.
+    pub removed: HashSet<K>,
+}
+
+impl<K: Hash + Eq, V> Default for MapDelta<K, V> {
+    fn default() -> Self {
+        Self {
+            inserted: HashMap::new(),
+            updated: HashMap::new(),
+            removed: HashSet::new(),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> MapDelta<K, V> {
+    /// True if applying this delta would change nothing.
+    pub fn is_empty(&self) -> bool {
+        self.inserted.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Emits the [MapDelta] between each snapshot and the one before it.
+/// The first snapshot is diffed against an empty map.  Does not tick when
+/// a snapshot is unchanged.
+pub(crate) struct DiffMapStream<K: Element + Hash + Eq, V: Element + PartialEq> {
+    upstream: Rc<dyn Stream<Rc<HashMap<K, V>>>>,
+    previous: Rc<HashMap<K, V>>,
+    value: MapDelta<K, V>,
+}
+
+impl<K: Element + Hash + Eq, V: Element + PartialEq> DiffMapStream<K, V> {
+    pub fn new(upstream: Rc<dyn Stream<Rc<HashMap<K, V>>>>) -> Self {
+        Self {
+            upstream,
+            previous: Rc::default(),
+            value: MapDelta::default(),
+        }
+    }
+}
+
+#[node(active = [upstream], output = value: MapDelta<K, V>)]
+impl<K: Element + Hash + Eq, V: Element + PartialEq> MutableNode for DiffMapStream<K, V> {
+    fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+        let current = self.upstream.peek_value();
+        if Rc::ptr_eq(&current, &self.previous) {
+            return Ok(false);
+        }
+        let mut delta = MapDelta::default();
+        for (key, value) in current.iter() {
+            match self.previous.get(key) {
+                None => {
+                    delta.inserted.insert(key.clone(), value.clone());
+                }
+                Some(old) if old != value => {
+                    delta.updated.insert(key.clone(), value.clone());
+                }
+                Some(_) => {}
+            }
+        }
+        for key in self.previous.keys() {
+            if !current.contains_key(key) {
+                delta.removed.insert(key.clone());
+            }
+        }
+        self.previous = current;
+        if delta.is_empty() {
+            return Ok(false);
+        }
+        self.value = delta;
+        Ok(true)
+    }
+}
+
+/// Rebuilds a snapshot stream from [MapDelta]s, starting from an initial
+/// map.  Does not tick on an empty delta.
+pub(crate) struct ApplyDeltasStream<K: Element + Hash + Eq, V: Element> {
+    upstream: Rc<dyn Stream<MapDelta<K, V>>>,
+    value: Rc<HashMap<K, V>>,
+}
+
+impl<K: Element + Hash + Eq, V: Element> ApplyDeltasStream<K, V> {
+    pub fn new(upstream: Rc<dyn Stream<MapDelta<K, V>>>, initial: HashMap<K, V>) -> Self {
+        Self {
+            upstream,
+            value: Rc::new(initial),
+        }
+    }
+}
+
+#[node(active = [upstream], output = value: Rc<HashMap<K, V>>)]
+impl<K: Element + Hash + Eq, V: Element> MutableNode for ApplyDeltasStream<K, V> {
+    fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+        let delta = self.upstream.peek_ref_cell();
+        if delta.is_empty() {
+            return Ok(false);
+        }
+        // Only copies the map if a downstream node still holds the last snapshot.
+        let map = Rc::make_mut(&mut self.value);
+        for key in &delta.removed {
+            map.remove(key);
+        }
+        for (key, value) in delta.inserted.iter().chain(delta.updated.iter()) {
+            map.insert(key.clone(), value.clone());
+        }
+        Ok(true)
+    }
+}
+
+/// Operators for streams of `Rc<HashMap>` snapshots.
+pub trait MapSnapshotStreamOperators<K: Element + Hash + Eq, V: Element + PartialEq> {
+    /// Emits the [MapDelta] between consecutive snapshots, the inverse of
+    /// [apply_deltas](MapDeltaStreamOperators::apply_deltas).  Does not tick
+    /// when nothing changed.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::collections::HashMap;
+    /// # use std::rc::Rc;
+    /// # use std::time::Duration;
+    /// let deltas = ticker(Duration::from_millis(10))
+    ///     .count()
+    ///     .map(|n| Rc::new(HashMap::from([("count", n)])))
+    ///     .diff_map();
+    /// ```
+    #[must_use]
+    fn diff_map(self: &Rc<Self>) -> Rc<dyn Stream<MapDelta<K, V>>>;
+}
+
+impl<K: Element + Hash + Eq, V: Element + PartialEq> MapSnapshotStreamOperators<K, V>
+    for dyn Stream<Rc<HashMap<K, V>>>
+{
+    fn diff_map(self: &Rc<Self>) -> Rc<dyn Stream<MapDelta<K, V>>> {
+        DiffMapStream::new(self.clone()).into_stream()
+    }
+}
+
+/// Operators for streams of [MapDelta]s.
+pub trait MapDeltaStreamOperators<K: Element + Hash + Eq, V: Element> {
+    /// Applies each delta to a map seeded with `initial`, emitting the
+    /// resulting snapshot.  The inverse of
+    /// [diff_map](MapSnapshotStreamOperators::diff_map).  Does not tick on
+    /// an empty delta.
+    #[must_use]
+    fn apply_deltas(self: &Rc<Self>, initial: HashMap<K, V>) -> Rc<dyn Stream<Rc<HashMap<K, V>>>>;
+}
+
+impl<K: Element + Hash + Eq, V: Element> MapDeltaStreamOperators<K, V>
+    for dyn Stream<MapDelta<K, V>>
+{
+    fn apply_deltas(self: &Rc<Self>, initial: HashMap<K, V>) -> Rc<dyn Stream<Rc<HashMap<K, V>>>> {
+        ApplyDeltasStream::new(self.clone(), initial).into_stream()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::*;
+    use crate::nodes::*;
+    use crate::queue::ValueAt;
+
+    type Snapshot = Rc<HashMap<u32, i64>>;
+
+    fn snapshots(maps: Vec<Vec<(u32, i64)>>) -> Rc<dyn Stream<Snapshot>> {
+        let mut stream = CallBackStream::new();
+        for (i, map) in maps.into_iter().enumerate() {
+            let map = Rc::new(map.into_iter().collect::<HashMap<_, _>>());
+            stream.push(ValueAt::new(map, NanoTime::new(i as u64)));
+        }
+        stream.into_stream()
+    }
+
+    fn run<T: Element>(stream: &Rc<dyn Stream<T>>) -> Vec<ValueAt<T>> {
+        let collected = stream.collect();
+        collected
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        collected.peek_value()
+    }
+
+    #[test]
+    fn diff_map_reports_inserts_updates_and_removals() {
+        let deltas = run(&snapshots(vec![
+            vec![(1, 10), (2, 20)],
+            vec![(1, 11), (3, 30)],
+            vec![(3, 30)],
+        ])
+        .diff_map());
+        let expected = vec![
+            MapDelta {
+                inserted: HashMap::from([(1, 10), (2, 20)]),
+                ..Default::default()
+            },
+            MapDelta {
+                inserted: HashMap::from([(3, 30)]),
+                updated: HashMap::from([(1, 11)]),
+                removed: HashSet::from([2]),
+            },
+            MapDelta {
+                removed: HashSet::from([1]),
+                ..Default::default()
+            },
+        ];
+        assert_eq!(
+            expected,
+            deltas.into_iter().map(|d| d.value).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn unchanged_snapshots_and_empty_deltas_do_not_tick() {
+        let deltas = run(&snapshots(vec![vec![(1, 10)], vec![(1, 10)], vec![(1, 12)]]).diff_map());
+        assert_eq!(
+            vec![NanoTime::new(0), NanoTime::new(2)],
+            deltas.iter().map(|d| d.time).collect::<Vec<_>>()
+        );
+
+        let mut empty = CallBackStream::<MapDelta<u32, i64>>::new();
+        empty.push(ValueAt::new(MapDelta::default(), NanoTime::new(0)));
+        let applied = run(&empty.into_stream().apply_deltas(HashMap::from([(1, 1)])));
+        assert!(applied.is_empty());
+    }
+
+    #[test]
+    fn diff_then_apply_round_trips_random_snapshots() {
+        // xorshift64: deterministic, no extra dev-dependency.
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move |bound: u64| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed % bound
+        };
+        let mut maps = Vec::new();
+        let mut map = HashMap::new();
+        for _ in 0..200 {
+            for _ in 0..next(4) {
+                match next(3) {
+                    0 => {
+                        map.remove(&(next(16) as u32));
+                    }
+                    _ => {
+                        map.insert(next(16) as u32, next(5) as i64);
+                    }
+                }
+            }
+            maps.push(map.clone().into_iter().collect::<Vec<_>>());
+        }
+
+        // The snapshots diff_map ticks on: those that differ from their
+        // predecessor, with the first compared against an empty map.
+        let mut expected = Vec::new();
+        let mut previous = HashMap::new();
+        for (i, map) in maps.iter().enumerate() {
+            let map: HashMap<u32, i64> = map.iter().copied().collect();
+            if map != previous {
+                expected.push(ValueAt::new(map.clone(), NanoTime::new(i as u64)));
+            }
+            previous = map;
+        }
+
+        let rebuilt = run(&snapshots(maps).diff_map().apply_deltas(HashMap::new()));
+        let rebuilt: Vec<_> = rebuilt
+            .into_iter()
+            .map(|v| ValueAt::new((*v.value).clone(), v.time))
+            .collect();
+        assert!(expected.len() > 100);
+        assert_eq!(expected, rebuilt);
+    }
+}
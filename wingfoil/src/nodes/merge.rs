@@ -43,6 +43,36 @@ impl<T: Element> MutableNode for MergeStream<T> {
     }
 }
 
+/// Emits the value of whichever upstream `selector` picks, when that
+/// upstream ticks.  `selector` is passive, so switching takes effect on the
+/// selected stream's next tick.  Used by [switch_on](crate::nodes::switch_on).
+#[derive(new)]
+pub struct SwitchStream<T: Element> {
+    selector: Rc<dyn Stream<usize>>,
+    streams: Vec<Rc<dyn Stream<T>>>,
+    #[new(default)]
+    value: T,
+}
+
+#[node(active = [streams], passive = [selector], output = value: T)]
+impl<T: Element> MutableNode for SwitchStream<T> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        let selected = self.selector.peek_value();
+        let stream = self.streams.get(selected).ok_or_else(|| {
+            anyhow::anyhow!(
+                "switch_on: selector {selected} out of range for {} streams",
+                self.streams.len()
+            )
+        })?;
+        if state.ticked(stream.clone().as_node()) {
+            self.value = stream.peek_value();
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::graph::*;
@@ -92,4 +122,39 @@ mod tests {
         let values: Vec<u64> = merged.peek_value().iter().map(|v| v.value).collect();
         assert_eq!(values, vec![1, 2, 3]);
     }
+
+    #[test]
+    fn switch_on_tracks_selected_stream() {
+        // Selects `fast` for the first two ticks, then `slow`, which ticks
+        // every other cycle.
+        let selector = ticker(Duration::from_nanos(100))
+            .count()
+            .map(|n| usize::from(n > 2));
+        let fast = ticker(Duration::from_nanos(100)).count();
+        let slow = ticker(Duration::from_nanos(200))
+            .count()
+            .map(|x: u64| x * 100);
+        switch_on(selector, vec![fast, slow])
+            .assert_eq(vec![
+                ValueAt::new(1, NanoTime::new(0)),
+                ValueAt::new(2, NanoTime::new(100)),
+                ValueAt::new(200, NanoTime::new(200)),
+                ValueAt::new(300, NanoTime::new(400)),
+            ])
+            .run(
+                RunMode::HistoricalFrom(NanoTime::ZERO),
+                RunFor::Duration(Duration::from_nanos(400)),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn switch_on_rejects_out_of_range_selector() {
+        let selector = constant(2usize);
+        let src = ticker(Duration::from_nanos(100)).count();
+        let err = switch_on(selector, vec![src])
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(1))
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("selector 2 out of range for 1 streams"));
+    }
 }
@@ -0,0 +1,162 @@
+use crate::types::*;
+
+use arc_swap::ArcSwapOption;
+use std::rc::Rc;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// A read-only view of a stream's latest value, for threads outside the
+/// graph, e.g. a monitoring or REST thread.  Cheap to clone and share.
+/// Returned by [mirror](crate::nodes::StreamOperators::mirror).
+///
+/// The graph publishes each tick with an atomic pointer swap, so
+/// [latest](Mirror::latest) never blocks the graph thread and the graph never
+/// waits for readers.  Async consumers can await changes with
+/// [subscribe_changes](Mirror::subscribe_changes); hold its borrows briefly,
+/// as the graph takes the watch channel's lock to publish.
+pub struct Mirror<T> {
+    slot: Arc<ArcSwapOption<(NanoTime, T)>>,
+    changes: Arc<watch::Sender<(NanoTime, T)>>,
+}
+
+impl<T> Clone for Mirror<T> {
+    fn clone(&self) -> Self {
+        Self {
+            slot: self.slot.clone(),
+            changes: self.changes.clone(),
+        }
+    }
+}
+
+impl<T: Element + Send + Sync> Mirror<T> {
+    fn new() -> Self {
+        let (changes, _) = watch::channel((NanoTime::ZERO, T::default()));
+        Self {
+            slot: Arc::new(ArcSwapOption::empty()),
+            changes: Arc::new(changes),
+        }
+    }
+
+    /// The most recently published value and the engine time it ticked at,
+    /// or `None` before the stream first ticks.
+    pub fn latest(&self) -> Option<(NanoTime, T)> {
+        self.slot.load_full().map(|latest| (*latest).clone())
+    }
+
+    /// A receiver that is notified of each published value.  Before the
+    /// first tick it holds `(NanoTime::ZERO, T::default())`.
+    pub fn subscribe_changes(&self) -> watch::Receiver<(NanoTime, T)> {
+        self.changes.subscribe()
+    }
+
+    fn publish(&self, time: NanoTime, value: T) {
+        self.slot.store(Some(Arc::new((time, value.clone()))));
+        self.changes.send_replace((time, value));
+    }
+}
+
+/// Publishes each tick of its upstream to a [Mirror].  Used by
+/// [mirror](crate::nodes::StreamOperators::mirror).
+pub(crate) struct MirrorNode<T: Element> {
+    upstream: Rc<dyn Stream<T>>,
+    mirror: Mirror<T>,
+}
+
+#[node(active = [upstream])]
+impl<T: Element + Send + Sync> MutableNode for MirrorNode<T> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        self.mirror
+            .publish(state.time(), self.upstream.peek_value());
+        Ok(false)
+    }
+}
+
+pub(crate) fn mirror<T: Element + Send + Sync>(
+    upstream: Rc<dyn Stream<T>>,
+) -> (Rc<dyn Node>, Mirror<T>) {
+    let mirror = Mirror::new();
+    let node = MirrorNode {
+        upstream,
+        mirror: mirror.clone(),
+    };
+    (node.into_node(), mirror)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::*;
+    use crate::nodes::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+
+    /// Runs a realtime graph mirroring a fast count while another thread
+    /// polls `latest`, returning what the reader saw.
+    fn poll_while_running(run_for: Duration) -> Vec<(NanoTime, u64)> {
+        let (node, mirror) = ticker(Duration::from_micros(50)).count().mirror();
+        let done = Arc::new(AtomicBool::new(false));
+        let reader = {
+            let done = done.clone();
+            std::thread::spawn(move || {
+                let mut seen = Vec::new();
+                while !done.load(Ordering::Acquire) {
+                    if let Some(latest) = mirror.latest() {
+                        seen.push(latest);
+                    }
+                }
+                seen.extend(mirror.latest());
+                seen
+            })
+        };
+        node.run(RunMode::RealTime, RunFor::Duration(run_for))
+            .unwrap();
+        done.store(true, Ordering::Release);
+        reader.join().unwrap()
+    }
+
+    #[test]
+    fn mirror_reader_sees_increasing_counts() {
+        for _ in 0..20 {
+            let seen = poll_while_running(Duration::from_millis(20));
+            assert!(!seen.is_empty());
+            assert!(seen.windows(2).all(|w| w[0].1 <= w[1].1), "{seen:?}");
+            assert!(seen.windows(2).all(|w| w[0].0 <= w[1].0), "{seen:?}");
+            assert!(seen.last().unwrap().1 > 1);
+        }
+    }
+
+    #[test]
+    fn mirror_is_empty_until_first_tick() {
+        let (node, mirror) = ticker(Duration::from_nanos(100)).count().mirror();
+        assert_eq!(mirror.latest(), None);
+        node.run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+            .unwrap();
+        assert_eq!(mirror.latest(), Some((NanoTime::new(200), 3)));
+    }
+
+    #[test]
+    fn mirror_subscribe_changes_wakes_async_consumer() {
+        let (node, mirror) = ticker(Duration::from_micros(100)).count().mirror();
+        let mut changes = mirror.subscribe_changes();
+        let consumer = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap();
+            runtime.block_on(async {
+                loop {
+                    changes.changed().await.unwrap();
+                    let (_, count) = *changes.borrow_and_update();
+                    if count >= 10 {
+                        return count;
+                    }
+                }
+            })
+        });
+        node.run(
+            RunMode::RealTime,
+            RunFor::Duration(Duration::from_millis(50)),
+        )
+        .unwrap();
+        assert!(consumer.join().unwrap() >= 10);
+    }
+}
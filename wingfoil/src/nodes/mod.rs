@@ -1,17 +1,24 @@
 //! A library of Stream and Node operators and functions.
 //!
 
+mod alert;
 mod always;
+#[cfg(any(test, feature = "test-utils"))]
+mod assert;
 #[cfg(feature = "async")]
 mod async_io;
+mod average;
 mod bimap;
 mod buffer;
+mod burst;
 mod callback;
 #[cfg(feature = "async")]
 mod channel;
 mod combine;
 mod constant;
 mod consumer;
+#[cfg(feature = "decimal")]
+mod decimal;
 mod delay;
 mod delay_with_reset;
 mod demux;
@@ -26,47 +33,100 @@ mod fold;
 #[cfg(feature = "async")]
 mod graph_node;
 mod graph_state;
+mod group_by;
 mod inspect;
 mod iterator_stream;
+mod join;
+mod lag;
 mod limit;
+mod logged;
 mod map;
+mod map_diff;
 mod map_filter;
 mod merge;
+#[cfg(feature = "async")]
+mod mirror;
+mod monotonic;
 mod never;
 mod node_flow;
+mod non_finite;
+mod partition;
+#[cfg(feature = "async")]
+mod pipe;
 mod print;
 mod producer;
-// `ReceiverStream` is only consumed by the zmq and aeron adapters; gate the
-// module on them so the default build doesn't flag it as dead code.
-#[cfg(any(feature = "zmq", feature = "aeron", feature = "aeron-rs"))]
+// `ReceiverStream` is only consumed by the zmq, aeron and shmem adapters; gate
+// the module on them so the default build doesn't flag it as dead code.
+#[cfg(any(
+    feature = "zmq",
+    feature = "aeron",
+    feature = "aeron-rs",
+    feature = "shmem"
+))]
 pub(crate) mod receiver;
+mod retry;
 mod sample;
+mod skip_if_behind;
+mod snapshot;
+mod source_fn;
+mod split_result;
+mod summary;
 mod throttle;
 mod tick;
 mod timed;
+#[cfg(feature = "tracing")]
+mod trace;
 mod trimap;
 mod try_bimap;
 mod try_map;
 mod try_trimap;
+mod tumbling;
 mod window;
 mod with_time;
 
+pub use alert::{Alert, AlertStreamOperators, Severity, alerts_merge};
 pub use always::*;
+#[cfg(any(test, feature = "test-utils"))]
+pub use assert::AssertStreamOperators;
 #[cfg(feature = "async")]
 pub use async_io::*;
+pub use average::EmptyWindowPolicy;
+pub use burst::BurstStreamOperators;
 pub use callback::CallBackStream;
 pub use channel::ChannelReceiverStream;
+#[cfg(feature = "decimal")]
+pub use decimal::{Decimal, DecimalStreamOperators, RoundingStrategy};
 pub use demux::*;
 #[cfg(feature = "dynamic-graph")]
 pub use dynamic_group::*;
 use feedback::FeedbackSendStream;
+use feedback::feedback_init;
 pub use feedback::{FeedbackSink, feedback, feedback_node};
 #[cfg(feature = "async")]
 pub use graph_node::*;
-pub use iterator_stream::{IteratorStream, SimpleIteratorStream, TryIteratorStream};
+pub use iterator_stream::{
+    ChainIteratorStream, IteratorStream, MergeOrderedStream, SimpleIteratorStream,
+    TryIteratorStream,
+};
+pub use map_diff::{MapDelta, MapDeltaStreamOperators, MapSnapshotStreamOperators};
 pub use map_filter::MapFilterStream;
+#[cfg(feature = "async")]
+pub use mirror::Mirror;
 pub use never::*;
-
+pub use node_flow::RateLimitPolicy;
+pub use non_finite::NonFinitePolicy;
+#[cfg(feature = "async")]
+pub use pipe::*;
+pub use retry::ExponentialBackoff;
+pub use snapshot::replay;
+pub use source_fn::{Reschedule, Scheduler};
+pub use summary::{ResultsOperators, Summary, SummaryHandle};
+pub use tick::Schedule;
+#[cfg(feature = "tracing")]
+pub use trace::{DEFAULT_TRACE_CAPACITY, Trace, TraceNode};
+
+use alert::AlertWhenStream;
+use average::{AverageWindow, MovingAverageStream};
 use bimap::*;
 use buffer::BufferStream;
 use constant::*;
@@ -79,14 +139,27 @@ use filter::*;
 use finally::*;
 use fold::*;
 use graph_state::*;
+use group_by::{ReduceByKeyStream, ReduceByKeyUpdatesStream};
 use inspect::*;
+use join::AsofJoinStream;
+use lag::{LagStream, LagTicksStream, LeadTicksStream};
 use limit::*;
+use logged::LoggedStream;
 use map::*;
 use merge::*;
+use monotonic::MonotonicStream;
 use node_flow::*;
-use print::*;
+use non_finite::RejectNonFiniteStream;
+use print::PrintStream;
+use print::json_log_line;
+pub use print::{DEFAULT_MAX_LEN, debug_truncated, truncate};
 use producer::*;
+use retry::ExponentialBackoffStream;
 use sample::*;
+use skip_if_behind::SkipIfBehindStream;
+use snapshot::write_collected;
+use source_fn::SourceFnStream;
+use split_result::ResultBranchStream;
 use throttle::*;
 use tick::*;
 use timed::*;
@@ -94,20 +167,25 @@ use trimap::*;
 use try_bimap::*;
 use try_map::*;
 use try_trimap::*;
+pub use tumbling::TumblingEmpty;
+use tumbling::TumblingStream;
 use window::WindowStream;
-use with_time::WithTimeStream;
+use with_time::{TimeSinceLastStream, WithTimeStream};
 
+use crate::adapters::statistics::StatisticsOperators;
+use crate::bencher::BenchResult;
 use crate::graph::*;
-use crate::queue::ValueAt;
+use crate::queue::{TimedValueAt, ValueAt};
 use crate::types::*;
 
 #[cfg(feature = "zmq")]
 pub(crate) use receiver::*;
 
+pub use itertools::Either;
+
 use log::Level;
-#[cfg(not(feature = "tracing"))]
-use log::log;
 use std::cmp::Eq;
+use std::collections::HashMap;
 #[cfg(feature = "async")]
 use std::future::Future;
 use std::hash::Hash;
@@ -180,6 +258,27 @@ pub fn try_trimap<IN1: Element, IN2: Element, IN3: Element, OUT: Element>(
     TryTriMapStream::new(upstream1, upstream2, upstream3, Box::new(func)).into_stream()
 }
 
+/// Returns a stream that plays back each of `streams` in turn, e.g. one
+/// historical data source per day.  Fails the graph if the streams overlap in
+/// time: an earlier stream ticking after a later one has started, or two
+/// ticking at the same time.
+#[must_use]
+pub fn chain_streams<T: Element>(streams: Vec<Rc<dyn Stream<T>>>) -> Rc<dyn Stream<T>> {
+    ChainIteratorStream::new(streams).into_stream()
+}
+
+/// Merges many historical sources, each ascending in time, into one stream
+/// that ticks a [Burst] of every item due at each time, e.g. one csv file per
+/// instrument.  Unlike [merge] of one stream per source, the sources are read
+/// inside a single node, so the graph schedules one callback per timestamp
+/// rather than one per source.  An `Err` item fails the graph when reached.
+#[must_use]
+pub fn merge_ordered<T: Element>(
+    sources: Vec<Box<dyn Iterator<Item = anyhow::Result<ValueAt<T>>>>>,
+) -> Rc<dyn Stream<Burst<T>>> {
+    MergeOrderedStream::new(sources).into_stream()
+}
+
 /// Returns a stream that merges it's sources into one.  Ticks when either of it's sources ticks.
 /// If more than one source ticks at the same time, the first one that was supplied is used.
 #[must_use]
@@ -190,6 +289,18 @@ where
     MergeStream::new(sources).into_stream()
 }
 
+/// Returns a stream that emits the value of `streams[selector]` whenever
+/// that stream ticks, e.g. to switch from simulated to live market data at
+/// runtime.  `selector` is passive: changing it does not tick the result.
+/// Fails the graph if `selector` is out of range.
+#[must_use]
+pub fn switch_on<T: Element>(
+    selector: Rc<dyn Stream<usize>>,
+    streams: Vec<Rc<dyn Stream<T>>>,
+) -> Rc<dyn Stream<T>> {
+    SwitchStream::new(selector, streams).into_stream()
+}
+
 /// Returns a stream that ticks once with the specified value, on the first cycle.
 #[must_use]
 pub fn constant<T: Element>(value: T) -> Rc<dyn Stream<T>> {
@@ -205,10 +316,166 @@ where
     combine::combine(streams)
 }
 
+/// Combines keyed [Stream]s into a map of each key's latest value.  Ticks
+/// whenever any source ticks; keys whose stream has not yet ticked are
+/// absent.  Unlike [combine], a value's origin doesn't depend on which
+/// sources ticked together.
+/// ```
+/// # use wingfoil::*;
+/// # use std::time::Duration;
+/// let count = ticker(Duration::from_millis(10)).count();
+/// let quotes = combine_keyed(vec![
+///     ("bid", count.map(|n| n as f64 - 0.5)),
+///     ("ask", count.map(|n| n as f64 + 0.5)),
+/// ]);
+/// ```
+#[must_use]
+pub fn combine_keyed<K, T>(pairs: Vec<(K, Rc<dyn Stream<T>>)>) -> Rc<dyn Stream<Rc<HashMap<K, T>>>>
+where
+    K: Element + Hash + Eq,
+    T: Element,
+{
+    combine::combine_keyed(pairs)
+}
+
+/// Zips [Stream]s in lockstep: the nth tick holds the nth value of every
+/// source, emitted once the slowest has produced it.  Unlike [combine],
+/// which takes whatever ticked, no value is skipped or repeated; values of
+/// faster sources queue until the slowest catches up, so the rates should
+/// match on average.
+/// ```
+/// # use wingfoil::*;
+/// # use std::time::Duration;
+/// let legs = (1..=3)
+///     .map(|i| ticker(Duration::from_millis(i)).count())
+///     .collect();
+/// let rows = zip_n(legs); // ticks every 3ms with [n, n, n]
+/// ```
+#[must_use]
+pub fn zip_n<T: Element>(streams: Vec<Rc<dyn Stream<T>>>) -> Rc<dyn Stream<Vec<T>>> {
+    combine::zip_n(streams)
+}
+
 /// Returns a [Node] that ticks with the specified period.
 #[must_use]
 pub fn ticker(period: Duration) -> Rc<dyn Node> {
-    TickNode::new(NanoTime::new(period.as_nanos() as u64)).into_node()
+    TickNode::new(Schedule::Fixed(period)).into_node()
+}
+
+/// Returns a [Node] that ticks at start time and then after each gap of
+/// `schedule`, e.g. backing off geometrically while polling.
+/// ```
+/// # use wingfoil::*;
+/// # use std::time::Duration;
+/// ticker_schedule(Schedule::Geometric {
+///     initial: Duration::from_millis(10),
+///     factor: 2.0,
+///     max: Duration::from_secs(1),
+/// })
+/// .count();
+/// ```
+#[must_use]
+pub fn ticker_schedule(schedule: Schedule) -> Rc<dyn Node> {
+    TickNode::new(schedule).into_node()
+}
+
+/// What an operator such as [sample_trigger](StreamOperators::sample_trigger)
+/// ticks on: a fixed period, or any [Node].  Converts into an `Rc<dyn Node>`
+/// for operators that take one, e.g.
+/// [delay_with_reset](StreamOperators::delay_with_reset).
+#[derive(Clone)]
+pub enum Trigger {
+    /// Ticks every period, as a [ticker].
+    Period(Duration),
+    /// Ticks whenever the node does.
+    Node(Rc<dyn Node>),
+}
+
+impl From<Trigger> for Rc<dyn Node> {
+    fn from(trigger: Trigger) -> Self {
+        match trigger {
+            Trigger::Period(period) => ticker(period),
+            Trigger::Node(node) => node,
+        }
+    }
+}
+
+impl From<Duration> for Trigger {
+    fn from(period: Duration) -> Self {
+        Trigger::Period(period)
+    }
+}
+
+impl From<Rc<dyn Node>> for Trigger {
+    fn from(node: Rc<dyn Node>) -> Self {
+        Trigger::Node(node)
+    }
+}
+
+/// Returns a [Stream] of the values `produce` returns when called at the
+/// times requested by `schedule`, which is called once as the graph starts.
+/// It ticks when `produce` returns Some.  This is the recommended way to
+/// write a source such as a calendar, synthetic data or a polling wrapper,
+/// without implementing [MutableNode].  `produce` can ask for further calls
+/// through a [Reschedule] handle.
+/// ```
+/// # use wingfoil::*;
+/// # use std::time::Duration;
+/// // a price every second through a session, and one at its close
+/// let open = NanoTime::from(Duration::from_secs(9 * 3600));
+/// let close = NanoTime::from(Duration::from_secs(16 * 3600));
+/// let mut price = 100.0;
+/// let prices = source_fn(
+///     move |s| {
+///         s.every_between(Duration::from_secs(1), open, close).at(close);
+///     },
+///     move |_time| {
+///         price *= 1.0001;
+///         Some(price)
+///     },
+/// );
+/// // a poller that backs off while there is nothing new
+/// let wake = Reschedule::new();
+/// let mut gap = Duration::from_millis(10);
+/// let polled = source_fn(
+///     {
+///         let wake = wake.clone();
+///         move |s| _ = s.at(s.start_time()).rescheduled_by(&wake)
+///     },
+///     move |time| {
+///         gap = (gap * 2).min(Duration::from_secs(1));
+///         wake.at(time + gap);
+///         None::<u64>
+///     },
+/// );
+/// prices
+///     .run(RunMode::HistoricalFrom(open), RunFor::Duration(Duration::from_secs(10)))
+///     .unwrap();
+/// ```
+#[must_use]
+pub fn source_fn<T: Element>(
+    schedule: impl Fn(&mut Scheduler) + 'static,
+    produce: impl FnMut(NanoTime) -> Option<T> + 'static,
+) -> Rc<dyn Stream<T>> {
+    SourceFnStream::new(Box::new(schedule), Box::new(produce)).into_stream()
+}
+
+/// Returns a [Node] that ticks every `initial` until `control` ticks, then at
+/// the latest period received on `control`.  Periods are measured from the
+/// previous tick, so shortening the period can pull the next tick in (firing
+/// immediately if it is already overdue).  If `control` ticks several times
+/// between ticks the last value wins.  A zero period is an error.
+/// ```
+/// # use wingfoil::*;
+/// # use std::time::Duration;
+/// let period = ticker(Duration::from_secs(1))
+///     .count()
+///     .map(|n| Duration::from_millis(100 * n));
+/// adaptive_ticker(Duration::from_millis(100), period).count();
+/// ```
+#[must_use]
+pub fn adaptive_ticker(initial: Duration, control: Rc<dyn Stream<Duration>>) -> Rc<dyn Node> {
+    AdaptiveTickNode::new(NanoTime::new(initial.as_nanos() as u64), control).into_node()
 }
 
 /// A trait containing operators that can be applied to [Node]s.
@@ -244,6 +511,34 @@ pub trait NodeOperators {
     #[must_use]
     fn ticked_at_elapsed(self: &Rc<Self>) -> Rc<dyn Stream<NanoTime>>;
 
+    /// Emits the value time of source ticks: when the value was originally
+    /// produced, which for values received from another graph, a delay or a
+    /// feedback channel can be earlier than [ticked_at](NodeOperators::ticked_at).
+    /// Equal to `ticked_at` for sources that record no value time.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// // 0, 100, 200, etc. while ticked_at is 50, 150, 250, etc.
+    /// ticker(Duration::from_nanos(100))
+    ///     .count()
+    ///     .lag(Duration::from_nanos(50))
+    ///     .value_time();
+    /// ```
+    #[must_use]
+    fn value_time(self: &Rc<Self>) -> Rc<dyn Stream<NanoTime>>;
+
+    /// Emits the start of the `period`-long time bucket that source ticks
+    /// fall in, floored from the unix epoch, when it changes.  A key for
+    /// grouping ticks into bars, e.g. for OHLC or VWAP.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// // 0, 1000, 2000, etc. while the source ticks every 300ns
+    /// ticker(Duration::from_nanos(300)).time_bucket(Duration::from_nanos(1_000));
+    /// ```
+    #[must_use]
+    fn time_bucket(self: &Rc<Self>, period: Duration) -> Rc<dyn Stream<NanoTime>>;
+
     /// Emits the result of supplied closure on each upstream tick.
     /// ```
     /// # use wingfoil::*;
@@ -254,6 +549,21 @@ pub trait NodeOperators {
     #[must_use]
     fn produce<T: Element>(self: &Rc<Self>, func: impl Fn() -> T + 'static) -> Rc<dyn Stream<T>>;
 
+    /// Like [produce](NodeOperators::produce) but for a closure that can
+    /// fail.  An `Err` stops the graph and is returned from the run, with
+    /// the engine time of the failure as context.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// ticker(Duration::from_millis(10))
+    ///     .produce_result(|| Ok(std::fs::metadata(".")?.len()));
+    /// ```
+    #[must_use]
+    fn produce_result<T: Element>(
+        self: &Rc<Self>,
+        func: impl Fn() -> anyhow::Result<T> + 'static,
+    ) -> Rc<dyn Stream<T>>;
+
     /// Shortcut for [Graph::run] i.e. initialise and execute the graph.
     /// ```
     /// # use wingfoil::*;
@@ -265,6 +575,15 @@ pub trait NodeOperators {
     /// count.peek_value(); // 3
     /// ```
     fn run(self: &Rc<Self>, run_mode: RunMode, run_to: RunFor) -> anyhow::Result<()>;
+    /// Shortcut for [bench](crate::bench): times `n_iters` engine cycles of
+    /// the graph rooted here.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let result = ticker(Duration::from_nanos(1)).count().bench(1_000).unwrap();
+    /// println!("{result}");
+    /// ```
+    fn bench(self: &Rc<Self>, n_iters: u32) -> anyhow::Result<BenchResult>;
     fn into_graph(self: &Rc<Self>, run_mode: RunMode, run_for: RunFor) -> Graph;
 }
 
@@ -283,12 +602,37 @@ impl NodeOperators for dyn Node {
         let f = Box::new(|state: &mut GraphState| state.elapsed());
         GraphStateStream::new(self.clone(), f).into_stream()
     }
+    fn value_time(self: &Rc<Self>) -> Rc<dyn Stream<NanoTime>> {
+        let node = self.clone();
+        let f = Box::new(move |state: &mut GraphState| state.value_time(node.clone()));
+        GraphStateStream::new(self.clone(), f).into_stream()
+    }
+    fn time_bucket(self: &Rc<Self>, period: Duration) -> Rc<dyn Stream<NanoTime>> {
+        let period = period.as_nanos() as u64;
+        assert!(period > 0, "time_bucket period must be positive");
+        let f = Box::new(move |state: &mut GraphState| {
+            let time = u64::from(state.time());
+            NanoTime::new(time - time % period)
+        });
+        GraphStateStream::new(self.clone(), f)
+            .into_stream()
+            .distinct()
+    }
     fn produce<T: Element>(self: &Rc<Self>, func: impl Fn() -> T + 'static) -> Rc<dyn Stream<T>> {
         ProducerStream::new(self.clone(), Box::new(func)).into_stream()
     }
+    fn produce_result<T: Element>(
+        self: &Rc<Self>,
+        func: impl Fn() -> anyhow::Result<T> + 'static,
+    ) -> Rc<dyn Stream<T>> {
+        TryProducerStream::new(self.clone(), Box::new(func)).into_stream()
+    }
     fn run(self: &Rc<Self>, run_mode: RunMode, run_for: RunFor) -> anyhow::Result<()> {
         Graph::new(vec![self.clone()], run_mode, run_for).run()
     }
+    fn bench(self: &Rc<Self>, n_iters: u32) -> anyhow::Result<BenchResult> {
+        crate::bench(vec![self.clone()], n_iters)
+    }
     fn into_graph(self: &Rc<Self>, run_mode: RunMode, run_for: RunFor) -> Graph {
         Graph::new(vec![self.clone()], run_mode, run_for)
     }
@@ -304,15 +648,30 @@ impl<T> NodeOperators for dyn Stream<T> {
     fn ticked_at_elapsed(self: &Rc<Self>) -> Rc<dyn Stream<NanoTime>> {
         self.clone().as_node().ticked_at_elapsed()
     }
+    fn value_time(self: &Rc<Self>) -> Rc<dyn Stream<NanoTime>> {
+        self.clone().as_node().value_time()
+    }
+    fn time_bucket(self: &Rc<Self>, period: Duration) -> Rc<dyn Stream<NanoTime>> {
+        self.clone().as_node().time_bucket(period)
+    }
     fn produce<OUT: Element>(
         self: &Rc<Self>,
         func: impl Fn() -> OUT + 'static,
     ) -> Rc<dyn Stream<OUT>> {
         self.clone().as_node().produce(func)
     }
+    fn produce_result<OUT: Element>(
+        self: &Rc<Self>,
+        func: impl Fn() -> anyhow::Result<OUT> + 'static,
+    ) -> Rc<dyn Stream<OUT>> {
+        self.clone().as_node().produce_result(func)
+    }
     fn run(self: &Rc<Self>, run_mode: RunMode, run_for: RunFor) -> anyhow::Result<()> {
         self.clone().as_node().run(run_mode, run_for)
     }
+    fn bench(self: &Rc<Self>, n_iters: u32) -> anyhow::Result<BenchResult> {
+        self.clone().as_node().bench(n_iters)
+    }
     fn into_graph(self: &Rc<Self>, run_mode: RunMode, run_for: RunFor) -> Graph {
         self.clone().as_node().into_graph(run_mode, run_for)
     }
@@ -330,6 +689,37 @@ pub trait NodeFlowOperators {
     /// Propagates at most `limit` ticks from upstream.
     #[must_use]
     fn limit(self: &Rc<Self>, limit: u32) -> Rc<dyn Node>;
+    /// Token-bucket rate limiter: passes upstream ticks while tokens
+    /// remain, refilling at `tokens_per_sec` up to `burst` tokens.  The
+    /// bucket starts full.  Excess ticks are dropped or queued per `policy`.
+    /// To rate limit the values of a stream with [RateLimitPolicy::Drop],
+    /// sample it on the limited node:
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let orders = ticker(Duration::from_millis(1)).count();
+    /// let allowed = orders.clone().as_node().rate_limit(100.0, 10, RateLimitPolicy::Drop);
+    /// let throttled_orders = orders.sample(allowed);
+    /// ```
+    #[must_use]
+    fn rate_limit(
+        self: &Rc<Self>,
+        tokens_per_sec: f64,
+        burst: u32,
+        policy: RateLimitPolicy,
+    ) -> Rc<dyn Node>;
+    /// Passes upstream ticks, and also ticks after each `interval` without
+    /// one, to keep downstream alive through quiet periods.  Reset by real
+    /// ticks.  To re-emit the last value of a stream as its heartbeat,
+    /// sample it:
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let quotes = ticker(Duration::from_secs(60)).count();
+    /// let kept_alive = quotes.sample(quotes.clone().as_node().heartbeat(Duration::from_secs(5)));
+    /// ```
+    #[must_use]
+    fn heartbeat(self: &Rc<Self>, interval: Duration) -> Rc<dyn Node>;
     /// Drops upstream ticks when `condition` is false.
     #[must_use]
     fn filter(self: &Rc<Self>, condition: Rc<dyn Stream<bool>>) -> Rc<dyn Node>;
@@ -348,6 +738,25 @@ impl NodeFlowOperators for dyn Node {
     fn limit(self: &Rc<Self>, limit: u32) -> Rc<dyn Node> {
         LimitNode::new(self.clone(), limit).into_node()
     }
+    fn rate_limit(
+        self: &Rc<Self>,
+        tokens_per_sec: f64,
+        burst: u32,
+        policy: RateLimitPolicy,
+    ) -> Rc<dyn Node> {
+        assert!(
+            tokens_per_sec > 0.0 && burst > 0,
+            "rate_limit requires a positive rate and a burst of at least 1"
+        );
+        RateLimitNode::new(self.clone(), tokens_per_sec, burst, policy).into_node()
+    }
+    fn heartbeat(self: &Rc<Self>, interval: Duration) -> Rc<dyn Node> {
+        assert!(
+            !interval.is_zero(),
+            "heartbeat requires a non-zero interval"
+        );
+        HeartbeatNode::new(self.clone(), NanoTime::new(interval.as_nanos() as u64)).into_node()
+    }
     fn filter(self: &Rc<Self>, condition: Rc<dyn Stream<bool>>) -> Rc<dyn Node> {
         FilterNode::new(self.clone(), condition).into_node()
     }
@@ -362,16 +771,86 @@ pub trait StreamOperators<T: Element> {
     /// accumulate the source into a vector
     #[must_use]
     fn accumulate(self: &Rc<Self>) -> Rc<dyn Stream<Vec<T>>>;
+    /// Like [accumulate](StreamOperators::accumulate) but keeps only the
+    /// last `max` values, so memory stays bounded on long runs.  Emits the
+    /// current window, oldest first, on each tick.
+    #[must_use]
+    fn accumulate_bounded(self: &Rc<Self>, max: usize) -> Rc<dyn Stream<Vec<T>>>;
+    /// Emits an [Alert] labelled `source`, with the given severity and a
+    /// message built by `message`, for each value matching `predicate`.
+    /// The value is attached as the alert's payload.  Route the alerts with
+    /// [route_alerts](AlertStreamOperators::route_alerts).
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// ticker(Duration::from_millis(10))
+    ///     .count()
+    ///     .alert_when("count", |n| *n > 100, Severity::Critical, |n| format!("{n} too big"))
+    ///     .route_alerts(Severity::Warning, Severity::Critical);
+    /// ```
+    #[must_use]
+    fn alert_when(
+        self: &Rc<Self>,
+        source: &str,
+        predicate: impl Fn(&T) -> bool + 'static,
+        severity: Severity,
+        message: impl Fn(&T) -> String + 'static,
+    ) -> Rc<dyn Stream<Alert>>;
     /// Buffer the source stream.  The buffer is automatically flushed on the last cycle;
     #[must_use]
     fn buffer(self: &Rc<Self>, capacity: usize) -> Rc<dyn Stream<Vec<T>>>;
     /// Buffer the source stream based on time interval. The window is automatically flushed when the interval is exceeded or on the last cycle.
     #[must_use]
     fn window(self: &Rc<Self>, interval: Duration) -> Rc<dyn Stream<Vec<T>>>;
+    /// Collects the source into non-overlapping windows `[k * window,
+    /// (k + 1) * window)` of engine time, each emitted when it closes, at
+    /// its end time.  A window still open on the last cycle is flushed
+    /// then, unless it opened on that cycle as the previous one closed.
+    /// `empty` says whether windows without values are emitted.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// // [1, 2, 3, 4] at 100ns, [5, 6, 7] at 200ns, etc.
+    /// ticker(Duration::from_nanos(30))
+    ///     .count()
+    ///     .tumbling(Duration::from_nanos(100), TumblingEmpty::Skip);
+    /// ```
+    #[must_use]
+    fn tumbling(self: &Rc<Self>, window: Duration, empty: TumblingEmpty) -> Rc<dyn Stream<Vec<T>>>;
+    /// Like [tumbling](StreamOperators::tumbling) but folds each window's
+    /// values into a copy of `init` with `func` instead of collecting
+    /// them.  Windows without values are skipped.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// // 10 at 100ns, 18 at 200ns, etc.
+    /// ticker(Duration::from_nanos(30))
+    ///     .count()
+    ///     .tumbling_fold(Duration::from_nanos(100), 0, |sum, n| *sum += n);
+    /// ```
+    #[must_use]
+    fn tumbling_fold<A: Element>(
+        self: &Rc<Self>,
+        window: Duration,
+        init: A,
+        func: impl Fn(&mut A, T) + 'static,
+    ) -> Rc<dyn Stream<A>>;
     /// Used to accumulate values, which can be retrieved after
     /// the graph has completed running. Useful for unit tests.
     #[must_use]
     fn collect(self: &Rc<Self>) -> Rc<dyn Stream<Vec<ValueAt<T>>>>;
+    /// Like [collect](StreamOperators::collect) but also records each
+    /// value's [value_time](NodeOperators::value_time), for comparing values
+    /// by when they were produced rather than when this graph saw them.
+    #[must_use]
+    fn collect_with_value_time(self: &Rc<Self>) -> Rc<dyn Stream<Vec<TimedValueAt<T>>>>;
+    /// Collects the stream like [collect](StreamOperators::collect) and, when
+    /// the graph stops, writes it to `path` as JSON for golden-file tests.
+    /// Play the file back with [replay].
+    #[must_use]
+    fn collect_to_file(self: &Rc<Self>, path: &str) -> Rc<dyn Node>
+    where
+        T: serde::Serialize;
     /// collapses a burst (i.e. IntoIter\[T\]) of ticks into a single tick \[T\].
     /// Does not tick if burst is empty.
     #[must_use]
@@ -400,6 +879,32 @@ pub trait StreamOperators<T: Element> {
     /// Like [inspect](StreamOperators::inspect) but for feedback channels.
     #[must_use]
     fn feedback(self: &Rc<Self>, sink: FeedbackSink<T>) -> Rc<dyn Stream<T>>
+    where
+        T: PartialEq;
+    /// Loops each result back through a [feedback] channel: on each tick,
+    /// ticks `step(value, previous)`, where `previous` is the last result, or
+    /// `init` before the first.  Returns the looped stream and the node that
+    /// writes it back, which must also be run, e.g. as a root of the [Graph].
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let count = ticker(Duration::from_nanos(100)).count();
+    /// let (digits, writer) = count.feedback_loop(0, |n, prev| prev * 10 + n);
+    /// // 1, 12, 123, ...
+    /// Graph::new(
+    ///     vec![writer, digits.print().as_node()],
+    ///     RunMode::HistoricalFrom(NanoTime::ZERO),
+    ///     RunFor::Cycles(3),
+    /// )
+    /// .run()
+    /// .unwrap();
+    /// ```
+    #[must_use]
+    fn feedback_loop(
+        self: &Rc<Self>,
+        init: T,
+        step: impl Fn(T, T) -> T + 'static,
+    ) -> (Rc<dyn Stream<T>>, Rc<dyn Node>)
     where
         T: PartialEq;
     /// executes supplied fallible closure on each tick.
@@ -415,6 +920,48 @@ pub trait StreamOperators<T: Element> {
         self: &Rc<Self>,
         func: impl Fn(&mut OUT, T) + 'static,
     ) -> Rc<dyn Stream<OUT>>;
+    /// Like [fold](StreamOperators::fold) but the accumulator starts at
+    /// `init` rather than `OUT::default()`, e.g. 1 for a running product.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// // 1, 2, 6, 24, etc.
+    /// ticker(Duration::from_millis(10))
+    ///     .count()
+    ///     .fold_init(1, |product: &mut u64, n| *product *= n);
+    /// ```
+    #[must_use]
+    fn fold_init<OUT: Element>(
+        self: &Rc<Self>,
+        init: OUT,
+        func: impl Fn(&mut OUT, T) + 'static,
+    ) -> Rc<dyn Stream<OUT>>;
+    /// Keeps a running aggregate per key, combining each value with its
+    /// key's aggregate using `reduce_fn`, and emits a snapshot of every key's
+    /// aggregate on each tick.  The first value for a key becomes its
+    /// aggregate as is.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// // running total per parity: {0: 2 + 4 + .., 1: 1 + 3 + ..}
+    /// ticker(Duration::from_millis(10))
+    ///     .count()
+    ///     .reduce_by_key(|n| n % 2, |total, n| total + n);
+    /// ```
+    #[must_use]
+    fn reduce_by_key<K: Element + Hash + Eq>(
+        self: &Rc<Self>,
+        key_fn: impl Fn(&T) -> K + 'static,
+        reduce_fn: impl Fn(T, T) -> T + 'static,
+    ) -> Rc<dyn Stream<Rc<HashMap<K, T>>>>;
+    /// Like [reduce_by_key](StreamOperators::reduce_by_key), but emits only
+    /// the `(key, aggregate)` updated by each tick.
+    #[must_use]
+    fn reduce_by_key_updates<K: Element + Hash + Eq>(
+        self: &Rc<Self>,
+        key_fn: impl Fn(&T) -> K + 'static,
+        reduce_fn: impl Fn(T, T) -> T + 'static,
+    ) -> Rc<dyn Stream<(K, T)>>;
     /// difference in it's source from one cycle to the next
     #[must_use]
     fn difference(self: &Rc<Self>) -> Rc<dyn Stream<T>>
@@ -426,6 +973,34 @@ pub trait StreamOperators<T: Element> {
     fn delay(self: &Rc<Self>, delay: Duration) -> Rc<dyn Stream<T>>
     where
         T: PartialEq;
+    /// Emits each value `delay` later, like [delay](StreamOperators::delay)
+    /// but without its `PartialEq` bound: every value is kept, including
+    /// equal ones.
+    #[must_use]
+    fn lag(self: &Rc<Self>, delay: Duration) -> Rc<dyn Stream<T>>;
+    /// On each tick, emits the value from `ticks` ticks earlier, which is
+    /// what signal pipelines usually mean by lag.  Silent for the first
+    /// `ticks` ticks.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// // 1, 2, 3, etc. from the third tick on
+    /// ticker(Duration::from_millis(10)).count().lag_ticks(2);
+    /// ```
+    #[must_use]
+    fn lag_ticks(self: &Rc<Self>, ticks: usize) -> Rc<dyn Stream<T>>;
+    /// Pairs each value with the value `ticks` ticks later, e.g. a feature
+    /// with its label for supervised learning.  `(value, later)` is emitted
+    /// when the later value arrives.  Using future values is only meaningful
+    /// in a backtest, so the graph fails to start in [RunMode::RealTime].
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// // (1, 3), (2, 4), etc.
+    /// ticker(Duration::from_millis(10)).count().lead_ticks(2);
+    /// ```
+    #[must_use]
+    fn lead_ticks(self: &Rc<Self>, ticks: usize) -> Rc<dyn Stream<(T, T)>>;
     /// Like [`delay`](StreamOperators::delay) but with a reset trigger.
     /// When the trigger fires, the output snaps to the current upstream value
     /// and the pending queue is cleared.
@@ -446,6 +1021,45 @@ pub trait StreamOperators<T: Element> {
     where
         K: Hash + Eq + PartialEq + std::fmt::Debug + 'static,
         F: Fn(&T) -> (K, DemuxEvent) + 'static;
+    /// Like [demux](StreamOperators::demux), but the overflow stream carries
+    /// each value's key and why it overflowed, see [OverflowEvent].
+    fn demux_with_diagnostics<K, F>(
+        self: &Rc<Self>,
+        capacity: usize,
+        func: F,
+    ) -> (Vec<Rc<dyn Stream<T>>>, Overflow<OverflowEvent<T, K>>)
+    where
+        K: Element + Hash + Eq,
+        F: Fn(&T) -> (K, DemuxEvent) + 'static;
+    /// Like [demux](StreamOperators::demux), but wires `build_child` onto
+    /// each of the `capacity` slots, so every key gets its own processing,
+    /// and [combine]s the children.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// // Running count per symbol, across up to 10 symbols at a time.
+    /// let trades = ticker(Duration::from_millis(10))
+    ///     .count()
+    ///     .map(|n| format!("sym{}", n % 3));
+    /// let (counts, overflow) = trades.demux_map(
+    ///     10,
+    ///     |symbol| (symbol.clone(), DemuxEvent::None),
+    ///     |slot| {
+    ///         let count = slot.clone().as_node().count();
+    ///         bimap(Dep::Active(slot), Dep::Passive(count), |symbol, n| (symbol, n))
+    ///     },
+    /// );
+    /// ```
+    fn demux_map<K, F, OUT>(
+        self: &Rc<Self>,
+        capacity: usize,
+        func: F,
+        build_child: impl Fn(Rc<dyn Stream<T>>) -> Rc<dyn Stream<OUT>>,
+    ) -> (Rc<dyn Stream<Burst<OUT>>>, Overflow<T>)
+    where
+        OUT: Element,
+        K: Hash + Eq + PartialEq + std::fmt::Debug + 'static,
+        F: Fn(&T) -> (K, DemuxEvent) + 'static;
     /// Demuxes its source into a vec of n streams, where source is IntoIterator
     /// For example demuxes Vec of U into n streams of Vec of U
     fn demux_it<K, F, U>(
@@ -470,6 +1084,42 @@ pub trait StreamOperators<T: Element> {
         U: Element,
         K: Hash + Eq + PartialEq + std::fmt::Debug + 'static,
         F: Fn(&U) -> (K, DemuxEvent) + 'static;
+    /// Like [demux_it](StreamOperators::demux_it), but wires `build_child`
+    /// onto each of the `capacity` slots and [combine]s the children, as
+    /// [demux_map](StreamOperators::demux_map) does for
+    /// [demux](StreamOperators::demux).
+    fn demux_it_map<K, F, U, OUT>(
+        self: &Rc<Self>,
+        capacity: usize,
+        func: F,
+        build_child: impl Fn(Rc<dyn Stream<Burst<U>>>) -> Rc<dyn Stream<OUT>>,
+    ) -> (Rc<dyn Stream<Burst<OUT>>>, Overflow<Burst<U>>)
+    where
+        T: IntoIterator<Item = U>,
+        U: Element,
+        OUT: Element,
+        K: Hash + Eq + PartialEq + std::fmt::Debug + 'static,
+        F: Fn(&U) -> (K, DemuxEvent) + 'static;
+    /// Passes its source through unchanged, failing the graph with an error
+    /// naming both values if the key extracted by `key_fn` ever decreases,
+    /// e.g. an out-of-order sequence number in a feed.  Equal keys pass;
+    /// incomparable ones, such as `NaN`, fail.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let err = ticker(Duration::from_nanos(100))
+    ///     .count()
+    ///     .map(|n| if n == 3 { 1 } else { n })
+    ///     .assert_monotonic(|seq| *seq)
+    ///     .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(4))
+    ///     .unwrap_err();
+    /// assert!(format!("{err:#}").contains("key went backwards from 2 to 1"));
+    /// ```
+    #[must_use]
+    fn assert_monotonic<K: PartialOrd + std::fmt::Debug + 'static>(
+        self: &Rc<Self>,
+        key_fn: impl Fn(&T) -> K + 'static,
+    ) -> Rc<dyn Stream<T>>;
     /// only propagates it's source if it is changed
     #[must_use]
     fn distinct(self: &Rc<Self>) -> Rc<dyn Stream<T>>
@@ -490,20 +1140,143 @@ pub trait StreamOperators<T: Element> {
         self: &Rc<Self>,
         func: impl Fn(T) -> Option<OUT> + 'static,
     ) -> Rc<dyn Stream<OUT>>;
+    /// Splits into the values matching `predicate` and those that don't,
+    /// calling it once per tick.  Only the matching side ticks, so this is
+    /// cheaper than a pair of inverted [filter_value](Self::filter_value)s.
+    /// Both sides must be wired into the graph.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::rc::Rc;
+    /// # use std::time::Duration;
+    /// let (evens, odds) = ticker(Duration::from_millis(10))
+    ///     .count()
+    ///     .partition(|n| n % 2 == 0);
+    /// let evens = evens.collect();
+    /// let odds = odds.collect();
+    /// Graph::new(
+    ///     vec![evens.clone().as_node(), odds.clone().as_node()],
+    ///     RunMode::HistoricalFrom(NanoTime::ZERO),
+    ///     RunFor::Cycles(4),
+    /// )
+    /// .run()
+    /// .unwrap();
+    /// let values = |s: &Rc<dyn Stream<Vec<ValueAt<u64>>>>| {
+    ///     s.peek_value().iter().map(|v| v.value).collect::<Vec<_>>()
+    /// };
+    /// assert_eq!(values(&evens), vec![2, 4]);
+    /// assert_eq!(values(&odds), vec![1, 3]);
+    /// ```
+    #[must_use]
+    fn partition(
+        self: &Rc<Self>,
+        predicate: impl Fn(&T) -> bool + 'static,
+    ) -> (Rc<dyn Stream<T>>, Rc<dyn Stream<T>>);
+    /// Like [partition](Self::partition), but `func` maps each value to the
+    /// [Either] side it ticks on, so the sides can differ in type.
+    #[must_use]
+    fn partition_map<A: Element, B: Element>(
+        self: &Rc<Self>,
+        func: impl Fn(T) -> Either<A, B> + 'static,
+    ) -> (Rc<dyn Stream<A>>, Rc<dyn Stream<B>>);
     /// Passes through values unchanged while calling the supplied closure
     /// on a reference to each value, for side effects (debugging, logging, etc.).
     #[must_use]
     fn inspect(self: &Rc<Self>, func: impl Fn(&T) + 'static) -> Rc<dyn Stream<T>>;
+    /// Attaches a side branch, e.g. a writer or logger, built by `func` from
+    /// this stream, and passes the stream through so the chain can go on.
+    /// The branch is wired along with the returned stream, so it runs even
+    /// though nothing reads it.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let doubled = ticker(Duration::from_millis(10))
+    ///     .count()
+    ///     .also(|count| count.logged("count", log::Level::Info).as_node())
+    ///     .map(|n| n * 2);
+    /// ```
+    #[must_use]
+    fn also(
+        self: &Rc<Self>,
+        func: impl FnOnce(&Rc<dyn Stream<T>>) -> Rc<dyn Node>,
+    ) -> Rc<dyn Stream<T>>;
     /// propagates source up to limit times
     #[must_use]
     fn limit(self: &Rc<Self>, limit: u32) -> Rc<dyn Stream<T>>;
-    /// logs source and propagates it
+    /// logs source and propagates it, under the target `"wingfoil"`.
+    /// Values are `Debug` formatted and truncated to [DEFAULT_MAX_LEN]
+    /// chars.  The level is checked every tick, so changes to the logger's
+    /// level mid-run take effect, and nothing is formatted while disabled.
     #[must_use]
     fn logged(self: &Rc<Self>, label: &str, level: Level) -> Rc<dyn Stream<T>>;
+    /// Like [logged](StreamOperators::logged), under `target` rather than
+    /// `"wingfoil"`, so that per-module logger filters such as
+    /// `RUST_LOG=fills=debug` can pick it out.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// ticker(Duration::from_millis(10))
+    ///     .count()
+    ///     .logged_target("fills", "count", log::Level::Debug);
+    /// ```
+    #[must_use]
+    fn logged_target(self: &Rc<Self>, target: &str, label: &str, level: Level)
+    -> Rc<dyn Stream<T>>;
+    /// Like [logged](StreamOperators::logged), formatting each value with
+    /// `formatter`.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// ticker(Duration::from_millis(10))
+    ///     .produce(|| vec![0u8; 10_000])
+    ///     .logged_with("fills", log::Level::Info, |fills| format!("{} fills", fills.len()));
+    /// ```
+    #[must_use]
+    fn logged_with(
+        self: &Rc<Self>,
+        label: &str,
+        level: Level,
+        formatter: impl Fn(&T) -> String + 'static,
+    ) -> Rc<dyn Stream<T>>;
+    /// Like [logged](StreamOperators::logged), but each value is logged as a
+    /// JSON object `{"time":..,"label":..,"value":..}`, with `time` in
+    /// nanoseconds, for JSON log pipelines such as Loki or ELK.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// #[derive(Clone, Debug, Default, serde::Serialize)]
+    /// struct Fill {
+    ///     price: f64,
+    ///     qty: u32,
+    /// }
+    /// ticker(Duration::from_millis(10))
+    ///     .count()
+    ///     .map(|n| Fill { price: 100.0 + n as f64, qty: 1 })
+    ///     .logged_json("fills", log::Level::Info);
+    /// ```
+    #[must_use]
+    fn logged_json(self: &Rc<Self>, label: &str, level: Level) -> Rc<dyn Stream<T>>
+    where
+        T: serde::Serialize;
     /// Map's it's source into a new Stream using the supplied closure.
     #[must_use]
     fn map<OUT: Element>(self: &Rc<Self>, func: impl Fn(T) -> OUT + 'static)
     -> Rc<dyn Stream<OUT>>;
+    /// Like [map](StreamOperators::map) but the closure borrows the source's
+    /// value instead of receiving a clone of it.  Prefer this for large
+    /// payloads that the closure only reads.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// ticker(Duration::from_millis(10))
+    ///     .count()
+    ///     .map(|n| vec![n; 1024])
+    ///     .map_ref(|v: &Vec<u64>| v.iter().sum::<u64>());
+    /// ```
+    #[must_use]
+    fn map_ref<OUT: Element>(
+        self: &Rc<Self>,
+        func: impl Fn(&T) -> OUT + 'static,
+    ) -> Rc<dyn Stream<OUT>>;
     /// Map's source into a new Stream using a fallible closure.
     /// Errors propagate to graph execution.
     #[must_use]
@@ -511,7 +1284,32 @@ pub trait StreamOperators<T: Element> {
         self: &Rc<Self>,
         func: impl Fn(T) -> anyhow::Result<OUT> + 'static,
     ) -> Rc<dyn Stream<OUT>>;
-    /// Uses func to build graph, which is spawned on worker thread
+    /// Like [map](Self::map), but a panic in `func` fails the graph run with
+    /// an error rather than unwinding through it, e.g. out of a
+    /// [mapper](Self::mapper) worker thread.
+    #[must_use]
+    fn map_catch<OUT: Element>(
+        self: &Rc<Self>,
+        func: impl Fn(T) -> OUT + 'static,
+    ) -> Rc<dyn Stream<OUT>>;
+    /// Uses func to build graph, which is spawned on worker thread.
+    ///
+    /// `func` is handed the worker's copy of the source and must wire
+    /// everything else from it: streams of the calling thread are
+    /// `Rc<RefCell<..>>` and capturing one is rejected at compile time.
+    /// See `examples/threading`.
+    /// ```compile_fail
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let source = ticker(Duration::from_millis(10)).count();
+    /// let other = ticker(Duration::from_millis(20)).count();
+    /// source.mapper(move |burst| bimap(Dep::Active(burst), Dep::Passive(other), |a, b| (a, b)));
+    /// ```
+    ///
+    /// In [RunMode::HistoricalFrom] the worker only hears about the times
+    /// its input ticks, so it may only tick at those times: a delay that is
+    /// not a multiple of the source period fails the run.  Use
+    /// [mapper_lockstep](Self::mapper_lockstep) for such graphs.
     #[cfg(feature = "async")]
     #[must_use]
     fn mapper<FUNC, OUT>(self: &Rc<Self>, func: FUNC) -> Rc<dyn Stream<Burst<OUT>>>
@@ -519,25 +1317,209 @@ pub trait StreamOperators<T: Element> {
         T: Element + Send,
         OUT: Element + Send + Hash + Eq,
         FUNC: FnOnce(Rc<dyn Stream<Burst<T>>>) -> Rc<dyn Stream<OUT>> + Send + 'static;
-    /// negates it's input
+    /// Like [mapper](Self::mapper), but in [RunMode::HistoricalFrom] the
+    /// parent waits for the worker to acknowledge each time before moving on,
+    /// and wakes it at its own callbacks.  Any worker graph, including
+    /// arbitrary delays and nested mappers, then replays deterministically, at
+    /// the cost of a round trip per tick.  Same as `mapper` in
+    /// [RunMode::RealTime].
+    #[cfg(feature = "async")]
     #[must_use]
-    fn not(self: &Rc<Self>) -> Rc<dyn Stream<T>>
+    fn mapper_lockstep<FUNC, OUT>(self: &Rc<Self>, func: FUNC) -> Rc<dyn Stream<Burst<OUT>>>
     where
-        T: std::ops::Not<Output = T>;
+        T: Element + Send,
+        OUT: Element + Send + Hash + Eq,
+        FUNC: FnOnce(Rc<dyn Stream<Burst<T>>>) -> Rc<dyn Stream<OUT>> + Send + 'static;
+    /// Fans this stream out to `n` consumers in this graph.  Nodes already
+    /// share their output, so each is this same stream and ticks on every
+    /// upstream tick with the same value.
+    #[must_use]
+    fn broadcast(self: &Rc<Self>, n: usize) -> Vec<Rc<dyn Stream<T>>>;
+    /// Fans this stream out to `n` consumers in other graphs, typically on
+    /// worker threads, each over its own channel, as for [pipe_local].
+    /// Returns the sending [Node], to be added to this graph, and a
+    /// [PipeReceiver] per consumer, which builds its `Stream<Burst<T>>`.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let run_mode = RunMode::HistoricalFrom(NanoTime::ZERO);
+    /// let source = ticker(Duration::from_millis(10)).count();
+    /// let (send, receivers) = source.broadcast_channels(2);
+    /// let workers: Vec<_> = receivers
+    ///     .into_iter()
+    ///     .map(|recv| {
+    ///         std::thread::spawn(move || {
+    ///             let received = recv().collapse().collect();
+    ///             received
+    ///                 .run(run_mode, RunFor::Forever)
+    ///                 .map(|_| received.peek_value().len())
+    ///         })
+    ///     })
+    ///     .collect();
+    /// send.run(run_mode, RunFor::Cycles(3)).unwrap();
+    /// for worker in workers {
+    ///     assert_eq!(worker.join().unwrap().unwrap(), 3);
+    /// }
+    /// ```
+    #[cfg(feature = "async")]
+    #[must_use]
+    fn broadcast_channels(self: &Rc<Self>, n: usize) -> (Rc<dyn Node>, Vec<PipeReceiver<T>>)
+    where
+        T: Send;
+    /// Publishes each value to a [Mirror], through which threads outside the
+    /// graph can read the latest value without blocking it.  Returns the
+    /// publishing [Node], to be added to this graph, and the [Mirror].
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let (node, mirror) = ticker(Duration::from_millis(10)).count().mirror();
+    /// let monitor = std::thread::spawn(move || mirror.latest());
+    /// node.run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+    ///     .unwrap();
+    /// assert!(monitor.join().unwrap().is_none_or(|(_, count)| count <= 3));
+    /// ```
+    #[cfg(feature = "async")]
+    #[must_use]
+    fn mirror(self: &Rc<Self>) -> (Rc<dyn Node>, Mirror<T>)
+    where
+        T: Send + Sync;
+    /// negates it's input
+    #[must_use]
+    fn not(self: &Rc<Self>) -> Rc<dyn Stream<T>>
+    where
+        T: std::ops::Not<Output = T>;
 
     #[must_use]
     fn reduce(self: &Rc<Self>, func: impl Fn(T, T) -> T + 'static) -> Rc<dyn Stream<T>>;
     /// samples it's source on each tick of trigger
     #[must_use]
     fn sample(self: &Rc<Self>, trigger: Rc<dyn Node>) -> Rc<dyn Stream<T>>;
-    // print stream values to stdout
+    /// Like [sample](StreamOperators::sample), but the [Trigger] can be a
+    /// period, saving a separate [ticker].
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let price = ticker(Duration::from_millis(10)).count();
+    /// price.sample_trigger(Trigger::Period(Duration::from_secs(1)));
+    /// ```
+    #[must_use]
+    fn sample_trigger(self: &Rc<Self>, trigger: impl Into<Trigger>) -> Rc<dyn Stream<T>>;
+    /// Like [sample](StreamOperators::sample), but the trigger is a stream:
+    /// on each of its ticks, emits `combine` of this stream's latest value
+    /// and the trigger's value.  Ticks of this stream alone emit nothing.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let price = ticker(Duration::from_millis(10)).count().map(|n| 100 + n);
+    /// let volume = ticker(Duration::from_millis(25)).count();
+    /// price.sample_with(volume, |price, volume| price * volume);
+    /// ```
+    #[must_use]
+    fn sample_with<U: Element, OUT: Element>(
+        self: &Rc<Self>,
+        trigger: Rc<dyn Stream<U>>,
+        combine: impl Fn(&T, &U) -> OUT + 'static,
+    ) -> Rc<dyn Stream<OUT>>;
+    /// Emits each value as `(value, 1)`, then re-emits it as `(value, 2)`,
+    /// `(value, 3)`, ... after an [ExponentialBackoff] delay each time `failed`
+    /// ticks, which is typically a [feedback_node] signalled by the consumer
+    /// when an attempt fails.  A new value replaces any pending retry.  Fails
+    /// the graph if attempt `max_attempts` fails.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let (tx, failed) = feedback_node();
+    /// let backoff = ExponentialBackoff::new(Duration::from_millis(10), Duration::from_secs(1));
+    /// let attempts = ticker(Duration::from_secs(1))
+    ///     .count()
+    ///     .with_retry(5, backoff, failed);
+    /// // report an attempt as failed by ticking the feedback channel
+    /// let report = attempts
+    ///     .map(|(value, _attempt)| value % 2 == 0)
+    ///     .filter_value(|failed| *failed)
+    ///     .as_node()
+    ///     .feedback(tx);
+    /// ```
+    #[must_use]
+    fn with_retry(
+        self: &Rc<Self>,
+        max_attempts: u32,
+        backoff: ExponentialBackoff,
+        failed: Rc<dyn Node>,
+    ) -> Rc<dyn Stream<(T, u32)>>;
+    /// As-of join: on each tick, pairs the value with the most recent value of
+    /// `other` at or before the current time, or `None` if `other` has not
+    /// ticked within `tolerance`.  Ticks only when this stream ticks.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let quotes = ticker(Duration::from_millis(10)).count();
+    /// ticker(Duration::from_millis(25))
+    ///     .count()
+    ///     .asof_join(quotes, Duration::from_millis(10));
+    /// ```
+    #[must_use]
+    fn asof_join<B: Element>(
+        self: &Rc<Self>,
+        other: Rc<dyn Stream<B>>,
+        tolerance: Duration,
+    ) -> Rc<dyn Stream<(T, Option<B>)>>;
+    /// print stream values to stdout when the graph stops.  Values are
+    /// `Debug` formatted and truncated to [DEFAULT_MAX_LEN] chars.
     #[must_use]
     fn print(self: &Rc<Self>) -> Rc<dyn Stream<T>>;
+    /// Like [print](StreamOperators::print), formatting each value with
+    /// `formatter`, e.g. [debug_truncated] with a different length.
+    #[must_use]
+    fn print_with(self: &Rc<Self>, formatter: impl Fn(&T) -> String + 'static)
+    -> Rc<dyn Stream<T>>;
+    /// Sums the source but, unlike a running sum, ticks only once with the
+    /// grand total, on the last engine cycle if the source ticks on it.
+    /// However the run ends, e.g. a [RunFor::Forever] replay draining, the
+    /// total is also its value once the graph stops, to read with
+    /// [peek_value](StreamPeek::peek_value).
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let total = ticker(Duration::from_millis(10)).count().total();
+    /// total
+    ///     .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(4))
+    ///     .unwrap();
+    /// assert_eq!(total.peek_value(), 10);
+    /// ```
+    #[must_use]
+    fn total(self: &Rc<Self>) -> Rc<dyn Stream<T>>
+    where
+        T: Add<Output = T>;
     /// Suppresses upstream values that arrive faster than the specified interval.
     /// Emits the first value immediately, then ignores subsequent values until
     /// the interval elapses.
     #[must_use]
     fn throttle(self: &Rc<Self>, interval: Duration) -> Rc<dyn Stream<T>>;
+    /// Drops values while a realtime graph lags further than its
+    /// [lag_budget](crate::GraphBuilder::lag_budget), so that expensive
+    /// work downstream is skipped and the graph can catch up.  Passes every
+    /// value when no budget is set, and in historical mode.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let signals = ticker(Duration::from_millis(1))
+    ///     .count()
+    ///     .skip_if_behind()
+    ///     .map(|n| n * 2); // stands in for an expensive model
+    /// let mut graph = Graph::builder()
+    ///     .lag_budget(Duration::from_millis(5))
+    ///     .build(vec![signals.as_node()], RunMode::RealTime, RunFor::Cycles(3));
+    /// graph.run().unwrap();
+    /// ```
+    #[must_use]
+    fn skip_if_behind(self: &Rc<Self>) -> Rc<dyn Stream<T>>;
+    /// Passes through values unchanged, emitting a `trace` level tracing event
+    /// with the causal chain of upstream nodes behind each tick.  Build a
+    /// [TraceNode] directly to inspect the recorded [Trace]s.
+    #[cfg(feature = "tracing")]
+    #[must_use]
+    fn trace(self: &Rc<Self>) -> Rc<dyn Stream<T>>;
     /// Pairs each value with the graph time at which it ticked.
     /// Equivalent to `.map(|v| (time, v))` but with access to the graph clock.
     /// ```
@@ -550,6 +1532,18 @@ pub trait StreamOperators<T: Element> {
     /// ```
     #[must_use]
     fn with_time(self: &Rc<Self>) -> Rc<dyn Stream<(NanoTime, T)>>;
+    /// Emits the engine time elapsed since the previous tick.
+    /// Does not tick on the first value.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// // 10ms, 10ms, etc.
+    /// ticker(Duration::from_millis(10))
+    ///     .count()
+    ///     .time_since_last();
+    /// ```
+    #[must_use]
+    fn time_since_last(self: &Rc<Self>) -> Rc<dyn Stream<Duration>>;
     /// Passes through values unchanged. On shutdown logs a summary:
     /// tick count, elapsed wall time, and (in historical mode) elapsed engine
     /// time and the replay speedup factor.
@@ -561,10 +1555,36 @@ impl<T> StreamOperators<T> for dyn Stream<T>
 where
     T: Element + 'static,
 {
+    fn alert_when(
+        self: &Rc<Self>,
+        source: &str,
+        predicate: impl Fn(&T) -> bool + 'static,
+        severity: Severity,
+        message: impl Fn(&T) -> String + 'static,
+    ) -> Rc<dyn Stream<Alert>> {
+        AlertWhenStream::new(
+            self.clone(),
+            source,
+            Box::new(predicate),
+            severity,
+            Box::new(message),
+        )
+        .into_stream()
+    }
+
     fn accumulate(self: &Rc<Self>) -> Rc<dyn Stream<Vec<T>>> {
-        self.fold(|acc: &mut Vec<T>, value| {
-            acc.push(value);
-        })
+        FoldStream::new(
+            self.clone(),
+            Box::new(|acc: &mut Vec<T>, value| {
+                acc.push(value);
+            }),
+        )
+        .with_memory(vec_memory)
+        .into_stream()
+    }
+
+    fn accumulate_bounded(self: &Rc<Self>, max: usize) -> Rc<dyn Stream<Vec<T>>> {
+        BoundedAccumulateStream::new(self.clone(), max).into_stream()
     }
 
     fn buffer(self: &Rc<Self>, capacity: usize) -> Rc<dyn Stream<Vec<T>>> {
@@ -575,15 +1595,53 @@ where
         WindowStream::new(self.clone(), NanoTime::new(interval.as_nanos() as u64)).into_stream()
     }
 
+    fn tumbling(self: &Rc<Self>, window: Duration, empty: TumblingEmpty) -> Rc<dyn Stream<Vec<T>>> {
+        let push = |values: &mut Vec<T>, value| values.push(value);
+        TumblingStream::new(self.clone(), window, empty, Vec::new(), Box::new(push)).into_stream()
+    }
+
+    fn tumbling_fold<A: Element>(
+        self: &Rc<Self>,
+        window: Duration,
+        init: A,
+        func: impl Fn(&mut A, T) + 'static,
+    ) -> Rc<dyn Stream<A>> {
+        TumblingStream::new(
+            self.clone(),
+            window,
+            TumblingEmpty::Skip,
+            init,
+            Box::new(func),
+        )
+        .into_stream()
+    }
+
     fn collect(self: &Rc<Self>) -> Rc<dyn Stream<Vec<ValueAt<T>>>> {
         bimap(
             Dep::Active(self.clone()),
             Dep::Active(self.clone().as_node().ticked_at()),
             ValueAt::new,
         )
-        .fold(|acc: &mut Vec<ValueAt<T>>, value| {
-            acc.push(value);
-        })
+        .accumulate()
+    }
+
+    fn collect_with_value_time(self: &Rc<Self>) -> Rc<dyn Stream<Vec<TimedValueAt<T>>>> {
+        trimap(
+            Dep::Active(self.clone()),
+            Dep::Active(self.clone().as_node().ticked_at()),
+            Dep::Active(self.value_time()),
+            TimedValueAt::new,
+        )
+        .accumulate()
+    }
+
+    fn collect_to_file(self: &Rc<Self>, path: &str) -> Rc<dyn Node>
+    where
+        T: serde::Serialize,
+    {
+        let path = path.to_string();
+        self.collect()
+            .finally(move |values, _| write_collected(&path, &values))
     }
 
     fn collapse<OUT>(self: &Rc<Self>) -> Rc<dyn Stream<OUT>>
@@ -623,6 +1681,32 @@ where
         demux::demux(self.clone(), demux::DemuxMap::new(capacity), func)
     }
 
+    fn demux_map<K, F, OUT>(
+        self: &Rc<Self>,
+        capacity: usize,
+        func: F,
+        build_child: impl Fn(Rc<dyn Stream<T>>) -> Rc<dyn Stream<OUT>>,
+    ) -> (Rc<dyn Stream<Burst<OUT>>>, Overflow<T>)
+    where
+        OUT: Element,
+        K: Hash + Eq + PartialEq + std::fmt::Debug + 'static,
+        F: Fn(&T) -> (K, DemuxEvent) + 'static,
+    {
+        demux::demux_map(self.clone(), DemuxMap::new(capacity), func, build_child)
+    }
+
+    fn demux_with_diagnostics<K, F>(
+        self: &Rc<Self>,
+        capacity: usize,
+        func: F,
+    ) -> (Vec<Rc<dyn Stream<T>>>, Overflow<OverflowEvent<T, K>>)
+    where
+        K: Element + Hash + Eq,
+        F: Fn(&T) -> (K, DemuxEvent) + 'static,
+    {
+        demux::demux_with_diagnostics(self.clone(), demux::DemuxMap::new(capacity), func)
+    }
+
     fn demux_it<K, F, U>(
         self: &Rc<Self>,
         capacity: usize,
@@ -651,6 +1735,22 @@ where
         demux_it(self.clone(), map, func)
     }
 
+    fn demux_it_map<K, F, U, OUT>(
+        self: &Rc<Self>,
+        capacity: usize,
+        func: F,
+        build_child: impl Fn(Rc<dyn Stream<Burst<U>>>) -> Rc<dyn Stream<OUT>>,
+    ) -> (Rc<dyn Stream<Burst<OUT>>>, Overflow<Burst<U>>)
+    where
+        T: IntoIterator<Item = U>,
+        U: Element,
+        OUT: Element,
+        K: Hash + Eq + PartialEq + std::fmt::Debug + 'static,
+        F: Fn(&U) -> (K, DemuxEvent) + 'static,
+    {
+        demux::demux_it_map(self.clone(), DemuxMap::new(capacity), func, build_child)
+    }
+
     fn for_each(self: &Rc<Self>, func: impl Fn(T, NanoTime) + 'static) -> Rc<dyn Node> {
         ConsumerNode::new(self.clone(), Box::new(func)).into_node()
     }
@@ -662,6 +1762,20 @@ where
         FeedbackSendStream::new(self.clone(), sink).into_stream()
     }
 
+    fn feedback_loop(
+        self: &Rc<Self>,
+        init: T,
+        step: impl Fn(T, T) -> T + 'static,
+    ) -> (Rc<dyn Stream<T>>, Rc<dyn Node>)
+    where
+        T: PartialEq,
+    {
+        let (sink, previous) = feedback_init(init);
+        let looped = bimap(Dep::Active(self.clone()), Dep::Passive(previous), step);
+        let writer = looped.feedback(sink).as_node();
+        (looped, writer)
+    }
+
     fn try_for_each(
         self: &Rc<Self>,
         func: impl Fn(T, NanoTime) -> anyhow::Result<()> + 'static,
@@ -676,6 +1790,18 @@ where
         DelayStream::new(self.clone(), NanoTime::new(duration.as_nanos() as u64)).into_stream()
     }
 
+    fn lag(self: &Rc<Self>, delay: Duration) -> Rc<dyn Stream<T>> {
+        LagStream::new(self.clone(), NanoTime::from(delay)).into_stream()
+    }
+
+    fn lag_ticks(self: &Rc<Self>, ticks: usize) -> Rc<dyn Stream<T>> {
+        LagTicksStream::new(self.clone(), ticks).into_stream()
+    }
+
+    fn lead_ticks(self: &Rc<Self>, ticks: usize) -> Rc<dyn Stream<(T, T)>> {
+        LeadTicksStream::new(self.clone(), ticks).into_stream()
+    }
+
     fn delay_with_reset(
         self: &Rc<Self>,
         delay: Duration,
@@ -699,6 +1825,13 @@ where
         DifferenceStream::new(self.clone()).into_stream()
     }
 
+    fn assert_monotonic<K: PartialOrd + std::fmt::Debug + 'static>(
+        self: &Rc<Self>,
+        key_fn: impl Fn(&T) -> K + 'static,
+    ) -> Rc<dyn Stream<T>> {
+        MonotonicStream::new(self.clone(), Box::new(key_fn)).into_stream()
+    }
+
     fn distinct(self: &Rc<Self>) -> Rc<dyn Stream<T>>
     where
         T: PartialEq,
@@ -729,6 +1862,26 @@ where
         MapFilterStream::new(self.clone(), Box::new(f)).into_stream()
     }
 
+    fn partition(
+        self: &Rc<Self>,
+        predicate: impl Fn(&T) -> bool + 'static,
+    ) -> (Rc<dyn Stream<T>>, Rc<dyn Stream<T>>) {
+        self.partition_map(move |val| {
+            if predicate(&val) {
+                Either::Left(val)
+            } else {
+                Either::Right(val)
+            }
+        })
+    }
+
+    fn partition_map<A: Element, B: Element>(
+        self: &Rc<Self>,
+        func: impl Fn(T) -> Either<A, B> + 'static,
+    ) -> (Rc<dyn Stream<A>>, Rc<dyn Stream<B>>) {
+        partition::partition_map(self.clone(), func)
+    }
+
     fn finally<F: FnOnce(T, &GraphState) -> anyhow::Result<()> + 'static>(
         self: &Rc<Self>,
         func: F,
@@ -743,36 +1896,86 @@ where
         FoldStream::new(self.clone(), Box::new(func)).into_stream()
     }
 
+    fn fold_init<OUT: Element>(
+        self: &Rc<Self>,
+        init: OUT,
+        func: impl Fn(&mut OUT, T) + 'static,
+    ) -> Rc<dyn Stream<OUT>> {
+        FoldStream::new(self.clone(), Box::new(func))
+            .with_init(init)
+            .into_stream()
+    }
+
+    fn reduce_by_key<K: Element + Hash + Eq>(
+        self: &Rc<Self>,
+        key_fn: impl Fn(&T) -> K + 'static,
+        reduce_fn: impl Fn(T, T) -> T + 'static,
+    ) -> Rc<dyn Stream<Rc<HashMap<K, T>>>> {
+        ReduceByKeyStream::new(self.clone(), Box::new(key_fn), Box::new(reduce_fn)).into_stream()
+    }
+
+    fn reduce_by_key_updates<K: Element + Hash + Eq>(
+        self: &Rc<Self>,
+        key_fn: impl Fn(&T) -> K + 'static,
+        reduce_fn: impl Fn(T, T) -> T + 'static,
+    ) -> Rc<dyn Stream<(K, T)>> {
+        ReduceByKeyUpdatesStream::new(self.clone(), Box::new(key_fn), Box::new(reduce_fn))
+            .into_stream()
+    }
+
     fn inspect(self: &Rc<Self>, func: impl Fn(&T) + 'static) -> Rc<dyn Stream<T>> {
         InspectStream::new(self.clone(), Box::new(func)).into_stream()
     }
 
+    fn also(
+        self: &Rc<Self>,
+        func: impl FnOnce(&Rc<dyn Stream<T>>) -> Rc<dyn Node>,
+    ) -> Rc<dyn Stream<T>> {
+        let side = func(self);
+        AlsoStream::new(self.clone(), side).into_stream()
+    }
+
     fn limit(self: &Rc<Self>, limit: u32) -> Rc<dyn Stream<T>> {
         LimitStream::new(self.clone(), limit).into_stream()
     }
 
     fn logged(self: &Rc<Self>, label: &str, level: Level) -> Rc<dyn Stream<T>> {
-        #[cfg(not(feature = "tracing"))]
-        if !log::log_enabled!(level) {
-            return self.clone();
-        }
-        #[cfg(feature = "tracing")]
-        if !tracing_log_enabled!(level) {
-            return self.clone();
-        }
+        self.logged_with(label, level, debug_truncated(DEFAULT_MAX_LEN))
+    }
+
+    fn logged_target(
+        self: &Rc<Self>,
+        target: &str,
+        label: &str,
+        level: Level,
+    ) -> Rc<dyn Stream<T>> {
+        let formatter = debug_truncated(DEFAULT_MAX_LEN);
         let lbl = label.to_string();
-        let func = move |value: T, time: NanoTime| {
-            #[cfg(not(feature = "tracing"))]
-            log!(target: "wingfoil", level, "{} {} {:?}", time.pretty(), lbl, value);
-            #[cfg(feature = "tracing")]
-            tracing_log!(level; time, lbl, value);
-            value
-        };
-        bimap(
-            Dep::Active(self.clone()),
-            Dep::Active(self.clone().as_node().ticked_at_elapsed()),
-            func,
-        )
+        log_lines(self, target, level, move |value, time| {
+            format!("{} {} {}", time.pretty(), lbl, formatter(value))
+        })
+    }
+
+    fn logged_with(
+        self: &Rc<Self>,
+        label: &str,
+        level: Level,
+        formatter: impl Fn(&T) -> String + 'static,
+    ) -> Rc<dyn Stream<T>> {
+        let lbl = label.to_string();
+        log_lines(self, "wingfoil", level, move |value, time| {
+            format!("{} {} {}", time.pretty(), lbl, formatter(value))
+        })
+    }
+
+    fn logged_json(self: &Rc<Self>, label: &str, level: Level) -> Rc<dyn Stream<T>>
+    where
+        T: serde::Serialize,
+    {
+        let lbl = label.to_string();
+        log_lines(self, "wingfoil", level, move |value, time| {
+            json_log_line(time, &lbl, value)
+        })
     }
 
     fn map<OUT: Element>(
@@ -782,6 +1985,13 @@ where
         MapStream::new(self.clone(), Box::new(func)).into_stream()
     }
 
+    fn map_ref<OUT: Element>(
+        self: &Rc<Self>,
+        func: impl Fn(&T) -> OUT + 'static,
+    ) -> Rc<dyn Stream<OUT>> {
+        MapRefStream::new(self.clone(), Box::new(func)).into_stream()
+    }
+
     fn try_map<OUT: Element>(
         self: &Rc<Self>,
         func: impl Fn(T) -> anyhow::Result<OUT> + 'static,
@@ -789,6 +1999,13 @@ where
         TryMapStream::new(self.clone(), Box::new(func)).into_stream()
     }
 
+    fn map_catch<OUT: Element>(
+        self: &Rc<Self>,
+        func: impl Fn(T) -> OUT + 'static,
+    ) -> Rc<dyn Stream<OUT>> {
+        MapCatchStream::new(self.clone(), Box::new(func)).into_stream()
+    }
+
     #[cfg(feature = "async")]
     fn mapper<FUNC, OUT>(self: &Rc<Self>, func: FUNC) -> Rc<dyn Stream<Burst<OUT>>>
     where
@@ -796,7 +2013,37 @@ where
         OUT: Element + Send + Hash + Eq,
         FUNC: FnOnce(Rc<dyn Stream<Burst<T>>>) -> Rc<dyn Stream<OUT>> + Send + 'static,
     {
-        GraphMapStream::new(self.clone(), func).into_stream()
+        GraphMapStream::new(self.clone(), func, false).into_stream()
+    }
+
+    #[cfg(feature = "async")]
+    fn mapper_lockstep<FUNC, OUT>(self: &Rc<Self>, func: FUNC) -> Rc<dyn Stream<Burst<OUT>>>
+    where
+        T: Element + Send,
+        OUT: Element + Send + Hash + Eq,
+        FUNC: FnOnce(Rc<dyn Stream<Burst<T>>>) -> Rc<dyn Stream<OUT>> + Send + 'static,
+    {
+        GraphMapStream::new(self.clone(), func, true).into_stream()
+    }
+
+    fn broadcast(self: &Rc<Self>, n: usize) -> Vec<Rc<dyn Stream<T>>> {
+        vec![self.clone(); n]
+    }
+
+    #[cfg(feature = "async")]
+    fn broadcast_channels(self: &Rc<Self>, n: usize) -> (Rc<dyn Node>, Vec<PipeReceiver<T>>)
+    where
+        T: Send,
+    {
+        pipe_broadcast(self.clone(), n)
+    }
+
+    #[cfg(feature = "async")]
+    fn mirror(self: &Rc<Self>) -> (Rc<dyn Node>, Mirror<T>)
+    where
+        T: Send + Sync,
+    {
+        mirror::mirror(self.clone())
     }
 
     fn not(self: &Rc<Self>) -> Rc<dyn Stream<T>>
@@ -810,6 +2057,18 @@ where
         PrintStream::new(self.clone()).into_stream()
     }
 
+    fn print_with(
+        self: &Rc<Self>,
+        formatter: impl Fn(&T) -> String + 'static,
+    ) -> Rc<dyn Stream<T>> {
+        PrintStream::with(
+            self.clone(),
+            Box::new(formatter),
+            Box::new(std::io::stdout()),
+        )
+        .into_stream()
+    }
+
     fn reduce(self: &Rc<Self>, func: impl Fn(T, T) -> T + 'static) -> Rc<dyn Stream<T>> {
         let f = move |acc: &mut T, val: T| {
             *acc = func((*acc).clone(), val);
@@ -820,14 +2079,64 @@ where
     fn sample(self: &Rc<Self>, trigger: Rc<dyn Node>) -> Rc<dyn Stream<T>> {
         SampleStream::new(self.clone(), trigger).into_stream()
     }
+
+    fn sample_trigger(self: &Rc<Self>, trigger: impl Into<Trigger>) -> Rc<dyn Stream<T>> {
+        self.sample(trigger.into().into())
+    }
+
+    fn sample_with<U: Element, OUT: Element>(
+        self: &Rc<Self>,
+        trigger: Rc<dyn Stream<U>>,
+        combine: impl Fn(&T, &U) -> OUT + 'static,
+    ) -> Rc<dyn Stream<OUT>> {
+        SampleWithStream::new(self.clone(), trigger, Box::new(combine)).into_stream()
+    }
+
+    fn with_retry(
+        self: &Rc<Self>,
+        max_attempts: u32,
+        backoff: ExponentialBackoff,
+        failed: Rc<dyn Node>,
+    ) -> Rc<dyn Stream<(T, u32)>> {
+        ExponentialBackoffStream::new(self.clone(), failed, max_attempts, backoff).into_stream()
+    }
+
+    fn asof_join<B: Element>(
+        self: &Rc<Self>,
+        other: Rc<dyn Stream<B>>,
+        tolerance: Duration,
+    ) -> Rc<dyn Stream<(T, Option<B>)>> {
+        let tolerance = NanoTime::new(tolerance.as_nanos() as u64);
+        AsofJoinStream::new(self.clone(), other, tolerance).into_stream()
+    }
     fn throttle(self: &Rc<Self>, interval: Duration) -> Rc<dyn Stream<T>> {
         ThrottleStream::new(self.clone(), NanoTime::new(interval.as_nanos() as u64)).into_stream()
     }
 
+    fn skip_if_behind(self: &Rc<Self>) -> Rc<dyn Stream<T>> {
+        SkipIfBehindStream::new(self.clone()).into_stream()
+    }
+
+    fn total(self: &Rc<Self>) -> Rc<dyn Stream<T>>
+    where
+        T: Add<Output = T>,
+    {
+        TotalStream::new(self.clone()).into_stream()
+    }
+
+    #[cfg(feature = "tracing")]
+    fn trace(self: &Rc<Self>) -> Rc<dyn Stream<T>> {
+        TraceNode::new(self.clone(), DEFAULT_TRACE_CAPACITY).into_stream()
+    }
+
     fn with_time(self: &Rc<Self>) -> Rc<dyn Stream<(NanoTime, T)>> {
         WithTimeStream::new(self.clone()).into_stream()
     }
 
+    fn time_since_last(self: &Rc<Self>) -> Rc<dyn Stream<Duration>> {
+        TimeSinceLastStream::new(self.clone().as_node()).into_stream()
+    }
+
     fn timed(self: &Rc<Self>) -> Rc<dyn Stream<T>> {
         TimedStream::new(self.clone()).into_stream()
     }
@@ -854,6 +2163,26 @@ where
     }
 }
 
+/// Operators available only on a time-tagged `Stream<(NanoTime, T)>`.
+pub trait TimeTaggedStreamOperators<T>
+where
+    T: Element + 'static,
+{
+    /// Drops the timestamp, the reverse of
+    /// [with_time](StreamOperators::with_time).
+    #[must_use]
+    fn without_time(self: &Rc<Self>) -> Rc<dyn Stream<T>>;
+}
+
+impl<T> TimeTaggedStreamOperators<T> for dyn Stream<(NanoTime, T)>
+where
+    T: Element + 'static,
+{
+    fn without_time(self: &Rc<Self>) -> Rc<dyn Stream<T>> {
+        self.map(|(_, value): (NanoTime, T)| value)
+    }
+}
+
 /// Operators available only on a `Stream<Option<T>>`.
 pub trait OptionStreamOperators<T>
 where
@@ -875,12 +2204,213 @@ where
     }
 }
 
+/// Logs the line `line` builds for each tick of `stream` under `target`
+/// and propagates it.
+fn log_lines<T: Element>(
+    stream: &Rc<dyn Stream<T>>,
+    target: &str,
+    level: Level,
+    line: impl Fn(&T, NanoTime) -> String + 'static,
+) -> Rc<dyn Stream<T>> {
+    LoggedStream::new(stream.clone(), target.to_string(), level, Box::new(line)).into_stream()
+}
+
+/// Operators available only on a `Stream<Result<T, E>>`.
+///
+/// `Result` is not [Default], so it is not an [Element] and the generic
+/// operators cannot produce it; such streams come from custom nodes.
+pub trait ResultStreamOperators<T, E>
+where
+    T: Element,
+    E: Element,
+{
+    /// Splits into a stream of `Ok` payloads and a stream of `Err`
+    /// payloads.  Each ticks only when its variant arrives.
+    #[must_use]
+    fn split_result(self: &Rc<Self>) -> (Rc<dyn Stream<T>>, Rc<dyn Stream<E>>);
+}
+
+impl<T, E> ResultStreamOperators<T, E> for dyn Stream<Result<T, E>>
+where
+    T: Element,
+    E: Element,
+{
+    fn split_result(self: &Rc<Self>) -> (Rc<dyn Stream<T>>, Rc<dyn Stream<E>>) {
+        let oks = ResultBranchStream::new(self.clone(), |result| result.as_ref().ok());
+        let errs = ResultBranchStream::new(self.clone(), |result| result.as_ref().err());
+        (oks.into_stream(), errs.into_stream())
+    }
+}
+
+/// Operators available only on a `Stream<f64>`.
+pub trait FloatStreamOperators {
+    /// Rate of change per second: the difference from the previous value
+    /// divided by the engine time elapsed since it, in seconds.  Unlike
+    /// [difference](StreamOperators::difference) this accounts for irregular
+    /// gaps between ticks.  Does not tick on the first value.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// // 2.0, 2.0, etc.
+    /// ticker(Duration::from_millis(500))
+    ///     .count()
+    ///     .map(|x| x as f64)
+    ///     .derivative();
+    /// ```
+    #[must_use]
+    fn derivative(self: &Rc<Self>) -> Rc<dyn Stream<f64>>;
+
+    /// Mean of the values ticked within the last `window` of engine time,
+    /// emitted on every tick.  NaN values are left out of the window;
+    /// `policy` decides what to emit when that leaves it empty.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// ticker(Duration::from_millis(100))
+    ///     .count()
+    ///     .map(|x| x as f64)
+    ///     .moving_average(Duration::from_secs(1), EmptyWindowPolicy::Nan);
+    /// ```
+    #[must_use]
+    fn moving_average(
+        self: &Rc<Self>,
+        window: Duration,
+        policy: EmptyWindowPolicy,
+    ) -> Rc<dyn Stream<f64>>;
+
+    /// Mean of the last `size` (non-NaN) values, emitted on every tick.
+    #[must_use]
+    fn moving_average_count(
+        self: &Rc<Self>,
+        size: usize,
+        policy: EmptyWindowPolicy,
+    ) -> Rc<dyn Stream<f64>>;
+
+    /// Converts each value to a [Decimal], rounded to `dp` places with
+    /// `strategy`.  Does not tick on NaN or infinite values.
+    #[cfg(feature = "decimal")]
+    #[must_use]
+    fn to_decimal(self: &Rc<Self>, dp: u32, strategy: RoundingStrategy) -> Rc<dyn Stream<Decimal>>;
+
+    /// Passes on finite values, keeping NaN and infinite values from
+    /// corrupting downstream aggregates: `policy` decides whether they are
+    /// dropped or fail the graph.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// // 1.0, 0.5, 0.333.. with the first (infinite) value dropped
+    /// ticker(Duration::from_millis(100))
+    ///     .count()
+    ///     .map(|x| 1.0 / (x - 1) as f64)
+    ///     .reject_nan(NonFinitePolicy::Drop);
+    /// ```
+    #[must_use]
+    fn reject_nan(self: &Rc<Self>, policy: NonFinitePolicy) -> Rc<dyn Stream<f64>>;
+
+    /// Replaces NaN and infinite values with `fill`.
+    #[must_use]
+    fn replace_nan(self: &Rc<Self>, fill: f64) -> Rc<dyn Stream<f64>>;
+
+    /// Running maximum drawdown of an equity or cumulative PnL curve: the
+    /// largest decline from a running peak to a later value seen so far, as
+    /// a non-negative amount.  Emitted on every tick, starting at 0.0; NaN
+    /// values leave it unchanged.  This is
+    /// [StatisticsOperators::max_drawdown](crate::adapters::statistics::StatisticsOperators::max_drawdown)
+    /// with the sign flipped, read from the same
+    /// [drawdown_stats](crate::adapters::statistics::StatisticsOperators::drawdown_stats).
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// // 0.0, 0.0, 5.0, 5.0 for equity 10, 12, 7, 15
+    /// ticker(Duration::from_secs(1))
+    ///     .count()
+    ///     .map(|n| [10.0, 12.0, 7.0, 15.0][(n as usize - 1) % 4])
+    ///     .cumulative_max_drawdown();
+    /// ```
+    #[must_use]
+    fn cumulative_max_drawdown(self: &Rc<Self>) -> Rc<dyn Stream<f64>>;
+}
+
+impl FloatStreamOperators for dyn Stream<f64> {
+    fn derivative(self: &Rc<Self>) -> Rc<dyn Stream<f64>> {
+        DerivativeStream::new(self.clone()).into_stream()
+    }
+
+    fn moving_average(
+        self: &Rc<Self>,
+        window: Duration,
+        policy: EmptyWindowPolicy,
+    ) -> Rc<dyn Stream<f64>> {
+        let window = AverageWindow::Time(NanoTime::from(window));
+        MovingAverageStream::new(self.clone(), window, policy).into_stream()
+    }
+
+    fn moving_average_count(
+        self: &Rc<Self>,
+        size: usize,
+        policy: EmptyWindowPolicy,
+    ) -> Rc<dyn Stream<f64>> {
+        MovingAverageStream::new(self.clone(), AverageWindow::Count(size), policy).into_stream()
+    }
+
+    #[cfg(feature = "decimal")]
+    fn to_decimal(self: &Rc<Self>, dp: u32, strategy: RoundingStrategy) -> Rc<dyn Stream<Decimal>> {
+        self.filter_map(move |value| decimal::f64_to_decimal(value, dp, strategy))
+    }
+
+    fn reject_nan(self: &Rc<Self>, policy: NonFinitePolicy) -> Rc<dyn Stream<f64>> {
+        RejectNonFiniteStream::new(self.clone(), policy).into_stream()
+    }
+
+    fn replace_nan(self: &Rc<Self>, fill: f64) -> Rc<dyn Stream<f64>> {
+        self.map(move |value| if value.is_finite() { value } else { fill })
+    }
+
+    fn cumulative_max_drawdown(self: &Rc<Self>) -> Rc<dyn Stream<f64>> {
+        // 0.0 - rather than negation, so no drawdown is 0.0 and not -0.0
+        StatisticsOperators::drawdown_stats(self).map(|d| 0.0 - d.max_drawdown)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::queue::ValueAt;
     use std::cell::RefCell;
 
+    fn max_drawdowns(equity: &'static [f64]) -> Vec<f64> {
+        let drawdown = ticker(Duration::from_secs(1))
+            .count()
+            .map(move |n| equity[n as usize - 1])
+            .cumulative_max_drawdown()
+            .collect();
+        drawdown
+            .run(
+                RunMode::HistoricalFrom(NanoTime::ZERO),
+                RunFor::Cycles(equity.len() as u32),
+            )
+            .unwrap();
+        drawdown.peek_value().iter().map(|v| v.value).collect()
+    }
+
+    #[test]
+    fn cumulative_max_drawdown_of_equity_curve() {
+        // peak 110 -> trough 80 is 30, larger than the later 130 -> 105
+        let equity = &[100.0, 110.0, 95.0, 80.0, 120.0, 130.0, 105.0, 140.0];
+        let drawdowns = max_drawdowns(equity);
+        assert_eq!(
+            drawdowns,
+            vec![0.0, 0.0, 15.0, 30.0, 30.0, 30.0, 30.0, 30.0]
+        );
+        assert!(drawdowns[0].is_sign_positive());
+    }
+
+    #[test]
+    fn cumulative_max_drawdown_ignores_nan() {
+        let equity = &[10.0, f64::NAN, 4.0, f64::NAN, 12.0];
+        assert_eq!(max_drawdowns(equity), vec![0.0, 0.0, 6.0, 6.0, 6.0]);
+    }
+
     /// Helper: create a single-tick CallBackStream source.
     fn make_source(value: u64, time: u64) -> Rc<dyn Stream<u64>> {
         let src = Rc::new(RefCell::new(CallBackStream::<u64>::new()));
@@ -1070,6 +2600,30 @@ mod tests {
         assert_eq!(values, vec![1, 3, 5]);
     }
 
+    #[test]
+    fn broadcast_outputs_tick_with_every_upstream_tick() {
+        let source = ticker(Duration::from_nanos(100)).count();
+        let outputs: Vec<_> = source
+            .broadcast(3)
+            .iter()
+            .map(|output| output.collect())
+            .collect();
+        let nodes = outputs.iter().map(|o| o.clone().as_node()).collect();
+        Graph::new(
+            nodes,
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(4),
+        )
+        .run()
+        .unwrap();
+        let expected: Vec<_> = (1..=4)
+            .map(|i| ValueAt::new(i, NanoTime::new((i - 1) * 100)))
+            .collect();
+        for output in outputs {
+            assert_eq!(output.peek_value(), expected);
+        }
+    }
+
     #[test]
     fn split_decomposes_tuple_stream() {
         let cb = Rc::new(RefCell::new(CallBackStream::<(u64, u64)>::new()));
@@ -0,0 +1,105 @@
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use crate::types::*;
+
+/// Passes its source through, failing the graph if a key extracted from it
+/// ever decreases.  Used by
+/// [assert_monotonic](crate::nodes::StreamOperators::assert_monotonic).
+pub(crate) struct MonotonicStream<T: Element, K> {
+    upstream: Rc<dyn Stream<T>>,
+    value: T,
+    key_fn: Box<dyn Fn(&T) -> K>,
+    /// The last key, with the value and time it came from.
+    last: Option<(K, T, NanoTime)>,
+}
+
+impl<T: Element, K> MonotonicStream<T, K> {
+    pub fn new(upstream: Rc<dyn Stream<T>>, key_fn: Box<dyn Fn(&T) -> K>) -> Self {
+        Self {
+            upstream,
+            value: T::default(),
+            key_fn,
+            last: None,
+        }
+    }
+}
+
+#[node(active = [upstream], output = value: T)]
+impl<T: Element, K: PartialOrd + Debug + 'static> MutableNode for MonotonicStream<T, K> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        let value = self.upstream.peek_value();
+        let key = (self.key_fn)(&value);
+        if let Some((last_key, last_value, last_time)) = &self.last {
+            // Written as `!(key >= last)` so incomparable keys (NaN) fail too.
+            #[allow(clippy::neg_cmp_op_on_partial_ord)]
+            if !(key >= *last_key) {
+                anyhow::bail!(
+                    "assert_monotonic: key went backwards from {last_key:?} to {key:?} at {:?}; \
+                     previous value {last_value:?} at {last_time:?}, offending value {value:?}",
+                    state.time()
+                );
+            }
+        }
+        self.last = Some((key, value.clone(), state.time()));
+        self.value = value;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::*;
+    use crate::nodes::*;
+    use crate::queue::ValueAt;
+
+    fn sequence(values: &[u64]) -> Rc<dyn Stream<u64>> {
+        let mut stream = CallBackStream::new();
+        for (i, value) in values.iter().enumerate() {
+            stream.push(ValueAt::new(*value, NanoTime::new(i as u64 * 100)));
+        }
+        stream.into_stream()
+    }
+
+    fn run<T: Element>(stream: &Rc<dyn Stream<T>>) -> anyhow::Result<Vec<T>> {
+        let collected = stream.collect();
+        collected.run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)?;
+        Ok(collected
+            .peek_value()
+            .into_iter()
+            .map(|v| v.value)
+            .collect())
+    }
+
+    #[test]
+    fn passes_non_decreasing_keys_through() {
+        let checked = sequence(&[1, 2, 2, 5]).assert_monotonic(|x| *x);
+        assert_eq!(run(&checked).unwrap(), vec![1, 2, 2, 5]);
+    }
+
+    #[test]
+    fn fails_naming_the_offending_values() {
+        let checked = sequence(&[10, 20, 15, 30]).assert_monotonic(|x| *x);
+        let err = format!("{:#}", run(&checked).unwrap_err());
+        assert!(err.contains("key went backwards from 20 to 15"), "{err}");
+        assert!(err.contains("at NanoTime(200)"), "{err}");
+        assert!(err.contains("previous value 20 at NanoTime(100)"), "{err}");
+    }
+
+    #[test]
+    fn checks_only_the_extracted_key() {
+        // Sequence numbers in the high digits, payload in the low ones.
+        let checked = sequence(&[11, 19, 23, 35]).assert_monotonic(|x| x / 10);
+        assert_eq!(run(&checked).unwrap(), vec![11, 19, 23, 35]);
+        let checked = sequence(&[11, 19, 23, 15]).assert_monotonic(|x| x / 10);
+        let err = format!("{:#}", run(&checked).unwrap_err());
+        assert!(err.contains("from 2 to 1"), "{err}");
+        assert!(err.contains("offending value 15"), "{err}");
+    }
+
+    #[test]
+    fn fails_on_incomparable_keys() {
+        let checked = sequence(&[1, 2]).assert_monotonic(|x| if *x == 2 { f64::NAN } else { 1.0 });
+        assert!(run(&checked).is_err());
+    }
+}
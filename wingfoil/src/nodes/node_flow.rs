@@ -63,7 +63,7 @@ impl MutableNode for DelayNode {
             let current_time = state.time();
             if state.ticked(self.upstream.clone()) {
                 let next_time = current_time + self.delay;
-                state.add_callback(next_time);
+                state.schedule_at_self(next_time);
                 self.queue.push((), next_time);
             }
             let mut ticked = false;
@@ -104,6 +104,143 @@ impl MutableNode for LimitNode {
     }
 }
 
+/// Passes upstream ticks and also ticks whenever upstream has been quiet
+/// for `interval`, measured from the last tick of either kind.
+///
+/// Keeps a single callback in flight: a real tick only moves the deadline,
+/// and the pending callback reschedules itself to it when it fires early.
+pub(crate) struct HeartbeatNode {
+    upstream: Rc<dyn Node>,
+    interval: NanoTime,
+    deadline: NanoTime,
+    scheduled: NanoTime,
+}
+
+impl HeartbeatNode {
+    pub fn new(upstream: Rc<dyn Node>, interval: NanoTime) -> Self {
+        Self {
+            upstream,
+            interval,
+            deadline: NanoTime::ZERO,
+            scheduled: NanoTime::ZERO,
+        }
+    }
+
+    fn schedule(&mut self, state: &mut GraphState) {
+        self.scheduled = self.deadline;
+        state.schedule_at_self(self.deadline);
+    }
+}
+
+#[node(active = [upstream])]
+impl MutableNode for HeartbeatNode {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        let now = state.time();
+        let ticked = state.ticked(self.upstream.clone()) || now >= self.deadline;
+        if ticked {
+            self.deadline = now + self.interval;
+        }
+        if self.scheduled <= now {
+            self.schedule(state);
+        }
+        Ok(ticked)
+    }
+
+    fn start(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
+        self.deadline = state.start_time() + self.interval;
+        self.schedule(state);
+        Ok(())
+    }
+}
+
+/// What [rate_limit](crate::nodes::NodeFlowOperators::rate_limit) does with
+/// ticks that arrive while the bucket is empty.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RateLimitPolicy {
+    /// Discard them.
+    #[default]
+    Drop,
+    /// Hold them and release one per token as the bucket refills.  The
+    /// backlog is unbounded, so a source that stays faster than the rate
+    /// falls ever further behind.
+    Queue,
+}
+
+/// Token bucket: passes upstream ticks while tokens remain, refilling at
+/// `tokens_per_sec` up to `burst`.  Starts full.
+pub(crate) struct RateLimitNode {
+    upstream: Rc<dyn Node>,
+    tokens_per_nano: f64,
+    burst: f64,
+    policy: RateLimitPolicy,
+    tokens: f64,
+    last_refill: Option<NanoTime>,
+    queued: u64,
+}
+
+impl RateLimitNode {
+    pub fn new(
+        upstream: Rc<dyn Node>,
+        tokens_per_sec: f64,
+        burst: u32,
+        policy: RateLimitPolicy,
+    ) -> Self {
+        Self {
+            upstream,
+            tokens_per_nano: tokens_per_sec / 1e9,
+            burst: burst as f64,
+            policy,
+            tokens: burst as f64,
+            last_refill: None,
+            queued: 0,
+        }
+    }
+
+    fn refill(&mut self, now: NanoTime) {
+        if let Some(last) = self.last_refill {
+            let elapsed = u64::from(now - last) as f64;
+            self.tokens = (self.tokens + elapsed * self.tokens_per_nano).min(self.burst);
+        }
+        self.last_refill = Some(now);
+    }
+
+    fn take_token(&mut self) -> bool {
+        // Tolerate rounding in the refill so a callback scheduled for the
+        // moment a token is due always finds it.
+        if self.tokens >= 1.0 - 1e-9 {
+            self.tokens = (self.tokens - 1.0).max(0.0);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[node(active = [upstream])]
+impl MutableNode for RateLimitNode {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        let now = state.time();
+        self.refill(now);
+        if state.ticked(self.upstream.clone()) {
+            match self.policy {
+                RateLimitPolicy::Drop => return Ok(self.take_token()),
+                RateLimitPolicy::Queue => self.queued += 1,
+            }
+        }
+        // At most one tick per cycle: the rest of the backlog waits for
+        // later cycles, scheduled below.
+        let ticked = self.queued > 0 && self.take_token();
+        if ticked {
+            self.queued -= 1;
+        }
+        if self.queued > 0 {
+            let wait = ((1.0 - self.tokens).max(0.0) / self.tokens_per_nano).ceil() as u64;
+            state.schedule_at_self(now + NanoTime::new(wait.max(1)));
+        }
+        Ok(ticked)
+    }
+}
+
 /// Drops upstream ticks when `condition` is false.
 pub(crate) struct FilterNode {
     upstream: Rc<dyn Node>,
@@ -151,6 +288,7 @@ mod tests {
     use crate::graph::*;
     use crate::nodes::*;
     use crate::queue::ValueAt;
+    use std::cell::RefCell;
     use std::time::Duration;
 
     #[test]
@@ -222,16 +360,11 @@ mod tests {
         ticker(Duration::from_nanos(10))
             .delay(Duration::from_nanos(0))
             .count()
-            .collect()
-            .finally(|values, _| {
-                let expected = vec![
-                    ValueAt::new(1, NanoTime::new(0)),
-                    ValueAt::new(2, NanoTime::new(10)),
-                    ValueAt::new(3, NanoTime::new(20)),
-                ];
-                assert_eq!(expected, values);
-                Ok(())
-            })
+            .assert_eq(vec![
+                ValueAt::new(1, NanoTime::new(0)),
+                ValueAt::new(2, NanoTime::new(10)),
+                ValueAt::new(3, NanoTime::new(20)),
+            ])
             .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
             .unwrap();
     }
@@ -242,16 +375,11 @@ mod tests {
         ticker(Duration::from_nanos(10))
             .limit(3)
             .count()
-            .collect()
-            .finally(|values, _| {
-                let expected = vec![
-                    ValueAt::new(1, NanoTime::new(0)),
-                    ValueAt::new(2, NanoTime::new(10)),
-                    ValueAt::new(3, NanoTime::new(20)),
-                ];
-                assert_eq!(expected, values);
-                Ok(())
-            })
+            .assert_eq(vec![
+                ValueAt::new(1, NanoTime::new(0)),
+                ValueAt::new(2, NanoTime::new(10)),
+                ValueAt::new(3, NanoTime::new(20)),
+            ])
             .run(
                 RunMode::HistoricalFrom(NanoTime::ZERO),
                 RunFor::Duration(Duration::from_nanos(90)),
@@ -259,6 +387,66 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn node_heartbeat_fills_gaps_between_sparse_ticks() {
+        let source = Rc::new(RefCell::new(CallBackStream::<()>::new()));
+        for time in [10, 100, 105, 300] {
+            source
+                .borrow_mut()
+                .push(ValueAt::new((), NanoTime::new(time)));
+        }
+        let times = source
+            .clone()
+            .as_stream()
+            .as_node()
+            .heartbeat(Duration::from_nanos(30))
+            .ticked_at()
+            .collect();
+        times
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(14))
+            .unwrap();
+        let times: Vec<u64> = times.peek_value().iter().map(|t| t.value.into()).collect();
+        // Real ticks at 10, 100, 105 and 300; heartbeats 30ns after the last tick.
+        // The 14 cycles include the start at 0 and the stale callback at 130.
+        assert_eq!(
+            times,
+            vec![10, 40, 70, 100, 105, 135, 165, 195, 225, 255, 285, 300]
+        );
+    }
+
+    fn rate_limited_times(policy: RateLimitPolicy, run_for: RunFor) -> Vec<NanoTime> {
+        // 1000 ticks/sec source capped at 100 tokens/sec with a burst of 3.
+        let limited = ticker(Duration::from_millis(1))
+            .rate_limit(100.0, 3, policy)
+            .count()
+            .collect();
+        limited
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), run_for)
+            .unwrap();
+        limited.peek_value().iter().map(|v| v.time).collect()
+    }
+
+    #[test]
+    fn node_rate_limit_drops_excess() {
+        let times = rate_limited_times(
+            RateLimitPolicy::Drop,
+            RunFor::Duration(Duration::from_millis(995)),
+        );
+        // The burst passes at once, then one tick per 10ms refill.
+        let ms = |n: u64| NanoTime::new(n * 1_000_000);
+        assert_eq!(times[..5], [ms(0), ms(1), ms(2), ms(10), ms(20)]);
+        assert_eq!(times.len(), 3 + 99);
+    }
+
+    #[test]
+    fn node_rate_limit_queues_excess() {
+        let times = rate_limited_times(RateLimitPolicy::Queue, RunFor::Cycles(2000));
+        let ms = |n: u64| NanoTime::new(n * 1_000_000);
+        assert_eq!(times[..5], [ms(0), ms(1), ms(2), ms(10), ms(20)]);
+        // Every release is spaced by at least the refill interval.
+        assert!(times[3..].windows(2).all(|w| w[1] - w[0] >= ms(10)));
+    }
+
     #[test]
     fn node_filter_gates_ticks() {
         // Source ticks every 10ns producing 1,2,3,...
@@ -0,0 +1,94 @@
+use crate::types::*;
+use derive_new::new;
+use std::rc::Rc;
+
+/// What [reject_nan](crate::nodes::FloatStreamOperators::reject_nan) does
+/// with a NaN or infinite value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NonFinitePolicy {
+    /// Drop the value, not ticking.
+    #[default]
+    Drop,
+    /// Fail the graph with an error.
+    Error,
+}
+
+/// Passes on finite values, handling NaN and infinite ones as `policy`
+/// says.  Used by [reject_nan](crate::nodes::FloatStreamOperators::reject_nan).
+#[derive(new)]
+pub(crate) struct RejectNonFiniteStream {
+    upstream: Rc<dyn Stream<f64>>,
+    policy: NonFinitePolicy,
+    #[new(default)]
+    value: f64,
+}
+
+#[node(active = [upstream], output = value: f64)]
+impl MutableNode for RejectNonFiniteStream {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        let value = self.upstream.peek_value();
+        if value.is_finite() {
+            self.value = value;
+            return Ok(true);
+        }
+        match self.policy {
+            NonFinitePolicy::Drop => Ok(false),
+            NonFinitePolicy::Error => {
+                anyhow::bail!("reject_nan: non-finite value {value} at {}", state.time())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::*;
+    use crate::nodes::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// 1.0, NaN, 2.0, inf, -inf, 3.0 at times 0 to 5.
+    fn dirty() -> Rc<dyn Stream<f64>> {
+        let src = Rc::new(RefCell::new(CallBackStream::new()));
+        let values = [1.0, f64::NAN, 2.0, f64::INFINITY, f64::NEG_INFINITY, 3.0];
+        for (i, value) in values.into_iter().enumerate() {
+            src.borrow_mut()
+                .push(ValueAt::new(value, NanoTime::new(i as u64)));
+        }
+        src.as_stream()
+    }
+
+    fn run(stream: Rc<dyn Stream<f64>>) -> anyhow::Result<Vec<(f64, u64)>> {
+        let collected = stream.collect();
+        collected.run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)?;
+        Ok(collected
+            .peek_value()
+            .into_iter()
+            .map(|v| (v.value, u64::from(v.time)))
+            .collect())
+    }
+
+    #[test]
+    fn reject_nan_drops_non_finite_values() {
+        let clean = run(dirty().reject_nan(NonFinitePolicy::Drop)).unwrap();
+        assert_eq!(clean, vec![(1.0, 0), (2.0, 2), (3.0, 5)]);
+    }
+
+    #[test]
+    fn reject_nan_can_fail_the_graph() {
+        let err = run(dirty().reject_nan(NonFinitePolicy::Error)).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("reject_nan: non-finite value NaN at"),
+            "{err:#}"
+        );
+    }
+
+    #[test]
+    fn replace_nan_fills_non_finite_values() {
+        let filled = run(dirty().replace_nan(0.0)).unwrap();
+        assert_eq!(
+            filled,
+            vec![(1.0, 0), (0.0, 1), (2.0, 2), (0.0, 3), (0.0, 4), (3.0, 5)]
+        );
+    }
+}
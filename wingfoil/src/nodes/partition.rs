@@ -0,0 +1,249 @@
+use crate::types::*;
+use itertools::Either;
+use std::cell::RefCell;
+use std::mem;
+use std::rc::{Rc, Weak};
+
+/// Splits `source` with `func`, marking just the side the value went to
+/// dirty.  Used by [partition_map](crate::nodes::StreamOperators::partition_map).
+pub(crate) fn partition_map<T, A, B, F>(
+    source: Rc<dyn Stream<T>>,
+    func: F,
+) -> (Rc<dyn Stream<A>>, Rc<dyn Stream<B>>)
+where
+    T: Element,
+    A: Element,
+    B: Element,
+    F: Fn(T) -> Either<A, B> + 'static,
+{
+    let left_slot = Rc::new(RefCell::new(A::default()));
+    let right_slot = Rc::new(RefCell::new(B::default()));
+    let parent = Rc::new(RefCell::new(PartitionParent {
+        source,
+        func,
+        left_slot: left_slot.clone(),
+        right_slot: right_slot.clone(),
+        children: vec![],
+        child_indices: None,
+    }));
+    let left = PartitionChild::new(parent.clone().as_node(), left_slot).into_stream();
+    let right = PartitionChild::new(parent.clone().as_node(), right_slot).into_stream();
+    // weak, as the children hold the parent
+    parent.borrow_mut().children = vec![
+        Rc::downgrade(&left.clone().as_node()),
+        Rc::downgrade(&right.clone().as_node()),
+    ];
+    (left, right)
+}
+
+/// Applies `func` to each source value, stashes the result in the slot of
+/// its side and marks that side's child dirty, like a demux parent.  Never
+/// ticks itself.
+struct PartitionParent<T: Element, A, B, F> {
+    source: Rc<dyn Stream<T>>,
+    func: F,
+    left_slot: Rc<RefCell<A>>,
+    right_slot: Rc<RefCell<B>>,
+    /// The left and right children.
+    children: Vec<Weak<dyn Node>>,
+    child_indices: Option<(usize, usize)>,
+}
+
+impl<T, A, B, F> MutableNode for PartitionParent<T, A, B, F>
+where
+    T: Element,
+    F: Fn(T) -> Either<A, B>,
+{
+    fn upstreams(&self) -> UpStreams {
+        UpStreams::new(vec![self.source.clone().as_node()], vec![])
+    }
+
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        let (left, right) = self
+            .child_indices
+            .expect("partition children resolved during setup");
+        match (self.func)(self.source.peek_value()) {
+            Either::Left(value) => {
+                *self.left_slot.borrow_mut() = value;
+                state.mark_dirty(left);
+            }
+            Either::Right(value) => {
+                *self.right_slot.borrow_mut() = value;
+                state.mark_dirty(right);
+            }
+        }
+        Ok(false)
+    }
+
+    fn setup(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
+        let indices = ["left", "right"]
+            .into_iter()
+            .zip(&self.children)
+            .map(|(side, child)| {
+                child
+                    .upgrade()
+                    .and_then(|child| state.node_index(child))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "the {side} side of a partition is not wired into the graph.  Wire both sides, or use filter_value for one"
+                        )
+                    })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        self.child_indices = Some((indices[0], indices[1]));
+        Ok(())
+    }
+
+    fn triggers(&self) -> Vec<Rc<dyn Node>> {
+        self.children.iter().filter_map(Weak::upgrade).collect()
+    }
+}
+
+/// One side of a partition: ticks with the value its parent stashed for it.
+#[derive(derive_new::new)]
+struct PartitionChild<X: Element> {
+    parent: Rc<dyn Node>,
+    slot: Rc<RefCell<X>>,
+    #[new(default)]
+    value: X,
+}
+
+impl<X: Element> MutableNode for PartitionChild<X> {
+    fn upstreams(&self) -> UpStreams {
+        // the parent never ticks but wire it passively to cycle after it
+        UpStreams::new(vec![], vec![self.parent.clone()])
+    }
+
+    fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+        self.value = mem::take(&mut *self.slot.borrow_mut());
+        Ok(true)
+    }
+}
+
+impl<X: Element> StreamPeekRef<X> for PartitionChild<X> {
+    fn peek_ref(&self) -> &X {
+        &self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::*;
+    use crate::nodes::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn run(nodes: Vec<Rc<dyn Node>>) {
+        Graph::new(
+            nodes,
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(6),
+        )
+        .run()
+        .unwrap();
+    }
+
+    fn values<T: Element>(stream: &Rc<dyn Stream<Vec<ValueAt<T>>>>) -> Vec<(T, u64)> {
+        stream
+            .peek_value()
+            .into_iter()
+            .map(|v| (v.value, u64::from(v.time)))
+            .collect()
+    }
+
+    #[test]
+    fn partition_ticks_exactly_one_side() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+        let (evens, odds) = ticker(Duration::from_nanos(10))
+            .count()
+            .partition(move |n| {
+                counter.set(counter.get() + 1);
+                n % 2 == 0
+            });
+        let evens = evens.collect();
+        let odds = odds.collect();
+        run(vec![evens.clone().as_node(), odds.clone().as_node()]);
+        assert_eq!(values(&evens), vec![(2, 10), (4, 30), (6, 50)]);
+        assert_eq!(values(&odds), vec![(1, 0), (3, 20), (5, 40)]);
+        assert_eq!(calls.get(), 6);
+    }
+
+    #[test]
+    fn partition_map_splits_by_type() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+        let (small, large) = ticker(Duration::from_nanos(10))
+            .count()
+            .partition_map(move |n| {
+                counter.set(counter.get() + 1);
+                if n < 4 {
+                    Either::Left(-(n as i32))
+                } else {
+                    Either::Right(format!("big {n}"))
+                }
+            });
+        let small = small.collect();
+        let large = large.collect();
+        run(vec![small.clone().as_node(), large.clone().as_node()]);
+        assert_eq!(values(&small), vec![(-1, 0), (-2, 10), (-3, 20)]);
+        assert_eq!(
+            values(&large),
+            vec![
+                ("big 4".to_string(), 30),
+                ("big 5".to_string(), 40),
+                ("big 6".to_string(), 50),
+            ]
+        );
+        assert_eq!(calls.get(), 6);
+    }
+
+    #[test]
+    fn unwired_side_is_an_error() {
+        let (evens, _odds) = ticker(Duration::from_nanos(10))
+            .count()
+            .partition(|n| n % 2 == 0);
+        let err = evens
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(1))
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("the right side of a partition is not wired"),
+            "{err:#}"
+        );
+        // likewise once the side is dropped
+        let (_, odds) = ticker(Duration::from_nanos(10))
+            .count()
+            .partition(|n| n % 2 == 0);
+        let err = odds
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(1))
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("the left side of a partition is not wired"),
+            "{err:#}"
+        );
+    }
+
+    #[test]
+    fn triggers_survive_setup() {
+        let (evens, odds) = ticker(Duration::from_nanos(10))
+            .count()
+            .partition(|n| n % 2 == 0);
+        let mut graph = Graph::new(
+            vec![evens.as_node(), odds.as_node()],
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(2),
+        );
+        graph.validate().unwrap();
+        graph.run().unwrap();
+        graph.validate().unwrap();
+    }
+
+    #[test]
+    fn unbuilt_partition_is_freed() {
+        let source = ticker(Duration::from_nanos(10)).count();
+        let sides = source.partition(|n| n % 2 == 0);
+        assert!(Rc::strong_count(&source) > 1);
+        drop(sides);
+        assert_eq!(Rc::strong_count(&source), 1);
+    }
+}
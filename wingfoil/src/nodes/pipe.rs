@@ -0,0 +1,218 @@
+use crate::channel::{ChannelSender, NotifierChannelSender, channel_pair};
+use crate::*;
+
+use std::rc::Rc;
+
+/// One receiving end of a pipe.
+///
+/// The receiving graph may start after this one, so in real-time mode its
+/// ready-notifier is picked up lazily from `notifier_rx` on first send.
+struct PipeSender<T: Element + Send> {
+    sender: ChannelSender<T>,
+    notifier_rx: Option<kanal::Receiver<ReadyNotifier>>,
+}
+
+impl<T: Element + Send> PipeSender<T> {
+    fn adopt_notifier(&mut self) {
+        let notifier = self
+            .notifier_rx
+            .as_ref()
+            .and_then(|rx| rx.try_recv().ok().flatten());
+        if let Some(notifier) = notifier {
+            self.sender.set_notifier(notifier);
+            self.notifier_rx = None;
+        }
+    }
+}
+
+/// Sending half of a pipe.  Forwards each tick of `source` onto every
+/// channel and closes them with
+/// [`EndOfStream`](crate::channel::Message::EndOfStream) on stop, so the
+/// receiving graphs shut down cleanly.
+struct PipeSenderNode<T: Element + Send> {
+    source: Rc<dyn Stream<T>>,
+    senders: Vec<PipeSender<T>>,
+}
+
+impl<T: Element + Send> MutableNode for PipeSenderNode<T> {
+    fn upstreams(&self) -> UpStreams {
+        UpStreams::new(vec![self.source.clone().as_node()], vec![])
+    }
+
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        let realtime = state.run_mode() == RunMode::RealTime;
+        let value = self.source.peek_value();
+        for sender in &mut self.senders {
+            if realtime {
+                sender.adopt_notifier();
+            }
+            sender
+                .sender
+                .send(state, value.clone())
+                .map_err(|e| anyhow::anyhow!("pipe receiver disconnected: {e}"))?;
+        }
+        Ok(true)
+    }
+
+    fn stop(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
+        let realtime = state.run_mode() == RunMode::RealTime;
+        for sender in &mut self.senders {
+            if realtime {
+                sender.adopt_notifier();
+            }
+            sender.sender.close()?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds the receiving `Stream<Burst<T>>` of a pipe inside another graph.
+pub type PipeReceiver<T> = Box<dyn FnOnce() -> Rc<dyn Stream<Burst<T>>> + Send>;
+
+/// Pipes `stream` to `n` receivers, each with its own channel.
+pub(crate) fn pipe_broadcast<T: Element + Send>(
+    stream: Rc<dyn Stream<T>>,
+    n: usize,
+) -> (Rc<dyn Node>, Vec<PipeReceiver<T>>) {
+    let (senders, receivers) = (0..n)
+        .map(|_| {
+            let (sender, receiver) = channel_pair(None, None);
+            let (notifier_tx, notifier_rx): (NotifierChannelSender, _) = kanal::bounded(1);
+            let sender = PipeSender {
+                sender,
+                notifier_rx: Some(notifier_rx),
+            };
+            let recv: PipeReceiver<T> = Box::new(move || {
+                ChannelReceiverStream::new(receiver, None, Some(notifier_tx)).into_stream()
+            });
+            (sender, recv)
+        })
+        .unzip();
+    let send = PipeSenderNode {
+        source: stream,
+        senders,
+    }
+    .into_node();
+    (send, receivers)
+}
+
+/// Pipes a [Stream] from this graph into another graph, typically running on
+/// another thread, over an in-process channel.
+///
+/// Returns the sending [Node], to be added to this graph, and a factory that
+/// builds the receiving `Stream<Burst<T>>` inside the other graph.  Values
+/// carry the sender's engine time, so in [RunMode::HistoricalFrom] the
+/// receiving graph replays them at the same times regardless of thread
+/// scheduling.  Both graphs must use the same [RunMode].
+///
+/// The receiving graph waits for the sender to close the channel on
+/// teardown, so the sending graph should not outlive it by much.
+/// ```
+/// # use wingfoil::*;
+/// # use std::time::Duration;
+/// let (send, recv) = pipe_local(ticker(Duration::from_millis(10)).count());
+/// let worker = std::thread::spawn(move || {
+///     let received = recv().collapse().collect();
+///     received
+///         .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+///         .map(|_| received.peek_value().len())
+/// });
+/// send.run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+///     .unwrap();
+/// assert_eq!(worker.join().unwrap().unwrap(), 3);
+/// ```
+pub fn pipe_local<T: Element + Send>(
+    stream: Rc<dyn Stream<T>>,
+) -> (
+    Rc<dyn Node>,
+    impl FnOnce() -> Rc<dyn Stream<Burst<T>>> + Send + 'static,
+) {
+    let (send, mut receivers) = pipe_broadcast(stream, 1);
+    let recv = receivers
+        .pop()
+        .expect("invariant: pipe_broadcast returns n receivers");
+    (send, recv)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::rc::Rc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn pipe_local_matches_single_graph_results() {
+        let run_mode = RunMode::HistoricalFrom(NanoTime::ZERO);
+        let period = Duration::from_millis(100);
+        let source = move || ticker(period).count().limit(6);
+        let scale = |src: Rc<dyn Stream<u64>>| src.map(|x| x * 10);
+
+        let run_for = RunFor::Duration(period * 10);
+        let expected = scale(source()).collect();
+        expected.run(run_mode, run_for.clone()).unwrap();
+
+        let (send, recv) = pipe_local(source());
+        let worker = thread::spawn(move || {
+            let received = scale(recv().collapse()).collect();
+            received.run(run_mode, RunFor::Forever)?;
+            anyhow::Ok(received.peek_value())
+        });
+        send.run(run_mode, run_for).unwrap();
+        let actual = worker.join().unwrap().unwrap();
+
+        assert_eq!(actual, expected.peek_value());
+        assert_eq!(actual.len(), 6);
+    }
+
+    #[test]
+    fn broadcast_channels_deliver_every_tick_to_each_receiver() {
+        let run_mode = RunMode::HistoricalFrom(NanoTime::ZERO);
+        let period = Duration::from_millis(100);
+        let (send, receivers) = ticker(period).count().broadcast_channels(3);
+        let workers: Vec<_> = receivers
+            .into_iter()
+            .map(|recv| {
+                thread::spawn(move || {
+                    let received = recv().collapse().collect();
+                    received.run(run_mode, RunFor::Forever)?;
+                    anyhow::Ok(received.peek_value())
+                })
+            })
+            .collect();
+        send.run(run_mode, RunFor::Cycles(5)).unwrap();
+        let expected: Vec<_> = (1..=5)
+            .map(|i| ValueAt::new(i, NanoTime::new((i - 1) * period.as_nanos() as u64)))
+            .collect();
+        for worker in workers {
+            assert_eq!(worker.join().unwrap().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn pipe_local_realtime_delivers_values() {
+        let (send, recv) = pipe_local(ticker(Duration::from_millis(10)).count());
+        let worker = thread::spawn(move || {
+            let received = recv().collect();
+            received.run(
+                RunMode::RealTime,
+                RunFor::Duration(Duration::from_millis(300)),
+            )?;
+            anyhow::Ok(received.peek_value())
+        });
+        send.run(
+            RunMode::RealTime,
+            RunFor::Duration(Duration::from_millis(100)),
+        )
+        .unwrap();
+        let values: Vec<u64> = worker
+            .join()
+            .unwrap()
+            .unwrap()
+            .into_iter()
+            .flat_map(|burst| burst.value)
+            .collect();
+        assert!(!values.is_empty());
+        assert_eq!(values, (1..=values.len() as u64).collect::<Vec<_>>());
+    }
+}
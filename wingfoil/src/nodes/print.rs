@@ -1,24 +1,93 @@
 use crate::types::*;
 
+use serde::Serialize;
+use std::fmt::Debug;
+use std::io::Write;
 use std::ops::Drop;
 use std::rc::Rc;
 
-/// Propagates input and also pushes into buffer which is printed
-/// on Drop.
+/// Length, in chars, beyond which [print](crate::nodes::StreamOperators::print)
+/// and [logged](crate::nodes::StreamOperators::logged) truncate a value.
+pub const DEFAULT_MAX_LEN: usize = 256;
+
+/// Cuts `text` down to `max_len` chars, noting how many were dropped,
+/// e.g. `"[1, 2, 3…(+9744 chars)"`.
+pub fn truncate(text: String, max_len: usize) -> String {
+    match text.char_indices().nth(max_len) {
+        None => text,
+        Some((cut, _)) => {
+            let dropped = text[cut..].chars().count();
+            format!("{}…(+{dropped} chars)", &text[..cut])
+        }
+    }
+}
+
+/// A formatter for [print_with](crate::nodes::StreamOperators::print_with) and
+/// [logged_with](crate::nodes::StreamOperators::logged_with): the `Debug`
+/// output [truncated](truncate) to `max_len` chars.
+pub fn debug_truncated<T: Debug>(max_len: usize) -> impl Fn(&T) -> String {
+    move |value| truncate(format!("{value:?}"), max_len)
+}
+
+/// One line of [logged_json](crate::nodes::StreamOperators::logged_json):
+/// `{"time":..,"label":..,"value":..}`, with `time` in nanoseconds.  A value
+/// that fails to serialize is logged as a string describing the error.
+pub(crate) fn json_log_line<T: Serialize>(time: NanoTime, label: &str, value: &T) -> String {
+    #[derive(Serialize)]
+    struct Line<'a, V> {
+        time: NanoTime,
+        label: &'a str,
+        value: V,
+    }
+    serde_json::to_string(&Line { time, label, value }).unwrap_or_else(|err| {
+        let value = format!("<unserializable: {err}>");
+        serde_json::to_string(&Line { time, label, value })
+            .expect("a string value always serializes")
+    })
+}
+
+/// Propagates input and also pushes into buffer which is written out,
+/// one formatted line per value, when the graph stops.
 pub struct PrintStream<T: Element> {
     upstream: Rc<dyn Stream<T>>,
+    formatter: Box<dyn Fn(&T) -> String>,
+    writer: Box<dyn Write>,
     buffer: Vec<T>,
     value: T,
 }
 
 impl<T: Element> PrintStream<T> {
+    /// Prints the `Debug` output of each value to stdout, truncated to
+    /// [DEFAULT_MAX_LEN] chars.
     pub fn new(upstream: Rc<dyn Stream<T>>) -> PrintStream<T> {
+        PrintStream::with(
+            upstream,
+            Box::new(debug_truncated(DEFAULT_MAX_LEN)),
+            Box::new(std::io::stdout()),
+        )
+    }
+
+    /// Writes `formatter`'s output for each value to `writer`.
+    pub fn with(
+        upstream: Rc<dyn Stream<T>>,
+        formatter: Box<dyn Fn(&T) -> String>,
+        writer: Box<dyn Write>,
+    ) -> PrintStream<T> {
         PrintStream {
             upstream,
+            formatter,
+            writer,
             buffer: Vec::with_capacity(1000),
             value: T::default(),
         }
     }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        for val in self.buffer.drain(..) {
+            writeln!(self.writer, "{}", (self.formatter)(&val))?;
+        }
+        self.writer.flush()
+    }
 }
 
 #[node(active = [upstream], output = value: T)]
@@ -28,24 +97,47 @@ impl<T: Element> MutableNode for PrintStream<T> {
         self.buffer.push(self.value.clone());
         Ok(true)
     }
+
+    fn stop(&mut self, _state: &mut GraphState) -> anyhow::Result<()> {
+        self.flush()?;
+        Ok(())
+    }
 }
 
 impl<T: Element> Drop for PrintStream<T> {
     fn drop(&mut self) {
-        for val in self.buffer.iter() {
-            println!("{val:?}");
-        }
+        // Only non-empty if the graph never stopped, e.g. it failed to start.
+        let _ = self.flush();
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::graph::*;
     use crate::nodes::*;
     use crate::types::NanoTime;
-    use std::cell::RefCell;
+    use std::cell::{Cell, RefCell};
     use std::rc::Rc;
 
+    #[derive(Clone, Default)]
+    struct SharedBuf(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuf {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.borrow().clone()).unwrap()
+        }
+    }
+
     #[test]
     fn print_passes_through_values() {
         let src: Rc<RefCell<CallBackStream<u64>>> = Rc::new(RefCell::new(CallBackStream::new()));
@@ -60,4 +152,99 @@ mod tests {
         let vals: Vec<u64> = collected.peek_value().iter().map(|v| v.value).collect();
         assert_eq!(vals, vec![1, 2, 3]);
     }
+
+    #[test]
+    fn json_log_line_shape() {
+        #[derive(Serialize)]
+        struct Fill {
+            price: f64,
+            qty: u32,
+            side: &'static str,
+        }
+        let fill = Fill {
+            price: 101.5,
+            qty: 3,
+            side: "buy",
+        };
+        let line = json_log_line(NanoTime::new(1_500), "fills", &fill);
+        assert_eq!(
+            line,
+            r#"{"time":1500,"label":"fills","value":{"price":101.5,"qty":3,"side":"buy"}}"#
+        );
+    }
+
+    #[test]
+    fn json_log_line_reports_unserializable_values() {
+        let mut map = std::collections::HashMap::new();
+        map.insert((1, 2), "tuple keys are not valid JSON");
+        let line: serde_json::Value =
+            serde_json::from_str(&json_log_line(NanoTime::ZERO, "bad", &map)).unwrap();
+        assert_eq!(line["label"], "bad");
+        assert!(
+            line["value"]
+                .as_str()
+                .unwrap()
+                .starts_with("<unserializable")
+        );
+    }
+
+    #[test]
+    fn truncation_boundaries() {
+        let exact = "x".repeat(DEFAULT_MAX_LEN);
+        assert_eq!(truncate(exact.clone(), DEFAULT_MAX_LEN), exact);
+        let over = "x".repeat(DEFAULT_MAX_LEN + 1);
+        assert_eq!(
+            truncate(over, DEFAULT_MAX_LEN),
+            format!("{exact}…(+1 chars)")
+        );
+        // Cuts on char, not byte, boundaries.
+        assert_eq!(truncate("ééé".to_string(), 2), "éé…(+1 chars)");
+    }
+
+    #[test]
+    fn default_print_truncates_long_values() {
+        let out = SharedBuf::default();
+        let fills = ticker(Duration::from_nanos(100)).produce(|| vec![0u8; 10_000]);
+        let printed = PrintStream::with(
+            fills,
+            Box::new(debug_truncated(DEFAULT_MAX_LEN)),
+            Box::new(out.clone()),
+        )
+        .into_stream();
+        printed
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(1))
+            .unwrap();
+        let text = out.text();
+        let full_len = format!("{:?}", vec![0u8; 10_000]).chars().count();
+        assert_eq!(
+            text,
+            format!(
+                "{}…(+{} chars)\n",
+                &format!("{:?}", vec![0u8; 10_000])[..DEFAULT_MAX_LEN],
+                full_len - DEFAULT_MAX_LEN
+            )
+        );
+    }
+
+    #[test]
+    fn custom_formatter_is_called_once_per_tick() {
+        let out = SharedBuf::default();
+        let calls = Rc::new(Cell::new(0));
+        let calls2 = calls.clone();
+        let count = ticker(Duration::from_nanos(100)).count();
+        let printed = PrintStream::with(
+            count,
+            Box::new(move |n: &u64| {
+                calls2.set(calls2.get() + 1);
+                format!("n={n}")
+            }),
+            Box::new(out.clone()),
+        )
+        .into_stream();
+        printed
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+            .unwrap();
+        assert_eq!(out.text(), "n=1\nn=2\nn=3\n");
+        assert_eq!(calls.get(), 3);
+    }
 }
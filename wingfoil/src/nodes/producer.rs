@@ -23,6 +23,26 @@ impl<T: Element> MutableNode for ProducerStream<T> {
     }
 }
 
+/// Like [ProducerStream] but with a fallible closure.  An `Err` fails the
+/// graph run.  Used by
+/// [produce_result](crate::nodes::NodeOperators::produce_result).
+#[derive(new)]
+pub(crate) struct TryProducerStream<T: Element> {
+    upstream: Rc<dyn Node>,
+    func: Box<dyn Fn() -> anyhow::Result<T>>,
+    #[new(default)]
+    value: T,
+}
+
+#[node(active = [upstream], output = value: T)]
+impl<T: Element> MutableNode for TryProducerStream<T> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        self.value =
+            (self.func)().map_err(|e| e.context(format!("producer failed at {}", state.time())))?;
+        Ok(true)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::graph::*;
@@ -66,4 +86,38 @@ mod tests {
         let vals: Vec<u64> = collected.peek_value().iter().map(|v| v.value).collect();
         assert_eq!(vals, vec![1, 2, 3]);
     }
+
+    #[test]
+    fn produce_result_emits_ok_values() {
+        let produced = ticker(Duration::from_nanos(100))
+            .produce_result(|| Ok(7u64))
+            .collect();
+        produced
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(2))
+            .unwrap();
+        let vals: Vec<u64> = produced.peek_value().iter().map(|v| v.value).collect();
+        assert_eq!(vals, vec![7, 7]);
+    }
+
+    #[test]
+    fn produce_result_error_fails_the_run() {
+        let calls = Rc::new(RefCell::new(0u64));
+        let calls2 = calls.clone();
+        let produced = ticker(Duration::from_nanos(100)).produce_result(move || {
+            let mut n = calls2.borrow_mut();
+            *n += 1;
+            if *n == 3 {
+                anyhow::bail!("gauge unreadable");
+            }
+            Ok(*n)
+        });
+        let err = produced
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(10))
+            .unwrap_err();
+        let message = format!("{err:#}");
+        assert!(message.contains("gauge unreadable"), "{message}");
+        assert!(message.contains("producer failed at"), "{message}");
+        assert_eq!(*calls.borrow(), 3);
+        assert_eq!(produced.peek_value(), 2);
+    }
 }
@@ -27,9 +27,10 @@ impl<T: Element + Send> State<T> {
     /// Check whether the producer thread is still healthy.
     ///
     /// `finished` reports whether the channel has drained a
-    /// [`Message::EndOfStream`](crate::channel::Message::EndOfStream): a thread
-    /// that returns `Ok(())` after signalling end-of-stream has shut down
-    /// cleanly and is not treated as an error.
+    /// [`Message::EndOfStream`](crate::channel::Message::EndOfStream), or still
+    /// holds undelivered messages that may include it: a thread that returns
+    /// `Ok(())` after signalling end-of-stream has shut down cleanly and is not
+    /// treated as an error.
     pub fn check_running(&mut self, finished: bool) -> anyhow::Result<()> {
         match self {
             State::JoinHandle(handle) if handle.is_finished() => {
@@ -92,7 +93,10 @@ impl<T: Element + Send> MutableNode for ReceiverStream<T> {
         // checked first, a still-running thread would skip error handling, and
         // nothing would wake the graph once the sender drops.
         let cycle_result = self.inner.cycle(state)?;
-        if let Err(thread_err) = self.state.check_running(self.inner.finished()) {
+        // In historical mode the receiver only reads up to the engine time, so
+        // the end-of-stream may still be buffered when the thread exits.
+        let finished = self.inner.finished() || self.inner.has_buffered();
+        if let Err(thread_err) = self.state.check_running(finished) {
             self.pending_err = Some(thread_err);
             // Self-notify: schedule one more graph cycle to propagate the error.
             if let Some(notifier) = &self.notifier {
@@ -0,0 +1,154 @@
+use crate::types::*;
+
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Delay schedule for [with_retry](crate::nodes::StreamOperators::with_retry):
+/// after the `n`th failed attempt the next is made `base * 2^(n - 1)` later,
+/// capped at `max`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExponentialBackoff {
+    pub base: Duration,
+    pub max: Duration,
+}
+
+impl ExponentialBackoff {
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self { base, max }
+    }
+
+    /// Delay before retrying after `attempt` (1-based) has failed.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u32
+            .checked_shl(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        self.base.saturating_mul(factor).min(self.max)
+    }
+}
+
+/// Emits each upstream value as `(value, 1)` and re-emits it with an
+/// incremented attempt number, after an [ExponentialBackoff] delay, whenever
+/// `failed` ticks.  Used by [with_retry](crate::nodes::StreamOperators::with_retry).
+pub(crate) struct ExponentialBackoffStream<T: Element> {
+    upstream: Rc<dyn Stream<T>>,
+    failed: Rc<dyn Node>,
+    max_attempts: u32,
+    backoff: ExponentialBackoff,
+    retry_at: Option<NanoTime>,
+    value: (T, u32),
+}
+
+impl<T: Element> ExponentialBackoffStream<T> {
+    pub fn new(
+        upstream: Rc<dyn Stream<T>>,
+        failed: Rc<dyn Node>,
+        max_attempts: u32,
+        backoff: ExponentialBackoff,
+    ) -> Self {
+        Self {
+            upstream,
+            failed,
+            max_attempts,
+            backoff,
+            retry_at: None,
+            value: (T::default(), 0),
+        }
+    }
+}
+
+#[node(active = [upstream, failed], output = value: (T, u32))]
+impl<T: Element> MutableNode for ExponentialBackoffStream<T> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        let now = state.time();
+        if state.ticked(self.upstream.clone().as_node()) {
+            // a new value supersedes any retry still pending for the old one
+            self.retry_at = None;
+            self.value = (self.upstream.peek_value(), 1);
+            return Ok(true);
+        }
+        if state.ticked(self.failed.clone()) {
+            let attempt = self.value.1;
+            anyhow::ensure!(
+                attempt < self.max_attempts,
+                "giving up on {:?} after {attempt} attempts",
+                self.value.0
+            );
+            let retry_at = now + NanoTime::new(self.backoff.delay(attempt).as_nanos() as u64);
+            self.retry_at = Some(retry_at);
+            state.schedule_at_self(retry_at);
+        }
+        if self.retry_at == Some(now) {
+            self.retry_at = None;
+            self.value.1 += 1;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::*;
+    use crate::nodes::*;
+    use crate::queue::ValueAt;
+    use std::cell::RefCell;
+
+    /// Runs `with_retry` on a single value at t=0, where each attempt fails
+    /// while `fails(attempt)` is true.
+    fn run_retry(
+        max_attempts: u32,
+        fails: impl Fn(u32) -> bool + 'static,
+    ) -> (anyhow::Result<()>, Vec<ValueAt<(&'static str, u32)>>) {
+        let src = Rc::new(RefCell::new(CallBackStream::new()));
+        src.borrow_mut().push(ValueAt::new("order", NanoTime::ZERO));
+        let (tx, failed) = feedback_node();
+        let backoff = ExponentialBackoff::new(Duration::from_nanos(100), Duration::from_nanos(150));
+        let attempts = src.as_stream().with_retry(max_attempts, backoff, failed);
+        let report = attempts
+            .map(move |(_, attempt)| fails(attempt))
+            .filter_value(|failed| *failed)
+            .as_node()
+            .feedback(tx);
+        let collected = attempts.collect();
+        let result = Graph::new(
+            vec![collected.clone().as_node(), report],
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Forever,
+        )
+        .run();
+        (result, collected.peek_value())
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let backoff = ExponentialBackoff::new(Duration::from_millis(10), Duration::from_millis(50));
+        let delays: Vec<_> = (1..=5).map(|n| backoff.delay(n).as_millis()).collect();
+        assert_eq!(delays, vec![10, 20, 40, 50, 50]);
+        assert_eq!(backoff.delay(100), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn with_retry_reemits_until_success() {
+        let (result, attempts) = run_retry(5, |attempt| attempt < 3);
+        result.unwrap();
+        // failures are fed back 1ns after each attempt, then retried after
+        // 100ns and min(200ns, 150ns)
+        assert_eq!(
+            attempts,
+            vec![
+                ValueAt::new(("order", 1), NanoTime::new(0)),
+                ValueAt::new(("order", 2), NanoTime::new(101)),
+                ValueAt::new(("order", 3), NanoTime::new(252)),
+            ]
+        );
+    }
+
+    #[test]
+    fn with_retry_fails_graph_after_max_attempts() {
+        let (result, attempts) = run_retry(3, |_| true);
+        let err = format!("{:?}", result.unwrap_err());
+        assert!(err.contains("after 3 attempts"), "{err}");
+        assert_eq!(attempts.len(), 3);
+    }
+}
@@ -15,18 +15,45 @@ pub struct SampleStream<T: Element> {
 
 #[node(passive = [upstream], active = [trigger], output = value: T)]
 impl<T: Element> MutableNode for SampleStream<T> {
-    fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        state.ensure_ticked_ever(&self.upstream)?;
         self.value = self.upstream.peek_value();
         Ok(true)
     }
 }
 
+/// On each tick of its trigger, combines the latest source value with the
+/// trigger's value.  Used by
+/// [sample_with](crate::nodes::StreamOperators::sample_with).
+#[derive(new)]
+pub(crate) struct SampleWithStream<T: Element, U: Element, OUT: Element> {
+    upstream: Rc<dyn Stream<T>>,
+    trigger: Rc<dyn Stream<U>>,
+    combine: Box<dyn Fn(&T, &U) -> OUT>,
+    #[new(default)]
+    value: OUT,
+}
+
+#[node(passive = [upstream], active = [trigger], output = value: OUT)]
+impl<T: Element, U: Element, OUT: Element> MutableNode for SampleWithStream<T, U, OUT> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        state.ensure_ticked_ever(&self.upstream)?;
+        self.value = (self.combine)(
+            &self.upstream.peek_ref_cell(),
+            &self.trigger.peek_ref_cell(),
+        );
+        Ok(true)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
     use crate::graph::*;
     use crate::nodes::*;
+    use crate::queue::ValueAt;
+    use std::cell::RefCell;
 
     #[test]
     fn sample_works() {
@@ -48,4 +75,87 @@ mod tests {
         .run()
         .unwrap();
     }
+
+    fn stream_of(values: &[(u64, u64)]) -> Rc<dyn Stream<u64>> {
+        let src = Rc::new(RefCell::new(CallBackStream::new()));
+        for (time, value) in values {
+            src.borrow_mut()
+                .push(ValueAt::new(*value, NanoTime::new(*time)));
+        }
+        src.as_stream()
+    }
+
+    fn sampled(stream: Rc<dyn Stream<u64>>) -> Vec<(u64, u64)> {
+        let collected = stream.collect();
+        collected
+            .run(
+                RunMode::HistoricalFrom(NanoTime::ZERO),
+                RunFor::Duration(Duration::from_nanos(30)),
+            )
+            .unwrap();
+        collected
+            .peek_value()
+            .iter()
+            .map(|v| (u64::from(v.time), v.value))
+            .collect()
+    }
+
+    #[test]
+    fn sample_trigger_by_period_matches_ticker() {
+        let price = || stream_of(&[(5, 100), (12, 101), (25, 102)]);
+        // the default before the first price
+        let expected = vec![(0, 0), (10, 100), (20, 101), (30, 102), (40, 102)];
+        let by_period = price().sample_trigger(Trigger::Period(Duration::from_nanos(10)));
+        assert_eq!(sampled(by_period), expected);
+        let by_ticker = price().sample(ticker(Duration::from_nanos(10)));
+        assert_eq!(sampled(by_ticker), expected);
+        let by_duration = price().sample_trigger(Duration::from_nanos(10));
+        assert_eq!(sampled(by_duration), expected);
+    }
+
+    #[test]
+    fn sample_trigger_by_node() {
+        let price = stream_of(&[(5, 100), (12, 101), (25, 102)]);
+        let fills = stream_of(&[(7, 1), (8, 1), (26, 1)]).as_node();
+        let sampled = sampled(price.sample_trigger(Trigger::Node(fills)));
+        assert_eq!(sampled, vec![(7, 100), (8, 100), (26, 102)]);
+    }
+
+    #[test]
+    fn sample_with_strict_uninitialized_rejects_unticked_source() {
+        let notional = |strict: bool| {
+            let price = stream_of(&[(10, 100)]);
+            let volume = stream_of(&[(5, 1), (20, 5)]);
+            let notional = price.sample_with(volume, |price, volume| price * volume);
+            Graph::builder().strict_uninitialized(strict).build(
+                vec![notional.as_node()],
+                RunMode::HistoricalFrom(NanoTime::ZERO),
+                RunFor::Forever,
+            )
+        };
+        notional(false).run().unwrap();
+        let err = format!("{:#}", notional(true).run().unwrap_err());
+        assert!(err.contains("before it ever ticked"), "{err}");
+    }
+
+    #[test]
+    fn sample_with_combines_price_with_trigger_volume() {
+        let price = stream_of(&[(10, 100), (30, 102), (35, 103)]);
+        let volume = stream_of(&[(5, 1), (20, 5), (40, 2), (50, 3)]);
+        let notional = price
+            .sample_with(volume, |price, volume| price * volume)
+            .collect();
+        notional
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        let expected = vec![
+            // no price yet, so the default
+            ValueAt::new(0, NanoTime::new(5)),
+            ValueAt::new(500, NanoTime::new(20)),
+            // price ticks alone don't emit; the latest, 103, is used
+            ValueAt::new(206, NanoTime::new(40)),
+            ValueAt::new(309, NanoTime::new(50)),
+        ];
+        assert_eq!(notional.peek_value(), expected);
+    }
 }
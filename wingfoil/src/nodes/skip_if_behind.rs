@@ -0,0 +1,79 @@
+use crate::types::*;
+use derive_new::new;
+use std::rc::Rc;
+
+/// Drops upstream values while the graph [is behind](GraphState::is_behind),
+/// so that costly nodes downstream are skipped until it catches up.
+#[derive(new)]
+pub(crate) struct SkipIfBehindStream<T: Element> {
+    upstream: Rc<dyn Stream<T>>,
+    #[new(default)]
+    value: T,
+}
+
+#[node(active = [upstream], output = value: T)]
+impl<T: Element> MutableNode for SkipIfBehindStream<T> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        if state.is_behind() {
+            return Ok(false);
+        }
+        self.value = self.upstream.peek_value();
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::*;
+    use crate::nodes::*;
+    use crate::{MockClock, NanoTime};
+
+    /// Ticks 1, 2, 3, ... every 10ns in realtime on a mock clock, where
+    /// computing 3 takes 50ns, then sheds load with a 20ns budget.
+    fn run_with_slow_tick(budget: Option<Duration>, run_mode: RunMode) -> Vec<u64> {
+        let clock = MockClock::new(NanoTime::new(1_000));
+        let slow_clock = clock.clone();
+        let values = ticker(Duration::from_nanos(10))
+            .count()
+            .map(move |n| {
+                if n == 3 {
+                    slow_clock.advance(Duration::from_nanos(50));
+                }
+                n
+            })
+            .skip_if_behind()
+            .collect();
+        let mut builder = Graph::builder().with_clock(clock);
+        if let Some(budget) = budget {
+            builder = builder.lag_budget(budget);
+        }
+        builder
+            .build(vec![values.clone().as_node()], run_mode, RunFor::Cycles(10))
+            .run()
+            .unwrap();
+        values.peek_value().iter().map(|v| v.value).collect()
+    }
+
+    #[test]
+    fn sheds_ticks_until_caught_up() {
+        // 3 finishes 50ns late, and the timers for 4 and 5 fire 40ns and
+        // 30ns late; by 6 the graph is back within budget.
+        let values = run_with_slow_tick(Some(Duration::from_nanos(20)), RunMode::RealTime);
+        assert_eq!(values, vec![1, 2, 6, 7, 8, 9, 10]);
+    }
+
+    #[test]
+    fn passes_everything_without_a_budget() {
+        let values = run_with_slow_tick(None, RunMode::RealTime);
+        assert_eq!(values, (1..=10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn never_behind_in_historical_mode() {
+        let values = run_with_slow_tick(
+            Some(Duration::from_nanos(20)),
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+        );
+        assert_eq!(values, (1..=10).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,88 @@
+//! Golden-file support: write a collected stream to disk and play it back.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::rc::Rc;
+
+use anyhow::Context;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::nodes::SimpleIteratorStream;
+use crate::queue::ValueAt;
+use crate::types::*;
+
+/// Writes `values` to `path` as a pretty-printed JSON array of
+/// `{"value": .., "time": ..}` records.  Used by
+/// [collect_to_file](crate::nodes::StreamOperators::collect_to_file).
+pub(crate) fn write_collected<T: Serialize>(
+    path: &str,
+    values: &[ValueAt<T>],
+) -> anyhow::Result<()> {
+    let file = File::create(path).with_context(|| format!("failed to create {path}"))?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer_pretty(&mut writer, values)
+        .with_context(|| format!("failed to write {path}"))?;
+    writer.write_all(b"\n")?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Plays back a file written by
+/// [collect_to_file](crate::nodes::StreamOperators::collect_to_file),
+/// ticking each recorded value at its recorded time.  The file is read
+/// eagerly, so a missing or malformed file is reported here rather than
+/// when the graph runs.
+pub fn replay<T>(path: &str) -> anyhow::Result<Rc<dyn Stream<T>>>
+where
+    T: Element + DeserializeOwned,
+{
+    let file = File::open(path).with_context(|| format!("failed to open {path}"))?;
+    let values: Vec<ValueAt<T>> = serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("failed to parse {path}"))?;
+    Ok(SimpleIteratorStream::new(Box::new(values.into_iter())).into_stream())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::*;
+    use crate::nodes::*;
+    use std::time::Duration;
+
+    #[test]
+    fn collected_file_round_trips_via_replay() {
+        let path = std::env::temp_dir().join(format!(
+            "wingfoil_snapshot_test_{}.json",
+            std::process::id()
+        ));
+        let path = path.to_str().unwrap();
+
+        let source = ticker(Duration::from_nanos(100))
+            .count()
+            .map(|n| format!("tick {n}"));
+        source
+            .collect_to_file(path)
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(4))
+            .unwrap();
+
+        let replayed = replay::<String>(path).unwrap().collect();
+        replayed
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        let replayed: Vec<(String, NanoTime)> = replayed
+            .peek_value()
+            .into_iter()
+            .map(|v| (v.value, v.time))
+            .collect();
+        let expected: Vec<(String, NanoTime)> = (1..=4)
+            .map(|n| (format!("tick {n}"), NanoTime::new((n - 1) * 100)))
+            .collect();
+        assert_eq!(replayed, expected);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn replay_reports_missing_file() {
+        assert!(replay::<u64>("/nonexistent/golden.json").is_err());
+    }
+}
@@ -0,0 +1,395 @@
+use crate::types::*;
+
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::mem;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// A periodic schedule, from [Scheduler::every] or [Scheduler::every_between].
+struct Periodic {
+    next: NanoTime,
+    period: u64,
+    /// Exclusive.
+    to: Option<NanoTime>,
+}
+
+impl Periodic {
+    /// Moves `next` past `time`, keeping it on the period's grid.  Returns
+    /// false once the schedule has ended.
+    fn advance_past(&mut self, time: NanoTime) -> bool {
+        if self.next <= time {
+            let behind = u64::from(time) - u64::from(self.next);
+            self.next = self.next + NanoTime::new((behind / self.period + 1) * self.period);
+        }
+        self.to.is_none_or(|to| self.next < to)
+    }
+}
+
+/// The times a [source_fn](crate::nodes::source_fn) asks to be called at,
+/// given to its `schedule` closure when the graph starts.  Times before the
+/// start of the run are dropped.
+pub struct Scheduler {
+    start: NanoTime,
+    end: Option<NanoTime>,
+    times: Vec<NanoTime>,
+    periodic: Vec<Periodic>,
+    handles: Vec<Reschedule>,
+}
+
+impl Scheduler {
+    /// When the run starts: the historical start time, or now in realtime.
+    pub fn start_time(&self) -> NanoTime {
+        self.start
+    }
+
+    /// When the run ends, if it is bounded.  See [GraphState::end_time].
+    pub fn end_time(&self) -> Option<NanoTime> {
+        self.end
+    }
+
+    /// Calls the source once at `time`.
+    pub fn at(&mut self, time: NanoTime) -> &mut Self {
+        self.times.push(time);
+        self
+    }
+
+    /// Calls the source at start time and then every `period`.
+    pub fn every(&mut self, period: Duration) -> &mut Self {
+        self.every_from(period, self.start, None)
+    }
+
+    /// Calls the source every `period` from `from` until, but not
+    /// including, `to`, e.g. through a trading session.  Periods that would
+    /// fall before the start of the run are skipped.
+    pub fn every_between(&mut self, period: Duration, from: NanoTime, to: NanoTime) -> &mut Self {
+        self.every_from(period, from, Some(to))
+    }
+
+    /// Also calls the source at the times given to `handle`, which its
+    /// `produce` closure can hold to schedule its own next call.
+    pub fn rescheduled_by(&mut self, handle: &Reschedule) -> &mut Self {
+        self.handles.push(handle.clone());
+        self
+    }
+
+    fn every_from(&mut self, period: Duration, from: NanoTime, to: Option<NanoTime>) -> &mut Self {
+        let period = period.as_nanos() as u64;
+        assert!(period > 0, "source_fn period must be positive");
+        self.periodic.push(Periodic {
+            next: from,
+            period,
+            to,
+        });
+        self
+    }
+}
+
+/// Lets a [source_fn](crate::nodes::source_fn)'s `produce` closure ask to be
+/// called again, e.g. a poller backing off or a process with random gaps.
+/// Register it with [Scheduler::rescheduled_by].  Clones share the same
+/// requests; times not after the current one are ignored.
+#[derive(Clone, Default)]
+pub struct Reschedule {
+    times: Rc<RefCell<Vec<NanoTime>>>,
+}
+
+impl Reschedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Calls the source again at `time`.
+    pub fn at(&self, time: NanoTime) {
+        self.times.borrow_mut().push(time);
+    }
+
+    fn take(&self) -> Vec<NanoTime> {
+        mem::take(&mut self.times.borrow_mut())
+    }
+}
+
+/// Calls `produce` at the times requested by `schedule`, ticking when it
+/// returns Some.  Used by [source_fn](crate::nodes::source_fn).
+pub(crate) struct SourceFnStream<T: Element> {
+    schedule: Box<dyn Fn(&mut Scheduler)>,
+    produce: Box<dyn FnMut(NanoTime) -> Option<T>>,
+    times: BTreeSet<NanoTime>,
+    periodic: Vec<Periodic>,
+    handles: Vec<Reschedule>,
+    value: T,
+}
+
+impl<T: Element> SourceFnStream<T> {
+    pub fn new(
+        schedule: Box<dyn Fn(&mut Scheduler)>,
+        produce: Box<dyn FnMut(NanoTime) -> Option<T>>,
+    ) -> Self {
+        Self {
+            schedule,
+            produce,
+            times: BTreeSet::new(),
+            periodic: Vec::new(),
+            handles: Vec::new(),
+            value: T::default(),
+        }
+    }
+
+    /// Takes the times requested through the handles that are after `time`.
+    fn take_rescheduled(&mut self, time: NanoTime) {
+        for handle in &self.handles {
+            self.times
+                .extend(handle.take().into_iter().filter(|t| *t > time));
+        }
+    }
+
+    /// Asks the graph for a callback at the earliest time due.  Only one is
+    /// outstanding at a time, so the node never cycles twice for a time.
+    fn schedule_next(&mut self, state: &mut GraphState) {
+        let next = self
+            .periodic
+            .iter()
+            .map(|periodic| periodic.next)
+            .chain(self.times.first().copied())
+            .min();
+        if let Some(next) = next {
+            state.schedule_at_self(next);
+        }
+    }
+}
+
+impl<T: Element> MutableNode for SourceFnStream<T> {
+    fn start(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
+        let mut scheduler = Scheduler {
+            start: state.start_time(),
+            end: state.end_time(),
+            times: Vec::new(),
+            periodic: Vec::new(),
+            handles: Vec::new(),
+        };
+        (self.schedule)(&mut scheduler);
+        let start = scheduler.start;
+        self.times = scheduler
+            .times
+            .into_iter()
+            .filter(|t| *t >= start)
+            .collect();
+        self.periodic = scheduler.periodic;
+        if start > NanoTime::ZERO {
+            // start itself is due
+            let before = start - NanoTime::new(1);
+            self.periodic
+                .retain_mut(|periodic| periodic.advance_past(before));
+        }
+        self.handles = scheduler.handles;
+        for handle in &self.handles {
+            self.times
+                .extend(handle.take().into_iter().filter(|t| *t >= start));
+        }
+        self.schedule_next(state);
+        Ok(())
+    }
+
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        let time = state.time();
+        self.times = self.times.split_off(&(time + NanoTime::new(1)));
+        self.periodic
+            .retain_mut(|periodic| periodic.advance_past(time));
+        let value = (self.produce)(time);
+        self.take_rescheduled(time);
+        self.schedule_next(state);
+        match value {
+            Some(value) => {
+                self.value = value;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+impl<T: Element> StreamPeekRef<T> for SourceFnStream<T> {
+    fn peek_ref(&self) -> &T {
+        &self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::MockClock;
+    use crate::graph::*;
+    use crate::nodes::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    const START: u64 = 1_000_000;
+
+    fn run_modes() -> [RunMode; 2] {
+        [
+            RunMode::HistoricalFrom(NanoTime::new(START)),
+            RunMode::RealTime,
+        ]
+    }
+
+    /// Runs `stream`, returning its values with their times since start.
+    /// Realtime runs use a [MockClock] starting at the same time.
+    fn run<T: Element>(
+        stream: &Rc<dyn Stream<T>>,
+        run_mode: RunMode,
+        run_for: RunFor,
+    ) -> Vec<(u64, T)> {
+        let collected = stream.collect();
+        Graph::builder()
+            .with_clock(MockClock::new(NanoTime::new(START)))
+            .build(vec![collected.clone().as_node()], run_mode, run_for)
+            .run()
+            .unwrap();
+        collected
+            .peek_value()
+            .into_iter()
+            .map(|v| (u64::from(v.time) - START, v.value))
+            .collect()
+    }
+
+    #[test]
+    fn one_shot_times() {
+        for run_mode in run_modes() {
+            let calls = Rc::new(Cell::new(0));
+            let stream = {
+                let calls = calls.clone();
+                source_fn(
+                    |s| {
+                        let start = s.start_time();
+                        // out of order, repeated and before the start
+                        s.at(start + NanoTime::new(300))
+                            .at(start + NanoTime::new(100))
+                            .at(start + NanoTime::new(100))
+                            .at(NanoTime::new(START - 1));
+                    },
+                    move |_| {
+                        calls.set(calls.get() + 1);
+                        Some(calls.get())
+                    },
+                )
+            };
+            assert_eq!(
+                run(&stream, run_mode, RunFor::Cycles(2)),
+                vec![(100, 1), (300, 2)],
+                "{run_mode:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn periodic_in_session() {
+        for run_mode in run_modes() {
+            // a 250ns session that opened before the run started
+            let stream = source_fn(
+                |s| {
+                    let start = s.start_time();
+                    s.every_between(
+                        Duration::from_nanos(100),
+                        start - NanoTime::new(150),
+                        start + NanoTime::new(250),
+                    );
+                },
+                |time| Some(u64::from(time) - START),
+            );
+            assert_eq!(
+                run(&stream, run_mode, RunFor::Cycles(2)),
+                vec![(50, 50), (150, 150)],
+                "{run_mode:?}"
+            );
+            let every = source_fn(|s| _ = s.every(Duration::from_nanos(100)), |_| Some(()));
+            let times: Vec<u64> = run(&every, run_mode, RunFor::Cycles(3))
+                .into_iter()
+                .map(|(time, _)| time)
+                .collect();
+            assert_eq!(times, vec![0, 100, 200], "{run_mode:?}");
+        }
+    }
+
+    #[test]
+    fn produce_none_does_not_tick() {
+        for run_mode in run_modes() {
+            let stream = source_fn(
+                |s| _ = s.every(Duration::from_nanos(100)),
+                |time| {
+                    let offset = u64::from(time) - START;
+                    offset.is_multiple_of(200).then_some(offset)
+                },
+            );
+            assert_eq!(
+                run(&stream, run_mode, RunFor::Cycles(5)),
+                vec![(0, 0), (200, 200), (400, 400)],
+                "{run_mode:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn self_rescheduling_random_walk() {
+        for run_mode in run_modes() {
+            let wake = Reschedule::new();
+            let walk = {
+                let mut seed: u64 = 7;
+                let mut price: i64 = 100;
+                source_fn(
+                    {
+                        let wake = wake.clone();
+                        move |s| _ = s.at(s.start_time()).rescheduled_by(&wake)
+                    },
+                    move |time| {
+                        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                        let step = (seed >> 33) % 3;
+                        price += step as i64 - 1;
+                        // the gap to the next step varies with the draw
+                        wake.at(time + NanoTime::new(10 * (step + 1)));
+                        Some(price)
+                    },
+                )
+            };
+            let steps = run(&walk, run_mode, RunFor::Cycles(20));
+            assert_eq!(steps.len(), 20, "{run_mode:?}");
+            for pair in steps.windows(2) {
+                let gap = pair[1].0 - pair[0].0;
+                let step = pair[1].1 - pair[0].1;
+                assert!([10, 20, 30].contains(&gap), "{pair:?}");
+                assert!((-1..=1).contains(&step), "{pair:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn rescheduling_merges_with_periodic_times() {
+        for run_mode in run_modes() {
+            let wake = Reschedule::new();
+            let stream = {
+                source_fn(
+                    {
+                        let wake = wake.clone();
+                        move |s| _ = s.every(Duration::from_nanos(100)).rescheduled_by(&wake)
+                    },
+                    move |time| {
+                        let offset = u64::from(time) - START;
+                        // an extra call between periods, one on a period and
+                        // one in the past, which is ignored
+                        if offset == 0 {
+                            wake.at(time + NanoTime::new(50));
+                            wake.at(time + NanoTime::new(100));
+                        }
+                        if offset == 50 {
+                            wake.at(NanoTime::new(START));
+                        }
+                        Some(offset)
+                    },
+                )
+            };
+            let times: Vec<u64> = run(&stream, run_mode, RunFor::Cycles(4))
+                .into_iter()
+                .map(|(time, _)| time)
+                .collect();
+            assert_eq!(times, vec![0, 50, 100, 200], "{run_mode:?}");
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::rc::Rc;
+
+use crate::types::*;
+
+/// One branch of a `Stream<Result<T, E>>`: ticks with the `Ok` payload, or
+/// with the `Err` payload, skipping the other variant.  Used by
+/// [split_result](crate::nodes::ResultStreamOperators::split_result).
+pub(crate) struct ResultBranchStream<T, E, OUT: Element> {
+    upstream: Rc<dyn Stream<Result<T, E>>>,
+    select: fn(&Result<T, E>) -> Option<&OUT>,
+    value: OUT,
+}
+
+impl<T, E, OUT: Element> ResultBranchStream<T, E, OUT> {
+    pub fn new(
+        upstream: Rc<dyn Stream<Result<T, E>>>,
+        select: fn(&Result<T, E>) -> Option<&OUT>,
+    ) -> Self {
+        Self {
+            upstream,
+            select,
+            value: OUT::default(),
+        }
+    }
+}
+
+#[node(active = [upstream], output = value: OUT)]
+impl<T: 'static, E: 'static, OUT: Element> MutableNode for ResultBranchStream<T, E, OUT> {
+    fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+        match (self.select)(&self.upstream.peek_ref_cell()) {
+            Some(value) => {
+                self.value = value.clone();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::*;
+    use crate::nodes::*;
+    use std::rc::Rc;
+
+    /// `Ok(n)` for odd counts, `Err("even n")` for even ones.  `Result` is
+    /// not `Default`, so built-in operators cannot produce it.
+    struct Alternating {
+        upstream: Rc<dyn Stream<u64>>,
+        value: Result<u64, String>,
+    }
+
+    #[node(active = [upstream], output = value: Result<u64, String>)]
+    impl MutableNode for Alternating {
+        fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+            let n = self.upstream.peek_value();
+            self.value = if n % 2 == 1 {
+                Ok(n)
+            } else {
+                Err(format!("even {n}"))
+            };
+            Ok(true)
+        }
+    }
+
+    #[test]
+    fn each_branch_gets_its_variant() {
+        let results = Alternating {
+            upstream: ticker(Duration::from_nanos(100)).count(),
+            value: Ok(0),
+        }
+        .into_stream();
+        let (oks, errs) = results.split_result();
+        let oks = oks.collect();
+        let errs = errs.collect();
+        Graph::new(
+            vec![oks.clone().as_node(), errs.clone().as_node()],
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(4),
+        )
+        .run()
+        .unwrap();
+        let oks: Vec<(u64, NanoTime)> =
+            oks.peek_value().iter().map(|v| (v.value, v.time)).collect();
+        assert_eq!(oks, vec![(1, NanoTime::new(0)), (3, NanoTime::new(200))]);
+        let errs: Vec<(String, NanoTime)> = errs
+            .peek_value()
+            .into_iter()
+            .map(|v| (v.value, v.time))
+            .collect();
+        assert_eq!(
+            errs,
+            vec![
+                ("even 2".to_string(), NanoTime::new(100)),
+                ("even 4".to_string(), NanoTime::new(300)),
+            ]
+        );
+    }
+}
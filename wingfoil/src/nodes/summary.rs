@@ -0,0 +1,195 @@
+use crate::nodes::StreamOperators;
+use crate::queue::ValueAt;
+use crate::types::*;
+use log::info;
+use num_traits::ToPrimitive;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Summary statistics of [collected](crate::nodes::StreamOperators::collect)
+/// results, from [summarize](ResultsOperators::summarize).  The statistics
+/// are `None` rather than NaN when there are too few values.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Summary {
+    pub count: usize,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    /// Sample standard deviation (ddof = 1), so `None` below two values.
+    pub std: Option<f64>,
+    pub first_time: Option<NanoTime>,
+    pub last_time: Option<NanoTime>,
+    /// Engine time from the first value to the last.
+    pub span: Duration,
+}
+
+impl Summary {
+    pub fn new<T: ToPrimitive>(results: &[ValueAt<T>]) -> Self {
+        let values: Vec<f64> = results
+            .iter()
+            .map(|v| v.value.to_f64().unwrap_or(f64::NAN))
+            .collect();
+        let count = values.len();
+        let mean = (count > 0).then(|| values.iter().sum::<f64>() / count as f64);
+        let std = mean.filter(|_| count > 1).map(|mean| {
+            let m2: f64 = values.iter().map(|v| (v - mean).powi(2)).sum();
+            (m2 / (count - 1) as f64).sqrt()
+        });
+        let first_time = results.first().map(|v| v.time);
+        let last_time = results.last().map(|v| v.time);
+        let span = match (first_time, last_time) {
+            (Some(first), Some(last)) => Duration::from_nanos(u64::from(last) - u64::from(first)),
+            _ => Duration::ZERO,
+        };
+        Self {
+            count,
+            min: values.iter().copied().reduce(f64::min),
+            max: values.iter().copied().reduce(f64::max),
+            mean,
+            std,
+            first_time,
+            last_time,
+            span,
+        }
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn opt<T: fmt::Display>(value: Option<T>) -> String {
+            value.map_or_else(|| "-".to_string(), |v| v.to_string())
+        }
+        write!(
+            f,
+            "count={} min={} max={} mean={} std={} first={} last={} span={:?}",
+            self.count,
+            opt(self.min),
+            opt(self.max),
+            opt(self.mean),
+            opt(self.std),
+            opt(self.first_time),
+            opt(self.last_time),
+            self.span,
+        )
+    }
+}
+
+/// The [Summary] computed by [summarize](ResultsOperators::summarize), read
+/// after the graph has run.
+#[derive(Clone)]
+pub struct SummaryHandle {
+    node: Rc<dyn Node>,
+    summary: Rc<RefCell<Summary>>,
+}
+
+impl SummaryHandle {
+    /// The node that computes the summary, to run or add to a graph.
+    pub fn node(&self) -> Rc<dyn Node> {
+        self.node.clone()
+    }
+
+    /// The summary, computed as the graph stops.  Empty until then.
+    pub fn summary(&self) -> Summary {
+        self.summary.borrow().clone()
+    }
+}
+
+/// Operators on the output of [collect](crate::nodes::StreamOperators::collect).
+pub trait ResultsOperators<T: Element> {
+    /// Computes and logs a [Summary] of the collected values as the graph
+    /// stops.
+    /// ```
+    /// # use wingfoil::*;
+    /// # use std::time::Duration;
+    /// let summary = ticker(Duration::from_secs(1)).count().collect().summarize();
+    /// summary
+    ///     .node()
+    ///     .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(4))
+    ///     .unwrap();
+    /// assert_eq!(summary.summary().mean, Some(2.5));
+    /// ```
+    #[must_use]
+    fn summarize(self: &Rc<Self>) -> SummaryHandle
+    where
+        T: ToPrimitive;
+}
+
+impl<T: Element> ResultsOperators<T> for dyn Stream<Vec<ValueAt<T>>> {
+    fn summarize(self: &Rc<Self>) -> SummaryHandle
+    where
+        T: ToPrimitive,
+    {
+        let summary = Rc::new(RefCell::new(Summary::default()));
+        let node = {
+            let summary = summary.clone();
+            self.finally(move |results, _| {
+                let computed = Summary::new(&results);
+                info!("summary: {computed}");
+                *summary.borrow_mut() = computed;
+                Ok(())
+            })
+        };
+        SummaryHandle { node, summary }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::*;
+    use crate::nodes::*;
+
+    #[test]
+    fn summarize_known_sequence() {
+        // 2, 4, 4, 4, 5, 5, 7, 9 at 0s, 1s, ...
+        let values = [2, 4, 4, 4, 5, 5, 7, 9];
+        let summary = ticker(Duration::from_secs(1))
+            .count()
+            .map(move |n| values[n as usize - 1])
+            .collect()
+            .summarize();
+        summary
+            .node()
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(8))
+            .unwrap();
+        let summary = summary.summary();
+        assert_eq!(summary.count, 8);
+        assert_eq!(summary.min, Some(2.0));
+        assert_eq!(summary.max, Some(9.0));
+        assert_eq!(summary.mean, Some(5.0));
+        // squared deviations sum to 32
+        let std = summary.std.unwrap();
+        assert!((std - (32.0_f64 / 7.0).sqrt()).abs() < 1e-12, "{std}");
+        assert_eq!(summary.first_time, Some(NanoTime::ZERO));
+        assert_eq!(summary.last_time, Some(NanoTime::new(7_000_000_000)));
+        assert_eq!(summary.span, Duration::from_secs(7));
+    }
+
+    #[test]
+    fn summarize_empty_has_no_nans() {
+        let summary = ticker(Duration::from_secs(1))
+            .count()
+            .filter_value(|_| false)
+            .collect()
+            .summarize();
+        summary
+            .node()
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+            .unwrap();
+        assert_eq!(summary.summary(), Summary::default());
+        assert_eq!(
+            summary.summary().to_string(),
+            "count=0 min=- max=- mean=- std=- first=- last=- span=0ns"
+        );
+    }
+
+    #[test]
+    fn single_value_has_no_std() {
+        let summary = Summary::new(&[ValueAt::new(1.5, NanoTime::new(10))]);
+        assert_eq!(summary.mean, Some(1.5));
+        assert_eq!(summary.std, None);
+        assert_eq!(summary.span, Duration::ZERO);
+    }
+}
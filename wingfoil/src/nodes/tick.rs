@@ -1,35 +1,154 @@
 use crate::types::*;
 
-use derive_new::new;
+use std::cmp::max;
+use std::rc::Rc;
+use std::time::Duration;
 
-/// A [Node] that ticks at a specified interval.
-/// Used by [ticker](crate::nodes::ticker).
-#[derive(new)]
+/// The gaps between the ticks of a [ticker_schedule](crate::nodes::ticker_schedule).
+pub enum Schedule {
+    /// The same gap before every tick, as with [ticker](crate::nodes::ticker).
+    Fixed(Duration),
+    /// Starts at `initial` and grows by `factor` on each tick, capped at `max`.
+    Geometric {
+        initial: Duration,
+        factor: f64,
+        max: Duration,
+    },
+    /// Computes the gap before the Nth tick, where the first tick at start
+    /// time is tick 0.
+    Custom(Box<dyn Fn(u64) -> Duration>),
+}
+
+impl Schedule {
+    /// The gap between tick `n - 1` and tick `n`.
+    pub fn gap(&self, n: u64) -> Duration {
+        match self {
+            Schedule::Fixed(interval) => *interval,
+            Schedule::Geometric {
+                initial,
+                factor,
+                max,
+            } => {
+                let exponent = n.saturating_sub(1).min(i32::MAX as u64) as i32;
+                let nanos = initial.as_nanos() as f64 * factor.powi(exponent);
+                if nanos >= max.as_nanos() as f64 {
+                    *max
+                } else {
+                    Duration::from_nanos(nanos.round() as u64)
+                }
+            }
+            Schedule::Custom(func) => func(n),
+        }
+    }
+}
+
+/// A [Node] that ticks at start time and then after each gap of its
+/// [Schedule].  Used by [ticker](crate::nodes::ticker) and
+/// [ticker_schedule](crate::nodes::ticker_schedule).
 pub(crate) struct TickNode {
-    interval: NanoTime,
-    #[new(default)]
+    schedule: Schedule,
+    ticks: u64,
     at_time: Option<NanoTime>,
 }
 
+impl TickNode {
+    pub fn new(schedule: Schedule) -> Self {
+        Self {
+            schedule,
+            ticks: 0,
+            at_time: None,
+        }
+    }
+}
+
 impl MutableNode for TickNode {
     fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        self.ticks += 1;
+        let gap = NanoTime::new(self.schedule.gap(self.ticks).as_nanos() as u64);
         let next_time = match self.at_time {
             Some(t) => {
                 // anchor to first call to mitigate drift
-                t + self.interval
+                t + gap
             }
             None => {
                 // first call
-                state.time() + self.interval
+                state.time() + gap
             }
         };
         self.at_time = Some(next_time);
-        state.add_callback(next_time);
+        state.schedule_at_self(next_time);
+        Ok(true)
+    }
+
+    fn start(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
+        state.schedule_at_self(state.start_time());
+        Ok(())
+    }
+}
+
+/// A [Node] that ticks at an interval which is updated on each tick of
+/// `control`.  Used by [adaptive_ticker](crate::nodes::adaptive_ticker).
+///
+/// The next tick is always `last tick + interval`.  A control tick reschedules
+/// it: earlier if the new interval has already elapsed since the last tick
+/// (firing immediately if it is overdue), later otherwise.  The callback
+/// scheduled under the old interval is left in the queue and skipped when it
+/// fires.
+pub(crate) struct AdaptiveTickNode {
+    interval: NanoTime,
+    control: Rc<dyn Stream<Duration>>,
+    last_tick: Option<NanoTime>,
+    next_tick: Option<NanoTime>,
+}
+
+impl AdaptiveTickNode {
+    pub fn new(interval: NanoTime, control: Rc<dyn Stream<Duration>>) -> Self {
+        Self {
+            interval,
+            control,
+            last_tick: None,
+            next_tick: None,
+        }
+    }
+}
+
+impl MutableNode for AdaptiveTickNode {
+    fn upstreams(&self) -> UpStreams {
+        UpStreams::new(vec![self.control.clone().as_node()], vec![])
+    }
+
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        let now = state.time();
+        if state.ticked(self.control.clone().as_node()) {
+            let period = self.control.peek_value();
+            anyhow::ensure!(!period.is_zero(), "adaptive_ticker period must be non-zero");
+            self.interval = NanoTime::new(period.as_nanos() as u64);
+            if let Some(last) = self.last_tick {
+                let next = max(last + self.interval, now);
+                if next > now && self.next_tick != Some(next) {
+                    state.schedule_at_self(next);
+                }
+                self.next_tick = Some(next);
+            }
+        }
+        if self.next_tick != Some(now) {
+            // a control-only tick, or a callback superseded by a new interval
+            return Ok(false);
+        }
+        let next = now + self.interval;
+        self.last_tick = Some(now);
+        self.next_tick = Some(next);
+        state.schedule_at_self(next);
         Ok(true)
     }
 
     fn start(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
-        state.add_callback(state.start_time());
+        anyhow::ensure!(
+            self.interval != NanoTime::ZERO,
+            "adaptive_ticker period must be non-zero"
+        );
+        self.next_tick = Some(state.start_time());
+        state.schedule_at_self(state.start_time());
         Ok(())
     }
 }
@@ -40,6 +159,8 @@ mod tests {
     use crate::adapters::statistics::{StatisticsOperators, Weighting, Window};
     use crate::graph::*;
     use crate::nodes::*;
+    use crate::queue::ValueAt;
+    use std::cell::RefCell;
 
     #[test]
     fn tick_node_works_in_realtime() {
@@ -63,4 +184,102 @@ mod tests {
         let err = (period.as_nanos() as f64 - average.last().unwrap().value).abs();
         debug_assert!(err < Duration::from_millis(10).as_nanos() as f64)
     }
+
+    fn schedule_tick_times(schedule: Schedule, cycles: u32) -> Vec<u64> {
+        let ticks = ticker_schedule(schedule).ticked_at().collect();
+        ticks
+            .run(
+                RunMode::HistoricalFrom(NanoTime::ZERO),
+                RunFor::Cycles(cycles),
+            )
+            .unwrap();
+        ticks
+            .peek_value()
+            .iter()
+            .map(|t| u64::from(t.value))
+            .collect()
+    }
+
+    #[test]
+    fn ticker_schedule_geometric_grows_to_max() {
+        let schedule = Schedule::Geometric {
+            initial: Duration::from_nanos(10),
+            factor: 2.0,
+            max: Duration::from_nanos(100),
+        };
+        assert_eq!(
+            schedule_tick_times(schedule, 8),
+            vec![0, 10, 30, 70, 150, 250, 350, 450]
+        );
+    }
+
+    #[test]
+    fn ticker_schedule_custom_gap_by_tick_index() {
+        let schedule = Schedule::Custom(Box::new(|n| Duration::from_nanos(n * 5)));
+        assert_eq!(schedule_tick_times(schedule, 5), vec![0, 5, 15, 30, 50]);
+    }
+
+    fn control(changes: &[(u64, u64)]) -> Rc<dyn Stream<Duration>> {
+        let src = Rc::new(RefCell::new(CallBackStream::new()));
+        for (at, period) in changes {
+            src.borrow_mut().push(ValueAt::new(
+                Duration::from_nanos(*period),
+                NanoTime::new(*at),
+            ));
+        }
+        src.as_stream()
+    }
+
+    fn tick_times(changes: &[(u64, u64)], run_for: u64) -> anyhow::Result<Vec<u64>> {
+        let ticks = adaptive_ticker(Duration::from_nanos(100), control(changes))
+            .ticked_at()
+            .collect();
+        ticks.run(
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Duration(Duration::from_nanos(run_for)),
+        )?;
+        // the historical run may overshoot `run_for` by a cycle or two
+        Ok(ticks
+            .peek_value()
+            .iter()
+            .map(|t| u64::from(t.value))
+            .filter(|t| *t <= run_for)
+            .collect())
+    }
+
+    #[test]
+    fn adaptive_ticker_halving_period_ticks_sooner() {
+        // halved at 250, so the tick due at 300 is pulled in to 250
+        assert_eq!(
+            tick_times(&[(250, 50)], 400).unwrap(),
+            vec![0, 100, 200, 250, 300, 350, 400]
+        );
+        // halved at 280: the next tick (200 + 50) is overdue so fires at once
+        assert_eq!(
+            tick_times(&[(280, 50)], 350).unwrap(),
+            vec![0, 100, 200, 280, 330]
+        );
+    }
+
+    #[test]
+    fn adaptive_ticker_lengthening_period_skips_stale_callback() {
+        assert_eq!(
+            tick_times(&[(150, 300)], 800).unwrap(),
+            vec![0, 100, 400, 700]
+        );
+    }
+
+    #[test]
+    fn adaptive_ticker_last_control_tick_wins() {
+        assert_eq!(
+            tick_times(&[(120, 50), (130, 200)], 600).unwrap(),
+            vec![0, 100, 300, 500]
+        );
+    }
+
+    #[test]
+    fn adaptive_ticker_rejects_zero_period() {
+        let err = tick_times(&[(150, 0)], 400).unwrap_err();
+        assert!(format!("{err:?}").contains("non-zero"));
+    }
 }
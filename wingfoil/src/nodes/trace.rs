@@ -0,0 +1,182 @@
+use derive_new::new;
+
+use crate::types::*;
+use std::collections::{HashSet, VecDeque};
+use std::rc::Rc;
+
+/// Number of traces retained by [trace](crate::nodes::StreamOperators::trace).
+pub const DEFAULT_TRACE_CAPACITY: usize = 1024;
+
+/// The causal chain behind a single tick of a [TraceNode].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+    /// Engine time of the tick.
+    pub time: NanoTime,
+    /// `(node_index, type_name)` of every node that ticked on this cycle and
+    /// feeds the traced stream through active edges, starting with the traced
+    /// stream itself and walking depth-first towards the sources.
+    pub upstream_chain: Vec<(usize, String)>,
+    /// The entries of `upstream_chain` with no ticked active upstream of their
+    /// own, i.e. the inputs that caused this tick.
+    pub root_causes: Vec<(usize, String)>,
+}
+
+/// Passes its source through unchanged, recording a [Trace] of the ticked
+/// upstream nodes each time it ticks.  The most recent `capacity` traces are
+/// kept.  Build it directly to keep a handle for [last_trace](Self::last_trace):
+/// ```
+/// # use wingfoil::*;
+/// # use std::cell::RefCell;
+/// # use std::rc::Rc;
+/// # use std::time::Duration;
+/// let source = ticker(Duration::from_millis(10)).count().map(|x| x * 2);
+/// let traced = Rc::new(RefCell::new(TraceNode::new(source, 16)));
+/// traced
+///     .clone()
+///     .as_stream()
+///     .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+///     .unwrap();
+/// let trace = traced.borrow().last_trace().unwrap();
+/// assert_eq!(trace.root_causes.len(), 1);
+/// assert!(trace.root_causes[0].1.starts_with("TickNode"));
+/// ```
+#[derive(new)]
+pub struct TraceNode<T: Element> {
+    source: Rc<dyn Stream<T>>,
+    capacity: usize,
+    #[new(default)]
+    traces: VecDeque<Trace>,
+    #[new(default)]
+    value: T,
+}
+
+impl<T: Element> TraceNode<T> {
+    /// The trace recorded on the most recent tick, if any.
+    pub fn last_trace(&self) -> Option<Trace> {
+        self.traces.back().cloned()
+    }
+
+    /// All retained traces, oldest first.
+    pub fn traces(&self) -> impl Iterator<Item = &Trace> {
+        self.traces.iter()
+    }
+
+    fn record(&mut self, state: &GraphState) {
+        let mut trace = Trace {
+            time: state.time(),
+            upstream_chain: Vec::new(),
+            root_causes: Vec::new(),
+        };
+        let mut seen = HashSet::new();
+        let mut stack = vec![self.source.clone().as_node()];
+        while let Some(node) = stack.pop() {
+            let Some(index) = state.node_index(node.clone()) else {
+                continue;
+            };
+            if !seen.insert(index) {
+                continue;
+            }
+            let entry = (index, node.type_name());
+            let ticked: Vec<_> = node
+                .upstreams()
+                .active
+                .into_iter()
+                .filter(|up| state.ticked(up.clone()))
+                .collect();
+            if ticked.is_empty() {
+                trace.root_causes.push(entry.clone());
+            }
+            trace.upstream_chain.push(entry);
+            // reversed so upstreams are visited in declaration order
+            stack.extend(ticked.into_iter().rev());
+        }
+        tracing::trace!(
+            target: "wingfoil",
+            "{} trace {:?} caused by {:?}",
+            trace.time.pretty(),
+            trace.upstream_chain,
+            trace.root_causes
+        );
+        if self.traces.len() == self.capacity {
+            self.traces.pop_front();
+        }
+        self.traces.push_back(trace);
+    }
+}
+
+#[node(active = [source], output = value: T)]
+impl<T: Element> MutableNode for TraceNode<T> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        self.value = self.source.peek_value();
+        if self.capacity > 0 {
+            self.record(state);
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::*;
+    use crate::nodes::*;
+    use crate::queue::ValueAt;
+    use std::cell::RefCell;
+
+    fn source(ticks: &[u64]) -> Rc<RefCell<CallBackStream<u64>>> {
+        let src = Rc::new(RefCell::new(CallBackStream::new()));
+        for t in ticks {
+            src.borrow_mut().push(ValueAt::new(*t, NanoTime::new(*t)));
+        }
+        src
+    }
+
+    fn names(entries: &[(usize, String)]) -> Vec<String> {
+        entries.iter().map(|(_, name)| name.clone()).collect()
+    }
+
+    #[test]
+    fn trace_identifies_root_cause_of_each_tick() {
+        let left = source(&[10, 30]);
+        let right = source(&[20, 30]);
+        let left_ix = left.clone().as_stream().map(|x| x + 1);
+        let right_ix = right.clone().as_stream();
+        let summed = add(&left_ix, &right_ix);
+        let traced = Rc::new(RefCell::new(TraceNode::new(summed, 2)));
+        let mut graph = Graph::new(
+            vec![traced.clone().as_node()],
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Forever,
+        );
+        let left_index = graph.state.node_index(left.clone().as_node()).unwrap();
+        let right_index = graph.state.node_index(right.clone().as_node()).unwrap();
+        graph.run().unwrap();
+
+        let traces: Vec<Trace> = traced.borrow().traces().cloned().collect();
+        // capacity 2 keeps the last two of three ticks
+        assert_eq!(traces.len(), 2);
+
+        let only_right = &traces[0];
+        assert_eq!(only_right.time, NanoTime::new(20));
+        assert_eq!(only_right.upstream_chain.len(), 2);
+        assert_eq!(only_right.root_causes.len(), 1);
+        assert_eq!(only_right.root_causes[0].0, right_index);
+
+        let both = traced.borrow().last_trace().unwrap();
+        assert_eq!(both.time, NanoTime::new(30));
+        assert_eq!(both.upstream_chain.len(), 4);
+        let roots: Vec<usize> = both.root_causes.iter().map(|(ix, _)| *ix).collect();
+        assert_eq!(roots, vec![left_index, right_index]);
+        assert!(names(&both.upstream_chain)[1].starts_with("MapStream"));
+    }
+
+    #[test]
+    fn trace_passes_values_through() {
+        let traced = source(&[1, 2, 3]).as_stream().trace().collect();
+        traced
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        let values: Vec<u64> = traced.peek_value().iter().map(|v| v.value).collect();
+        assert_eq!(values, vec![1, 2, 3]);
+    }
+}
@@ -28,7 +28,9 @@ impl<IN1: 'static, IN2: 'static, OUT: Element> MutableNode for TryBiMapStream<IN
             passive.into_iter().map(|(n, _)| n).collect(),
         )
     }
-    fn cycle(&mut self, _state: &mut GraphState) -> anyhow::Result<bool> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        state.ensure_ticked_ever(self.upstream1.stream())?;
+        state.ensure_ticked_ever(self.upstream2.stream())?;
         self.value = (self.func)(
             self.upstream1.stream().peek_value(),
             self.upstream2.stream().peek_value(),
@@ -0,0 +1,203 @@
+use crate::types::*;
+use std::mem;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// What a [tumbling](crate::nodes::StreamOperators::tumbling) window that
+/// received no values emits when it closes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TumblingEmpty {
+    /// Emit nothing.
+    #[default]
+    Skip,
+    /// Emit the empty window.  The operator then ticks at every window
+    /// boundary, like a [ticker](crate::nodes::ticker), so the run needs a
+    /// [RunFor] bound.
+    Emit,
+}
+
+/// Folds upstream values into non-overlapping windows `[k * window,
+/// (k + 1) * window)` of engine time, emitting each when it closes at its
+/// end time, or on the last cycle if still open.  A window that opens on
+/// the last cycle as the previous one closes is not emitted, as a node
+/// ticks at most once a cycle.  Used by
+/// [tumbling_fold](crate::nodes::StreamOperators::tumbling_fold).
+pub(crate) struct TumblingStream<T: Element, A: Element> {
+    upstream: Rc<dyn Stream<T>>,
+    window: u64,
+    empty: TumblingEmpty,
+    init: A,
+    func: Box<dyn Fn(&mut A, T)>,
+    /// The end of the open window, if any.
+    window_end: Option<NanoTime>,
+    acc: A,
+    /// Values folded into `acc`.
+    count: usize,
+    value: A,
+}
+
+impl<T: Element, A: Element> TumblingStream<T, A> {
+    pub fn new(
+        upstream: Rc<dyn Stream<T>>,
+        window: Duration,
+        empty: TumblingEmpty,
+        init: A,
+        func: Box<dyn Fn(&mut A, T)>,
+    ) -> Self {
+        let window = window.as_nanos() as u64;
+        assert!(window > 0, "tumbling window must be positive");
+        Self {
+            upstream,
+            window,
+            empty,
+            acc: init.clone(),
+            init,
+            func,
+            window_end: None,
+            count: 0,
+            value: A::default(),
+        }
+    }
+
+    /// The end of the window that `time` falls in.
+    fn end_of(&self, time: NanoTime) -> NanoTime {
+        let time = u64::from(time);
+        NanoTime::new(time - time % self.window + self.window)
+    }
+
+    /// Moves the open window to the output, returning whether it ticks.
+    fn close(&mut self) -> bool {
+        self.window_end = None;
+        let count = mem::take(&mut self.count);
+        self.value = mem::replace(&mut self.acc, self.init.clone());
+        count > 0 || self.empty == TumblingEmpty::Emit
+    }
+
+    fn open(&mut self, state: &mut GraphState) {
+        let end = self.end_of(state.time());
+        self.window_end = Some(end);
+        state.schedule_at_self(end);
+    }
+}
+
+#[node(active = [upstream], output = value: A)]
+impl<T: Element, A: Element> MutableNode for TumblingStream<T, A> {
+    fn start(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
+        if self.empty == TumblingEmpty::Emit {
+            self.open(state);
+        }
+        Ok(())
+    }
+
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        let mut ticked = false;
+        // a value at a window's end time belongs to the next window
+        if self.window_end.is_some_and(|end| state.time() >= end) {
+            ticked = self.close();
+            if self.empty == TumblingEmpty::Emit {
+                self.open(state);
+            }
+        }
+        if state.ticked(self.upstream.clone().as_node()) {
+            if self.window_end.is_none() {
+                self.open(state);
+            }
+            (self.func)(&mut self.acc, self.upstream.peek_value());
+            self.count += 1;
+        }
+        if state.is_last_cycle() && !ticked && self.count > 0 {
+            ticked = self.close();
+        }
+        Ok(ticked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::*;
+    use crate::nodes::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Values 1, 2, ... at `times`, in tumbling windows of 100ns.
+    fn windows(times: &[u64], empty: TumblingEmpty, run_for: RunFor) -> Vec<(Vec<u64>, u64)> {
+        let src: Rc<RefCell<CallBackStream<u64>>> = Rc::new(RefCell::new(CallBackStream::new()));
+        for (i, time) in times.iter().enumerate() {
+            src.borrow_mut()
+                .push(ValueAt::new(i as u64 + 1, NanoTime::new(*time)));
+        }
+        let windows = src
+            .as_stream()
+            .tumbling(Duration::from_nanos(100), empty)
+            .collect();
+        windows
+            .run(RunMode::HistoricalFrom(NanoTime::ZERO), run_for)
+            .unwrap();
+        windows
+            .peek_value()
+            .into_iter()
+            .map(|v| (v.value, u64::from(v.time)))
+            .collect()
+    }
+
+    #[test]
+    fn values_straddling_boundaries() {
+        let windows = windows(
+            &[10, 99, 100, 150, 199, 200],
+            TumblingEmpty::Skip,
+            RunFor::Forever,
+        );
+        assert_eq!(
+            windows,
+            vec![(vec![1, 2], 100), (vec![3, 4, 5], 200), (vec![6], 300)]
+        );
+    }
+
+    #[test]
+    fn empty_middle_window_skipped() {
+        let windows = windows(&[10, 250, 260], TumblingEmpty::Skip, RunFor::Forever);
+        assert_eq!(windows, vec![(vec![1], 100), (vec![2, 3], 300)]);
+    }
+
+    #[test]
+    fn empty_middle_window_emitted() {
+        let windows = windows(
+            &[10, 250, 260],
+            TumblingEmpty::Emit,
+            RunFor::Duration(Duration::from_nanos(250)),
+        );
+        assert_eq!(
+            windows,
+            vec![(vec![1], 100), (vec![], 200), (vec![2, 3], 260)]
+        );
+    }
+
+    #[test]
+    fn partial_window_flushes_on_last_cycle() {
+        let windows = windows(&[10, 120, 130], TumblingEmpty::Skip, RunFor::Cycles(4));
+        assert_eq!(windows, vec![(vec![1], 100), (vec![2, 3], 130)]);
+    }
+
+    #[test]
+    fn tumbling_fold_sums_without_collecting() {
+        let sums = ticker(Duration::from_nanos(30))
+            .count()
+            .tumbling_fold(Duration::from_nanos(100), 0, |sum, n| *sum += n)
+            .collect();
+        sums.run(
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Duration(Duration::from_nanos(230)),
+        )
+        .unwrap();
+        let sums: Vec<(u64, u64)> = sums
+            .peek_value()
+            .into_iter()
+            .map(|v| (v.value, u64::from(v.time)))
+            .collect();
+        // ticks at 0, 30, 60, 90 | 120, 150, 180 | 210, 240, 270 (last)
+        assert_eq!(
+            sums,
+            vec![(1 + 2 + 3 + 4, 100), (5 + 6 + 7, 200), (8 + 9 + 10, 270)]
+        );
+    }
+}
@@ -41,6 +41,10 @@ impl<T: Element> MutableNode for WindowStream<T> {
 
         Ok(flushed)
     }
+
+    fn approx_memory(&self) -> Option<usize> {
+        Some(vec_memory(&self.buffer) + vec_memory(&self.value))
+    }
 }
 
 impl<T: Element> WindowStream<T> {
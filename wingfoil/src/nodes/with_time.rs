@@ -1,6 +1,7 @@
 use derive_new::new;
 
 use std::rc::Rc;
+use std::time::Duration;
 
 use crate::types::*;
 
@@ -22,10 +23,38 @@ impl<T: Element> MutableNode for WithTimeStream<T> {
     }
 }
 
+/// Emits the engine time elapsed since the previous tick of its upstream.
+/// Does not tick on the first upstream tick.
+/// Used by [time_since_last](crate::nodes::StreamOperators::time_since_last).
+#[derive(new)]
+pub struct TimeSinceLastStream {
+    upstream: Rc<dyn Node>,
+    #[new(default)]
+    last: Option<NanoTime>,
+    #[new(default)]
+    value: Duration,
+}
+
+#[node(active = [upstream], output = value: Duration)]
+impl MutableNode for TimeSinceLastStream {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        let now = state.time();
+        match self.last.replace(now) {
+            Some(last) => {
+                self.value = (now - last).into();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::graph::*;
     use crate::nodes::*;
+    use crate::queue::ValueAt;
+    use std::cell::RefCell;
 
     #[test]
     fn timestamps_match_graph_time() {
@@ -47,4 +76,57 @@ mod tests {
         assert_eq!(items[0].value, (NanoTime::new(0), 1u64));
         assert_eq!(items[3].value, (NanoTime::new(300), 4u64));
     }
+
+    #[test]
+    fn with_time_tags_ticker_values() {
+        let out = ticker(Duration::from_millis(10))
+            .count()
+            .with_time()
+            .collect();
+        out.run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+            .unwrap();
+        let values: Vec<(NanoTime, u64)> = out.peek_value().into_iter().map(|v| v.value).collect();
+        assert_eq!(
+            values,
+            vec![
+                (NanoTime::new(0), 1),
+                (NanoTime::new(10_000_000), 2),
+                (NanoTime::new(20_000_000), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn without_time_drops_the_timestamp() {
+        let out = ticker(Duration::from_millis(10))
+            .count()
+            .with_time()
+            .without_time()
+            .collect();
+        out.run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(3))
+            .unwrap();
+        let values: Vec<u64> = out.peek_value().into_iter().map(|v| v.value).collect();
+        assert_eq!(values, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn time_since_last_measures_irregular_gaps() {
+        let src = Rc::new(RefCell::new(CallBackStream::<u32>::new()));
+        for (ms, value) in [(0, 1), (5, 2), (20, 3), (21, 4)] {
+            src.borrow_mut()
+                .push(ValueAt::new(value, NanoTime::new(ms * 1_000_000)));
+        }
+        let gaps = src.clone().as_stream().time_since_last().collect();
+        gaps.run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Forever)
+            .unwrap();
+        let gaps: Vec<Duration> = gaps.peek_value().into_iter().map(|v| v.value).collect();
+        assert_eq!(
+            gaps,
+            vec![
+                Duration::from_millis(5),
+                Duration::from_millis(15),
+                Duration::from_millis(1),
+            ]
+        );
+    }
 }
@@ -0,0 +1,256 @@
+//! Progress reporting for long runs, e.g. multi-hour historical backtests.
+//! Register a callback with [GraphBuilder::on_progress](crate::GraphBuilder::on_progress).
+
+use std::time::{Duration, Instant};
+
+use crate::time::NanoTime;
+
+/// A snapshot of a running graph, passed to the
+/// [on_progress](crate::GraphBuilder::on_progress) callback.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    /// Engine time of the last cycle.
+    pub time: NanoTime,
+    /// Engine time the run started from.
+    pub start_time: NanoTime,
+    /// Engine time a [RunFor::Duration](crate::RunFor::Duration) run ends.
+    pub end_time: Option<NanoTime>,
+    /// Engine cycles completed.
+    pub cycles: u32,
+    /// Cycle bound of a [RunFor::Cycles](crate::RunFor::Cycles) run.
+    pub end_cycle: Option<u32>,
+    /// Wall clock time since the run started.
+    pub elapsed: Duration,
+    /// Set on the final report, once the run has finished.
+    pub finished: bool,
+}
+
+impl Progress {
+    /// Engine cycles per wall clock second.
+    pub fn cycles_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.cycles as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Fraction of the run completed, in `0.0..=1.0`, if the run is bounded
+    /// by cycles or duration.
+    pub fn fraction(&self) -> Option<f64> {
+        if self.finished {
+            return Some(1.0);
+        }
+        let fraction = match (self.end_cycle, self.end_time) {
+            (Some(end_cycle), _) => self.cycles as f64 / end_cycle.max(1) as f64,
+            (None, Some(end_time)) => {
+                let done = u64::from(self.time).saturating_sub(self.start_time.into());
+                let total = u64::from(end_time).saturating_sub(self.start_time.into());
+                done as f64 / total.max(1) as f64
+            }
+            (None, None) => return None,
+        };
+        Some(fraction.clamp(0.0, 1.0))
+    }
+
+    /// Estimated wall clock time remaining, extrapolated from progress so
+    /// far.
+    pub fn remaining(&self) -> Option<Duration> {
+        match self.fraction()? {
+            f if f >= 1.0 => Some(Duration::ZERO),
+            f if f > 0.0 => Some(self.elapsed.mul_f64((1.0 - f) / f)),
+            _ => None,
+        }
+    }
+}
+
+/// Calls back with a [Progress] at most every `every` of wall clock time.
+pub(crate) struct ProgressReporter {
+    every: Duration,
+    callback: Box<dyn Fn(Progress)>,
+    started: Instant,
+    next_report: Instant,
+}
+
+impl ProgressReporter {
+    pub fn new(every: Duration, callback: Box<dyn Fn(Progress)>) -> Self {
+        let now = Instant::now();
+        Self {
+            every,
+            callback,
+            started: now,
+            next_report: now,
+        }
+    }
+
+    pub fn start(&mut self) {
+        self.started = Instant::now();
+        self.next_report = self.started + self.every;
+    }
+
+    /// Whether a report is due, checked once per cycle.
+    pub fn due(&self) -> bool {
+        Instant::now() >= self.next_report
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn report(&mut self, progress: Progress) {
+        (self.callback)(progress);
+        self.next_report = Instant::now() + self.every;
+    }
+}
+
+/// Draws [Progress] as a single line on stderr, redrawn in place.
+/// ```
+/// # use wingfoil::*;
+/// # use std::time::Duration;
+/// let bar = ProgressBar::default();
+/// let node = ticker(Duration::from_nanos(1)).produce(|| ());
+/// Graph::builder()
+///     .on_progress(Duration::from_secs(1), move |progress| bar.report(&progress))
+///     .build(vec![node], RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(10))
+///     .run()
+///     .unwrap();
+/// ```
+#[cfg(feature = "progress")]
+#[derive(Debug, Clone)]
+pub struct ProgressBar {
+    width: usize,
+}
+
+#[cfg(feature = "progress")]
+impl Default for ProgressBar {
+    fn default() -> Self {
+        Self { width: 30 }
+    }
+}
+
+#[cfg(feature = "progress")]
+impl ProgressBar {
+    /// Bar width in characters.
+    #[must_use]
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn report(&self, progress: &Progress) {
+        use std::io::Write;
+        let mut stderr = std::io::stderr().lock();
+        let _ = write!(stderr, "\r{}", self.line(progress));
+        if progress.finished {
+            let _ = writeln!(stderr);
+        }
+        let _ = stderr.flush();
+    }
+
+    fn line(&self, progress: &Progress) -> String {
+        let time = chrono::DateTime::<chrono::Utc>::from(progress.time).format("%Y-%m-%d %H:%M:%S");
+        let stats = format!(
+            "{time}  {} cycles  {:.0}/s",
+            progress.cycles,
+            progress.cycles_per_sec()
+        );
+        let Some(fraction) = progress.fraction() else {
+            return stats;
+        };
+        let filled = (fraction * self.width as f64).round() as usize;
+        let eta = progress.remaining().map_or("?".to_string(), |eta| {
+            let secs = eta.as_secs();
+            format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+        });
+        format!(
+            "[{}{}] {:>5.1}%  {stats}  eta {eta}",
+            "#".repeat(filled),
+            ".".repeat(self.width.saturating_sub(filled)),
+            fraction * 100.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::*;
+    use crate::nodes::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn reports(every: Duration, run_for: RunFor) -> Vec<Progress> {
+        let reports = Rc::new(RefCell::new(Vec::new()));
+        let sink = reports.clone();
+        let node = ticker(Duration::from_nanos(100)).count().as_node();
+        Graph::builder()
+            .on_progress(every, move |progress| sink.borrow_mut().push(progress))
+            .build(vec![node], RunMode::HistoricalFrom(NanoTime::ZERO), run_for)
+            .run()
+            .unwrap();
+        reports.take()
+    }
+
+    #[test]
+    fn progress_reports_each_due_cycle_then_finishes() {
+        let reports = reports(Duration::ZERO, RunFor::Cycles(50));
+        // One per cycle, since every report is immediately due, plus the final one.
+        assert_eq!(reports.len(), 51);
+        assert!(reports.windows(2).all(|w| w[0].time <= w[1].time));
+        assert!(reports.windows(2).all(|w| w[0].cycles <= w[1].cycles));
+        assert_eq!(reports[24].end_cycle, Some(50));
+        assert_eq!(reports[24].fraction(), Some(0.5));
+        let last = reports.last().unwrap();
+        assert!(last.finished);
+        assert_eq!((last.cycles, last.fraction()), (50, Some(1.0)));
+        assert_eq!(last.remaining(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn progress_by_duration_and_throttled() {
+        let reports = reports(
+            Duration::from_secs(3600),
+            RunFor::Duration(Duration::from_nanos(1_000)),
+        );
+        // Nothing is due within the hour, so only the final report remains.
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].end_time, Some(NanoTime::new(1_000)));
+        assert_eq!(reports[0].fraction(), Some(1.0));
+    }
+
+    #[cfg(feature = "progress")]
+    #[test]
+    fn progress_bar_draws_fraction_and_eta() {
+        let progress = Progress {
+            time: NanoTime::ZERO,
+            start_time: NanoTime::ZERO,
+            end_time: None,
+            cycles: 40,
+            end_cycle: Some(100),
+            elapsed: Duration::from_secs(2),
+            finished: false,
+        };
+        let line = ProgressBar::default().with_width(10).line(&progress);
+        assert_eq!(
+            line,
+            "[####......]  40.0%  1970-01-01 00:00:00  40 cycles  20/s  eta 0:00:03"
+        );
+    }
+
+    #[test]
+    fn fraction_follows_engine_time() {
+        let progress = Progress {
+            time: NanoTime::new(1_250),
+            start_time: NanoTime::new(1_000),
+            end_time: Some(NanoTime::new(2_000)),
+            cycles: 3,
+            end_cycle: None,
+            elapsed: Duration::from_secs(10),
+            finished: false,
+        };
+        assert_eq!(progress.fraction(), Some(0.25));
+        assert_eq!(progress.remaining(), Some(Duration::from_secs(30)));
+        assert_eq!(progress.cycles_per_sec(), 0.3);
+    }
+}
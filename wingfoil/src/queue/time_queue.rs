@@ -3,22 +3,25 @@ use std::collections::BinaryHeap;
 
 use crate::types::NanoTime;
 
-/// An entry in a [`TimeQueue`], ordered by `(time, seq)` alone.
+/// An entry in a [`TimeQueue`], ordered by `(time, rank, seq)` alone.
 ///
 /// The ordering deliberately ignores the payload `T` so the heap needs no
-/// `Ord`/`Hash`/`Eq` bound on `T`. `seq` is a per-queue monotonic counter that
-/// makes the order *total* (no two entries compare equal) and gives a stable
-/// FIFO order among entries sharing a `time`.
+/// `Ord`/`Hash`/`Eq` bound on `T`. `rank` is the caller's tiebreak from
+/// [`push_ranked`](TimeQueue::push_ranked), `0` for a plain `push`. `seq` is a
+/// per-queue monotonic counter that makes the order *total* (no two entries
+/// compare equal) and gives a stable FIFO order among entries sharing a
+/// `time` and `rank`.
 #[derive(Debug)]
 struct Entry<T> {
     time: NanoTime,
+    rank: u64,
     seq: u64,
     value: T,
 }
 
 impl<T> Entry<T> {
-    fn key(&self) -> (NanoTime, u64) {
-        (self.time, self.seq)
+    fn key(&self) -> (NanoTime, u64, u64) {
+        (self.time, self.rank, self.seq)
     }
 }
 
@@ -51,8 +54,8 @@ impl<T> Ord for Entry<T> {
 /// than fire twice. If you are tempted to "fix" duplicate suppression, it is
 /// working as designed; see also `CLAUDE.md`.
 ///
-/// Distinct values at the same `time` are all kept and pop in FIFO (insertion)
-/// order.
+/// Distinct values at the same `time` are all kept and pop in ascending rank
+/// (see [`push_ranked`](TimeQueue::push_ranked)), then FIFO (insertion) order.
 ///
 /// ## Why `PartialEq`, not `Hash + Eq`
 ///
@@ -69,7 +72,8 @@ impl<T> Ord for Entry<T> {
 #[derive(Debug)]
 pub(crate) struct TimeQueue<T> {
     // `BinaryHeap` is a max-heap; `Reverse` turns it into a min-heap on
-    // `(time, seq)`, i.e. earliest time first, ties broken by insertion order.
+    // `(time, rank, seq)`, i.e. earliest time first, ties broken by rank and
+    // then insertion order.
     heap: BinaryHeap<Reverse<Entry<T>>>,
     next_seq: u64,
 }
@@ -97,6 +101,10 @@ impl<T> TimeQueue<T> {
         self.heap.is_empty()
     }
 
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
     /// Pop the earliest item, or `None` if the queue is empty.
     pub fn pop(&mut self) -> Option<T> {
         self.heap.pop().map(|Reverse(e)| e.value)
@@ -106,8 +114,15 @@ impl<T> TimeQueue<T> {
     /// `while let Some(v) = q.pop_if_pending(now) { ... }` idiom that drains all
     /// callbacks due at or before the current engine tick.
     pub fn pop_if_pending(&mut self, current_time: NanoTime) -> Option<T> {
+        self.pop_timed_if_pending(current_time)
+            .map(|(value, _)| value)
+    }
+
+    /// Like [`pop_if_pending`](Self::pop_if_pending) but also returns the
+    /// time the item was queued for.
+    pub fn pop_timed_if_pending(&mut self, current_time: NanoTime) -> Option<(T, NanoTime)> {
         match self.next_time() {
-            Some(t) if t <= current_time => self.pop(),
+            Some(t) if t <= current_time => self.pop().map(|value| (value, t)),
             _ => None,
         }
     }
@@ -121,6 +136,13 @@ impl<T: PartialEq> TimeQueue<T> {
     /// Push `value` at `time`. A `(value, time)` pair already present in the
     /// queue is suppressed (see the type-level docs — dedup is intentional).
     pub fn push(&mut self, value: T, time: NanoTime) {
+        self.push_ranked(value, time, 0);
+    }
+
+    /// Like [`push`](Self::push), but values at the same `time` pop in
+    /// ascending `rank`, and only those of equal rank in insertion order,
+    /// e.g. the graph scheduler ranks callbacks by node index.
+    pub fn push_ranked(&mut self, value: T, time: NanoTime, rank: u64) {
         if self
             .heap
             .iter()
@@ -130,7 +152,12 @@ impl<T: PartialEq> TimeQueue<T> {
         }
         let seq = self.next_seq;
         self.next_seq += 1;
-        self.heap.push(Reverse(Entry { time, seq, value }));
+        self.heap.push(Reverse(Entry {
+            time,
+            rank,
+            seq,
+            value,
+        }));
     }
 }
 
@@ -177,6 +204,22 @@ mod tests {
         assert!(queue.is_empty());
     }
 
+    #[test]
+    fn ranked_values_same_time_pop_in_rank_order() {
+        let mut queue: TimeQueue<u32> = TimeQueue::new();
+        queue.push_ranked(3, NanoTime::new(100), 3);
+        queue.push_ranked(1, NanoTime::new(100), 1);
+        queue.push_ranked(2, NanoTime::new(50), 2);
+        queue.push_ranked(1, NanoTime::new(100), 1); // duplicate → suppressed
+        queue.push_ranked(4, NanoTime::new(100), 1);
+        assert_eq!(queue.pop(), Some(2));
+        assert_eq!(queue.pop(), Some(1));
+        // equal rank → FIFO
+        assert_eq!(queue.pop(), Some(4));
+        assert_eq!(queue.pop(), Some(3));
+        assert!(queue.is_empty());
+    }
+
     #[test]
     fn float_payloads_are_supported_and_deduplicated() {
         // f64 is neither `Hash` nor `Eq`; the queue must still accept it, order
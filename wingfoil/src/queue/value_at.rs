@@ -31,6 +31,16 @@ impl<T: PartialEq> PartialEq for ValueAt<T> {
 
 impl<T: PartialEq> Eq for ValueAt<T> {}
 
+/// A value captured at an engine time together with its value time, the
+/// time it was originally produced.  See
+/// [collect_with_value_time](crate::nodes::StreamOperators::collect_with_value_time).
+#[derive(Debug, Clone, new, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimedValueAt<T> {
+    pub value: T,
+    pub time: NanoTime,
+    pub value_time: NanoTime,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -71,4 +81,27 @@ mod tests {
         assert_eq!(v.value, 0);
         assert_eq!(v.time, NanoTime::new(0));
     }
+
+    #[test]
+    fn json_round_trips_with_none_fields() {
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+        struct TwoWayPrice {
+            bid_price: Option<u64>,
+            ask_price: Option<u64>,
+        }
+        let price = TwoWayPrice {
+            bid_price: Some(100),
+            ask_price: None,
+        };
+        let at = ValueAt::new(price, NanoTime::new(10));
+        let json = serde_json::to_string(&at).unwrap();
+        assert_eq!(
+            json,
+            r#"{"value":{"bid_price":100,"ask_price":null},"time":10}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<ValueAt<TwoWayPrice>>(&json).unwrap(),
+            at
+        );
+    }
 }
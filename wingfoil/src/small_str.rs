@@ -0,0 +1,289 @@
+//! Inline, fixed-capacity strings for keys and labels on hot paths.
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::str::FromStr;
+
+/// A string of at most `N` bytes stored inline, so it is `Copy` and never
+/// allocates.  Suited to tickers, venues and other short keys, e.g. for
+/// [demux](crate::StreamOperators::demux).  Hashes, compares and
+/// (de)serializes as a `str`, and derefs to one, so it can be looked up by
+/// `&str` and passed wherever a `&str` is taken.  See [SmallStr].
+#[derive(Clone, Copy)]
+pub struct InlineStr<const N: usize> {
+    len: u8,
+    bytes: [u8; N],
+}
+
+/// An [InlineStr] the size of a `String`: 23 bytes and a length.
+pub type SmallStr = InlineStr<23>;
+
+/// A string too long for an [InlineStr].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError {
+    pub len: usize,
+    pub capacity: usize,
+}
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "string of {} bytes exceeds the inline capacity of {}",
+            self.len, self.capacity
+        )
+    }
+}
+
+impl std::error::Error for CapacityError {}
+
+impl<const N: usize> InlineStr<N> {
+    /// The most bytes it can hold.
+    pub const CAPACITY: usize = {
+        assert!(N <= u8::MAX as usize, "InlineStr capacity must fit in a u8");
+        N
+    };
+
+    /// Copies `s`, failing if it is longer than [CAPACITY](Self::CAPACITY).
+    pub fn new(s: &str) -> Result<Self, CapacityError> {
+        if s.len() > Self::CAPACITY {
+            return Err(CapacityError {
+                len: s.len(),
+                capacity: Self::CAPACITY,
+            });
+        }
+        let mut bytes = [0; N];
+        bytes[..s.len()].copy_from_slice(s.as_bytes());
+        Ok(Self {
+            len: s.len() as u8,
+            bytes,
+        })
+    }
+
+    /// Copies as much of `s` as fits, cutting it at a char boundary.
+    pub fn truncated(s: &str) -> Self {
+        let mut end = s.len().min(Self::CAPACITY);
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        Self::new(&s[..end]).expect("truncated to capacity")
+    }
+
+    /// Appends `s`, failing, and leaving `self` unchanged, if it does not
+    /// fit.  Also reached through [fmt::Write], so `write!` can build one
+    /// without an intermediate `String`.
+    pub fn push_str(&mut self, s: &str) -> Result<(), CapacityError> {
+        let len = self.len as usize;
+        if len + s.len() > Self::CAPACITY {
+            return Err(CapacityError {
+                len: len + s.len(),
+                capacity: Self::CAPACITY,
+            });
+        }
+        self.bytes[len..len + s.len()].copy_from_slice(s.as_bytes());
+        self.len += s.len() as u8;
+        Ok(())
+    }
+
+    pub fn as_str(&self) -> &str {
+        let bytes = &self.bytes[..self.len as usize];
+        // SAFETY: bytes are only ever copied from whole `str`s, or a prefix
+        // of one ending on a char boundary.
+        unsafe { std::str::from_utf8_unchecked(bytes) }
+    }
+}
+
+impl<const N: usize> Default for InlineStr<N> {
+    fn default() -> Self {
+        Self {
+            len: 0,
+            bytes: [0; N],
+        }
+    }
+}
+
+impl<const N: usize> Deref for InlineStr<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> AsRef<str> for InlineStr<N> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> Borrow<str> for InlineStr<N> {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> PartialEq for InlineStr<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> Eq for InlineStr<N> {}
+
+impl<const N: usize> PartialEq<str> for InlineStr<N> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for InlineStr<N> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<const N: usize> PartialOrd for InlineStr<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> Ord for InlineStr<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<const N: usize> Hash for InlineStr<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // as a str, to agree with Borrow<str>
+        self.as_str().hash(state)
+    }
+}
+
+impl<const N: usize> fmt::Display for InlineStr<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Debug for InlineStr<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Write for InlineStr<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s).map_err(|_| fmt::Error)
+    }
+}
+
+impl<const N: usize> FromStr for InlineStr<N> {
+    type Err = CapacityError;
+
+    fn from_str(s: &str) -> Result<Self, CapacityError> {
+        Self::new(s)
+    }
+}
+
+impl<const N: usize> TryFrom<&str> for InlineStr<N> {
+    type Error = CapacityError;
+
+    fn try_from(s: &str) -> Result<Self, CapacityError> {
+        Self::new(s)
+    }
+}
+
+impl<const N: usize> Serialize for InlineStr<N> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(self.as_str())
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for InlineStr<N> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        struct InlineStrVisitor<const N: usize>;
+
+        impl<const N: usize> Visitor<'_> for InlineStrVisitor<N> {
+            type Value = InlineStr<N>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a string of at most {N} bytes")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<InlineStr<N>, E> {
+                InlineStr::new(s).map_err(E::custom)
+            }
+        }
+
+        d.deserialize_str(InlineStrVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn holds_up_to_capacity() {
+        let s = SmallStr::new("AAPL").unwrap();
+        assert_eq!(s, "AAPL");
+        assert_eq!(s.len(), 4);
+        assert_eq!(s.to_string(), "AAPL");
+        assert_eq!(format!("{s:?}"), "\"AAPL\"");
+        assert_eq!(
+            std::mem::size_of::<SmallStr>(),
+            std::mem::size_of::<String>()
+        );
+        let full = "x".repeat(SmallStr::CAPACITY);
+        assert_eq!(SmallStr::new(&full).unwrap(), full.as_str());
+        assert_eq!(
+            SmallStr::new(&format!("{full}x")),
+            Err(CapacityError {
+                len: 24,
+                capacity: 23
+            })
+        );
+        // cut before the 2-byte 'é' rather than through it
+        assert_eq!(InlineStr::<4>::truncated("abcé"), "abc");
+        assert_eq!(SmallStr::default(), "");
+    }
+
+    #[test]
+    fn writes_in_place() {
+        use std::fmt::Write;
+        let mut s = InlineStr::<6>::default();
+        write!(s, "inst{}", 12).unwrap();
+        assert_eq!(s, "inst12");
+        assert!(write!(s, "3").is_err());
+        assert_eq!(s, "inst12");
+    }
+
+    #[test]
+    fn looks_up_by_str() {
+        let mut counts: HashMap<SmallStr, u32> = HashMap::new();
+        *counts.entry("MSFT".parse().unwrap()).or_default() += 1;
+        assert_eq!(counts.get("MSFT"), Some(&1));
+        assert!("AAPL".parse::<SmallStr>().unwrap() < "MSFT".parse().unwrap());
+    }
+
+    #[test]
+    fn serde_round_trips_as_str() {
+        let s = SmallStr::new("VOD.L").unwrap();
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(json, "\"VOD.L\"");
+        assert_eq!(serde_json::from_str::<SmallStr>(&json).unwrap(), s);
+        let err = serde_json::from_str::<InlineStr<2>>(&json).unwrap_err();
+        assert!(
+            err.to_string().contains("exceeds the inline capacity"),
+            "{err}"
+        );
+    }
+}
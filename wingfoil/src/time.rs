@@ -1,5 +1,49 @@
-use chrono::DateTime;
+//! Time in wingfoil, measured in nanoseconds since the unix epoch as a
+//! [NanoTime].
+//!
+//! ## Engine time vs value time
+//!
+//! *Engine time* is the graph's clock on the cycle a node ticks, as read by
+//! [GraphState::time](crate::GraphState::time) and
+//! [ticked_at](crate::NodeOperators::ticked_at).  *Value time* is when the
+//! value was originally produced, as read by
+//! [value_time](crate::NodeOperators::value_time).  For most nodes the two
+//! are equal, but they differ when a value is delivered after it was made:
+//!
+//! - values received from another graph, e.g. a
+//!   [mapper](crate::StreamOperators::mapper) or
+//!   [producer](crate::producer) worker, carry the sender's engine time,
+//!   while in realtime the receiving graph cycles them on its own clock;
+//! - [delay](crate::StreamOperators::delay) and
+//!   [lag](crate::StreamOperators::lag) emit later but keep the time the
+//!   source ticked;
+//! - [feedback](crate::feedback) delivers on the next cycle, 1ns after the
+//!   value was sent;
+//! - replayed values, e.g. from [replay](crate::replay), keep their recorded
+//!   time even if a realtime graph plays them late.
+//!
+//! Compare values across graphs or threads by value time; engine time
+//! includes delivery artifacts such as checkpoints and the 1ns feedback step.
+//! ```
+//! # use wingfoil::*;
+//! # use std::time::Duration;
+//! let delayed = ticker(Duration::from_nanos(100))
+//!     .count()
+//!     .delay(Duration::from_nanos(50))
+//!     .collect_with_value_time();
+//! delayed
+//!     .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(2))
+//!     .unwrap();
+//! let first = &delayed.peek_value()[0];
+//! assert_eq!(first.time, NanoTime::new(50));
+//! assert_eq!(first.value_time, NanoTime::ZERO);
+//! ```
+//! Nodes that deliver values late record their value time with
+//! [GraphState::set_value_time](crate::GraphState::set_value_time); any other
+//! node's value time is its engine time.
+
 use chrono::naive::NaiveDateTime;
+use chrono::{DateTime, Utc};
 use derive_more::Display;
 use derive_new::new;
 use formato::Formato;
@@ -149,6 +193,53 @@ impl From<NanoTime> for NaiveDateTime {
     }
 }
 
+impl From<NanoTime> for DateTime<Utc> {
+    fn from(t: NanoTime) -> Self {
+        NaiveDateTime::from(t).and_utc()
+    }
+}
+
+/// Fails for times before the unix epoch or after NanoTime's u64 range
+/// (in 2554).
+impl TryFrom<DateTime<Utc>> for NanoTime {
+    type Error = anyhow::Error;
+
+    fn try_from(date_time: DateTime<Utc>) -> anyhow::Result<Self> {
+        let secs = u64::try_from(date_time.timestamp())
+            .map_err(|_| anyhow::anyhow!("{date_time} is before the unix epoch"))?;
+        secs.checked_mul(NanoTime::NANOS_PER_SECOND)
+            .and_then(|nanos| nanos.checked_add(date_time.timestamp_subsec_nanos() as RawTime))
+            .map(NanoTime)
+            .ok_or_else(|| anyhow::anyhow!("{date_time} is outside NanoTime's range"))
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<NanoTime> for ::time::OffsetDateTime {
+    fn from(t: NanoTime) -> Self {
+        // u64 nanos end in 2554, well inside the time crate's default range.
+        ::time::OffsetDateTime::from_unix_timestamp_nanos(t.0 as i128)
+            .expect("NanoTime is always within the time crate's range")
+    }
+}
+
+/// Fails for times before the unix epoch or after NanoTime's u64 range
+/// (in 2554).
+#[cfg(feature = "time")]
+impl TryFrom<::time::OffsetDateTime> for NanoTime {
+    type Error = anyhow::Error;
+
+    fn try_from(date_time: ::time::OffsetDateTime) -> anyhow::Result<Self> {
+        let nanos = date_time.unix_timestamp_nanos();
+        if nanos < 0 {
+            anyhow::bail!("{date_time} is before the unix epoch");
+        }
+        RawTime::try_from(nanos)
+            .map(NanoTime)
+            .map_err(|_| anyhow::anyhow!("{date_time} is outside NanoTime's range"))
+    }
+}
+
 impl From<NanoTime> for Duration {
     fn from(t: NanoTime) -> Self {
         Duration::from_nanos(u64::from(t))
@@ -211,6 +302,32 @@ impl Mul<i64> for NanoTime {
     }
 }
 
+/// Serde helpers that encode a [`NanoTime`] as an RFC 3339 string
+/// (e.g. `2024-01-02T03:04:05.000000006Z`) instead of the default raw u64
+/// nanos. Opt in per field with `#[serde(with = "wingfoil::rfc3339")]`.
+pub mod rfc3339 {
+    use super::NanoTime;
+    use chrono::{DateTime, SecondsFormat};
+    use serde::{Deserialize, Deserializer, Serializer, de, ser};
+
+    pub fn serialize<S: Serializer>(t: &NanoTime, serializer: S) -> Result<S::Ok, S::Error> {
+        let nanos = i64::try_from(u64::from(*t))
+            .map_err(|_| ser::Error::custom(format!("{t} is outside chrono's range")))?;
+        let dt = DateTime::from_timestamp_nanos(nanos);
+        serializer.serialize_str(&dt.to_rfc3339_opts(SecondsFormat::Nanos, true))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NanoTime, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let dt = DateTime::parse_from_rfc3339(&s).map_err(de::Error::custom)?;
+        let nanos = dt
+            .timestamp_nanos_opt()
+            .filter(|n| *n >= 0)
+            .ok_or_else(|| de::Error::custom(format!("{s} is outside NanoTime's range")))?;
+        Ok(NanoTime::new(nanos as u64))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::NanoTime;
@@ -324,6 +441,61 @@ mod tests {
         assert_eq!(t, t2);
     }
 
+    /// Either side of the epoch, a fraction of a second, and past the i64
+    /// nanos limit in 2262.
+    const ROUND_TRIPS: [u64; 5] = [
+        0,
+        1,
+        999_999_999,
+        1_600_000_000_123_456_789,
+        i64::MAX as u64 + 1,
+    ];
+
+    #[test]
+    fn chrono_date_time_round_trips() {
+        use chrono::{DateTime, Utc};
+        for raw in ROUND_TRIPS {
+            let t = NanoTime::new(raw);
+            let dt: DateTime<Utc> = t.into();
+            assert_eq!(NanoTime::try_from(dt).unwrap(), t);
+        }
+        let epoch = DateTime::<Utc>::from(NanoTime::ZERO);
+        assert_eq!(epoch.to_rfc3339(), "1970-01-01T00:00:00+00:00");
+        let before = epoch - chrono::Duration::nanoseconds(1);
+        let err = NanoTime::try_from(before).unwrap_err();
+        assert!(err.to_string().contains("before the unix epoch"), "{err}");
+        let after = DateTime::<Utc>::from(NanoTime::MAX) + chrono::Duration::seconds(1);
+        let err = NanoTime::try_from(after).unwrap_err();
+        assert!(
+            err.to_string().contains("outside NanoTime's range"),
+            "{err}"
+        );
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_offset_date_time_round_trips() {
+        use ::time::OffsetDateTime;
+        for raw in ROUND_TRIPS.into_iter().chain([u64::MAX]) {
+            let t = NanoTime::new(raw);
+            let dt: OffsetDateTime = t.into();
+            assert_eq!(NanoTime::try_from(dt).unwrap(), t);
+        }
+        assert_eq!(
+            OffsetDateTime::from(NanoTime::ZERO),
+            OffsetDateTime::UNIX_EPOCH
+        );
+        let before = OffsetDateTime::UNIX_EPOCH - ::time::Duration::nanoseconds(1);
+        let err = NanoTime::try_from(before).unwrap_err();
+        assert!(err.to_string().contains("before the unix epoch"), "{err}");
+        let after = OffsetDateTime::from(NanoTime::MAX) + ::time::Duration::nanoseconds(1);
+        let err = NanoTime::try_from(after).unwrap_err();
+        assert!(
+            err.to_string().contains("outside NanoTime's range"),
+            "{err}"
+        );
+    }
+
     #[test]
     fn into_f64_converts() {
         let t = NanoTime::new(42);
@@ -345,4 +517,27 @@ mod tests {
         // 1_500_000_000 ns * 1e-9 = 1.5 seconds
         assert!(s.contains("1.500"), "expected 1.500 in '{s}'");
     }
+
+    #[test]
+    fn serde_defaults_to_raw_nanos() {
+        let t = NanoTime::new(1_500_000_000);
+        assert_eq!(serde_json::to_string(&t).unwrap(), "1500000000");
+        assert_eq!(serde_json::from_str::<NanoTime>("1500000000").unwrap(), t);
+    }
+
+    #[test]
+    fn rfc3339_roundtrips() {
+        #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+        struct Stamped {
+            #[serde(with = "super::rfc3339")]
+            time: NanoTime,
+        }
+        let stamped = Stamped {
+            time: NanoTime::new(1_704_164_645_000_000_006),
+        };
+        let json = serde_json::to_string(&stamped).unwrap();
+        assert_eq!(json, r#"{"time":"2024-01-02T03:04:05.000000006Z"}"#);
+        assert_eq!(serde_json::from_str::<Stamped>(&json).unwrap(), stamped);
+        assert!(serde_json::from_str::<Stamped>(r#"{"time":"1969-12-31T23:59:59Z"}"#).is_err());
+    }
 }
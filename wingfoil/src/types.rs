@@ -1,4 +1,5 @@
 use derive_new::new;
+use std::any::Any;
 use std::cell::RefCell;
 use std::fmt::{Debug, Display};
 use std::rc::Rc;
@@ -153,6 +154,11 @@ pub trait MutableNode {
     fn start(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
         Ok(())
     }
+    /// Called by the graph straight after the first cycle in which this
+    /// node ticked, and never again.  Use it for one-off work on the first
+    /// output (e.g. logging a startup message) instead of a flag in `cycle`.
+    #[allow(unused_variables)]
+    fn on_first_tick(&mut self, state: &mut GraphState) {}
     /// Called by the graph after the last cycle.  Can be used to clean up resources.
     #[allow(unused_variables)]
     fn stop(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
@@ -166,6 +172,46 @@ pub trait MutableNode {
     fn type_name(&self) -> String {
         tynm::type_name::<Self>()
     }
+
+    /// Rough bytes held by this node's internal state, e.g. the buffers of
+    /// [accumulate](crate::nodes::StreamOperators::accumulate) or
+    /// [window](crate::nodes::StreamOperators::window), for
+    /// [Graph::memory_report](crate::Graph::memory_report).  Never exact:
+    /// typically container capacities times element size, not following
+    /// pointers held by the elements.  `None` if the node doesn't report.
+    fn approx_memory(&self) -> Option<usize> {
+        None
+    }
+
+    /// Nodes this node fires directly through
+    /// [GraphState::mark_dirty](crate::GraphState) rather than through an
+    /// active edge, e.g. a demux parent's children.  Lets
+    /// [Graph::validate](crate::Graph::validate) see that they can fire and
+    /// check that they are wired into the graph.
+    fn triggers(&self) -> Vec<Rc<dyn Node>> {
+        vec![]
+    }
+
+    /// For [fuse_maps](crate::fuse_maps): a map's source and, boxed, an
+    /// `Rc<dyn Fn() -> OUT>` computing its value straight from that source.
+    #[doc(hidden)]
+    fn composed_map(&self) -> Option<(Rc<dyn Node>, Box<dyn Any>)> {
+        None
+    }
+
+    /// For [fuse_maps](crate::fuse_maps), which calls it only when nothing
+    /// else in the graph reads this node's upstream: reads the upstream
+    /// map's source directly, bypassing the upstream, if both are maps.
+    /// Returns whether it did.
+    #[doc(hidden)]
+    fn fuse_upstream(&mut self) -> bool {
+        false
+    }
+}
+
+/// Shallow heap bytes of a [Vec]: capacity times element size.
+pub(crate) fn vec_memory<T>(vec: &Vec<T>) -> usize {
+    vec.capacity() * std::mem::size_of::<T>()
 }
 
 impl Display for dyn Node {
@@ -187,8 +233,13 @@ pub trait Node: MutableNode {
     fn cycle(&self, state: &mut GraphState) -> anyhow::Result<bool>;
     fn setup(&self, state: &mut GraphState) -> anyhow::Result<()>;
     fn start(&self, state: &mut GraphState) -> anyhow::Result<()>;
+    fn on_first_tick(&self, state: &mut GraphState);
     fn stop(&self, state: &mut GraphState) -> anyhow::Result<()>;
     fn teardown(&self, state: &mut GraphState) -> anyhow::Result<()>;
+    #[doc(hidden)]
+    fn fuse_upstream(&self) -> bool {
+        false
+    }
 }
 
 /// A trait through which a reference to [Stream]'s value can
@@ -222,12 +273,18 @@ impl<NODE: MutableNode> Node for RefCell<NODE> {
     fn start(&self, state: &mut GraphState) -> anyhow::Result<()> {
         self.borrow_mut().start(state)
     }
+    fn on_first_tick(&self, state: &mut GraphState) {
+        self.borrow_mut().on_first_tick(state)
+    }
     fn stop(&self, state: &mut GraphState) -> anyhow::Result<()> {
         self.borrow_mut().stop(state)
     }
     fn teardown(&self, state: &mut GraphState) -> anyhow::Result<()> {
         self.borrow_mut().teardown(state)
     }
+    fn fuse_upstream(&self) -> bool {
+        self.borrow_mut().fuse_upstream()
+    }
 }
 
 impl<NODE: MutableNode> MutableNode for RefCell<NODE> {
@@ -247,6 +304,9 @@ impl<NODE: MutableNode> MutableNode for RefCell<NODE> {
     fn start(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
         self.borrow_mut().start(state)
     }
+    fn on_first_tick(&mut self, state: &mut GraphState) {
+        self.borrow_mut().on_first_tick(state)
+    }
     fn stop(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
         self.borrow_mut().stop(state)
     }
@@ -256,6 +316,18 @@ impl<NODE: MutableNode> MutableNode for RefCell<NODE> {
     fn type_name(&self) -> String {
         self.borrow().type_name()
     }
+    fn approx_memory(&self) -> Option<usize> {
+        self.borrow().approx_memory()
+    }
+    fn triggers(&self) -> Vec<Rc<dyn Node>> {
+        self.borrow().triggers()
+    }
+    fn composed_map(&self) -> Option<(Rc<dyn Node>, Box<dyn Any>)> {
+        self.borrow().composed_map()
+    }
+    fn fuse_upstream(&mut self) -> bool {
+        self.borrow_mut().fuse_upstream()
+    }
 }
 
 impl<STREAM, T> StreamPeek<T> for RefCell<STREAM>
@@ -278,6 +350,58 @@ where
 {
 }
 
+// Box
+
+/// Lets boxed nodes, e.g. a `Vec<Box<dyn MutableNode>>` assembled from
+/// runtime configuration, be wired like any other node.
+impl<NODE: MutableNode + ?Sized> MutableNode for Box<NODE> {
+    fn cycle(&mut self, state: &mut GraphState) -> anyhow::Result<bool> {
+        (**self).cycle(state)
+    }
+    fn upstreams(&self) -> UpStreams {
+        (**self).upstreams()
+    }
+    fn setup(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
+        (**self).setup(state)
+    }
+    fn start(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
+        (**self).start(state)
+    }
+    fn on_first_tick(&mut self, state: &mut GraphState) {
+        (**self).on_first_tick(state)
+    }
+    fn stop(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
+        (**self).stop(state)
+    }
+    fn teardown(&mut self, state: &mut GraphState) -> anyhow::Result<()> {
+        (**self).teardown(state)
+    }
+    fn type_name(&self) -> String {
+        (**self).type_name()
+    }
+    fn approx_memory(&self) -> Option<usize> {
+        (**self).approx_memory()
+    }
+    fn triggers(&self) -> Vec<Rc<dyn Node>> {
+        (**self).triggers()
+    }
+    fn composed_map(&self) -> Option<(Rc<dyn Node>, Box<dyn Any>)> {
+        (**self).composed_map()
+    }
+    fn fuse_upstream(&mut self) -> bool {
+        (**self).fuse_upstream()
+    }
+}
+
+impl<T: Clone, STREAM: StreamPeekRef<T> + ?Sized> StreamPeekRef<T> for Box<STREAM> {
+    fn peek_ref(&self) -> &T {
+        (**self).peek_ref()
+    }
+    fn clone_from_cell_ref(&self, cell_ref: std::cell::Ref<'_, T>) -> T {
+        (**self).clone_from_cell_ref(cell_ref)
+    }
+}
+
 /// Used to cast Rc<dyn [Stream]> to Rc<dyn [Node]>
 pub trait AsNode {
     #[must_use]
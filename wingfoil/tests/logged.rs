@@ -0,0 +1,63 @@
+//! `logged` checks the log level every tick.  Lives in its own test binary
+//! because it installs the process-wide logger.
+#![cfg(not(feature = "tracing"))]
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::sync::Mutex;
+use std::time::Duration;
+use wingfoil::*;
+
+/// Keeps the target and message of every record.
+struct Capture(Mutex<Vec<(String, String)>>);
+
+impl Log for Capture {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let line = (record.target().to_string(), record.args().to_string());
+        self.0.lock().unwrap().push(line);
+    }
+
+    fn flush(&self) {}
+}
+
+static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+
+#[test]
+fn toggling_max_level_mid_run_starts_and_stops_logging() {
+    log::set_logger(&CAPTURE).unwrap();
+    log::set_max_level(LevelFilter::Off);
+    // the level changes upstream of the logging, so ticks 2 and 3 log
+    let count = ticker(Duration::from_nanos(10))
+        .count()
+        .inspect(|n| match n {
+            2 => log::set_max_level(LevelFilter::Info),
+            4 => log::set_max_level(LevelFilter::Warn),
+            _ => {}
+        });
+    merge(vec![
+        count.logged("count", Level::Info),
+        count.logged_target("fills", "count", Level::Info),
+    ])
+    .run(RunMode::HistoricalFrom(NanoTime::ZERO), RunFor::Cycles(6))
+    .unwrap();
+    let lines = CAPTURE.0.lock().unwrap().clone();
+    let lines = lines
+        .iter()
+        .map(|(target, text)| {
+            let value = text.rsplit(' ').next().unwrap();
+            (target.as_str(), value)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        vec![
+            ("wingfoil", "2"),
+            ("fills", "2"),
+            ("wingfoil", "3"),
+            ("fills", "3"),
+        ]
+    );
+}
@@ -0,0 +1,112 @@
+//! Counts the heap allocations of a demux-by-ticker pipeline, to show that
+//! `SmallStr` keys keep the per-message path allocation free.  Lives in its
+//! own test binary because it installs the process-wide allocator.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::hash::Hash;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use wingfoil::*;
+
+/// Counts allocations made by threads that have turned counting on, so
+/// tests running in parallel do not disturb each other.
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.try_with(Cell::get).unwrap_or(false) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+/// Allocations made by this thread while running `f`.
+fn allocations(f: impl FnOnce()) -> u64 {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    COUNTING.with(|counting| counting.set(true));
+    f();
+    COUNTING.with(|counting| counting.set(false));
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+const TICKERS: [&str; 4] = ["AAPL", "MSFT", "GOOG", "VOD.L"];
+
+#[derive(Debug, Clone, Default)]
+struct Quote<S> {
+    ticker: S,
+    price: f64,
+}
+
+/// Builds and runs `messages` quotes through a demux by ticker, with each
+/// slot totalling its prices, returning the allocations made.
+fn demux_by_ticker<S>(messages: u32, ticker: impl Fn(usize) -> S + 'static) -> u64
+where
+    S: Element + Hash + Eq,
+{
+    allocations(|| {
+        let quotes = ticker_quotes(ticker);
+        let (slots, overflow) = quotes.demux(TICKERS.len(), |quote: &Quote<S>| {
+            (quote.ticker.clone(), DemuxEvent::None)
+        });
+        let mut nodes: Vec<Rc<dyn Node>> = slots
+            .iter()
+            .map(|slot| {
+                slot.fold(|total: &mut f64, quote| *total += quote.price)
+                    .as_node()
+            })
+            .collect();
+        nodes.push(overflow.panic());
+        Graph::new(
+            nodes,
+            RunMode::HistoricalFrom(NanoTime::ZERO),
+            RunFor::Cycles(messages),
+        )
+        .run()
+        .unwrap();
+    })
+}
+
+fn ticker_quotes<S: Element>(ticker: impl Fn(usize) -> S + 'static) -> Rc<dyn Stream<Quote<S>>> {
+    wingfoil::ticker(Duration::from_nanos(1))
+        .count()
+        .map(move |n| Quote {
+            ticker: ticker(n as usize % TICKERS.len()),
+            price: n as f64,
+        })
+}
+
+#[test]
+fn small_str_demux_allocates_nothing_per_message() {
+    let tickers = TICKERS.map(|ticker| SmallStr::new(ticker).unwrap());
+    let few = demux_by_ticker(1_000, move |i| tickers[i]);
+    let many = demux_by_ticker(1_000_000, move |i| tickers[i]);
+    // only building and starting the graph allocates
+    assert_eq!(few, many, "allocations grew with the message count");
+}
+
+#[test]
+fn string_demux_allocates_per_message() {
+    let messages = 10_000;
+    let allocations = demux_by_ticker(messages, |i| TICKERS[i].to_string());
+    // at least one for the key of each quote, and one for its clone
+    assert!(
+        allocations >= 2 * messages as u64,
+        "{allocations} allocations"
+    );
+}